@@ -109,6 +109,7 @@ fn bench_reflexion_episodes(args: &Args) -> Result<BenchmarkResult> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -131,6 +132,7 @@ fn bench_reflexion_episodes(args: &Args) -> Result<BenchmarkResult> {
         let entry = VectorEntry {
             id: Some(format!("episode_{}", i)),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: Some(
                 vec![
                     ("trajectory".to_string(), json!(format!("traj_{}", i))),
@@ -168,6 +170,7 @@ fn bench_reflexion_episodes(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query,
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -214,6 +217,7 @@ fn bench_skill_library(args: &Args) -> Result<BenchmarkResult> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -234,6 +238,7 @@ fn bench_skill_library(args: &Args) -> Result<BenchmarkResult> {
         let entry = VectorEntry {
             id: Some(format!("skill_{}", i)),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: Some(
                 vec![
                     ("name".to_string(), json!(format!("skill_{}", i))),
@@ -271,6 +276,7 @@ fn bench_skill_library(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query,
+            using: None,
             k: 5,
             filter: None,
             ef_search: None,
@@ -321,6 +327,7 @@ fn bench_causal_graph(args: &Args) -> Result<BenchmarkResult> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -342,6 +349,7 @@ fn bench_causal_graph(args: &Args) -> Result<BenchmarkResult> {
         let entry = VectorEntry {
             id: Some(format!("node_{}", i)),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: Some(
                 vec![
                     ("state".to_string(), json!(format!("state_{}", i))),
@@ -375,6 +383,7 @@ fn bench_causal_graph(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query,
+            using: None,
             k: 20,
             filter: None,
             ef_search: None,
@@ -422,6 +431,7 @@ fn bench_learning_session(args: &Args) -> Result<BenchmarkResult> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -449,6 +459,7 @@ fn bench_learning_session(args: &Args) -> Result<BenchmarkResult> {
             let entry = VectorEntry {
                 id: Some(format!("item_{}", i)),
                 vector: gen.generate(1).into_iter().next().unwrap(),
+                named_vectors: None,
                 metadata: Some(
                     vec![("timestamp".to_string(), json!(i))]
                         .into_iter()
@@ -462,6 +473,7 @@ fn bench_learning_session(args: &Args) -> Result<BenchmarkResult> {
             let query_start = Instant::now();
             db.search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
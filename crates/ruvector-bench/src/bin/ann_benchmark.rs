@@ -275,8 +275,10 @@ fn run_benchmark(
             ef_construction: args.ef_construction,
             ef_search,
             max_elements: vectors.len() * 2,
+            ..Default::default()
         }),
         quantization: Some(quantization),
+        named_vectors: None,
     };
 
     // Measure build time and memory
@@ -292,6 +294,7 @@ fn run_benchmark(
         let entry = VectorEntry {
             id: Some(idx.to_string()),
             vector: vector.clone(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
@@ -314,6 +317,7 @@ fn run_benchmark(
         let query_start = Instant::now();
         let results = db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: args.k,
             filter: None,
             ef_search: Some(ef_search),
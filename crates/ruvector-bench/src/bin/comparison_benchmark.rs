@@ -103,6 +103,7 @@ fn bench_ruvector_optimized(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -150,6 +151,7 @@ fn bench_ruvector_no_quant(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -199,6 +201,7 @@ fn simulate_python_baseline(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -257,6 +260,7 @@ fn simulate_brute_force(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -313,6 +317,7 @@ fn setup_ruvector(
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(quantization),
+        named_vectors: None,
     };
 
     let db = VectorDB::new(options)?;
@@ -332,6 +337,7 @@ fn setup_ruvector(
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
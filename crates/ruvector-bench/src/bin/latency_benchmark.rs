@@ -118,6 +118,7 @@ fn bench_single_threaded(args: &Args) -> Result<BenchmarkResult> {
         let query_start = Instant::now();
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -174,6 +175,7 @@ fn bench_multi_threaded(args: &Args, num_threads: usize) -> Result<BenchmarkResu
             let query_start = Instant::now();
             db.search(SearchQuery {
                 vector: query.clone(),
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
@@ -233,6 +235,7 @@ fn bench_ef_search_latency(args: &Args) -> Result<Vec<BenchmarkResult>> {
             let query_start = Instant::now();
             db.search(SearchQuery {
                 vector: query.clone(),
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: Some(ef_search),
@@ -292,6 +295,7 @@ fn bench_quantization_latency(args: &Args) -> Result<Vec<BenchmarkResult>> {
             let query_start = Instant::now();
             db.search(SearchQuery {
                 vector: query.clone(),
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
@@ -343,6 +347,7 @@ fn setup_database(
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(quantization),
+        named_vectors: None,
     };
 
     let db = VectorDB::new(options)?;
@@ -363,6 +368,7 @@ fn setup_database(
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
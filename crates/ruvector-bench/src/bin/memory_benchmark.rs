@@ -100,6 +100,7 @@ fn bench_memory_scale(args: &Args, num_vectors: usize) -> Result<BenchmarkResult
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -125,6 +126,7 @@ fn bench_memory_scale(args: &Args, num_vectors: usize) -> Result<BenchmarkResult
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
@@ -215,6 +217,7 @@ fn bench_quantization_memory(args: &Args) -> Result<Vec<BenchmarkResult>> {
             storage_path: db_path.to_str().unwrap().to_string(),
             hnsw_config: Some(HnswConfig::default()),
             quantization: Some(quant_config),
+            named_vectors: None,
         };
 
         let mem_profiler = MemoryProfiler::new();
@@ -235,6 +238,7 @@ fn bench_quantization_memory(args: &Args) -> Result<Vec<BenchmarkResult>> {
             let entry = VectorEntry {
                 id: Some(i.to_string()),
                 vector: gen.generate(1).into_iter().next().unwrap(),
+                named_vectors: None,
                 metadata: None,
             };
             db.insert(entry)?;
@@ -307,8 +311,10 @@ fn bench_index_overhead(args: &Args) -> Result<BenchmarkResult> {
             ef_construction: 200,
             ef_search: 100,
             max_elements: num_vectors * 2,
+            ..Default::default()
         }),
         quantization: Some(QuantizationConfig::None), // No quantization for overhead analysis
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -329,6 +335,7 @@ fn bench_index_overhead(args: &Args) -> Result<BenchmarkResult> {
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
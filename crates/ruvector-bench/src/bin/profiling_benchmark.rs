@@ -138,6 +138,7 @@ fn profile_indexing(args: &Args) -> Result<()> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let mem_profiler = MemoryProfiler::new();
@@ -160,6 +161,7 @@ fn profile_indexing(args: &Args) -> Result<()> {
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
@@ -191,6 +193,7 @@ fn profile_search(args: &Args) -> Result<()> {
     for query in &queries {
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -222,6 +225,7 @@ fn profile_mixed_workload(args: &Args) -> Result<()> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let db = VectorDB::new(options)?;
@@ -251,6 +255,7 @@ fn profile_mixed_workload(args: &Args) -> Result<()> {
             let entry = VectorEntry {
                 id: Some(i.to_string()),
                 vector: gen.generate(1).into_iter().next().unwrap(),
+                named_vectors: None,
                 metadata: None,
             };
             db.insert(entry)?;
@@ -260,6 +265,7 @@ fn profile_mixed_workload(args: &Args) -> Result<()> {
             let query = gen.generate(1).into_iter().next().unwrap();
             db.search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
@@ -302,6 +308,7 @@ fn setup_database(args: &Args) -> Result<(VectorDB, Vec<Vec<f32>>)> {
         storage_path: db_path.to_str().unwrap().to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: Some(QuantizationConfig::Scalar),
+        named_vectors: None,
     };
 
     let db = VectorDB::new(options)?;
@@ -321,6 +328,7 @@ fn setup_database(args: &Args) -> Result<(VectorDB, Vec<Vec<f32>>)> {
         let entry = VectorEntry {
             id: Some(i.to_string()),
             vector: gen.generate(1).into_iter().next().unwrap(),
+            named_vectors: None,
             metadata: None,
         };
         db.insert(entry)?;
@@ -1,17 +1,16 @@
 //! CLI command implementations
 
-use crate::cli::{
-    export_csv, export_json, format_error, format_search_results, format_stats, format_success,
-    ProgressTracker,
-};
+use crate::cli::export::export_points;
+use crate::cli::import::{read_external, read_file, Checkpoint, Loader};
+use crate::cli::{format_search_results, format_stats, format_success};
 use crate::config::Config;
 use anyhow::{Context, Result};
 use colored::*;
 use ruvector_core::{
-    types::{DbOptions, SearchQuery, VectorEntry},
+    types::{SearchParams, SearchQuery, VectorSelector},
     VectorDB,
 };
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::time::Instant;
 
 /// Create a new database
@@ -34,10 +33,15 @@ pub fn create_database(path: &str, dimensions: usize, config: &Config) -> Result
 }
 
 /// Insert vectors from a file
+///
+/// The file is streamed in batches of `cli.batch_size`. With a checkpoint
+/// file, progress is recorded after every batch and an interrupted insert
+/// resumes after the last recorded batch.
 pub fn insert_vectors(
     db_path: &str,
     input_file: &str,
     format: &str,
+    checkpoint: Option<&Path>,
     config: &Config,
     show_progress: bool,
 ) -> Result<()> {
@@ -47,65 +51,38 @@ pub fn insert_vectors(
 
     let db = VectorDB::new(db_options).context("Failed to open database")?;
 
-    // Parse input file
-    let entries = match format {
-        "json" => parse_json_file(input_file)?,
-        "csv" => parse_csv_file(input_file)?,
-        "npy" => parse_npy_file(input_file)?,
-        _ => return Err(anyhow::anyhow!("Unsupported format: {}", format)),
-    };
-
-    let total = entries.len();
-    println!(
-        "{}",
-        format_success(&format!("Loaded {} vectors from {}", total, input_file))
-    );
-
-    // Insert with progress
     let start = Instant::now();
-    let tracker = ProgressTracker::new();
-    let pb = if show_progress {
-        Some(tracker.create_bar(total as u64, "Inserting vectors..."))
-    } else {
-        None
-    };
-
-    let batch_size = config.cli.batch_size;
-    let mut inserted = 0;
-
-    for chunk in entries.chunks(batch_size) {
-        db.insert_batch(chunk.to_vec())
-            .context("Failed to insert batch")?;
-        inserted += chunk.len();
-
-        if let Some(ref pb) = pb {
-            pb.set_position(inserted as u64);
-        }
+    let mut loader = Loader::new(&db, input_file, config.cli.batch_size, show_progress)?;
+    if let Some(path) = checkpoint {
+        loader = loader.with_checkpoint(Checkpoint::open(path, input_file, format)?);
     }
+    read_file(input_file, format, &mut loader)?;
+    let total = loader.finish()?;
 
-    if let Some(pb) = pb {
-        pb.finish_with_message("Insertion complete!");
-    }
+    print_load_summary(total, input_file, start.elapsed().as_secs_f64());
+    Ok(())
+}
 
-    let elapsed = start.elapsed();
+fn print_load_summary(total: u64, source: &str, secs: f64) {
     println!(
         "{}",
         format_success(&format!(
-            "Inserted {} vectors in {:.2}s ({:.0} vectors/sec)",
+            "Inserted {} vectors from {} in {:.2}s ({:.0} vectors/sec)",
             total,
-            elapsed.as_secs_f64(),
-            total as f64 / elapsed.as_secs_f64()
+            source,
+            secs,
+            total as f64 / secs
         ))
     );
-
-    Ok(())
 }
 
 /// Search for similar vectors
 pub fn search_vectors(
     db_path: &str,
     query_vector: Vec<f32>,
+    using: Option<String>,
     k: usize,
+    params: &SearchParams,
     config: &Config,
     show_vectors: bool,
 ) -> Result<()> {
@@ -116,12 +93,16 @@ pub fn search_vectors(
 
     let start = Instant::now();
     let results = db
-        .search(SearchQuery {
-            vector: query_vector,
-            k,
-            filter: None,
-            ef_search: None,
-        })
+        .search_with_params(
+            SearchQuery {
+                vector: query_vector,
+                using: using.map(VectorSelector::Named),
+                k,
+                filter: None,
+                ef_search: None,
+            },
+            params,
+        )
         .context("Failed to search")?;
 
     let elapsed = start.elapsed();
@@ -189,6 +170,7 @@ pub fn run_benchmark(db_path: &str, config: &Config, num_queries: usize) -> Resu
     for query in queries.iter().take(10) {
         let _ = db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -200,6 +182,7 @@ pub fn run_benchmark(db_path: &str, config: &Config, num_queries: usize) -> Resu
     for query in &queries {
         db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -220,11 +203,15 @@ pub fn run_benchmark(db_path: &str, config: &Config, num_queries: usize) -> Resu
 }
 
 /// Export database to file
+///
+/// Points are streamed in id order as JSON, JSON Lines, CSV, NPY (with a
+/// `.meta.jsonl` sidecar for ids and metadata) or Parquet.
 pub fn export_database(
     db_path: &str,
     output_file: &str,
     format: &str,
     config: &Config,
+    show_progress: bool,
 ) -> Result<()> {
     let mut db_options = config.to_db_options();
     db_options.storage_path = db_path.to_string();
@@ -236,109 +223,56 @@ pub fn export_database(
         format_success(&format!("Exporting database to: {}", output_file))
     );
 
-    // Export is currently limited - would need to add all_ids() method to VectorDB
-    // For now, return an error with a helpful message
-    return Err(anyhow::anyhow!(
-        "Export functionality requires VectorDB::all_ids() method. This will be implemented in a future update."
-    ));
-
-    // TODO: Implement when VectorDB exposes all_ids()
-    // let ids = db.all_ids()?;
-    // let tracker = ProgressTracker::new();
-    // let pb = tracker.create_bar(ids.len() as u64, "Exporting vectors...");
-    // ...
+    let start = Instant::now();
+    let exported = export_points(
+        &db,
+        output_file,
+        format,
+        config.cli.batch_size,
+        show_progress,
+    )?;
+
+    println!(
+        "{}",
+        format_success(&format!(
+            "Exported {} vectors in {:.2}s",
+            exported,
+            start.elapsed().as_secs_f64()
+        ))
+    );
+    Ok(())
 }
 
 /// Import from other vector databases
+///
+/// Reads FAISS flat, IDMap and IVFFlat index files, and Qdrant, Pinecone
+/// or Weaviate JSON dumps, streamed like [`insert_vectors`].
 pub fn import_from_external(
     db_path: &str,
     source: &str,
     source_path: &str,
+    checkpoint: Option<&Path>,
     config: &Config,
+    show_progress: bool,
 ) -> Result<()> {
+    let mut db_options = config.to_db_options();
+    db_options.storage_path = db_path.to_string();
+
+    let db = VectorDB::new(db_options).context("Failed to open database")?;
+
     println!(
         "{}",
         format_success(&format!("Importing from {} database", source))
     );
 
-    match source {
-        "faiss" => {
-            // TODO: Implement FAISS import
-            return Err(anyhow::anyhow!("FAISS import not yet implemented"));
-        }
-        "pinecone" => {
-            // TODO: Implement Pinecone import
-            return Err(anyhow::anyhow!("Pinecone import not yet implemented"));
-        }
-        "weaviate" => {
-            // TODO: Implement Weaviate import
-            return Err(anyhow::anyhow!("Weaviate import not yet implemented"));
-        }
-        _ => return Err(anyhow::anyhow!("Unsupported source: {}", source)),
-    }
-}
-
-// Helper functions
-
-fn parse_json_file(path: &str) -> Result<Vec<VectorEntry>> {
-    let content = std::fs::read_to_string(path).context("Failed to read JSON file")?;
-    serde_json::from_str(&content).context("Failed to parse JSON")
-}
-
-fn parse_csv_file(path: &str) -> Result<Vec<VectorEntry>> {
-    let mut reader = csv::Reader::from_path(path).context("Failed to open CSV file")?;
-
-    let mut entries = Vec::new();
-
-    for result in reader.records() {
-        let record = result.context("Failed to read CSV record")?;
-
-        let id = if record.get(0).map(|s| s.is_empty()).unwrap_or(true) {
-            None
-        } else {
-            Some(record.get(0).unwrap().to_string())
-        };
-
-        let vector: Vec<f32> =
-            serde_json::from_str(record.get(1).context("Missing vector column")?)
-                .context("Failed to parse vector")?;
-
-        let metadata = if let Some(meta_str) = record.get(2) {
-            if !meta_str.is_empty() {
-                Some(serde_json::from_str(meta_str).context("Failed to parse metadata")?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        entries.push(VectorEntry {
-            id,
-            vector,
-            metadata,
-        });
+    let start = Instant::now();
+    let mut loader = Loader::new(&db, source_path, config.cli.batch_size, show_progress)?;
+    if let Some(path) = checkpoint {
+        loader = loader.with_checkpoint(Checkpoint::open(path, source_path, source)?);
     }
+    read_external(source, source_path, &mut loader)?;
+    let total = loader.finish()?;
 
-    Ok(entries)
-}
-
-fn parse_npy_file(path: &str) -> Result<Vec<VectorEntry>> {
-    use ndarray::Array2;
-    use ndarray_npy::ReadNpyExt;
-
-    let file = std::fs::File::open(path).context("Failed to open NPY file")?;
-    let array: Array2<f32> = Array2::read_npy(file).context("Failed to read NPY file")?;
-
-    let entries: Vec<VectorEntry> = array
-        .outer_iter()
-        .enumerate()
-        .map(|(i, row)| VectorEntry {
-            id: Some(format!("vec_{}", i)),
-            vector: row.to_vec(),
-            metadata: None,
-        })
-        .collect();
-
-    Ok(entries)
+    print_load_summary(total, source_path, start.elapsed().as_secs_f64());
+    Ok(())
 }
@@ -0,0 +1,289 @@
+//! Streaming export of a database to JSON, JSON Lines, CSV, NPY or Parquet
+//!
+//! Points are read a page at a time in id order, so the export never holds
+//! the whole database in memory.
+
+use crate::cli::format::{csv_record, CSV_HEADER};
+use crate::cli::import::npy_sidecar;
+use crate::cli::npy::NpyWriter;
+use crate::cli::ProgressTracker;
+use anyhow::{bail, Context, Result};
+use ruvector_core::types::VectorEntry;
+use ruvector_core::VectorDB;
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Writes pages of points to an export file
+trait PageWriter {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()>;
+
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// Export every point of `db` to `output`, returning the number exported
+pub fn export_points(
+    db: &VectorDB,
+    output: &str,
+    format: &str,
+    page_size: usize,
+    show_progress: bool,
+) -> Result<u64> {
+    let total = db.len().context("Failed to count points")?;
+    let file = BufWriter::new(File::create(output).context("Failed to create output file")?);
+
+    let mut writer: Box<dyn PageWriter> = match format {
+        "json" => Box::new(JsonWriter::new(file)?),
+        "jsonl" => Box::new(JsonlWriter(file)),
+        "csv" => Box::new(CsvWriter::new(file)?),
+        "npy" => Box::new(NpyExport::new(
+            file,
+            output,
+            total,
+            db.options().dimensions,
+        )?),
+        "parquet" => parquet_writer(file)?,
+        _ => bail!("Unsupported format: {}", format),
+    };
+
+    let tracker = ProgressTracker::new();
+    let pb = show_progress.then(|| tracker.create_bar(total as u64, "Exporting vectors..."));
+
+    let mut exported = 0;
+    let mut offset = None;
+    loop {
+        let page = db
+            .scroll(None, offset.as_deref(), page_size.max(1))
+            .context("Failed to read points")?;
+        writer.write_page(&page.points)?;
+        exported += page.points.len() as u64;
+        if let Some(ref pb) = pb {
+            pb.set_position(exported);
+        }
+
+        offset = page.next_offset;
+        if offset.is_none() {
+            break;
+        }
+    }
+    writer.finish()?;
+
+    if let Some(pb) = pb {
+        pb.finish_with_message("Export complete!");
+    }
+    Ok(exported)
+}
+
+/// A JSON array of points, as read by `insert --format json`
+struct JsonWriter<W: Write> {
+    writer: W,
+    first: bool,
+}
+
+impl<W: Write> JsonWriter<W> {
+    fn new(mut writer: W) -> Result<Self> {
+        writer.write_all(b"[")?;
+        Ok(Self {
+            writer,
+            first: true,
+        })
+    }
+}
+
+impl<W: Write> PageWriter for JsonWriter<W> {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        for entry in entries {
+            if !self.first {
+                self.writer.write_all(b",")?;
+            }
+            self.first = false;
+            self.writer.write_all(b"\n  ")?;
+            serde_json::to_writer(&mut self.writer, entry)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.writer.write_all(b"\n]\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// One point per line
+struct JsonlWriter<W: Write>(W);
+
+impl<W: Write> PageWriter for JsonlWriter<W> {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        for entry in entries {
+            serde_json::to_writer(&mut self.0, entry)?;
+            self.0.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// `id,vector,metadata,named_vectors` rows with JSON-encoded values
+struct CsvWriter<W: Write>(csv::Writer<W>);
+
+impl<W: Write> CsvWriter<W> {
+    fn new(writer: W) -> Result<Self> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(CSV_HEADER)?;
+        Ok(Self(writer))
+    }
+}
+
+impl<W: Write> PageWriter for CsvWriter<W> {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        for entry in entries {
+            self.0.write_record(csv_record(entry)?)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// Default vectors as an NPY matrix, with ids, metadata and named vectors
+/// in a JSON Lines sidecar of the same row order
+struct NpyExport<W: Write> {
+    matrix: NpyWriter<W>,
+    sidecar: BufWriter<File>,
+}
+
+impl<W: Write> NpyExport<W> {
+    fn new(writer: W, output: &str, rows: usize, cols: usize) -> Result<Self> {
+        let sidecar = File::create(npy_sidecar(output)).context("Failed to create NPY sidecar")?;
+        Ok(Self {
+            matrix: NpyWriter::new(writer, rows, cols)?,
+            sidecar: BufWriter::new(sidecar),
+        })
+    }
+}
+
+impl<W: Write> PageWriter for NpyExport<W> {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        for entry in entries {
+            self.matrix.write_row(&entry.vector).with_context(|| {
+                format!(
+                    "Point {} cannot be exported to NPY",
+                    entry.id.as_deref().unwrap_or_default()
+                )
+            })?;
+            let meta = json!({
+                "id": entry.id,
+                "metadata": entry.metadata,
+                "named_vectors": entry.named_vectors,
+            });
+            serde_json::to_writer(&mut self.sidecar, &meta)?;
+            self.sidecar.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.matrix.finish()?;
+        self.sidecar.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_writer(file: BufWriter<File>) -> Result<Box<dyn PageWriter>> {
+    Ok(Box::new(ParquetWriter::new(file)?))
+}
+
+#[cfg(not(feature = "parquet"))]
+fn parquet_writer(_file: BufWriter<File>) -> Result<Box<dyn PageWriter>> {
+    bail!("Parquet support requires building ruvector-cli with the `parquet` feature")
+}
+
+/// One row group per page, with `id`, `vector` (list of floats) and
+/// JSON-encoded `metadata` and `named_vectors` columns
+#[cfg(feature = "parquet")]
+struct ParquetWriter {
+    writer: parquet::arrow::ArrowWriter<BufWriter<File>>,
+    schema: arrow::datatypes::SchemaRef,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetWriter {
+    fn new(file: BufWriter<File>) -> Result<Self> {
+        use arrow::datatypes::{DataType, Field, Schema};
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, true),
+            Field::new(
+                "vector",
+                DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
+                true,
+            ),
+            Field::new("metadata", DataType::Utf8, true),
+            Field::new("named_vectors", DataType::Utf8, true),
+        ]));
+        let writer = parquet::arrow::ArrowWriter::try_new(file, schema.clone(), None)
+            .context("Failed to create Parquet writer")?;
+        Ok(Self { writer, schema })
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl PageWriter for ParquetWriter {
+    fn write_page(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        use arrow::array::{ArrayRef, Float32Builder, ListBuilder, StringArray};
+        use arrow::record_batch::RecordBatch;
+        use std::sync::Arc;
+
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let ids: StringArray = entries.iter().map(|e| e.id.clone()).collect();
+        let mut vectors = ListBuilder::new(Float32Builder::new());
+        for entry in entries {
+            vectors.values().append_slice(&entry.vector);
+            vectors.append(true);
+        }
+        let metadata: StringArray = entries
+            .iter()
+            .map(|e| e.metadata.as_ref().map(serde_json::to_string).transpose())
+            .collect::<serde_json::Result<_>>()?;
+        let named_vectors: StringArray = entries
+            .iter()
+            .map(|e| {
+                e.named_vectors
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()
+            })
+            .collect::<serde_json::Result<_>>()?;
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(ids),
+            Arc::new(vectors.finish()),
+            Arc::new(metadata),
+            Arc::new(named_vectors),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)?;
+        // Close the row group so memory stays bounded by the page size
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.writer
+            .close()
+            .context("Failed to finish Parquet file")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+//! Reader for FAISS index files that store raw vectors
+//!
+//! Supports `IndexFlat` (L2 and inner product), `IndexIDMap`/`IndexIDMap2`
+//! around a flat index, and `IndexIVFFlat` with array inverted lists, as
+//! written by `faiss.write_index`. Quantized indexes (PQ, SQ, HNSW on
+//! codes) do not keep the original vectors and are rejected.
+
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+/// Distance of a FAISS index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaissMetric {
+    InnerProduct,
+    L2,
+    /// Any other `faiss::MetricType` value
+    Other(i32),
+}
+
+/// Fields shared by every index header
+#[derive(Debug, Clone, Copy)]
+pub struct FaissHeader {
+    /// Vector dimension
+    pub dimension: usize,
+    /// Number of stored vectors
+    pub count: usize,
+    pub metric: FaissMetric,
+}
+
+/// A FAISS index file opened for streaming its vectors
+pub struct FaissIndex {
+    reader: BufReader<File>,
+    header: FaissHeader,
+    layout: Layout,
+}
+
+/// Where the vectors and their ids are in the file
+enum Layout {
+    /// `count` vectors at `data`, with sequential ids or those of an IDMap
+    Flat { data: u64, ids: Option<Vec<i64>> },
+    /// Inverted lists starting at `lists`, each with its vectors then ids
+    Ivf { lists: u64, sizes: Vec<u64> },
+}
+
+impl FaissIndex {
+    /// Open an index file and parse its structure
+    pub fn open(path: &str) -> Result<Self> {
+        let file = File::open(path).context("Failed to open FAISS index")?;
+        let mut reader = BufReader::new(file);
+
+        let fourcc = read_fourcc(&mut reader)?;
+        let (header, layout) = match &fourcc {
+            b"IxFI" | b"IxF2" | b"IxFl" => {
+                let header = read_header(&mut reader)?;
+                let data = skip_flat_codes(&mut reader, &header)?;
+                (header, Layout::Flat { data, ids: None })
+            }
+            b"IxMp" | b"IxM2" => {
+                let header = read_header(&mut reader)?;
+                let inner = read_fourcc(&mut reader)?;
+                if !matches!(&inner, b"IxFI" | b"IxF2" | b"IxFl") {
+                    bail!(
+                        "IDMap around a '{}' index is not supported; only flat indexes keep vectors",
+                        String::from_utf8_lossy(&inner)
+                    );
+                }
+                let inner_header = read_header(&mut reader)?;
+                let data = skip_flat_codes(&mut reader, &inner_header)?;
+                let ids = read_i64_vec(&mut reader)?;
+                if ids.len() != inner_header.count || header.count != inner_header.count {
+                    bail!(
+                        "IDMap has {} ids for {} vectors",
+                        ids.len(),
+                        inner_header.count
+                    );
+                }
+                (
+                    inner_header,
+                    Layout::Flat {
+                        data,
+                        ids: Some(ids),
+                    },
+                )
+            }
+            b"IwFl" => {
+                let header = read_header(&mut reader)?;
+                let (lists, sizes) = read_ivf_structure(&mut reader, &header)?;
+                (header, Layout::Ivf { lists, sizes })
+            }
+            other => bail!(
+                "Unsupported FAISS index type '{}'; only flat, IDMap and IVFFlat indexes store raw vectors",
+                String::from_utf8_lossy(other)
+            ),
+        };
+
+        Ok(Self {
+            reader,
+            header,
+            layout,
+        })
+    }
+
+    /// Dimension, size and metric of the index
+    pub fn header(&self) -> FaissHeader {
+        self.header
+    }
+
+    /// Call `f` with the id and vector of every stored vector
+    pub fn for_each(mut self, mut f: impl FnMut(i64, Vec<f32>) -> Result<()>) -> Result<()> {
+        let dimension = self.header.dimension;
+        match self.layout {
+            Layout::Flat { data, ids } => {
+                self.reader.seek(SeekFrom::Start(data))?;
+                for i in 0..self.header.count {
+                    let vector = read_f32s(&mut self.reader, dimension)?;
+                    let id = ids.as_ref().map_or(i as i64, |ids| ids[i]);
+                    f(id, vector)?;
+                }
+            }
+            Layout::Ivf { lists, sizes } => {
+                self.reader.seek(SeekFrom::Start(lists))?;
+                for size in sizes.into_iter().filter(|&size| size > 0) {
+                    let size = size as usize;
+                    let vectors = read_f32s(&mut self.reader, size * dimension)?;
+                    let ids = read_i64s(&mut self.reader, size)?;
+                    for (id, vector) in ids.into_iter().zip(vectors.chunks_exact(dimension)) {
+                        f(id, vector.to_vec())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read `faiss::read_index_header`
+fn read_header(reader: &mut impl Read) -> Result<FaissHeader> {
+    let dimension = read_i32(reader)?;
+    let count = read_i64(reader)?;
+    // Two unused idx_t fields, then is_trained
+    read_i64(reader)?;
+    read_i64(reader)?;
+    read_u8(reader)?;
+    let metric = match read_i32(reader)? {
+        0 => FaissMetric::InnerProduct,
+        1 => FaissMetric::L2,
+        other => FaissMetric::Other(other),
+    };
+    if metric_has_arg(metric) {
+        read_f32s(reader, 1)?;
+    }
+
+    if dimension <= 0 || count < 0 {
+        bail!("Corrupt FAISS header: d={}, ntotal={}", dimension, count);
+    }
+    Ok(FaissHeader {
+        dimension: dimension as usize,
+        count: count as usize,
+        metric,
+    })
+}
+
+fn metric_has_arg(metric: FaissMetric) -> bool {
+    matches!(metric, FaissMetric::Other(other) if other > 1)
+}
+
+/// Skip the float codes of a flat index, returning where they start
+fn skip_flat_codes<R: Read + Seek>(reader: &mut R, header: &FaissHeader) -> Result<u64> {
+    let floats = read_u64(reader)?;
+    if floats != (header.count * header.dimension) as u64 {
+        bail!(
+            "Flat index holds {} floats, expected {} vectors of {}",
+            floats,
+            header.count,
+            header.dimension
+        );
+    }
+    let data = reader.stream_position()?;
+    reader.seek(SeekFrom::Current(floats as i64 * 4))?;
+    Ok(data)
+}
+
+/// Parse an IVF index up to its inverted lists, returning where the list
+/// data starts and the size of each list
+fn read_ivf_structure<R: Read + Seek>(
+    reader: &mut R,
+    header: &FaissHeader,
+) -> Result<(u64, Vec<u64>)> {
+    let nlist = read_u64(reader)?;
+    let _nprobe = read_u64(reader)?;
+
+    // The coarse quantizer is itself a flat index
+    let quantizer = read_fourcc(reader)?;
+    if !matches!(&quantizer, b"IxFI" | b"IxF2" | b"IxFl") {
+        bail!(
+            "Unsupported IVF quantizer '{}'",
+            String::from_utf8_lossy(&quantizer)
+        );
+    }
+    let quantizer_header = read_header(reader)?;
+    skip_flat_codes(reader, &quantizer_header)?;
+
+    // Direct map: type, array, and a hash table for type 2
+    let direct_map = read_u8(reader)?;
+    skip_vec(reader, 8)?;
+    if direct_map == 2 {
+        skip_vec(reader, 16)?;
+    }
+
+    let lists = read_fourcc(reader)?;
+    match &lists {
+        b"ilar" => {}
+        b"il00" => bail!("IVF index was saved without its inverted lists"),
+        other => bail!(
+            "Unsupported inverted lists '{}'; only in-memory lists can be read",
+            String::from_utf8_lossy(other)
+        ),
+    }
+    let list_count = read_u64(reader)?;
+    let code_size = read_u64(reader)?;
+    if list_count != nlist || code_size != (header.dimension * 4) as u64 {
+        bail!(
+            "Inverted lists do not match the index: {} lists of {}-byte codes",
+            list_count,
+            code_size
+        );
+    }
+
+    let mut sizes = vec![0u64; nlist as usize];
+    match &read_fourcc(reader)? {
+        b"full" => {
+            let full = read_u64_vec(reader)?;
+            if full.len() != sizes.len() {
+                bail!("Expected {} list sizes, found {}", sizes.len(), full.len());
+            }
+            sizes = full;
+        }
+        b"sprs" => {
+            for pair in read_u64_vec(reader)?.chunks_exact(2) {
+                let list = pair[0] as usize;
+                *sizes.get_mut(list).context("List size out of range")? = pair[1];
+            }
+        }
+        other => bail!(
+            "Unknown list size encoding '{}'",
+            String::from_utf8_lossy(other)
+        ),
+    }
+
+    Ok((reader.stream_position()?, sizes))
+}
+
+fn read_fourcc(reader: &mut impl Read) -> Result<[u8; 4]> {
+    let mut fourcc = [0u8; 4];
+    reader
+        .read_exact(&mut fourcc)
+        .context("Unexpected end of FAISS index")?;
+    Ok(fourcc)
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_i32(reader: &mut impl Read) -> Result<i32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_i64(reader: &mut impl Read) -> Result<i64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(i64::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_f32s(reader: &mut impl Read, n: usize) -> Result<Vec<f32>> {
+    let mut buf = vec![0u8; n * 4];
+    reader
+        .read_exact(&mut buf)
+        .context("Unexpected end of FAISS index")?;
+    Ok(buf
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect())
+}
+
+fn read_i64s(reader: &mut impl Read, n: usize) -> Result<Vec<i64>> {
+    let mut buf = vec![0u8; n * 8];
+    reader
+        .read_exact(&mut buf)
+        .context("Unexpected end of FAISS index")?;
+    Ok(buf
+        .chunks_exact(8)
+        .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+        .collect())
+}
+
+/// Read a length-prefixed `std::vector<int64_t>`
+fn read_i64_vec(reader: &mut impl Read) -> Result<Vec<i64>> {
+    let len = read_u64(reader)? as usize;
+    read_i64s(reader, len)
+}
+
+/// Read a length-prefixed `std::vector<size_t>`
+fn read_u64_vec(reader: &mut impl Read) -> Result<Vec<u64>> {
+    Ok(read_i64_vec(reader)?
+        .into_iter()
+        .map(|v| v as u64)
+        .collect())
+}
+
+/// Skip a length-prefixed vector of `size`-byte elements
+fn skip_vec<R: Read + Seek>(reader: &mut R, size: i64) -> Result<()> {
+    let len = read_u64(reader)? as i64;
+    reader.seek(SeekFrom::Current(len * size))?;
+    Ok(())
+}
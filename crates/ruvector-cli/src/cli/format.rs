@@ -63,31 +63,22 @@ pub fn format_info(msg: &str) -> String {
     format!("{} {}", "ℹ".blue().bold(), msg)
 }
 
-/// Export vector entries to JSON
-pub fn export_json(entries: &[VectorEntry]) -> anyhow::Result<String> {
-    serde_json::to_string_pretty(entries)
-        .map_err(|e| anyhow::anyhow!("Failed to serialize to JSON: {}", e))
-}
-
-/// Export vector entries to CSV
-pub fn export_csv(entries: &[VectorEntry]) -> anyhow::Result<String> {
-    let mut wtr = csv::Writer::from_writer(vec![]);
-
-    // Write header
-    wtr.write_record(&["id", "vector", "metadata"])?;
-
-    // Write entries
-    for entry in entries {
-        wtr.write_record(&[
-            entry.id.as_ref().map(|s| s.as_str()).unwrap_or(""),
-            &serde_json::to_string(&entry.vector)?,
-            &serde_json::to_string(&entry.metadata)?,
-        ])?;
-    }
-
-    wtr.flush()?;
-    String::from_utf8(wtr.into_inner()?)
-        .map_err(|e| anyhow::anyhow!("Failed to convert CSV to string: {}", e))
+/// Columns of CSV exports, as read back by `insert --format csv`
+pub const CSV_HEADER: [&str; 4] = ["id", "vector", "metadata", "named_vectors"];
+
+/// CSV row of a vector entry, with JSON-encoded vectors and metadata
+pub fn csv_record(entry: &VectorEntry) -> anyhow::Result<[String; 4]> {
+    Ok([
+        entry.id.clone().unwrap_or_default(),
+        serde_json::to_string(&entry.vector)?,
+        serde_json::to_string(&entry.metadata)?,
+        entry
+            .named_vectors
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?
+            .unwrap_or_default(),
+    ])
 }
 
 // Graph-specific formatting functions
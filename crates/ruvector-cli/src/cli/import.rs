@@ -0,0 +1,656 @@
+//! Streaming import of vector files and other databases' dumps
+//!
+//! Sources are read entry by entry and inserted in batches, so files larger
+//! than memory can be loaded. A checkpoint file records how far a load got,
+//! letting an interrupted import resume where it stopped. Entries without an
+//! id are given one derived from the source and their row, so replaying a
+//! batch after a crash overwrites it instead of duplicating it.
+
+use crate::cli::faiss::{FaissIndex, FaissMetric};
+use crate::cli::npy::NpyReader;
+use crate::cli::{format_info, format_warning, ProgressTracker};
+use anyhow::{bail, Context, Result};
+use indicatif::ProgressBar;
+use ruvector_core::types::{DistanceMetric, VectorEntry};
+use ruvector_core::VectorDB;
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Inserts streamed entries in batches, tracking progress and an optional
+/// checkpoint
+pub struct Loader<'a> {
+    db: &'a VectorDB,
+    batch: Vec<VectorEntry>,
+    batch_size: usize,
+    /// Namespace of the ids given to entries read without one
+    source_id: Uuid,
+    /// Entries read from the source, including skipped ones
+    read: u64,
+    /// Entries inserted by this run
+    inserted: u64,
+    checkpoint: Option<Checkpoint>,
+    show_progress: bool,
+    progress: Option<ProgressBar>,
+    tracker: ProgressTracker,
+}
+
+impl<'a> Loader<'a> {
+    /// Create a loader inserting entries of `source` `batch_size` at a time
+    pub fn new(
+        db: &'a VectorDB,
+        source: &str,
+        batch_size: usize,
+        show_progress: bool,
+    ) -> Result<Self> {
+        let source = std::fs::canonicalize(source)
+            .with_context(|| format!("Failed to open source {}", source))?;
+        Ok(Self {
+            db,
+            batch: Vec::with_capacity(batch_size),
+            batch_size: batch_size.max(1),
+            source_id: Uuid::new_v5(&Uuid::NAMESPACE_URL, source.to_string_lossy().as_bytes()),
+            read: 0,
+            inserted: 0,
+            checkpoint: None,
+            show_progress,
+            progress: None,
+            tracker: ProgressTracker::new(),
+        })
+    }
+
+    /// Record progress in `checkpoint`, skipping the entries an earlier
+    /// run already loaded from the same source
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        if checkpoint.loaded > 0 {
+            println!(
+                "{}",
+                format_info(&format!(
+                    "Resuming after {} entries from checkpoint {}",
+                    checkpoint.loaded,
+                    checkpoint.path.display()
+                ))
+            );
+        }
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Show a progress bar over `total` entries, when the source knows its size
+    pub fn set_total(&mut self, total: u64) {
+        if self.show_progress && self.progress.is_none() {
+            let pb = self.tracker.create_bar(total, "Importing vectors...");
+            pb.set_position(self.read);
+            self.progress = Some(pb);
+        }
+    }
+
+    /// Add an entry, inserting a batch once it is full
+    pub fn push(&mut self, mut entry: VectorEntry) -> Result<()> {
+        self.read += 1;
+        if self.read <= self.skipped() {
+            return Ok(());
+        }
+        if entry.id.is_none() {
+            let row = self.read - 1;
+            entry.id = Some(Uuid::new_v5(&self.source_id, &row.to_le_bytes()).to_string());
+        }
+        self.batch.push(entry);
+        if self.batch.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn skipped(&self) -> u64 {
+        self.checkpoint.as_ref().map_or(0, |c| c.loaded)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(self.batch_size));
+        let count = batch.len() as u64;
+        self.db
+            .insert_batch(batch)
+            .with_context(|| format!("Failed to insert batch ending at entry {}", self.read))?;
+        self.inserted += count;
+
+        if let Some(checkpoint) = &mut self.checkpoint {
+            checkpoint.loaded = self.read;
+            checkpoint.save()?;
+        }
+
+        if self.show_progress {
+            let pb = self
+                .progress
+                .get_or_insert_with(|| self.tracker.create_spinner("Importing vectors..."));
+            pb.set_position(self.read);
+            if pb.length().is_none() {
+                pb.set_message(format!("Imported {} vectors", self.read));
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert the last batch and remove the checkpoint, returning the number
+    /// of entries inserted by this run
+    pub fn finish(mut self) -> Result<u64> {
+        self.flush()?;
+        if let Some(pb) = self.progress.take() {
+            pb.finish_with_message("Import complete!");
+        }
+        if let Some(checkpoint) = self.checkpoint.take() {
+            if self.read < checkpoint.loaded {
+                bail!(
+                    "Source has {} entries but the checkpoint recorded {}; it may have changed",
+                    self.read,
+                    checkpoint.loaded
+                );
+            }
+            checkpoint.remove()?;
+        }
+        Ok(self.inserted)
+    }
+}
+
+/// Progress of a resumable import, saved after every batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Source file the progress refers to
+    source: PathBuf,
+    /// Format or source type it is read as
+    format: String,
+    /// Size of the source, to notice when it changes
+    source_len: u64,
+    /// Entries of the source already inserted
+    loaded: u64,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    /// Load the checkpoint at `path`, or start a new one if there is none
+    ///
+    /// Fails if the checkpoint belongs to a different source.
+    pub fn open(path: &Path, source: &str, format: &str) -> Result<Self> {
+        let source = std::fs::canonicalize(source)
+            .with_context(|| format!("Failed to open source {}", source))?;
+        let source_len = std::fs::metadata(&source)?.len();
+
+        if !path.exists() {
+            return Ok(Self {
+                source,
+                format: format.to_string(),
+                source_len,
+                loaded: 0,
+                path: path.to_path_buf(),
+            });
+        }
+
+        let content = std::fs::read_to_string(path).context("Failed to read checkpoint")?;
+        let mut checkpoint: Checkpoint =
+            serde_json::from_str(&content).context("Failed to parse checkpoint")?;
+        if checkpoint.source != source
+            || checkpoint.format != format
+            || checkpoint.source_len != source_len
+        {
+            bail!(
+                "Checkpoint {} is for {} ({}, {} bytes); delete it to start over",
+                path.display(),
+                checkpoint.source.display(),
+                checkpoint.format,
+                checkpoint.source_len
+            );
+        }
+        checkpoint.path = path.to_path_buf();
+        Ok(checkpoint)
+    }
+
+    /// Atomically replace the checkpoint file
+    fn save(&self) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?).context("Failed to write checkpoint")?;
+        std::fs::rename(&tmp, &self.path).context("Failed to write checkpoint")?;
+        Ok(())
+    }
+
+    fn remove(self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).context("Failed to remove checkpoint")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Stream a file in one of the native formats (json, jsonl, csv, npy,
+/// parquet) into `loader`
+pub fn read_file(path: &str, format: &str, loader: &mut Loader) -> Result<()> {
+    match format {
+        "json" => read_json_points(path, false, loader),
+        "jsonl" => read_json_points(path, true, loader),
+        "csv" => read_csv(path, loader),
+        "npy" => read_npy(path, loader),
+        "parquet" => read_parquet(path, loader),
+        _ => bail!("Unsupported format: {}", format),
+    }
+}
+
+/// Stream a dump of another vector database into `loader`
+pub fn read_external(source: &str, path: &str, loader: &mut Loader) -> Result<()> {
+    match source {
+        "faiss" => read_faiss(path, loader),
+        // The JSON reader recognises each of their layouts
+        "qdrant" | "pinecone" | "weaviate" => read_json_points(path, false, loader),
+        _ => bail!(
+            "Unsupported source: {} (expected faiss, qdrant, pinecone or weaviate)",
+            source
+        ),
+    }
+}
+
+/// A point in any of the supported JSON layouts
+///
+/// Accepts ruvector's `VectorEntry` as well as Qdrant points (`payload`,
+/// named vectors as an object), Pinecone vectors (`values`, `metadata`) and
+/// Weaviate objects (`properties`).
+#[derive(Debug, Deserialize)]
+struct JsonPoint {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(default, alias = "values")]
+    vector: Option<JsonVector>,
+    #[serde(default, alias = "vectors")]
+    named_vectors: Option<HashMap<String, Vec<f32>>>,
+    #[serde(default, alias = "payload", alias = "properties")]
+    metadata: Option<HashMap<String, Value>>,
+}
+
+/// A dense vector, or named vectors keyed by space
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonVector {
+    Dense(Vec<f32>),
+    Named(HashMap<String, Vec<f32>>),
+}
+
+impl JsonPoint {
+    fn into_entry(self) -> Result<VectorEntry> {
+        let id = match self.id {
+            None | Some(Value::Null) => None,
+            Some(Value::String(id)) => Some(id),
+            Some(Value::Number(id)) => Some(id.to_string()),
+            Some(other) => bail!("Unsupported point id {}", other),
+        };
+        let (vector, mut named_vectors) = match self.vector {
+            Some(JsonVector::Dense(vector)) => (vector, None),
+            Some(JsonVector::Named(named)) => (Vec::new(), Some(named)),
+            None => (Vec::new(), None),
+        };
+        if let Some(named) = self.named_vectors {
+            named_vectors.get_or_insert_with(HashMap::new).extend(named);
+        }
+
+        Ok(VectorEntry {
+            id,
+            vector,
+            named_vectors,
+            metadata: self.metadata,
+        })
+    }
+}
+
+/// Stream the points of a JSON document, or of a JSON Lines file if
+/// `lines` is set or the file is named `.jsonl`/`.ndjson`
+///
+/// Documents are streamed element by element, whether they are an array
+/// of points or an object holding them.
+fn read_json_points(path: &str, lines: bool, loader: &mut Loader) -> Result<()> {
+    let file = File::open(path).context("Failed to open JSON file")?;
+    let reader = BufReader::new(file);
+
+    let lines = lines
+        || Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext == "jsonl" || ext == "ndjson");
+    if lines {
+        for point in serde_json::Deserializer::from_reader(reader).into_iter::<JsonPoint>() {
+            loader.push(point.context("Failed to parse JSON line")?.into_entry()?)?;
+        }
+        return Ok(());
+    }
+
+    let mut error = None;
+    let mut sink = |point: JsonPoint| loader.push(point.into_entry()?);
+    let seed = PointStream {
+        level: Level::Document,
+        sink: &mut sink,
+        error: &mut error,
+    };
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    let result = seed.deserialize(&mut de).and_then(|()| de.end());
+    // Errors raised while inserting take precedence over the parse error
+    // they were smuggled through
+    if let Some(error) = error {
+        return Err(error);
+    }
+    result.context("Failed to parse JSON")
+}
+
+/// How deep a [`PointStream`] is in the document
+#[derive(Clone, Copy)]
+enum Level {
+    /// Points, or an object holding them under `result`, `points`,
+    /// `vectors` or `objects`
+    Document,
+    /// An array of points, or an object of points keyed by id
+    Points,
+}
+
+/// Deserializes points one at a time, handing each to `sink`
+struct PointStream<'a, F> {
+    level: Level,
+    sink: &'a mut F,
+    error: &'a mut Option<anyhow::Error>,
+}
+
+impl<F: FnMut(JsonPoint) -> Result<()>> PointStream<'_, F> {
+    fn emit<E: serde::de::Error>(&mut self, point: JsonPoint) -> std::result::Result<(), E> {
+        (self.sink)(point).map_err(|e| {
+            let message = e.to_string();
+            *self.error = Some(e);
+            E::custom(message)
+        })
+    }
+}
+
+impl<'de, F: FnMut(JsonPoint) -> Result<()>> DeserializeSeed<'de> for PointStream<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, F: FnMut(JsonPoint) -> Result<()>> Visitor<'de> for PointStream<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array or object of points")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(point) = seq.next_element::<JsonPoint>()? {
+            self.emit(point)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> std::result::Result<(), A::Error> {
+        match self.level {
+            Level::Document => {
+                while let Some(key) = map.next_key::<String>()? {
+                    let level = match key.as_str() {
+                        "result" => Level::Document,
+                        "points" | "vectors" | "objects" => Level::Points,
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                            continue;
+                        }
+                    };
+                    map.next_value_seed(PointStream {
+                        level,
+                        sink: &mut *self.sink,
+                        error: &mut *self.error,
+                    })?;
+                }
+            }
+            Level::Points => {
+                while let Some(id) = map.next_key::<String>()? {
+                    let mut point: JsonPoint = map.next_value()?;
+                    point.id.get_or_insert(Value::String(id));
+                    self.emit(point)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stream a CSV file with `id,vector,metadata[,named_vectors]` columns
+fn read_csv(path: &str, loader: &mut Loader) -> Result<()> {
+    let mut reader = csv::Reader::from_path(path).context("Failed to open CSV file")?;
+
+    for result in reader.records() {
+        let record = result.context("Failed to read CSV record")?;
+        loader.push(csv_entry(&record)?)?;
+    }
+    Ok(())
+}
+
+fn csv_entry(record: &csv::StringRecord) -> Result<VectorEntry> {
+    let id = match record.get(0) {
+        Some(id) if !id.is_empty() => Some(id.to_string()),
+        _ => None,
+    };
+
+    // The default vector may be left empty when named vectors are given
+    let vector_str = record.get(1).context("Missing vector column")?;
+    let vector: Vec<f32> = if vector_str.is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(vector_str).context("Failed to parse vector")?
+    };
+
+    let metadata = match record.get(2) {
+        Some(meta_str) if !meta_str.is_empty() => {
+            serde_json::from_str(meta_str).context("Failed to parse metadata")?
+        }
+        _ => None,
+    };
+
+    let named_vectors = match record.get(3) {
+        Some(named_str) if !named_str.is_empty() => {
+            Some(serde_json::from_str(named_str).context("Failed to parse named vectors")?)
+        }
+        _ => None,
+    };
+
+    Ok(VectorEntry {
+        id,
+        vector,
+        named_vectors,
+        metadata,
+    })
+}
+
+/// Sidecar with the ids, metadata and named vectors of an NPY export
+pub fn npy_sidecar(path: &str) -> PathBuf {
+    Path::new(path).with_extension("meta.jsonl")
+}
+
+/// Stream the rows of an NPY matrix, with ids and metadata from its
+/// sidecar if there is one, or `vec_<row>` ids otherwise
+fn read_npy(path: &str, loader: &mut Loader) -> Result<()> {
+    let file = File::open(path).context("Failed to open NPY file")?;
+    let mut reader = NpyReader::new(BufReader::new(file)).context("Failed to read NPY file")?;
+    let dimensions = loader.db.options().dimensions;
+    if reader.cols() != dimensions {
+        bail!(
+            "NPY file has {}-dimensional vectors, the database {}",
+            reader.cols(),
+            dimensions
+        );
+    }
+    loader.set_total(reader.rows() as u64);
+
+    let sidecar = npy_sidecar(path);
+    let mut points = if sidecar.exists() {
+        let file = File::open(&sidecar).context("Failed to open NPY sidecar")?;
+        Some(serde_json::Deserializer::from_reader(BufReader::new(file)).into_iter::<JsonPoint>())
+    } else {
+        None
+    };
+
+    let mut row_index = 0;
+    while let Some(row) = reader.next_row()? {
+        let entry = match points.as_mut().map(Iterator::next) {
+            Some(Some(point)) => {
+                let mut entry = point.context("Failed to parse NPY sidecar")?.into_entry()?;
+                entry.vector = row;
+                entry
+            }
+            Some(None) => bail!(
+                "{} ends before row {} of {}",
+                sidecar.display(),
+                row_index,
+                reader.rows()
+            ),
+            None => VectorEntry {
+                id: Some(format!("vec_{}", row_index)),
+                vector: row,
+                named_vectors: None,
+                metadata: None,
+            },
+        };
+        loader.push(entry)?;
+        row_index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+fn read_parquet(path: &str, loader: &mut Loader) -> Result<()> {
+    use arrow::array::{Array, AsArray, ListArray, StringArray};
+    use arrow::datatypes::{DataType, Field, Float32Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::sync::Arc;
+
+    let file = File::open(path).context("Failed to open Parquet file")?;
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(file).context("Failed to read Parquet file")?;
+    loader.set_total(builder.metadata().file_metadata().num_rows() as u64);
+    let reader = builder.build()?;
+
+    let list_type = DataType::List(Arc::new(Field::new("item", DataType::Float32, true)));
+    for batch in reader {
+        let batch = batch.context("Failed to read Parquet row group")?;
+        let strings = |name: &str| -> Result<Option<StringArray>> {
+            batch
+                .column_by_name(name)
+                .map(|column| {
+                    arrow::compute::cast(column, &DataType::Utf8)
+                        .map(|column| column.as_string::<i32>().clone())
+                        .with_context(|| format!("Column '{}' is not text", name))
+                })
+                .transpose()
+        };
+        let ids = strings("id")?;
+        let metadata = strings("metadata")?;
+        let named_vectors = strings("named_vectors")?;
+        let vectors: Option<ListArray> = batch
+            .column_by_name("vector")
+            .map(|column| {
+                arrow::compute::cast(column, &list_type)
+                    .map(|column| column.as_list::<i32>().clone())
+                    .context("Column 'vector' is not a list of floats")
+            })
+            .transpose()?;
+
+        for row in 0..batch.num_rows() {
+            let text = |column: &Option<StringArray>| {
+                column
+                    .as_ref()
+                    .filter(|column| column.is_valid(row))
+                    .map(|column| column.value(row).to_string())
+            };
+            let vector = match &vectors {
+                Some(vectors) if vectors.is_valid(row) => vectors
+                    .value(row)
+                    .as_primitive::<Float32Type>()
+                    .values()
+                    .to_vec(),
+                _ => Vec::new(),
+            };
+            loader.push(VectorEntry {
+                id: text(&ids),
+                vector,
+                named_vectors: text(&named_vectors)
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .context("Failed to parse named vectors")?,
+                metadata: text(&metadata)
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .context("Failed to parse metadata")?,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn read_parquet(_path: &str, _loader: &mut Loader) -> Result<()> {
+    bail!("Parquet support requires building ruvector-cli with the `parquet` feature")
+}
+
+/// Stream the vectors of a FAISS index, using FAISS ids as point ids
+fn read_faiss(path: &str, loader: &mut Loader) -> Result<()> {
+    let index = FaissIndex::open(path)?;
+    let header = index.header();
+
+    let dimensions = loader.db.options().dimensions;
+    if header.dimension != dimensions {
+        bail!(
+            "FAISS index has {}-dimensional vectors, the database {}",
+            header.dimension,
+            dimensions
+        );
+    }
+    let metric = loader.db.options().distance_metric;
+    let matches = match header.metric {
+        FaissMetric::L2 => metric == DistanceMetric::Euclidean,
+        FaissMetric::InnerProduct => {
+            matches!(metric, DistanceMetric::DotProduct | DistanceMetric::Cosine)
+        }
+        FaissMetric::Other(_) => false,
+    };
+    if !matches {
+        println!(
+            "{}",
+            format_warning(&format!(
+                "FAISS index uses {:?}, the database {:?}",
+                header.metric, metric
+            ))
+        );
+    }
+
+    println!(
+        "{}",
+        format_info(&format!(
+            "FAISS index: {} vectors of dimension {}",
+            header.count, header.dimension
+        ))
+    );
+    loader.set_total(header.count as u64);
+    index.for_each(|id, vector| {
+        loader.push(VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        })
+    })
+}
@@ -1,11 +1,15 @@
 //! CLI module for Ruvector
 
 pub mod commands;
+pub mod export;
+pub mod faiss;
 pub mod format;
 pub mod graph;
 pub mod hooks;
 #[cfg(feature = "postgres")]
 pub mod hooks_postgres;
+pub mod import;
+pub mod npy;
 pub mod progress;
 
 pub use commands::*;
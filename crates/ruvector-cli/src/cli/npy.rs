@@ -0,0 +1,209 @@
+//! Streaming reader and writer for 2-D NumPy `.npy` files
+//!
+//! Rows are read and written one at a time, so exports and imports do not
+//! hold the whole matrix in memory. Only C-order little-endian `f4`/`f8`
+//! arrays are supported.
+
+use anyhow::{bail, Context, Result};
+use std::io::{Read, Write};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Element type of an array
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dtype {
+    F32,
+    F64,
+}
+
+impl Dtype {
+    fn size(self) -> usize {
+        match self {
+            Dtype::F32 => 4,
+            Dtype::F64 => 8,
+        }
+    }
+}
+
+/// Reads the rows of a 2-D array as `f32` vectors
+pub struct NpyReader<R> {
+    reader: R,
+    dtype: Dtype,
+    rows: usize,
+    cols: usize,
+    read: usize,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> NpyReader<R> {
+    /// Parse the header, leaving the reader at the first row
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut preamble = [0u8; 8];
+        reader
+            .read_exact(&mut preamble)
+            .context("Failed to read NPY header")?;
+        if &preamble[..6] != MAGIC {
+            bail!("Not an NPY file");
+        }
+
+        let header_len = match preamble[6] {
+            1 => {
+                let mut len = [0u8; 2];
+                reader.read_exact(&mut len)?;
+                u16::from_le_bytes(len) as usize
+            }
+            2 | 3 => {
+                let mut len = [0u8; 4];
+                reader.read_exact(&mut len)?;
+                u32::from_le_bytes(len) as usize
+            }
+            version => bail!("Unsupported NPY version {}", version),
+        };
+        let mut header = vec![0u8; header_len];
+        reader.read_exact(&mut header)?;
+        let header = String::from_utf8_lossy(&header);
+
+        let dtype = match header_value(&header, "descr") {
+            Some("'<f4'") | Some("'float32'") => Dtype::F32,
+            Some("'<f8'") | Some("'float64'") => Dtype::F64,
+            Some(descr) => bail!("Unsupported NPY dtype {}; expected <f4 or <f8", descr),
+            None => bail!("NPY header has no dtype"),
+        };
+        if header_value(&header, "fortran_order") == Some("True") {
+            bail!("Fortran-order NPY arrays are not supported");
+        }
+        let (rows, cols) = header_value(&header, "shape")
+            .and_then(parse_shape)
+            .context("Expected a 2-D NPY array")?;
+
+        Ok(Self {
+            reader,
+            dtype,
+            rows,
+            cols,
+            read: 0,
+            buf: vec![0u8; cols * dtype.size()],
+        })
+    }
+
+    /// Number of rows in the array
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Length of each row
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Read the next row, or `None` after the last one
+    pub fn next_row(&mut self) -> Result<Option<Vec<f32>>> {
+        if self.read == self.rows {
+            return Ok(None);
+        }
+        self.reader
+            .read_exact(&mut self.buf)
+            .with_context(|| format!("NPY data ends at row {}", self.read))?;
+        self.read += 1;
+
+        let row = match self.dtype {
+            Dtype::F32 => self
+                .buf
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+            Dtype::F64 => self
+                .buf
+                .chunks_exact(8)
+                .map(|b| f64::from_le_bytes(b.try_into().unwrap()) as f32)
+                .collect(),
+        };
+        Ok(Some(row))
+    }
+}
+
+/// The raw value of `key` in an NPY header dictionary
+fn header_value<'h>(header: &'h str, key: &str) -> Option<&'h str> {
+    let start = header.find(&format!("'{}'", key))? + key.len() + 2;
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')')? + 1
+    } else {
+        rest.find([',', '}'])?
+    };
+    Some(rest[..end].trim())
+}
+
+/// Parse a `(rows, cols)` shape tuple
+fn parse_shape(shape: &str) -> Option<(usize, usize)> {
+    let dims: Vec<usize> = shape
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse().ok())
+        .collect::<Option<_>>()?;
+    match dims.as_slice() {
+        [rows, cols] => Some((*rows, *cols)),
+        _ => None,
+    }
+}
+
+/// Writes `f32` rows of a 2-D array whose shape is known up front
+pub struct NpyWriter<W> {
+    writer: W,
+    rows: usize,
+    cols: usize,
+    written: usize,
+}
+
+impl<W: Write> NpyWriter<W> {
+    /// Write the header of a `rows` x `cols` array
+    pub fn new(mut writer: W, rows: usize, cols: usize) -> Result<Self> {
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            rows, cols
+        );
+        // Pad so the data starts on a 64-byte boundary, ending with a newline
+        let unpadded = MAGIC.len() + 2 + 2 + header.len() + 1;
+        header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+        header.push('\n');
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[1, 0])?;
+        writer.write_all(&(header.len() as u16).to_le_bytes())?;
+        writer.write_all(header.as_bytes())?;
+
+        Ok(Self {
+            writer,
+            rows,
+            cols,
+            written: 0,
+        })
+    }
+
+    /// Write the next row
+    pub fn write_row(&mut self, row: &[f32]) -> Result<()> {
+        if row.len() != self.cols {
+            bail!("Row has {} values, expected {}", row.len(), self.cols);
+        }
+        if self.written == self.rows {
+            bail!("Array already holds {} rows", self.rows);
+        }
+        for value in row {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Check that every row was written and flush
+    pub fn finish(mut self) -> Result<W> {
+        if self.written != self.rows {
+            bail!("Wrote {} of {} rows", self.written, self.rows);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
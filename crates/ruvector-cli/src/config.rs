@@ -1,8 +1,11 @@
 //! Configuration management for Ruvector CLI
 
 use anyhow::{Context, Result};
-use ruvector_core::types::{DbOptions, DistanceMetric, HnswConfig, QuantizationConfig};
+use ruvector_core::types::{
+    DbOptions, DistanceMetric, HnswConfig, QuantizationConfig, VectorSpaceConfig,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Ruvector CLI configuration
@@ -43,6 +46,10 @@ pub struct DatabaseConfig {
     /// Quantization configuration
     #[serde(default)]
     pub quantization: Option<QuantizationConfig>,
+
+    /// Additional named vector spaces, keyed by name
+    #[serde(default)]
+    pub named_vectors: Option<HashMap<String, VectorSpaceConfig>>,
 }
 
 /// CLI configuration
@@ -124,6 +131,7 @@ impl Default for DatabaseConfig {
             distance_metric: DistanceMetric::Cosine,
             hnsw: Some(HnswConfig::default()),
             quantization: Some(QuantizationConfig::Scalar),
+            named_vectors: None,
         }
     }
 }
@@ -230,6 +238,7 @@ impl Config {
             storage_path: self.database.storage_path.clone(),
             hnsw_config: self.database.hnsw.clone(),
             quantization: self.database.quantization.clone(),
+            named_vectors: self.database.named_vectors.clone(),
         }
     }
 
@@ -3,6 +3,7 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
 use colored::*;
+use ruvector_core::SearchParams;
 use std::path::PathBuf;
 
 mod cli;
@@ -55,10 +56,14 @@ enum Commands {
         #[arg(short, long)]
         input: String,
 
-        /// Input format (json, csv, npy)
+        /// Input format (json, jsonl, csv, npy, parquet)
         #[arg(short, long, default_value = "json")]
         format: String,
 
+        /// Record progress in this file and resume from it if it exists
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+
         /// Hide progress bar
         #[arg(long)]
         no_progress: bool,
@@ -74,6 +79,10 @@ enum Commands {
         #[arg(short, long)]
         query: String,
 
+        /// Named vector space to search instead of the default one
+        #[arg(long)]
+        using: Option<String>,
+
         /// Number of results
         #[arg(short = 'k', long, default_value = "10")]
         top_k: usize,
@@ -81,6 +90,26 @@ enum Commands {
         /// Show full vectors in results
         #[arg(long)]
         show_vectors: bool,
+
+        /// HNSW candidate list size (overrides the configured ef_search)
+        #[arg(long)]
+        ef_search: Option<usize>,
+
+        /// Scan every vector for exact results instead of traversing the index
+        #[arg(long)]
+        exact: bool,
+
+        /// Skip re-ranking quantized results with full-precision vectors
+        #[arg(long)]
+        no_rescore: bool,
+
+        /// Candidates collected per result before re-ranking
+        #[arg(long, default_value = "1.0")]
+        oversampling: f32,
+
+        /// Drop results whose distance is above this value
+        #[arg(long)]
+        score_threshold: Option<f32>,
     },
 
     /// Show database information
@@ -111,9 +140,13 @@ enum Commands {
         #[arg(short, long)]
         output: String,
 
-        /// Output format (json, csv)
+        /// Output format (json, jsonl, csv, npy, parquet)
         #[arg(short, long, default_value = "json")]
         format: String,
+
+        /// Hide progress bar
+        #[arg(long)]
+        no_progress: bool,
     },
 
     /// Import from other vector databases
@@ -122,13 +155,21 @@ enum Commands {
         #[arg(short = 'b', long, default_value = "./ruvector.db")]
         db: String,
 
-        /// Source database type (faiss, pinecone, weaviate)
+        /// Source database type (faiss, qdrant, pinecone, weaviate)
         #[arg(short, long)]
         source: String,
 
-        /// Source file or connection path
+        /// FAISS index file or JSON dump to read
         #[arg(short = 'p', long)]
         source_path: String,
+
+        /// Record progress in this file and resume from it if it exists
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+
+        /// Hide progress bar
+        #[arg(long)]
+        no_progress: bool,
     },
 
     /// Graph database operations (Neo4j-compatible)
@@ -170,25 +211,60 @@ async fn main() -> Result<()> {
             db,
             input,
             format,
+            checkpoint,
             no_progress,
-        } => insert_vectors(&db, &input, &format, &config, !no_progress),
+        } => insert_vectors(
+            &db,
+            &input,
+            &format,
+            checkpoint.as_deref(),
+            &config,
+            !no_progress,
+        ),
         Commands::Search {
             db,
             query,
+            using,
             top_k,
             show_vectors,
+            ef_search,
+            exact,
+            no_rescore,
+            oversampling,
+            score_threshold,
         } => {
             let query_vec = parse_query_vector(&query)?;
-            search_vectors(&db, query_vec, top_k, &config, show_vectors)
+            let params = SearchParams {
+                ef_search,
+                exact,
+                rescore: !no_rescore,
+                oversampling,
+                score_threshold,
+            };
+            search_vectors(&db, query_vec, using, top_k, &params, &config, show_vectors)
         }
         Commands::Info { db } => show_info(&db, &config),
         Commands::Benchmark { db, queries } => run_benchmark(&db, &config, queries),
-        Commands::Export { db, output, format } => export_database(&db, &output, &format, &config),
+        Commands::Export {
+            db,
+            output,
+            format,
+            no_progress,
+        } => export_database(&db, &output, &format, &config, !no_progress),
         Commands::Import {
             db,
             source,
             source_path,
-        } => import_from_external(&db, &source, &source_path, &config),
+            checkpoint,
+            no_progress,
+        } => import_from_external(
+            &db,
+            &source,
+            &source_path,
+            checkpoint.as_deref(),
+            &config,
+            !no_progress,
+        ),
         Commands::Graph { action } => {
             use cli::graph::GraphCommands;
             match action {
@@ -5,7 +5,7 @@ use super::protocol::*;
 use crate::config::Config;
 use anyhow::{Context, Result};
 use ruvector_core::{
-    types::{DbOptions, DistanceMetric, SearchQuery, VectorEntry},
+    types::{DistanceMetric, FilterExpression, SearchQuery, VectorEntry},
     VectorDB,
 };
 use ruvector_gnn::{compress::TensorCompress, search::differentiable_search};
@@ -420,6 +420,7 @@ impl McpHandler {
             .map(|v| VectorEntry {
                 id: v.id,
                 vector: v.vector,
+                named_vectors: None,
                 metadata: v.metadata.and_then(|m| serde_json::from_value(m).ok()),
             })
             .collect();
@@ -434,8 +435,11 @@ impl McpHandler {
 
         let results = db.search(SearchQuery {
             vector: params.query,
+            using: None,
             k: params.k,
-            filter: params.filter.and_then(|f| serde_json::from_value(f).ok()),
+            filter: params
+                .filter
+                .and_then(|f| FilterExpression::from_json(f).ok()),
             ef_search: None,
         })?;
 
@@ -202,3 +202,266 @@ fn test_error_handling() {
         .failure()
         .stderr(predicate::str::contains("Error"));
 }
+
+fn create_db(db_path: &std::path::Path, dimensions: usize) {
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("create")
+        .arg("--path")
+        .arg(db_path.to_str().unwrap())
+        .arg("--dimensions")
+        .arg(dimensions.to_string());
+    cmd.assert().success();
+}
+
+#[test]
+fn test_export_import_round_trip() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("source.db");
+    let json_path = dir.path().join("vectors.json");
+
+    let test_data = r#"[
+        {"id": "v1", "vector": [1.0, 0.0, 0.0], "metadata": {"label": "a"}},
+        {"id": "v2", "vector": [0.0, 1.0, 0.0], "metadata": {"label": "b"}},
+        {"id": "v3", "vector": [0.0, 0.0, 1.0]}
+    ]"#;
+    fs::write(&json_path, test_data).unwrap();
+
+    create_db(&db_path, 3);
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("insert")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap())
+        .arg("--input")
+        .arg(json_path.to_str().unwrap())
+        .arg("--no-progress");
+    cmd.assert().success();
+
+    for format in ["json", "jsonl", "csv", "npy"] {
+        let export_path = dir.path().join(format!("export.{}", format));
+        let mut cmd = Command::cargo_bin("ruvector").unwrap();
+        cmd.arg("export")
+            .arg("--db")
+            .arg(db_path.to_str().unwrap())
+            .arg("--output")
+            .arg(export_path.to_str().unwrap())
+            .arg("--format")
+            .arg(format)
+            .arg("--no-progress");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Exported 3 vectors"));
+
+        let copy_path = dir.path().join(format!("copy-{}.db", format));
+        create_db(&copy_path, 3);
+        let mut cmd = Command::cargo_bin("ruvector").unwrap();
+        cmd.arg("insert")
+            .arg("--db")
+            .arg(copy_path.to_str().unwrap())
+            .arg("--input")
+            .arg(export_path.to_str().unwrap())
+            .arg("--format")
+            .arg(format)
+            .arg("--no-progress");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("Inserted 3 vectors"));
+
+        // Ids and vectors survive the round trip
+        let mut cmd = Command::cargo_bin("ruvector").unwrap();
+        cmd.arg("search")
+            .arg("--db")
+            .arg(copy_path.to_str().unwrap())
+            .arg("--query")
+            .arg("[0.0, 1.0, 0.0]")
+            .arg("--top-k")
+            .arg("1");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("v2"));
+    }
+}
+
+#[test]
+fn test_import_faiss_id_map() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let index_path = dir.path().join("index.faiss");
+
+    // IndexIDMap around an IndexFlatL2 with two 3-dimensional vectors, as
+    // written by faiss.write_index
+    let header = |index: &mut Vec<u8>| {
+        index.extend_from_slice(&3i32.to_le_bytes());
+        index.extend_from_slice(&2i64.to_le_bytes());
+        index.extend_from_slice(&[0u8; 16]);
+        index.push(1);
+        index.extend_from_slice(&1i32.to_le_bytes());
+    };
+    let mut index = Vec::new();
+    index.extend_from_slice(b"IxMp");
+    header(&mut index);
+    index.extend_from_slice(b"IxF2");
+    header(&mut index);
+    index.extend_from_slice(&6u64.to_le_bytes());
+    for value in [1.0f32, 0.0, 0.0, 0.0, 0.0, 1.0] {
+        index.extend_from_slice(&value.to_le_bytes());
+    }
+    index.extend_from_slice(&2u64.to_le_bytes());
+    for id in [100i64, 200] {
+        index.extend_from_slice(&id.to_le_bytes());
+    }
+    fs::write(&index_path, index).unwrap();
+
+    create_db(&db_path, 3);
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("import")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap())
+        .arg("--source")
+        .arg("faiss")
+        .arg("--source-path")
+        .arg(index_path.to_str().unwrap())
+        .arg("--no-progress");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Inserted 2 vectors"));
+
+    // FAISS ids become string ids
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("search")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap())
+        .arg("--query")
+        .arg("[0.0, 0.0, 1.0]")
+        .arg("--top-k")
+        .arg("1");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("200"));
+}
+
+#[test]
+fn test_import_qdrant_dump() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let dump_path = dir.path().join("points.json");
+
+    let dump = r#"{
+        "result": {
+            "points": [
+                {"id": 7, "vector": [1.0, 0.0], "payload": {"city": "Berlin"}},
+                {"id": "a1", "vector": [0.0, 1.0], "payload": {"city": "Paris"}}
+            ],
+            "next_page_offset": null
+        },
+        "status": "ok",
+        "time": 0.001
+    }"#;
+    fs::write(&dump_path, dump).unwrap();
+
+    create_db(&db_path, 2);
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("import")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap())
+        .arg("--source")
+        .arg("qdrant")
+        .arg("--source-path")
+        .arg(dump_path.to_str().unwrap())
+        .arg("--no-progress");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Inserted 2 vectors"));
+}
+
+#[test]
+fn test_insert_resumes_from_checkpoint() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let input_path = dir.path().join("vectors.jsonl");
+    let checkpoint_path = dir.path().join("insert.checkpoint");
+
+    let lines = [
+        r#"{"id": "v1", "vector": [1.0, 0.0]}"#,
+        r#"{"id": "v2", "vector": [0.0, 1.0]}"#,
+        r#"{"id": "v3", "vector": [1.0, 1.0]}"#,
+    ];
+    fs::write(&input_path, lines.join("\n")).unwrap();
+
+    // A load that was interrupted after the first two entries
+    let checkpoint = serde_json::json!({
+        "source": fs::canonicalize(&input_path).unwrap(),
+        "format": "jsonl",
+        "source_len": fs::metadata(&input_path).unwrap().len(),
+        "loaded": 2,
+    });
+    fs::write(&checkpoint_path, checkpoint.to_string()).unwrap();
+
+    create_db(&db_path, 2);
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.arg("insert")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap())
+        .arg("--input")
+        .arg(input_path.to_str().unwrap())
+        .arg("--format")
+        .arg("jsonl")
+        .arg("--checkpoint")
+        .arg(checkpoint_path.to_str().unwrap())
+        .arg("--no-progress");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Inserted 1 vectors"));
+
+    // A finished load removes its checkpoint
+    assert!(!checkpoint_path.exists());
+}
+
+#[test]
+fn test_resume_after_crash_before_checkpoint_does_not_duplicate() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("test.db");
+    let input_path = dir.path().join("vectors.jsonl");
+    let checkpoint_path = dir.path().join("insert.checkpoint");
+
+    let lines = [
+        r#"{"vector": [1.0, 0.0]}"#,
+        r#"{"vector": [0.0, 1.0]}"#,
+        r#"{"vector": [1.0, 1.0]}"#,
+    ];
+    fs::write(&input_path, lines.join("\n")).unwrap();
+
+    let insert = || {
+        let mut cmd = Command::cargo_bin("ruvector").unwrap();
+        cmd.arg("insert")
+            .arg("--db")
+            .arg(db_path.to_str().unwrap())
+            .arg("--input")
+            .arg(input_path.to_str().unwrap())
+            .arg("--format")
+            .arg("jsonl")
+            .arg("--checkpoint")
+            .arg(checkpoint_path.to_str().unwrap())
+            .arg("--no-progress");
+        cmd
+    };
+
+    create_db(&db_path, 2);
+    insert().assert().success();
+
+    // The load was killed after inserting its batch but before saving the
+    // checkpoint, so the resumed load replays the whole batch
+    assert!(!checkpoint_path.exists());
+    insert()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Inserted 3 vectors"));
+
+    let mut cmd = Command::cargo_bin("ruvector").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .arg("info")
+        .arg("--db")
+        .arg(db_path.to_str().unwrap());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Vectors: 3"));
+}
@@ -4,10 +4,12 @@
 //! - Cluster node management and health monitoring
 //! - Consistent hashing for shard distribution
 //! - DAG-based consensus protocol
+//! - Raft-replicated vector collections with scatter-gather search
 //! - Dynamic node discovery and topology management
 
 pub mod consensus;
 pub mod discovery;
+pub mod replicated;
 pub mod shard;
 
 use chrono::{DateTime, Utc};
@@ -24,6 +26,10 @@ use uuid::Uuid;
 
 pub use consensus::DagConsensus;
 pub use discovery::{DiscoveryService, GossipDiscovery, StaticDiscovery};
+pub use replicated::{
+    InProcessCluster, ReadConsistency, ReplicatedCollection, ShardReplica, VectorOp,
+    VectorStateMachine,
+};
 pub use shard::{ConsistentHashRing, ShardRouter};
 
 /// Cluster-related errors
@@ -50,6 +56,9 @@ pub enum ClusterError {
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
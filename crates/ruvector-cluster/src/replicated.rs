@@ -0,0 +1,815 @@
+//! Raft-replicated vector collections
+//!
+//! Each shard of a [`ReplicatedCollection`] is a Raft group whose replicas
+//! each hold a [`VectorDB`]. Inserts and deletes are replicated as
+//! [`VectorOp`] log commands and applied by every replica in log order;
+//! searches are scattered over the shards picked by [`ShardRouter`] and
+//! merged into a global top-k.
+
+use bincode::config;
+use futures::future::join_all;
+use ruvector_core::types::DbOptions;
+use ruvector_core::{
+    PointSelector, RuvectorError, SearchQuery, SearchResult, VectorDB, VectorEntry, VectorId,
+};
+use ruvector_raft::{
+    InProcessNetwork, MemoryStorage, RaftError, RaftNode, RaftNodeConfig, RaftResult, StateMachine,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::debug;
+
+use crate::{ClusterError, Result, ShardRouter};
+
+/// Interval between attempts while a shard has no leader
+const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long one attempt waits for a leader, a few election timeouts
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A write replicated through a shard's Raft log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VectorOp {
+    /// Insert or replace vectors, each with its ID set
+    Insert(Vec<VectorEntry>),
+    /// Delete vectors by ID
+    Delete(Vec<VectorId>),
+}
+
+impl VectorOp {
+    fn encode(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| ClusterError::SerializationError(e.to_string()))
+    }
+}
+
+/// Magic bytes opening a snapshot of a [`VectorStateMachine`]
+const SNAPSHOT_MAGIC: &[u8; 4] = b"RVS1";
+
+/// Snapshot entries upserted per logged operation while restoring
+const RESTORE_BATCH: usize = 1024;
+
+/// One vector entry in a snapshot, with its metadata as JSON text since
+/// bincode cannot encode arbitrary JSON values
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    id: VectorId,
+    vector: Vec<f32>,
+    named_vectors: Option<HashMap<String, Vec<f32>>>,
+    metadata: Option<String>,
+}
+
+/// Applies committed [`VectorOp`]s to a replica's [`VectorDB`]
+///
+/// Operations are encoded as JSON, which round-trips the JSON metadata of
+/// vector entries. Snapshots are the magic bytes followed by one
+/// bincode-encoded [`SnapshotEntry`] per stored vector, written and read
+/// one entry at a time.
+pub struct VectorStateMachine {
+    db: Arc<VectorDB>,
+}
+
+impl VectorStateMachine {
+    /// Create a state machine applying operations to `db`
+    pub fn new(db: Arc<VectorDB>) -> Self {
+        Self { db }
+    }
+
+    /// Get the database the operations are applied to
+    pub fn db(&self) -> &Arc<VectorDB> {
+        &self.db
+    }
+}
+
+impl StateMachine for VectorStateMachine {
+    fn apply(&self, _index: u64, command: &[u8]) -> RaftResult<()> {
+        let op: VectorOp = serde_json::from_slice(command)
+            .map_err(|e| RaftError::CommandRejected(e.to_string()))?;
+        match op {
+            VectorOp::Insert(entries) => {
+                self.db.insert_batch(entries).map_err(apply_error)?;
+            }
+            VectorOp::Delete(ids) => {
+                self.db
+                    .delete_points(&PointSelector::Ids(ids))
+                    .map_err(apply_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn snapshot(&self, out: &mut dyn Write) -> RaftResult<()> {
+        let mut out = BufWriter::new(out);
+        out.write_all(SNAPSHOT_MAGIC)?;
+        for id in self.db.keys().map_err(raft_error)? {
+            let Some(entry) = self.db.get(&id).map_err(raft_error)? else {
+                continue;
+            };
+            let metadata = entry
+                .metadata
+                .map(|metadata| serde_json::to_string(&metadata))
+                .transpose()
+                .map_err(raft_error)?;
+            let entry = SnapshotEntry {
+                id,
+                vector: entry.vector,
+                named_vectors: entry.named_vectors,
+                metadata,
+            };
+            bincode::serde::encode_into_std_write(&entry, &mut out, config::standard())?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Upsert the snapshot's entries in batches, then delete the entries it
+    /// does not hold
+    ///
+    /// Every batch and the final delete are logged operations of the
+    /// database, so an interrupted restore leaves the entries it already
+    /// wrote, and the node completes it by restoring the stored snapshot
+    /// again when it restarts.
+    fn restore(&self, data: &mut dyn Read) -> RaftResult<()> {
+        let mut restored = HashSet::new();
+        for_each_snapshot_batch(data, |entries| {
+            restored.extend(entries.iter().filter_map(|entry| entry.id.clone()));
+            self.db.insert_batch(entries).map_err(raft_error)?;
+            Ok(())
+        })?;
+
+        let stale: Vec<VectorId> = self
+            .db
+            .keys()
+            .map_err(raft_error)?
+            .into_iter()
+            .filter(|id| !restored.contains(id))
+            .collect();
+        if !stale.is_empty() {
+            self.db
+                .delete_points(&PointSelector::Ids(stale))
+                .map_err(raft_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode the entries of a snapshot, or of a JSON array as written by
+/// earlier versions, and pass them on in batches of at most
+/// [`RESTORE_BATCH`]
+fn for_each_snapshot_batch(
+    data: &mut dyn Read,
+    mut f: impl FnMut(Vec<VectorEntry>) -> RaftResult<()>,
+) -> RaftResult<()> {
+    let mut data = BufReader::new(data);
+    let mut magic = Vec::with_capacity(SNAPSHOT_MAGIC.len());
+    (&mut data)
+        .take(SNAPSHOT_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        magic.extend(data.bytes().collect::<std::io::Result<Vec<u8>>>()?);
+        let entries: Vec<VectorEntry> = serde_json::from_slice(&magic).map_err(raft_error)?;
+        for batch in entries.chunks(RESTORE_BATCH) {
+            f(batch.to_vec())?;
+        }
+        return Ok(());
+    }
+
+    let mut batch = Vec::with_capacity(RESTORE_BATCH);
+    while !data.fill_buf()?.is_empty() {
+        let entry: SnapshotEntry =
+            bincode::serde::decode_from_std_read(&mut data, config::standard())?;
+        let metadata = entry
+            .metadata
+            .map(|metadata| serde_json::from_str(&metadata))
+            .transpose()
+            .map_err(raft_error)?;
+        batch.push(VectorEntry {
+            id: Some(entry.id),
+            vector: entry.vector,
+            named_vectors: entry.named_vectors,
+            metadata,
+        });
+        if batch.len() == RESTORE_BATCH {
+            f(std::mem::take(&mut batch))?;
+        }
+    }
+    if !batch.is_empty() {
+        f(batch)?;
+    }
+    Ok(())
+}
+
+fn raft_error(err: impl std::fmt::Display) -> RaftError {
+    RaftError::Internal(err.to_string())
+}
+
+/// Reject operations that every replica refuses alike; anything else, such
+/// as an I/O error, is left for Raft to retry
+fn apply_error(err: RuvectorError) -> RaftError {
+    match err {
+        RuvectorError::DimensionMismatch { .. }
+        | RuvectorError::InvalidInput(_)
+        | RuvectorError::InvalidParameter(_)
+        | RuvectorError::InvalidDimension(_) => RaftError::CommandRejected(err.to_string()),
+        err => raft_error(err),
+    }
+}
+
+/// How up to date the replica serving a read must be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Served by the shard leader once it has confirmed its leadership, so
+    /// every acknowledged write is visible
+    Linearizable,
+    /// Served by any replica whose state machine had caught up with the
+    /// leader's commit index within the bound, preferring followers
+    BoundedStaleness(Duration),
+    /// Served by any replica, however far behind it is
+    Any,
+}
+
+/// One replica of a shard: a Raft node and the database it applies to
+#[derive(Clone)]
+pub struct ShardReplica {
+    /// Raft node of the replica
+    pub node: Arc<RaftNode>,
+    /// Database the node's committed operations are applied to
+    pub db: Arc<VectorDB>,
+}
+
+impl ShardReplica {
+    /// Create a replica from a node and the database of its state machine
+    pub fn new(node: Arc<RaftNode>, db: Arc<VectorDB>) -> Self {
+        Self { node, db }
+    }
+
+    fn is_leader(&self) -> bool {
+        self.node.current_state().is_leader()
+    }
+}
+
+/// A vector collection sharded with [`ShardRouter`] and replicated with
+/// one Raft group per shard
+pub struct ReplicatedCollection {
+    router: ShardRouter,
+    /// Replicas of each shard, indexed by shard ID
+    shards: Vec<Vec<ShardReplica>>,
+    /// How long to wait for a shard leader before failing a request
+    request_timeout: Duration,
+}
+
+impl ReplicatedCollection {
+    /// Create a collection over the replicas of each shard
+    pub fn new(shards: Vec<Vec<ShardReplica>>) -> Result<Self> {
+        if shards.is_empty() || shards.iter().any(|replicas| replicas.is_empty()) {
+            return Err(ClusterError::InvalidConfig(
+                "Every shard needs at least one replica".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            router: ShardRouter::new(shards.len() as u32),
+            shards,
+            request_timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Set how long requests wait for a shard to elect a leader
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Number of shards
+    pub fn shard_count(&self) -> u32 {
+        self.shards.len() as u32
+    }
+
+    /// Get the replicas of a shard
+    pub fn replicas(&self, shard_id: u32) -> Result<&[ShardReplica]> {
+        self.shards
+            .get(shard_id as usize)
+            .map(Vec::as_slice)
+            .ok_or(ClusterError::ShardNotFound(shard_id))
+    }
+
+    /// Get the shard a vector ID belongs to
+    pub fn shard_for(&self, id: &str) -> u32 {
+        self.router.get_shard_for_vector(id)
+    }
+
+    /// Insert a vector, returning its ID once the write is committed
+    pub async fn insert(&self, entry: VectorEntry) -> Result<VectorId> {
+        let mut ids = self.insert_batch(vec![entry]).await?;
+        Ok(ids.remove(0))
+    }
+
+    /// Insert vectors, replicating one command per shard
+    ///
+    /// IDs are assigned before routing, so a command retried after a
+    /// leader change replaces the vectors instead of duplicating them.
+    pub async fn insert_batch(&self, entries: Vec<VectorEntry>) -> Result<Vec<VectorId>> {
+        let mut by_shard: HashMap<u32, Vec<VectorEntry>> = HashMap::new();
+        let mut ids = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            let id = entry
+                .id
+                .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                .clone();
+            by_shard.entry(self.shard_for(&id)).or_default().push(entry);
+            ids.push(id);
+        }
+
+        let writes = by_shard
+            .into_iter()
+            .map(|(shard_id, entries)| self.replicate(shard_id, VectorOp::Insert(entries)));
+        for written in join_all(writes).await {
+            written?;
+        }
+        Ok(ids)
+    }
+
+    /// Delete a vector once the deletion is committed
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let shard_id = self.shard_for(id);
+        self.replicate(shard_id, VectorOp::Delete(vec![id.to_string()]))
+            .await
+    }
+
+    /// Get a vector by ID
+    pub async fn get(&self, id: &str, consistency: ReadConsistency) -> Result<Option<VectorEntry>> {
+        let replica = self.read_replica(self.shard_for(id), consistency).await?;
+        replica.db.get(id).map_err(database_error)
+    }
+
+    /// Search every shard and merge the results into the global top-k
+    pub async fn search(
+        &self,
+        query: SearchQuery,
+        consistency: ReadConsistency,
+    ) -> Result<Vec<SearchResult>> {
+        let k = query.k;
+        let searches = (0..self.shard_count()).map(|shard_id| {
+            let query = query.clone();
+            async move {
+                let replica = self.read_replica(shard_id, consistency).await?;
+                replica.db.search(query).map_err(database_error)
+            }
+        });
+
+        let mut results = Vec::new();
+        for shard_results in join_all(searches).await {
+            results.extend(shard_results?);
+        }
+
+        // Scores are distances, lower is better
+        results.sort_by(|a, b| a.score.total_cmp(&b.score));
+        results.truncate(k);
+        Ok(results)
+    }
+
+    /// Count the vectors over all shards
+    pub async fn len(&self, consistency: ReadConsistency) -> Result<usize> {
+        let mut total = 0;
+        for shard_id in 0..self.shard_count() {
+            let replica = self.read_replica(shard_id, consistency).await?;
+            total += replica.db.len().map_err(database_error)?;
+        }
+        Ok(total)
+    }
+
+    /// Replicate an operation through the leader of a shard
+    async fn replicate(&self, shard_id: u32, op: VectorOp) -> Result<()> {
+        let command = op.encode()?;
+        let deadline = Instant::now() + self.request_timeout;
+        loop {
+            let replica = self.leader(shard_id, deadline).await?;
+            let submitted = attempt(deadline, replica.node.submit_command(command.clone())).await;
+            match submitted {
+                Some(Ok(_)) => return Ok(()),
+                // Inserts and deletes are idempotent, so they can be retried
+                // even when the old leader may have committed them
+                Some(Err(e)) if !is_leader_change(&e) => return Err(consensus_error(e)),
+                _ if Instant::now() >= deadline => return Err(timed_out()),
+                _ => {
+                    debug!("Shard {} leader changed, retrying", shard_id);
+                    sleep(RETRY_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Pick the replica of a shard that serves a read
+    pub(crate) async fn read_replica(
+        &self,
+        shard_id: u32,
+        consistency: ReadConsistency,
+    ) -> Result<&ShardReplica> {
+        let replicas = self.replicas(shard_id)?;
+        let deadline = Instant::now() + self.request_timeout;
+        match consistency {
+            ReadConsistency::Linearizable => self.confirmed_leader(shard_id, deadline).await,
+            ReadConsistency::BoundedStaleness(bound) => {
+                let fresh = |replica: &&ShardReplica| {
+                    replica.node.caught_up_age().is_some_and(|age| age <= bound)
+                };
+                match replicas
+                    .iter()
+                    .filter(|replica| !replica.is_leader())
+                    .find(fresh)
+                {
+                    Some(replica) => Ok(replica),
+                    // A leader cut off from the majority keeps its role and
+                    // may be further behind than the bound, so it has to
+                    // confirm its leadership first
+                    None => self.confirmed_leader(shard_id, deadline).await,
+                }
+            }
+            ReadConsistency::Any => Ok(replicas
+                .iter()
+                .find(|replica| !replica.is_leader())
+                .unwrap_or(&replicas[0])),
+        }
+    }
+
+    /// Wait for a shard leader that confirms it still leads a majority
+    async fn confirmed_leader(&self, shard_id: u32, deadline: Instant) -> Result<&ShardReplica> {
+        loop {
+            let replica = self.leader(shard_id, deadline).await?;
+            match attempt(deadline, replica.node.read_index()).await {
+                Some(Ok(_)) => return Ok(replica),
+                Some(Err(e)) if !is_leader_change(&e) => return Err(consensus_error(e)),
+                _ if Instant::now() >= deadline => return Err(timed_out()),
+                _ => sleep(RETRY_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Wait for a shard to have a leader
+    ///
+    /// A partitioned leader keeps its role until it hears of a newer term,
+    /// so the leader of the latest term is picked.
+    async fn leader(&self, shard_id: u32, deadline: Instant) -> Result<&ShardReplica> {
+        let replicas = self.replicas(shard_id)?;
+        loop {
+            let leader = replicas
+                .iter()
+                .filter(|replica| replica.is_leader())
+                .max_by_key(|replica| replica.node.current_term());
+            if let Some(replica) = leader {
+                return Ok(replica);
+            }
+            if Instant::now() >= deadline {
+                return Err(ClusterError::ConsensusError(format!(
+                    "No leader for shard {}",
+                    shard_id
+                )));
+            }
+            sleep(RETRY_INTERVAL).await;
+        }
+    }
+}
+
+/// Wait for a request to a leader, giving up after [`ATTEMPT_TIMEOUT`] in
+/// case that leader was partitioned away and replaced
+async fn attempt<T>(deadline: Instant, request: impl Future<Output = T>) -> Option<T> {
+    let attempt_deadline = deadline.min(Instant::now() + ATTEMPT_TIMEOUT);
+    tokio::time::timeout_at(attempt_deadline, request)
+        .await
+        .ok()
+}
+
+fn is_leader_change(err: &RaftError) -> bool {
+    matches!(err, RaftError::NotLeader | RaftError::LeadershipLost(_))
+}
+
+fn timed_out() -> ClusterError {
+    ClusterError::ConsensusError("Request timed out".to_string())
+}
+
+fn consensus_error(err: RaftError) -> ClusterError {
+    ClusterError::ConsensusError(err.to_string())
+}
+
+fn database_error(err: RuvectorError) -> ClusterError {
+    ClusterError::DatabaseError(err.to_string())
+}
+
+/// In-process cluster running every replica of every shard, connected by
+/// one [`InProcessNetwork`] per shard
+///
+/// Meant for tests and local experiments: partitions are simulated with
+/// [`InProcessCluster::network`].
+pub struct InProcessCluster {
+    collection: ReplicatedCollection,
+    networks: Vec<Arc<InProcessNetwork>>,
+}
+
+impl InProcessCluster {
+    /// Start `shard_count` shards of `replication_factor` replicas
+    ///
+    /// Each replica stores its database under `dir`, using `options` with
+    /// the storage path replaced. Must be called within a Tokio runtime.
+    pub fn start(
+        dir: impl AsRef<Path>,
+        options: DbOptions,
+        shard_count: u32,
+        replication_factor: usize,
+    ) -> Result<Self> {
+        let mut shards = Vec::new();
+        let mut networks = Vec::new();
+        for shard_id in 0..shard_count {
+            let network = InProcessNetwork::new();
+            let members: Vec<String> = (0..replication_factor)
+                .map(|replica| format!("shard{}-replica{}", shard_id, replica))
+                .collect();
+
+            let mut replicas = Vec::new();
+            for node_id in &members {
+                let db = Arc::new(
+                    VectorDB::new(DbOptions {
+                        storage_path: dir
+                            .as_ref()
+                            .join(format!("{}.db", node_id))
+                            .to_string_lossy()
+                            .into_owned(),
+                        ..options.clone()
+                    })
+                    .map_err(database_error)?,
+                );
+                let node = RaftNode::with_storage(
+                    RaftNodeConfig::new(node_id.clone(), members.clone()),
+                    Arc::new(MemoryStorage::new()),
+                    Arc::new(VectorStateMachine::new(db.clone())),
+                )
+                .map_err(consensus_error)?
+                .with_transport(network.transport(node_id.clone()));
+                let node = Arc::new(node);
+                network.register(&node);
+                tokio::spawn(node.clone().start());
+                replicas.push(ShardReplica::new(node, db));
+            }
+            shards.push(replicas);
+            networks.push(network);
+        }
+
+        Ok(Self {
+            collection: ReplicatedCollection::new(shards)?,
+            networks,
+        })
+    }
+
+    /// Get the collection served by the cluster
+    pub fn collection(&self) -> &ReplicatedCollection {
+        &self.collection
+    }
+
+    /// Get the network of a shard's Raft group
+    pub fn network(&self, shard_id: u32) -> Result<&Arc<InProcessNetwork>> {
+        self.networks
+            .get(shard_id as usize)
+            .ok_or(ClusterError::ShardNotFound(shard_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruvector_core::DistanceMetric;
+    use tempfile::TempDir;
+
+    fn options(dir: &TempDir) -> DbOptions {
+        DbOptions {
+            dimensions: 3,
+            distance_metric: DistanceMetric::Euclidean,
+            storage_path: dir.path().join("db").to_string_lossy().into_owned(),
+            hnsw_config: None,
+            quantization: None,
+            named_vectors: None,
+        }
+    }
+
+    fn entry(i: usize) -> VectorEntry {
+        VectorEntry {
+            id: Some(format!("v{}", i)),
+            vector: vec![i as f32, 0.0, 0.0],
+            named_vectors: None,
+            metadata: Some(HashMap::from([("n".to_string(), serde_json::json!(i))])),
+        }
+    }
+
+    fn query(x: f32, k: usize) -> SearchQuery {
+        SearchQuery {
+            vector: vec![x, 0.0, 0.0],
+            using: None,
+            k,
+            filter: None,
+            ef_search: None,
+        }
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<String> {
+        results.iter().map(|result| result.id.clone()).collect()
+    }
+
+    /// Poll `condition` until it holds, failing after ten seconds
+    async fn eventually(mut condition: impl FnMut() -> bool) {
+        for _ in 0..1000 {
+            if condition() {
+                return;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not reached in time");
+    }
+
+    #[test]
+    fn test_state_machine_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = VectorStateMachine::new(Arc::new(VectorDB::new(options(&dir)).unwrap()));
+        let insert = VectorOp::Insert((0..4).map(entry).collect());
+        source.apply(1, &insert.encode().unwrap()).unwrap();
+        let delete = VectorOp::Delete(vec!["v1".to_string()]);
+        source.apply(2, &delete.encode().unwrap()).unwrap();
+
+        let other = tempfile::tempdir().unwrap();
+        let target = VectorStateMachine::new(Arc::new(VectorDB::new(options(&other)).unwrap()));
+        target
+            .apply(1, &VectorOp::Insert(vec![entry(9)]).encode().unwrap())
+            .unwrap();
+        let mut snapshot = Vec::new();
+        source.snapshot(&mut snapshot).unwrap();
+
+        // A restore cut short fails without dropping what the replica held
+        let truncated = &snapshot[..snapshot.len() - 3];
+        assert!(target.restore(&mut &truncated[..]).is_err());
+        assert!(target.db().get("v9").unwrap().is_some());
+
+        // Restoring again completes it
+        target.restore(&mut snapshot.as_slice()).unwrap();
+
+        let mut keys = target.db().keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["v0", "v2", "v3"]);
+        let restored = target.db().get("v2").unwrap().unwrap();
+        assert_eq!(restored.metadata, entry(2).metadata);
+
+        // Invalid operations fail on every replica alike
+        let bad = VectorOp::Insert(vec![VectorEntry {
+            vector: vec![1.0],
+            ..entry(5)
+        }]);
+        assert!(matches!(
+            target.apply(3, &bad.encode().unwrap()),
+            Err(RaftError::CommandRejected(_))
+        ));
+        assert!(matches!(
+            target.apply(4, b"not an operation"),
+            Err(RaftError::CommandRejected(_))
+        ));
+
+        // Snapshots written as JSON still restore
+        let legacy = serde_json::to_vec(&vec![entry(7)]).unwrap();
+        target.restore(&mut legacy.as_slice()).unwrap();
+        assert_eq!(target.db().keys().unwrap(), vec!["v7"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replicated_collection_scatter_gather() {
+        let dir = tempfile::tempdir().unwrap();
+        let cluster = InProcessCluster::start(dir.path(), options(&dir), 2, 3).unwrap();
+        let collection = cluster.collection();
+
+        let inserted = collection
+            .insert_batch((0..20).map(entry).collect())
+            .await
+            .unwrap();
+        assert_eq!(inserted.len(), 20);
+        let shards: std::collections::HashSet<_> =
+            inserted.iter().map(|id| collection.shard_for(id)).collect();
+        assert_eq!(shards.len(), 2, "vectors should span both shards");
+
+        // The global top-k merges the nearest vectors of every shard
+        let results = collection
+            .search(query(7.2, 4), ReadConsistency::Linearizable)
+            .await
+            .unwrap();
+        assert_eq!(ids(&results), vec!["v7", "v8", "v6", "v9"]);
+        assert_eq!(
+            collection.len(ReadConsistency::Linearizable).await.unwrap(),
+            20
+        );
+
+        // Every replica applies the same log
+        for shard_id in 0..2 {
+            let replicas = collection.replicas(shard_id).unwrap();
+            let expected = replicas
+                .iter()
+                .find(|replica| replica.is_leader())
+                .unwrap()
+                .db
+                .len()
+                .unwrap();
+            eventually(|| {
+                replicas
+                    .iter()
+                    .all(|replica| replica.db.len().unwrap() == expected)
+            })
+            .await;
+        }
+        let results = collection
+            .search(query(7.2, 4), ReadConsistency::Any)
+            .await
+            .unwrap();
+        assert_eq!(ids(&results), vec!["v7", "v8", "v6", "v9"]);
+
+        collection.delete("v7").await.unwrap();
+        assert!(collection
+            .get("v7", ReadConsistency::Linearizable)
+            .await
+            .unwrap()
+            .is_none());
+        let results = collection
+            .search(query(7.2, 2), ReadConsistency::Linearizable)
+            .await
+            .unwrap();
+        assert_eq!(ids(&results), vec!["v8", "v6"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_replicated_collection_survives_leader_partition() {
+        let dir = tempfile::tempdir().unwrap();
+        let cluster = InProcessCluster::start(dir.path(), options(&dir), 1, 3).unwrap();
+        let collection = cluster.collection();
+        collection.insert(entry(1)).await.unwrap();
+
+        // Writes go to the leader elected by the majority side
+        let old_leader = collection
+            .read_replica(0, ReadConsistency::Linearizable)
+            .await
+            .unwrap()
+            .clone();
+        let network = cluster.network(0).unwrap();
+        network.isolate(old_leader.node.node_id());
+        collection.insert(entry(2)).await.unwrap();
+        let leader = collection
+            .read_replica(0, ReadConsistency::Linearizable)
+            .await
+            .unwrap();
+        assert_ne!(leader.node.node_id(), old_leader.node.node_id());
+        assert!(leader.db.get("v2").unwrap().is_some());
+
+        // A follower cut off from the leader stops serving bounded reads
+        let bound = Duration::from_millis(500);
+        network.heal(old_leader.node.node_id());
+        eventually(|| !old_leader.is_leader()).await;
+        let follower = collection
+            .replicas(0)
+            .unwrap()
+            .iter()
+            .find(|replica| !replica.is_leader())
+            .unwrap();
+        network.isolate(follower.node.node_id());
+        sleep(bound * 2).await;
+        for _ in 0..10 {
+            let replica = collection
+                .read_replica(0, ReadConsistency::BoundedStaleness(bound))
+                .await
+                .unwrap();
+            assert_ne!(replica.node.node_id(), follower.node.node_id());
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bounded_read_rejects_partitioned_leader() {
+        let dir = tempfile::tempdir().unwrap();
+        let cluster = InProcessCluster::start(dir.path(), options(&dir), 1, 3).unwrap();
+        let collection = cluster.collection();
+        collection.insert(entry(1)).await.unwrap();
+
+        // With every replica cut off, no majority can confirm the old leader
+        // and no follower stays within the bound
+        let network = cluster.network(0).unwrap();
+        let replicas = collection.replicas(0).unwrap();
+        for replica in replicas {
+            network.isolate(replica.node.node_id());
+        }
+        let bound = Duration::from_millis(500);
+        sleep(bound * 2).await;
+        assert!(replicas.iter().any(ShardReplica::is_leader));
+
+        let read = collection
+            .read_replica(0, ReadConsistency::BoundedStaleness(bound))
+            .await;
+        assert!(matches!(read, Err(ClusterError::ConsensusError(_))));
+        assert!(collection
+            .get("v1", ReadConsistency::BoundedStaleness(bound))
+            .await
+            .is_err());
+    }
+}
@@ -1,8 +1,9 @@
 //! Collection types and operations
 
-use ruvector_core::types::{DistanceMetric, HnswConfig, QuantizationConfig};
+use ruvector_core::types::{DistanceMetric, HnswConfig, QuantizationConfig, VectorSpaceConfig};
 use ruvector_core::vector_db::VectorDB;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 use crate::error::{CollectionError, Result};
 
@@ -23,6 +24,10 @@ pub struct CollectionConfig {
 
     /// Whether to store payload data on disk
     pub on_disk_payload: bool,
+
+    /// Additional named vector spaces, keyed by name
+    #[serde(default)]
+    pub named_vectors: Option<HashMap<String, VectorSpaceConfig>>,
 }
 
 impl CollectionConfig {
@@ -72,6 +77,7 @@ impl CollectionConfig {
             hnsw_config: Some(HnswConfig::default()),
             quantization: Some(QuantizationConfig::Scalar),
             on_disk_payload: true,
+            named_vectors: None,
         }
     }
 }
@@ -119,6 +125,7 @@ impl Collection {
             storage_path,
             hnsw_config: config.hnsw_config.clone(),
             quantization: config.quantization.clone(),
+            named_vectors: config.named_vectors.clone(),
         };
 
         let db = VectorDB::new(db_options)?;
@@ -227,6 +234,7 @@ mod tests {
             hnsw_config: None,
             quantization: None,
             on_disk_payload: true,
+            named_vectors: None,
         };
         assert!(config.validate().is_err());
 
@@ -237,6 +245,7 @@ mod tests {
             hnsw_config: None,
             quantization: None,
             on_disk_payload: true,
+            named_vectors: None,
         };
         assert!(config.validate().is_err());
     }
@@ -28,6 +28,7 @@
 //!     hnsw_config: Some(HnswConfig::default()),
 //!     quantization: None,
 //!     on_disk_payload: true,
+//!     named_vectors: None,
 //! };
 //!
 //! manager.create_collection("documents", config)?;
@@ -3,7 +3,7 @@
 use dashmap::DashMap;
 use parking_lot::RwLock;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::collection::{Collection, CollectionConfig, CollectionStats};
@@ -42,7 +42,7 @@ impl CollectionManager {
     ///
     /// ```no_run
     /// use ruvector_collections::CollectionManager;
-    /// use std::path::PathBuf;
+    /// use std::path::{Path, PathBuf};
     ///
     /// let manager = CollectionManager::new(PathBuf::from("./collections")).unwrap();
     /// ```
@@ -190,6 +190,8 @@ impl CollectionManager {
     }
 
     /// Get statistics for a collection
+    ///
+    /// The disk size covers every file in the collection's directory.
     pub fn collection_stats(&self, name: &str) -> Result<CollectionStats> {
         let collection =
             self.get_collection(name)
@@ -198,7 +200,9 @@ impl CollectionManager {
                 })?;
 
         let guard = collection.read();
-        guard.stats()
+        let mut stats = guard.stats()?;
+        stats.disk_size_bytes = dir_size(&self.base_path.join(&guard.name))?;
+        Ok(stats)
     }
 
     // ===== Alias Management =====
@@ -475,6 +479,21 @@ impl CollectionManager {
     }
 }
 
+/// Total size of the files under `path`
+fn dir_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += if metadata.is_dir() {
+            dir_size(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(size)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -511,6 +530,7 @@ mod tests {
 
         // Get collection by alias
         assert!(manager.get_collection("test_alias").is_some());
+        assert!(manager.collection_stats("test_alias")?.disk_size_bytes > 0);
 
         // Cleanup
         manager.delete_alias("test_alias")?;
@@ -27,6 +27,7 @@ fn bench_batch_insert(c: &mut Criterion) {
                             .map(|i| VectorEntry {
                                 id: Some(format!("vec_{}", i)),
                                 vector: (0..128).map(|j| ((i + j) as f32) * 0.01).collect(),
+                                named_vectors: None,
                                 metadata: None,
                             })
                             .collect();
@@ -65,6 +66,7 @@ fn bench_individual_insert_vs_batch(c: &mut Criterion) {
                     .map(|i| VectorEntry {
                         id: Some(format!("vec_{}", i)),
                         vector: vec![i as f32; 64],
+                        named_vectors: None,
                         metadata: None,
                     })
                     .collect();
@@ -95,6 +97,7 @@ fn bench_individual_insert_vs_batch(c: &mut Criterion) {
                     .map(|i| VectorEntry {
                         id: Some(format!("vec_{}", i)),
                         vector: vec![i as f32; 64],
+                        named_vectors: None,
                         metadata: None,
                     })
                     .collect();
@@ -128,6 +131,7 @@ fn bench_parallel_searches(c: &mut Criterion) {
         .map(|i| VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..128).map(|j| ((i + j) as f32) * 0.01).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -142,6 +146,7 @@ fn bench_parallel_searches(c: &mut Criterion) {
                 let _ = db
                     .search(SearchQuery {
                         vector: black_box(query),
+                        using: None,
                         k: 10,
                         filter: None,
                         ef_search: None,
@@ -173,6 +178,7 @@ fn bench_batch_delete(c: &mut Criterion) {
                         .map(|i| VectorEntry {
                             id: Some(format!("vec_{}", i)),
                             vector: vec![i as f32; 32],
+                            named_vectors: None,
                             metadata: None,
                         })
                         .collect();
@@ -17,6 +17,7 @@ fn bench_hnsw_search(c: &mut Criterion) {
             .to_string(),
         hnsw_config: Some(HnswConfig::default()),
         quantization: None,
+        named_vectors: None,
     };
 
     let db = VectorDB::new(options).unwrap();
@@ -26,6 +27,7 @@ fn bench_hnsw_search(c: &mut Criterion) {
         .map(|i| VectorEntry {
             id: Some(format!("v{}", i)),
             vector: (0..128).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -40,6 +42,7 @@ fn bench_hnsw_search(c: &mut Criterion) {
             bench.iter(|| {
                 db.search(SearchQuery {
                     vector: black_box(query.clone()),
+                    using: None,
                     k: black_box(k),
                     filter: None,
                     ef_search: None,
@@ -43,6 +43,7 @@ fn bench_insert_single(c: &mut Criterion) {
                 distance_metric: DistanceMetric::Cosine,
                 hnsw_config: Some(HnswConfig::default()),
                 quantization: None,
+                named_vectors: None,
             };
             let db = VectorDB::new(options).unwrap();
             let mut idx = 0;
@@ -51,6 +52,7 @@ fn bench_insert_single(c: &mut Criterion) {
                 let entry = VectorEntry {
                     id: None,
                     vector: vectors[idx % vectors.len()].clone(),
+                    named_vectors: None,
                     metadata: None,
                 };
                 let _ = black_box(db.insert(entry));
@@ -81,6 +83,7 @@ fn bench_insert_batch(c: &mut Criterion) {
                         distance_metric: DistanceMetric::Cosine,
                         hnsw_config: Some(HnswConfig::default()),
                         quantization: None,
+                        named_vectors: None,
                     };
                     let db = VectorDB::new(options).unwrap();
 
@@ -89,6 +92,7 @@ fn bench_insert_batch(c: &mut Criterion) {
                         .map(|v| VectorEntry {
                             id: None,
                             vector: v.clone(),
+                            named_vectors: None,
                             metadata: None,
                         })
                         .collect();
@@ -116,8 +120,10 @@ fn bench_search(c: &mut Criterion) {
             ef_construction: 100,
             ef_search: 50,
             max_elements: 100000,
+            ..Default::default()
         }),
         quantization: None,
+        named_vectors: None,
     };
     let db = VectorDB::new(options).unwrap();
 
@@ -128,6 +134,7 @@ fn bench_search(c: &mut Criterion) {
         .map(|v| VectorEntry {
             id: None,
             vector: v.clone(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -144,6 +151,7 @@ fn bench_search(c: &mut Criterion) {
                 let query = &queries[query_idx % queries.len()];
                 let search_query = SearchQuery {
                     vector: query.clone(),
+                    using: None,
                     k,
                     filter: None,
                     ef_search: None,
@@ -1,142 +1,52 @@
 //! Filtered Search with Automatic Strategy Selection
 //!
-//! Supports two filtering strategies:
-//! - Pre-filtering: Apply metadata filters before graph traversal
+//! Supports three filtering strategies:
+//! - Pre-filtering: Resolve matching ids first, then search only among them
+//! - In-graph filtering: Skip non-matching nodes while traversing the graph
 //! - Post-filtering: Traverse graph then apply filters
 //! - Automatic strategy selection based on filter selectivity
+//!
+//! Filters are [`ruvector_filter::FilterExpression`]s, the same type used by
+//! `SearchQuery` and the payload indexes.
 
-use crate::error::{Result, RuvectorError};
+use crate::error::Result;
 use crate::types::{SearchResult, VectorId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub use ruvector_filter::FilterExpression;
+
+/// Selectivity below which a filter is resolved up front and searched exhaustively
+pub const PRE_FILTER_SELECTIVITY: f32 = 0.01;
+
+/// Selectivity above which the filter is applied to over-fetched results
+pub const POST_FILTER_SELECTIVITY: f32 = 0.5;
+
 /// Filter strategy selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FilterStrategy {
     /// Apply filters before search (efficient for highly selective filters)
     PreFilter,
+    /// Check filters while traversing the graph (efficient for moderate selectivity)
+    InGraph,
     /// Apply filters after search (efficient for low selectivity)
     PostFilter,
     /// Automatically select strategy based on estimated selectivity
     Auto,
 }
 
-/// Filter expression for metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum FilterExpression {
-    /// Equality check: field == value
-    Eq(String, serde_json::Value),
-    /// Not equal: field != value
-    Ne(String, serde_json::Value),
-    /// Greater than: field > value
-    Gt(String, serde_json::Value),
-    /// Greater than or equal: field >= value
-    Gte(String, serde_json::Value),
-    /// Less than: field < value
-    Lt(String, serde_json::Value),
-    /// Less than or equal: field <= value
-    Lte(String, serde_json::Value),
-    /// In list: field in [values]
-    In(String, Vec<serde_json::Value>),
-    /// Not in list: field not in [values]
-    NotIn(String, Vec<serde_json::Value>),
-    /// Range check: min <= field <= max
-    Range(String, serde_json::Value, serde_json::Value),
-    /// Logical AND
-    And(Vec<FilterExpression>),
-    /// Logical OR
-    Or(Vec<FilterExpression>),
-    /// Logical NOT
-    Not(Box<FilterExpression>),
-}
-
-impl FilterExpression {
-    /// Evaluate filter against metadata
-    pub fn evaluate(&self, metadata: &HashMap<String, serde_json::Value>) -> bool {
-        match self {
-            FilterExpression::Eq(field, value) => metadata.get(field) == Some(value),
-            FilterExpression::Ne(field, value) => metadata.get(field) != Some(value),
-            FilterExpression::Gt(field, value) => {
-                if let Some(field_value) = metadata.get(field) {
-                    compare_values(field_value, value) > 0
-                } else {
-                    false
-                }
-            }
-            FilterExpression::Gte(field, value) => {
-                if let Some(field_value) = metadata.get(field) {
-                    compare_values(field_value, value) >= 0
-                } else {
-                    false
-                }
-            }
-            FilterExpression::Lt(field, value) => {
-                if let Some(field_value) = metadata.get(field) {
-                    compare_values(field_value, value) < 0
-                } else {
-                    false
-                }
-            }
-            FilterExpression::Lte(field, value) => {
-                if let Some(field_value) = metadata.get(field) {
-                    compare_values(field_value, value) <= 0
-                } else {
-                    false
-                }
-            }
-            FilterExpression::In(field, values) => {
-                if let Some(field_value) = metadata.get(field) {
-                    values.contains(field_value)
-                } else {
-                    false
-                }
-            }
-            FilterExpression::NotIn(field, values) => {
-                if let Some(field_value) = metadata.get(field) {
-                    !values.contains(field_value)
-                } else {
-                    true
-                }
-            }
-            FilterExpression::Range(field, min, max) => {
-                if let Some(field_value) = metadata.get(field) {
-                    compare_values(field_value, min) >= 0 && compare_values(field_value, max) <= 0
-                } else {
-                    false
-                }
-            }
-            FilterExpression::And(exprs) => exprs.iter().all(|e| e.evaluate(metadata)),
-            FilterExpression::Or(exprs) => exprs.iter().any(|e| e.evaluate(metadata)),
-            FilterExpression::Not(expr) => !expr.evaluate(metadata),
-        }
-    }
-
-    /// Estimate selectivity of filter (0.0 = very selective, 1.0 = not selective)
-    pub fn estimate_selectivity(&self, total_vectors: usize) -> f32 {
-        match self {
-            FilterExpression::Eq(_, _) => 0.1, // Equality is typically selective
-            FilterExpression::Ne(_, _) => 0.9, // Not equal is less selective
-            FilterExpression::In(_, values) => (values.len() as f32) / 100.0,
-            FilterExpression::NotIn(_, values) => 1.0 - (values.len() as f32) / 100.0,
-            FilterExpression::Range(_, _, _) => 0.3, // Ranges are moderately selective
-            FilterExpression::Gt(_, _) | FilterExpression::Gte(_, _) => 0.5,
-            FilterExpression::Lt(_, _) | FilterExpression::Lte(_, _) => 0.5,
-            FilterExpression::And(exprs) => {
-                // AND is more selective (multiply selectivities)
-                exprs
-                    .iter()
-                    .map(|e| e.estimate_selectivity(total_vectors))
-                    .product()
-            }
-            FilterExpression::Or(exprs) => {
-                // OR is less selective (sum selectivities, capped at 1.0)
-                exprs
-                    .iter()
-                    .map(|e| e.estimate_selectivity(total_vectors))
-                    .sum::<f32>()
-                    .min(1.0)
-            }
-            FilterExpression::Not(expr) => 1.0 - expr.estimate_selectivity(total_vectors),
+impl FilterStrategy {
+    /// Pick a strategy for a filter matching roughly `selectivity` of the vectors
+    ///
+    /// `indexed` tells whether the filter can be resolved to an allow-set from
+    /// payload indexes; without one, only post-filtering is possible.
+    pub fn for_selectivity(selectivity: f32, indexed: bool) -> Self {
+        if !indexed || selectivity >= POST_FILTER_SELECTIVITY {
+            FilterStrategy::PostFilter
+        } else if selectivity < PRE_FILTER_SELECTIVITY {
+            FilterStrategy::PreFilter
+        } else {
+            FilterStrategy::InGraph
         }
     }
 }
@@ -183,7 +93,7 @@ impl FilteredSearch {
     pub fn get_filtered_ids(&self) -> Vec<VectorId> {
         self.metadata_store
             .iter()
-            .filter(|(_, metadata)| self.filter.evaluate(metadata))
+            .filter(|(_, metadata)| self.filter.matches_metadata(metadata))
             .map(|(id, _)| id.clone())
             .collect()
     }
@@ -194,7 +104,7 @@ impl FilteredSearch {
             .into_iter()
             .filter(|result| {
                 if let Some(metadata) = result.metadata.as_ref() {
-                    self.filter.evaluate(metadata)
+                    self.filter.matches_metadata(metadata)
                 } else {
                     false
                 }
@@ -213,7 +123,7 @@ impl FilteredSearch {
         };
 
         match strategy {
-            FilterStrategy::PreFilter => {
+            FilterStrategy::PreFilter | FilterStrategy::InGraph => {
                 // Get filtered IDs first
                 let filtered_ids = self.get_filtered_ids();
 
@@ -243,28 +153,6 @@ impl FilteredSearch {
     }
 }
 
-// Helper function to compare JSON values
-fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> i32 {
-    use serde_json::Value;
-
-    match (a, b) {
-        (Value::Number(a), Value::Number(b)) => {
-            let a_f64 = a.as_f64().unwrap_or(0.0);
-            let b_f64 = b.as_f64().unwrap_or(0.0);
-            if a_f64 < b_f64 {
-                -1
-            } else if a_f64 > b_f64 {
-                1
-            } else {
-                0
-            }
-        }
-        (Value::String(a), Value::String(b)) => a.cmp(b) as i32,
-        (Value::Bool(a), Value::Bool(b)) => a.cmp(b) as i32,
-        _ => 0,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,11 +163,11 @@ mod tests {
         let mut metadata = HashMap::new();
         metadata.insert("category".to_string(), json!("electronics"));
 
-        let filter = FilterExpression::Eq("category".to_string(), json!("electronics"));
-        assert!(filter.evaluate(&metadata));
+        let filter = FilterExpression::eq("category", json!("electronics"));
+        assert!(filter.matches_metadata(&metadata));
 
-        let filter = FilterExpression::Eq("category".to_string(), json!("books"));
-        assert!(!filter.evaluate(&metadata));
+        let filter = FilterExpression::eq("category", json!("books"));
+        assert!(!filter.matches_metadata(&metadata));
     }
 
     #[test]
@@ -287,11 +175,11 @@ mod tests {
         let mut metadata = HashMap::new();
         metadata.insert("price".to_string(), json!(50.0));
 
-        let filter = FilterExpression::Range("price".to_string(), json!(10.0), json!(100.0));
-        assert!(filter.evaluate(&metadata));
+        let filter = FilterExpression::range("price", Some(json!(10.0)), Some(json!(100.0)));
+        assert!(filter.matches_metadata(&metadata));
 
-        let filter = FilterExpression::Range("price".to_string(), json!(60.0), json!(100.0));
-        assert!(!filter.evaluate(&metadata));
+        let filter = FilterExpression::range("price", Some(json!(60.0)), Some(json!(100.0)));
+        assert!(!filter.matches_metadata(&metadata));
     }
 
     #[test]
@@ -300,11 +188,11 @@ mod tests {
         metadata.insert("category".to_string(), json!("electronics"));
         metadata.insert("price".to_string(), json!(50.0));
 
-        let filter = FilterExpression::And(vec![
-            FilterExpression::Eq("category".to_string(), json!("electronics")),
-            FilterExpression::Lt("price".to_string(), json!(100.0)),
+        let filter = FilterExpression::and(vec![
+            FilterExpression::eq("category", json!("electronics")),
+            FilterExpression::lt("price", json!(100.0)),
         ]);
-        assert!(filter.evaluate(&metadata));
+        assert!(filter.matches_metadata(&metadata));
     }
 
     #[test]
@@ -312,11 +200,11 @@ mod tests {
         let mut metadata = HashMap::new();
         metadata.insert("category".to_string(), json!("electronics"));
 
-        let filter = FilterExpression::Or(vec![
-            FilterExpression::Eq("category".to_string(), json!("books")),
-            FilterExpression::Eq("category".to_string(), json!("electronics")),
+        let filter = FilterExpression::or(vec![
+            FilterExpression::eq("category", json!("books")),
+            FilterExpression::eq("category", json!("electronics")),
         ]);
-        assert!(filter.evaluate(&metadata));
+        assert!(filter.matches_metadata(&metadata));
     }
 
     #[test]
@@ -324,22 +212,43 @@ mod tests {
         let mut metadata = HashMap::new();
         metadata.insert("tag".to_string(), json!("popular"));
 
-        let filter = FilterExpression::In(
-            "tag".to_string(),
+        let filter = FilterExpression::in_values(
+            "tag",
             vec![json!("popular"), json!("trending"), json!("new")],
         );
-        assert!(filter.evaluate(&metadata));
+        assert!(filter.matches_metadata(&metadata));
     }
 
     #[test]
     fn test_selectivity_estimation() {
-        let filter_eq = FilterExpression::Eq("field".to_string(), json!("value"));
+        let filter_eq = FilterExpression::eq("field", json!("value"));
         assert!(filter_eq.estimate_selectivity(1000) < 0.5);
 
-        let filter_ne = FilterExpression::Ne("field".to_string(), json!("value"));
+        let filter_ne = FilterExpression::ne("field", json!("value"));
         assert!(filter_ne.estimate_selectivity(1000) > 0.5);
     }
 
+    #[test]
+    fn test_strategy_for_selectivity() {
+        assert_eq!(
+            FilterStrategy::for_selectivity(0.001, true),
+            FilterStrategy::PreFilter
+        );
+        assert_eq!(
+            FilterStrategy::for_selectivity(0.1, true),
+            FilterStrategy::InGraph
+        );
+        assert_eq!(
+            FilterStrategy::for_selectivity(0.9, true),
+            FilterStrategy::PostFilter
+        );
+        // Without payload indexes there is no allow-set to search within
+        assert_eq!(
+            FilterStrategy::for_selectivity(0.001, false),
+            FilterStrategy::PostFilter
+        );
+    }
+
     #[test]
     fn test_auto_strategy_selection() {
         let mut metadata_store = HashMap::new();
@@ -350,12 +259,12 @@ mod tests {
         }
 
         // Highly selective filter should choose pre-filter
-        let filter = FilterExpression::Eq("id".to_string(), json!(42));
+        let filter = FilterExpression::eq("id", json!(42));
         let search = FilteredSearch::new(filter, FilterStrategy::Auto, metadata_store.clone());
         assert_eq!(search.auto_select_strategy(), FilterStrategy::PreFilter);
 
         // Less selective filter should choose post-filter
-        let filter = FilterExpression::Gte("id".to_string(), json!(0));
+        let filter = FilterExpression::gte("id", json!(0));
         let search = FilteredSearch::new(filter, FilterStrategy::Auto, metadata_store);
         assert_eq!(search.auto_select_strategy(), FilterStrategy::PostFilter);
     }
@@ -296,6 +296,7 @@ impl AgenticDB {
         self.vector_db.insert(VectorEntry {
             id: Some(format!("reflexion_{}", id)),
             vector: embedding,
+            named_vectors: None,
             metadata: Some({
                 let mut meta = HashMap::new();
                 meta.insert("type".to_string(), serde_json::json!("reflexion"));
@@ -319,12 +320,9 @@ impl AgenticDB {
         // Search in vector DB
         let results = self.vector_db.search(SearchQuery {
             vector: query_embedding,
+            using: None,
             k,
-            filter: Some({
-                let mut filter = HashMap::new();
-                filter.insert("type".to_string(), serde_json::json!("reflexion"));
-                filter
-            }),
+            filter: Some(FilterExpression::eq("type", serde_json::json!("reflexion"))),
             ef_search: None,
         })?;
 
@@ -392,6 +390,7 @@ impl AgenticDB {
         self.vector_db.insert(VectorEntry {
             id: Some(format!("skill_{}", id)),
             vector: embedding,
+            named_vectors: None,
             metadata: Some({
                 let mut meta = HashMap::new();
                 meta.insert("type".to_string(), serde_json::json!("skill"));
@@ -409,12 +408,9 @@ impl AgenticDB {
 
         let results = self.vector_db.search(SearchQuery {
             vector: query_embedding,
+            using: None,
             k,
-            filter: Some({
-                let mut filter = HashMap::new();
-                filter.insert("type".to_string(), serde_json::json!("skill"));
-                filter
-            }),
+            filter: Some(FilterExpression::eq("type", serde_json::json!("skill"))),
             ef_search: None,
         })?;
 
@@ -504,6 +500,7 @@ impl AgenticDB {
         self.vector_db.insert(VectorEntry {
             id: Some(format!("causal_{}", id)),
             vector: embedding,
+            named_vectors: None,
             metadata: Some({
                 let mut meta = HashMap::new();
                 meta.insert("type".to_string(), serde_json::json!("causal"));
@@ -531,12 +528,9 @@ impl AgenticDB {
         // Get all causal edges
         let results = self.vector_db.search(SearchQuery {
             vector: query_embedding,
+            using: None,
             k: k * 2, // Get more results for utility ranking
-            filter: Some({
-                let mut filter = HashMap::new();
-                filter.insert("type".to_string(), serde_json::json!("causal"));
-                filter
-            }),
+            filter: Some(FilterExpression::eq("type", serde_json::json!("causal"))),
             ef_search: None,
         })?;
 
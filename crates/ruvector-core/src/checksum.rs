@@ -0,0 +1,19 @@
+//! 64-bit FNV-1a checksums, used to detect torn or corrupted records in
+//! the write-ahead log and the HNSW graph files
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// Checksum of `bytes`
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    extend(OFFSET_BASIS, bytes)
+}
+
+/// Continue the checksum `sum` over `bytes`
+///
+/// `extend(checksum(a), b)` equals the checksum of `a` followed by `b`.
+pub(crate) fn extend(sum: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(sum, |hash, &b| (hash ^ b as u64).wrapping_mul(PRIME))
+}
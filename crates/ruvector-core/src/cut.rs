@@ -0,0 +1,353 @@
+//! Consistent cuts of a [`VectorDB`] and databases rebuilt from them
+//!
+//! [`VectorDB::consistent_cut`] pins a storage read transaction while it
+//! holds the write-ahead log, so the entries of a cut reflect exactly the
+//! operations up to one sequence number. They can then be read at leisure
+//! while the database keeps accepting writes. The HNSW graphs are read from
+//! the live indexes in parts of [`GRAPH_PART_SLOTS`] slots, one brief read
+//! lock at a time, so they can also reflect later operations; the ids those
+//! touched are named by [`VectorDB::operations_since`] from the cut's
+//! sequence number.
+//!
+//! [`CutWriter`] goes the other way: it writes entries straight to the
+//! storage of a new database and the graph parts to its graph directories,
+//! so opening the database finds the graphs in sync with storage instead of
+//! rebuilding them.
+
+use crate::error::{Result, RuvectorError};
+use crate::index::VectorIndex;
+use crate::storage::{StorageView, VectorStorage};
+use crate::types::{DbOptions, GraphPart, VectorEntry, VectorId};
+use crate::vector_db::VectorDB;
+use crate::wal::{wal_path_for, WalOperation, WriteAheadLog};
+use parking_lot::RwLock;
+use ruvector_filter::IndexType;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[cfg(feature = "hnsw")]
+use crate::index::hnsw::HnswIndex;
+#[cfg(feature = "hnsw")]
+use crate::vector_db::{entry_vector, graph_dir};
+
+/// Graph slots per [`GraphPart`] read from a cut
+pub const GRAPH_PART_SLOTS: usize = 1024;
+
+/// An index of a database, shared with the cut
+type SharedIndex = Arc<RwLock<Box<dyn VectorIndex>>>;
+
+/// The contents of a database as of one sequence number
+pub struct ConsistentCut {
+    /// Sequence number of the last operation included
+    pub sequence: u64,
+    /// Configuration of the database
+    pub options: DbOptions,
+    /// Payload index definitions
+    pub payload_indexes: Vec<(String, IndexType)>,
+    pub(crate) view: StorageView,
+    /// Live index of each vector space, by space name (`None` for the
+    /// default space)
+    pub(crate) indexes: Vec<(Option<String>, SharedIndex)>,
+}
+
+impl ConsistentCut {
+    /// Ids of every entry in the cut
+    pub fn ids(&self) -> Result<Vec<VectorId>> {
+        self.view.all_ids()
+    }
+
+    /// Entry `id` as of the cut
+    pub fn get(&self, id: &str) -> Result<Option<VectorEntry>> {
+        self.view.get(id)
+    }
+
+    /// Vector spaces of the database, `None` for the default space
+    pub fn spaces(&self) -> Vec<Option<String>> {
+        self.indexes
+            .iter()
+            .map(|(space, _)| space.clone())
+            .collect()
+    }
+
+    /// Part of the HNSW graph of `space` from slot `start`, as it is now
+    ///
+    /// Returns `None` past the last slot, or if the space has no graph.
+    /// Parts may reflect operations after [`sequence`](Self::sequence).
+    pub fn graph_part(&self, space: Option<&str>, start: u32) -> Result<Option<GraphPart>> {
+        let Some((_, index)) = self.indexes.iter().find(|(s, _)| s.as_deref() == space) else {
+            return Ok(None);
+        };
+        index.read().export_graph_part(start, GRAPH_PART_SLOTS)
+    }
+}
+
+/// Builds a new database from the contents of a cut
+///
+/// Entries must be written with [`insert`](Self::insert) before the graph
+/// parts that hold them. Entries that the graphs may hold in another
+/// version, because operations passed to [`apply`](Self::apply) or named to
+/// [`mark_changed`](Self::mark_changed) changed them, are indexed again from
+/// storage before the graphs are persisted.
+pub struct CutWriter {
+    options: DbOptions,
+    storage: VectorStorage,
+    #[cfg(feature = "hnsw")]
+    graphs: Vec<(Option<String>, HnswIndex)>,
+    stale: HashSet<VectorId>,
+}
+
+impl CutWriter {
+    /// Start a database at `options.storage_path`, which must not hold one yet
+    pub fn new(options: DbOptions) -> Result<Self> {
+        let storage = VectorStorage::new(&options.storage_path, options.dimensions)?;
+        if storage.load_config()?.is_some() {
+            return Err(RuvectorError::InvalidPath(format!(
+                "{} already holds a database",
+                options.storage_path
+            )));
+        }
+        storage.save_config(&options)?;
+
+        Ok(Self {
+            options,
+            storage,
+            #[cfg(feature = "hnsw")]
+            graphs: Vec::new(),
+            stale: HashSet::new(),
+        })
+    }
+
+    /// Add the next part of the graph of the vector space `space` (`None`
+    /// for the default space)
+    ///
+    /// Parts of a space must come in slot order. Their vectors are read from
+    /// the entries written so far; slots whose id was not written are
+    /// dropped from the graph.
+    pub fn add_graph_part(&mut self, space: Option<&str>, part: GraphPart) -> Result<()> {
+        #[cfg(feature = "hnsw")]
+        {
+            let vectors = part
+                .ids
+                .iter()
+                .map(|id| match id {
+                    Some(id) => Ok(self
+                        .storage
+                        .get(id)?
+                        .and_then(|entry| entry_vector(entry, space))),
+                    None => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            let position = self.graphs.iter().position(|(s, _)| s.as_deref() == space);
+            let index = match position {
+                Some(position) => &mut self.graphs[position].1,
+                None => {
+                    let index = self.create_graph(space)?;
+                    self.graphs.push((space.map(str::to_string), index));
+                    &mut self.graphs.last_mut().expect("graph just added").1
+                }
+            };
+            index.import_part(part, vectors)?;
+        }
+        #[cfg(not(feature = "hnsw"))]
+        let _ = (space, part);
+        Ok(())
+    }
+
+    /// Create the persisted graph of `space` that parts are imported into
+    #[cfg(feature = "hnsw")]
+    fn create_graph(&self, space: Option<&str>) -> Result<HnswIndex> {
+        let config = match space {
+            None => Some(self.options.default_vector_space()),
+            Some(name) => self
+                .options
+                .named_vectors
+                .as_ref()
+                .and_then(|spaces| spaces.get(name))
+                .cloned(),
+        };
+        let hnsw_config = config
+            .as_ref()
+            .filter(|config| !config.multivector)
+            .and_then(|config| config.hnsw_config.clone());
+        let (Some(config), Some(hnsw_config)) = (config, hnsw_config) else {
+            return Err(RuvectorError::InvalidInput(format!(
+                "Vector space {:?} has no HNSW graph",
+                space
+            )));
+        };
+
+        let dir = graph_dir(&self.options.storage_path, space);
+        let index =
+            HnswIndex::create(&dir, config.dimensions, config.distance_metric, hnsw_config)?;
+        if !index.is_persistent() {
+            return Err(RuvectorError::InvalidPath(format!(
+                "HNSW graph directory {:?} is in use",
+                dir
+            )));
+        }
+        Ok(index)
+    }
+
+    /// Write entries
+    pub fn insert(&mut self, entries: &[VectorEntry]) -> Result<()> {
+        self.storage.insert_batch(entries)?;
+        Ok(())
+    }
+
+    /// Apply an upsert or delete on top of what was written so far
+    pub fn apply(&mut self, operation: &WalOperation) -> Result<()> {
+        match operation {
+            WalOperation::Upsert(entries) => {
+                self.storage.insert_batch(entries)?;
+                self.stale
+                    .extend(entries.iter().filter_map(|entry| entry.id.clone()));
+            }
+            WalOperation::Delete(ids) => {
+                for id in ids {
+                    self.storage.delete(id)?;
+                }
+                self.stale.extend(ids.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Index `ids` again from storage once the graphs are complete, as the
+    /// graph parts may hold other versions of them
+    pub fn mark_changed(&mut self, ids: impl IntoIterator<Item = VectorId>) {
+        self.stale.extend(ids);
+    }
+
+    /// Drop the graphs added so far, leaving them to be rebuilt from storage
+    /// when the database is opened
+    pub fn discard_graphs(&mut self) -> Result<()> {
+        #[cfg(feature = "hnsw")]
+        for (space, index) in self.graphs.drain(..) {
+            drop(index);
+            std::fs::remove_dir_all(graph_dir(&self.options.storage_path, space.as_deref()))?;
+        }
+        Ok(())
+    }
+
+    /// Persist the graphs and open the database
+    ///
+    /// The write-ahead log of the new database continues numbering after
+    /// `sequence`, the sequence number of the cut that was written.
+    pub fn finish(
+        self,
+        sequence: u64,
+        payload_indexes: &[(String, IndexType)],
+    ) -> Result<VectorDB> {
+        #[cfg(feature = "hnsw")]
+        for (space, mut index) in self.graphs {
+            index.finish_import()?;
+            for id in &self.stale {
+                let vector = self
+                    .storage
+                    .get(id)?
+                    .and_then(|entry| entry_vector(entry, space.as_deref()));
+                match vector {
+                    Some(vector) => index.add(id.clone(), vector)?,
+                    None => {
+                        index.remove(id)?;
+                    }
+                }
+            }
+            // Also picks up entries that no part held, and checkpoints
+            VectorDB::reconcile_index(&mut index, &self.storage, space.as_deref())?;
+        }
+
+        self.storage.save_payload_indexes(payload_indexes)?;
+        let (mut wal, _) = WriteAheadLog::open(&wal_path_for(&self.options.storage_path))?;
+        wal.restart_at(sequence)?;
+        drop(wal);
+
+        VectorDB::new(self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{HnswConfig, SearchQuery};
+    use tempfile::tempdir;
+
+    fn entry(id: &str, vector: Vec<f32>) -> VectorEntry {
+        VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        }
+    }
+
+    fn options(path: &std::path::Path) -> DbOptions {
+        DbOptions {
+            dimensions: 3,
+            storage_path: path.to_string_lossy().to_string(),
+            hnsw_config: Some(HnswConfig::default()),
+            ..DbOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_cut_and_rebuild() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let db = VectorDB::new(options(&dir.path().join("source.db")))?;
+        db.insert(entry("a", vec![1.0, 0.0, 0.0]))?;
+        db.insert(entry("b", vec![0.0, 1.0, 0.0]))?;
+
+        let cut = db.consistent_cut()?;
+        // Writes after the cut are not part of its entries, but the graph
+        // is read afterwards and holds them
+        db.insert(entry("c", vec![0.0, 0.0, 1.0]))?;
+        db.delete("a")?;
+        assert_eq!(cut.sequence, 2);
+        assert_eq!(cut.spaces(), [None]);
+        let mut ids = cut.ids()?;
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+
+        let mut target = cut.options.clone();
+        target.storage_path = dir.path().join("restored.db").to_string_lossy().to_string();
+        let mut writer = CutWriter::new(target.clone())?;
+        let entries = ids
+            .iter()
+            .map(|id| Ok(cut.get(id)?.unwrap()))
+            .collect::<Result<Vec<_>>>()?;
+        writer.insert(&entries)?;
+        let mut start = 0;
+        while let Some(part) = cut.graph_part(None, start)? {
+            start = part.end();
+            writer.add_graph_part(None, part)?;
+        }
+        assert_eq!(start, 3);
+        let changed = db.operations_since(cut.sequence)?.unwrap();
+        writer.mark_changed(
+            changed
+                .into_iter()
+                .flat_map(|record| record.operation.into_ids()),
+        );
+        // Move "b" after the cut
+        writer.apply(&WalOperation::Upsert(vec![entry("b", vec![0.0, 0.0, 1.0])]))?;
+        let restored = writer.finish(cut.sequence, &cut.payload_indexes)?;
+
+        assert_eq!(restored.sequence(), 2);
+        assert_eq!(restored.len()?, 2);
+        let results = restored.search(SearchQuery {
+            vector: vec![0.0, 0.0, 1.0],
+            using: None,
+            k: 3,
+            filter: None,
+            ef_search: None,
+        })?;
+        let found: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(found, ["b", "a"]);
+        assert!(results[0].score < 1e-6);
+
+        // The target now holds a database
+        assert!(CutWriter::new(target).is_err());
+        Ok(())
+    }
+}
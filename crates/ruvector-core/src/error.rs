@@ -70,6 +70,12 @@ pub enum RuvectorError {
     Internal(String),
 }
 
+impl From<ruvector_filter::FilterError> for RuvectorError {
+    fn from(err: ruvector_filter::FilterError) -> Self {
+        RuvectorError::InvalidInput(err.to_string())
+    }
+}
+
 #[cfg(feature = "storage")]
 impl From<redb::Error> for RuvectorError {
     fn from(err: redb::Error) -> Self {
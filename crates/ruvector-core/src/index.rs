@@ -1,11 +1,15 @@
 //! Index structures for efficient vector search
 
+mod codes;
 pub mod flat;
 #[cfg(feature = "hnsw")]
 pub mod hnsw;
+pub mod multivector;
+mod vectors;
 
 use crate::error::Result;
-use crate::types::{DistanceMetric, SearchResult, VectorId};
+use crate::types::{GraphPart, IndexStats, SearchParams, SearchResult, VectorId};
+use std::collections::HashSet;
 
 /// Trait for vector index implementations
 pub trait VectorIndex: Send + Sync {
@@ -23,6 +27,52 @@ pub trait VectorIndex: Send + Sync {
     /// Search for k nearest neighbors
     fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>>;
 
+    /// Search for k nearest neighbors with per-query tuning
+    ///
+    /// Indexes should honor the knobs that apply to them; the default only
+    /// applies the score threshold.
+    fn search_with_params(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        let mut results = self.search(query, k)?;
+        params.apply_threshold(&mut results);
+        Ok(results)
+    }
+
+    /// Search for k nearest neighbors among the ids accepted by `filter`
+    ///
+    /// Indexes that can check the filter while searching should override
+    /// this; the default over-fetches from
+    /// [`search_with_params`](Self::search_with_params) until k accepted
+    /// results are found or the whole index has been returned.
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        search_overfetched(self, query, k, params, k.saturating_mul(2), filter)
+    }
+
+    /// Exact k nearest neighbors among `ids`
+    ///
+    /// Used when a filter leaves so few candidates that scanning them beats
+    /// traversing the index.
+    fn search_among(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        ids: &[VectorId],
+    ) -> Result<Vec<SearchResult>> {
+        let ids: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        self.search_filtered(query, k, params, &|id| ids.contains(id))
+    }
+
     /// Remove a vector from the index
     fn remove(&mut self, id: &VectorId) -> Result<bool>;
 
@@ -33,4 +83,102 @@ pub trait VectorIndex: Send + Sync {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Persist the current index state, if the index is backed by disk
+    fn checkpoint(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Read up to `max_slots` slots of the search graph from slot `start`,
+    /// for a snapshot
+    ///
+    /// Returns `None` past the last slot, or if the index has no graph that
+    /// is worth restoring instead of rebuilding.
+    fn export_graph_part(&self, start: u32, max_slots: usize) -> Result<Option<GraphPart>> {
+        let _ = (start, max_slots);
+        Ok(None)
+    }
+
+    /// Size and health figures for monitoring
+    fn stats(&self) -> IndexStats {
+        IndexStats {
+            vectors: self.len(),
+            ..Default::default()
+        }
+    }
+
+    /// Estimate the recall@k of default searches against an exact scan
+    ///
+    /// Searches for up to `samples` stored vectors, leaving each query's own
+    /// point out of both result sets. Returns `None` if the index cannot
+    /// tell, or holds too few vectors.
+    fn estimate_recall(&self, samples: usize, k: usize) -> Result<Option<f32>> {
+        let _ = (samples, k);
+        Ok(None)
+    }
+}
+
+/// Post-filter `index.search_with_params` results, starting with `fetch`
+/// candidates and doubling until k results pass `filter` or the whole index
+/// was returned
+pub(crate) fn search_overfetched<I: VectorIndex + ?Sized>(
+    index: &I,
+    query: &[f32],
+    k: usize,
+    params: &SearchParams,
+    fetch: usize,
+    filter: &dyn Fn(&str) -> bool,
+) -> Result<Vec<SearchResult>> {
+    let total = index.len();
+    let mut fetch = fetch.max(k).min(total);
+    loop {
+        let accepted: Vec<_> = index
+            .search_with_params(query, fetch, params)?
+            .into_iter()
+            .filter(|r| filter(&r.id))
+            .take(k)
+            .collect();
+        if accepted.len() >= k || fetch >= total {
+            return Ok(accepted);
+        }
+        fetch = fetch.saturating_mul(2).min(total);
+    }
+}
+
+/// Recall@k of default searches for `queries` against exact ones
+///
+/// Each query is a stored vector; its own id is left out of both result
+/// sets. Returns `None` if no query has any other neighbor.
+pub(crate) fn sampled_recall<I: VectorIndex + ?Sized>(
+    index: &I,
+    queries: &[(VectorId, Vec<f32>)],
+    k: usize,
+) -> Result<Option<f32>> {
+    if index.len() < 2 || k == 0 {
+        return Ok(None);
+    }
+    let exact = SearchParams {
+        exact: true,
+        ..Default::default()
+    };
+    let (mut found, mut expected) = (0, 0);
+    for (id, query) in queries {
+        let neighbors = |params: &SearchParams| -> Result<Vec<VectorId>> {
+            Ok(index
+                .search_with_params(query, k + 1, params)?
+                .into_iter()
+                .map(|result| result.id)
+                .filter(|neighbor| neighbor != id)
+                .take(k)
+                .collect())
+        };
+        let truth: HashSet<VectorId> = neighbors(&exact)?.into_iter().collect();
+        found += neighbors(&SearchParams::default())?
+            .iter()
+            .filter(|neighbor| truth.contains(*neighbor))
+            .count();
+        expected += truth.len();
+    }
+
+    Ok((expected > 0).then(|| found as f32 / expected as f32))
 }
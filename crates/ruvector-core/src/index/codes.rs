@@ -0,0 +1,139 @@
+//! Quantized codes used to traverse the HNSW graph and scan flat indexes
+//!
+//! Codes live in RAM in one slot-major buffer next to the full-precision
+//! [`VectorStore`], which keeps the f32 vectors in a file whenever codes are
+//! enabled. Query traversal only touches the codes; the f32 vectors are read
+//! back when candidates are rescored, during exact scans and while linking
+//! the graph.
+//!
+//! Codes are not persisted. Opening an index with quantization enabled
+//! re-encodes every stored vector, one O(n·d) pass over the vector file.
+
+use super::vectors::VectorStore;
+use crate::quantization::{
+    binary_asymmetric_distance, scalar_asymmetric_distance, BinaryQuantized, QuantizedVector,
+    ScalarQuantized,
+};
+use crate::types::{DistanceMetric, QuantizationConfig};
+
+/// Slot-indexed quantized vectors
+pub(crate) enum CodeStore {
+    /// One int8 code per dimension plus a `(min, scale)` range per vector
+    Scalar {
+        dimensions: usize,
+        codes: Vec<u8>,
+        ranges: Vec<(f32, f32)>,
+    },
+    /// One sign bit per dimension
+    Binary { bytes: usize, bits: Vec<u8> },
+}
+
+impl CodeStore {
+    /// Create an empty store for `quantization`
+    ///
+    /// Returns `None` for `QuantizationConfig::None`. `Product` falls back to
+    /// scalar codes, since its codebooks need training data the indexes do
+    /// not have up front; databases configured with it keep opening.
+    pub fn new(quantization: &QuantizationConfig, dimensions: usize) -> Option<Self> {
+        match quantization {
+            QuantizationConfig::Scalar => Some(CodeStore::Scalar {
+                dimensions,
+                codes: Vec::new(),
+                ranges: Vec::new(),
+            }),
+            QuantizationConfig::Binary => Some(CodeStore::Binary {
+                bytes: dimensions.div_ceil(8),
+                bits: Vec::new(),
+            }),
+            QuantizationConfig::None => None,
+            QuantizationConfig::Product { .. } => {
+                tracing::warn!(
+                    "Product quantization is not supported by the vector indexes, using scalar"
+                );
+                Self::new(&QuantizationConfig::Scalar, dimensions)
+            }
+        }
+    }
+
+    /// Encode every slot of `vectors`
+    pub fn from_vectors(quantization: &QuantizationConfig, vectors: &VectorStore) -> Option<Self> {
+        let mut store = Self::new(quantization, vectors.dimensions())?;
+        for slot in 0..vectors.len() {
+            store.push(vectors.get(slot as u32));
+        }
+        Some(store)
+    }
+
+    /// Append the codes for a new slot
+    pub fn push(&mut self, vector: &[f32]) {
+        match self {
+            CodeStore::Scalar { codes, ranges, .. } => {
+                let quantized = ScalarQuantized::quantize(vector);
+                codes.extend_from_slice(&quantized.data);
+                ranges.push((quantized.min, quantized.scale));
+            }
+            CodeStore::Binary { bits, .. } => {
+                bits.extend_from_slice(&BinaryQuantized::quantize(vector).bits);
+            }
+        }
+    }
+
+    /// Re-encode an existing `slot`
+    pub fn set(&mut self, slot: u32, vector: &[f32]) {
+        let slot = slot as usize;
+        match self {
+            CodeStore::Scalar {
+                dimensions,
+                codes,
+                ranges,
+            } => {
+                let quantized = ScalarQuantized::quantize(vector);
+                let start = slot * *dimensions;
+                codes[start..start + *dimensions].copy_from_slice(&quantized.data);
+                ranges[slot] = (quantized.min, quantized.scale);
+            }
+            CodeStore::Binary { bytes, bits } => {
+                let start = slot * *bytes;
+                bits[start..start + *bytes]
+                    .copy_from_slice(&BinaryQuantized::quantize(vector).bits);
+            }
+        }
+    }
+
+    /// Asymmetric distance from a full-precision query to the codes at `slot`
+    #[inline]
+    pub fn distance(&self, query: &[f32], slot: u32, metric: DistanceMetric) -> f32 {
+        let slot = slot as usize;
+        match self {
+            CodeStore::Scalar {
+                dimensions,
+                codes,
+                ranges,
+            } => {
+                let start = slot * dimensions;
+                let (min, scale) = ranges[slot];
+                scalar_asymmetric_distance(
+                    query,
+                    &codes[start..start + dimensions],
+                    min,
+                    scale,
+                    metric,
+                )
+            }
+            CodeStore::Binary { bytes, bits } => {
+                let start = slot * bytes;
+                binary_asymmetric_distance(query, &bits[start..start + bytes], metric)
+            }
+        }
+    }
+
+    /// Heap bytes held by the codes
+    pub fn memory_bytes(&self) -> usize {
+        match self {
+            CodeStore::Scalar { codes, ranges, .. } => {
+                codes.len() + ranges.len() * std::mem::size_of::<(f32, f32)>()
+            }
+            CodeStore::Binary { bits, .. } => bits.len(),
+        }
+    }
+}
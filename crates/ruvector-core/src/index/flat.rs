@@ -1,17 +1,28 @@
 //! Flat (brute-force) index for baseline and small datasets
 
+use super::codes::CodeStore;
+use super::vectors::VectorStore;
 use crate::distance::distance;
-use crate::error::Result;
+use crate::error::{Result, RuvectorError};
 use crate::index::VectorIndex;
-use crate::types::{DistanceMetric, SearchResult, VectorId};
-use dashmap::DashMap;
+use crate::types::{
+    DistanceMetric, IndexStats, QuantizationConfig, SearchParams, SearchResult, VectorId,
+};
+use std::collections::HashMap;
 
 #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
 use rayon::prelude::*;
 
 /// Flat index using brute-force search
 pub struct FlatIndex {
-    vectors: DashMap<VectorId, Vec<f32>>,
+    vectors: VectorStore,
+    /// Quantized copies of `vectors` that searches scan first, if enabled
+    codes: Option<CodeStore>,
+    /// Id stored at each slot; `None` for free slots
+    ids: Vec<Option<VectorId>>,
+    id_to_slot: HashMap<VectorId, u32>,
+    /// Slots of removed vectors that new vectors can reuse
+    free: Vec<u32>,
     metric: DistanceMetric,
     dimensions: usize,
 }
@@ -20,67 +31,234 @@ impl FlatIndex {
     /// Create a new flat index
     pub fn new(dimensions: usize, metric: DistanceMetric) -> Self {
         Self {
-            vectors: DashMap::new(),
+            vectors: VectorStore::new(dimensions),
+            codes: None,
+            ids: Vec::new(),
+            id_to_slot: HashMap::new(),
+            free: Vec::new(),
             metric,
             dimensions,
         }
     }
+
+    /// Scan quantized codes instead of f32 vectors
+    ///
+    /// Scalar and binary quantization keep one code per vector in RAM and
+    /// rank every vector by its asymmetric distance to the query, then
+    /// rescore the best `k × oversampling` with the f32 vectors, which move
+    /// to a temporary file (with the `storage` feature). `None` scans at full
+    /// precision; product quantization falls back to scalar.
+    pub fn set_quantization(&mut self, quantization: &QuantizationConfig) -> Result<()> {
+        let codes = CodeStore::from_vectors(quantization, &self.vectors);
+        #[cfg(feature = "storage")]
+        if codes.is_some() {
+            self.vectors.spill()?;
+        }
+        self.codes = codes;
+        Ok(())
+    }
+
+    /// Slots holding a vector
+    fn live_slots(&self) -> impl Iterator<Item = u32> + '_ {
+        self.ids
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, id)| id.as_ref().map(|_| slot as u32))
+    }
+
+    /// Top k among `slots`, scanning codes first if quantization is enabled
+    fn search_slots(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        slots: Vec<u32>,
+    ) -> Result<Vec<SearchResult>> {
+        let exact = |slot: u32| distance(query, self.vectors.get(slot), self.metric);
+        let mut scored = match &self.codes {
+            Some(codes) if !params.exact => {
+                let mut shortlist = score(&slots, |slot| {
+                    Ok(codes.distance(query, slot, self.metric))
+                })?;
+                shortlist.sort_by(|a, b| a.1.total_cmp(&b.1));
+                // Without rescoring only the k results get full-precision scores
+                let keep = if params.rescore { params.candidates(k) } else { k };
+                shortlist.truncate(keep);
+                for (slot, dist) in &mut shortlist {
+                    *dist = exact(*slot)?;
+                }
+                shortlist
+            }
+            _ => score(&slots, exact)?,
+        };
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+        scored.truncate(k);
+
+        let mut results = scored
+            .into_iter()
+            .filter_map(|(slot, score)| {
+                Some(SearchResult {
+                    id: self.ids[slot as usize].clone()?,
+                    score,
+                    vector: None,
+                    metadata: None,
+                })
+            })
+            .collect();
+        params.apply_threshold(&mut results);
+        Ok(results)
+    }
+}
+
+/// Distance of every slot - parallel on native, sequential on WASM
+fn score<F>(slots: &[u32], dist: F) -> Result<Vec<(u32, f32)>>
+where
+    F: Fn(u32) -> Result<f32> + Send + Sync,
+{
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    let scored = slots
+        .par_iter()
+        .map(|&slot| Ok((slot, dist(slot)?)))
+        .collect();
+
+    #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
+    let scored = slots
+        .iter()
+        .map(|&slot| Ok((slot, dist(slot)?)))
+        .collect();
+
+    scored
 }
 
 impl VectorIndex for FlatIndex {
     fn add(&mut self, id: VectorId, vector: Vec<f32>) -> Result<()> {
-        self.vectors.insert(id, vector);
+        if vector.len() != self.dimensions {
+            return Err(RuvectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.len(),
+            });
+        }
+        if let Some(&slot) = self.id_to_slot.get(&id) {
+            self.vectors.set(slot, &vector)?;
+            if let Some(codes) = self.codes.as_mut() {
+                codes.set(slot, &vector);
+            }
+            return Ok(());
+        }
+
+        let slot = match self.free.last().copied() {
+            Some(slot) => {
+                self.vectors.set(slot, &vector)?;
+                self.free.pop();
+                if let Some(codes) = self.codes.as_mut() {
+                    codes.set(slot, &vector);
+                }
+                self.ids[slot as usize] = Some(id.clone());
+                slot
+            }
+            None => {
+                let slot = self.vectors.push(&vector)?;
+                if let Some(codes) = self.codes.as_mut() {
+                    codes.push(&vector);
+                }
+                self.ids.push(Some(id.clone()));
+                slot
+            }
+        };
+        self.id_to_slot.insert(id, slot);
         Ok(())
     }
 
     fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>> {
-        // Distance calculation - parallel on native, sequential on WASM
-        #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
-        let mut results: Vec<_> = self
-            .vectors
-            .iter()
-            .par_bridge()
-            .map(|entry| {
-                let id = entry.key().clone();
-                let vector = entry.value();
-                let dist = distance(query, vector, self.metric)?;
-                Ok((id, dist))
-            })
-            .collect::<Result<Vec<_>>>()?;
+        self.search_with_params(query, k, &SearchParams::default())
+    }
 
-        #[cfg(any(not(feature = "parallel"), target_arch = "wasm32"))]
-        let mut results: Vec<_> = self
-            .vectors
-            .iter()
-            .map(|entry| {
-                let id = entry.key().clone();
-                let vector = entry.value();
-                let dist = distance(query, vector, self.metric)?;
-                Ok((id, dist))
-            })
-            .collect::<Result<Vec<_>>>()?;
+    fn search_with_params(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        // Every search scans all vectors, so ef_search has no effect; exact
+        // skips the codes of a quantized index.
+        self.search_slots(query, k, params, self.live_slots().collect())
+    }
 
-        // Sort by distance and take top k
-        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-        results.truncate(k);
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        // The filter is not required to be Sync, so apply it up front
+        let slots = self
+            .live_slots()
+            .filter(|&slot| self.ids[slot as usize].as_deref().is_some_and(filter))
+            .collect();
+        self.search_slots(query, k, params, slots)
+    }
 
-        Ok(results
-            .into_iter()
-            .map(|(id, score)| SearchResult {
-                id,
-                score,
-                vector: None,
-                metadata: None,
-            })
-            .collect())
+    fn search_among(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        ids: &[VectorId],
+    ) -> Result<Vec<SearchResult>> {
+        let slots = ids
+            .iter()
+            .filter_map(|id| self.id_to_slot.get(id).copied())
+            .collect();
+        self.search_slots(query, k, params, slots)
     }
 
     fn remove(&mut self, id: &VectorId) -> Result<bool> {
-        Ok(self.vectors.remove(id).is_some())
+        let Some(slot) = self.id_to_slot.remove(id) else {
+            return Ok(false);
+        };
+        self.ids[slot as usize] = None;
+        self.free.push(slot);
+        Ok(true)
     }
 
     fn len(&self) -> usize {
-        self.vectors.len()
+        self.id_to_slot.len()
+    }
+
+    fn stats(&self) -> IndexStats {
+        let ids: usize = self
+            .id_to_slot
+            .keys()
+            // Each id is stored in `ids` and as a key of `id_to_slot`
+            .map(|id| 2 * id.capacity() + std::mem::size_of::<(VectorId, u32)>())
+            .sum();
+        IndexStats {
+            vectors: self.id_to_slot.len(),
+            memory_bytes: self.vectors.memory_bytes()
+                + self.codes.as_ref().map_or(0, CodeStore::memory_bytes)
+                + self.ids.capacity() * std::mem::size_of::<Option<VectorId>>()
+                + ids,
+            ..Default::default()
+        }
+    }
+
+    fn estimate_recall(&self, samples: usize, k: usize) -> Result<Option<f32>> {
+        if self.codes.is_none() {
+            // Every search is exhaustive
+            return Ok((self.len() > 1).then_some(1.0));
+        }
+        let live: Vec<u32> = self.live_slots().collect();
+        let queries: Vec<(VectorId, Vec<f32>)> = live
+            .iter()
+            .step_by((live.len() / samples.max(1)).max(1))
+            .take(samples)
+            .filter_map(|&slot| {
+                let id = self.ids[slot as usize].clone()?;
+                Some((id, self.vectors.get(slot).to_vec()))
+            })
+            .collect();
+        super::sampled_recall(self, &queries, k)
     }
 }
 
@@ -105,4 +283,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_quantized_flat_index() -> Result<()> {
+        let mut index = FlatIndex::new(8, DistanceMetric::Euclidean);
+        index.set_quantization(&QuantizationConfig::Scalar)?;
+        for i in 0..100 {
+            let vector: Vec<f32> = (0..8).map(|d| ((i * 7 + d * 3) % 17) as f32).collect();
+            index.add(format!("v{}", i), vector)?;
+        }
+        index.remove(&"v5".to_string())?;
+        index.add("v100".to_string(), vec![100.0; 8])?;
+        assert_eq!(index.len(), 100);
+        #[cfg(feature = "storage")]
+        assert_eq!(index.vectors.heap_bytes(), 0);
+
+        let query: Vec<f32> = (0..8).map(|d| ((42 * 7 + d * 3) % 17) as f32).collect();
+        let params = SearchParams {
+            oversampling: 4.0,
+            ..Default::default()
+        };
+        let results = index.search_with_params(&query, 3, &params)?;
+        assert_eq!(results[0].score, 0.0);
+        assert!(results.iter().all(|r| r.id != "v5"));
+        assert_eq!(index.search(&[100.0; 8], 1)?[0].id, "v100");
+        assert!(index.estimate_recall(10, 3)?.is_some());
+        Ok(())
+    }
 }
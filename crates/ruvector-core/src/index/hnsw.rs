@@ -1,31 +1,45 @@
 //! HNSW (Hierarchical Navigable Small World) index implementation
-
+//!
+//! The graph is implemented natively (see [`graph`]) so that its layers and
+//! neighbor lists can be persisted next to the redb storage and reopened
+//! without rebuilding (see [`persistence`]). With scalar or binary
+//! quantization configured, queries traverse the graph over compact codes
+//! kept in RAM (see [`codes`](super::codes)) and rescore the best candidates
+//! with the full-precision vectors, which are then kept in a file.
+
+mod graph;
+mod links;
+#[cfg(feature = "storage")]
+mod persistence;
+
+#[cfg(feature = "storage")]
+pub use persistence::graph_dir_for;
+
+use super::codes::CodeStore;
+use super::vectors::VectorStore;
 use crate::distance::distance;
 use crate::error::{Result, RuvectorError};
 use crate::index::VectorIndex;
-use crate::types::{DistanceMetric, HnswConfig, SearchResult, VectorId};
+use crate::types::{
+    DistanceMetric, GraphPart, HnswConfig, IndexStats, QuantizationConfig, SearchParams,
+    SearchResult, VectorId,
+};
 use bincode::{Decode, Encode};
-use dashmap::DashMap;
-use hnsw_rs::prelude::*;
-use parking_lot::RwLock;
+use graph::HnswGraph;
+use links::LinkStore;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-/// Distance function wrapper for hnsw_rs
-struct DistanceFn {
-    metric: DistanceMetric,
-}
-
-impl DistanceFn {
-    fn new(metric: DistanceMetric) -> Self {
-        Self { metric }
-    }
-}
+#[cfg(feature = "storage")]
+use persistence::{Checkpoint, GraphSnapshot, GraphWriter};
+#[cfg(feature = "storage")]
+use std::path::Path;
 
-impl Distance<f32> for DistanceFn {
-    fn eval(&self, a: &[f32], b: &[f32]) -> f32 {
-        distance(a, b, self.metric).unwrap_or(f32::MAX)
-    }
-}
+/// Slots whose neighbor lists a compaction repairs per write lock
+const COMPACTION_BATCH_SLOTS: u32 = 1024;
 
 /// HNSW index wrapper
 pub struct HnswIndex {
@@ -33,23 +47,51 @@ pub struct HnswIndex {
     config: HnswConfig,
     metric: DistanceMetric,
     dimensions: usize,
+    maintenance: Arc<Maintenance>,
+    /// Background compaction started by the last write, if any
+    #[cfg(not(target_arch = "wasm32"))]
+    compaction: Option<std::thread::JoinHandle<()>>,
+}
+
+/// Coordination between an index and its compactions and checkpoints
+#[derive(Default)]
+struct Maintenance {
+    /// Held while compacting or checkpointing, so they run one at a time
+    lock: Mutex<()>,
+    /// Set while a background compaction is queued or running
+    compacting: AtomicBool,
+    /// Set once the index is dropped, to stop a compaction between batches
+    closing: AtomicBool,
 }
 
 struct HnswInner {
-    hnsw: Hnsw<'static, f32, DistanceFn>,
-    vectors: DashMap<VectorId, Vec<f32>>,
-    id_to_idx: DashMap<VectorId, usize>,
-    idx_to_id: DashMap<usize, VectorId>,
-    next_idx: usize,
+    graph: HnswGraph,
+    vectors: VectorStore,
+    /// Quantized copies of `vectors` that queries traverse, if enabled
+    codes: Option<CodeStore>,
+    /// Id stored at each slot; `None` for deleted and free slots
+    ids: Vec<Option<VectorId>>,
+    id_to_slot: HashMap<VectorId, u32>,
+    /// Deleted slots still present in the graph, waiting for compaction
+    tombstones: Vec<u32>,
+    /// Compacted slots that new inserts can reuse
+    free: Vec<u32>,
+    /// Compacted slots that become reusable once a checkpoint records them
+    /// as free; until then the persisted graph may still refer to them
+    pending_free: Vec<u32>,
+    #[cfg(feature = "storage")]
+    writer: Option<GraphWriter>,
 }
 
 /// Serializable HNSW index state
 #[derive(Encode, Decode, Clone)]
 pub struct HnswState {
-    vectors: Vec<(String, Vec<f32>)>,
-    id_to_idx: Vec<(String, usize)>,
-    idx_to_id: Vec<(usize, String)>,
-    next_idx: usize,
+    vectors: Vec<f32>,
+    links: Vec<Vec<Vec<u32>>>,
+    ids: Vec<Option<String>>,
+    free: Vec<u32>,
+    entry_point: Option<u32>,
+    max_level: usize,
     config: SerializableHnswConfig,
     dimensions: usize,
     metric: SerializableDistanceMetric,
@@ -61,6 +103,7 @@ struct SerializableHnswConfig {
     ef_construction: usize,
     ef_search: usize,
     max_elements: usize,
+    compaction_threshold: f32,
 }
 
 #[derive(Encode, Decode, Clone, Copy)]
@@ -93,32 +136,402 @@ impl From<SerializableDistanceMetric> for DistanceMetric {
     }
 }
 
+impl HnswInner {
+    fn new(dimensions: usize, config: &HnswConfig) -> Self {
+        Self::from_parts(
+            HnswGraph::new(config.m, config.ef_construction),
+            VectorStore::new(dimensions),
+            Vec::new(),
+            Vec::new(),
+        )
+    }
+
+    /// Assemble the index state from restored parts; `None` ids that are not
+    /// in `free` are treated as tombstones
+    fn from_parts(
+        graph: HnswGraph,
+        vectors: VectorStore,
+        ids: Vec<Option<VectorId>>,
+        free: Vec<u32>,
+    ) -> Self {
+        let free_set: HashSet<u32> = free.iter().copied().collect();
+        let mut id_to_slot = HashMap::with_capacity(ids.len());
+        let mut tombstones = Vec::new();
+        for (slot, id) in ids.iter().enumerate() {
+            match id {
+                Some(id) => {
+                    id_to_slot.insert(id.clone(), slot as u32);
+                }
+                None if !free_set.contains(&(slot as u32)) => tombstones.push(slot as u32),
+                None => {}
+            }
+        }
+
+        Self {
+            graph,
+            vectors,
+            codes: None,
+            ids,
+            id_to_slot,
+            tombstones,
+            free,
+            pending_free: Vec::new(),
+            #[cfg(feature = "storage")]
+            writer: None,
+        }
+    }
+
+    /// Insert a vector, replacing any previous vector stored under `id`
+    fn insert(&mut self, id: VectorId, vector: &[f32], metric: DistanceMetric) -> Result<()> {
+        // Store the vector before touching the graph or the previous vector
+        // under `id`, so a failed write leaves both in place; growing the
+        // graph first means pushing its node cannot fail afterwards
+        let reused = self.free.last().copied();
+        match reused {
+            Some(slot) => self.vectors.set(slot, vector)?,
+            None => {
+                self.graph.reserve(1)?;
+                self.vectors.push(vector)?;
+            }
+        }
+
+        let level = self.graph.random_level();
+        let slot = match reused {
+            Some(slot) => {
+                self.free.pop();
+                self.graph.reset_node(slot, level);
+                if let Some(codes) = self.codes.as_mut() {
+                    codes.set(slot, vector);
+                }
+                slot
+            }
+            None => {
+                let slot = self.graph.push_node(level)?;
+                if let Some(codes) = self.codes.as_mut() {
+                    codes.push(vector);
+                }
+                self.ids.push(None);
+                slot
+            }
+        };
+
+        // The new node is not live yet, so relinking around the old one
+        // leaves it out
+        if let Err(e) = self.remove(&id, metric) {
+            // Compaction reclaims the unused slot
+            self.tombstones.push(slot);
+            return Err(e);
+        }
+        self.ids[slot as usize] = Some(id.clone());
+        self.id_to_slot.insert(id.clone(), slot);
+
+        let (vectors, ids) = (&self.vectors, &self.ids);
+        let touched = self.graph.link(
+            slot,
+            |o| ids[o as usize].is_some(),
+            |a, b| distance(vectors.get(a), vectors.get(b), metric).unwrap_or(f32::MAX),
+        );
+
+        #[cfg(feature = "storage")]
+        if let Some(writer) = self.writer.as_mut() {
+            writer.append_insert(slot, reused.is_some(), &id, vector, &self.graph, &touched)?;
+        }
+        #[cfg(not(feature = "storage"))]
+        let _ = touched;
+
+        Ok(())
+    }
+
+    /// Delete `id`, returning whether it was present
+    ///
+    /// The node becomes a tombstone: its neighbors are relinked around it,
+    /// but it stays traversable until the next compaction.
+    fn remove(&mut self, id: &str, metric: DistanceMetric) -> Result<bool> {
+        let Some(slot) = self.id_to_slot.remove(id) else {
+            return Ok(false);
+        };
+        self.ids[slot as usize] = None;
+        self.tombstones.push(slot);
+
+        let (vectors, ids) = (&self.vectors, &self.ids);
+        let touched = self.graph.unlink(
+            slot,
+            |o| ids[o as usize].is_some(),
+            |a, b| distance(vectors.get(a), vectors.get(b), metric).unwrap_or(f32::MAX),
+        );
+
+        #[cfg(feature = "storage")]
+        if let Some(writer) = self.writer.as_mut() {
+            writer.append_delete(slot, &self.graph, &touched)?;
+        }
+        #[cfg(not(feature = "storage"))]
+        let _ = touched;
+
+        Ok(true)
+    }
+
+    /// Approximate bytes held by the graph, vectors, codes and ids
+    fn memory_bytes(&self) -> usize {
+        let ids: usize = self
+            .id_to_slot
+            .keys()
+            // Each id is stored in `ids` and as a key of `id_to_slot`
+            .map(|id| 2 * id.capacity() + std::mem::size_of::<(VectorId, u32)>())
+            .sum();
+        self.graph.memory_bytes()
+            + self.vectors.memory_bytes()
+            + self.codes.as_ref().map_or(0, CodeStore::memory_bytes)
+            + self.ids.capacity() * std::mem::size_of::<Option<VectorId>>()
+            + ids
+    }
+
+    /// Fraction of graph slots that are tombstones
+    fn tombstone_ratio(&self) -> f32 {
+        if self.graph.len() == 0 {
+            0.0
+        } else {
+            self.tombstones.len() as f32 / self.graph.len() as f32
+        }
+    }
+
+    /// Relink the live nodes in `slots` away from tombstones
+    fn repair(&mut self, slots: Range<u32>, metric: DistanceMetric) {
+        let (vectors, ids) = (&self.vectors, &self.ids);
+        self.graph.repair(
+            slots,
+            |o| ids[o as usize].is_some(),
+            |a, b| distance(vectors.get(a), vectors.get(b), metric).unwrap_or(f32::MAX),
+        );
+    }
+
+    /// Clear the oldest `count` tombstones once every slot has been repaired
+    ///
+    /// Persistent indexes only reuse the slots after the next checkpoint.
+    fn release(&mut self, count: usize) {
+        let ids = &self.ids;
+        let alive = |o: u32| ids[o as usize].is_some();
+        if self.graph.entry_point.is_some_and(|e| !alive(e)) {
+            self.graph.reselect_entry(alive);
+        }
+
+        let released: Vec<u32> = self.tombstones.drain(..count).collect();
+        for &slot in &released {
+            self.graph.clear_links(slot);
+        }
+        #[cfg(feature = "storage")]
+        if self.writer.is_some() {
+            self.pending_free.extend(released);
+            return;
+        }
+        self.reuse(released);
+    }
+
+    /// Make `slots` available to new inserts, low slots first
+    fn reuse(&mut self, slots: Vec<u32>) {
+        self.free.extend(slots);
+        self.free.sort_unstable_by(|a, b| b.cmp(a));
+    }
+
+    /// Free and pending slots, as recorded in a checkpoint
+    fn free_slots(&self) -> Vec<u32> {
+        self.free.iter().chain(&self.pending_free).copied().collect()
+    }
+
+    /// Move the vectors and graph into the graph directory `dir` and write
+    /// a first checkpoint there
+    ///
+    /// Leaves the index unpersisted if another index in this process owns
+    /// `dir`.
+    #[cfg(feature = "storage")]
+    fn persist_to(
+        &mut self,
+        dir: &Path,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: &HnswConfig,
+    ) -> Result<()> {
+        let created = GraphWriter::create(
+            dir,
+            dimensions,
+            config.m,
+            &mut self.graph,
+            &mut self.vectors,
+        )?;
+        let Some(mut writer) = created else {
+            self.writer = None;
+            return Ok(());
+        };
+        let free = self.free_slots();
+        writer
+            .begin_checkpoint(
+                &GraphSnapshot {
+                    dimensions,
+                    metric,
+                    config,
+                    graph: &self.graph,
+                    ids: &self.ids,
+                    free: &free,
+                },
+                &self.vectors,
+            )?
+            .write()?;
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    /// Whether enough has been logged since the last checkpoint to write a new one
+    #[cfg(feature = "storage")]
+    fn checkpoint_due(&self) -> bool {
+        self.writer
+            .as_ref()
+            .is_some_and(|w| w.should_checkpoint(self.graph.len()))
+    }
+
+    /// Flush the log records of the last mutation
+    #[cfg(feature = "storage")]
+    fn flush_log(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Capture a checkpoint to write once the lock is released, along with
+    /// the pending slots it records as free
+    #[cfg(feature = "storage")]
+    fn begin_checkpoint(
+        &mut self,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: &HnswConfig,
+    ) -> Result<Option<(Checkpoint, Vec<u32>)>> {
+        let free = self.free_slots();
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(None);
+        };
+        let checkpoint = writer.begin_checkpoint(
+            &GraphSnapshot {
+                dimensions,
+                metric,
+                config,
+                graph: &self.graph,
+                ids: &self.ids,
+                free: &free,
+            },
+            &self.vectors,
+        )?;
+        Ok(Some((checkpoint, std::mem::take(&mut self.pending_free))))
+    }
+}
+
+impl Drop for HnswIndex {
+    fn drop(&mut self) {
+        // Nothing may write to the graph directory once the index is gone
+        self.maintenance.closing.store(true, Ordering::Release);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(compaction) = self.compaction.take() {
+            let _ = compaction.join();
+        }
+    }
+}
+
 impl HnswIndex {
     /// Create a new HNSW index
     pub fn new(dimensions: usize, metric: DistanceMetric, config: HnswConfig) -> Result<Self> {
-        let distance_fn = DistanceFn::new(metric);
-
-        // Create HNSW with configured parameters
-        let hnsw = Hnsw::<f32, DistanceFn>::new(
-            config.m,
-            config.max_elements,
-            dimensions,
-            config.ef_construction,
-            distance_fn,
-        );
+        let inner = HnswInner::new(dimensions, &config);
+        Ok(Self::from_inner(inner, dimensions, metric, config))
+    }
 
-        Ok(Self {
-            inner: Arc::new(RwLock::new(HnswInner {
-                hnsw,
-                vectors: DashMap::new(),
-                id_to_idx: DashMap::new(),
-                idx_to_id: DashMap::new(),
-                next_idx: 0,
-            })),
+    fn from_inner(
+        inner: HnswInner,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: HnswConfig,
+    ) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(inner)),
             config,
             metric,
             dimensions,
-        })
+            maintenance: Arc::new(Maintenance::default()),
+            #[cfg(not(target_arch = "wasm32"))]
+            compaction: None,
+        }
+    }
+
+    /// Create an empty index persisted in the graph directory `dir`
+    ///
+    /// Any graph previously stored in `dir` is discarded. If another index in
+    /// this process already owns `dir`, the returned index is kept in memory
+    /// only.
+    #[cfg(feature = "storage")]
+    pub fn create<P: AsRef<Path>>(
+        dir: P,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: HnswConfig,
+    ) -> Result<Self> {
+        let mut inner = HnswInner::new(dimensions, &config);
+        inner.persist_to(dir.as_ref(), dimensions, metric, &config)?;
+        if inner.writer.is_none() {
+            tracing::warn!(
+                "HNSW graph directory {:?} is already in use, index will not be persisted",
+                dir.as_ref()
+            );
+        }
+
+        Ok(Self::from_inner(inner, dimensions, metric, config))
+    }
+
+    /// Reopen an index persisted in the graph directory `dir`
+    ///
+    /// Returns `Ok(None)` if nothing usable is stored there: the directory is
+    /// missing, was written with a different configuration, or is owned by
+    /// another open index. Returns an error if the files are corrupt.
+    #[cfg(feature = "storage")]
+    pub fn open<P: AsRef<Path>>(
+        dir: P,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: HnswConfig,
+    ) -> Result<Option<Self>> {
+        let Some(loaded) = persistence::load(dir.as_ref(), dimensions, metric, &config)? else {
+            return Ok(None);
+        };
+
+        let mut inner =
+            HnswInner::from_parts(loaded.graph, loaded.vectors, loaded.ids, loaded.free);
+        inner.writer = Some(loaded.writer);
+
+        Ok(Some(Self::from_inner(inner, dimensions, metric, config)))
+    }
+
+    /// Start persisting the index in the graph directory `dir`
+    ///
+    /// Writes a full checkpoint of the current graph, discarding whatever
+    /// was stored in `dir`. Returns `false` if another index in this process
+    /// owns `dir`, in which case the index stays in memory only.
+    #[cfg(feature = "storage")]
+    pub fn persist<P: AsRef<Path>>(&self, dir: P) -> Result<bool> {
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        inner.persist_to(dir.as_ref(), self.dimensions, self.metric, &self.config)?;
+        // The new snapshot records pending slots as free
+        let pending = std::mem::take(&mut inner.pending_free);
+        inner.reuse(pending);
+        Ok(inner.writer.is_some())
+    }
+
+    /// Whether mutations are being written to a graph directory
+    pub fn is_persistent(&self) -> bool {
+        #[cfg(feature = "storage")]
+        {
+            self.inner.read().writer.is_some()
+        }
+        #[cfg(not(feature = "storage"))]
+        {
+            false
+        }
     }
 
     /// Get configuration
@@ -126,10 +539,178 @@ impl HnswIndex {
         &self.config
     }
 
-    /// Set efSearch parameter for query-time accuracy tuning
-    pub fn set_ef_search(&mut self, _ef_search: usize) {
-        // Note: hnsw_rs controls ef_search via the search method's knbn parameter
-        // We store it in config and use it in search_with_ef
+    /// Check whether `id` is indexed
+    pub fn contains(&self, id: &str) -> bool {
+        self.inner.read().id_to_slot.contains_key(id)
+    }
+
+    /// All ids currently in the index
+    pub fn ids(&self) -> Vec<VectorId> {
+        self.inner.read().id_to_slot.keys().cloned().collect()
+    }
+
+    /// Fraction of graph nodes that are deleted but not yet compacted
+    pub fn tombstone_ratio(&self) -> f32 {
+        self.inner.read().tombstone_ratio()
+    }
+
+    /// Detach deleted nodes from the graph and make their slots reusable
+    ///
+    /// Runs automatically in the background once the tombstone ratio
+    /// exceeds [`HnswConfig::compaction_threshold`]. Persistent indexes
+    /// write a checkpoint afterwards.
+    pub fn compact(&self) -> Result<()> {
+        Self::compact_inner(
+            &self.inner,
+            &self.maintenance,
+            self.dimensions,
+            self.metric,
+            &self.config,
+        )
+    }
+
+    /// Repair the graph in batches of slots, taking the write lock for one
+    /// batch at a time so searches and writes proceed in between, then free
+    /// the tombstones that existed when it started
+    fn compact_inner(
+        inner: &RwLock<HnswInner>,
+        maintenance: &Maintenance,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: &HnswConfig,
+    ) -> Result<()> {
+        let _maintenance = maintenance.lock.lock();
+        // Tombstones are only ever appended, so the ones present now stay
+        // at the front while later deletes add more
+        let (tombstones, slots) = {
+            let inner = inner.read();
+            (inner.tombstones.len(), inner.graph.len() as u32)
+        };
+        if tombstones == 0 {
+            return Ok(());
+        }
+
+        // Nodes added from here on only link to live nodes, so the slots
+        // present now are all that need repairing
+        let mut start = 0;
+        while start < slots {
+            if maintenance.closing.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            let end = start.saturating_add(COMPACTION_BATCH_SLOTS).min(slots);
+            inner.write().repair(start..end, metric);
+            start = end;
+        }
+        inner.write().release(tombstones);
+
+        #[cfg(feature = "storage")]
+        Self::write_checkpoint(inner, dimensions, metric, config)?;
+        #[cfg(not(feature = "storage"))]
+        let _ = (dimensions, config);
+
+        Ok(())
+    }
+
+    /// Capture a checkpoint under the write lock and write it outside it
+    ///
+    /// The caller holds the maintenance lock. Slots the checkpoint records as
+    /// free become reusable once it is on disk.
+    #[cfg(feature = "storage")]
+    fn write_checkpoint(
+        inner: &RwLock<HnswInner>,
+        dimensions: usize,
+        metric: DistanceMetric,
+        config: &HnswConfig,
+    ) -> Result<()> {
+        let captured = inner.write().begin_checkpoint(dimensions, metric, config)?;
+        let Some((checkpoint, freed)) = captured else {
+            return Ok(());
+        };
+        let result = checkpoint.write();
+        let mut inner = inner.write();
+        match result {
+            Ok(()) => inner.reuse(freed),
+            Err(_) => inner.pending_free.extend(freed),
+        }
+        result
+    }
+
+    /// Checkpoint if enough has been logged, then compact if enough has
+    /// been deleted
+    fn after_write(&mut self) -> Result<()> {
+        #[cfg(feature = "storage")]
+        if self.inner.read().checkpoint_due() {
+            // A running compaction or checkpoint covers these records too
+            if let Some(_maintenance) = self.maintenance.lock.try_lock() {
+                Self::write_checkpoint(&self.inner, self.dimensions, self.metric, &self.config)?;
+            }
+        }
+        self.maybe_compact();
+        Ok(())
+    }
+
+    /// Start a background compaction if the tombstone ratio is over the threshold
+    fn maybe_compact(&mut self) {
+        let threshold = self.config.compaction_threshold;
+        if threshold <= 0.0 || self.inner.read().tombstone_ratio() < threshold {
+            return;
+        }
+        if self.maintenance.compacting.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let inner = Arc::clone(&self.inner);
+        let maintenance = Arc::clone(&self.maintenance);
+        let (dimensions, metric, config) = (self.dimensions, self.metric, self.config.clone());
+        let run = move || {
+            if let Err(e) = Self::compact_inner(&inner, &maintenance, dimensions, metric, &config)
+            {
+                tracing::warn!("HNSW compaction failed: {}", e);
+            }
+            maintenance.compacting.store(false, Ordering::Release);
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // The previous compaction has finished, since it cleared the flag
+            if let Some(previous) = self.compaction.take() {
+                let _ = previous.join();
+            }
+            self.compaction = Some(std::thread::spawn(run));
+        }
+        #[cfg(target_arch = "wasm32")]
+        run();
+    }
+
+    /// Set the default efSearch used when a query does not override it
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.config.ef_search = ef_search;
+    }
+
+    /// Traverse the graph over quantized codes instead of f32 vectors
+    ///
+    /// Scalar and binary quantization encode every indexed vector and keep
+    /// the codes up to date from then on, and the f32 vectors move off the
+    /// heap: persistent indexes already serve them from `vectors.bin`, and
+    /// in-memory ones spill them to a temporary file (with the `storage`
+    /// feature). `None` searches at full precision; product quantization
+    /// falls back to scalar. Codes are not persisted, so reopened indexes
+    /// rebuild them with one pass over the stored vectors.
+    pub fn set_quantization(&mut self, quantization: &QuantizationConfig) -> Result<()> {
+        let mut inner = self.inner.write();
+        let codes = CodeStore::from_vectors(quantization, &inner.vectors);
+        #[cfg(feature = "storage")]
+        if codes.is_some() {
+            inner.vectors.spill()?;
+        }
+        inner.codes = codes;
+        Ok(())
+    }
+
+    /// Bytes of RAM held by quantized codes; 0 when searching at full precision
+    pub fn quantized_memory_bytes(&self) -> usize {
+        let inner = self.inner.read();
+        inner.codes.as_ref().map_or(0, CodeStore::memory_bytes)
     }
 
     /// Serialize the index to bytes using bincode
@@ -137,27 +718,24 @@ impl HnswIndex {
         let inner = self.inner.read();
 
         let state = HnswState {
-            vectors: inner
-                .vectors
-                .iter()
-                .map(|entry| (entry.key().clone(), entry.value().clone()))
-                .collect(),
-            id_to_idx: inner
-                .id_to_idx
-                .iter()
-                .map(|entry| (entry.key().clone(), *entry.value()))
-                .collect(),
-            idx_to_id: inner
-                .idx_to_id
-                .iter()
-                .map(|entry| (*entry.key(), entry.value().clone()))
+            vectors: inner.vectors.to_flat(),
+            links: (0..inner.graph.len() as u32)
+                .map(|slot| {
+                    (0..=inner.graph.level(slot))
+                        .map(|layer| inner.graph.neighbors(slot, layer).to_vec())
+                        .collect()
+                })
                 .collect(),
-            next_idx: inner.next_idx,
+            ids: inner.ids.clone(),
+            free: inner.free_slots(),
+            entry_point: inner.graph.entry_point,
+            max_level: inner.graph.max_level,
             config: SerializableHnswConfig {
                 m: self.config.m,
                 ef_construction: self.config.ef_construction,
                 ef_search: self.config.ef_search,
                 max_elements: self.config.max_elements,
+                compaction_threshold: self.config.compaction_threshold,
             },
             dimensions: self.dimensions,
             metric: self.metric.into(),
@@ -169,6 +747,8 @@ impl HnswIndex {
     }
 
     /// Deserialize the index from bytes using bincode
+    ///
+    /// The graph topology is restored as stored; no vectors are re-inserted.
     pub fn deserialize(bytes: &[u8]) -> Result<Self> {
         let (state, _): (HnswState, usize) =
             bincode::decode_from_slice(bytes, bincode::config::standard()).map_err(|e| {
@@ -183,48 +763,166 @@ impl HnswIndex {
             ef_construction: state.config.ef_construction,
             ef_search: state.config.ef_search,
             max_elements: state.config.max_elements,
+            compaction_threshold: state.config.compaction_threshold,
         };
 
         let dimensions = state.dimensions;
         let metric: DistanceMetric = state.metric.into();
 
-        let distance_fn = DistanceFn::new(metric);
-        let mut hnsw = Hnsw::<'static, f32, DistanceFn>::new(
+        let node_count = state.links.len();
+        if state.ids.len() != node_count || state.vectors.len() != node_count * dimensions {
+            return Err(RuvectorError::SerializationError(
+                "Failed to deserialize HNSW index: inconsistent node counts".to_string(),
+            ));
+        }
+        let in_range = |slot: &u32| (*slot as usize) < node_count;
+        if !state.links.iter().flatten().flatten().all(in_range)
+            || !state.entry_point.iter().all(in_range)
+            || !state.free.iter().all(in_range)
+        {
+            return Err(RuvectorError::SerializationError(
+                "Failed to deserialize HNSW index: link out of range".to_string(),
+            ));
+        }
+        let max_links = graph::base_layer_links(config.m);
+        if state
+            .links
+            .iter()
+            .any(|lists| lists.first().is_some_and(|base| base.len() > max_links))
+        {
+            return Err(RuvectorError::SerializationError(
+                "Failed to deserialize HNSW index: neighbor list too long".to_string(),
+            ));
+        }
+
+        let mut base = LinkStore::new(max_links);
+        let mut upper = HashMap::new();
+        for lists in state.links {
+            let slot = base.push()?;
+            let mut lists = lists.into_iter();
+            if let Some(links) = lists.next() {
+                base.set(slot, &links);
+            }
+            let lists: Vec<Vec<u32>> = lists.collect();
+            if !lists.is_empty() {
+                upper.insert(slot, lists);
+            }
+        }
+        let graph = HnswGraph::from_parts(
             config.m,
-            config.max_elements,
-            dimensions,
             config.ef_construction,
-            distance_fn,
+            base,
+            upper,
+            state.entry_point,
+            state.max_level,
         );
+        let vectors = VectorStore::from_flat(dimensions, state.vectors);
 
-        // Rebuild the index by inserting all vectors
-        let id_to_idx: DashMap<VectorId, usize> = state.id_to_idx.into_iter().collect();
-        let idx_to_id: DashMap<usize, VectorId> = state.idx_to_id.into_iter().collect();
-
-        // Insert vectors into HNSW in order
-        for entry in idx_to_id.iter() {
-            let idx = *entry.key();
-            let id = entry.value();
-            if let Some(vector) = state.vectors.iter().find(|(vid, _)| vid == id) {
-                // Use insert_data method with slice and idx
-                hnsw.insert_data(&vector.1, idx);
-            }
+        let inner = HnswInner::from_parts(graph, vectors, state.ids, state.free);
+        Ok(Self::from_inner(inner, dimensions, metric, config))
+    }
+
+    /// Append the slots of an exported graph part, without linking them again
+    ///
+    /// Parts must come in slot order. `vectors` holds the vector of each slot
+    /// of `part`, or `None` where the id is not to be indexed; those slots
+    /// become tombstones, as do slots whose id an earlier slot already took.
+    /// Nothing is logged, so the import is only durable once
+    /// [`finish_import`](Self::finish_import) checkpoints it.
+    #[cfg(feature = "storage")]
+    pub(crate) fn import_part(
+        &mut self,
+        part: GraphPart,
+        vectors: Vec<Option<Vec<f32>>>,
+    ) -> Result<()> {
+        let invalid = |reason: &str| {
+            RuvectorError::SerializationError(format!("Invalid HNSW graph part: {}", reason))
+        };
+        let mut guard = self.inner.write();
+        let inner = &mut *guard;
+        if part.start as usize != inner.graph.len() {
+            return Err(invalid("out of sequence"));
+        }
+        if part.links.len() != part.ids.len() || vectors.len() != part.ids.len() {
+            return Err(invalid("inconsistent node counts"));
+        }
+        if let Some(vector) = vectors
+            .iter()
+            .flatten()
+            .find(|v| v.len() != self.dimensions)
+        {
+            return Err(RuvectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: vector.len(),
+            });
         }
 
-        let vectors_map: DashMap<VectorId, Vec<f32>> = state.vectors.into_iter().collect();
+        let unused = vec![0.0; self.dimensions];
+        for ((id, lists), vector) in part.ids.into_iter().zip(part.links).zip(vectors) {
+            let level = lists.len().saturating_sub(1);
+            if level > graph::MAX_LEVEL
+                || lists
+                    .iter()
+                    .enumerate()
+                    .any(|(layer, links)| links.len() > inner.graph.max_links(layer))
+            {
+                return Err(invalid("neighbor list too long"));
+            }
+            let node = match (id, vector) {
+                (Some(id), Some(vector)) if !inner.id_to_slot.contains_key(&id) => {
+                    Some((id, vector))
+                }
+                _ => None,
+            };
+
+            let vector = node.as_ref().map_or(&unused[..], |(_, vector)| vector);
+            inner.graph.reserve(1)?;
+            inner.vectors.push(vector)?;
+            let slot = inner.graph.push_node(level)?;
+            if let Some(codes) = inner.codes.as_mut() {
+                codes.push(vector);
+            }
+            for (layer, links) in lists.iter().enumerate() {
+                inner.graph.set_links(slot, layer, links);
+            }
+            match node {
+                Some((id, _)) => {
+                    inner.id_to_slot.insert(id.clone(), slot);
+                    inner.ids.push(Some(id));
+                }
+                None => {
+                    inner.ids.push(None);
+                    inner.tombstones.push(slot);
+                }
+            }
+        }
+        inner.graph.entry_point = part.entry_point;
+        inner.graph.max_level = part.max_level;
+        Ok(())
+    }
 
-        Ok(Self {
-            inner: Arc::new(RwLock::new(HnswInner {
-                hnsw,
-                vectors: vectors_map,
-                id_to_idx,
-                idx_to_id,
-                next_idx: state.next_idx,
-            })),
-            config,
-            metric,
-            dimensions,
-        })
+    /// Make the imported graph searchable and write it to disk
+    ///
+    /// Parts read from a live index can disagree on the entry point, so it is
+    /// picked again unless it is a live node on the top layer. Tombstones are
+    /// compacted away, then a checkpoint is written.
+    #[cfg(feature = "storage")]
+    pub(crate) fn finish_import(&mut self) -> Result<()> {
+        {
+            let mut guard = self.inner.write();
+            let inner = &mut *guard;
+            let ids = &inner.ids;
+            let alive = |o: u32| ids.get(o as usize).is_some_and(Option::is_some);
+            let graph = &mut inner.graph;
+            if !graph
+                .entry_point
+                .is_some_and(|e| alive(e) && graph.level(e) == graph.max_level)
+            {
+                graph.reselect_entry(alive);
+            }
+        }
+        self.compact()?;
+        VectorIndex::checkpoint(self)
     }
 
     /// Search with custom efSearch parameter
@@ -234,29 +932,124 @@ impl HnswIndex {
         k: usize,
         ef_search: usize,
     ) -> Result<Vec<SearchResult>> {
-        if query.len() != self.dimensions {
-            return Err(RuvectorError::DimensionMismatch {
-                expected: self.dimensions,
-                actual: query.len(),
-            });
-        }
+        let params = SearchParams {
+            ef_search: Some(ef_search),
+            ..Default::default()
+        };
+        self.search_accepting(query, k, &params, |_| true)
+    }
 
+    /// Beam search that only collects nodes whose id passes `accept`
+    ///
+    /// Rejected nodes are still traversed, so the filter narrows the results
+    /// without disconnecting the graph.
+    fn search_accepting<A>(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        accept: A,
+    ) -> Result<Vec<SearchResult>>
+    where
+        A: Fn(&str) -> bool,
+    {
+        self.check_query(query)?;
         let inner = self.inner.read();
 
-        // Use HNSW search with custom ef parameter (knbn)
-        let neighbors = inner.hnsw.search(query, k, ef_search);
+        if params.exact {
+            let slots = inner
+                .id_to_slot
+                .iter()
+                .filter_map(|(id, &slot)| accept(id).then_some(slot));
+            return self.scan(&inner, query, k, params, slots);
+        }
+
+        let candidates = params.candidates(k);
+        let ef_search = params.ef_search.unwrap_or(self.config.ef_search);
+        // Tombstones still cost beam slots while being traversed, so widen the
+        // beam in proportion to how much of the graph is deleted
+        let live_fraction = (1.0 - inner.tombstone_ratio()).max(0.1);
+        // A beam wider than the live nodes cannot collect more of them
+        let ef = (ef_search.max(candidates) as f32 / live_fraction).ceil() as usize;
+        let ef = ef.min(inner.id_to_slot.len()).max(1);
+
+        let metric = self.metric;
+        let accepted = |slot: u32| inner.ids[slot as usize].as_deref().is_some_and(&accept);
+        let mut neighbors = match &inner.codes {
+            Some(codes) => {
+                let quantized = |slot| codes.distance(query, slot, metric);
+                let mut neighbors = inner.graph.search(ef, quantized, accepted);
+                // Quantized distances only shortlist the candidates; re-rank
+                // them with the full-precision vectors before cutting to k.
+                // Without rescoring the cut comes first, but the results
+                // still carry full-precision scores for the threshold
+                neighbors.truncate(if params.rescore { candidates } else { k });
+                for neighbor in &mut neighbors {
+                    neighbor.dist = distance(query, inner.vectors.get(neighbor.slot), metric)?;
+                }
+                neighbors.sort_by(|a, b| a.dist.total_cmp(&b.dist));
+                neighbors
+            }
+            None => inner.graph.search(
+                ef,
+                |slot| distance(query, inner.vectors.get(slot), metric).unwrap_or(f32::MAX),
+                accepted,
+            ),
+        };
+        neighbors.truncate(k);
 
-        Ok(neighbors
+        let mut results: Vec<SearchResult> = neighbors
             .into_iter()
             .filter_map(|neighbor| {
-                inner.idx_to_id.get(&neighbor.d_id).map(|id| SearchResult {
+                inner.ids[neighbor.slot as usize]
+                    .as_ref()
+                    .map(|id| SearchResult {
+                        id: id.clone(),
+                        score: neighbor.dist,
+                        vector: None,
+                        metadata: None,
+                    })
+            })
+            .collect();
+        params.apply_threshold(&mut results);
+        Ok(results)
+    }
+
+    /// Exact k nearest neighbors among `slots`, without traversing the graph
+    fn scan(
+        &self,
+        inner: &HnswInner,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        slots: impl Iterator<Item = u32>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut results = slots
+            .filter_map(|slot| Some((inner.ids[slot as usize].as_ref()?, slot)))
+            .map(|(id, slot)| {
+                Ok(SearchResult {
                     id: id.clone(),
-                    score: neighbor.distance,
+                    score: distance(query, inner.vectors.get(slot), self.metric)?,
                     vector: None,
                     metadata: None,
                 })
             })
-            .collect())
+            .collect::<Result<Vec<_>>>()?;
+        results.sort_by(|a, b| a.score.total_cmp(&b.score));
+        results.truncate(k);
+        params.apply_threshold(&mut results);
+        Ok(results)
+    }
+
+    /// Reject queries whose dimensions do not match the index
+    fn check_query(&self, query: &[f32]) -> Result<()> {
+        if query.len() != self.dimensions {
+            return Err(RuvectorError::DimensionMismatch {
+                expected: self.dimensions,
+                actual: query.len(),
+            });
+        }
+        Ok(())
     }
 }
 
@@ -269,19 +1062,14 @@ impl VectorIndex for HnswIndex {
             });
         }
 
-        let mut inner = self.inner.write();
-        let idx = inner.next_idx;
-        inner.next_idx += 1;
+        {
+            let mut inner = self.inner.write();
+            inner.insert(id, &vector, self.metric)?;
 
-        // Insert into HNSW graph using insert_data
-        inner.hnsw.insert_data(&vector, idx);
-
-        // Store mappings
-        inner.vectors.insert(id.clone(), vector);
-        inner.id_to_idx.insert(id.clone(), idx);
-        inner.idx_to_id.insert(idx, id);
-
-        Ok(())
+            #[cfg(feature = "storage")]
+            inner.flush_log()?;
+        }
+        self.after_write()
     }
 
     fn add_batch(&mut self, entries: Vec<(VectorId, Vec<f32>)>) -> Result<()> {
@@ -295,65 +1083,138 @@ impl VectorIndex for HnswIndex {
             }
         }
 
-        let mut inner = self.inner.write();
-
-        // Prepare batch data for parallel insertion
-        use rayon::prelude::*;
+        {
+            let mut inner = self.inner.write();
+            for (id, vector) in entries {
+                inner.insert(id, &vector, self.metric)?;
+            }
 
-        // First, assign indices and collect vector data
-        let data_with_ids: Vec<_> = entries
-            .iter()
-            .enumerate()
-            .map(|(i, (id, vector))| {
-                let idx = inner.next_idx + i;
-                (id.clone(), idx, vector.clone())
-            })
-            .collect();
+            #[cfg(feature = "storage")]
+            inner.flush_log()?;
+        }
+        self.after_write()
+    }
 
-        // Update next_idx
-        inner.next_idx += entries.len();
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>> {
+        // Use configured ef_search
+        self.search_with_ef(query, k, self.config.ef_search)
+    }
 
-        // Insert into HNSW sequentially
-        // Note: Using sequential insertion to avoid Send requirements with RwLock guard
-        // For large batches, consider restructuring to use hnsw_rs parallel_insert
-        for (_id, idx, vector) in &data_with_ids {
-            inner.hnsw.insert_data(vector, *idx);
+    fn stats(&self) -> IndexStats {
+        let inner = self.inner.read();
+        IndexStats {
+            vectors: inner.id_to_slot.len(),
+            memory_bytes: inner.memory_bytes(),
+            tombstone_ratio: inner.tombstone_ratio(),
+            ef_search: Some(self.config.ef_search),
         }
+    }
 
-        // Store mappings
-        for (id, idx, vector) in data_with_ids {
-            inner.vectors.insert(id.clone(), vector);
-            inner.id_to_idx.insert(id.clone(), idx);
-            inner.idx_to_id.insert(idx, id);
-        }
+    fn estimate_recall(&self, samples: usize, k: usize) -> Result<Option<f32>> {
+        // Queries are stored vectors spread over the id map
+        let queries: Vec<(VectorId, Vec<f32>)> = {
+            let inner = self.inner.read();
+            let live = inner.id_to_slot.len();
+            if live < 2 || samples == 0 || k == 0 {
+                return Ok(None);
+            }
+            inner
+                .id_to_slot
+                .iter()
+                .step_by((live / samples).max(1))
+                .take(samples)
+                .map(|(id, &slot)| (id.clone(), inner.vectors.get(slot).to_vec()))
+                .collect()
+        };
 
-        Ok(())
+        super::sampled_recall(self, &queries, k)
     }
 
-    fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>> {
-        // Use configured ef_search
-        self.search_with_ef(query, k, self.config.ef_search)
+    fn search_with_params(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_accepting(query, k, params, |_| true)
+    }
+
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_accepting(query, k, params, filter)
+    }
+
+    fn search_among(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        ids: &[VectorId],
+    ) -> Result<Vec<SearchResult>> {
+        self.check_query(query)?;
+        let inner = self.inner.read();
+        let slots = ids
+            .iter()
+            .filter_map(|id| inner.id_to_slot.get(id).copied());
+        self.scan(&inner, query, k, params, slots)
     }
 
     fn remove(&mut self, id: &VectorId) -> Result<bool> {
-        let mut inner = self.inner.write();
+        let removed = {
+            let mut inner = self.inner.write();
+            let removed = inner.remove(id, self.metric)?;
 
-        // Note: hnsw_rs doesn't support direct deletion
-        // We remove from our mappings but the graph structure remains
-        // This is a known limitation of HNSW
-        let removed = inner.vectors.remove(id).is_some();
+            #[cfg(feature = "storage")]
+            inner.flush_log()?;
 
+            removed
+        };
         if removed {
-            if let Some((_, idx)) = inner.id_to_idx.remove(id) {
-                inner.idx_to_id.remove(&idx);
-            }
+            self.after_write()?;
         }
 
         Ok(removed)
     }
 
     fn len(&self) -> usize {
-        self.inner.read().vectors.len()
+        self.inner.read().id_to_slot.len()
+    }
+
+    fn checkpoint(&self) -> Result<()> {
+        #[cfg(feature = "storage")]
+        {
+            let _maintenance = self.maintenance.lock.lock();
+            Self::write_checkpoint(&self.inner, self.dimensions, self.metric, &self.config)?;
+        }
+        Ok(())
+    }
+
+    fn export_graph_part(&self, start: u32, max_slots: usize) -> Result<Option<GraphPart>> {
+        let inner = self.inner.read();
+        let len = inner.graph.len();
+        if start as usize >= len || max_slots == 0 {
+            return Ok(None);
+        }
+        let end = len.min(start as usize + max_slots);
+
+        Ok(Some(GraphPart {
+            start,
+            ids: inner.ids[start as usize..end].to_vec(),
+            links: (start..end as u32)
+                .map(|slot| {
+                    (0..=inner.graph.level(slot))
+                        .map(|layer| inner.graph.neighbors(slot, layer).to_vec())
+                        .collect()
+                })
+                .collect(),
+            entry_point: inner.graph.entry_point,
+            max_level: inner.graph.max_level,
+        }))
     }
 }
 
@@ -370,6 +1231,22 @@ mod tests {
             .collect()
     }
 
+    /// Log generations in a graph directory, oldest first
+    #[cfg(feature = "storage")]
+    fn log_files(dir: &Path) -> Vec<std::path::PathBuf> {
+        let mut logs: Vec<(u64, std::path::PathBuf)> = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name()?.to_str()?;
+                let generation = name.strip_prefix("graph.")?.strip_suffix(".log")?;
+                Some((generation.parse().ok()?, path))
+            })
+            .collect();
+        logs.sort();
+        logs.into_iter().map(|(_, path)| path).collect()
+    }
+
     fn normalize_vector(v: &[f32]) -> Vec<f32> {
         let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
         if norm > 0.0 {
@@ -394,6 +1271,7 @@ mod tests {
             ef_construction: 100,
             ef_search: 50,
             max_elements: 1000,
+            ..Default::default()
         };
 
         let mut index = HnswIndex::new(128, DistanceMetric::Cosine, config)?;
@@ -417,6 +1295,248 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_search_params() -> Result<()> {
+        let config = HnswConfig {
+            m: 8,
+            ef_construction: 50,
+            ef_search: 10,
+            ..Default::default()
+        };
+        let mut index = HnswIndex::new(32, DistanceMetric::Euclidean, config)?;
+        let vectors = generate_random_vectors(300, 32);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+
+        let query = &vectors[42];
+        let exact = index.search_with_params(
+            query,
+            20,
+            &SearchParams {
+                exact: true,
+                ..Default::default()
+            },
+        )?;
+        let mut expected: Vec<_> = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (distance(query, v, DistanceMetric::Euclidean).unwrap(), i))
+            .collect();
+        expected.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let expected_ids: Vec<_> = expected[..20]
+            .iter()
+            .map(|(_, i)| format!("vec_{}", i))
+            .collect();
+        let exact_ids: Vec<_> = exact.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(exact_ids, expected_ids);
+
+        // A wide beam finds the same neighbors as the exhaustive scan
+        let wide = index.search_with_params(
+            query,
+            20,
+            &SearchParams {
+                ef_search: Some(300),
+                ..Default::default()
+            },
+        )?;
+        let wide_ids: Vec<_> = wide.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(wide_ids, expected_ids);
+
+        let threshold = exact[4].score;
+        let within = index.search_with_params(
+            query,
+            20,
+            &SearchParams {
+                score_threshold: Some(threshold),
+                ef_search: Some(300),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(within.len(), 5);
+        assert!(within.iter().all(|r| r.score <= threshold));
+
+        // The beam never grows past the live nodes, however many candidates
+        // are asked for
+        let oversampled = index.search_with_params(
+            query,
+            20,
+            &SearchParams {
+                oversampling: 1e38,
+                ..Default::default()
+            },
+        )?;
+        let oversampled_ids: Vec<_> = oversampled.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(oversampled_ids, expected_ids);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantized_traversal_rescores() -> Result<()> {
+        let config = HnswConfig {
+            m: 8,
+            ef_construction: 50,
+            ef_search: 50,
+            ..Default::default()
+        };
+        let mut index = HnswIndex::new(64, DistanceMetric::Euclidean, config)?;
+        index.set_quantization(&QuantizationConfig::Scalar)?;
+        let vectors = generate_random_vectors(500, 64);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        // Replacing a vector re-encodes its codes
+        index.add("vec_7".to_string(), vectors[8].clone())?;
+
+        assert!(index.quantized_memory_bytes() * 3 < 500 * 64 * 4);
+        // Only the codes stay on the heap
+        #[cfg(feature = "storage")]
+        assert_eq!(index.inner.read().vectors.heap_bytes(), 0);
+
+        let params = SearchParams {
+            oversampling: 4.0,
+            ..Default::default()
+        };
+        let query = &vectors[42];
+        let exact = index.search_with_params(
+            query,
+            10,
+            &SearchParams {
+                exact: true,
+                ..Default::default()
+            },
+        )?;
+        let results = index.search_with_params(query, 10, &params)?;
+        assert_eq!(results[0].id, "vec_42");
+        // Rescored candidates carry full-precision distances
+        for result in &results {
+            let slot = index.inner.read().id_to_slot[&result.id];
+            let stored = index.inner.read().vectors.get(slot).to_vec();
+            let exact_score = distance(query, &stored, DistanceMetric::Euclidean)?;
+            assert_eq!(result.score, exact_score);
+        }
+        let found = results
+            .iter()
+            .filter(|r| exact.iter().any(|e| e.id == r.id))
+            .count();
+        assert!(found >= 8, "recall too low: {}/10", found);
+
+        let unscored = index.search_with_params(
+            query,
+            10,
+            &SearchParams {
+                rescore: false,
+                ..params.clone()
+            },
+        )?;
+        assert_eq!(unscored.len(), 10);
+
+        let replaced = index.search_with_params(&vectors[8], 2, &params)?;
+        let mut ids: Vec<_> = replaced.iter().map(|r| r.id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, ["vec_7", "vec_8"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_quantized_traversal() -> Result<()> {
+        let config = HnswConfig {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 100,
+            ..Default::default()
+        };
+        let mut index = HnswIndex::new(64, DistanceMetric::Cosine, config)?;
+        let vectors: Vec<Vec<f32>> = generate_random_vectors(300, 64)
+            .into_iter()
+            .map(|v| v.into_iter().map(|x| x - 0.5).collect())
+            .collect();
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        // Codes are built for vectors indexed before quantization was enabled
+        index.set_quantization(&QuantizationConfig::Binary)?;
+        assert_eq!(index.quantized_memory_bytes(), 300 * 8);
+
+        let params = SearchParams {
+            oversampling: 10.0,
+            ..Default::default()
+        };
+        for i in [0, 99, 250] {
+            let results = index.search_with_params(&vectors[i], 1, &params)?;
+            assert_eq!(results[0].id, format!("vec_{}", i));
+        }
+
+        index.set_quantization(&QuantizationConfig::None)?;
+        assert_eq!(index.quantized_memory_bytes(), 0);
+
+        // Product quantization falls back to scalar codes
+        index.set_quantization(&QuantizationConfig::Scalar)?;
+        let scalar_bytes = index.quantized_memory_bytes();
+        index.set_quantization(&QuantizationConfig::Product {
+            subspaces: 8,
+            k: 256,
+        })?;
+        assert_eq!(index.quantized_memory_bytes(), scalar_bytes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quantized_score_threshold() -> Result<()> {
+        let vectors: Vec<Vec<f32>> = generate_random_vectors(300, 32)
+            .into_iter()
+            .map(|v| v.into_iter().map(|x| x - 0.5).collect())
+            .collect();
+        let query = &vectors[5];
+
+        for quantization in [QuantizationConfig::Scalar, QuantizationConfig::Binary] {
+            let mut index = HnswIndex::new(32, DistanceMetric::Euclidean, HnswConfig::default())?;
+            for (i, vector) in vectors.iter().enumerate() {
+                index.add(format!("vec_{}", i), vector.clone())?;
+            }
+            index.set_quantization(&quantization)?;
+
+            let exact = index.search_with_params(
+                query,
+                20,
+                &SearchParams {
+                    exact: true,
+                    ..Default::default()
+                },
+            )?;
+            let threshold = exact[9].score;
+
+            // Scores are full-precision distances whether or not the
+            // candidates were re-ranked, so the threshold cuts the same way
+            for rescore in [true, false] {
+                let results = index.search_with_params(
+                    query,
+                    20,
+                    &SearchParams {
+                        ef_search: Some(300),
+                        rescore,
+                        oversampling: 4.0,
+                        score_threshold: Some(threshold),
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(results[0].id, "vec_5");
+                assert!(results.len() <= 10);
+                for result in &results {
+                    let i: usize = result.id["vec_".len()..].parse().unwrap();
+                    let exact_score = distance(query, &vectors[i], DistanceMetric::Euclidean)?;
+                    assert_eq!(result.score, exact_score);
+                    assert!(result.score <= threshold);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_hnsw_batch_insert() -> Result<()> {
         let config = HnswConfig::default();
@@ -442,6 +1562,7 @@ mod tests {
             ef_construction: 100,
             ef_search: 50,
             max_elements: 1000,
+            ..Default::default()
         };
 
         let mut index = HnswIndex::new(128, DistanceMetric::Cosine, config)?;
@@ -470,6 +1591,392 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_graph_parts_import() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HnswConfig::default();
+        let mut index = HnswIndex::new(16, DistanceMetric::Euclidean, config.clone())?;
+        let vectors = generate_random_vectors(100, 16);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        index.remove(&"vec_1".to_string())?;
+
+        let mut imported =
+            HnswIndex::create(dir.path(), 16, DistanceMetric::Euclidean, config.clone())?;
+        let mut start = 0;
+        while let Some(part) = index.export_graph_part(start, 30)? {
+            assert!(part.ids.len() <= 30);
+            start = part.end();
+            // Leave out "vec_2", as if it was not in the restored storage
+            let part_vectors = part
+                .ids
+                .iter()
+                .map(|id| {
+                    id.as_ref()
+                        .filter(|id| *id != "vec_2")
+                        .map(|id| vectors[id[4..].parse::<usize>().unwrap()].clone())
+                })
+                .collect();
+            imported.import_part(part, part_vectors)?;
+        }
+        assert_eq!(start, 100);
+        let part = index.export_graph_part(0, 30)?.unwrap();
+        assert!(imported.import_part(part, vec![None; 30]).is_err());
+        imported.finish_import()?;
+        drop(imported);
+
+        let reopened = HnswIndex::open(dir.path(), 16, DistanceMetric::Euclidean, config)?
+            .expect("imported graph was persisted");
+        assert_eq!(reopened.len(), 98);
+        assert!(!reopened.contains("vec_2"));
+        let results = reopened.search(&vectors[10], 1)?;
+        assert_eq!(results[0].id, "vec_10");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_persisted_graph_reopens_without_rebuild() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let graph_dir = dir.path().join("test.db.hnsw");
+        let config = HnswConfig {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 50,
+            max_elements: 1000,
+            ..Default::default()
+        };
+
+        let vectors: Vec<_> = generate_random_vectors(200, 32)
+            .iter()
+            .map(|v| normalize_vector(v))
+            .collect();
+        let query = vectors[7].clone();
+
+        let expected = {
+            let mut index =
+                HnswIndex::create(&graph_dir, 32, DistanceMetric::Cosine, config.clone())?;
+            assert!(index.is_persistent());
+            for (i, vector) in vectors.iter().enumerate().take(150) {
+                index.add(format!("vec_{}", i), vector.clone())?;
+            }
+            index.checkpoint()?;
+            let log_len = || {
+                let logs = log_files(&graph_dir);
+                assert_eq!(logs.len(), 1, "covered generations are deleted");
+                std::fs::metadata(&logs[0]).unwrap().len()
+            };
+            assert_eq!(log_len(), 0);
+            // These only reach the log, not the snapshot, each as soon as
+            // its call returns
+            index.add("vec_150".to_string(), vectors[150].clone())?;
+            assert!(log_len() > 0);
+            for (i, vector) in vectors.iter().enumerate().skip(151) {
+                index.add(format!("vec_{}", i), vector.clone())?;
+            }
+            index.remove(&"vec_3".to_string())?;
+            // A replacement logs the old node's deletion, then the new node
+            index.add("vec_5".to_string(), vectors[6].clone())?;
+            index.search(&query, 10)?
+        };
+
+        let index = HnswIndex::open(&graph_dir, 32, DistanceMetric::Cosine, config.clone())?
+            .expect("graph should have been persisted");
+        assert_eq!(index.len(), 199);
+        assert!(!index.contains("vec_3"));
+        let mut replaced: Vec<_> = index
+            .search(&vectors[6], 2)?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        replaced.sort_unstable();
+        assert_eq!(replaced, ["vec_5", "vec_6"]);
+
+        let results = index.search(&query, 10)?;
+        let ids = |r: &[SearchResult]| r.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&results), ids(&expected));
+        drop(index);
+
+        // A different construction config cannot reuse the graph
+        let other = HnswConfig { m: 8, ..config };
+        assert!(HnswIndex::open(&graph_dir, 32, DistanceMetric::Cosine, other)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_persisted_graph_ignores_torn_log_tail() -> Result<()> {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let graph_dir = dir.path().join("test.db.hnsw");
+        let config = HnswConfig::default();
+
+        {
+            let mut index =
+                HnswIndex::create(&graph_dir, 8, DistanceMetric::Euclidean, config.clone())?;
+            for i in 0..20 {
+                index.add(format!("vec_{}", i), vec![i as f32; 8])?;
+            }
+        }
+
+        // Simulate a crash in the middle of writing a record
+        let mut log = std::fs::OpenOptions::new()
+            .append(true)
+            .open(log_files(&graph_dir).pop().unwrap())?;
+        log.write_all(&[42, 0, 0, 0, 1, 2, 3])?;
+        drop(log);
+
+        let index = HnswIndex::open(&graph_dir, 8, DistanceMetric::Euclidean, config)?
+            .expect("graph should have been persisted");
+        assert_eq!(index.len(), 20);
+        let results = index.search(&[5.0; 8], 1)?;
+        assert_eq!(results[0].id, "vec_5");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_persisted_graph_skips_links_past_lost_records() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let graph_dir = dir.path().join("test.db.hnsw");
+        let config = HnswConfig::default();
+
+        {
+            let mut index =
+                HnswIndex::create(&graph_dir, 8, DistanceMetric::Euclidean, config.clone())?;
+            for i in 0..20 {
+                index.add(format!("vec_{}", i), vec![i as f32; 8])?;
+            }
+        }
+
+        // Lose the last insert record, whose lists already reached `links.bin`
+        let path = log_files(&graph_dir).pop().unwrap();
+        let bytes = std::fs::read(&path)?;
+        let mut offset = 0;
+        let mut last = 0;
+        while offset < bytes.len() {
+            last = offset;
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            offset += 12 + len as usize;
+        }
+        std::fs::write(&path, &bytes[..last])?;
+
+        let mut index = HnswIndex::open(&graph_dir, 8, DistanceMetric::Euclidean, config)?
+            .expect("graph should have been persisted");
+        assert_eq!(index.len(), 19);
+        let results = index.search(&[19.0; 8], 3)?;
+        assert_eq!(results[0].id, "vec_18");
+
+        // Pushing slot 19 again overwrites its stale record
+        index.add("vec_19".to_string(), vec![19.0; 8])?;
+        let results = index.search(&[19.0; 8], 1)?;
+        assert_eq!(results[0].id, "vec_19");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deleted_nodes_are_skipped_and_relinked() -> Result<()> {
+        let config = HnswConfig {
+            compaction_threshold: 0.0,
+            ..HnswConfig::default()
+        };
+        let mut index = HnswIndex::new(16, DistanceMetric::Euclidean, config)?;
+
+        let vectors = generate_random_vectors(400, 16);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        for i in (0..400).step_by(2) {
+            assert!(index.remove(&format!("vec_{}", i))?);
+        }
+        assert!(!index.remove(&"vec_0".to_string())?);
+        assert_eq!(index.len(), 200);
+        assert!((index.tombstone_ratio() - 0.5).abs() < f32::EPSILON);
+
+        // Every query still gets k live results, and nearest ones at that
+        for i in (1..400).step_by(40) {
+            let results = index.search(&vectors[i], 10)?;
+            assert_eq!(results.len(), 10);
+            assert_eq!(results[0].id, format!("vec_{}", i));
+            assert!(results.iter().all(|r| index.contains(&r.id)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_stats_and_recall_estimate() -> Result<()> {
+        let config = HnswConfig {
+            ef_search: 64,
+            compaction_threshold: 0.0,
+            ..HnswConfig::default()
+        };
+        let mut index = HnswIndex::new(16, DistanceMetric::Euclidean, config)?;
+        assert_eq!(index.estimate_recall(8, 10)?, None);
+
+        let vectors = generate_random_vectors(300, 16);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        for i in 0..30 {
+            index.remove(&format!("vec_{}", i))?;
+        }
+
+        let stats = index.stats();
+        assert_eq!(stats.vectors, 270);
+        assert_eq!(stats.ef_search, Some(64));
+        assert!((stats.tombstone_ratio - 0.1).abs() < 1e-6);
+        // At least the raw vectors and one base-layer list per node
+        assert!(stats.memory_bytes > 300 * 16 * 4 + 300 * std::mem::size_of::<Vec<u32>>());
+
+        let recall = index.estimate_recall(16, 10)?.unwrap();
+        assert!(recall > 0.8 && recall <= 1.0, "recall {}", recall);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compaction_reuses_slots() -> Result<()> {
+        let config = HnswConfig {
+            compaction_threshold: 0.0,
+            ..HnswConfig::default()
+        };
+        let mut index = HnswIndex::new(16, DistanceMetric::Euclidean, config)?;
+
+        let vectors = generate_random_vectors(200, 16);
+        for (i, vector) in vectors.iter().enumerate().take(100) {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        for i in 0..50 {
+            index.remove(&format!("vec_{}", i))?;
+        }
+
+        index.compact()?;
+        assert_eq!(index.tombstone_ratio(), 0.0);
+        assert_eq!(index.inner.read().free.len(), 50);
+
+        for (i, vector) in vectors.iter().enumerate().skip(100).take(50) {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        assert_eq!(index.inner.read().graph.len(), 100);
+        assert_eq!(index.len(), 100);
+
+        for i in (50..150).step_by(10) {
+            let results = index.search(&vectors[i], 5)?;
+            assert_eq!(results[0].id, format!("vec_{}", i));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compaction_in_batches_alongside_searches() -> Result<()> {
+        let config = HnswConfig {
+            compaction_threshold: 0.0,
+            ..HnswConfig::default()
+        };
+        let mut index = HnswIndex::new(8, DistanceMetric::Euclidean, config)?;
+
+        let count = COMPACTION_BATCH_SLOTS as usize * 3;
+        let vectors = generate_random_vectors(count, 8);
+        for (i, vector) in vectors.iter().enumerate() {
+            index.add(format!("vec_{}", i), vector.clone())?;
+        }
+        for i in (0..count).step_by(2) {
+            index.remove(&format!("vec_{}", i))?;
+        }
+
+        // The write lock is released between batches, so searches keep
+        // returning live results while the graph is repaired
+        std::thread::scope(|scope| -> Result<()> {
+            let compaction = scope.spawn(|| index.compact());
+            for i in (1..count).step_by(97) {
+                let results = index.search(&vectors[i], 5)?;
+                assert!(results.iter().all(|r| index.contains(&r.id)));
+            }
+            compaction.join().unwrap()
+        })?;
+
+        assert_eq!(index.tombstone_ratio(), 0.0);
+        assert_eq!(index.inner.read().free.len(), count / 2);
+        // Odd points are the live ones
+        for i in (1..count).step_by(102) {
+            assert_eq!(index.search(&vectors[i], 1)?[0].id, format!("vec_{}", i));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_upserts_reclaim_replaced_nodes() -> Result<()> {
+        let mut index = HnswIndex::new(8, DistanceMetric::Euclidean, HnswConfig::default())?;
+
+        for round in 0..20 {
+            for i in 0..50 {
+                index.add(format!("vec_{}", i), vec![(i * 20 + round) as f32; 8])?;
+            }
+        }
+        index.compact()?;
+
+        // Background compaction kept replaced vectors from piling up
+        assert_eq!(index.len(), 50);
+        assert!(index.inner.read().graph.len() < 50 * 20 / 2);
+        let results = index.search(&[(7 * 20 + 19) as f32; 8], 1)?;
+        assert_eq!(results[0].id, "vec_7");
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_persisted_deletes_and_compaction_survive_reopen() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let graph_dir = dir.path().join("test.db.hnsw");
+        let config = HnswConfig {
+            compaction_threshold: 0.0,
+            ..HnswConfig::default()
+        };
+        let vectors = generate_random_vectors(120, 16);
+
+        {
+            let mut index =
+                HnswIndex::create(&graph_dir, 16, DistanceMetric::Euclidean, config.clone())?;
+            for (i, vector) in vectors.iter().enumerate().take(100) {
+                index.add(format!("vec_{}", i), vector.clone())?;
+            }
+            for i in 0..30 {
+                index.remove(&format!("vec_{}", i))?;
+            }
+            index.compact()?;
+            assert_eq!(index.inner.read().free.len(), 30);
+            assert_eq!(log_files(&graph_dir).len(), 1);
+            // Logged after the compaction checkpoint, into freed slots
+            for (i, vector) in vectors.iter().enumerate().skip(100) {
+                index.add(format!("vec_{}", i), vector.clone())?;
+            }
+            index.remove(&"vec_50".to_string())?;
+        }
+
+        let index = HnswIndex::open(&graph_dir, 16, DistanceMetric::Euclidean, config)?
+            .expect("graph should have been persisted");
+        assert_eq!(index.len(), 89);
+        assert_eq!(index.inner.read().graph.len(), 100);
+        assert!(!index.contains("vec_10"));
+        assert!(!index.contains("vec_50"));
+        for i in [31, 75, 105, 119] {
+            let results = index.search(&vectors[i], 3)?;
+            assert_eq!(results[0].id, format!("vec_{}", i));
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_dimension_mismatch() -> Result<()> {
         let config = HnswConfig::default();
@@ -0,0 +1,663 @@
+//! Layered HNSW graph over dense `u32` slots
+//!
+//! The graph only knows about slots and neighbor lists. Vectors, ids and
+//! distance computation live in the owning [`HnswIndex`](super::HnswIndex),
+//! which hands distance closures to the insert and search routines. The
+//! base layer lives in a fixed-stride [`LinkStore`], which persistent indexes
+//! keep in a mapped file; the upper layers, which only a small fraction of
+//! the nodes reach, are kept on the heap. Keeping the topology separate from
+//! everything else is what lets it be persisted and reloaded without
+//! re-running construction.
+
+use super::links::LinkStore;
+use crate::error::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ops::Range;
+
+/// Upper bound on the number of layers a node can be assigned to
+pub(crate) const MAX_LEVEL: usize = 16;
+
+/// Seed for level assignment so builds are reproducible
+const LEVEL_SEED: u64 = 0x5eed_4e5f;
+
+/// A slot together with its distance to the current query
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Candidate {
+    pub slot: u32,
+    pub dist: f32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then_with(|| self.slot.cmp(&other.slot))
+    }
+}
+
+/// Maximum base-layer neighbor list length for a graph built with `m`
+pub(crate) fn base_layer_links(m: usize) -> usize {
+    m.max(2) * 2
+}
+
+/// HNSW topology: neighbor lists, entry point and construction parameters
+pub(crate) struct HnswGraph {
+    /// Layer 0 neighbor lists of every slot
+    base: LinkStore,
+    /// Lists on layers `1..=level` of the nodes above the base layer
+    upper: HashMap<u32, Vec<Vec<u32>>>,
+    pub entry_point: Option<u32>,
+    pub max_level: usize,
+    m: usize,
+    m0: usize,
+    ef_construction: usize,
+    level_mult: f64,
+    rng: StdRng,
+}
+
+impl HnswGraph {
+    /// Create an empty graph with `m` links per upper layer and `2 * m` on layer 0
+    pub fn new(m: usize, ef_construction: usize) -> Self {
+        let m = m.max(2);
+        Self {
+            base: LinkStore::new(base_layer_links(m)),
+            upper: HashMap::new(),
+            entry_point: None,
+            max_level: 0,
+            m,
+            m0: base_layer_links(m),
+            ef_construction: ef_construction.max(m),
+            level_mult: 1.0 / (m as f64).ln(),
+            rng: StdRng::seed_from_u64(LEVEL_SEED),
+        }
+    }
+
+    /// Reassemble a graph from persisted topology
+    ///
+    /// `base` must hold lists of up to [`base_layer_links`] entries.
+    pub fn from_parts(
+        m: usize,
+        ef_construction: usize,
+        base: LinkStore,
+        upper: HashMap<u32, Vec<Vec<u32>>>,
+        entry_point: Option<u32>,
+        max_level: usize,
+    ) -> Self {
+        let mut graph = Self::new(m, ef_construction);
+        // Reseed so a reopened graph does not replay the same level sequence
+        graph.rng = StdRng::seed_from_u64(LEVEL_SEED ^ base.len() as u64);
+        graph.base = base;
+        graph.upper = upper;
+        graph.entry_point = entry_point;
+        graph.max_level = max_level;
+        graph
+    }
+
+    /// Number of slots allocated in the graph
+    pub fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    /// Approximate bytes held by the neighbor lists, mapped or on the heap
+    pub fn memory_bytes(&self) -> usize {
+        let lists = std::mem::size_of::<Vec<u32>>();
+        self.base.memory_bytes()
+            + self.upper.capacity() * std::mem::size_of::<(u32, Vec<Vec<u32>>)>()
+            + self
+                .upper
+                .values()
+                .flatten()
+                .map(|links| lists + links.capacity() * std::mem::size_of::<u32>())
+                .sum::<usize>()
+    }
+
+    /// Move the base layer into `file` at byte `offset`, see
+    /// [`LinkStore::move_to_file`]
+    #[cfg(feature = "storage")]
+    pub fn move_to_file(&mut self, file: std::fs::File, offset: usize) -> Result<()> {
+        self.base.move_to_file(file, offset)
+    }
+
+    /// Another handle on the file holding the base layer, if it is in one
+    #[cfg(feature = "storage")]
+    pub fn file_handle(&self) -> Result<Option<std::fs::File>> {
+        self.base.file_handle()
+    }
+
+    /// Highest layer `slot` is present on
+    pub fn level(&self, slot: u32) -> usize {
+        self.upper.get(&slot).map_or(0, Vec::len)
+    }
+
+    /// Nodes above the base layer with their lists on layers `1..=level`
+    pub fn upper_nodes(&self) -> impl Iterator<Item = (u32, &[Vec<u32>])> + '_ {
+        self.upper
+            .iter()
+            .map(|(&slot, lists)| (slot, lists.as_slice()))
+    }
+
+    /// Maximum neighbor list length on `layer`
+    pub fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m0
+        } else {
+            self.m
+        }
+    }
+
+    /// Draw a level from the exponentially decaying HNSW distribution
+    pub fn random_level(&mut self) -> usize {
+        let r: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        ((-r.ln() * self.level_mult) as usize).min(MAX_LEVEL)
+    }
+
+    /// Make room for `additional` more nodes, so pushing them cannot fail
+    pub fn reserve(&mut self, additional: usize) -> Result<()> {
+        self.base.reserve(self.len() + additional)
+    }
+
+    /// Append an unlinked node and return its slot
+    pub fn push_node(&mut self, level: usize) -> Result<u32> {
+        let slot = self.base.push()?;
+        self.set_level(slot, level);
+        Ok(slot)
+    }
+
+    /// Reset a freed slot to an unlinked node on layers `0..=level`
+    pub fn reset_node(&mut self, slot: u32, level: usize) {
+        self.base.set(slot, &[]);
+        self.set_level(slot, level);
+    }
+
+    fn set_level(&mut self, slot: u32, level: usize) {
+        if level == 0 {
+            self.upper.remove(&slot);
+        } else {
+            self.upper.insert(slot, vec![Vec::new(); level]);
+        }
+    }
+
+    /// Replace the neighbor list of `slot` on `layer`, if the node is on it
+    pub fn set_links(&mut self, slot: u32, layer: usize, links: &[u32]) {
+        if layer == 0 {
+            self.base.set(slot, links);
+        } else if let Some(list) = self
+            .upper
+            .get_mut(&slot)
+            .and_then(|lists| lists.get_mut(layer - 1))
+        {
+            list.clear();
+            list.extend_from_slice(links);
+        }
+    }
+
+    /// Link an already pushed node into the graph
+    ///
+    /// `dist(a, b)` must return the distance between two slots, and only
+    /// slots for which `alive` returns true are chosen as neighbors. Returns
+    /// the `(slot, layer)` pairs of *other* nodes whose neighbor lists changed
+    /// so callers can persist them incrementally.
+    pub fn link<L, D>(&mut self, slot: u32, alive: L, dist: D) -> Vec<(u32, usize)>
+    where
+        L: Fn(u32) -> bool,
+        D: Fn(u32, u32) -> f32,
+    {
+        let level = self.level(slot);
+        let mut touched = Vec::new();
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(slot);
+            self.max_level = level;
+            return touched;
+        };
+
+        let to_new = |other: u32| dist(slot, other);
+        let mut current = vec![Candidate {
+            slot: entry,
+            dist: to_new(entry),
+        }];
+
+        // Greedy descent through the layers above the new node
+        for layer in (level + 1..=self.max_level).rev() {
+            current = vec![self.greedy_closest(current[0], layer, &to_new)];
+        }
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let found = self.search_layer(&current, self.ef_construction, layer, &to_new, |o| {
+                o != slot && alive(o)
+            });
+            if found.is_empty() {
+                continue;
+            }
+            let selected = self.select_neighbors(&found, self.max_links(layer), &dist);
+            self.set_links(slot, layer, &selected);
+
+            for neighbor in selected {
+                if self.add_link(neighbor, slot, layer, &dist) {
+                    touched.push((neighbor, layer));
+                }
+            }
+
+            current = found;
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry_point = Some(slot);
+        }
+
+        touched
+    }
+
+    /// Detach a deleted node from the neighbors it links to
+    ///
+    /// Every neighbor that links back to `slot` drops that link and is
+    /// relinked using the deleted node's other neighbors as candidates, so
+    /// the region stays navigable. The deleted node keeps its own out-links
+    /// until compaction, letting searches that still reach it pass through.
+    /// Returns the `(slot, layer)` pairs whose neighbor lists changed.
+    pub fn unlink<L, D>(&mut self, slot: u32, alive: L, dist: D) -> Vec<(u32, usize)>
+    where
+        L: Fn(u32) -> bool,
+        D: Fn(u32, u32) -> f32,
+    {
+        let mut touched = Vec::new();
+        let links: Vec<Vec<u32>> = (0..=self.level(slot))
+            .map(|layer| self.links(slot, layer).collect())
+            .collect();
+
+        for (layer, neighbors) in links.iter().enumerate() {
+            for &neighbor in neighbors {
+                if neighbor == slot || !alive(neighbor) {
+                    continue;
+                }
+                if !self.neighbors(neighbor, layer).contains(&slot) {
+                    continue;
+                }
+
+                let mut seen = HashSet::new();
+                let mut candidates: Vec<Candidate> = self
+                    .links(neighbor, layer)
+                    .chain(neighbors.iter().copied())
+                    .filter(|&o| o != slot && o != neighbor && alive(o) && seen.insert(o))
+                    .map(|o| Candidate {
+                        slot: o,
+                        dist: dist(neighbor, o),
+                    })
+                    .collect();
+                candidates.sort();
+
+                let relinked = self.select_neighbors(&candidates, self.max_links(layer), &dist);
+                self.set_links(neighbor, layer, &relinked);
+                touched.push((neighbor, layer));
+            }
+        }
+
+        if self.entry_point == Some(slot) {
+            self.reselect_entry(|o| o != slot && alive(o));
+        }
+
+        touched
+    }
+
+    /// Rebuild every neighbor list of the nodes in `slots` that still refers
+    /// to a dead slot
+    ///
+    /// Dead links are replaced with the best of the node's remaining links
+    /// and the live links of the dead neighbors it pointed to. Nodes left
+    /// without any candidates search the graph around themselves instead.
+    /// Once every slot has been repaired, no live node links to a dead one,
+    /// so dead slots can be cleared and reused.
+    pub fn repair<L, D>(&mut self, slots: Range<u32>, alive: L, dist: D)
+    where
+        L: Fn(u32) -> bool,
+        D: Fn(u32, u32) -> f32,
+    {
+        let len = self.len() as u32;
+        for slot in slots.start..slots.end.min(len) {
+            if !alive(slot) {
+                continue;
+            }
+            for layer in 0..=self.level(slot) {
+                if self
+                    .neighbors(slot, layer)
+                    .iter()
+                    .all(|&o| o < len && alive(o))
+                {
+                    continue;
+                }
+
+                let mut seen = HashSet::new();
+                let mut candidates: Vec<Candidate> = Vec::new();
+                let mut consider = |o: u32| {
+                    if o != slot && alive(o) && seen.insert(o) {
+                        candidates.push(Candidate {
+                            slot: o,
+                            dist: dist(slot, o),
+                        });
+                    }
+                };
+                for neighbor in self.links(slot, layer) {
+                    if alive(neighbor) {
+                        consider(neighbor);
+                    } else {
+                        self.links(neighbor, layer).for_each(&mut consider);
+                    }
+                }
+
+                if candidates.is_empty() {
+                    candidates = self.search_around(slot, layer, &alive, &dist);
+                }
+                candidates.sort();
+
+                let selected = self.select_neighbors(&candidates, self.max_links(layer), &dist);
+                self.set_links(slot, layer, &selected);
+                for neighbor in selected {
+                    self.add_link(neighbor, slot, layer, &dist);
+                }
+            }
+        }
+    }
+
+    /// Search `layer` for live neighbor candidates of an existing node
+    fn search_around<L, D>(&self, slot: u32, layer: usize, alive: &L, dist: &D) -> Vec<Candidate>
+    where
+        L: Fn(u32) -> bool,
+        D: Fn(u32, u32) -> f32,
+    {
+        let Some(entry) = self.entry_point.filter(|&e| e != slot) else {
+            return Vec::new();
+        };
+        let to_slot = |o: u32| dist(slot, o);
+        let mut start = Candidate {
+            slot: entry,
+            dist: to_slot(entry),
+        };
+        for upper in (layer + 1..=self.max_level).rev() {
+            start = self.greedy_closest(start, upper, &to_slot);
+        }
+        self.search_layer(&[start], self.ef_construction, layer, &to_slot, |o| {
+            o != slot && alive(o)
+        })
+    }
+
+    /// Drop all links of a node that is no longer referenced
+    pub fn clear_links(&mut self, slot: u32) {
+        for layer in 0..=self.level(slot) {
+            self.set_links(slot, layer, &[]);
+        }
+    }
+
+    /// Pick the live node on the highest layer as the new entry point
+    pub fn reselect_entry<L>(&mut self, alive: L)
+    where
+        L: Fn(u32) -> bool,
+    {
+        // Prefer the highest level, then the lowest slot
+        let best = self
+            .upper
+            .iter()
+            .filter(|(&slot, _)| alive(slot))
+            .map(|(&slot, lists)| (lists.len(), std::cmp::Reverse(slot)))
+            .max()
+            .map(|(_, std::cmp::Reverse(slot))| slot)
+            .or_else(|| (0..self.len() as u32).find(|&slot| alive(slot)));
+        self.entry_point = best;
+        self.max_level = best.map_or(0, |slot| self.level(slot));
+    }
+
+    /// Find up to `ef` closest slots to the query on layer 0
+    ///
+    /// `dist` returns the distance from the query to a slot. Only slots for
+    /// which `accept` returns true are collected as results, but every slot is
+    /// still traversed so rejected nodes keep the graph connected.
+    pub fn search<F, A>(&self, ef: usize, dist: F, accept: A) -> Vec<Candidate>
+    where
+        F: Fn(u32) -> f32,
+        A: Fn(u32) -> bool,
+    {
+        let Some(entry) = self.entry_point else {
+            return Vec::new();
+        };
+
+        let mut current = Candidate {
+            slot: entry,
+            dist: dist(entry),
+        };
+        for layer in (1..=self.max_level).rev() {
+            current = self.greedy_closest(current, layer, &dist);
+        }
+
+        self.search_layer(&[current], ef.max(1), 0, &dist, accept)
+    }
+
+    /// Greedy walk towards the query on a single layer
+    fn greedy_closest<F>(&self, start: Candidate, layer: usize, dist: &F) -> Candidate
+    where
+        F: Fn(u32) -> f32,
+    {
+        let mut best = start;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for neighbor in self.links(best.slot, layer) {
+                let d = dist(neighbor);
+                if d < best.dist {
+                    best = Candidate {
+                        slot: neighbor,
+                        dist: d,
+                    };
+                    improved = true;
+                }
+            }
+        }
+        best
+    }
+
+    /// Beam search on one layer, returning accepted results sorted by distance
+    pub fn search_layer<F, A>(
+        &self,
+        entries: &[Candidate],
+        ef: usize,
+        layer: usize,
+        dist: &F,
+        accept: A,
+    ) -> Vec<Candidate>
+    where
+        F: Fn(u32) -> f32,
+        A: Fn(u32) -> bool,
+    {
+        let mut visited: HashSet<u32> = HashSet::with_capacity(ef.saturating_mul(4).min(self.len()));
+        // Min-heap of frontier nodes, max-heap of the best results so far
+        let mut frontier: BinaryHeap<std::cmp::Reverse<Candidate>> = BinaryHeap::new();
+        let mut results: BinaryHeap<Candidate> = BinaryHeap::new();
+        // Distance bound used to stop expanding once no closer node can be found
+        let mut bound = f32::INFINITY;
+
+        for &entry in entries {
+            if visited.insert(entry.slot) {
+                frontier.push(std::cmp::Reverse(entry));
+                if accept(entry.slot) {
+                    results.push(entry);
+                }
+            }
+        }
+        if results.len() >= ef {
+            bound = results.peek().map_or(f32::INFINITY, |c| c.dist);
+        }
+
+        while let Some(std::cmp::Reverse(current)) = frontier.pop() {
+            if current.dist > bound {
+                break;
+            }
+
+            for neighbor in self.links(current.slot, layer) {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let d = dist(neighbor);
+                if d >= bound {
+                    continue;
+                }
+                let candidate = Candidate {
+                    slot: neighbor,
+                    dist: d,
+                };
+                frontier.push(std::cmp::Reverse(candidate));
+                if accept(neighbor) {
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                    if results.len() >= ef {
+                        bound = results.peek().map_or(f32::INFINITY, |c| c.dist);
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    /// Neighbor list of `slot` on `layer`, empty if the node is not on that layer
+    pub fn neighbors(&self, slot: u32, layer: usize) -> &[u32] {
+        if slot as usize >= self.len() {
+            &[]
+        } else if layer == 0 {
+            self.base.get(slot)
+        } else {
+            self.upper
+                .get(&slot)
+                .and_then(|lists| lists.get(layer - 1))
+                .map_or(&[], Vec::as_slice)
+        }
+    }
+
+    /// Neighbors of `slot` on `layer` that are slots of this graph
+    ///
+    /// A base-layer list written through to its file ahead of a log record
+    /// that was lost in a crash can still name slots past the end.
+    fn links(&self, slot: u32, layer: usize) -> impl Iterator<Item = u32> + '_ {
+        let len = self.len() as u32;
+        self.neighbors(slot, layer)
+            .iter()
+            .copied()
+            .filter(move |&o| o < len)
+    }
+
+    /// Neighbor selection heuristic (Malkov & Yashunin, algorithm 4)
+    ///
+    /// Prefers candidates that are closer to the base node than to any
+    /// already selected neighbor, then back-fills with the pruned ones so
+    /// sparse regions still get a full neighbor list.
+    pub fn select_neighbors<D>(&self, candidates: &[Candidate], m: usize, dist: &D) -> Vec<u32>
+    where
+        D: Fn(u32, u32) -> f32,
+    {
+        let mut selected: Vec<Candidate> = Vec::with_capacity(m);
+        let mut pruned = Vec::new();
+
+        for &candidate in candidates {
+            if selected.len() >= m {
+                break;
+            }
+            let diverse = selected
+                .iter()
+                .all(|s| dist(candidate.slot, s.slot) > candidate.dist);
+            if diverse {
+                selected.push(candidate);
+            } else {
+                pruned.push(candidate);
+            }
+        }
+
+        for candidate in pruned {
+            if selected.len() >= m {
+                break;
+            }
+            selected.push(candidate);
+        }
+
+        selected.into_iter().map(|c| c.slot).collect()
+    }
+
+    /// Add a directed link `from -> to`, shrinking `from`'s list if it overflows
+    ///
+    /// Returns whether `from`'s neighbor list changed.
+    fn add_link<D>(&mut self, from: u32, to: u32, layer: usize, dist: &D) -> bool
+    where
+        D: Fn(u32, u32) -> f32,
+    {
+        let max_links = self.max_links(layer);
+        if layer > self.level(from) {
+            return false;
+        }
+        let mut links: Vec<u32> = self.links(from, layer).collect();
+        if links.contains(&to) {
+            return false;
+        }
+        links.push(to);
+        if links.len() > max_links {
+            let mut candidates: Vec<Candidate> = links
+                .iter()
+                .map(|&slot| Candidate {
+                    slot,
+                    dist: dist(from, slot),
+                })
+                .collect();
+            candidates.sort();
+            links = self.select_neighbors(&candidates, max_links, dist);
+        }
+        self.set_links(from, layer, &links);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_and_search_line() {
+        let points: Vec<f32> = (0..200).map(|i| i as f32).collect();
+        let dist = |a: u32, b: u32| (points[a as usize] - points[b as usize]).abs();
+
+        let mut graph = HnswGraph::new(8, 64);
+        for _ in 0..points.len() {
+            let level = graph.random_level();
+            let slot = graph.push_node(level).unwrap();
+            graph.link(slot, |_| true, dist);
+        }
+
+        let query = 73.2f32;
+        let results = graph.search(16, |slot| (points[slot as usize] - query).abs(), |_| true);
+        assert_eq!(results[0].slot, 73);
+        assert_eq!(results[1].slot, 74);
+
+        // Odd slots are rejected but still traversed
+        let results = graph.search(
+            16,
+            |slot| (points[slot as usize] - query).abs(),
+            |slot| slot % 2 == 0,
+        );
+        assert_eq!(results[0].slot, 74);
+        assert!(results.iter().all(|c| c.slot % 2 == 0));
+    }
+}
@@ -0,0 +1,253 @@
+//! Fixed-stride storage for base-layer neighbor lists
+//!
+//! Every slot owns one record of `1 + max_links` little-endian `u32` words:
+//! the list length followed by the list, padded to the full stride. Records
+//! live either on the heap or in a file that is written and read in place
+//! through a shared memory map, so a persistent graph's base layer never has
+//! to be decoded when it is reopened. Layer 0 holds all nodes and twice the
+//! links of any other layer, which makes it nearly all of the topology.
+
+use crate::error::Result;
+#[cfg(feature = "storage")]
+use crate::error::RuvectorError;
+#[cfg(feature = "storage")]
+use memmap2::{MmapMut, MmapOptions};
+#[cfg(feature = "storage")]
+use std::fs::File;
+
+/// Slots a file-backed store maps at least, so small graphs do not remap
+/// on every insert
+#[cfg(feature = "storage")]
+const MIN_FILE_SLOTS: usize = 1024;
+
+/// Records in a file, written and read through a shared map
+#[cfg(feature = "storage")]
+struct FileLinks {
+    file: File,
+    /// Shared writable map of the first `capacity` records
+    mmap: MmapMut,
+    /// Byte offset of slot 0 in the file
+    offset: usize,
+    len: usize,
+    capacity: usize,
+}
+
+enum Backing {
+    Heap(Vec<u32>),
+    #[cfg(feature = "storage")]
+    File(FileLinks),
+}
+
+/// One bounded neighbor list per slot
+pub(crate) struct LinkStore {
+    /// Longest list a record holds
+    max_links: usize,
+    backing: Backing,
+}
+
+impl LinkStore {
+    /// Create an empty heap store for lists of up to `max_links` entries
+    pub fn new(max_links: usize) -> Self {
+        Self {
+            max_links,
+            backing: Backing::Heap(Vec::new()),
+        }
+    }
+
+    /// Serve the first `len` records stored at byte `offset` of `file`
+    ///
+    /// Anything in the file past those slots is overwritten as new records
+    /// are pushed.
+    #[cfg(feature = "storage")]
+    pub fn open_file(max_links: usize, file: File, offset: usize, len: usize) -> Result<Self> {
+        let stored = (file_len(&file)? as usize).saturating_sub(offset);
+        if stored < len * record_bytes(max_links) {
+            return Err(RuvectorError::StorageError(
+                "link file shorter than expected".to_string(),
+            ));
+        }
+        let mut links = FileLinks::new(file, offset, max_links)?;
+        links.len = len;
+        Ok(Self {
+            max_links,
+            backing: Backing::File(links),
+        })
+    }
+
+    /// Write every record into `file` at byte `offset` and serve them from
+    /// there from now on
+    #[cfg(feature = "storage")]
+    pub fn move_to_file(&mut self, file: File, offset: usize) -> Result<()> {
+        let len = self.len();
+        let mut target = FileLinks::new(file, offset, self.max_links)?;
+        target.reserve(len, self.max_links)?;
+        target.len = len;
+        let mut moved = Self {
+            max_links: self.max_links,
+            backing: Backing::File(target),
+        };
+        for slot in 0..len as u32 {
+            moved.set(slot, self.get(slot));
+        }
+        *self = moved;
+        Ok(())
+    }
+
+    /// Another handle on the backing file, to sync it without borrowing
+    /// the store; `None` for heap stores
+    #[cfg(feature = "storage")]
+    pub fn file_handle(&self) -> Result<Option<File>> {
+        match &self.backing {
+            Backing::File(links) => Ok(Some(links.file.try_clone()?)),
+            Backing::Heap(_) => Ok(None),
+        }
+    }
+
+    /// Number of records stored
+    pub fn len(&self) -> usize {
+        match &self.backing {
+            Backing::Heap(data) => data.len() / self.stride(),
+            #[cfg(feature = "storage")]
+            Backing::File(links) => links.len,
+        }
+    }
+
+    /// Bytes of records, mapped or on the heap
+    pub fn memory_bytes(&self) -> usize {
+        match &self.backing {
+            Backing::Heap(data) => data.capacity() * std::mem::size_of::<u32>(),
+            #[cfg(feature = "storage")]
+            Backing::File(links) => links.len * record_bytes(self.max_links),
+        }
+    }
+
+    /// Neighbor list stored at `slot`
+    #[inline]
+    pub fn get(&self, slot: u32) -> &[u32] {
+        let stride = self.stride();
+        let record = match &self.backing {
+            Backing::Heap(data) => &data[slot as usize * stride..][..stride],
+            #[cfg(feature = "storage")]
+            Backing::File(links) => {
+                let start = links.offset + slot as usize * record_bytes(self.max_links);
+                let bytes = &links.mmap[start..start + record_bytes(self.max_links)];
+                // SAFETY: `FileLinks::new` checked that the target is
+                // little-endian and that `offset` keeps every record 4-byte
+                // aligned in the page-aligned map; the bytes were written as
+                // little-endian u32 values by this store.
+                unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const u32, stride) }
+            }
+        };
+        // A damaged length must not read past the record
+        let len = (record[0] as usize).min(self.max_links);
+        &record[1..1 + len]
+    }
+
+    /// Make room for at least `slots` records
+    pub fn reserve(&mut self, slots: usize) -> Result<()> {
+        let stride = self.stride();
+        match &mut self.backing {
+            Backing::Heap(data) => data.reserve((slots * stride).saturating_sub(data.len())),
+            #[cfg(feature = "storage")]
+            Backing::File(links) => links.reserve(slots, self.max_links)?,
+        }
+        Ok(())
+    }
+
+    /// Append an empty list and return its slot
+    pub fn push(&mut self) -> Result<u32> {
+        let slot = self.len();
+        self.reserve(slot + 1)?;
+        match &mut self.backing {
+            Backing::Heap(data) => data.resize(data.len() + self.max_links + 1, 0),
+            #[cfg(feature = "storage")]
+            Backing::File(links) => links.len = slot + 1,
+        }
+        self.set(slot as u32, &[]);
+        Ok(slot as u32)
+    }
+
+    /// Overwrite the list stored at an existing `slot`
+    ///
+    /// Entries past `max_links` are dropped; the graph never builds longer
+    /// lists.
+    pub fn set(&mut self, slot: u32, links: &[u32]) {
+        debug_assert!(links.len() <= self.max_links);
+        let links = &links[..links.len().min(self.max_links)];
+        let stride = self.stride();
+        match &mut self.backing {
+            Backing::Heap(data) => {
+                let record = &mut data[slot as usize * stride..][..stride];
+                record[0] = links.len() as u32;
+                record[1..1 + links.len()].copy_from_slice(links);
+            }
+            #[cfg(feature = "storage")]
+            Backing::File(file) => {
+                let start = file.offset + slot as usize * record_bytes(self.max_links);
+                let record = &mut file.mmap[start..start + (1 + links.len()) * 4];
+                let words = std::iter::once(links.len() as u32).chain(links.iter().copied());
+                for (bytes, word) in record.chunks_exact_mut(4).zip(words) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    /// Words per record
+    fn stride(&self) -> usize {
+        self.max_links + 1
+    }
+}
+
+#[cfg(feature = "storage")]
+impl FileLinks {
+    fn new(file: File, offset: usize, max_links: usize) -> Result<Self> {
+        if cfg!(target_endian = "big") || offset % std::mem::align_of::<u32>() != 0 {
+            return Err(RuvectorError::StorageError(
+                "file-backed links need a little-endian target and an aligned offset".to_string(),
+            ));
+        }
+        let capacity = ((file_len(&file)? as usize).saturating_sub(offset)
+            / record_bytes(max_links))
+        .max(MIN_FILE_SLOTS);
+        file.set_len(file_len(&file)?.max((offset + capacity * record_bytes(max_links)) as u64))?;
+        let mmap = map(&file, offset + capacity * record_bytes(max_links))?;
+        Ok(Self {
+            file,
+            mmap,
+            offset,
+            len: 0,
+            capacity,
+        })
+    }
+
+    /// Make room for at least `slots` records, growing the file and map
+    fn reserve(&mut self, slots: usize, max_links: usize) -> Result<()> {
+        if slots <= self.capacity {
+            return Ok(());
+        }
+        let capacity = slots.max(self.capacity * 2);
+        let total = self.offset + capacity * record_bytes(max_links);
+        self.file.set_len(total as u64)?;
+        self.mmap = map(&self.file, total)?;
+        self.capacity = capacity;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "storage")]
+fn map(file: &File, len: usize) -> Result<MmapMut> {
+    // SAFETY: the file is private to this store, which only changes it
+    // through this map or by growing it.
+    Ok(unsafe { MmapOptions::new().len(len).map_mut(file)? })
+}
+
+#[cfg(feature = "storage")]
+fn file_len(file: &File) -> Result<u64> {
+    Ok(file.metadata()?.len())
+}
+
+#[cfg(feature = "storage")]
+fn record_bytes(max_links: usize) -> usize {
+    (max_links + 1) * std::mem::size_of::<u32>()
+}
@@ -0,0 +1,871 @@
+//! On-disk layout for the HNSW graph
+//!
+//! A persisted index lives in a directory next to the redb file and holds:
+//!
+//! - `vectors.bin`: slot-major f32 vectors, written through as they are stored
+//!   and read back through a shared memory map
+//! - `links.bin`: base-layer neighbor lists in fixed-stride records, written
+//!   and read in place through a shared memory map
+//! - `graph.snap`: full checkpoint of the upper-layer neighbor lists, ids and
+//!   free slots, naming the first log generation it does not cover
+//! - `graph.<generation>.log`: append-only records of every mutation, in
+//!   numbered generations
+//!
+//! Every insert logs its vector, the new node's neighbor lists and the lists
+//! it modified on other nodes; every delete logs the lists relinked around
+//! the deleted node. Records are flushed to the OS at the end of every
+//! mutating call, so a crashed process loses none of them. Once the log grows
+//! past a fraction of the graph, a checkpoint is taken in two steps. Under
+//! the index lock, writes move on to a new log generation and the snapshot is
+//! encoded in memory; after the lock is released, the vectors and base layer
+//! are synced, the snapshot is written atomically and the generations it
+//! covers are deleted.
+//!
+//! Reopening maps the vectors and the base layer, decodes the snapshot and
+//! replays the uncovered generations in order, writing the lists they record
+//! over the mapped ones. The base layer holds every node and most of the
+//! links, and is used where it lies; what is still decoded linearly is one
+//! slot state and id per node, which the caller indexes by id anyway, and the
+//! upper layers, which roughly one node in `m` reaches. Torn log records at
+//! the tail are dropped; they postdate the last checkpoint, so the caller's
+//! write-ahead log still holds the operations they recorded.
+//! A base-layer list written through ahead of a record lost in a crash can
+//! still name slots past the end of the graph; traversal skips them.
+
+use super::graph::{base_layer_links, HnswGraph, MAX_LEVEL};
+use super::links::LinkStore;
+use crate::checksum::{self, checksum};
+use crate::error::{Result, RuvectorError};
+use crate::index::vectors::VectorStore;
+use crate::types::{DistanceMetric, HnswConfig, VectorId};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_FILE: &str = "graph.snap";
+const SNAPSHOT_TMP_FILE: &str = "graph.snap.tmp";
+const LOG_PREFIX: &str = "graph.";
+const LOG_SUFFIX: &str = ".log";
+const VECTORS_FILE: &str = "vectors.bin";
+const LINKS_FILE: &str = "links.bin";
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"RVHNSWG1";
+const VECTORS_MAGIC: &[u8; 8] = b"RVHNSWV1";
+const LINKS_MAGIC: &[u8; 8] = b"RVHNSWL1";
+const FORMAT_VERSION: u32 = 2;
+
+/// Size of the `vectors.bin` and `links.bin` headers; keeps the payload
+/// cache-line aligned
+const HEADER_LEN: usize = 64;
+
+/// Never checkpoint more often than every this many log records
+const MIN_CHECKPOINT_RECORDS: usize = 10_000;
+
+/// Always checkpoint once the log holds this many bytes
+const MAX_LOG_BYTES: u64 = 1 << 30;
+
+const RECORD_INSERT: u8 = 1;
+const RECORD_DELETE: u8 = 2;
+
+const SLOT_TOMBSTONE: u8 = 0;
+const SLOT_LIVE: u8 = 1;
+const SLOT_FREE: u8 = 2;
+
+const NO_ENTRY: u32 = u32::MAX;
+
+// Graph directories currently owned by an open index in this process. A
+// second index on the same directory would interleave log writes, so it
+// falls back to an unpersisted in-memory graph instead.
+static OPEN_GRAPHS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Directory holding the persisted graph for a redb file at `storage_path`
+pub fn graph_dir_for(storage_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.hnsw", storage_path))
+}
+
+/// Everything decoded from a graph directory
+pub(crate) struct LoadedGraph {
+    pub graph: HnswGraph,
+    pub vectors: VectorStore,
+    pub ids: Vec<Option<VectorId>>,
+    pub free: Vec<u32>,
+    pub writer: GraphWriter,
+}
+
+/// Borrowed view of the index state written into a checkpoint
+pub(crate) struct GraphSnapshot<'a> {
+    pub dimensions: usize,
+    pub metric: DistanceMetric,
+    pub config: &'a HnswConfig,
+    pub graph: &'a HnswGraph,
+    pub ids: &'a [Option<VectorId>],
+    pub free: &'a [u32],
+}
+
+/// Appends log records and writes checkpoints for one open graph directory
+pub(crate) struct GraphWriter {
+    dir: PathBuf,
+    /// Generation of the log file being appended to
+    generation: u64,
+    log: BufWriter<File>,
+    log_records: usize,
+    log_bytes: u64,
+}
+
+/// A snapshot captured by [`GraphWriter::begin_checkpoint`], ready to be
+/// written without holding the index lock
+pub(crate) struct Checkpoint {
+    dir: PathBuf,
+    /// Encoded snapshot, including its checksum trailer
+    snapshot: Vec<u8>,
+    /// Handle on `vectors.bin`, synced before the snapshot is written
+    vectors: Option<File>,
+    /// Handle on `links.bin`, likewise
+    links: Option<File>,
+    /// First log generation not covered by the snapshot
+    generation: u64,
+}
+
+impl GraphWriter {
+    /// Claim `dir` for this process, returning `None` if it is already open
+    fn claim(dir: &Path) -> Result<Option<PathBuf>> {
+        let dir = if dir.is_absolute() {
+            dir.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(dir)
+        };
+        let mut open = OPEN_GRAPHS.lock();
+        if !open.insert(dir.clone()) {
+            return Ok(None);
+        }
+        Ok(Some(dir))
+    }
+
+    /// Start a fresh graph directory, discarding whatever was there
+    ///
+    /// Moves the vectors and the base layer into the directory's files; the
+    /// caller writes the first checkpoint. Returns `None` if another index
+    /// in this process owns the directory.
+    pub fn create(
+        dir: &Path,
+        dimensions: usize,
+        m: usize,
+        graph: &mut HnswGraph,
+        store: &mut VectorStore,
+    ) -> Result<Option<Self>> {
+        let Some(dir) = Self::claim(dir)? else {
+            return Ok(None);
+        };
+        let result = (|| {
+            std::fs::create_dir_all(&dir)?;
+            for (_, path) in log_files(&dir)? {
+                std::fs::remove_file(path)?;
+            }
+
+            let vectors = create_file(&dir.join(VECTORS_FILE), VECTORS_MAGIC, dimensions)?;
+            store.move_to_file(vectors, HEADER_LEN)?;
+            let links = create_file(&dir.join(LINKS_FILE), LINKS_MAGIC, base_layer_links(m))?;
+            graph.move_to_file(links, HEADER_LEN)?;
+
+            Ok(Self {
+                dir: dir.clone(),
+                generation: 0,
+                log: BufWriter::new(File::create(log_path(&dir, 0))?),
+                log_records: 0,
+                log_bytes: 0,
+            })
+        })();
+
+        if result.is_err() {
+            OPEN_GRAPHS.lock().remove(&dir);
+        }
+        result.map(Some)
+    }
+
+    /// Whether enough has been logged since the last checkpoint to write a new one
+    pub fn should_checkpoint(&self, node_count: usize) -> bool {
+        self.log_records >= MIN_CHECKPOINT_RECORDS.max(node_count / 2)
+            || self.log_bytes >= MAX_LOG_BYTES
+    }
+
+    /// Log a newly linked node and the neighbor lists it touched
+    ///
+    /// `reused` marks an insert into a previously freed slot.
+    pub fn append_insert(
+        &mut self,
+        slot: u32,
+        reused: bool,
+        id: &str,
+        vector: &[f32],
+        graph: &HnswGraph,
+        touched: &[(u32, usize)],
+    ) -> Result<()> {
+        let mut payload = Vec::with_capacity(64 + vector.len() * 4 + touched.len() * 64);
+        payload.push(RECORD_INSERT);
+        put_u32(&mut payload, slot);
+        payload.push(reused as u8);
+        put_str(&mut payload, id);
+        for value in vector {
+            payload.extend_from_slice(&value.to_le_bytes());
+        }
+        let level = graph.level(slot);
+        payload.push(level as u8);
+        for layer in 0..=level {
+            put_links(&mut payload, graph.neighbors(slot, layer));
+        }
+        put_touched(&mut payload, graph, touched);
+
+        self.append_record(&payload)
+    }
+
+    /// Log the deletion of `slot` and the neighbor lists relinked around it
+    pub fn append_delete(
+        &mut self,
+        slot: u32,
+        graph: &HnswGraph,
+        touched: &[(u32, usize)],
+    ) -> Result<()> {
+        let mut payload = Vec::with_capacity(16 + touched.len() * 64);
+        payload.push(RECORD_DELETE);
+        put_u32(&mut payload, slot);
+        put_touched(&mut payload, graph, touched);
+        self.append_record(&payload)
+    }
+
+    fn append_record(&mut self, payload: &[u8]) -> Result<()> {
+        self.log.write_all(&(payload.len() as u32).to_le_bytes())?;
+        self.log.write_all(&checksum(payload).to_le_bytes())?;
+        self.log.write_all(payload)?;
+        self.log_records += 1;
+        self.log_bytes += 12 + payload.len() as u64;
+        Ok(())
+    }
+
+    /// Push buffered log records to the OS
+    pub fn flush(&mut self) -> Result<()> {
+        self.log.flush()?;
+        Ok(())
+    }
+
+    /// Capture a checkpoint of the current state
+    ///
+    /// Encodes the snapshot in memory and moves writes on to a new log
+    /// generation, so records appended from now on are not covered by it.
+    /// Nothing is written until [`Checkpoint::write`], which the caller runs
+    /// once it has released the index lock.
+    pub fn begin_checkpoint(
+        &mut self,
+        snapshot: &GraphSnapshot<'_>,
+        store: &VectorStore,
+    ) -> Result<Checkpoint> {
+        let generation = self.generation + 1;
+        let mut out = ChecksumWriter::new(Vec::new());
+        write_snapshot(&mut out, snapshot, generation)?;
+        let sum = out.sum;
+        let mut encoded = out.inner;
+        encoded.extend_from_slice(&sum.to_le_bytes());
+        let vectors = store.file_handle()?;
+        let links = snapshot.graph.file_handle()?;
+
+        self.log.flush()?;
+        self.log = BufWriter::new(File::create(log_path(&self.dir, generation))?);
+        self.generation = generation;
+        self.log_records = 0;
+        self.log_bytes = 0;
+
+        Ok(Checkpoint {
+            dir: self.dir.clone(),
+            snapshot: encoded,
+            vectors,
+            links,
+            generation,
+        })
+    }
+}
+
+impl Checkpoint {
+    /// Sync the vectors and base layer, write the snapshot atomically and
+    /// delete the log generations it covers
+    pub fn write(self) -> Result<()> {
+        // The base layer is written through a shared map, whose dirty pages
+        // are the file's page cache and are written back by syncing it
+        for file in self.vectors.iter().chain(&self.links) {
+            file.sync_data()?;
+        }
+
+        let tmp_path = self.dir.join(SNAPSHOT_TMP_FILE);
+        {
+            let mut file = File::create(&tmp_path)?;
+            file.write_all(&self.snapshot)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, self.dir.join(SNAPSHOT_FILE))?;
+        if let Ok(dir) = File::open(&self.dir) {
+            let _ = dir.sync_all();
+        }
+
+        for (generation, path) in log_files(&self.dir)? {
+            if generation < self.generation {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for GraphWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+        OPEN_GRAPHS.lock().remove(&self.dir);
+    }
+}
+
+/// Open the graph persisted in `dir`
+///
+/// Returns `Ok(None)` if there is no persisted graph, it was built with a
+/// different configuration, or another index in this process already owns
+/// the directory. Returns an error if the files exist but are corrupt.
+pub(crate) fn load(
+    dir: &Path,
+    dimensions: usize,
+    metric: DistanceMetric,
+    config: &HnswConfig,
+) -> Result<Option<LoadedGraph>> {
+    if [SNAPSHOT_FILE, VECTORS_FILE, LINKS_FILE]
+        .iter()
+        .any(|file| !dir.join(file).exists())
+    {
+        return Ok(None);
+    }
+    let Some(claimed) = GraphWriter::claim(dir)? else {
+        return Ok(None);
+    };
+
+    match load_claimed(&claimed, dimensions, metric, config) {
+        Ok(Some(loaded)) => Ok(Some(loaded)),
+        other => {
+            OPEN_GRAPHS.lock().remove(&claimed);
+            other
+        }
+    }
+}
+
+fn load_claimed(
+    dir: &Path,
+    dimensions: usize,
+    metric: DistanceMetric,
+    config: &HnswConfig,
+) -> Result<Option<LoadedGraph>> {
+    let snapshot = std::fs::read(dir.join(SNAPSHOT_FILE))?;
+    if snapshot.len() < 8 {
+        return Err(corrupt("snapshot truncated"));
+    }
+    let (body, trailer) = snapshot.split_at(snapshot.len() - 8);
+    if checksum(body).to_le_bytes() != trailer {
+        return Err(corrupt("snapshot checksum mismatch"));
+    }
+
+    let mut dec = Decoder::new(body);
+    if dec.bytes(8)? != SNAPSHOT_MAGIC || dec.u32()? != FORMAT_VERSION {
+        return Err(corrupt("unrecognized snapshot header"));
+    }
+    let stored_dimensions = dec.u32()? as usize;
+    let stored_metric = dec.u8()?;
+    let stored_m = dec.u32()? as usize;
+    let stored_ef_construction = dec.u32()? as usize;
+    if stored_dimensions != dimensions
+        || stored_metric != metric_tag(metric)
+        || stored_m != config.m
+        || stored_ef_construction != config.ef_construction
+    {
+        tracing::info!("Persisted HNSW graph was built with a different configuration");
+        return Ok(None);
+    }
+
+    let node_count = dec.u64()? as usize;
+    let entry_point = dec.u32()?;
+    let max_level = dec.u8()? as usize;
+    let first_generation = dec.u64()?;
+
+    let mut ids = Vec::with_capacity(node_count);
+    let mut free = HashSet::new();
+    for slot in 0..node_count {
+        match dec.u8()? {
+            SLOT_LIVE => ids.push(Some(dec.str()?)),
+            SLOT_TOMBSTONE => ids.push(None),
+            SLOT_FREE => {
+                ids.push(None);
+                free.insert(slot as u32);
+            }
+            _ => return Err(corrupt("unknown slot state")),
+        }
+    }
+
+    let upper_count = dec.u32()? as usize;
+    let mut upper = HashMap::with_capacity(upper_count.min(node_count));
+    for _ in 0..upper_count {
+        let slot = dec.u32()?;
+        let level = dec.level()?;
+        if slot as usize >= node_count || level == 0 {
+            return Err(corrupt("upper layer node out of range"));
+        }
+        let lists = (0..level)
+            .map(|_| dec.links(node_count))
+            .collect::<Result<Vec<_>>>()?;
+        upper.insert(slot, lists);
+    }
+
+    // Records and vectors past the snapshot are overwritten as the log is
+    // replayed
+    let max_links = base_layer_links(config.m);
+    let links_file = open_file(&dir.join(LINKS_FILE), LINKS_MAGIC, max_links)
+        .map_err(|_| corrupt("unrecognized link file header"))?;
+    let base = LinkStore::open_file(max_links, links_file, HEADER_LEN, node_count)
+        .map_err(|_| corrupt("link file shorter than snapshot"))?;
+    let vectors_file = open_file(&dir.join(VECTORS_FILE), VECTORS_MAGIC, dimensions)
+        .map_err(|_| corrupt("unrecognized vector file header"))?;
+    let mut vectors = VectorStore::open_file(dimensions, vectors_file, HEADER_LEN, node_count)
+        .map_err(|_| corrupt("vector file shorter than snapshot"))?;
+
+    let mut graph = HnswGraph::from_parts(
+        config.m,
+        config.ef_construction,
+        base,
+        upper,
+        (entry_point != NO_ENTRY).then_some(entry_point),
+        max_level,
+    );
+    if graph.entry_point.is_some_and(|e| e as usize >= node_count) {
+        return Err(corrupt("entry point out of range"));
+    }
+
+    // Generations before the snapshot's are left over from a checkpoint that
+    // was interrupted while deleting them
+    let mut generation = first_generation;
+    let mut valid_len = 0;
+    let mut log_bytes = 0;
+    let mut torn = false;
+    for (log_generation, path) in log_files(dir)? {
+        if log_generation < first_generation {
+            std::fs::remove_file(path)?;
+        } else if torn {
+            // Records after a torn one no longer apply
+            tracing::warn!("Discarding HNSW log generation {} after a torn record", log_generation);
+            std::fs::remove_file(path)?;
+        } else {
+            let (len, complete) =
+                replay_log(&path, &mut graph, &mut vectors, &mut ids, &mut free)?;
+            generation = log_generation;
+            valid_len = len;
+            log_bytes += len;
+            torn = !complete;
+        }
+    }
+
+    let mut log = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(log_path(dir, generation))?;
+    log.set_len(valid_len)?;
+    log.seek(SeekFrom::End(0))?;
+
+    let mut free: Vec<u32> = free.into_iter().collect();
+    free.sort_unstable_by(|a, b| b.cmp(a));
+
+    Ok(Some(LoadedGraph {
+        graph,
+        vectors,
+        ids,
+        free,
+        writer: GraphWriter {
+            dir: dir.to_path_buf(),
+            generation,
+            log: BufWriter::new(log),
+            log_records: 0,
+            log_bytes,
+        },
+    }))
+}
+
+/// Apply the intact records of one log generation, returning the length of
+/// its valid prefix and whether that is the whole file
+fn replay_log(
+    path: &Path,
+    graph: &mut HnswGraph,
+    vectors: &mut VectorStore,
+    ids: &mut Vec<Option<VectorId>>,
+    free: &mut HashSet<u32>,
+) -> Result<(u64, bool)> {
+    let bytes = std::fs::read(path)?;
+
+    let mut offset = 0usize;
+    let mut replayed = 0usize;
+    while offset + 12 <= bytes.len() {
+        let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let sum = u64::from_le_bytes(bytes[offset + 4..offset + 12].try_into().unwrap());
+        let Some(payload) = bytes.get(offset + 12..offset + 12 + len) else {
+            break;
+        };
+        if checksum(payload) != sum || apply_record(payload, graph, vectors, ids, free).is_err() {
+            break;
+        }
+        offset += 12 + len;
+        replayed += 1;
+    }
+
+    if offset < bytes.len() {
+        tracing::warn!(
+            "Discarding {} bytes of incomplete HNSW log after {} records",
+            bytes.len() - offset,
+            replayed
+        );
+    }
+    Ok((offset as u64, offset == bytes.len()))
+}
+
+fn apply_record(
+    payload: &[u8],
+    graph: &mut HnswGraph,
+    vectors: &mut VectorStore,
+    ids: &mut Vec<Option<VectorId>>,
+    free: &mut HashSet<u32>,
+) -> Result<()> {
+    let mut dec = Decoder::new(payload);
+    match dec.u8()? {
+        RECORD_INSERT => {
+            let slot = dec.u32()?;
+            let reused = dec.u8()? != 0;
+            let valid = if reused {
+                free.contains(&slot)
+            } else {
+                slot as usize == graph.len()
+            };
+            if !valid {
+                return Err(corrupt("log insert out of sequence"));
+            }
+            let id = dec.str()?;
+            let vector = dec.vector(vectors.dimensions())?;
+            let level = dec.level()?;
+            let bound = graph.len().max(slot as usize + 1);
+            let lists = (0..=level)
+                .map(|_| dec.links(bound))
+                .collect::<Result<Vec<_>>>()?;
+            if lists[0].len() > graph.max_links(0) {
+                return Err(corrupt("log list too long"));
+            }
+            let touched = dec.touched(bound, graph.max_links(0))?;
+
+            if reused {
+                free.remove(&slot);
+                graph.reset_node(slot, level);
+                vectors.set(slot, &vector)?;
+                ids[slot as usize] = Some(id);
+            } else {
+                graph.push_node(level)?;
+                vectors.push(&vector)?;
+                ids.push(Some(id));
+            }
+            for (layer, links) in lists.iter().enumerate() {
+                graph.set_links(slot, layer, links);
+            }
+            apply_touched(graph, touched);
+            Ok(())
+        }
+        RECORD_DELETE => {
+            let slot = dec.u32()? as usize;
+            let touched = dec.touched(graph.len(), graph.max_links(0))?;
+            match ids.get_mut(slot) {
+                Some(id) => *id = None,
+                None => return Err(corrupt("log delete out of range")),
+            }
+            apply_touched(graph, touched);
+            Ok(())
+        }
+        _ => Err(corrupt("unknown log record")),
+    }
+}
+
+/// Neighbor lists rewritten by a record, plus the entry point afterwards
+struct Touched {
+    lists: Vec<(usize, usize, Vec<u32>)>,
+    entry_point: u32,
+    max_level: usize,
+}
+
+fn apply_touched(graph: &mut HnswGraph, touched: Touched) {
+    for (slot, layer, links) in touched.lists {
+        graph.set_links(slot as u32, layer, &links);
+    }
+    graph.entry_point = (touched.entry_point != NO_ENTRY).then_some(touched.entry_point);
+    graph.max_level = touched.max_level;
+}
+
+fn write_snapshot<W: Write>(
+    out: &mut ChecksumWriter<W>,
+    snapshot: &GraphSnapshot<'_>,
+    first_generation: u64,
+) -> Result<()> {
+    let graph = snapshot.graph;
+    out.write_all(SNAPSHOT_MAGIC)?;
+    out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    out.write_all(&(snapshot.dimensions as u32).to_le_bytes())?;
+    out.write_all(&[metric_tag(snapshot.metric)])?;
+    out.write_all(&(snapshot.config.m as u32).to_le_bytes())?;
+    out.write_all(&(snapshot.config.ef_construction as u32).to_le_bytes())?;
+    out.write_all(&(graph.len() as u64).to_le_bytes())?;
+    out.write_all(&graph.entry_point.unwrap_or(NO_ENTRY).to_le_bytes())?;
+    out.write_all(&[graph.max_level as u8])?;
+    out.write_all(&first_generation.to_le_bytes())?;
+
+    let free: HashSet<u32> = snapshot.free.iter().copied().collect();
+    let mut buf = Vec::new();
+    for (slot, id) in snapshot.ids.iter().enumerate() {
+        buf.clear();
+        match id {
+            Some(id) => {
+                buf.push(SLOT_LIVE);
+                put_str(&mut buf, id);
+            }
+            None if free.contains(&(slot as u32)) => buf.push(SLOT_FREE),
+            None => buf.push(SLOT_TOMBSTONE),
+        }
+        out.write_all(&buf)?;
+    }
+
+    // The base layer is in `links.bin`
+    let upper: Vec<(u32, &[Vec<u32>])> = graph.upper_nodes().collect();
+    out.write_all(&(upper.len() as u32).to_le_bytes())?;
+    for (slot, lists) in upper {
+        buf.clear();
+        put_u32(&mut buf, slot);
+        buf.push(lists.len() as u8);
+        for links in lists {
+            put_links(&mut buf, links);
+        }
+        out.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+/// Create `path` with a header of `magic`, the format version and `param`
+fn create_file(path: &Path, magic: &[u8; 8], param: usize) -> Result<File> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?;
+    let mut header = [0u8; HEADER_LEN];
+    header[..8].copy_from_slice(magic);
+    header[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header[12..16].copy_from_slice(&(param as u32).to_le_bytes());
+    file.write_all(&header)?;
+    Ok(file)
+}
+
+/// Open a file written by [`create_file`], checking its magic and `param`
+fn open_file(path: &Path, magic: &[u8; 8], param: usize) -> Result<File> {
+    use std::io::Read;
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut header = [0u8; HEADER_LEN];
+    file.read_exact(&mut header)?;
+    if &header[..8] != magic
+        || u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize != param
+    {
+        return Err(corrupt("unrecognized header"));
+    }
+    Ok(file)
+}
+
+fn log_path(dir: &Path, generation: u64) -> PathBuf {
+    dir.join(format!("{}{}{}", LOG_PREFIX, generation, LOG_SUFFIX))
+}
+
+/// Log files in `dir` by ascending generation
+fn log_files(dir: &Path) -> Result<Vec<(u64, PathBuf)>> {
+    let mut logs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let generation = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(LOG_PREFIX)?.strip_suffix(LOG_SUFFIX))
+            .and_then(|generation| generation.parse().ok());
+        if let Some(generation) = generation {
+            logs.push((generation, path));
+        }
+    }
+    logs.sort_unstable();
+    Ok(logs)
+}
+
+fn metric_tag(metric: DistanceMetric) -> u8 {
+    match metric {
+        DistanceMetric::Euclidean => 0,
+        DistanceMetric::Cosine => 1,
+        DistanceMetric::DotProduct => 2,
+        DistanceMetric::Manhattan => 3,
+    }
+}
+
+fn corrupt(reason: &str) -> RuvectorError {
+    RuvectorError::SerializationError(format!("Corrupt HNSW graph: {}", reason))
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(buf: &mut Vec<u8>, value: &str) {
+    put_u32(buf, value.len() as u32);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn put_links(buf: &mut Vec<u8>, links: &[u32]) {
+    put_u32(buf, links.len() as u32);
+    for &link in links {
+        put_u32(buf, link);
+    }
+}
+
+fn put_touched(buf: &mut Vec<u8>, graph: &HnswGraph, touched: &[(u32, usize)]) {
+    put_u32(buf, touched.len() as u32);
+    for &(slot, layer) in touched {
+        put_u32(buf, slot);
+        buf.push(layer as u8);
+        put_links(buf, graph.neighbors(slot, layer));
+    }
+    put_u32(buf, graph.entry_point.unwrap_or(NO_ENTRY));
+    buf.push(graph.max_level as u8);
+}
+
+/// Writer that checksums everything passing through it
+struct ChecksumWriter<W: Write> {
+    inner: W,
+    sum: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            sum: checksum(&[]),
+        }
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.sum = checksum::extend(self.sum, &buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Bounds-checked little-endian reader
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| corrupt("unexpected end of data"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn level(&mut self) -> Result<usize> {
+        let level = self.u8()? as usize;
+        if level > MAX_LEVEL {
+            return Err(corrupt("node level out of range"));
+        }
+        Ok(level)
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|_| corrupt("invalid id"))
+    }
+
+    fn vector(&mut self, dimensions: usize) -> Result<Vec<f32>> {
+        Ok(self
+            .bytes(dimensions * 4)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+
+    /// Read a neighbor list whose entries must all be below `bound`
+    fn links(&mut self, bound: usize) -> Result<Vec<u32>> {
+        let len = self.u32()? as usize;
+        let raw = self.bytes(len.checked_mul(4).ok_or_else(|| corrupt("link count"))?)?;
+        let links: Vec<u32> = raw
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        if links.iter().any(|&l| l as usize >= bound) {
+            return Err(corrupt("link out of range"));
+        }
+        Ok(links)
+    }
+
+    /// Read the lists a record rewrote; base-layer lists may hold at most
+    /// `max_base` entries
+    fn touched(&mut self, bound: usize, max_base: usize) -> Result<Touched> {
+        let count = self.u32()? as usize;
+        let mut lists = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let slot = self.u32()? as usize;
+            let layer = self.u8()? as usize;
+            let links = self.links(bound)?;
+            if slot >= bound {
+                return Err(corrupt("log link out of range"));
+            }
+            if layer == 0 && links.len() > max_base {
+                return Err(corrupt("log list too long"));
+            }
+            lists.push((slot, layer, links));
+        }
+        let entry_point = self.u32()?;
+        if entry_point != NO_ENTRY && entry_point as usize >= bound {
+            return Err(corrupt("entry point out of range"));
+        }
+        let max_level = self.level()?;
+        Ok(Touched {
+            lists,
+            entry_point,
+            max_level,
+        })
+    }
+}
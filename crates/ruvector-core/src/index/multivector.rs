@@ -0,0 +1,306 @@
+//! Late-interaction (ColBERT-style) multi-vector index
+//!
+//! Every point stores a variable-length matrix of token vectors. Candidate
+//! points are gathered by searching a token-level index with each query
+//! token, then reranked by their MaxSim score against the full matrices.
+
+use crate::distance::batch_distances;
+use crate::error::{Result, RuvectorError};
+use crate::index::VectorIndex;
+use crate::types::{DistanceMetric, IndexStats, SearchParams, SearchResult, VectorId};
+use std::collections::{HashMap, HashSet};
+
+/// Token hits fetched per requested result and query token
+///
+/// Several tokens of one point often crowd the neighbours of a query token,
+/// so each token search over-fetches to leave room for distinct points.
+const TOKEN_OVERFETCH: usize = 4;
+
+/// Multi-vector index scored with MaxSim
+pub struct MultiVectorIndex {
+    /// Every token vector, keyed by [`token_id`]
+    tokens: Box<dyn VectorIndex>,
+    /// Token vectors of each point
+    matrices: HashMap<VectorId, Vec<Vec<f32>>>,
+    metric: DistanceMetric,
+    dimensions: usize,
+}
+
+impl MultiVectorIndex {
+    /// Create an empty index that looks up token vectors in `tokens`
+    pub fn new(dimensions: usize, metric: DistanceMetric, tokens: Box<dyn VectorIndex>) -> Self {
+        Self {
+            tokens,
+            matrices: HashMap::new(),
+            metric,
+            dimensions,
+        }
+    }
+
+    /// Split a row-major matrix into token vectors
+    fn split(&self, matrix: &[f32]) -> Result<Vec<Vec<f32>>> {
+        if self.dimensions == 0 || matrix.is_empty() || matrix.len() % self.dimensions != 0 {
+            return Err(RuvectorError::InvalidInput(format!(
+                "Multi-vector of {} values is not a whole number of {}-dimensional tokens",
+                matrix.len(),
+                self.dimensions
+            )));
+        }
+        Ok(matrix
+            .chunks_exact(self.dimensions)
+            .map(<[f32]>::to_vec)
+            .collect())
+    }
+
+    /// Sum over query tokens of the distance to the closest point token
+    fn max_sim(&self, query: &[Vec<f32>], matrix: &[Vec<f32>]) -> Result<f32> {
+        let mut score = 0.0;
+        for token in query {
+            score += batch_distances(token, matrix, self.metric)?
+                .into_iter()
+                .fold(f32::INFINITY, f32::min);
+        }
+        Ok(score)
+    }
+
+    /// Score `ids` with MaxSim and keep the best k
+    fn rank<'a>(
+        &self,
+        query: &[Vec<f32>],
+        ids: impl Iterator<Item = &'a VectorId>,
+        k: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        let mut scored = ids
+            .filter_map(|id| self.matrices.get_key_value(id))
+            .map(|(id, matrix)| Ok((id, self.max_sim(query, matrix)?)))
+            .collect::<Result<Vec<_>>>()?;
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        scored.truncate(k);
+
+        let mut results = scored
+            .into_iter()
+            .map(|(id, score)| SearchResult {
+                id: id.clone(),
+                score,
+                vector: None,
+                metadata: None,
+            })
+            .collect();
+        params.apply_threshold(&mut results);
+        Ok(results)
+    }
+
+    /// Points owning a token among the nearest neighbours of a query token
+    fn candidates(
+        &self,
+        query: &[Vec<f32>],
+        k: usize,
+        params: &SearchParams,
+        filter: Option<&dyn Fn(&str) -> bool>,
+    ) -> Result<HashSet<VectorId>> {
+        // The threshold applies to MaxSim scores, not token distances
+        let token_params = SearchParams {
+            score_threshold: None,
+            ..params.clone()
+        };
+        let fetch = params.candidates(k).saturating_mul(TOKEN_OVERFETCH);
+
+        let mut candidates = HashSet::new();
+        for token in query {
+            let hits = match filter {
+                Some(filter) => {
+                    self.tokens
+                        .search_filtered(token, fetch, &token_params, &|token_id| {
+                            filter(point_id(token_id))
+                        })?
+                }
+                None => self
+                    .tokens
+                    .search_with_params(token, fetch, &token_params)?,
+            };
+            candidates.extend(hits.into_iter().map(|hit| point_id(&hit.id).to_string()));
+        }
+        Ok(candidates)
+    }
+}
+
+impl VectorIndex for MultiVectorIndex {
+    fn add(&mut self, id: VectorId, vector: Vec<f32>) -> Result<()> {
+        self.add_batch(vec![(id, vector)])
+    }
+
+    fn add_batch(&mut self, entries: Vec<(VectorId, Vec<f32>)>) -> Result<()> {
+        // Later entries for the same id win
+        let matrices = entries
+            .into_iter()
+            .map(|(id, vector)| Ok((id, self.split(&vector)?)))
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let mut tokens = Vec::new();
+        for (id, matrix) in &matrices {
+            self.remove(id)?;
+            tokens.extend(
+                matrix
+                    .iter()
+                    .enumerate()
+                    .map(|(i, token)| (token_id(id, i), token.clone())),
+            );
+        }
+        self.tokens.add_batch(tokens)?;
+        self.matrices.extend(matrices);
+        Ok(())
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchResult>> {
+        self.search_with_params(query, k, &SearchParams::default())
+    }
+
+    fn search_with_params(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        let query = self.split(query)?;
+        if params.exact {
+            return self.rank(&query, self.matrices.keys(), k, params);
+        }
+        let candidates = self.candidates(&query, k, params, None)?;
+        self.rank(&query, candidates.iter(), k, params)
+    }
+
+    fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<SearchResult>> {
+        let query = self.split(query)?;
+        if params.exact {
+            let ids = self.matrices.keys().filter(|id| filter(id));
+            return self.rank(&query, ids, k, params);
+        }
+        let candidates = self.candidates(&query, k, params, Some(filter))?;
+        self.rank(&query, candidates.iter(), k, params)
+    }
+
+    fn search_among(
+        &self,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        ids: &[VectorId],
+    ) -> Result<Vec<SearchResult>> {
+        let query = self.split(query)?;
+        self.rank(&query, ids.iter(), k, params)
+    }
+
+    fn remove(&mut self, id: &VectorId) -> Result<bool> {
+        let Some(matrix) = self.matrices.remove(id) else {
+            return Ok(false);
+        };
+        for i in 0..matrix.len() {
+            self.tokens.remove(&token_id(id, i))?;
+        }
+        Ok(true)
+    }
+
+    fn len(&self) -> usize {
+        self.matrices.len()
+    }
+
+    fn stats(&self) -> IndexStats {
+        let matrices: usize = self
+            .matrices
+            .iter()
+            .map(|(id, matrix)| {
+                id.capacity()
+                    + matrix
+                        .iter()
+                        .map(|token| token.capacity() * std::mem::size_of::<f32>())
+                        .sum::<usize>()
+            })
+            .sum();
+        let tokens = self.tokens.stats();
+        IndexStats {
+            vectors: self.matrices.len(),
+            memory_bytes: tokens.memory_bytes + matrices,
+            ..tokens
+        }
+    }
+}
+
+/// Id of the `index`-th token of point `id` in the token index
+fn token_id(id: &str, index: usize) -> VectorId {
+    format!("{}#{}", id, index)
+}
+
+/// Point that a token id from [`token_id`] belongs to
+fn point_id(token_id: &str) -> &str {
+    token_id.rsplit_once('#').map_or(token_id, |(id, _)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::flat::FlatIndex;
+
+    fn index() -> MultiVectorIndex {
+        let tokens = Box::new(FlatIndex::new(2, DistanceMetric::Euclidean));
+        MultiVectorIndex::new(2, DistanceMetric::Euclidean, tokens)
+    }
+
+    #[test]
+    fn test_max_sim_ranking() -> Result<()> {
+        let mut index = index();
+        // Point ids may themselves contain '#'
+        index.add("a#1".to_string(), vec![1.0, 0.0, 0.0, 1.0])?;
+        index.add("b".to_string(), vec![1.0, 0.0])?;
+        index.add("c".to_string(), vec![5.0, 5.0, 6.0, 6.0, 7.0, 7.0])?;
+        assert_eq!(index.len(), 3);
+
+        // Both query tokens match a token of "a#1" exactly
+        let query = [1.0, 0.0, 0.0, 1.0];
+        let results = index.search(&query, 2)?;
+        assert_eq!(results[0].id, "a#1");
+        assert!(results[0].score < 1e-6);
+        assert_eq!(results[1].id, "b");
+        assert!((results[1].score - 2f32.sqrt()).abs() < 1e-5);
+
+        let exact = SearchParams {
+            exact: true,
+            ..SearchParams::default()
+        };
+        let ids: Vec<_> = index
+            .search_with_params(&query, 3, &exact)?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, ["a#1", "b", "c"]);
+
+        let filtered =
+            index.search_filtered(&query, 2, &SearchParams::default(), &|id| id != "a#1")?;
+        assert_eq!(filtered[0].id, "b");
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_and_remove() -> Result<()> {
+        let mut index = index();
+        index.add("a".to_string(), vec![1.0, 0.0, 0.0, 1.0, 1.0, 1.0])?;
+        index.add("a".to_string(), vec![3.0, 3.0])?;
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.tokens.len(), 1);
+
+        assert!(index.remove(&"a".to_string())?);
+        assert!(!index.remove(&"a".to_string())?);
+        assert!(index.tokens.is_empty());
+
+        // Ragged matrices are rejected
+        assert!(index.add("b".to_string(), vec![1.0, 2.0, 3.0]).is_err());
+        assert!(index.add("b".to_string(), Vec::new()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+//! Slot-indexed vector storage for the HNSW and flat indexes
+//!
+//! Vectors are kept in one slot-major buffer addressed by slot, either on
+//! the heap or in a file. File-backed stores write every vector through to
+//! the file as it is stored and read it back through a shared memory map,
+//! so the vectors live in the page cache rather than on the heap. Persistent
+//! HNSW indexes keep them in their `vectors.bin`; quantized in-memory
+//! indexes spill them to an unlinked temporary file, leaving only the codes
+//! resident.
+
+use crate::error::Result;
+#[cfg(feature = "storage")]
+use crate::error::RuvectorError;
+#[cfg(feature = "storage")]
+use memmap2::{Mmap, MmapOptions};
+#[cfg(feature = "storage")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "storage")]
+use std::io::{Seek, SeekFrom, Write};
+#[cfg(feature = "storage")]
+use std::path::PathBuf;
+
+/// Slots a file-backed store maps at least, so small stores do not remap
+/// on every insert
+#[cfg(feature = "storage")]
+const MIN_FILE_SLOTS: usize = 1024;
+
+/// Vectors in a file, written with plain writes and read through a map
+#[cfg(feature = "storage")]
+struct FileVectors {
+    file: File,
+    /// Shared read-only map of the first `capacity` slots
+    mmap: Mmap,
+    /// Byte offset of slot 0 in the file
+    offset: usize,
+    len: usize,
+    capacity: usize,
+    /// Temporary file to remove on drop, if it could not be unlinked while open
+    temp_path: Option<PathBuf>,
+}
+
+#[cfg(feature = "storage")]
+impl Drop for FileVectors {
+    fn drop(&mut self) {
+        if let Some(path) = &self.temp_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+enum Backing {
+    Heap(Vec<f32>),
+    #[cfg(feature = "storage")]
+    File(FileVectors),
+}
+
+/// Contiguous slot-major vector buffer
+pub(crate) struct VectorStore {
+    dimensions: usize,
+    backing: Backing,
+}
+
+impl VectorStore {
+    /// Create an empty heap store for vectors of `dimensions` components
+    pub fn new(dimensions: usize) -> Self {
+        Self::from_flat(dimensions, Vec::new())
+    }
+
+    /// Create a heap store from a flat buffer
+    pub fn from_flat(dimensions: usize, data: Vec<f32>) -> Self {
+        Self {
+            dimensions,
+            backing: Backing::Heap(data),
+        }
+    }
+
+    /// Serve the first `len` vectors stored at byte `offset` of `file`
+    ///
+    /// Anything in the file past those slots is overwritten as new vectors
+    /// are pushed.
+    #[cfg(feature = "storage")]
+    pub fn open_file(dimensions: usize, file: File, offset: usize, len: usize) -> Result<Self> {
+        let stored = (file_len(&file)? as usize).saturating_sub(offset);
+        if stored < len * vector_bytes(dimensions) {
+            return Err(RuvectorError::StorageError(
+                "vector file shorter than expected".to_string(),
+            ));
+        }
+        let mut vectors = FileVectors::new(file, offset, None, dimensions)?;
+        vectors.len = len;
+        Ok(Self {
+            dimensions,
+            backing: Backing::File(vectors),
+        })
+    }
+
+    /// Write every vector into `file` at byte `offset` and serve them from
+    /// there from now on
+    #[cfg(feature = "storage")]
+    pub fn move_to_file(&mut self, file: File, offset: usize) -> Result<()> {
+        self.move_into(FileVectors::new(file, offset, None, self.dimensions)?)
+    }
+
+    /// Move the vectors off the heap into an unlinked temporary file
+    ///
+    /// Does nothing if they are already file-backed.
+    #[cfg(feature = "storage")]
+    pub fn spill(&mut self) -> Result<()> {
+        if matches!(self.backing, Backing::File(_)) {
+            return Ok(());
+        }
+        let path = std::env::temp_dir().join(format!("ruvector-{}.vectors", uuid::Uuid::new_v4()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // Unlinking an open file fails on some platforms; remove it on drop there
+        let temp_path = std::fs::remove_file(&path).err().map(|_| path);
+        self.move_into(FileVectors::new(file, 0, temp_path, self.dimensions)?)
+    }
+
+    #[cfg(feature = "storage")]
+    fn move_into(&mut self, mut target: FileVectors) -> Result<()> {
+        let len = self.len();
+        target.reserve(len, self.dimensions)?;
+        let chunk_slots = ((1 << 20) / vector_bytes(self.dimensions).max(1)).max(1);
+        let mut slot = 0;
+        while slot < len {
+            let end = (slot + chunk_slots).min(len);
+            let mut bytes = Vec::with_capacity((end - slot) * vector_bytes(self.dimensions));
+            for s in slot..end {
+                bytes.extend(self.get(s as u32).iter().flat_map(|v| v.to_le_bytes()));
+            }
+            target.write_at(slot, &bytes, self.dimensions)?;
+            slot = end;
+        }
+        target.len = len;
+        self.backing = Backing::File(target);
+        Ok(())
+    }
+
+    /// Another handle on the backing file, to sync it without borrowing
+    /// the store; `None` for heap stores
+    #[cfg(feature = "storage")]
+    pub fn file_handle(&self) -> Result<Option<File>> {
+        match &self.backing {
+            Backing::File(vectors) => Ok(Some(vectors.file.try_clone()?)),
+            Backing::Heap(_) => Ok(None),
+        }
+    }
+
+    /// Number of components per vector
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    /// Number of vectors stored
+    pub fn len(&self) -> usize {
+        match &self.backing {
+            Backing::Heap(data) => data.len() / self.dimensions.max(1),
+            #[cfg(feature = "storage")]
+            Backing::File(vectors) => vectors.len,
+        }
+    }
+
+    /// Bytes of vector data, mapped or on the heap
+    pub fn memory_bytes(&self) -> usize {
+        match &self.backing {
+            Backing::Heap(data) => data.capacity() * std::mem::size_of::<f32>(),
+            #[cfg(feature = "storage")]
+            Backing::File(vectors) => vectors.len * vector_bytes(self.dimensions),
+        }
+    }
+
+    /// Bytes of vector data held on the heap
+    #[cfg(test)]
+    pub fn heap_bytes(&self) -> usize {
+        match &self.backing {
+            Backing::Heap(data) => data.capacity() * std::mem::size_of::<f32>(),
+            #[cfg(feature = "storage")]
+            Backing::File(_) => 0,
+        }
+    }
+
+    /// Vector stored at `slot`
+    #[inline]
+    pub fn get(&self, slot: u32) -> &[f32] {
+        let start = slot as usize * self.dimensions;
+        match &self.backing {
+            Backing::Heap(data) => &data[start..start + self.dimensions],
+            #[cfg(feature = "storage")]
+            Backing::File(vectors) => {
+                let bytes = &vectors.mmap[vectors.offset + start * 4..][..self.dimensions * 4];
+                // SAFETY: `FileVectors::new` checked that the target is
+                // little-endian and that `offset` keeps every slot 4-byte
+                // aligned in the page-aligned map; the bytes were written as
+                // little-endian f32 values by this store.
+                unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const f32, self.dimensions) }
+            }
+        }
+    }
+
+    /// Append a vector and return its slot
+    pub fn push(&mut self, vector: &[f32]) -> Result<u32> {
+        let slot = self.len();
+        match &mut self.backing {
+            Backing::Heap(data) => data.extend_from_slice(vector),
+            #[cfg(feature = "storage")]
+            Backing::File(vectors) => {
+                vectors.reserve(slot + 1, self.dimensions)?;
+                vectors.write_at(slot, &f32_bytes(vector), self.dimensions)?;
+                vectors.len = slot + 1;
+            }
+        }
+        Ok(slot as u32)
+    }
+
+    /// Overwrite the vector stored at an existing `slot`
+    pub fn set(&mut self, slot: u32, vector: &[f32]) -> Result<()> {
+        match &mut self.backing {
+            Backing::Heap(data) => {
+                let start = slot as usize * self.dimensions;
+                data[start..start + self.dimensions].copy_from_slice(vector);
+            }
+            #[cfg(feature = "storage")]
+            Backing::File(vectors) => {
+                vectors.write_at(slot as usize, &f32_bytes(vector), self.dimensions)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy all vectors into a single flat buffer
+    pub fn to_flat(&self) -> Vec<f32> {
+        let mut flat = Vec::with_capacity(self.len() * self.dimensions);
+        for slot in 0..self.len() {
+            flat.extend_from_slice(self.get(slot as u32));
+        }
+        flat
+    }
+}
+
+#[cfg(feature = "storage")]
+impl FileVectors {
+    fn new(file: File, offset: usize, temp_path: Option<PathBuf>, dimensions: usize) -> Result<Self> {
+        if cfg!(target_endian = "big") || offset % std::mem::align_of::<f32>() != 0 {
+            return Err(RuvectorError::StorageError(
+                "file-backed vectors need a little-endian target and an aligned offset"
+                    .to_string(),
+            ));
+        }
+        let capacity = ((file_len(&file)? as usize).saturating_sub(offset)
+            / vector_bytes(dimensions).max(1))
+        .max(MIN_FILE_SLOTS);
+        file.set_len(file_len(&file)?.max((offset + capacity * vector_bytes(dimensions)) as u64))?;
+        let mmap = map(&file, offset + capacity * vector_bytes(dimensions))?;
+        Ok(Self {
+            file,
+            mmap,
+            offset,
+            len: 0,
+            capacity,
+            temp_path,
+        })
+    }
+
+    /// Make room for at least `slots` vectors, growing the file and map
+    fn reserve(&mut self, slots: usize, dimensions: usize) -> Result<()> {
+        if slots <= self.capacity {
+            return Ok(());
+        }
+        let capacity = slots.max(self.capacity * 2);
+        let total = self.offset + capacity * vector_bytes(dimensions);
+        self.file.set_len(total as u64)?;
+        self.mmap = map(&self.file, total)?;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    /// Write the encoded vectors starting at `slot`
+    fn write_at(&mut self, slot: usize, bytes: &[u8], dimensions: usize) -> Result<()> {
+        let position = self.offset + slot * vector_bytes(dimensions);
+        self.file.seek(SeekFrom::Start(position as u64))?;
+        self.file.write_all(bytes)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "storage")]
+fn map(file: &File, len: usize) -> Result<Mmap> {
+    // SAFETY: the file is private to this store, which only writes to it
+    // through `write_at`; shared mappings see those writes coherently.
+    Ok(unsafe { MmapOptions::new().len(len).map(file)? })
+}
+
+#[cfg(feature = "storage")]
+fn file_len(file: &File) -> Result<u64> {
+    Ok(file.metadata()?.len())
+}
+
+#[cfg(feature = "storage")]
+fn vector_bytes(dimensions: usize) -> usize {
+    dimensions * std::mem::size_of::<f32>()
+}
+
+#[cfg(feature = "storage")]
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[cfg(all(test, feature = "storage"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spilled_store_keeps_vectors_off_the_heap() -> Result<()> {
+        let mut store = VectorStore::new(3);
+        store.push(&[1.0, 2.0, 3.0])?;
+        store.spill()?;
+        assert_eq!(store.heap_bytes(), 0);
+        assert_eq!(store.get(0), &[1.0, 2.0, 3.0]);
+
+        // Grows past the initial mapping
+        for i in 1..=MIN_FILE_SLOTS as u32 {
+            assert_eq!(store.push(&[i as f32; 3])?, i);
+        }
+        store.set(0, &[9.0; 3])?;
+        assert_eq!(store.get(0), &[9.0; 3]);
+        assert_eq!(store.get(MIN_FILE_SLOTS as u32), &[MIN_FILE_SLOTS as f32; 3]);
+        assert_eq!(store.len(), MIN_FILE_SLOTS + 1);
+        Ok(())
+    }
+}
@@ -30,10 +30,16 @@
 
 pub mod advanced_features;
 
+mod checksum;
+
 // AgenticDB requires storage feature
 #[cfg(feature = "storage")]
 pub mod agenticdb;
 
+// Snapshot cuts read and write redb storage directly
+#[cfg(feature = "storage")]
+pub mod cut;
+
 pub mod distance;
 pub mod embeddings;
 pub mod error;
@@ -52,6 +58,7 @@ pub use storage_memory as storage;
 
 pub mod types;
 pub mod vector_db;
+pub mod wal;
 
 // Performance optimization modules
 pub mod arena;
@@ -93,8 +100,12 @@ const _: () = {
 };
 
 pub use error::{Result, RuvectorError};
-pub use types::{DistanceMetric, SearchQuery, SearchResult, VectorEntry, VectorId};
+pub use types::{
+    DistanceMetric, IndexStats, IndexType, PointSelector, ScrollPage, SearchParams, SearchQuery,
+    SearchResult, VectorEntry, VectorId, VectorSelector, VectorSpaceConfig,
+};
 pub use vector_db::VectorDB;
+pub use wal::{WalOperation, WalRecord};
 
 #[cfg(test)]
 mod tests {
@@ -1,6 +1,7 @@
 //! Quantization techniques for memory compression
 
 use crate::error::Result;
+use crate::types::DistanceMetric;
 use serde::{Deserialize, Serialize};
 
 /// Trait for quantized vector representations
@@ -216,6 +217,79 @@ impl QuantizedVector for BinaryQuantized {
     }
 }
 
+impl ScalarQuantized {
+    /// Distance from a full-precision query to this vector, see
+    /// [`scalar_asymmetric_distance`]
+    pub fn asymmetric_distance(&self, query: &[f32], metric: DistanceMetric) -> f32 {
+        scalar_asymmetric_distance(query, &self.data, self.min, self.scale, metric)
+    }
+}
+
+impl BinaryQuantized {
+    /// Distance from a full-precision query to this vector, see
+    /// [`binary_asymmetric_distance`]
+    pub fn asymmetric_distance(&self, query: &[f32], metric: DistanceMetric) -> f32 {
+        binary_asymmetric_distance(query, &self.bits, metric)
+    }
+}
+
+/// Distance from a full-precision query to int8 codes produced by
+/// [`ScalarQuantized::quantize`]
+///
+/// Only the stored side is quantized: codes are dequantized on the fly and
+/// compared against the exact query, which keeps far more precision than
+/// quantizing both sides.
+#[inline]
+pub fn scalar_asymmetric_distance(
+    query: &[f32],
+    codes: &[u8],
+    min: f32,
+    scale: f32,
+    metric: DistanceMetric,
+) -> f32 {
+    asymmetric_distance(query, codes.iter().map(|&c| min + c as f32 * scale), metric)
+}
+
+/// Distance from a full-precision query to packed sign bits produced by
+/// [`BinaryQuantized::quantize`], reading each bit as +1.0 or -1.0
+#[inline]
+pub fn binary_asymmetric_distance(query: &[f32], bits: &[u8], metric: DistanceMetric) -> f32 {
+    let decoded = (0..query.len()).map(|i| {
+        if (bits[i / 8] >> (i % 8)) & 1 == 1 {
+            1.0
+        } else {
+            -1.0
+        }
+    });
+    asymmetric_distance(query, decoded, metric)
+}
+
+/// Apply `metric` to the query and a lazily decoded stored vector
+#[inline]
+fn asymmetric_distance(
+    query: &[f32],
+    decoded: impl Iterator<Item = f32>,
+    metric: DistanceMetric,
+) -> f32 {
+    let pairs = query.iter().copied().zip(decoded);
+    match metric {
+        DistanceMetric::Euclidean => pairs.map(|(q, x)| (q - x) * (q - x)).sum::<f32>().sqrt(),
+        DistanceMetric::Cosine => {
+            let (dot, norm_q, norm_x) = pairs.fold((0.0f32, 0.0f32, 0.0f32), |acc, (q, x)| {
+                (acc.0 + q * x, acc.1 + q * q, acc.2 + x * x)
+            });
+            let (norm_q, norm_x) = (norm_q.sqrt(), norm_x.sqrt());
+            if norm_q > 1e-8 && norm_x > 1e-8 {
+                1.0 - dot / (norm_q * norm_x)
+            } else {
+                1.0
+            }
+        }
+        DistanceMetric::DotProduct => -pairs.map(|(q, x)| q * x).sum::<f32>(),
+        DistanceMetric::Manhattan => pairs.map(|(q, x)| (q - x).abs()).sum(),
+    }
+}
+
 // Helper functions
 
 fn euclidean_squared(a: &[f32], b: &[f32]) -> f32 {
@@ -428,4 +502,37 @@ mod tests {
             dist_ab, dist_ba
         );
     }
+
+    #[test]
+    fn test_asymmetric_distance_tracks_exact_distance() {
+        use crate::distance::distance;
+
+        let stored = vec![0.5, -1.2, 3.3, 0.0, 2.1, -0.7];
+        let query = vec![0.4, -1.0, 3.0, 0.2, 2.5, -0.9];
+        let scalar = ScalarQuantized::quantize(&stored);
+
+        for metric in [
+            DistanceMetric::Euclidean,
+            DistanceMetric::Cosine,
+            DistanceMetric::DotProduct,
+            DistanceMetric::Manhattan,
+        ] {
+            let exact = distance(&query, &stored, metric).unwrap();
+            let approx = scalar.asymmetric_distance(&query, metric);
+            assert!(
+                (exact - approx).abs() < 0.1,
+                "{:?}: exact {} vs asymmetric {}",
+                metric,
+                exact,
+                approx
+            );
+        }
+
+        // Binary codes compare against the +1/-1 reconstruction
+        let binary = BinaryQuantized::quantize(&stored);
+        let reconstructed = binary.reconstruct();
+        let expected = distance(&query, &reconstructed, DistanceMetric::Euclidean).unwrap();
+        let approx = binary.asymmetric_distance(&query, DistanceMetric::Euclidean);
+        assert!((expected - approx).abs() < 1e-5);
+    }
 }
@@ -14,7 +14,9 @@ use once_cell::sync::Lazy;
 #[cfg(feature = "storage")]
 use parking_lot::Mutex;
 #[cfg(feature = "storage")]
-use redb::{Database, ReadableTable, ReadableTableMetadata, TableDefinition};
+use redb::{Database, ReadTransaction, ReadableTable, ReadableTableMetadata, TableDefinition};
+#[cfg(feature = "storage")]
+use ruvector_filter::IndexType;
 #[cfg(feature = "storage")]
 use serde_json;
 #[cfg(feature = "storage")]
@@ -28,11 +30,15 @@ use std::sync::Arc;
 
 const VECTORS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("vectors");
 const METADATA_TABLE: TableDefinition<&str, &str> = TableDefinition::new("metadata");
+const NAMED_VECTORS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("named_vectors");
 const CONFIG_TABLE: TableDefinition<&str, &str> = TableDefinition::new("config");
 
 /// Key used to store database configuration in CONFIG_TABLE
 const DB_CONFIG_KEY: &str = "__ruvector_db_config__";
 
+/// Key used to store payload index definitions in CONFIG_TABLE
+const PAYLOAD_INDEXES_KEY: &str = "__ruvector_payload_indexes__";
+
 // Global database connection pool to allow multiple VectorDB instances
 // to share the same underlying database file
 static DB_POOL: Lazy<Mutex<HashMap<PathBuf, Arc<Database>>>> =
@@ -116,6 +122,7 @@ impl VectorStorage {
                 {
                     let _ = write_txn.open_table(VECTORS_TABLE)?;
                     let _ = write_txn.open_table(METADATA_TABLE)?;
+                    let _ = write_txn.open_table(NAMED_VECTORS_TABLE)?;
                     let _ = write_txn.open_table(CONFIG_TABLE)?;
                 }
                 write_txn.commit()?;
@@ -130,12 +137,7 @@ impl VectorStorage {
 
     /// Insert a vector entry
     pub fn insert(&self, entry: &VectorEntry) -> Result<VectorId> {
-        if entry.vector.len() != self.dimensions {
-            return Err(RuvectorError::DimensionMismatch {
-                expected: self.dimensions,
-                actual: entry.vector.len(),
-            });
-        }
+        entry.check_dimensions(self.dimensions)?;
 
         let id = entry
             .id
@@ -159,6 +161,9 @@ impl VectorStorage {
                     .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
                 meta_table.insert(id.as_str(), metadata_json.as_str())?;
             }
+
+            let mut named_table = write_txn.open_table(NAMED_VECTORS_TABLE)?;
+            write_named_vectors(&mut named_table, &id, entry)?;
         }
         write_txn.commit()?;
 
@@ -173,14 +178,10 @@ impl VectorStorage {
         {
             let mut table = write_txn.open_table(VECTORS_TABLE)?;
             let mut meta_table = write_txn.open_table(METADATA_TABLE)?;
+            let mut named_table = write_txn.open_table(NAMED_VECTORS_TABLE)?;
 
             for entry in entries {
-                if entry.vector.len() != self.dimensions {
-                    return Err(RuvectorError::DimensionMismatch {
-                        expected: self.dimensions,
-                        actual: entry.vector.len(),
-                    });
-                }
+                entry.check_dimensions(self.dimensions)?;
 
                 let id = entry
                     .id
@@ -199,6 +200,8 @@ impl VectorStorage {
                     meta_table.insert(id.as_str(), metadata_json.as_str())?;
                 }
 
+                write_named_vectors(&mut named_table, &id, entry)?;
+
                 ids.push(id);
             }
         }
@@ -209,34 +212,7 @@ impl VectorStorage {
 
     /// Get a vector by ID
     pub fn get(&self, id: &str) -> Result<Option<VectorEntry>> {
-        let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(VECTORS_TABLE)?;
-
-        let Some(vector_data) = table.get(id)? else {
-            return Ok(None);
-        };
-
-        let (vector, _): (Vec<f32>, usize) =
-            bincode::decode_from_slice(vector_data.value(), config::standard())
-                .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
-
-        // Try to get metadata
-        let meta_table = read_txn.open_table(METADATA_TABLE)?;
-        let metadata = if let Some(meta_data) = meta_table.get(id)? {
-            let meta_str = meta_data.value();
-            Some(
-                serde_json::from_str(meta_str)
-                    .map_err(|e| RuvectorError::SerializationError(e.to_string()))?,
-            )
-        } else {
-            None
-        };
-
-        Ok(Some(VectorEntry {
-            id: Some(id.to_string()),
-            vector,
-            metadata,
-        }))
+        self.view()?.get(id)
     }
 
     /// Delete a vector by ID
@@ -250,6 +226,32 @@ impl VectorStorage {
 
             let mut meta_table = write_txn.open_table(METADATA_TABLE)?;
             let _ = meta_table.remove(id)?;
+
+            let mut named_table = write_txn.open_table(NAMED_VECTORS_TABLE)?;
+            let _ = named_table.remove(id)?;
+        }
+
+        write_txn.commit()?;
+        Ok(deleted)
+    }
+
+    /// Delete several vectors in one transaction, returning the ids that
+    /// existed
+    pub fn delete_batch(&self, ids: &[VectorId]) -> Result<Vec<VectorId>> {
+        let write_txn = self.db.begin_write()?;
+        let mut deleted = Vec::new();
+
+        {
+            let mut table = write_txn.open_table(VECTORS_TABLE)?;
+            let mut meta_table = write_txn.open_table(METADATA_TABLE)?;
+            let mut named_table = write_txn.open_table(NAMED_VECTORS_TABLE)?;
+            for id in ids {
+                if table.remove(id.as_str())?.is_some() {
+                    deleted.push(id.clone());
+                }
+                let _ = meta_table.remove(id.as_str())?;
+                let _ = named_table.remove(id.as_str())?;
+            }
         }
 
         write_txn.commit()?;
@@ -270,19 +272,45 @@ impl VectorStorage {
 
     /// Get all vector IDs
     pub fn all_ids(&self) -> Result<Vec<VectorId>> {
+        self.view()?.all_ids()
+    }
+
+    /// Get the IDs of the vectors that have a named vector `name`
+    ///
+    /// Only entries with named vectors are read, so this stays cheap for
+    /// spaces that few entries use.
+    pub fn named_vector_ids(&self, name: &str) -> Result<Vec<VectorId>> {
         let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(VECTORS_TABLE)?;
+        let table = read_txn.open_table(NAMED_VECTORS_TABLE)?;
 
         let mut ids = Vec::new();
-        let iter = table.iter()?;
-        for item in iter {
-            let (key, _) = item?;
-            ids.push(key.value().to_string());
+        for item in table.iter()? {
+            let (key, named_data) = item?;
+            let (named_vectors, _): (HashMap<String, Vec<f32>>, usize) =
+                bincode::decode_from_slice(named_data.value(), config::standard())
+                    .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
+            if named_vectors.contains_key(name) {
+                ids.push(key.value().to_string());
+            }
         }
-
         Ok(ids)
     }
 
+    /// Visit the vectors in id order, from the first id not below `offset`,
+    /// until `visit` returns false
+    pub fn scan(&self, offset: Option<&str>, visit: impl FnMut(VectorEntry) -> bool) -> Result<()> {
+        self.view()?.scan(offset, visit)
+    }
+
+    /// Pin a read-only view of the stored vectors as of now
+    ///
+    /// The view is unaffected by later writes, which are not blocked by it.
+    pub fn view(&self) -> Result<StorageView> {
+        Ok(StorageView {
+            txn: self.db.begin_read()?,
+        })
+    }
+
     /// Save database configuration to persistent storage
     pub fn save_config(&self, options: &DbOptions) -> Result<()> {
         let config_json = serde_json::to_string(options)
@@ -318,12 +346,152 @@ impl VectorStorage {
         Ok(Some(config))
     }
 
+    /// Save the payload index definitions (field name and index type)
+    pub fn save_payload_indexes(&self, indexes: &[(String, IndexType)]) -> Result<()> {
+        let indexes_json = serde_json::to_string(indexes)
+            .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
+
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(CONFIG_TABLE)?;
+            table.insert(PAYLOAD_INDEXES_KEY, indexes_json.as_str())?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Load the payload index definitions saved by `save_payload_indexes`
+    pub fn load_payload_indexes(&self) -> Result<Vec<(String, IndexType)>> {
+        let read_txn = self.db.begin_read()?;
+
+        let table = match read_txn.open_table(CONFIG_TABLE) {
+            Ok(t) => t,
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        let Some(indexes_data) = table.get(PAYLOAD_INDEXES_KEY)? else {
+            return Ok(Vec::new());
+        };
+
+        serde_json::from_str(indexes_data.value())
+            .map_err(|e| RuvectorError::SerializationError(e.to_string()))
+    }
+
     /// Get the stored dimensions
     pub fn dimensions(&self) -> usize {
         self.dimensions
     }
 }
 
+/// Read-only view of a [`VectorStorage`] pinned at one point in time
+pub struct StorageView {
+    txn: ReadTransaction,
+}
+
+impl StorageView {
+    /// Get a vector by ID
+    pub fn get(&self, id: &str) -> Result<Option<VectorEntry>> {
+        let table = self.txn.open_table(VECTORS_TABLE)?;
+
+        let Some(vector_data) = table.get(id)? else {
+            return Ok(None);
+        };
+
+        self.entry(id, vector_data.value()).map(Some)
+    }
+
+    /// Visit the vectors in id order, from the first id not below `offset`,
+    /// until `visit` returns false
+    pub fn scan(
+        &self,
+        offset: Option<&str>,
+        mut visit: impl FnMut(VectorEntry) -> bool,
+    ) -> Result<()> {
+        let table = self.txn.open_table(VECTORS_TABLE)?;
+        let range = match offset {
+            Some(offset) => table.range(offset..)?,
+            None => table.range::<&str>(..)?,
+        };
+        for item in range {
+            let (key, vector_data) = item?;
+            if !visit(self.entry(key.value(), vector_data.value())?) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a stored vector and add its metadata and named vectors
+    fn entry(&self, id: &str, vector_data: &[u8]) -> Result<VectorEntry> {
+        let (vector, _): (Vec<f32>, usize) =
+            bincode::decode_from_slice(vector_data, config::standard())
+                .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
+
+        // Try to get metadata
+        let meta_table = self.txn.open_table(METADATA_TABLE)?;
+        let metadata = if let Some(meta_data) = meta_table.get(id)? {
+            let meta_str = meta_data.value();
+            Some(
+                serde_json::from_str(meta_str)
+                    .map_err(|e| RuvectorError::SerializationError(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+
+        let named_table = self.txn.open_table(NAMED_VECTORS_TABLE)?;
+        let named_vectors = match named_table.get(id)? {
+            Some(named_data) => Some(
+                bincode::decode_from_slice(named_data.value(), config::standard())
+                    .map_err(|e| RuvectorError::SerializationError(e.to_string()))?
+                    .0,
+            ),
+            None => None,
+        };
+
+        Ok(VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors,
+            metadata,
+        })
+    }
+
+    /// Get all vector IDs
+    pub fn all_ids(&self) -> Result<Vec<VectorId>> {
+        let table = self.txn.open_table(VECTORS_TABLE)?;
+
+        let mut ids = Vec::new();
+        let iter = table.iter()?;
+        for item in iter {
+            let (key, _) = item?;
+            ids.push(key.value().to_string());
+        }
+
+        Ok(ids)
+    }
+}
+
+/// Store the named vectors of `entry`, replacing any previously stored ones
+fn write_named_vectors(
+    table: &mut redb::Table<&str, &[u8]>,
+    id: &str,
+    entry: &VectorEntry,
+) -> Result<()> {
+    match entry.named_vectors.as_ref().filter(|n| !n.is_empty()) {
+        Some(named_vectors) => {
+            let named_data = bincode::encode_to_vec(named_vectors, config::standard())
+                .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
+            table.insert(id, named_data.as_slice())?;
+        }
+        None => {
+            table.remove(id)?;
+        }
+    }
+    Ok(())
+}
+
 // Add uuid dependency
 use uuid;
 
@@ -340,6 +508,7 @@ mod tests {
         let entry = VectorEntry {
             id: Some("test1".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -363,11 +532,13 @@ mod tests {
             VectorEntry {
                 id: None,
                 vector: vec![1.0, 2.0, 3.0],
+                named_vectors: None,
                 metadata: None,
             },
             VectorEntry {
                 id: None,
                 vector: vec![4.0, 5.0, 6.0],
+                named_vectors: None,
                 metadata: None,
             },
         ];
@@ -387,6 +558,7 @@ mod tests {
         let entry = VectorEntry {
             id: Some("test1".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -400,6 +572,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_named_vector_ids() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let storage = VectorStorage::new(dir.path().join("test.db"), 2)?;
+        let entry = |id: &str, names: &[&str]| VectorEntry {
+            id: Some(id.to_string()),
+            vector: vec![1.0, 0.0],
+            named_vectors: Some(
+                names
+                    .iter()
+                    .map(|name| (name.to_string(), vec![0.0, 1.0]))
+                    .collect(),
+            ),
+            metadata: None,
+        };
+
+        storage.insert_batch(&[
+            entry("a", &["image"]),
+            entry("b", &[]),
+            entry("c", &["image", "title"]),
+            entry("d", &["title"]),
+        ])?;
+        assert_eq!(storage.named_vector_ids("image")?, ["a", "c"]);
+        assert_eq!(storage.named_vector_ids("title")?, ["c", "d"]);
+        assert!(storage.named_vector_ids("audio")?.is_empty());
+
+        storage.delete("c")?;
+        assert_eq!(storage.named_vector_ids("image")?, ["a"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_from_offset() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let storage = VectorStorage::new(dir.path().join("test.db"), 1)?;
+        let entries: Vec<_> = ["c", "a", "e", "b", "d"]
+            .iter()
+            .map(|id| VectorEntry {
+                id: Some(id.to_string()),
+                vector: vec![1.0],
+                named_vectors: None,
+                metadata: None,
+            })
+            .collect();
+        storage.insert_batch(&entries)?;
+
+        let mut seen = Vec::new();
+        storage.scan(Some("b"), |entry| {
+            seen.push(entry.id.unwrap());
+            seen.len() < 3
+        })?;
+        assert_eq!(seen, ["b", "c", "d"]);
+
+        let mut seen = Vec::new();
+        storage.scan(None, |entry| {
+            seen.push(entry.id.unwrap());
+            true
+        })?;
+        assert_eq!(seen, ["a", "b", "c", "d", "e"]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_multiple_instances_same_path() -> Result<()> {
         // This test verifies the fix for the database locking bug
@@ -414,6 +649,7 @@ mod tests {
         storage1.insert(&VectorEntry {
             id: Some("test1".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
@@ -428,6 +664,7 @@ mod tests {
         storage2.insert(&VectorEntry {
             id: Some("test2".to_string()),
             vector: vec![4.0, 5.0, 6.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
@@ -3,15 +3,17 @@
 //! This storage implementation doesn't require file system access,
 //! making it suitable for WebAssembly environments.
 
-use crate::error::{Result, RuvectorError};
+use crate::error::Result;
 use crate::types::{VectorEntry, VectorId};
 use dashmap::DashMap;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 /// In-memory storage backend using DashMap for thread-safe concurrent access
 pub struct MemoryStorage {
     vectors: DashMap<String, Vec<f32>>,
+    named_vectors: DashMap<String, HashMap<String, Vec<f32>>>,
     metadata: DashMap<String, JsonValue>,
     dimensions: usize,
     counter: AtomicU64,
@@ -22,6 +24,7 @@ impl MemoryStorage {
     pub fn new(dimensions: usize) -> Result<Self> {
         Ok(Self {
             vectors: DashMap::new(),
+            named_vectors: DashMap::new(),
             metadata: DashMap::new(),
             dimensions,
             counter: AtomicU64::new(0),
@@ -34,19 +37,28 @@ impl MemoryStorage {
         format!("vec_{}", id)
     }
 
+    /// Store the named vectors of `entry`, replacing any previous ones
+    fn store_named_vectors(&self, id: &str, entry: &VectorEntry) {
+        match entry.named_vectors.as_ref().filter(|n| !n.is_empty()) {
+            Some(named_vectors) => {
+                self.named_vectors
+                    .insert(id.to_string(), named_vectors.clone());
+            }
+            None => {
+                self.named_vectors.remove(id);
+            }
+        }
+    }
+
     /// Insert a vector entry
     pub fn insert(&self, entry: &VectorEntry) -> Result<VectorId> {
-        if entry.vector.len() != self.dimensions {
-            return Err(RuvectorError::DimensionMismatch {
-                expected: self.dimensions,
-                actual: entry.vector.len(),
-            });
-        }
+        entry.check_dimensions(self.dimensions)?;
 
         let id = entry.id.clone().unwrap_or_else(|| self.generate_id());
 
         // Insert vector
         self.vectors.insert(id.clone(), entry.vector.clone());
+        self.store_named_vectors(&id, entry);
 
         // Insert metadata if present
         if let Some(metadata) = &entry.metadata {
@@ -69,16 +81,12 @@ impl MemoryStorage {
         let mut ids = Vec::with_capacity(entries.len());
 
         for entry in entries {
-            if entry.vector.len() != self.dimensions {
-                return Err(RuvectorError::DimensionMismatch {
-                    expected: self.dimensions,
-                    actual: entry.vector.len(),
-                });
-            }
+            entry.check_dimensions(self.dimensions)?;
 
             let id = entry.id.clone().unwrap_or_else(|| self.generate_id());
 
             self.vectors.insert(id.clone(), entry.vector.clone());
+            self.store_named_vectors(&id, entry);
 
             if let Some(metadata) = &entry.metadata {
                 self.metadata.insert(
@@ -110,9 +118,12 @@ impl MemoryStorage {
                 }
             });
 
+            let named_vectors = self.named_vectors.get(id).map(|n| n.value().clone());
+
             Ok(Some(VectorEntry {
                 id: Some(id.to_string()),
                 vector,
+                named_vectors,
                 metadata,
             }))
         } else {
@@ -123,10 +134,22 @@ impl MemoryStorage {
     /// Delete a vector by ID
     pub fn delete(&self, id: &str) -> Result<bool> {
         let vector_removed = self.vectors.remove(id).is_some();
+        self.named_vectors.remove(id);
         self.metadata.remove(id);
         Ok(vector_removed)
     }
 
+    /// Delete several vectors, returning the ids that existed
+    pub fn delete_batch(&self, ids: &[VectorId]) -> Result<Vec<VectorId>> {
+        let mut deleted = Vec::new();
+        for id in ids {
+            if self.delete(id)? {
+                deleted.push(id.clone());
+            }
+        }
+        Ok(deleted)
+    }
+
     /// Get the number of vectors stored
     pub fn len(&self) -> Result<usize> {
         Ok(self.vectors.len())
@@ -150,9 +173,34 @@ impl MemoryStorage {
         Ok(self.keys())
     }
 
+    /// Visit the vectors in id order, from the first id not below `offset`,
+    /// until `visit` returns false
+    pub fn scan(
+        &self,
+        offset: Option<&str>,
+        mut visit: impl FnMut(VectorEntry) -> bool,
+    ) -> Result<()> {
+        let mut ids: Vec<String> = self
+            .vectors
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|id| offset.map_or(true, |offset| id.as_str() >= offset))
+            .collect();
+        ids.sort_unstable();
+        for id in ids {
+            if let Some(entry) = self.get(&id)? {
+                if !visit(entry) {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Clear all data
     pub fn clear(&self) -> Result<()> {
         self.vectors.clear();
+        self.named_vectors.clear();
         self.metadata.clear();
         Ok(())
     }
@@ -161,6 +209,7 @@ impl MemoryStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::RuvectorError;
     use serde_json::json;
 
     #[test]
@@ -170,6 +219,7 @@ mod tests {
         let entry = VectorEntry {
             id: Some("test_1".to_string()),
             vector: vec![0.1; 128],
+            named_vectors: None,
             metadata: Some(json!({"key": "value"})),
         };
 
@@ -189,6 +239,7 @@ mod tests {
             .map(|i| VectorEntry {
                 id: Some(format!("vec_{}", i)),
                 vector: vec![i as f32; 64],
+                named_vectors: None,
                 metadata: None,
             })
             .collect();
@@ -205,6 +256,7 @@ mod tests {
         let entry = VectorEntry {
             id: Some("delete_me".to_string()),
             vector: vec![1.0; 32],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -223,6 +275,7 @@ mod tests {
         let entry = VectorEntry {
             id: None,
             vector: vec![0.5; 16],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -241,6 +294,7 @@ mod tests {
         let entry = VectorEntry {
             id: Some("bad".to_string()),
             vector: vec![0.1; 64], // Wrong dimension
+            named_vectors: None,
             metadata: None,
         };
 
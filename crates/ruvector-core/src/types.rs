@@ -1,8 +1,11 @@
 //! Core types and data structures
 
+use crate::error::{Result, RuvectorError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub use ruvector_filter::{FilterExpression, IndexType};
+
 /// Unique identifier for vectors
 pub type VectorId = String;
 
@@ -24,25 +27,140 @@ pub enum DistanceMetric {
 pub struct VectorEntry {
     /// Optional ID (auto-generated if not provided)
     pub id: Option<VectorId>,
-    /// Vector data
+    /// Vector data for the default vector space
+    ///
+    /// May be left empty by entries that only carry named vectors.
+    #[serde(default)]
     pub vector: Vec<f32>,
+    /// Optional vectors for the named vector spaces in
+    /// [`DbOptions::named_vectors`]; any subset may be supplied
+    ///
+    /// Multi-vector spaces take one or more token vectors concatenated
+    /// row-major.
+    pub named_vectors: Option<HashMap<String, Vec<f32>>>,
     /// Optional metadata
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
+impl VectorEntry {
+    /// Check the default vector against the configured dimensions
+    ///
+    /// Entries that carry named vectors may leave the default vector empty.
+    pub(crate) fn check_dimensions(&self, dimensions: usize) -> Result<()> {
+        let named_only =
+            self.vector.is_empty() && self.named_vectors.as_ref().is_some_and(|n| !n.is_empty());
+        if self.vector.len() != dimensions && !named_only {
+            return Err(RuvectorError::DimensionMismatch {
+                expected: dimensions,
+                actual: self.vector.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Search query parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     /// Query vector
     pub vector: Vec<f32>,
+    /// Named vector space(s) to search instead of the default one
+    pub using: Option<VectorSelector>,
     /// Number of results to return (top-k)
     pub k: usize,
-    /// Optional metadata filters
-    pub filter: Option<HashMap<String, serde_json::Value>>,
+    /// Optional metadata filter, applied during traversal where possible
+    pub filter: Option<FilterExpression>,
     /// Optional ef_search parameter for HNSW (overrides default)
     pub ef_search: Option<usize>,
 }
 
+/// Named vector spaces targeted by a [`SearchQuery`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorSelector {
+    /// Search one named vector space with [`SearchQuery::vector`]
+    Named(String),
+    /// Search several named vector spaces, each with its own query vector,
+    /// and fuse the rankings with reciprocal rank fusion
+    ///
+    /// [`SearchQuery::vector`] is ignored. Fused scores are negated RRF
+    /// scores, so lower is still better.
+    Fusion(HashMap<String, Vec<f32>>),
+    /// Late-interaction search of a multi-vector space
+    ///
+    /// [`SearchQuery::vector`] holds the query token vectors concatenated
+    /// row-major. Points are ranked by their MaxSim score: for each query
+    /// token, the distance to the closest token of the point, summed over
+    /// query tokens (lower is better).
+    MaxSim(String),
+}
+
+/// Largest `ef_search` a query may ask for
+pub const MAX_EF_SEARCH: usize = 10_000;
+
+/// Per-query search tuning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchParams {
+    /// HNSW candidate list size (defaults to the index's configured efSearch)
+    pub ef_search: Option<usize>,
+    /// Scan every vector instead of traversing the graph, for exact results
+    pub exact: bool,
+    /// Re-rank candidates with full-precision vectors when the index
+    /// searched over quantized ones; without it the k best by quantized
+    /// distance are returned, still with full-precision scores
+    pub rescore: bool,
+    /// Candidates collected per requested result before re-ranking (finite,
+    /// at least 1.0)
+    pub oversampling: f32,
+    /// Drop results whose distance score is above this value
+    pub score_threshold: Option<f32>,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            ef_search: None,
+            exact: false,
+            rescore: true,
+            oversampling: 1.0,
+            score_threshold: None,
+        }
+    }
+}
+
+impl SearchParams {
+    /// Reject tuning values a search could not honor, such as an
+    /// `ef_search` over [`MAX_EF_SEARCH`]
+    pub fn validate(&self) -> Result<()> {
+        if !self.oversampling.is_finite() || self.oversampling < 1.0 {
+            return Err(RuvectorError::InvalidParameter(format!(
+                "oversampling must be a finite number of at least 1.0, got {}",
+                self.oversampling
+            )));
+        }
+        if let Some(ef_search) = self.ef_search.filter(|&ef| ef > MAX_EF_SEARCH) {
+            return Err(RuvectorError::InvalidParameter(format!(
+                "ef_search must be at most {}, got {}",
+                MAX_EF_SEARCH, ef_search
+            )));
+        }
+        Ok(())
+    }
+
+    /// Number of candidates to collect for `k` results
+    pub fn candidates(&self, k: usize) -> usize {
+        (k as f32 * self.oversampling.max(1.0)).ceil() as usize
+    }
+
+    /// Remove results beyond the score threshold, if any
+    pub fn apply_threshold(&self, results: &mut Vec<SearchResult>) {
+        if let Some(threshold) = self.score_threshold {
+            results.retain(|r| r.score <= threshold);
+        }
+    }
+}
+
 /// Search result with similarity score
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -56,6 +174,63 @@ pub struct SearchResult {
     pub metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// Points targeted by a delete or payload update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointSelector {
+    /// The points with these ids; ids that do not exist are skipped
+    Ids(Vec<VectorId>),
+    /// Every point whose metadata matches the filter
+    Filter(FilterExpression),
+}
+
+/// Size and health of a vector index, for monitoring
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexStats {
+    /// Number of vectors in the index
+    pub vectors: usize,
+    /// Approximate memory held by the index, including memory-mapped
+    /// vectors, in bytes
+    pub memory_bytes: usize,
+    /// Fraction of graph nodes that are deleted but not yet compacted
+    pub tombstone_ratio: f32,
+    /// Default HNSW candidate list size, for graph indexes
+    pub ef_search: Option<usize>,
+}
+
+/// A run of consecutive slots of an HNSW graph, for streaming the graph
+/// into a snapshot without its vectors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphPart {
+    /// First slot of the run
+    pub start: u32,
+    /// Id held by each slot; `None` for deleted and free slots
+    pub ids: Vec<Option<VectorId>>,
+    /// Neighbor lists of each slot, from layer 0 up to its level
+    pub links: Vec<Vec<Vec<u32>>>,
+    /// Entry point of the graph when the part was read
+    pub entry_point: Option<u32>,
+    /// Highest layer of the graph when the part was read
+    pub max_level: usize,
+}
+
+impl GraphPart {
+    /// Slot after the last one of the run
+    pub fn end(&self) -> u32 {
+        self.start + self.ids.len() as u32
+    }
+}
+
+/// One page of a scroll through a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollPage {
+    /// Points of the page, in id order
+    pub points: Vec<VectorEntry>,
+    /// Id to pass as the offset of the next page, or `None` after the
+    /// last page
+    pub next_offset: Option<VectorId>,
+}
+
 /// Database configuration options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbOptions {
@@ -68,7 +243,43 @@ pub struct DbOptions {
     /// HNSW configuration
     pub hnsw_config: Option<HnswConfig>,
     /// Quantization configuration
+    ///
+    /// Quantized codes are kept in RAM only, so opening a database with
+    /// quantization enabled re-encodes every stored vector (O(n·d)).
     pub quantization: Option<QuantizationConfig>,
+    /// Additional named vector spaces, each indexed separately
+    pub named_vectors: Option<HashMap<String, VectorSpaceConfig>>,
+}
+
+impl DbOptions {
+    /// The default vector space described by the top-level options
+    pub fn default_vector_space(&self) -> VectorSpaceConfig {
+        VectorSpaceConfig {
+            dimensions: self.dimensions,
+            distance_metric: self.distance_metric,
+            hnsw_config: self.hnsw_config.clone(),
+            quantization: self.quantization.clone(),
+            multivector: false,
+        }
+    }
+}
+
+/// Configuration of one vector space of a database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorSpaceConfig {
+    /// Vector dimensions
+    pub dimensions: usize,
+    /// Distance metric
+    pub distance_metric: DistanceMetric,
+    /// HNSW configuration (a flat index is used if `None`)
+    pub hnsw_config: Option<HnswConfig>,
+    /// Quantization configuration
+    pub quantization: Option<QuantizationConfig>,
+    /// Points store a variable number of token vectors in this space,
+    /// concatenated row-major, and are searched with
+    /// [`VectorSelector::MaxSim`]
+    #[serde(default)]
+    pub multivector: bool,
 }
 
 /// HNSW index configuration
@@ -82,6 +293,13 @@ pub struct HnswConfig {
     pub ef_search: usize,
     /// Maximum number of elements
     pub max_elements: usize,
+    /// Fraction of deleted nodes at which the graph is compacted (0 disables)
+    #[serde(default = "default_compaction_threshold")]
+    pub compaction_threshold: f32,
+}
+
+fn default_compaction_threshold() -> f32 {
+    0.2
 }
 
 impl Default for HnswConfig {
@@ -91,6 +309,7 @@ impl Default for HnswConfig {
             ef_construction: 200,
             ef_search: 100,
             max_elements: 10_000_000,
+            compaction_threshold: default_compaction_threshold(),
         }
     }
 }
@@ -102,7 +321,8 @@ pub enum QuantizationConfig {
     None,
     /// Scalar quantization to int8 (4x compression)
     Scalar,
-    /// Product quantization
+    /// Product quantization; the vector indexes do not train codebooks and
+    /// use scalar codes instead
     Product {
         /// Number of subspaces
         subspaces: usize,
@@ -121,6 +341,34 @@ impl Default for DbOptions {
             storage_path: "./ruvector.db".to_string(),
             hnsw_config: Some(HnswConfig::default()),
             quantization: Some(QuantizationConfig::Scalar),
+            named_vectors: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_params_validation() {
+        assert!(SearchParams::default().validate().is_ok());
+        let params = |ef_search, oversampling| SearchParams {
+            ef_search,
+            oversampling,
+            ..Default::default()
+        };
+        assert!(params(Some(MAX_EF_SEARCH), 4.0).validate().is_ok());
+        for invalid in [
+            params(Some(MAX_EF_SEARCH + 1), 1.0),
+            params(None, 0.5),
+            params(None, f32::NAN),
+            params(None, f32::INFINITY),
+        ] {
+            assert!(matches!(
+                invalid.validate(),
+                Err(RuvectorError::InvalidParameter(_))
+            ));
         }
     }
 }
@@ -1,14 +1,29 @@
 //! Main VectorDB interface
 
-use crate::error::Result;
+use crate::advanced_features::filtered_search::{FilterStrategy, PRE_FILTER_SELECTIVITY};
+use crate::error::{Result, RuvectorError};
 use crate::index::flat::FlatIndex;
+use crate::index::multivector::MultiVectorIndex;
+use crate::index::search_overfetched;
 
+#[cfg(feature = "storage")]
+use crate::cut::ConsistentCut;
+#[cfg(all(feature = "hnsw", feature = "storage"))]
+use crate::index::hnsw::graph_dir_for;
 #[cfg(feature = "hnsw")]
 use crate::index::hnsw::HnswIndex;
+#[cfg(feature = "storage")]
+use crate::wal::wal_path_for;
+#[cfg(all(feature = "hnsw", feature = "storage"))]
+use std::path::PathBuf;
 
 use crate::index::VectorIndex;
 use crate::types::*;
-use parking_lot::RwLock;
+use crate::wal::{WalOperation, WalRecord, WriteAheadLog};
+use parking_lot::{Mutex, MutexGuard, RwLock};
+use ruvector_filter::{FilterEvaluator, IndexType, PayloadIndexManager};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 // Import appropriate storage backend based on features
@@ -22,17 +37,54 @@ use crate::storage_memory::MemoryStorage as VectorStorage;
 pub struct VectorDB {
     storage: Arc<VectorStorage>,
     index: Arc<RwLock<Box<dyn VectorIndex>>>,
+    /// Indexes of the named vector spaces, by name
+    named_indexes: HashMap<String, Arc<RwLock<Box<dyn VectorIndex>>>>,
+    payload_indexes: Arc<RwLock<PayloadIndexManager>>,
+    /// Held across each mutation so the log order is the apply order
+    wal: Mutex<WriteAheadLog>,
+    /// Set when a logged operation reached storage but not every index;
+    /// writes and index reads fail until a reopen replays it
+    diverged: RwLock<Option<String>>,
     options: DbOptions,
 }
 
+/// Why a logged operation failed to apply
+enum ApplyError {
+    /// Nothing reached storage, so the operation can be aborted
+    Rejected(RuvectorError),
+    /// Storage committed the operation but an index did not follow
+    Diverged(RuvectorError),
+}
+
+impl From<ApplyError> for RuvectorError {
+    fn from(error: ApplyError) -> Self {
+        match error {
+            ApplyError::Rejected(e) | ApplyError::Diverged(e) => e,
+        }
+    }
+}
+
 impl VectorDB {
     /// Create a new vector database with the given options
     ///
-    /// If a storage path is provided and contains persisted vectors,
-    /// the HNSW index will be automatically rebuilt from storage.
+    /// If a storage path is provided and contains persisted vectors, the
+    /// HNSW graph persisted next to it is reopened; it is only rebuilt from
+    /// storage if the graph files are missing or corrupt.
     /// If opening an existing database, the stored configuration (dimensions,
     /// distance metric, etc.) will be used instead of the provided options.
+    /// Each named vector space gets its own index next to the default one.
+    /// Operations logged to the write-ahead log after its last checkpoint
+    /// are re-applied before the database is returned, which also brings
+    /// the reopened graphs up to date.
     pub fn new(mut options: DbOptions) -> Result<Self> {
+        for name in options
+            .named_vectors
+            .iter()
+            .flat_map(|spaces| spaces.keys())
+        {
+            check_vector_name(name)?;
+        }
+
         #[cfg(feature = "storage")]
         let storage = {
             // First, try to load existing configuration from the database
@@ -55,6 +107,7 @@ impl VectorDB {
                     distance_metric: config.distance_metric,
                     hnsw_config: config.hnsw_config,
                     quantization: config.quantization,
+                    named_vectors: config.named_vectors,
                 };
                 // Recreate storage with correct dimensions
                 Arc::new(VectorStorage::new(
@@ -75,57 +128,300 @@ impl VectorDB {
         #[cfg(not(feature = "storage"))]
         let storage = Arc::new(VectorStorage::new(options.dimensions)?);
 
+        #[cfg(feature = "storage")]
+        let (wal, replay) = WriteAheadLog::open(&wal_path_for(&options.storage_path))?;
+        #[cfg(not(feature = "storage"))]
+        let (wal, replay) = (WriteAheadLog::in_memory(), Vec::<WalRecord>::new());
+
+        // Without a history in the log, persisted graphs are checked against
+        // every stored id instead of being brought up to date by the replay
+        let reconcile = wal.is_new();
+        let index = Self::open_index(
+            &options.storage_path,
+            &options.default_vector_space(),
+            None,
+            &storage,
+            reconcile,
+        )?;
+        let mut named_indexes = HashMap::new();
+        for (name, space) in options.named_vectors.iter().flatten() {
+            let index = Self::open_index(
+                &options.storage_path,
+                space,
+                Some(name),
+                &storage,
+                reconcile,
+            )?;
+            named_indexes.insert(name.clone(), Arc::new(RwLock::new(index)));
+        }
+
+        let db = Self {
+            storage,
+            index: Arc::new(RwLock::new(index)),
+            named_indexes,
+            payload_indexes: Arc::new(RwLock::new(PayloadIndexManager::new())),
+            wal: Mutex::new(wal),
+            diverged: RwLock::new(None),
+            options,
+        };
+
+        if !replay.is_empty() {
+            tracing::info!(
+                "Replaying {} operations from the write-ahead log",
+                replay.len()
+            );
+            for record in replay {
+                db.apply(record.operation)?;
+            }
+            db.checkpoint()?;
+        }
+
+        #[cfg(feature = "storage")]
+        for (field, index_type) in db.storage.load_payload_indexes()? {
+            db.build_payload_index(&field, index_type)?;
+        }
+
+        Ok(db)
+    }
+
+    /// Open the index of one vector space, filled from storage
+    ///
+    /// `name` is `None` for the default vector space. `reconcile` asks for a
+    /// persisted graph to be checked against every stored id.
+    fn open_index(
+        storage_path: &str,
+        space: &VectorSpaceConfig,
+        name: Option<&str>,
+        storage: &VectorStorage,
+        reconcile: bool,
+    ) -> Result<Box<dyn VectorIndex>> {
+        if space.multivector {
+            return Self::open_multivector_index(space, name, storage);
+        }
+
         // Choose index based on configuration and available features
-        let mut index: Box<dyn VectorIndex> = if let Some(hnsw_config) = &options.hnsw_config {
-            #[cfg(feature = "hnsw")]
+        let index: Box<dyn VectorIndex> = if let Some(hnsw_config) = &space.hnsw_config {
+            #[cfg(all(feature = "hnsw", feature = "storage"))]
             {
-                Box::new(HnswIndex::new(
-                    options.dimensions,
-                    options.distance_metric,
-                    hnsw_config.clone(),
+                Box::new(Self::open_hnsw_index(
+                    storage_path,
+                    space,
+                    hnsw_config,
+                    name,
+                    storage,
+                    reconcile,
                 )?)
             }
+            #[cfg(all(feature = "hnsw", not(feature = "storage")))]
+            {
+                let mut index =
+                    HnswIndex::new(space.dimensions, space.distance_metric, hnsw_config.clone())?;
+                if let Some(quantization) = &space.quantization {
+                    index.set_quantization(quantization)?;
+                }
+                Box::new(index)
+            }
             #[cfg(not(feature = "hnsw"))]
             {
                 // Fall back to flat index if HNSW is not available
                 tracing::warn!("HNSW requested but not available (WASM build), using flat index");
-                Box::new(FlatIndex::new(options.dimensions, options.distance_metric))
+                let mut index = FlatIndex::new(space.dimensions, space.distance_metric);
+                if let Some(quantization) = &space.quantization {
+                    index.set_quantization(quantization)?;
+                }
+                #[cfg(feature = "storage")]
+                Self::load_into_index(&mut index, storage, Self::space_ids(storage, name)?, name)?;
+                Box::new(index)
             }
         } else {
-            Box::new(FlatIndex::new(options.dimensions, options.distance_metric))
+            // Rebuild index from persisted vectors if storage is not empty
+            // This fixes the bug where search() returns empty results after restart
+            let mut index = FlatIndex::new(space.dimensions, space.distance_metric);
+            if let Some(quantization) = &space.quantization {
+                index.set_quantization(quantization)?;
+            }
+            #[cfg(feature = "storage")]
+            Self::load_into_index(&mut index, storage, Self::space_ids(storage, name)?, name)?;
+            Box::new(index)
         };
 
-        // Rebuild index from persisted vectors if storage is not empty
-        // This fixes the bug where search() returns empty results after restart
+        #[cfg(not(all(feature = "hnsw", feature = "storage")))]
+        let _ = reconcile;
+        #[cfg(not(feature = "storage"))]
+        let _ = (storage_path, name, storage);
+
+        Ok(index)
+    }
+
+    /// Build the index of a multi-vector space from storage
+    ///
+    /// Token vectors go into an in-memory HNSW graph if the space configures
+    /// one, or a flat index otherwise; the token graph is not persisted and
+    /// is rebuilt whenever the database is opened.
+    fn open_multivector_index(
+        space: &VectorSpaceConfig,
+        name: Option<&str>,
+        storage: &VectorStorage,
+    ) -> Result<Box<dyn VectorIndex>> {
+        let tokens: Box<dyn VectorIndex> = match &space.hnsw_config {
+            #[cfg(feature = "hnsw")]
+            Some(hnsw_config) => {
+                let mut index =
+                    HnswIndex::new(space.dimensions, space.distance_metric, hnsw_config.clone())?;
+                if let Some(quantization) = &space.quantization {
+                    index.set_quantization(quantization)?;
+                }
+                Box::new(index)
+            }
+            _ => {
+                let mut index = FlatIndex::new(space.dimensions, space.distance_metric);
+                if let Some(quantization) = &space.quantization {
+                    index.set_quantization(quantization)?;
+                }
+                Box::new(index)
+            }
+        };
+
+        #[cfg_attr(not(feature = "storage"), allow(unused_mut))]
+        let mut index = MultiVectorIndex::new(space.dimensions, space.distance_metric, tokens);
         #[cfg(feature = "storage")]
-        {
-            let stored_ids = storage.all_ids()?;
-            if !stored_ids.is_empty() {
-                tracing::info!(
-                    "Rebuilding index from {} persisted vectors",
-                    stored_ids.len()
-                );
+        Self::load_into_index(&mut index, storage, Self::space_ids(storage, name)?, name)?;
+        #[cfg(not(feature = "storage"))]
+        let _ = (name, storage);
+        Ok(Box::new(index))
+    }
 
-                // Batch load all vectors for efficient index rebuilding
-                let mut entries = Vec::with_capacity(stored_ids.len());
-                for id in stored_ids {
-                    if let Some(entry) = storage.get(&id)? {
-                        entries.push((id, entry.vector));
-                    }
+    /// Open the persisted HNSW graph next to the storage file
+    ///
+    /// The graph is checkpointed before every write-ahead log checkpoint, so
+    /// replaying the log brings it up to date; only with `reconcile` is it
+    /// checked against every stored id of its space. It is rebuilt from scratch if the
+    /// graph files are missing or corrupt.
+    #[cfg(all(feature = "hnsw", feature = "storage"))]
+    fn open_hnsw_index(
+        storage_path: &str,
+        space: &VectorSpaceConfig,
+        hnsw_config: &HnswConfig,
+        name: Option<&str>,
+        storage: &VectorStorage,
+        reconcile: bool,
+    ) -> Result<HnswIndex> {
+        let graph_dir = graph_dir(storage_path, name);
+
+        match HnswIndex::open(
+            &graph_dir,
+            space.dimensions,
+            space.distance_metric,
+            hnsw_config.clone(),
+        ) {
+            Ok(Some(mut index)) => {
+                if let Some(quantization) = &space.quantization {
+                    index.set_quantization(quantization)?;
+                }
+                if reconcile {
+                    Self::reconcile_index(&mut index, storage, name)?;
                 }
 
-                // Add all vectors to index in batch for better performance
-                index.add_batch(entries)?;
+                tracing::info!("Loaded persisted HNSW graph with {} vectors", index.len());
+                return Ok(index);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Persisted HNSW graph at {:?} is unusable ({}), rebuilding",
+                    graph_dir,
+                    e
+                );
+            }
+        }
+
+        let mut index = HnswIndex::create(
+            &graph_dir,
+            space.dimensions,
+            space.distance_metric,
+            hnsw_config.clone(),
+        )?;
+        if let Some(quantization) = &space.quantization {
+            index.set_quantization(quantization)?;
+        }
+        Self::load_into_index(&mut index, storage, Self::space_ids(storage, name)?, name)?;
+        index.checkpoint()?;
+        Ok(index)
+    }
 
-                tracing::info!("Index rebuilt successfully");
+    /// Bring a persisted graph in line with the stored ids of its space,
+    /// then checkpoint it so that later opens can rely on the write-ahead log
+    #[cfg(all(feature = "hnsw", feature = "storage"))]
+    pub(crate) fn reconcile_index(
+        index: &mut HnswIndex,
+        storage: &VectorStorage,
+        name: Option<&str>,
+    ) -> Result<()> {
+        let stored_ids = Self::space_ids(storage, name)?;
+        let stored: HashSet<&VectorId> = stored_ids.iter().collect();
+        for id in index.ids() {
+            if !stored.contains(&id) {
+                index.remove(&id)?;
             }
         }
 
-        Ok(Self {
-            storage,
-            index: Arc::new(RwLock::new(index)),
-            options,
-        })
+        let missing: Vec<VectorId> = stored_ids
+            .iter()
+            .filter(|id| !index.contains(id))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            tracing::info!(
+                "Catching up persisted HNSW graph with {} vectors",
+                missing.len()
+            );
+            Self::load_into_index(index, storage, missing, name)?;
+        }
+        index.checkpoint()
+    }
+
+    /// Ids of the stored entries with a vector in the named space `name`,
+    /// or of every entry for the default space
+    #[cfg(feature = "storage")]
+    fn space_ids(storage: &VectorStorage, name: Option<&str>) -> Result<Vec<VectorId>> {
+        match name {
+            Some(name) => storage.named_vector_ids(name),
+            None => storage.all_ids(),
+        }
+    }
+
+    /// Add the stored vectors for `ids` to `index` in one batch
+    ///
+    /// Reads the named vector `name`, or the default vector if `None`;
+    /// entries without that vector are skipped.
+    #[cfg(feature = "storage")]
+    fn load_into_index(
+        index: &mut dyn VectorIndex,
+        storage: &VectorStorage,
+        ids: Vec<VectorId>,
+        name: Option<&str>,
+    ) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        tracing::info!("Rebuilding index from {} persisted vectors", ids.len());
+
+        // Batch load all vectors for efficient index rebuilding
+        let mut entries = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(vector) = storage
+                .get(&id)?
+                .and_then(|entry| entry_vector(entry, name))
+            {
+                entries.push((id, vector));
+            }
+        }
+
+        // Add all vectors to index in batch for better performance
+        index.add_batch(entries)?;
+
+        tracing::info!("Index rebuilt successfully");
+        Ok(())
     }
 
     /// Create with default options
@@ -137,97 +433,930 @@ impl VectorDB {
 
     /// Insert a vector entry
     pub fn insert(&self, entry: VectorEntry) -> Result<VectorId> {
-        let id = self.storage.insert(&entry)?;
+        let mut ids = self.insert_batch(vec![entry])?;
+        Ok(ids.remove(0))
+    }
 
-        // Add to index
-        let mut index = self.index.write();
-        index.add(id.clone(), entry.vector)?;
+    /// Insert multiple vectors in a batch
+    ///
+    /// The batch is logged as one operation, so after a crash it is either
+    /// re-applied in full or was never acknowledged.
+    pub fn insert_batch(&self, mut entries: Vec<VectorEntry>) -> Result<Vec<VectorId>> {
+        for entry in &mut entries {
+            self.check_entry(entry)?;
+            // Replays must reuse the generated ids
+            entry
+                .id
+                .get_or_insert_with(|| uuid::Uuid::new_v4().to_string());
+        }
 
-        Ok(id)
+        let mut wal = self.lock_wal()?;
+        let sequence = wal.append_upsert(&entries)?;
+        self.finish_logged(&mut wal, sequence, self.apply_upsert(entries))
     }
 
-    /// Insert multiple vectors in a batch
-    pub fn insert_batch(&self, entries: Vec<VectorEntry>) -> Result<Vec<VectorId>> {
-        let ids = self.storage.insert_batch(&entries)?;
+    /// Apply a logged operation to storage and every index
+    fn apply(&self, operation: WalOperation) -> Result<()> {
+        match operation {
+            WalOperation::Upsert(entries) => self.apply_upsert(entries).map(drop)?,
+            WalOperation::Delete(ids) => self.apply_delete(&ids).map(drop)?,
+        }
+        Ok(())
+    }
 
-        // Add to index
-        let mut index = self.index.write();
-        let index_entries: Vec<_> = ids
+    /// Write entries to storage, then to the payload and vector indexes
+    ///
+    /// Everything that can fail is read before the single storage
+    /// transaction; once it commits, a failure leaves the indexes behind.
+    fn apply_upsert(
+        &self,
+        entries: Vec<VectorEntry>,
+    ) -> std::result::Result<Vec<VectorId>, ApplyError> {
+        let previous = entries
             .iter()
-            .zip(entries.iter())
-            .map(|(id, entry)| (id.clone(), entry.vector.clone()))
-            .collect();
+            .map(|entry| self.indexed_metadata(entry.id.as_deref()))
+            .collect::<Result<Vec<_>>>()
+            .map_err(ApplyError::Rejected)?;
+        let ids = self
+            .storage
+            .insert_batch(&entries)
+            .map_err(ApplyError::Rejected)?;
+        self.index_upserted(&ids, entries, &previous)
+            .map_err(ApplyError::Diverged)?;
+        Ok(ids)
+    }
 
-        index.add_batch(index_entries)?;
+    /// Bring the payload and vector indexes up to date with stored entries
+    fn index_upserted(
+        &self,
+        ids: &[VectorId],
+        entries: Vec<VectorEntry>,
+        previous: &[Option<HashMap<String, Value>>],
+    ) -> Result<()> {
+        for ((id, entry), previous) in ids.iter().zip(&entries).zip(previous) {
+            self.update_payload_indexes(id, previous.as_ref(), entry.metadata.as_ref())?;
+        }
+        self.index_entries(ids.iter().cloned().zip(entries).collect())
+    }
 
-        Ok(ids)
+    /// Remove ids from storage in one transaction, then from every index,
+    /// returning how many existed
+    fn apply_delete(&self, ids: &[VectorId]) -> std::result::Result<usize, ApplyError> {
+        let previous = ids
+            .iter()
+            .map(|id| Ok((id, self.indexed_metadata(Some(id))?)))
+            .collect::<Result<HashMap<_, _>>>()
+            .map_err(ApplyError::Rejected)?;
+        let deleted = self
+            .storage
+            .delete_batch(ids)
+            .map_err(ApplyError::Rejected)?;
+        self.unindex_deleted(&deleted, &previous)
+            .map_err(ApplyError::Diverged)?;
+        Ok(deleted.len())
+    }
+
+    /// Drop deleted ids from the payload and vector indexes
+    fn unindex_deleted(
+        &self,
+        deleted: &[VectorId],
+        previous: &HashMap<&VectorId, Option<HashMap<String, Value>>>,
+    ) -> Result<()> {
+        for id in deleted {
+            let previous = previous.get(id).and_then(Option::as_ref);
+            self.update_payload_indexes(id, previous, None)?;
+            self.index.write().remove(id)?;
+            for index in self.named_indexes.values() {
+                index.write().remove(id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Close out the operation logged at `sequence` with the result of
+    /// applying it
+    ///
+    /// An operation that never reached storage is aborted in the log so it
+    /// is not replayed. One that did keeps its record, and the database
+    /// fails closed until a reopen replays it into the indexes.
+    fn finish_logged<T>(
+        &self,
+        wal: &mut WriteAheadLog,
+        sequence: u64,
+        result: std::result::Result<T, ApplyError>,
+    ) -> Result<T> {
+        match result {
+            Ok(value) => {
+                self.maybe_checkpoint(wal)?;
+                Ok(value)
+            }
+            Err(ApplyError::Rejected(e)) => {
+                if let Err(abort_error) = wal.abort(sequence) {
+                    tracing::warn!(
+                        "Failed to abort write-ahead log record {}: {}",
+                        sequence,
+                        abort_error
+                    );
+                }
+                Err(e)
+            }
+            Err(ApplyError::Diverged(e)) => {
+                tracing::error!(
+                    "Write-ahead log record {} reached storage but not every index: {}",
+                    sequence,
+                    e
+                );
+                *self.diverged.write() = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Lock the write-ahead log for a mutation
+    fn lock_wal(&self) -> Result<MutexGuard<'_, WriteAheadLog>> {
+        self.check_consistent()?;
+        Ok(self.wal.lock())
+    }
+
+    /// Fail if an earlier write left the indexes behind storage
+    fn check_consistent(&self) -> Result<()> {
+        match &*self.diverged.read() {
+            Some(error) => Err(RuvectorError::StorageError(format!(
+                "Indexes are behind storage after a failed write ({}); reopen the database to repair them",
+                error
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Checkpoint if the write-ahead log has grown large enough
+    fn maybe_checkpoint(&self, wal: &mut WriteAheadLog) -> Result<()> {
+        if wal.should_checkpoint() {
+            self.checkpoint_indexes()?;
+            wal.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Reject entries that storage or an index would refuse, before they
+    /// are logged
+    ///
+    /// Named vectors must belong to a declared vector space and have its
+    /// size; multi-vector spaces take any non-zero number of whole tokens.
+    fn check_entry(&self, entry: &VectorEntry) -> Result<()> {
+        entry.check_dimensions(self.options.dimensions)?;
+        for (name, vector) in entry.named_vectors.iter().flatten() {
+            let space = self.named_space(name)?;
+            let valid = if space.multivector {
+                !vector.is_empty() && vector.len() % space.dimensions.max(1) == 0
+            } else {
+                vector.len() == space.dimensions
+            };
+            if !valid {
+                return Err(RuvectorError::DimensionMismatch {
+                    expected: space.dimensions,
+                    actual: vector.len(),
+                });
+            }
+            if !vector.iter().all(|x| x.is_finite()) {
+                return Err(RuvectorError::InvalidInput(format!(
+                    "Vector '{}' has NaN or infinite values",
+                    name
+                )));
+            }
+        }
+        // The log could not encode them, nor could distances rank them
+        if !entry.vector.iter().all(|x| x.is_finite()) {
+            return Err(RuvectorError::InvalidInput(
+                "Vector has NaN or infinite values".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Add stored entries to the index of every vector space
+    ///
+    /// A space an entry carries no vector for drops whatever was indexed
+    /// under its id before, so upserts can remove vectors.
+    fn index_entries(&self, entries: Vec<(VectorId, VectorEntry)>) -> Result<()> {
+        let mut default_space = (Vec::new(), Vec::new());
+        let mut named: HashMap<&str, (Vec<_>, Vec<_>)> = HashMap::new();
+        for (id, entry) in entries {
+            let mut named_vectors = entry.named_vectors.unwrap_or_default();
+            for name in self.named_indexes.keys() {
+                let (added, removed) = named.entry(name.as_str()).or_default();
+                match named_vectors.remove(name) {
+                    Some(vector) => added.push((id.clone(), vector)),
+                    None => removed.push(id.clone()),
+                }
+            }
+            if entry.vector.is_empty() {
+                default_space.1.push(id);
+            } else {
+                default_space.0.push((id, entry.vector));
+            }
+        }
+
+        update_index(&self.index, default_space.0, default_space.1)?;
+        for (name, (added, removed)) in named {
+            update_index(&self.named_indexes[name], added, removed)?;
+        }
+        Ok(())
     }
 
     /// Search for similar vectors
+    ///
+    /// Filters are planned by their estimated selectivity: filters on
+    /// indexed fields are resolved to an allow-set that restricts graph
+    /// traversal (or is scanned exhaustively if it is tiny), while broad or
+    /// unindexed filters are checked against stored metadata on an
+    /// overfetched result set.
+    ///
+    /// `query.using` selects a named vector space, fuses the rankings of
+    /// several with reciprocal rank fusion, or runs a MaxSim search over a
+    /// multi-vector space.
     pub fn search(&self, query: SearchQuery) -> Result<Vec<SearchResult>> {
-        let index = self.index.read();
-        let mut results = index.search(&query.vector, query.k)?;
+        self.search_with_params(query, &SearchParams::default())
+    }
+
+    /// Search for similar vectors with per-query tuning
+    ///
+    /// `query.ef_search` is used when `params` does not set one. Parameters
+    /// out of range are rejected with [`RuvectorError::InvalidParameter`].
+    pub fn search_with_params(
+        &self,
+        query: SearchQuery,
+        params: &SearchParams,
+    ) -> Result<Vec<SearchResult>> {
+        self.check_consistent()?;
+        let params = SearchParams {
+            ef_search: params.ef_search.or(query.ef_search),
+            ..params.clone()
+        };
+        params.validate()?;
+        let filter = query.filter.as_ref();
+        let mut results = match &query.using {
+            None => self.search_index(&self.index, &query.vector, query.k, &params, filter)?,
+            Some(VectorSelector::Named(name)) => {
+                self.check_multivector(name, false)?;
+                let index = self.named_index(name)?;
+                self.search_index(index, &query.vector, query.k, &params, filter)?
+            }
+            Some(VectorSelector::MaxSim(name)) => {
+                self.check_multivector(name, true)?;
+                let index = self.named_index(name)?;
+                self.search_index(index, &query.vector, query.k, &params, filter)?
+            }
+            Some(VectorSelector::Fusion(queries)) => {
+                let candidates = params.candidates(query.k);
+                let rankings = queries
+                    .iter()
+                    .map(|(name, vector)| {
+                        let index = self.named_index(name)?;
+                        self.search_index(index, vector, candidates, &params, filter)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                fuse_rankings(rankings, query.k)
+            }
+        };
 
         // Enrich results with full data if needed
+        let name = match &query.using {
+            Some(VectorSelector::Named(name) | VectorSelector::MaxSim(name)) => Some(name.as_str()),
+            _ => None,
+        };
         for result in &mut results {
-            if let Ok(Some(entry)) = self.storage.get(&result.id) {
-                result.vector = Some(entry.vector);
-                result.metadata = entry.metadata;
+            if let Ok(Some(mut entry)) = self.storage.get(&result.id) {
+                result.metadata = entry.metadata.take();
+                result.vector = entry_vector(entry, name);
             }
         }
 
-        // Apply metadata filters if specified
-        if let Some(filter) = &query.filter {
-            results.retain(|r| {
-                if let Some(metadata) = &r.metadata {
-                    filter
-                        .iter()
-                        .all(|(key, value)| metadata.get(key).map_or(false, |v| v == value))
-                } else {
-                    false
+        Ok(results)
+    }
+
+    /// Search one vector space, applying `filter` if any
+    fn search_index(
+        &self,
+        index: &RwLock<Box<dyn VectorIndex>>,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: Option<&FilterExpression>,
+    ) -> Result<Vec<SearchResult>> {
+        let index = index.read();
+        match filter {
+            Some(filter) => self.search_with_filter(&**index, query, k, params, filter),
+            None => index.search_with_params(query, k, params),
+        }
+    }
+
+    /// Configuration of the named vector space `name`
+    fn named_space(&self, name: &str) -> Result<&VectorSpaceConfig> {
+        self.options
+            .named_vectors
+            .as_ref()
+            .and_then(|spaces| spaces.get(name))
+            .ok_or_else(|| unknown_vector(name))
+    }
+
+    /// Check that `name` is a multi-vector space exactly if `multivector`
+    ///
+    /// Multi-vector spaces are only searched with [`VectorSelector::MaxSim`],
+    /// and MaxSim only applies to them.
+    fn check_multivector(&self, name: &str, multivector: bool) -> Result<()> {
+        match (self.named_space(name)?.multivector, multivector) {
+            (true, false) => Err(RuvectorError::InvalidInput(format!(
+                "Named vector {} is a multi-vector space; search it with MaxSim",
+                name
+            ))),
+            (false, true) => Err(RuvectorError::InvalidInput(format!(
+                "Named vector {} is not a multi-vector space",
+                name
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Index of the named vector space `name`
+    fn named_index(&self, name: &str) -> Result<&RwLock<Box<dyn VectorIndex>>> {
+        self.named_indexes
+            .get(name)
+            .map(|index| &**index)
+            .ok_or_else(|| unknown_vector(name))
+    }
+
+    /// Search `index` for the k nearest vectors matching `filter`
+    fn search_with_filter(
+        &self,
+        index: &dyn VectorIndex,
+        query: &[f32],
+        k: usize,
+        params: &SearchParams,
+        filter: &FilterExpression,
+    ) -> Result<Vec<SearchResult>> {
+        let total = index.len();
+        let selectivity = filter.estimate_selectivity(total);
+        let allowed = {
+            let payload_indexes = self.payload_indexes.read();
+            let indexed = filter
+                .get_fields()
+                .iter()
+                .all(|field| payload_indexes.has_index(field));
+
+            match FilterStrategy::for_selectivity(selectivity, indexed) {
+                FilterStrategy::PostFilter | FilterStrategy::Auto => None,
+                FilterStrategy::PreFilter | FilterStrategy::InGraph => {
+                    match FilterEvaluator::new(&payload_indexes).evaluate(filter) {
+                        Ok(allowed) => Some(allowed),
+                        Err(e) => {
+                            tracing::debug!(
+                                "Payload indexes cannot resolve filter ({}), post-filtering",
+                                e
+                            );
+                            None
+                        }
+                    }
                 }
-            });
+            }
+        };
+
+        match allowed {
+            // The allow-set is exact, so re-plan on its real size
+            Some(allowed) if allowed.len() as f32 <= PRE_FILTER_SELECTIVITY * total as f32 => {
+                let ids: Vec<VectorId> = allowed.into_iter().collect();
+                index.search_among(query, k, params, &ids)
+            }
+            Some(allowed) => index.search_filtered(query, k, params, &|id| allowed.contains(id)),
+            None => {
+                let fetch = (k as f32 / selectivity.max(PRE_FILTER_SELECTIVITY)).ceil() as usize;
+                search_overfetched(index, query, k, params, fetch, &|id| {
+                    self.metadata_matches(id, filter)
+                })
+            }
+        }
+    }
+
+    /// Index a metadata field so filters on it can be resolved during search
+    ///
+    /// Existing vectors are indexed immediately, and the index definition
+    /// is persisted and rebuilt when the database is reopened.
+    pub fn create_payload_index(&self, field: &str, index_type: IndexType) -> Result<()> {
+        self.build_payload_index(field, index_type)?;
+        #[cfg(feature = "storage")]
+        self.storage
+            .save_payload_indexes(&self.payload_index_definitions())?;
+        Ok(())
+    }
+
+    /// Drop the payload index on a metadata field
+    pub fn drop_payload_index(&self, field: &str) -> Result<()> {
+        self.payload_indexes.write().drop_index(field)?;
+        #[cfg(feature = "storage")]
+        self.storage
+            .save_payload_indexes(&self.payload_index_definitions())?;
+        Ok(())
+    }
+
+    /// Indexed metadata fields and their index types
+    pub fn payload_index_definitions(&self) -> Vec<(String, IndexType)> {
+        let payload_indexes = self.payload_indexes.read();
+        let mut definitions: Vec<_> = payload_indexes
+            .indexed_fields()
+            .into_iter()
+            .filter_map(|field| {
+                let index_type = payload_indexes.get_index(&field)?.index_type();
+                Some((field, index_type))
+            })
+            .collect();
+        definitions.sort_by(|a, b| a.0.cmp(&b.0));
+        definitions
+    }
+
+    /// Create an in-memory payload index and fill it from storage
+    fn build_payload_index(&self, field: &str, index_type: IndexType) -> Result<()> {
+        let mut payload_indexes = self.payload_indexes.write();
+        payload_indexes.create_index(field, index_type)?;
+        let index = payload_indexes
+            .get_index_mut(field)
+            .expect("payload index was just created");
+        for id in self.storage.all_ids()? {
+            if let Some(value) = self
+                .storage
+                .get(&id)?
+                .and_then(|entry| entry.metadata)
+                .and_then(|mut metadata| metadata.remove(field))
+            {
+                index.add(&id, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stored metadata for `id`, if any payload index may need updating
+    fn indexed_metadata(&self, id: Option<&str>) -> Result<Option<HashMap<String, Value>>> {
+        match id {
+            Some(id) if self.payload_indexes.read().index_count() > 0 => {
+                Ok(self.storage.get(id)?.and_then(|entry| entry.metadata))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the payload index entries of `id`
+    fn update_payload_indexes(
+        &self,
+        id: &str,
+        previous: Option<&HashMap<String, Value>>,
+        metadata: Option<&HashMap<String, Value>>,
+    ) -> Result<()> {
+        let mut payload_indexes = self.payload_indexes.write();
+        if payload_indexes.index_count() == 0 {
+            return Ok(());
+        }
+        if let Some(previous) = previous {
+            payload_indexes.remove_payload(id, &payload_value(previous))?;
+        }
+        if let Some(metadata) = metadata {
+            payload_indexes.index_payload(id, &payload_value(metadata))?;
         }
+        Ok(())
+    }
+
+    /// Delete a vector by ID
+    pub fn delete(&self, id: &str) -> Result<bool> {
+        let ids = [id.to_string()];
+        let mut wal = self.lock_wal()?;
+        let sequence = wal.append_delete(&ids)?;
+        let deleted = self.finish_logged(&mut wal, sequence, self.apply_delete(&ids))?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete the selected points as one logged operation, returning how
+    /// many existed
+    pub fn delete_points(&self, selector: &PointSelector) -> Result<usize> {
+        let mut wal = self.lock_wal()?;
+        let ids = self.selected_ids(selector)?;
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let sequence = wal.append_delete(&ids)?;
+        self.finish_logged(&mut wal, sequence, self.apply_delete(&ids))
+    }
+
+    /// Merge `payload` into the metadata of the selected points, returning
+    /// how many were updated
+    pub fn set_payload(
+        &self,
+        selector: &PointSelector,
+        payload: &HashMap<String, Value>,
+    ) -> Result<usize> {
+        self.update_metadata(selector, |metadata| {
+            metadata.extend(payload.iter().map(|(k, v)| (k.clone(), v.clone())));
+        })
+    }
+
+    /// Replace the metadata of the selected points with `payload`
+    pub fn overwrite_payload(
+        &self,
+        selector: &PointSelector,
+        payload: &HashMap<String, Value>,
+    ) -> Result<usize> {
+        self.update_metadata(selector, |metadata| *metadata = payload.clone())
+    }
+
+    /// Remove `keys` from the metadata of the selected points
+    pub fn delete_payload(&self, selector: &PointSelector, keys: &[String]) -> Result<usize> {
+        self.update_metadata(selector, |metadata| {
+            for key in keys {
+                metadata.remove(key);
+            }
+        })
+    }
+
+    /// Rewrite the metadata of the selected points as one logged upsert
+    ///
+    /// The points are read under the write-ahead log lock, so concurrent
+    /// updates are not lost.
+    fn update_metadata(
+        &self,
+        selector: &PointSelector,
+        update: impl Fn(&mut HashMap<String, Value>),
+    ) -> Result<usize> {
+        let mut wal = self.lock_wal()?;
+        let mut entries = Vec::new();
+        for id in self.selected_ids(selector)? {
+            if let Some(mut entry) = self.storage.get(&id)? {
+                let mut metadata = entry.metadata.take().unwrap_or_default();
+                update(&mut metadata);
+                entry.metadata = (!metadata.is_empty()).then_some(metadata);
+                entries.push(entry);
+            }
+        }
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        let sequence = wal.append_upsert(&entries)?;
+        let updated = self.finish_logged(&mut wal, sequence, self.apply_upsert(entries))?;
+        Ok(updated.len())
+    }
+
+    /// Ids of the selected points, in id order
+    fn selected_ids(&self, selector: &PointSelector) -> Result<Vec<VectorId>> {
+        match selector {
+            PointSelector::Ids(ids) => {
+                let mut ids = ids.clone();
+                ids.sort();
+                ids.dedup();
+                Ok(ids)
+            }
+            PointSelector::Filter(filter) => self.matching_ids(filter),
+        }
+    }
+
+    /// Ids of every point whose metadata matches `filter`, in id order
+    ///
+    /// Filters on indexed fields only are answered from the payload
+    /// indexes; others are checked against stored metadata.
+    pub fn matching_ids(&self, filter: &FilterExpression) -> Result<Vec<VectorId>> {
+        self.check_consistent()?;
+        let mut ids = match self.indexed_matches(filter) {
+            Some(allowed) => allowed.into_iter().collect(),
+            None => {
+                let mut ids = self.storage.all_ids()?;
+                ids.retain(|id| self.metadata_matches(id, filter));
+                ids
+            }
+        };
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Count the points, or those whose metadata matches `filter`
+    pub fn count(&self, filter: Option<&FilterExpression>) -> Result<usize> {
+        self.check_consistent()?;
+        match filter {
+            None => self.len(),
+            Some(filter) => match self.indexed_matches(filter) {
+                Some(allowed) => Ok(allowed.len()),
+                None => Ok(self
+                    .storage
+                    .all_ids()?
+                    .iter()
+                    .filter(|id| self.metadata_matches(id, filter))
+                    .count()),
+            },
+        }
+    }
+
+    /// Page through the points in id order
+    ///
+    /// The page starts at the first id not below `offset` and holds up to
+    /// `limit` points, those matching `filter` if one is given. Points are
+    /// read in id order from `offset` on, stopping after the one that
+    /// starts the next page. Returns `InvalidParameter` if `limit` is 0.
+    pub fn scroll(
+        &self,
+        filter: Option<&FilterExpression>,
+        offset: Option<&str>,
+        limit: usize,
+    ) -> Result<ScrollPage> {
+        if limit == 0 {
+            return Err(RuvectorError::InvalidParameter(
+                "Scroll limit must be at least 1".to_string(),
+            ));
+        }
+        self.check_consistent()?;
+
+        let mut points = Vec::new();
+        let mut next_offset = None;
+        let mut take = |entry: VectorEntry| {
+            if points.len() == limit {
+                next_offset = entry.id;
+                return false;
+            }
+            points.push(entry);
+            true
+        };
+
+        match filter.and_then(|filter| self.indexed_matches(filter)) {
+            // Filters the payload indexes answered need no further checks
+            Some(allowed) => {
+                let mut ids: Vec<_> = allowed
+                    .into_iter()
+                    .filter(|id| offset.map_or(true, |offset| id.as_str() >= offset))
+                    .collect();
+                if ids.len() > limit + 1 {
+                    ids.select_nth_unstable(limit);
+                    ids.truncate(limit + 1);
+                }
+                ids.sort_unstable();
+                for id in ids {
+                    if let Some(entry) = self.storage.get(&id)? {
+                        if !take(entry) {
+                            break;
+                        }
+                    }
+                }
+            }
+            None => self.storage.scan(offset, |entry| {
+                if filter.is_some_and(|filter| !entry_matches(&entry, filter)) {
+                    return true;
+                }
+                take(entry)
+            })?,
+        }
+        Ok(ScrollPage {
+            points,
+            next_offset,
+        })
+    }
+
+    /// Ids matching `filter` from the payload indexes, if they index every
+    /// field it references
+    fn indexed_matches(&self, filter: &FilterExpression) -> Option<HashSet<VectorId>> {
+        let payload_indexes = self.payload_indexes.read();
+        let indexed = filter
+            .get_fields()
+            .iter()
+            .all(|field| payload_indexes.has_index(field));
+        if !indexed {
+            return None;
+        }
+        FilterEvaluator::new(&payload_indexes).evaluate(filter).ok()
+    }
+
+    /// Whether the stored metadata of `id` matches `filter`
+    fn metadata_matches(&self, id: &str, filter: &FilterExpression) -> bool {
+        matches!(self.storage.get(id), Ok(Some(entry)) if entry_matches(&entry, filter))
+    }
+
+    /// Get a vector by ID
+    pub fn get(&self, id: &str) -> Result<Option<VectorEntry>> {
+        self.storage.get(id)
+    }
+
+    /// Get the number of vectors
+    pub fn len(&self) -> Result<usize> {
+        self.storage.len()
+    }
+
+    /// Check if database is empty
+    pub fn is_empty(&self) -> Result<bool> {
+        self.storage.is_empty()
+    }
+
+    /// Get database options
+    pub fn options(&self) -> &DbOptions {
+        &self.options
+    }
+
+    /// Get all vector IDs (for iteration/serialization)
+    pub fn keys(&self) -> Result<Vec<String>> {
+        self.storage.all_ids()
+    }
+
+    /// Statistics of the index of the default vector space, or of the named
+    /// vector space `using`
+    pub fn index_stats(&self, using: Option<&str>) -> Result<IndexStats> {
+        let index = match using {
+            Some(name) => self.named_index(name)?,
+            None => &*self.index,
+        };
+        Ok(index.read().stats())
+    }
+
+    /// Estimate the recall@k of approximate searches in the default vector
+    /// space, or the named vector space `using`, from `samples` stored vectors
+    ///
+    /// Each sample costs an approximate search and an exact scan, during
+    /// which writes to the space wait, so keep `samples` small.
+    pub fn estimate_recall(
+        &self,
+        using: Option<&str>,
+        samples: usize,
+        k: usize,
+    ) -> Result<Option<f32>> {
+        let index = match using {
+            Some(name) => self.named_index(name)?,
+            None => &*self.index,
+        };
+        index.read().estimate_recall(samples, k)
+    }
+
+    /// Sequence number of the last logged insert, upsert or delete
+    ///
+    /// Sequence numbers increase by one per operation and survive restarts.
+    pub fn sequence(&self) -> u64 {
+        self.wal.lock().sequence()
+    }
+
+    /// Sequence number up to which storage and every index were last
+    /// checkpointed
+    pub fn checkpointed_sequence(&self) -> u64 {
+        self.wal.lock().checkpointed_sequence()
+    }
+
+    /// Logged operations after `sequence`, in order
+    ///
+    /// Returns `None` if some of them are no longer in the write-ahead log
+    /// because a checkpoint dropped them, or the database keeps no log file.
+    pub fn operations_since(&self, sequence: u64) -> Result<Option<Vec<WalRecord>>> {
+        self.wal.lock().records_since(sequence)
+    }
+
+    /// Take a consistent cut of the database for a snapshot
+    ///
+    /// The cut holds exactly the entries up to [`ConsistentCut::sequence`].
+    /// Writers are blocked only while the sequence number is read and a
+    /// storage view pinned; the entries and the HNSW graphs are read
+    /// afterwards, while writes continue.
+    #[cfg(feature = "storage")]
+    pub fn consistent_cut(&self) -> Result<ConsistentCut> {
+        let wal = self.lock_wal()?;
+        let view = self.storage.view()?;
+        let sequence = wal.sequence();
+        let payload_indexes = self.payload_index_definitions();
+        drop(wal);
+
+        let mut indexes = vec![(None, Arc::clone(&self.index))];
+        indexes.extend(
+            self.named_indexes
+                .iter()
+                .map(|(name, index)| (Some(name.clone()), Arc::clone(index))),
+        );
+        Ok(ConsistentCut {
+            sequence,
+            options: self.options.clone(),
+            payload_indexes,
+            view,
+            indexes,
+        })
+    }
+
+    /// Write a full checkpoint of every index to disk and truncate the
+    /// write-ahead log
+    ///
+    /// Persistent indexes checkpoint on their own as their log grows, and
+    /// the database checkpoints once the write-ahead log gets large; this
+    /// forces one, e.g. before a planned shutdown.
+    pub fn checkpoint(&self) -> Result<()> {
+        let mut wal = self.lock_wal()?;
+        self.checkpoint_indexes()?;
+        wal.checkpoint()
+    }
+
+    fn checkpoint_indexes(&self) -> Result<()> {
+        self.index.read().checkpoint()?;
+        for index in self.named_indexes.values() {
+            index.read().checkpoint()?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the metadata of `entry` matches `filter`; entries without
+/// metadata match nothing
+fn entry_matches(entry: &VectorEntry, filter: &FilterExpression) -> bool {
+    matches!(&entry.metadata, Some(metadata) if filter.matches_metadata(metadata))
+}
+
+/// Rank constant of reciprocal rank fusion
+const RRF_K: f32 = 60.0;
 
-        Ok(results)
+/// Fuse ranked result lists with reciprocal rank fusion
+///
+/// Fused scores are negated so that, as with distances, lower is better.
+fn fuse_rankings(rankings: Vec<Vec<SearchResult>>, k: usize) -> Vec<SearchResult> {
+    let mut fused: HashMap<VectorId, f32> = HashMap::new();
+    for ranking in rankings {
+        for (rank, result) in ranking.into_iter().enumerate() {
+            *fused.entry(result.id).or_default() -= 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
     }
 
-    /// Delete a vector by ID
-    pub fn delete(&self, id: &str) -> Result<bool> {
-        let deleted_storage = self.storage.delete(id)?;
+    let mut results: Vec<SearchResult> = fused
+        .into_iter()
+        .map(|(id, score)| SearchResult {
+            id,
+            score,
+            vector: None,
+            metadata: None,
+        })
+        .collect();
+    results.sort_by(|a, b| a.score.total_cmp(&b.score).then_with(|| a.id.cmp(&b.id)));
+    results.truncate(k);
+    results
+}
 
-        if deleted_storage {
-            let mut index = self.index.write();
-            let _ = index.remove(&id.to_string())?;
+/// Add and remove vectors in one index
+fn update_index(
+    index: &RwLock<Box<dyn VectorIndex>>,
+    mut added: Vec<(VectorId, Vec<f32>)>,
+    removed: Vec<VectorId>,
+) -> Result<()> {
+    let mut index = index.write();
+    for id in &removed {
+        index.remove(id)?;
+    }
+    match added.len() {
+        0 => {}
+        1 => {
+            let (id, vector) = added.remove(0);
+            index.add(id, vector)?;
         }
-
-        Ok(deleted_storage)
+        _ => index.add_batch(added)?,
     }
+    Ok(())
+}
 
-    /// Get a vector by ID
-    pub fn get(&self, id: &str) -> Result<Option<VectorEntry>> {
-        self.storage.get(id)
+/// Directory of the persisted HNSW graph of the vector space `name`
+///
+/// `name` is `None` for the default vector space.
+#[cfg(all(feature = "hnsw", feature = "storage"))]
+pub(crate) fn graph_dir(storage_path: &str, name: Option<&str>) -> PathBuf {
+    match name {
+        Some(name) => graph_dir_for(&format!("{}.{}", storage_path, name)),
+        None => graph_dir_for(storage_path),
     }
+}
 
-    /// Get the number of vectors
-    pub fn len(&self) -> Result<usize> {
-        self.storage.len()
+/// The vector of `entry` in the named space `name`, or its default vector
+pub(crate) fn entry_vector(entry: VectorEntry, name: Option<&str>) -> Option<Vec<f32>> {
+    match name {
+        Some(name) => entry.named_vectors?.remove(name),
+        None => Some(entry.vector).filter(|vector| !vector.is_empty()),
     }
+}
 
-    /// Check if database is empty
-    pub fn is_empty(&self) -> Result<bool> {
-        self.storage.is_empty()
-    }
+/// Error for a named vector space the database does not declare
+fn unknown_vector(name: &str) -> RuvectorError {
+    RuvectorError::InvalidInput(format!("Unknown named vector: {}", name))
+}
 
-    /// Get database options
-    pub fn options(&self) -> &DbOptions {
-        &self.options
+/// Reject named vector spaces whose names cannot be used in file names
+fn check_vector_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(RuvectorError::InvalidInput(format!(
+            "Invalid named vector {:?}: use letters, digits, '_' and '-'",
+            name
+        )));
     }
+    Ok(())
+}
 
-    /// Get all vector IDs (for iteration/serialization)
-    pub fn keys(&self) -> Result<Vec<String>> {
-        self.storage.all_ids()
-    }
+/// Metadata as a JSON object, the payload shape payload indexes expect
+fn payload_value(metadata: &HashMap<String, Value>) -> Value {
+    Value::Object(
+        metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+    )
 }
 
 #[cfg(test)]
@@ -264,24 +1393,28 @@ mod tests {
         db.insert(VectorEntry {
             id: Some("v1".to_string()),
             vector: vec![1.0, 0.0, 0.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
         db.insert(VectorEntry {
             id: Some("v2".to_string()),
             vector: vec![0.0, 1.0, 0.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
         db.insert(VectorEntry {
             id: Some("v3".to_string()),
             vector: vec![0.0, 0.0, 1.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
         // Search for exact match
         let results = db.search(SearchQuery {
             vector: vec![1.0, 0.0, 0.0],
+            using: None,
             k: 2,
             filter: None,
             ef_search: None,
@@ -318,24 +1451,28 @@ mod tests {
             db.insert(VectorEntry {
                 id: Some("v1".to_string()),
                 vector: vec![1.0, 0.0, 0.0],
+                named_vectors: None,
                 metadata: None,
             })?;
 
             db.insert(VectorEntry {
                 id: Some("v2".to_string()),
                 vector: vec![0.0, 1.0, 0.0],
+                named_vectors: None,
                 metadata: None,
             })?;
 
             db.insert(VectorEntry {
                 id: Some("v3".to_string()),
                 vector: vec![0.7, 0.7, 0.0],
+                named_vectors: None,
                 metadata: None,
             })?;
 
             // Verify search works before "restart"
             let results = db.search(SearchQuery {
                 vector: vec![0.8, 0.6, 0.0],
+                using: None,
                 k: 3,
                 filter: None,
                 ef_search: None,
@@ -364,6 +1501,7 @@ mod tests {
             // Verify search() works - THIS WAS THE BUG
             let results = db.search(SearchQuery {
                 vector: vec![0.8, 0.6, 0.0],
+                using: None,
                 k: 3,
                 filter: None,
                 ef_search: None,
@@ -384,4 +1522,685 @@ mod tests {
 
         Ok(())
     }
+
+    /// The HNSW graph is reopened from disk after a restart, and rebuilt
+    /// from storage if the graph files disappear
+    #[test]
+    #[cfg(all(feature = "storage", feature = "hnsw"))]
+    fn test_hnsw_graph_survives_restart() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("graph.db").to_string_lossy().to_string();
+
+        let open = || {
+            let mut options = DbOptions::default();
+            options.storage_path = db_path.clone();
+            options.dimensions = 3;
+            options.distance_metric = DistanceMetric::Euclidean;
+            VectorDB::new(options)
+        };
+        let query = || SearchQuery {
+            vector: vec![0.8, 0.6, 0.0],
+            using: None,
+            k: 3,
+            filter: None,
+            ef_search: None,
+        };
+
+        {
+            let db = open()?;
+            for (id, vector) in [
+                ("v1", vec![1.0, 0.0, 0.0]),
+                ("v2", vec![0.0, 1.0, 0.0]),
+                ("v3", vec![0.7, 0.7, 0.0]),
+            ] {
+                db.insert(VectorEntry {
+                    id: Some(id.to_string()),
+                    vector,
+                    named_vectors: None,
+                    metadata: None,
+                })?;
+            }
+        }
+
+        let graph_dir = graph_dir_for(&db_path);
+        assert!(Path::new(&graph_dir).join("graph.snap").exists());
+
+        {
+            let db = open()?;
+            let results = db.search(query())?;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].id, "v3");
+        }
+
+        std::fs::remove_dir_all(&graph_dir).unwrap();
+        {
+            let db = open()?;
+            let results = db.search(query())?;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].id, "v3");
+        }
+
+        Ok(())
+    }
+
+    /// A reopened graph is checked against every stored id only when the
+    /// write-ahead log cannot say what changed since it was checkpointed
+    #[test]
+    #[cfg(all(feature = "storage", feature = "hnsw"))]
+    fn test_graph_reconciled_without_write_ahead_log() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let db_path = dir
+            .path()
+            .join("reconcile.db")
+            .to_string_lossy()
+            .to_string();
+        let mut options = DbOptions::default();
+        options.storage_path = db_path.clone();
+        options.dimensions = 3;
+        let entry = |id: &str, vector: Vec<f32>| VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        };
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert(entry("v1", vec![1.0, 0.0, 0.0]))?;
+            db.insert(entry("v2", vec![0.0, 1.0, 0.0]))?;
+        }
+
+        // Writes from before the log existed reached storage only
+        {
+            let storage = VectorStorage::new(&db_path, 3)?;
+            storage.insert(&entry("v3", vec![0.0, 0.0, 1.0]))?;
+            storage.delete("v1")?;
+        }
+        std::fs::remove_file(wal_path_for(&db_path))?;
+
+        let query = SearchQuery {
+            vector: vec![0.0, 0.0, 1.0],
+            using: None,
+            k: 3,
+            filter: None,
+            ef_search: None,
+        };
+        {
+            let db = VectorDB::new(options.clone())?;
+            let results = db.search(query.clone())?;
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].id, "v3");
+        }
+
+        // The reconciled graph was checkpointed, so the next open trusts it
+        let db = VectorDB::new(options)?;
+        let results = db.search(query)?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "v3");
+        Ok(())
+    }
+
+    fn tagged_db(path: &Path, count: usize) -> Result<VectorDB> {
+        let mut options = DbOptions::default();
+        options.storage_path = path.join("filter.db").to_string_lossy().to_string();
+        options.dimensions = 4;
+        options.distance_metric = DistanceMetric::Euclidean;
+
+        let db = VectorDB::new(options)?;
+        let entries = (0..count)
+            .map(|i| {
+                let mut metadata = HashMap::new();
+                metadata.insert("tag".to_string(), serde_json::json!(format!("t{}", i % 10)));
+                metadata.insert("n".to_string(), serde_json::json!(i));
+                VectorEntry {
+                    id: Some(format!("v{}", i)),
+                    vector: vec![i as f32, (i % 7) as f32, (i % 13) as f32, 1.0],
+                    named_vectors: None,
+                    metadata: Some(metadata),
+                }
+            })
+            .collect();
+        db.insert_batch(entries)?;
+        Ok(db)
+    }
+
+    /// Selective filters still return k results, whichever strategy the
+    /// planner picks
+    #[test]
+    fn test_filtered_search_returns_k_matches() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let db = tagged_db(dir.path(), 500)?;
+        let search = |filter: FilterExpression| {
+            db.search(SearchQuery {
+                vector: vec![250.0, 3.0, 6.0, 1.0],
+                using: None,
+                k: 10,
+                filter: Some(filter),
+                ef_search: None,
+            })
+        };
+
+        // Unindexed: post-filtered on stored metadata
+        let results = search(FilterExpression::eq("tag", serde_json::json!("t3")))?;
+        assert_eq!(results.len(), 10);
+
+        db.create_payload_index("tag", IndexType::Keyword)?;
+        db.create_payload_index("n", IndexType::Integer)?;
+
+        // Indexed: traversal restricted to the allow-set
+        let results = search(FilterExpression::eq("tag", serde_json::json!("t3")))?;
+        assert_eq!(results.len(), 10);
+        for result in &results {
+            let metadata = result.metadata.as_ref().unwrap();
+            assert_eq!(metadata["tag"], serde_json::json!("t3"));
+        }
+
+        // Tiny allow-set: scanned exhaustively
+        let results = search(FilterExpression::in_values(
+            "n",
+            vec![serde_json::json!(7), serde_json::json!(499)],
+        ))?;
+        let ids: Vec<_> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["v7", "v499"]);
+
+        Ok(())
+    }
+
+    /// Payload indexes follow upserts and deletes, and are rebuilt on reopen
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_payload_indexes_stay_current() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let filter = FilterExpression::eq("tag", serde_json::json!("moved"));
+        let search = |db: &VectorDB| {
+            db.search(SearchQuery {
+                vector: vec![0.0, 0.0, 0.0, 1.0],
+                using: None,
+                k: 10,
+                filter: Some(filter.clone()),
+                ef_search: None,
+            })
+        };
+
+        {
+            let db = tagged_db(dir.path(), 200)?;
+            db.create_payload_index("tag", IndexType::Keyword)?;
+
+            let mut metadata = HashMap::new();
+            metadata.insert("tag".to_string(), serde_json::json!("moved"));
+            for id in ["v1", "v2"] {
+                db.insert(VectorEntry {
+                    id: Some(id.to_string()),
+                    vector: vec![0.0, 0.0, 0.0, 1.0],
+                    named_vectors: None,
+                    metadata: Some(metadata.clone()),
+                })?;
+            }
+            db.delete("v2")?;
+
+            let results = search(&db)?;
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].id, "v1");
+        }
+
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("filter.db").to_string_lossy().to_string();
+        let db = VectorDB::new(options)?;
+        assert_eq!(
+            db.payload_index_definitions(),
+            vec![("tag".to_string(), IndexType::Keyword)]
+        );
+        let results = search(&db)?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "v1");
+
+        Ok(())
+    }
+
+    /// Points are selected by id or filter for deletes and payload updates,
+    /// and paged through or counted with or without payload indexes
+    #[test]
+    fn test_point_operations() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let db = tagged_db(dir.path(), 100)?;
+        let t3 = FilterExpression::eq("tag", serde_json::json!("t3"));
+
+        // Scroll pages in id order and ends with no next offset
+        let mut seen = Vec::new();
+        let mut offset = None;
+        loop {
+            let page = db.scroll(None, offset.as_deref(), 30)?;
+            seen.extend(page.points.into_iter().map(|p| p.id.unwrap()));
+            match page.next_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+        let mut expected = db.keys()?;
+        expected.sort();
+        assert_eq!(seen, expected);
+
+        for indexed in [false, true] {
+            if indexed {
+                db.create_payload_index("tag", IndexType::Keyword)?;
+            }
+            assert_eq!(db.count(Some(&t3))?, 10);
+            let page = db.scroll(Some(&t3), Some("v5"), 4)?;
+            let ids: Vec<_> = page.points.iter().map(|p| p.id.clone().unwrap()).collect();
+            assert_eq!(ids, ["v53", "v63", "v73", "v83"]);
+            assert_eq!(page.next_offset.as_deref(), Some("v93"));
+            let first = db.scroll(Some(&t3), None, 2)?;
+            let ids: Vec<_> = first.points.iter().map(|p| p.id.clone().unwrap()).collect();
+            assert_eq!(ids, ["v13", "v23"]);
+            assert_eq!(first.next_offset.as_deref(), Some("v3"));
+            let last = db.scroll(Some(&t3), Some("v93"), 4)?;
+            assert_eq!(last.points.len(), 1);
+            assert_eq!(last.next_offset, None);
+        }
+        assert!(matches!(
+            db.scroll(None, None, 0),
+            Err(RuvectorError::InvalidParameter(_))
+        ));
+
+        let mut payload = HashMap::new();
+        payload.insert("tag".to_string(), serde_json::json!("t3"));
+        payload.insert("extra".to_string(), serde_json::json!(true));
+        let selector = PointSelector::Ids(vec!["v1".to_string(), "missing".to_string()]);
+        assert_eq!(db.set_payload(&selector, &payload)?, 1);
+        let metadata = db.get("v1")?.unwrap().metadata.unwrap();
+        assert_eq!(metadata["n"], serde_json::json!(1));
+        assert_eq!(db.count(Some(&t3))?, 11);
+
+        assert_eq!(
+            db.delete_payload(
+                &PointSelector::Ids(vec!["v1".to_string()]),
+                &["n".to_string()]
+            )?,
+            1
+        );
+        assert!(!db.get("v1")?.unwrap().metadata.unwrap().contains_key("n"));
+        payload.remove("tag");
+        db.overwrite_payload(&PointSelector::Ids(vec!["v1".to_string()]), &payload)?;
+        assert_eq!(db.get("v1")?.unwrap().metadata.unwrap(), payload);
+        assert_eq!(db.count(Some(&t3))?, 10);
+
+        let sequence = db.sequence();
+        assert_eq!(db.delete_points(&PointSelector::Filter(t3.clone()))?, 10);
+        assert_eq!(db.sequence(), sequence + 1);
+        assert_eq!(db.count(Some(&t3))?, 0);
+        assert_eq!(db.count(None)?, 90);
+        Ok(())
+    }
+
+    fn named_entry(id: &str, vector: Vec<f32>, named: &[(&str, Vec<f32>)]) -> VectorEntry {
+        VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: Some(
+                named
+                    .iter()
+                    .map(|(name, vector)| (name.to_string(), vector.clone()))
+                    .collect(),
+            ),
+            metadata: None,
+        }
+    }
+
+    /// Named vector spaces are indexed separately, searched by name or
+    /// fused, and survive upserts and reopening
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_named_vectors() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("named.db").to_string_lossy().to_string();
+        options.dimensions = 4;
+        options.distance_metric = DistanceMetric::Euclidean;
+        let space = |dimensions, hnsw_config| VectorSpaceConfig {
+            dimensions,
+            distance_metric: DistanceMetric::Euclidean,
+            hnsw_config,
+            quantization: None,
+            multivector: false,
+        };
+        options.named_vectors = Some(HashMap::from([
+            ("title".to_string(), space(2, None)),
+            ("image".to_string(), space(3, Some(HnswConfig::default()))),
+        ]));
+        let query = |using: VectorSelector, vector: Vec<f32>| SearchQuery {
+            vector,
+            using: Some(using),
+            k: 3,
+            filter: None,
+            ef_search: None,
+        };
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert_batch(vec![
+                named_entry(
+                    "p1",
+                    vec![1.0, 0.0, 0.0, 0.0],
+                    &[("title", vec![1.0, 0.0]), ("image", vec![1.0, 0.0, 0.0])],
+                ),
+                // Only a title embedding, no default vector
+                named_entry("p2", Vec::new(), &[("title", vec![0.0, 1.0])]),
+                named_entry(
+                    "p3",
+                    vec![0.0, 1.0, 0.0, 0.0],
+                    &[("image", vec![0.0, 1.0, 0.0])],
+                ),
+            ])?;
+
+            let results =
+                db.search(query(VectorSelector::Named("title".into()), vec![0.1, 0.9]))?;
+            assert_eq!(results[0].vector, Some(vec![0.0, 1.0]));
+            assert_eq!(ids(results), ["p2", "p1"]);
+
+            let results = db.search(SearchQuery {
+                vector: vec![0.0, 1.0, 0.0, 0.0],
+                using: None,
+                k: 3,
+                filter: None,
+                ef_search: None,
+            })?;
+            assert_eq!(ids(results), ["p3", "p1"]);
+
+            // p1 ranks first in both spaces
+            let fusion = VectorSelector::Fusion(HashMap::from([
+                ("title".to_string(), vec![1.0, 0.1]),
+                ("image".to_string(), vec![0.9, 0.0, 0.1]),
+            ]));
+            let results = db.search(query(fusion, Vec::new()))?;
+            assert_eq!(results[0].id, "p1");
+            assert!(results.windows(2).all(|w| w[0].score <= w[1].score));
+
+            // Unknown spaces and wrong sizes are rejected
+            assert!(db
+                .search(query(VectorSelector::Named("audio".into()), vec![1.0]))
+                .is_err());
+            assert!(db
+                .insert(named_entry(
+                    "p4",
+                    Vec::new(),
+                    &[("title", vec![1.0, 0.0, 0.0])]
+                ))
+                .is_err());
+            assert!(db
+                .insert(named_entry("p4", Vec::new(), &[("audio", vec![1.0])]))
+                .is_err());
+
+            // Upserting without an image drops p1 from the image space
+            db.insert(named_entry(
+                "p1",
+                vec![1.0, 0.0, 0.0, 0.0],
+                &[("title", vec![1.0, 0.0])],
+            ))?;
+            let results = db.search(query(
+                VectorSelector::Named("image".into()),
+                vec![1.0, 0.0, 0.0],
+            ))?;
+            assert_eq!(ids(results), ["p3"]);
+        }
+
+        let db = VectorDB::new(options)?;
+        assert_eq!(
+            db.get("p2")?.unwrap().named_vectors,
+            Some(HashMap::from([("title".to_string(), vec![0.0, 1.0])]))
+        );
+        let results = db.search(query(
+            VectorSelector::Named("image".into()),
+            vec![1.0, 0.0, 0.0],
+        ))?;
+        assert_eq!(ids(results), ["p3"]);
+        let results = db.search(query(VectorSelector::Named("title".into()), vec![1.0, 0.0]))?;
+        assert_eq!(ids(results), ["p1", "p2"]);
+
+        Ok(())
+    }
+
+    /// Multi-vector spaces are searched with MaxSim over token matrices,
+    /// and are rebuilt from storage when the database is reopened
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_multivector_max_sim() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("colbert.db").to_string_lossy().to_string();
+        options.dimensions = 2;
+        options.named_vectors = Some(HashMap::from([(
+            "tokens".to_string(),
+            VectorSpaceConfig {
+                dimensions: 2,
+                distance_metric: DistanceMetric::Euclidean,
+                hnsw_config: Some(HnswConfig::default()),
+                quantization: None,
+                multivector: true,
+            },
+        )]));
+        let query = |using: VectorSelector, vector: Vec<f32>| SearchQuery {
+            vector,
+            using: Some(using),
+            k: 2,
+            filter: None,
+            ef_search: None,
+        };
+        let max_sim = || VectorSelector::MaxSim("tokens".to_string());
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert_batch(vec![
+                named_entry("one", Vec::new(), &[("tokens", vec![1.0, 0.0])]),
+                named_entry("two", Vec::new(), &[("tokens", vec![1.0, 0.0, 0.0, 1.0])]),
+                named_entry("far", Vec::new(), &[("tokens", vec![9.0, 9.0, 8.0, 8.0])]),
+            ])?;
+
+            // "two" matches both query tokens, "one" only the first
+            let results = db.search(query(max_sim(), vec![1.0, 0.0, 0.0, 1.0]))?;
+            assert_eq!(results[0].id, "two");
+            assert_eq!(results[0].vector, Some(vec![1.0, 0.0, 0.0, 1.0]));
+            assert_eq!(results[1].id, "one");
+
+            // Token matrices must be whole, and the selector must fit the space
+            assert!(db
+                .insert(named_entry(
+                    "bad",
+                    Vec::new(),
+                    &[("tokens", vec![1.0, 2.0, 3.0])]
+                ))
+                .is_err());
+            assert!(db
+                .search(query(
+                    VectorSelector::Named("tokens".into()),
+                    vec![1.0, 0.0]
+                ))
+                .is_err());
+            assert!(db.search(query(max_sim(), vec![1.0, 0.0, 1.0])).is_err());
+        }
+
+        let db = VectorDB::new(options)?;
+        let results = db.search(query(max_sim(), vec![8.0, 8.0]))?;
+        assert_eq!(results[0].id, "far");
+        Ok(())
+    }
+
+    /// Operations logged but not yet applied are replayed on open, and
+    /// sequence numbers carry over across restarts
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_write_ahead_log_replay() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("wal.db").to_string_lossy().to_string();
+        options.dimensions = 3;
+        let entry = |id: &str, vector: Vec<f32>| VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        };
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert(entry("v1", vec![1.0, 0.0, 0.0]))?;
+            db.insert(entry("v2", vec![0.0, 1.0, 0.0]))?;
+            assert_eq!(db.sequence(), 2);
+            db.checkpoint()?;
+            assert_eq!(db.checkpointed_sequence(), 2);
+
+            assert!(db.delete("v1")?);
+            assert_eq!(db.sequence(), 3);
+            assert_eq!(db.operations_since(2)?.map(|ops| ops.len()), Some(1));
+            assert!(db.operations_since(1)?.is_none());
+        }
+
+        // Crash after logging an upsert but before applying it
+        {
+            let (mut wal, pending) = WriteAheadLog::open(&wal_path_for(&options.storage_path))?;
+            assert_eq!(pending.len(), 1);
+            wal.append_upsert(&[entry("v3", vec![0.0, 0.0, 1.0])])?;
+        }
+
+        let db = VectorDB::new(options)?;
+        assert_eq!(db.sequence(), 4);
+        assert_eq!(db.checkpointed_sequence(), 4);
+        assert!(db.get("v1")?.is_none());
+        assert_eq!(db.len()?, 2);
+        let results = db.search(SearchQuery {
+            vector: vec![0.0, 0.0, 1.0],
+            using: None,
+            k: 1,
+            filter: None,
+            ef_search: None,
+        })?;
+        assert_eq!(results[0].id, "v3");
+        Ok(())
+    }
+
+    /// Non-finite values are rejected before they are logged, so they
+    /// cannot cost the writes acknowledged after them on reopen
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_non_finite_vectors_are_rejected() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("nan.db").to_string_lossy().to_string();
+        options.dimensions = 2;
+        options.named_vectors = Some(HashMap::from([(
+            "title".to_string(),
+            VectorSpaceConfig {
+                dimensions: 2,
+                distance_metric: DistanceMetric::Euclidean,
+                hnsw_config: None,
+                quantization: None,
+                multivector: false,
+            },
+        )]));
+        let entry = |id: &str, vector: Vec<f32>, title: Vec<f32>| VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: Some(HashMap::from([("title".to_string(), title)])),
+            metadata: None,
+        };
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert(entry("before", vec![1.0, 0.0], vec![1.0, 0.0]))?;
+            for (vector, title) in [
+                (vec![f32::NAN, 0.0], vec![1.0, 0.0]),
+                (vec![f32::INFINITY, 0.0], vec![1.0, 0.0]),
+                (vec![1.0, 0.0], vec![0.0, f32::NEG_INFINITY]),
+            ] {
+                assert!(matches!(
+                    db.insert(entry("bad", vector, title)),
+                    Err(RuvectorError::InvalidInput(_))
+                ));
+            }
+            db.insert(entry("after", vec![0.0, 1.0], vec![0.0, 1.0]))?;
+            assert_eq!(db.sequence(), 2);
+        }
+
+        let db = VectorDB::new(options)?;
+        assert!(db.get("before")?.is_some());
+        assert!(db.get("after")?.is_some());
+        assert!(db.get("bad")?.is_none());
+        Ok(())
+    }
+
+    /// Index whose writes fail, standing in for an I/O error
+    struct FailingIndex;
+
+    impl VectorIndex for FailingIndex {
+        fn add(&mut self, _id: VectorId, _vector: Vec<f32>) -> Result<()> {
+            Err(RuvectorError::Internal(
+                "injected index failure".to_string(),
+            ))
+        }
+
+        fn search(&self, _query: &[f32], _k: usize) -> Result<Vec<SearchResult>> {
+            Ok(Vec::new())
+        }
+
+        fn remove(&mut self, _id: &VectorId) -> Result<bool> {
+            Err(RuvectorError::Internal(
+                "injected index failure".to_string(),
+            ))
+        }
+
+        fn len(&self) -> usize {
+            0
+        }
+    }
+
+    /// A write that reaches storage but not the index keeps its log record
+    /// and fails the database closed until a reopen replays it
+    #[test]
+    #[cfg(feature = "storage")]
+    fn test_index_failure_after_storage_write() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let mut options = DbOptions::default();
+        options.storage_path = dir.path().join("diverged.db").to_string_lossy().to_string();
+        options.dimensions = 3;
+        let entry = |id: &str, vector: Vec<f32>| VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        };
+        let query = |vector: Vec<f32>| SearchQuery {
+            vector,
+            using: None,
+            k: 1,
+            filter: None,
+            ef_search: None,
+        };
+
+        {
+            let db = VectorDB::new(options.clone())?;
+            db.insert(entry("v1", vec![1.0, 0.0, 0.0]))?;
+            let healthy = std::mem::replace(&mut *db.index.write(), Box::new(FailingIndex));
+
+            assert!(db.insert(entry("v2", vec![0.0, 1.0, 0.0])).is_err());
+            assert!(db.get("v2")?.is_some());
+            // The record is kept for replication and replay, not aborted
+            assert_eq!(db.operations_since(1)?.map(|ops| ops.len()), Some(1));
+
+            assert!(db.insert(entry("v3", vec![0.0, 0.0, 1.0])).is_err());
+            assert!(db.delete("v1").is_err());
+            assert!(db.search(query(vec![0.0, 1.0, 0.0])).is_err());
+            assert!(db.checkpoint().is_err());
+            drop(db);
+            drop(healthy);
+        }
+
+        let db = VectorDB::new(options)?;
+        assert_eq!(db.len()?, 2);
+        assert_eq!(db.search(query(vec![0.0, 1.0, 0.0]))?[0].id, "v2");
+        db.insert(entry("v3", vec![0.0, 0.0, 1.0]))?;
+        assert_eq!(db.sequence(), 3);
+        Ok(())
+    }
 }
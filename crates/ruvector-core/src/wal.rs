@@ -0,0 +1,476 @@
+//! Write-ahead log of [`VectorDB`](crate::VectorDB) mutations
+//!
+//! Every upsert and delete is assigned the next sequence number and appended
+//! to `<storage_path>.wal` before it touches storage or any index, so a
+//! crash between the two leaves a record to redo it from. An operation that
+//! fails to apply is followed by an abort record carrying its sequence
+//! number, and neither is replayed. The file starts with a header holding
+//! the sequence number of the last checkpoint, the point up to which storage
+//! and every index are known to be on disk:
+//!
+//! - header: magic `RVWAL001`, checkpointed sequence (u64)
+//! - records: payload length (u32), FNV-1a checksum (u64), then the
+//!   sequence number (u64) followed by the JSON-encoded [`WalOperation`],
+//!   or `"Abort"` for an abort record
+//!
+//! Records are synced to disk as they are appended. Opening the log returns
+//! the records past the checkpoint for the caller to re-apply; upserts and
+//! deletes are idempotent, so re-applying one that already reached storage
+//! is harmless. A torn record at the tail is dropped, while an intact record
+//! that cannot be decoded fails the open. A checkpoint rewrites the header
+//! and truncates the records.
+
+use crate::checksum::checksum;
+use crate::error::{Result, RuvectorError};
+use crate::types::{VectorEntry, VectorId};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const WAL_MAGIC: &[u8; 8] = b"RVWAL001";
+const HEADER_LEN: u64 = 16;
+const RECORD_HEADER_LEN: usize = 12;
+
+/// Checkpoint once this many bytes of records have been logged
+const CHECKPOINT_BYTES: u64 = 64 << 20;
+
+// Log files currently owned by an open database in this process. A second
+// database on the same storage path would interleave records, so it runs
+// without a log file instead.
+static OPEN_LOGS: Lazy<Mutex<HashSet<PathBuf>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Path of the write-ahead log for a redb file at `storage_path`
+pub fn wal_path_for(storage_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.wal", storage_path))
+}
+
+/// A logged mutation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WalOperation {
+    /// Insert or replace entries; ids are always assigned before logging
+    Upsert(Vec<VectorEntry>),
+    /// Delete entries by id
+    Delete(Vec<VectorId>),
+}
+
+impl WalOperation {
+    /// Ids of the entries the operation changes
+    pub fn into_ids(self) -> Vec<VectorId> {
+        match self {
+            WalOperation::Upsert(entries) => entries.into_iter().filter_map(|e| e.id).collect(),
+            WalOperation::Delete(ids) => ids,
+        }
+    }
+}
+
+/// What a record holds: a [`WalOperation`], borrowed when appending, or
+/// the abort of the operation logged under the same sequence number
+#[derive(Serialize, Deserialize)]
+enum LoggedOperation<'a> {
+    Upsert(Cow<'a, [VectorEntry]>),
+    Delete(Cow<'a, [VectorId]>),
+    Abort,
+}
+
+/// A logged mutation and its sequence number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalRecord {
+    /// Position of the operation in the history of the database
+    pub sequence: u64,
+    /// The mutation
+    pub operation: WalOperation,
+}
+
+/// Sequence-numbered log of mutations, optionally backed by a file
+pub struct WriteAheadLog {
+    file: Option<(PathBuf, File)>,
+    sequence: u64,
+    checkpointed: u64,
+    bytes: u64,
+    /// Whether the log file was created by this open
+    new: bool,
+}
+
+impl WriteAheadLog {
+    /// A log that only assigns sequence numbers, for in-memory databases
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            sequence: 0,
+            checkpointed: 0,
+            bytes: 0,
+            new: true,
+        }
+    }
+
+    /// Open or create the log at `path`
+    ///
+    /// Returns the log together with the records past its last checkpoint,
+    /// in sequence order. Falls back to an in-memory log if another database
+    /// in this process already owns the file.
+    pub fn open(path: &Path) -> Result<(Self, Vec<WalRecord>)> {
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(path)
+        };
+        if !OPEN_LOGS.lock().insert(path.clone()) {
+            tracing::warn!("Write-ahead log {:?} is already open, not logging", path);
+            return Ok((Self::in_memory(), Vec::new()));
+        }
+
+        match Self::open_claimed(&path) {
+            Ok(opened) => Ok(opened),
+            Err(e) => {
+                OPEN_LOGS.lock().remove(&path);
+                Err(e)
+            }
+        }
+    }
+
+    fn open_claimed(path: &Path) -> Result<(Self, Vec<WalRecord>)> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        // A new log, or one whose header was never completely written
+        let new = bytes.len() < HEADER_LEN as usize;
+        if new {
+            file.set_len(0)?;
+            write_header(&mut file, 0)?;
+            bytes = header(0).to_vec();
+        }
+        let (checkpointed, records, last_sequence, valid_len) = decode(&bytes)?;
+        if valid_len < bytes.len() {
+            tracing::warn!(
+                "Dropping {} bytes of torn records from {:?}",
+                bytes.len() - valid_len,
+                path
+            );
+            file.set_len(valid_len as u64)?;
+            file.sync_data()?;
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        let records: Vec<WalRecord> = records
+            .into_iter()
+            .filter(|r| r.sequence > checkpointed)
+            .collect();
+        let log = Self {
+            file: Some((path.to_path_buf(), file)),
+            sequence: last_sequence.max(checkpointed),
+            checkpointed,
+            bytes: valid_len as u64 - HEADER_LEN,
+            new,
+        };
+        Ok((log, records))
+    }
+
+    /// Whether the log holds no history of its database: the file was just
+    /// created, or the log is not backed by a file
+    ///
+    /// Otherwise every operation since the last checkpoint is in the log,
+    /// and storage and every index were on disk at that checkpoint.
+    pub fn is_new(&self) -> bool {
+        self.new
+    }
+
+    /// Sequence number of the last logged operation (0 if none)
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Sequence number up to which everything is on disk
+    pub fn checkpointed_sequence(&self) -> u64 {
+        self.checkpointed
+    }
+
+    /// Log an upsert of `entries` under the next sequence number and return it
+    pub fn append_upsert(&mut self, entries: &[VectorEntry]) -> Result<u64> {
+        self.append(&LoggedOperation::Upsert(Cow::Borrowed(entries)))
+    }
+
+    /// Log a delete of `ids` under the next sequence number and return it
+    pub fn append_delete(&mut self, ids: &[VectorId]) -> Result<u64> {
+        self.append(&LoggedOperation::Delete(Cow::Borrowed(ids)))
+    }
+
+    /// Log that the operation at `sequence` failed to apply, so it is not
+    /// replayed
+    ///
+    /// Sequence numbers are not reused.
+    pub fn abort(&mut self, sequence: u64) -> Result<()> {
+        self.write_record(sequence, &LoggedOperation::Abort)
+    }
+
+    fn append(&mut self, operation: &LoggedOperation) -> Result<u64> {
+        let sequence = self.sequence + 1;
+        self.write_record(sequence, operation)?;
+        self.sequence = sequence;
+        Ok(sequence)
+    }
+
+    fn write_record(&mut self, sequence: u64, operation: &LoggedOperation) -> Result<()> {
+        if let Some((_, file)) = &mut self.file {
+            let mut payload = sequence.to_le_bytes().to_vec();
+            serde_json::to_writer(&mut payload, operation)
+                .map_err(|e| RuvectorError::SerializationError(e.to_string()))?;
+
+            let mut record = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+            record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+            record.extend_from_slice(&checksum(&payload).to_le_bytes());
+            record.extend_from_slice(&payload);
+            file.write_all(&record)?;
+            file.sync_data()?;
+            self.bytes += record.len() as u64;
+        }
+        Ok(())
+    }
+
+    /// Whether enough has been logged since the last checkpoint to write a new one
+    pub fn should_checkpoint(&self) -> bool {
+        self.bytes >= CHECKPOINT_BYTES
+    }
+
+    /// Mark everything logged so far as on disk and drop the records
+    ///
+    /// Callers must have made storage and every index durable first.
+    pub fn checkpoint(&mut self) -> Result<()> {
+        if let Some((_, file)) = &mut self.file {
+            // The header is written first: if the truncation is lost, the
+            // stale records are at or below the checkpoint and get skipped
+            write_header(file, self.sequence)?;
+            file.set_len(HEADER_LEN)?;
+            file.seek(SeekFrom::End(0))?;
+            file.sync_all()?;
+        }
+        self.checkpointed = self.sequence;
+        self.bytes = 0;
+        Ok(())
+    }
+
+    /// Drop every record and continue numbering after `sequence`
+    ///
+    /// Used when a database is restored to the state it had at `sequence`.
+    pub fn restart_at(&mut self, sequence: u64) -> Result<()> {
+        self.sequence = sequence;
+        self.checkpoint()
+    }
+
+    /// Logged records with a sequence number above `sequence`
+    ///
+    /// Returns `None` if some of them were already dropped by a checkpoint,
+    /// or if the log is not backed by a file.
+    pub fn records_since(&self, sequence: u64) -> Result<Option<Vec<WalRecord>>> {
+        let Some((path, _)) = &self.file else {
+            return Ok(None);
+        };
+        if sequence < self.checkpointed {
+            return Ok(None);
+        }
+        let (_, records, _, _) = decode(&std::fs::read(path)?)?;
+        Ok(Some(
+            records
+                .into_iter()
+                .filter(|r| r.sequence > sequence)
+                .collect(),
+        ))
+    }
+}
+
+impl Drop for WriteAheadLog {
+    fn drop(&mut self) {
+        if let Some((path, _)) = &self.file {
+            OPEN_LOGS.lock().remove(path);
+        }
+    }
+}
+
+fn header(checkpointed: u64) -> [u8; HEADER_LEN as usize] {
+    let mut header = [0u8; HEADER_LEN as usize];
+    header[..8].copy_from_slice(WAL_MAGIC);
+    header[8..].copy_from_slice(&checkpointed.to_le_bytes());
+    header
+}
+
+fn write_header(file: &mut File, checkpointed: u64) -> Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&header(checkpointed))?;
+    file.sync_data()?;
+    Ok(())
+}
+
+/// Decode a log file into its checkpointed sequence, its records without
+/// the aborted ones, the last sequence number logged and the length of the
+/// intact prefix
+fn decode(bytes: &[u8]) -> Result<(u64, Vec<WalRecord>, u64, usize)> {
+    if bytes.len() < HEADER_LEN as usize || &bytes[..8] != WAL_MAGIC {
+        return Err(RuvectorError::StorageError(
+            "Write-ahead log has an invalid header".to_string(),
+        ));
+    }
+    let checkpointed = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+
+    let mut records: Vec<WalRecord> = Vec::new();
+    let mut last_sequence = 0;
+    let mut pos = HEADER_LEN as usize;
+    while let Some(record) = bytes.get(pos..pos + RECORD_HEADER_LEN) {
+        let len = u32::from_le_bytes(record[..4].try_into().unwrap()) as usize;
+        let sum = u64::from_le_bytes(record[4..].try_into().unwrap());
+        let start = pos + RECORD_HEADER_LEN;
+        let Some(payload) = bytes.get(start..start + len) else {
+            break;
+        };
+        if len < 8 || checksum(payload) != sum {
+            break;
+        }
+        let sequence = u64::from_le_bytes(payload[..8].try_into().unwrap());
+        // Intact but unreadable: dropping it would drop every acknowledged
+        // write after it too
+        let operation = serde_json::from_slice(&payload[8..]).map_err(|e| {
+            RuvectorError::SerializationError(format!(
+                "Write-ahead log record {} cannot be decoded: {}",
+                sequence, e
+            ))
+        })?;
+        let operation = match operation {
+            LoggedOperation::Upsert(entries) => WalOperation::Upsert(entries.into_owned()),
+            LoggedOperation::Delete(ids) => WalOperation::Delete(ids.into_owned()),
+            LoggedOperation::Abort => {
+                records.retain(|r| r.sequence != sequence);
+                pos = start + len;
+                continue;
+            }
+        };
+        records.push(WalRecord {
+            sequence,
+            operation,
+        });
+        last_sequence = sequence;
+        pos = start + len;
+    }
+    Ok((checkpointed, records, last_sequence, pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn delete(id: &str) -> Vec<VectorId> {
+        vec![id.to_string()]
+    }
+
+    #[test]
+    fn test_replay_past_checkpoint() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db.wal");
+
+        {
+            let (mut wal, records) = WriteAheadLog::open(&path)?;
+            assert!(records.is_empty());
+            assert!(wal.is_new());
+            assert_eq!(wal.append_delete(&delete("a"))?, 1);
+            assert_eq!(wal.append_delete(&delete("b"))?, 2);
+            wal.checkpoint()?;
+            assert_eq!(wal.append_delete(&delete("c"))?, 3);
+            assert_eq!(wal.records_since(1)?.map(|r| r.len()), None);
+            assert_eq!(wal.records_since(2)?.map(|r| r.len()), Some(1));
+        }
+
+        let (wal, records) = WriteAheadLog::open(&path)?;
+        assert!(!wal.is_new());
+        assert_eq!(wal.sequence(), 3);
+        assert_eq!(wal.checkpointed_sequence(), 2);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence, 3);
+        assert!(matches!(&records[0].operation, WalOperation::Delete(ids) if ids == &["c"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_torn_tail_is_dropped() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db.wal");
+
+        {
+            let (mut wal, _) = WriteAheadLog::open(&path)?;
+            wal.append_delete(&delete("a"))?;
+            wal.append_delete(&delete("b"))?;
+        }
+        let len = std::fs::metadata(&path)?.len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)?
+            .set_len(len - 3)?;
+
+        let (mut wal, records) = WriteAheadLog::open(&path)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(wal.sequence(), 1);
+        assert_eq!(wal.append_delete(&delete("b"))?, 2);
+        drop(wal);
+
+        let (_, records) = WriteAheadLog::open(&path)?;
+        assert_eq!(records.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_undecodable_record_fails_open() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db.wal");
+
+        {
+            let (mut wal, _) = WriteAheadLog::open(&path)?;
+            wal.append_delete(&delete("a"))?;
+            // What a NaN vector used to be logged as
+            let mut payload = 2u64.to_le_bytes().to_vec();
+            payload.extend_from_slice(br#"{"Upsert":[{"id":"b","vector":[null]}]}"#);
+            let file = &mut wal.file.as_mut().unwrap().1;
+            file.write_all(&(payload.len() as u32).to_le_bytes())?;
+            file.write_all(&checksum(&payload).to_le_bytes())?;
+            file.write_all(&payload)?;
+            wal.append_delete(&delete("c"))?;
+        }
+        let len = std::fs::metadata(&path)?.len();
+
+        assert!(matches!(
+            WriteAheadLog::open(&path),
+            Err(RuvectorError::SerializationError(_))
+        ));
+        assert_eq!(std::fs::metadata(&path)?.len(), len);
+        Ok(())
+    }
+
+    #[test]
+    fn test_aborted_operations_are_not_replayed() -> Result<()> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.db.wal");
+
+        {
+            let (mut wal, _) = WriteAheadLog::open(&path)?;
+            wal.append_delete(&delete("a"))?;
+            let sequence = wal.append_delete(&delete("b"))?;
+            wal.abort(sequence)?;
+            assert_eq!(wal.records_since(0)?.map(|r| r.len()), Some(1));
+        }
+
+        let (mut wal, records) = WriteAheadLog::open(&path)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].sequence, 1);
+        // The aborted sequence number is not reused
+        assert_eq!(wal.sequence(), 2);
+        assert_eq!(wal.append_delete(&delete("c"))?, 3);
+        Ok(())
+    }
+}
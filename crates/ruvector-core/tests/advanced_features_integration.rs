@@ -153,9 +153,9 @@ fn test_filtered_search_pre_filter() {
     }
 
     // Create filter: category == "A" AND price < 500
-    let filter = FilterExpression::And(vec![
-        FilterExpression::Eq("category".to_string(), json!("A")),
-        FilterExpression::Lt("price".to_string(), json!(500.0)),
+    let filter = FilterExpression::and(vec![
+        FilterExpression::eq("category", json!("A")),
+        FilterExpression::lt("price", json!(500.0)),
     ]);
 
     let search = FilteredSearch::new(filter, FilterStrategy::PreFilter, metadata_store);
@@ -183,7 +183,7 @@ fn test_filtered_search_auto_strategy() {
     }
 
     // Highly selective filter (should choose pre-filter)
-    let selective_filter = FilterExpression::Eq("id".to_string(), json!(42));
+    let selective_filter = FilterExpression::eq("id", json!(42));
     let search1 = FilteredSearch::new(
         selective_filter,
         FilterStrategy::Auto,
@@ -192,7 +192,7 @@ fn test_filtered_search_auto_strategy() {
     assert_eq!(search1.auto_select_strategy(), FilterStrategy::PreFilter);
 
     // Less selective filter (should choose post-filter)
-    let broad_filter = FilterExpression::Gte("id".to_string(), json!(0));
+    let broad_filter = FilterExpression::gte("id", json!(0));
     let search2 = FilteredSearch::new(broad_filter, FilterStrategy::Auto, metadata_store);
     assert_eq!(search2.auto_select_strategy(), FilterStrategy::PostFilter);
 
@@ -27,6 +27,7 @@ fn test_concurrent_reads() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..32).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -83,6 +84,7 @@ fn test_concurrent_writes_no_collision() {
                     .insert(VectorEntry {
                         id: Some(id.clone()),
                         vector: vec![thread_id as f32; 32],
+                        named_vectors: None,
                         metadata: None,
                     })
                     .unwrap();
@@ -118,6 +120,7 @@ fn test_concurrent_delete_and_insert() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: vec![i as f32; 16],
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -151,6 +154,7 @@ fn test_concurrent_delete_and_insert() {
                     .insert(VectorEntry {
                         id: Some(id),
                         vector: vec![(thread_id * 100 + i) as f32; 16],
+                        named_vectors: None,
                         metadata: None,
                     })
                     .unwrap();
@@ -188,6 +192,7 @@ fn test_concurrent_search_and_insert() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..64).map(|j| ((i + j) as f32) * 0.01).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -209,6 +214,7 @@ fn test_concurrent_search_and_insert() {
                 let results = db_clone
                     .search(SearchQuery {
                         vector: query,
+                        using: None,
                         k: 5,
                         filter: None,
                         ef_search: None,
@@ -235,6 +241,7 @@ fn test_concurrent_search_and_insert() {
                         vector: (0..64)
                             .map(|j| ((insert_id * 1000 + i + j) as f32) * 0.01)
                             .collect(),
+                        named_vectors: None,
                         metadata: None,
                     })
                     .unwrap();
@@ -283,6 +290,7 @@ fn test_atomicity_of_batch_insert() {
                         VectorEntry {
                             id: Some(id.clone()),
                             vector: vec![(thread_id * 100 + batch_idx * 10 + i) as f32; 16],
+                            named_vectors: None,
                             metadata: None,
                         }
                     })
@@ -327,6 +335,7 @@ fn test_read_write_consistency() {
     db.insert(VectorEntry {
         id: Some("test".to_string()),
         vector: vec![1.0; 32],
+        named_vectors: None,
         metadata: None,
     })
     .unwrap();
@@ -358,6 +367,7 @@ fn test_read_write_consistency() {
                     let _ = db_clone.insert(VectorEntry {
                         id: Some("test".to_string()),
                         vector: vec![thread_id as f32; 32],
+                        named_vectors: None,
                         metadata: None,
                     });
                 }
@@ -399,6 +409,7 @@ fn test_concurrent_metadata_updates() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: vec![i as f32; 16],
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -421,6 +432,7 @@ fn test_concurrent_metadata_updates() {
                     .insert(VectorEntry {
                         id: Some(id.clone()),
                         vector: vec![thread_id as f32; 16],
+                        named_vectors: None,
                         metadata: Some(metadata),
                     })
                     .unwrap();
@@ -64,6 +64,7 @@ fn test_hnsw_100_vectors() -> Result<()> {
         ef_construction: 100,
         ef_search: 200,
         max_elements: 1000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -130,6 +131,7 @@ fn test_hnsw_1k_vectors() -> Result<()> {
         ef_construction: 200,
         ef_search: 200,
         max_elements: 10000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -197,6 +199,7 @@ fn test_hnsw_10k_vectors() -> Result<()> {
         ef_construction: 200,
         ef_search: 200,
         max_elements: 100000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -275,6 +278,7 @@ fn test_hnsw_ef_search_tuning() -> Result<()> {
         ef_construction: 200,
         ef_search: 50, // Start with lower ef_search
         max_elements: 10000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -366,6 +370,7 @@ fn test_hnsw_serialization_large() -> Result<()> {
         ef_construction: 200,
         ef_search: 100,
         max_elements: 10000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -428,6 +433,7 @@ fn test_hnsw_different_metrics() -> Result<()> {
             ef_construction: 100,
             ef_search: 100,
             max_elements: 1000,
+            ..Default::default()
         };
 
         let mut index = HnswIndex::new(dimensions, metric, config)?;
@@ -460,6 +466,7 @@ fn test_hnsw_parallel_batch_insert() -> Result<()> {
         ef_construction: 200,
         ef_search: 100,
         max_elements: 10000,
+        ..Default::default()
     };
 
     let mut index = HnswIndex::new(dimensions, DistanceMetric::Cosine, config)?;
@@ -23,6 +23,7 @@ fn test_complete_insert_search_workflow() {
         ef_construction: 100,
         ef_search: 50,
         max_elements: 100_000,
+        ..Default::default()
     });
 
     let db = VectorDB::new(options).unwrap();
@@ -36,6 +37,7 @@ fn test_complete_insert_search_workflow() {
             VectorEntry {
                 id: Some(format!("vec_{}", i)),
                 vector: (0..128).map(|j| ((i + j) as f32) * 0.01).collect(),
+                named_vectors: None,
                 metadata: Some(metadata),
             }
         })
@@ -49,6 +51,7 @@ fn test_complete_insert_search_workflow() {
     let results = db
         .search(SearchQuery {
             vector: query,
+            using: None,
             k: 10,
             filter: None,
             ef_search: Some(100),
@@ -77,6 +80,7 @@ fn test_batch_operations_10k_vectors() {
         .map(|i| VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..384).map(|j| ((i + j) as f32) * 0.001).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -98,6 +102,7 @@ fn test_batch_operations_10k_vectors() {
         let results = db
             .search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
@@ -130,6 +135,7 @@ fn test_persistence_and_reload() {
             db.insert(VectorEntry {
                 id: Some(format!("vec_{}", i)),
                 vector: vec![i as f32, (i * 2) as f32, (i * 3) as f32],
+                named_vectors: None,
                 metadata: None,
             })
             .unwrap();
@@ -169,6 +175,7 @@ fn test_mixed_operations_workflow() {
         .map(|i| VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..64).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -187,6 +194,7 @@ fn test_mixed_operations_workflow() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..64).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -198,6 +206,7 @@ fn test_mixed_operations_workflow() {
     let results = db
         .search(SearchQuery {
             vector: query,
+            using: None,
             k: 20,
             filter: None,
             ef_search: None,
@@ -235,6 +244,7 @@ fn test_all_distance_metrics() {
             db.insert(VectorEntry {
                 id: Some(format!("vec_{}", i)),
                 vector: (0..32).map(|j| ((i + j) as f32) * 0.1).collect(),
+                named_vectors: None,
                 metadata: None,
             })
             .unwrap();
@@ -245,6 +255,7 @@ fn test_all_distance_metrics() {
         let results = db
             .search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 5,
                 filter: None,
                 ef_search: None,
@@ -278,18 +289,21 @@ fn test_hnsw_different_configurations() {
             ef_construction: 50,
             ef_search: 50,
             max_elements: 1000,
+            ..Default::default()
         },
         HnswConfig {
             m: 16,
             ef_construction: 100,
             ef_search: 100,
             max_elements: 1000,
+            ..Default::default()
         },
         HnswConfig {
             m: 32,
             ef_construction: 200,
             ef_search: 200,
             max_elements: 1000,
+            ..Default::default()
         },
     ];
 
@@ -307,6 +321,7 @@ fn test_hnsw_different_configurations() {
             .map(|i| VectorEntry {
                 id: Some(format!("vec_{}", i)),
                 vector: (0..64).map(|j| ((i + j) as f32) * 0.01).collect(),
+                named_vectors: None,
                 metadata: None,
             })
             .collect();
@@ -318,6 +333,7 @@ fn test_hnsw_different_configurations() {
         let results = db
             .search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: Some(config.ef_search),
@@ -351,6 +367,7 @@ fn test_complex_metadata_filtering() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..16).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: Some(metadata),
         })
         .unwrap();
@@ -364,8 +381,9 @@ fn test_complex_metadata_filtering() {
     let results1 = db
         .search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 100,
-            filter: Some(filter1),
+            filter: Some(filter1.into()),
             ef_search: None,
         })
         .unwrap();
@@ -383,8 +401,9 @@ fn test_complex_metadata_filtering() {
     let results2 = db
         .search(SearchQuery {
             vector: query,
+            using: None,
             k: 100,
-            filter: Some(filter2),
+            filter: Some(filter2.into()),
             ef_search: None,
         })
         .unwrap();
@@ -413,6 +432,7 @@ fn test_dimension_validation() {
     let result = db.insert(VectorEntry {
         id: None,
         vector: vec![1.0, 2.0, 3.0], // Only 3 dimensions, should be 64
+        named_vectors: None,
         metadata: None,
     });
 
@@ -433,6 +453,7 @@ fn test_search_with_wrong_dimension() {
     db.insert(VectorEntry {
         id: Some("v1".to_string()),
         vector: (0..64).map(|i| i as f32).collect(),
+        named_vectors: None,
         metadata: None,
     })
     .unwrap();
@@ -442,6 +463,7 @@ fn test_search_with_wrong_dimension() {
     let query = vec![1.0, 2.0, 3.0]; // Wrong dimension
     let result = db.search(SearchQuery {
         vector: query,
+        using: None,
         k: 10,
         filter: None,
         ef_search: None,
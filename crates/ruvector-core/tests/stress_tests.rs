@@ -24,6 +24,7 @@ fn test_million_vector_insertion() {
         ef_construction: 100,
         ef_search: 50,
         max_elements: 2_000_000,
+        ..Default::default()
     });
 
     let db = VectorDB::new(options).unwrap();
@@ -43,6 +44,7 @@ fn test_million_vector_insertion() {
                     vector: (0..128)
                         .map(|j| ((global_idx + j) as f32) * 0.0001)
                         .collect(),
+                    named_vectors: None,
                     metadata: None,
                 }
             })
@@ -67,6 +69,7 @@ fn test_million_vector_insertion() {
         let results = db
             .search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: Some(50),
@@ -108,6 +111,7 @@ fn test_concurrent_queries() {
         .map(|i| VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: (0..64).map(|j| ((i + j) as f32) * 0.01).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -140,6 +144,7 @@ fn test_concurrent_queries() {
                 let results = db_clone
                     .search(SearchQuery {
                         vector: query,
+                        using: None,
                         k: 10,
                         filter: None,
                         ef_search: None,
@@ -194,6 +199,7 @@ fn test_concurrent_inserts_and_queries() {
         .map(|i| VectorEntry {
             id: Some(format!("initial_{}", i)),
             vector: (0..32).map(|j| ((i + j) as f32) * 0.1).collect(),
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -221,6 +227,7 @@ fn test_concurrent_inserts_and_queries() {
                 let results = db_clone
                     .search(SearchQuery {
                         vector: query,
+                        using: None,
                         k: 5,
                         filter: None,
                         ef_search: None,
@@ -250,6 +257,7 @@ fn test_concurrent_inserts_and_queries() {
                     vector: (0..32)
                         .map(|j| ((writer_id * 1000 + i + j) as f32) * 0.1)
                         .collect(),
+                    named_vectors: None,
                     metadata: None,
                 };
 
@@ -293,6 +301,7 @@ fn test_memory_pressure_large_vectors() {
         ef_construction: 50,
         ef_search: 50,
         max_elements: 100_000,
+        ..Default::default()
     });
 
     let db = VectorDB::new(options).unwrap();
@@ -310,6 +319,7 @@ fn test_memory_pressure_large_vectors() {
                     vector: (0..2048)
                         .map(|j| ((global_idx + j) as f32) * 0.0001)
                         .collect(),
+                    named_vectors: None,
                     metadata: None,
                 }
             })
@@ -334,6 +344,7 @@ fn test_memory_pressure_large_vectors() {
         let results = db
             .search(SearchQuery {
                 vector: query,
+                using: None,
                 k: 10,
                 filter: None,
                 ef_search: None,
@@ -368,6 +379,7 @@ fn test_invalid_operations_dont_crash() {
     // 3. Search with k=0
     let result = db.search(SearchQuery {
         vector: vec![0.0; 32],
+        using: None,
         k: 0,
         filter: None,
         ef_search: None,
@@ -381,6 +393,7 @@ fn test_invalid_operations_dont_crash() {
             .insert(VectorEntry {
                 id: Some(format!("temp_{}", i)),
                 vector: vec![1.0; 32],
+                named_vectors: None,
                 metadata: None,
             })
             .unwrap();
@@ -392,6 +405,7 @@ fn test_invalid_operations_dont_crash() {
     db.insert(VectorEntry {
         id: Some("final".to_string()),
         vector: vec![1.0; 32],
+        named_vectors: None,
         metadata: None,
     })
     .unwrap();
@@ -414,6 +428,7 @@ fn test_repeated_operations() {
         let _ = db.insert(VectorEntry {
             id: Some("same_id".to_string()),
             vector: vec![1.0; 16],
+            named_vectors: None,
             metadata: None,
         });
     }
@@ -428,6 +443,7 @@ fn test_repeated_operations() {
     for _ in 0..100 {
         let _ = db.search(SearchQuery {
             vector: query.clone(),
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -454,6 +470,7 @@ fn test_extreme_k_values() {
         db.insert(VectorEntry {
             id: Some(format!("vec_{}", i)),
             vector: vec![i as f32; 16],
+            named_vectors: None,
             metadata: None,
         })
         .unwrap();
@@ -463,6 +480,7 @@ fn test_extreme_k_values() {
     let results = db
         .search(SearchQuery {
             vector: vec![1.0; 16],
+            using: None,
             k: 1000,
             filter: None,
             ef_search: None,
@@ -476,6 +494,7 @@ fn test_extreme_k_values() {
     let results = db
         .search(SearchQuery {
             vector: vec![1.0; 16],
+            using: None,
             k: 1,
             filter: None,
             ef_search: None,
@@ -215,6 +215,7 @@ mod storage_tests {
         let entry = VectorEntry {
             id: Some("explicit_id".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -232,6 +233,7 @@ mod storage_tests {
         let entry = VectorEntry {
             id: None,
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         };
 
@@ -253,6 +255,7 @@ mod storage_tests {
         let entry = VectorEntry {
             id: Some("meta_test".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: Some(metadata.clone()),
         };
 
@@ -272,6 +275,7 @@ mod storage_tests {
         let entry = VectorEntry {
             id: None,
             vector: vec![1.0, 2.0], // Wrong dimension
+            named_vectors: None,
             metadata: None,
         };
 
@@ -329,11 +333,13 @@ mod storage_tests {
             VectorEntry {
                 id: None,
                 vector: vec![1.0, 2.0, 3.0],
+                named_vectors: None,
                 metadata: None,
             },
             VectorEntry {
                 id: None,
                 vector: vec![1.0, 2.0], // Wrong dimension
+                named_vectors: None,
                 metadata: None,
             },
         ];
@@ -364,12 +370,14 @@ mod storage_tests {
         storage.insert(&VectorEntry {
             id: Some("id1".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
         storage.insert(&VectorEntry {
             id: Some("id2".to_string()),
             vector: vec![4.0, 5.0, 6.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
@@ -418,6 +426,7 @@ mod vector_db_tests {
         db.insert(VectorEntry {
             id: None,
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
@@ -439,6 +448,7 @@ mod vector_db_tests {
         let id = db.insert(VectorEntry {
             id: Some("test_id".to_string()),
             vector: vec![1.0, 2.0, 3.0],
+            named_vectors: None,
             metadata: None,
         })?;
 
@@ -463,6 +473,7 @@ mod vector_db_tests {
 
         let results = db.search(SearchQuery {
             vector: vec![1.0, 2.0, 3.0],
+            using: None,
             k: 10,
             filter: None,
             ef_search: None,
@@ -493,12 +504,14 @@ mod vector_db_tests {
         db.insert(VectorEntry {
             id: Some("v1".to_string()),
             vector: vec![1.0, 0.0, 0.0],
+            named_vectors: None,
             metadata: Some(meta1),
         })?;
 
         db.insert(VectorEntry {
             id: Some("v2".to_string()),
             vector: vec![0.9, 0.1, 0.0],
+            named_vectors: None,
             metadata: Some(meta2),
         })?;
 
@@ -508,8 +521,9 @@ mod vector_db_tests {
 
         let results = db.search(SearchQuery {
             vector: vec![1.0, 0.0, 0.0],
+            using: None,
             k: 10,
-            filter: Some(filter),
+            filter: Some(filter.into()),
             ef_search: None,
         })?;
 
@@ -532,16 +546,19 @@ mod vector_db_tests {
             VectorEntry {
                 id: None,
                 vector: vec![1.0, 0.0, 0.0],
+                named_vectors: None,
                 metadata: None,
             },
             VectorEntry {
                 id: None,
                 vector: vec![0.0, 1.0, 0.0],
+                named_vectors: None,
                 metadata: None,
             },
             VectorEntry {
                 id: None,
                 vector: vec![0.0, 0.0, 1.0],
+                named_vectors: None,
                 metadata: None,
             },
         ];
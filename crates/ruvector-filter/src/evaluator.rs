@@ -51,71 +51,7 @@ impl<'a> FilterEvaluator<'a> {
 
     /// Check if a payload matches a filter expression
     pub fn matches(&self, payload: &Value, filter: &FilterExpression) -> bool {
-        match filter {
-            FilterExpression::Eq { field, value } => {
-                Self::get_field_value(payload, field).map_or(false, |v| v == value)
-            }
-            FilterExpression::Ne { field, value } => {
-                Self::get_field_value(payload, field).map_or(true, |v| v != value)
-            }
-            FilterExpression::Gt { field, value } => Self::get_field_value(payload, field)
-                .map_or(false, |v| {
-                    Self::compare_values(v, value) == Some(std::cmp::Ordering::Greater)
-                }),
-            FilterExpression::Gte { field, value } => {
-                Self::get_field_value(payload, field).map_or(false, |v| {
-                    matches!(
-                        Self::compare_values(v, value),
-                        Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
-                    )
-                })
-            }
-            FilterExpression::Lt { field, value } => Self::get_field_value(payload, field)
-                .map_or(false, |v| {
-                    Self::compare_values(v, value) == Some(std::cmp::Ordering::Less)
-                }),
-            FilterExpression::Lte { field, value } => {
-                Self::get_field_value(payload, field).map_or(false, |v| {
-                    matches!(
-                        Self::compare_values(v, value),
-                        Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
-                    )
-                })
-            }
-            FilterExpression::Range { field, gte, lte } => {
-                if let Some(v) = Self::get_field_value(payload, field) {
-                    let gte_match = gte.as_ref().map_or(true, |gte_val| {
-                        matches!(
-                            Self::compare_values(v, gte_val),
-                            Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
-                        )
-                    });
-                    let lte_match = lte.as_ref().map_or(true, |lte_val| {
-                        matches!(
-                            Self::compare_values(v, lte_val),
-                            Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
-                        )
-                    });
-                    gte_match && lte_match
-                } else {
-                    false
-                }
-            }
-            FilterExpression::In { field, values } => {
-                Self::get_field_value(payload, field).map_or(false, |v| values.contains(v))
-            }
-            FilterExpression::Match { field, text } => Self::get_field_value(payload, field)
-                .and_then(|v| v.as_str())
-                .map_or(false, |s| s.to_lowercase().contains(&text.to_lowercase())),
-            FilterExpression::And(filters) => filters.iter().all(|f| self.matches(payload, f)),
-            FilterExpression::Or(filters) => filters.iter().any(|f| self.matches(payload, f)),
-            FilterExpression::Not(filter) => !self.matches(payload, filter),
-            FilterExpression::Exists { field } => Self::get_field_value(payload, field).is_some(),
-            FilterExpression::IsNull { field } => {
-                Self::get_field_value(payload, field).map_or(true, |v| v.is_null())
-            }
-            _ => false, // Geo operations not supported in direct matching
-        }
+        filter.matches(payload)
     }
 
     fn evaluate_eq(&self, field: &str, value: &Value) -> Result<HashSet<String>> {
@@ -459,26 +395,10 @@ impl<'a> FilterEvaluator<'a> {
 
         Ok(ids)
     }
-
-    fn get_field_value<'b>(payload: &'b Value, field: &str) -> Option<&'b Value> {
-        payload.as_object()?.get(field)
-    }
-
-    fn compare_values(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
-        match (a, b) {
-            (Value::Number(a), Value::Number(b)) => {
-                let a = a.as_f64()?;
-                let b = b.as_f64()?;
-                a.partial_cmp(&b)
-            }
-            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
-            _ => None,
-        }
-    }
 }
 
 /// Calculate haversine distance between two points in meters
-fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+pub(crate) fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     const EARTH_RADIUS_M: f64 = 6_371_000.0; // Earth's radius in meters
 
     let lat1_rad = lat1.to_radians();
@@ -1,5 +1,8 @@
+use crate::evaluator::haversine_distance;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
 /// Filter expression for querying vectors by payload
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,9 +66,13 @@ pub enum FilterExpression {
         bottom_right: (f64, f64),
     },
 
-    // Logical operators
+    // Logical operators, written as `{"type": "and", "filters": [...]}` and
+    // `{"type": "not", "filter": {...}}`
+    #[serde(with = "filters")]
     And(Vec<FilterExpression>),
+    #[serde(with = "filters")]
     Or(Vec<FilterExpression>),
+    #[serde(with = "negated")]
     Not(Box<FilterExpression>),
 
     // Existence check
@@ -203,6 +210,21 @@ impl FilterExpression {
         }
     }
 
+    /// Parse a filter from JSON
+    ///
+    /// Accepts a tagged expression (`{"type": "eq", "field": ..., "value": ...}`)
+    /// or, for compatibility, a plain object without a `type` key whose
+    /// entries must all match exactly. Malformed tagged expressions are
+    /// errors rather than exact-match filters.
+    pub fn from_json(value: Value) -> crate::error::Result<Self> {
+        match value {
+            Value::Object(map) if !map.contains_key("type") => {
+                Ok(map.into_iter().collect::<HashMap<_, _>>().into())
+            }
+            value => Ok(serde_json::from_value(value)?),
+        }
+    }
+
     /// Get all field names referenced in this expression
     pub fn get_fields(&self) -> Vec<String> {
         let mut fields = Vec::new();
@@ -212,6 +234,100 @@ impl FilterExpression {
         fields
     }
 
+    /// Check a JSON object payload against this expression without any index
+    pub fn matches(&self, payload: &Value) -> bool {
+        self.matches_with(&|field| payload.as_object()?.get(field))
+    }
+
+    /// Check a metadata map against this expression without any index
+    pub fn matches_metadata(&self, metadata: &HashMap<String, Value>) -> bool {
+        self.matches_with(&|field| metadata.get(field))
+    }
+
+    fn matches_with<'v>(&self, lookup: &dyn Fn(&str) -> Option<&'v Value>) -> bool {
+        let compare = |field: &str, value: &Value, accept: fn(Ordering) -> bool| {
+            lookup(field)
+                .and_then(|v| compare_values(v, value))
+                .is_some_and(accept)
+        };
+
+        match self {
+            Self::Eq { field, value } => lookup(field) == Some(value),
+            Self::Ne { field, value } => lookup(field) != Some(value),
+            Self::Gt { field, value } => compare(field, value, Ordering::is_gt),
+            Self::Gte { field, value } => compare(field, value, Ordering::is_ge),
+            Self::Lt { field, value } => compare(field, value, Ordering::is_lt),
+            Self::Lte { field, value } => compare(field, value, Ordering::is_le),
+            Self::Range { field, gte, lte } => {
+                lookup(field).is_some()
+                    && gte.as_ref().map_or(true, |v| compare(field, v, Ordering::is_ge))
+                    && lte.as_ref().map_or(true, |v| compare(field, v, Ordering::is_le))
+            }
+            Self::In { field, values } => lookup(field).is_some_and(|v| values.contains(v)),
+            Self::Match { field, text } => lookup(field)
+                .and_then(|v| v.as_str())
+                .is_some_and(|s| s.to_lowercase().contains(&text.to_lowercase())),
+            Self::GeoRadius {
+                field,
+                lat,
+                lon,
+                radius_m,
+            } => lookup(field)
+                .and_then(geo_point)
+                .is_some_and(|(plat, plon)| haversine_distance(*lat, *lon, plat, plon) <= *radius_m),
+            Self::GeoBoundingBox {
+                field,
+                top_left,
+                bottom_right,
+            } => lookup(field).and_then(geo_point).is_some_and(|(plat, plon)| {
+                plat <= top_left.0
+                    && plat >= bottom_right.0
+                    && plon >= top_left.1
+                    && plon <= bottom_right.1
+            }),
+            Self::And(filters) => filters.iter().all(|f| f.matches_with(lookup)),
+            Self::Or(filters) => filters.iter().any(|f| f.matches_with(lookup)),
+            Self::Not(filter) => !filter.matches_with(lookup),
+            Self::Exists { field } => lookup(field).is_some(),
+            Self::IsNull { field } => lookup(field).map_or(true, |v| v.is_null()),
+        }
+    }
+
+    /// Estimate the fraction of vectors matching this expression
+    ///
+    /// Returns a value in `[0.0, 1.0]` where lower means more selective. The
+    /// estimate is a heuristic based on the operators alone; it does not
+    /// look at index statistics, but never drops below one vector out of
+    /// `total_vectors`.
+    pub fn estimate_selectivity(&self, total_vectors: usize) -> f32 {
+        let estimate = match self {
+            Self::Eq { .. } => 0.1,
+            Self::Ne { .. } => 0.9,
+            Self::In { values, .. } => values.len() as f32 / 100.0,
+            Self::Range { .. } => 0.3,
+            Self::Gt { .. } | Self::Gte { .. } | Self::Lt { .. } | Self::Lte { .. } => 0.5,
+            Self::Match { .. } => 0.1,
+            Self::GeoRadius { .. } | Self::GeoBoundingBox { .. } => 0.1,
+            Self::Exists { .. } => 0.9,
+            Self::IsNull { .. } => 0.1,
+            Self::And(filters) => filters
+                .iter()
+                .map(|f| f.estimate_selectivity(total_vectors))
+                .product(),
+            Self::Or(filters) => filters
+                .iter()
+                .map(|f| f.estimate_selectivity(total_vectors))
+                .sum(),
+            Self::Not(filter) => 1.0 - filter.estimate_selectivity(total_vectors),
+        };
+        let one_vector = if total_vectors > 0 {
+            1.0 / total_vectors as f32
+        } else {
+            0.0
+        };
+        estimate.clamp(one_vector, 1.0)
+    }
+
     fn collect_fields(&self, fields: &mut Vec<String>) {
         match self {
             Self::Eq { field, .. }
@@ -241,6 +357,100 @@ impl FilterExpression {
     }
 }
 
+/// Exact-match filter on every entry of a metadata map
+impl From<HashMap<String, Value>> for FilterExpression {
+    fn from(map: HashMap<String, Value>) -> Self {
+        let mut filters: Vec<_> = map
+            .into_iter()
+            .map(|(field, value)| Self::Eq { field, value })
+            .collect();
+        if filters.len() == 1 {
+            filters.remove(0)
+        } else {
+            Self::And(filters)
+        }
+    }
+}
+
+/// Serde form of [`FilterExpression::And`] and [`FilterExpression::Or`]
+///
+/// An internally tagged enum cannot hold a bare sequence, so the operands
+/// go in a `filters` field.
+mod filters {
+    use super::FilterExpression;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Ref<'a> {
+        filters: &'a [FilterExpression],
+    }
+
+    #[derive(Deserialize)]
+    struct Owned {
+        filters: Vec<FilterExpression>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        filters: &[FilterExpression],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Ref { filters }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<FilterExpression>, D::Error> {
+        Ok(Owned::deserialize(deserializer)?.filters)
+    }
+}
+
+/// Serde form of [`FilterExpression::Not`]
+///
+/// Nesting the operand in a `filter` field keeps its own `type` tag apart
+/// from `"not"`, and stops serialization from wrapping the serializer once
+/// per level of negation.
+mod negated {
+    use super::FilterExpression;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Ref<'a> {
+        filter: &'a FilterExpression,
+    }
+
+    #[derive(Deserialize)]
+    struct Owned {
+        filter: Box<FilterExpression>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        filter: &FilterExpression,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Ref { filter }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<FilterExpression>, D::Error> {
+        Ok(Owned::deserialize(deserializer)?.filter)
+    }
+}
+
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn geo_point(value: &Value) -> Option<(f64, f64)> {
+    let obj = value.as_object()?;
+    Some((obj.get("lat")?.as_f64()?, obj.get("lon")?.as_f64()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,11 +482,83 @@ mod tests {
         assert_eq!(fields, vec!["age", "score", "status"]);
     }
 
+    #[test]
+    fn test_matches_metadata() {
+        let metadata: HashMap<String, Value> = [
+            ("category".to_string(), json!("electronics")),
+            ("price".to_string(), json!(50.0)),
+            ("location".to_string(), json!({"lat": 40.7128, "lon": -74.0060})),
+        ]
+        .into_iter()
+        .collect();
+
+        let filter = FilterExpression::and(vec![
+            FilterExpression::eq("category", json!("electronics")),
+            FilterExpression::range("price", Some(json!(10.0)), Some(json!(100.0))),
+            FilterExpression::geo_radius("location", 40.7, -74.0, 5_000.0),
+        ]);
+        assert!(filter.matches_metadata(&metadata));
+        assert!(!FilterExpression::lt("price", json!(50.0)).matches_metadata(&metadata));
+        assert!(FilterExpression::not(FilterExpression::exists("missing")).matches_metadata(&metadata));
+
+        let from_map: FilterExpression =
+            HashMap::from([("category".to_string(), json!("books"))]).into();
+        assert!(!from_map.matches_metadata(&metadata));
+    }
+
+    #[test]
+    fn test_selectivity_estimation() {
+        let eq = FilterExpression::eq("field", json!("value"));
+        assert!(eq.estimate_selectivity(1000) < 0.5);
+        assert!(FilterExpression::not(eq.clone()).estimate_selectivity(1000) > 0.5);
+
+        let and = FilterExpression::and(vec![eq.clone(), eq.clone()]);
+        assert!(and.estimate_selectivity(1000) < eq.estimate_selectivity(1000));
+
+        let or = FilterExpression::or(vec![FilterExpression::ne("a", json!(1)); 3]);
+        assert_eq!(or.estimate_selectivity(1000), 1.0);
+
+        let narrow = FilterExpression::and(vec![eq.clone(); 4]);
+        assert_eq!(narrow.estimate_selectivity(100), 0.01);
+        assert!((narrow.estimate_selectivity(0) - 1e-4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let filter = FilterExpression::from_json(json!({"type": "eq", "field": "a", "value": 1}));
+        assert!(matches!(filter, Ok(FilterExpression::Eq { .. })));
+
+        let filter = FilterExpression::from_json(json!({"a": 1, "b": "x"})).unwrap();
+        assert!(matches!(filter, FilterExpression::And(_)));
+
+        // A tagged object is never read as an exact-match map
+        assert!(FilterExpression::from_json(json!({"type": "eq", "field": "a"})).is_err());
+        assert!(FilterExpression::from_json(json!({"type": "nope"})).is_err());
+        assert!(FilterExpression::from_json(json!([1])).is_err());
+    }
+
     #[test]
     fn test_serialization() {
         let filter = FilterExpression::eq("status", json!("active"));
         let json = serde_json::to_string(&filter).unwrap();
         let deserialized: FilterExpression = serde_json::from_str(&json).unwrap();
         assert!(matches!(deserialized, FilterExpression::Eq { .. }));
+
+        let filter = FilterExpression::and(vec![
+            FilterExpression::eq("status", json!("active")),
+            FilterExpression::not(FilterExpression::or(vec![
+                FilterExpression::exists("deleted"),
+                FilterExpression::lt("age", json!(18)),
+            ])),
+        ]);
+        let value = serde_json::to_value(&filter).unwrap();
+        assert_eq!(value["type"], "and");
+        assert_eq!(value["filters"][1]["type"], "not");
+        assert_eq!(value["filters"][1]["filter"]["type"], "or");
+        let deserialized = FilterExpression::from_json(value).unwrap();
+        assert_eq!(deserialized.get_fields(), filter.get_fields());
+
+        let metadata = HashMap::from([("status".to_string(), json!("active"))]);
+        assert!(deserialized.matches_metadata(&metadata));
     }
 }
@@ -13,10 +13,11 @@ use ruvector_core::advanced::hypergraph::{
     HypergraphIndex as CoreHypergraphIndex,
 };
 use ruvector_core::DistanceMetric;
-use ruvector_graph::cypher::{parse_cypher, Statement};
+use ruvector_graph::cypher::Value;
 use ruvector_graph::node::NodeBuilder;
 use ruvector_graph::storage::GraphStorage;
-use ruvector_graph::GraphDB;
+use ruvector_graph::{Edge, GraphDB, Node, Properties};
+use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
 
 mod streaming;
@@ -257,72 +258,9 @@ impl GraphDatabase {
         let hypergraph = self.hypergraph.clone();
 
         tokio::task::spawn_blocking(move || {
-            // Parse the Cypher query
-            let parsed = parse_cypher(&cypher)
-                .map_err(|e| Error::from_reason(format!("Cypher parse error: {}", e)))?;
-
             let gdb = graph_db.read().expect("RwLock poisoned");
             let hg = hypergraph.read().expect("RwLock poisoned");
-
-            let mut result_nodes: Vec<JsNodeResult> = Vec::new();
-            let mut result_edges: Vec<JsEdgeResult> = Vec::new();
-
-            // Execute each statement
-            for statement in &parsed.statements {
-                match statement {
-                    Statement::Match(match_clause) => {
-                        // Extract label from match patterns for query
-                        for pattern in &match_clause.patterns {
-                            if let ruvector_graph::cypher::ast::Pattern::Node(node_pattern) =
-                                pattern
-                            {
-                                for label in &node_pattern.labels {
-                                    let nodes = gdb.get_nodes_by_label(label);
-                                    for node in nodes {
-                                        result_nodes.push(JsNodeResult {
-                                            id: node.id.clone(),
-                                            labels: node
-                                                .labels
-                                                .iter()
-                                                .map(|l| l.name.clone())
-                                                .collect(),
-                                            properties: node
-                                                .properties
-                                                .iter()
-                                                .map(|(k, v)| (k.clone(), format!("{:?}", v)))
-                                                .collect(),
-                                        });
-                                    }
-                                }
-                                // If no labels specified, return all nodes (simplified)
-                                if node_pattern.labels.is_empty() && node_pattern.variable.is_some()
-                                {
-                                    // This would need iteration over all nodes - for now just stats
-                                }
-                            }
-                        }
-                    }
-                    Statement::Create(create_clause) => {
-                        // Handle CREATE - but we need mutable access, so skip in query
-                    }
-                    Statement::Return(_) => {
-                        // RETURN is handled implicitly
-                    }
-                    _ => {}
-                }
-            }
-
-            let stats = hg.stats();
-
-            Ok::<JsQueryResult, Error>(JsQueryResult {
-                nodes: result_nodes,
-                edges: result_edges,
-                stats: Some(JsGraphStats {
-                    total_nodes: stats.total_entities as u32,
-                    total_edges: stats.total_hyperedges as u32,
-                    avg_degree: stats.avg_entity_degree as f64,
-                }),
-            })
+            execute_cypher(&gdb, &hg, &cypher)
         })
         .await
         .map_err(|e| Error::from_reason(format!("Task failed: {}", e)))?
@@ -336,19 +274,9 @@ impl GraphDatabase {
     /// ```
     #[napi]
     pub fn query_sync(&self, cypher: String) -> Result<JsQueryResult> {
+        let gdb = self.graph_db.read().expect("RwLock poisoned");
         let hg = self.hypergraph.read().expect("RwLock poisoned");
-        let stats = hg.stats();
-
-        // Simplified query result for now
-        Ok(JsQueryResult {
-            nodes: vec![],
-            edges: vec![],
-            stats: Some(JsGraphStats {
-                total_nodes: stats.total_entities as u32,
-                total_edges: stats.total_hyperedges as u32,
-                avg_degree: stats.avg_entity_degree as f64,
-            }),
-        })
+        execute_cypher(&gdb, &hg, &cypher)
     }
 
     /// Search for similar hyperedges
@@ -550,6 +478,98 @@ impl GraphDatabase {
     }
 }
 
+/// Run a Cypher query on the property graph and convert the result for JavaScript
+fn execute_cypher(gdb: &GraphDB, hg: &CoreHypergraphIndex, cypher: &str) -> Result<JsQueryResult> {
+    let result = gdb
+        .execute(cypher, &Properties::new())
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+
+    // Collect the distinct nodes and relationships returned anywhere in the rows
+    let mut entities = EntityCollector::default();
+    for row in &result {
+        for value in row.values() {
+            entities.collect(value);
+        }
+    }
+
+    let rows = result
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|(column, value)| (column.to_string(), value.to_json().to_string()))
+                .collect()
+        })
+        .collect();
+
+    let stats = hg.stats();
+    Ok(JsQueryResult {
+        nodes: entities.nodes,
+        edges: entities.edges,
+        columns: result.columns.clone(),
+        rows,
+        stats: Some(JsGraphStats {
+            total_nodes: u32::try_from(stats.total_entities).unwrap_or(u32::MAX),
+            total_edges: u32::try_from(stats.total_hyperedges).unwrap_or(u32::MAX),
+            avg_degree: f64::from(stats.avg_entity_degree),
+        }),
+    })
+}
+
+/// Gathers the nodes and relationships contained in query values
+#[derive(Default)]
+struct EntityCollector {
+    seen_nodes: HashSet<String>,
+    seen_edges: HashSet<String>,
+    nodes: Vec<JsNodeResult>,
+    edges: Vec<JsEdgeResult>,
+}
+
+impl EntityCollector {
+    fn collect(&mut self, value: &Value) {
+        match value {
+            Value::Node(node) => self.add_node(node),
+            Value::Relationship(edge) => self.add_edge(edge),
+            Value::Path(path) => {
+                path.nodes.iter().for_each(|n| self.add_node(n));
+                path.relationships.iter().for_each(|e| self.add_edge(e));
+            }
+            Value::List(items) => items.iter().for_each(|v| self.collect(v)),
+            Value::Map(map) => map.values().for_each(|v| self.collect(v)),
+            _ => {}
+        }
+    }
+
+    fn add_node(&mut self, node: &Node) {
+        if self.seen_nodes.insert(node.id.clone()) {
+            self.nodes.push(JsNodeResult {
+                id: node.id.clone(),
+                labels: node.labels.iter().map(|l| l.name.clone()).collect(),
+                properties: node
+                    .properties
+                    .iter()
+                    .map(|(k, v)| (k.clone(), format!("{v:?}")))
+                    .collect(),
+            });
+        }
+    }
+
+    fn add_edge(&mut self, edge: &Edge) {
+        if self.seen_edges.insert(edge.id.clone()) {
+            self.edges.push(JsEdgeResult {
+                id: edge.id.clone(),
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                edge_type: edge.edge_type.clone(),
+                properties: edge
+                    .properties
+                    .iter()
+                    .map(|(k, v)| (k.clone(), format!("{v:?}")))
+                    .collect(),
+            });
+        }
+    }
+}
+
 /// Get the version of the library
 #[napi]
 pub fn version() -> String {
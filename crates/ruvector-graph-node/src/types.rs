@@ -152,6 +152,10 @@ pub struct JsQueryResult {
     pub nodes: Vec<JsNodeResult>,
     /// Edges returned by the query
     pub edges: Vec<JsEdgeResult>,
+    /// Column names of the returned rows
+    pub columns: Vec<String>,
+    /// Returned rows, with each value encoded as JSON text
+    pub rows: Vec<HashMap<String, String>>,
     /// Optional statistics
     pub stats: Option<JsGraphStats>,
 }
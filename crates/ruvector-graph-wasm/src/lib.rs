@@ -6,7 +6,7 @@
 //! Features:
 //! - Node and edge CRUD operations
 //! - Hyperedge support for n-ary relationships
-//! - Basic Cypher query support
+//! - Cypher queries executed by the core graph engine
 //! - Web Workers support for parallel operations
 //! - Async query execution with streaming results
 //! - IndexedDB persistence (planned)
@@ -17,8 +17,10 @@ use ruvector_core::advanced::hypergraph::{
     Hyperedge as CoreHyperedge, HypergraphIndex, TemporalGranularity, TemporalHyperedge,
 };
 use ruvector_core::types::DistanceMetric;
+use ruvector_graph::cypher::Value;
+use ruvector_graph::{EdgeBuilder, GraphDB as CoreGraphDB, NodeBuilder, Properties};
 use serde_wasm_bindgen::{from_value, to_value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
@@ -28,8 +30,8 @@ pub mod async_ops;
 pub mod types;
 
 use types::{
-    js_object_to_hashmap, Edge, EdgeId, GraphError, Hyperedge, HyperedgeId, JsEdge, JsHyperedge,
-    JsNode, Node, NodeId, QueryResult,
+    js_object_to_properties, node_embedding, properties_to_json, GraphError, Hyperedge,
+    HyperedgeId, JsEdge, JsHyperedge, JsNode, QueryResult,
 };
 
 /// Initialize panic hook for better error messages
@@ -42,15 +44,10 @@ pub fn init() {
 /// Main GraphDB class for browser usage
 #[wasm_bindgen]
 pub struct GraphDB {
-    nodes: Arc<Mutex<HashMap<NodeId, Node>>>,
-    edges: Arc<Mutex<HashMap<EdgeId, Edge>>>,
+    /// Property graph with its label, property and adjacency indexes
+    graph: Arc<CoreGraphDB>,
     hypergraph: Arc<Mutex<HypergraphIndex>>,
     hyperedges: Arc<Mutex<HashMap<HyperedgeId, Hyperedge>>>,
-    // Index structures for efficient queries
-    labels_index: Arc<Mutex<HashMap<String, Vec<NodeId>>>>,
-    edge_types_index: Arc<Mutex<HashMap<String, Vec<EdgeId>>>>,
-    node_edges_out: Arc<Mutex<HashMap<NodeId, Vec<EdgeId>>>>,
-    node_edges_in: Arc<Mutex<HashMap<NodeId, Vec<EdgeId>>>>,
     distance_metric: DistanceMetric,
 }
 
@@ -72,19 +69,14 @@ impl GraphDB {
         };
 
         Ok(GraphDB {
-            nodes: Arc::new(Mutex::new(HashMap::new())),
-            edges: Arc::new(Mutex::new(HashMap::new())),
+            graph: Arc::new(CoreGraphDB::new()),
             hypergraph: Arc::new(Mutex::new(HypergraphIndex::new(distance_metric))),
             hyperedges: Arc::new(Mutex::new(HashMap::new())),
-            labels_index: Arc::new(Mutex::new(HashMap::new())),
-            edge_types_index: Arc::new(Mutex::new(HashMap::new())),
-            node_edges_out: Arc::new(Mutex::new(HashMap::new())),
-            node_edges_in: Arc::new(Mutex::new(HashMap::new())),
             distance_metric,
         })
     }
 
-    /// Execute a Cypher query (basic implementation)
+    /// Execute a Cypher query
     ///
     /// # Arguments
     /// * `cypher` - Cypher query string
@@ -95,8 +87,6 @@ impl GraphDB {
     pub async fn query(&self, cypher: String) -> Result<QueryResult, JsValue> {
         console::log_1(&format!("Executing Cypher: {}", cypher).into());
 
-        // Parse and execute basic Cypher queries
-        // This is a simplified implementation - a full Cypher parser would be more complex
         let result = self
             .execute_cypher(&cypher)
             .map_err(|e| JsValue::from(GraphError::from(e)))?;
@@ -115,41 +105,24 @@ impl GraphDB {
     #[wasm_bindgen(js_name = createNode)]
     pub fn create_node(&self, labels: Vec<String>, properties: JsValue) -> Result<String, JsValue> {
         let id = Uuid::new_v4().to_string();
-        let props = js_object_to_hashmap(properties).map_err(|e| JsValue::from_str(&e))?;
+        let props = js_object_to_properties(properties).map_err(|e| JsValue::from_str(&e))?;
 
-        // Extract embedding if present
-        let embedding = props
-            .get("embedding")
-            .and_then(|v| serde_json::from_value::<Vec<f32>>(v.clone()).ok());
+        let node = NodeBuilder::new()
+            .id(id.clone())
+            .labels(labels)
+            .properties(props)
+            .build();
+        let embedding = node_embedding(&node);
 
-        let node = Node {
-            id: id.clone(),
-            labels: labels.clone(),
-            properties: props,
-            embedding: embedding.clone(),
-        };
-
-        // Store node
-        self.nodes.lock().insert(id.clone(), node);
-
-        // Update label index
-        let mut labels_index = self.labels_index.lock();
-        for label in &labels {
-            labels_index
-                .entry(label.clone())
-                .or_insert_with(Vec::new)
-                .push(id.clone());
-        }
+        self.graph
+            .create_node(node)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
         // Add to hypergraph if embedding exists
         if let Some(emb) = embedding {
             self.hypergraph.lock().add_entity(id.clone(), emb);
         }
 
-        // Initialize edge lists
-        self.node_edges_out.lock().insert(id.clone(), Vec::new());
-        self.node_edges_in.lock().insert(id.clone(), Vec::new());
-
         Ok(id)
     }
 
@@ -172,47 +145,23 @@ impl GraphDB {
         properties: JsValue,
     ) -> Result<String, JsValue> {
         // Verify nodes exist
-        let nodes = self.nodes.lock();
-        if !nodes.contains_key(&from) {
-            return Err(JsValue::from_str(&format!("Node {} not found", from)));
-        }
-        if !nodes.contains_key(&to) {
-            return Err(JsValue::from_str(&format!("Node {} not found", to)));
+        for node_id in [&from, &to] {
+            if self.graph.get_node(node_id).is_none() {
+                return Err(JsValue::from_str(&format!("Node {} not found", node_id)));
+            }
         }
-        drop(nodes);
 
         let id = Uuid::new_v4().to_string();
-        let props = js_object_to_hashmap(properties).map_err(|e| JsValue::from_str(&e))?;
+        let props = js_object_to_properties(properties).map_err(|e| JsValue::from_str(&e))?;
 
-        let edge = Edge {
-            id: id.clone(),
-            from: from.clone(),
-            to: to.clone(),
-            edge_type: edge_type.clone(),
-            properties: props,
-        };
+        let edge = EdgeBuilder::new(from, to, edge_type)
+            .id(id.clone())
+            .properties(props)
+            .build();
 
-        // Store edge
-        self.edges.lock().insert(id.clone(), edge);
-
-        // Update indices
-        self.edge_types_index
-            .lock()
-            .entry(edge_type)
-            .or_insert_with(Vec::new)
-            .push(id.clone());
-
-        self.node_edges_out
-            .lock()
-            .entry(from)
-            .or_insert_with(Vec::new)
-            .push(id.clone());
-
-        self.node_edges_in
-            .lock()
-            .entry(to)
-            .or_insert_with(Vec::new)
-            .push(id.clone());
+        self.graph
+            .create_edge(edge)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
 
         Ok(id)
     }
@@ -236,13 +185,11 @@ impl GraphDB {
         confidence: Option<f32>,
     ) -> Result<String, JsValue> {
         // Verify all nodes exist
-        let nodes_map = self.nodes.lock();
         for node_id in &nodes {
-            if !nodes_map.contains_key(node_id) {
+            if self.graph.get_node(node_id).is_none() {
                 return Err(JsValue::from_str(&format!("Node {} not found", node_id)));
             }
         }
-        drop(nodes_map);
 
         let id = Uuid::new_v4().to_string();
 
@@ -295,13 +242,13 @@ impl GraphDB {
     /// JsNode or null if not found
     #[wasm_bindgen(js_name = getNode)]
     pub fn get_node(&self, id: String) -> Option<JsNode> {
-        self.nodes.lock().get(&id).map(|n| n.to_js())
+        self.graph.get_node(&id).as_ref().map(Into::into)
     }
 
     /// Get an edge by ID
     #[wasm_bindgen(js_name = getEdge)]
     pub fn get_edge(&self, id: String) -> Option<JsEdge> {
-        self.edges.lock().get(&id).map(|e| e.to_js())
+        self.graph.get_edge(&id).as_ref().map(Into::into)
     }
 
     /// Get a hyperedge by ID
@@ -319,47 +266,27 @@ impl GraphDB {
     /// True if deleted, false if not found
     #[wasm_bindgen(js_name = deleteNode)]
     pub fn delete_node(&self, id: String) -> bool {
-        // Remove from nodes
-        let removed = self.nodes.lock().remove(&id).is_some();
-
-        if removed {
-            // Clean up indices
-            let mut labels_index = self.labels_index.lock();
-            for (_, node_ids) in labels_index.iter_mut() {
-                node_ids.retain(|nid| nid != &id);
-            }
+        if self.graph.get_node(&id).is_none() {
+            return false;
+        }
 
-            // Remove associated edges
-            if let Some(out_edges) = self.node_edges_out.lock().remove(&id) {
-                for edge_id in out_edges {
-                    self.edges.lock().remove(&edge_id);
-                }
-            }
-            if let Some(in_edges) = self.node_edges_in.lock().remove(&id) {
-                for edge_id in in_edges {
-                    self.edges.lock().remove(&edge_id);
-                }
-            }
+        // Remove associated edges
+        let attached = self
+            .graph
+            .get_outgoing_edges(&id)
+            .into_iter()
+            .chain(self.graph.get_incoming_edges(&id));
+        for edge in attached {
+            let _ = self.graph.delete_edge(&edge.id);
         }
 
-        removed
+        self.graph.delete_node(&id).unwrap_or(false)
     }
 
     /// Delete an edge by ID
     #[wasm_bindgen(js_name = deleteEdge)]
     pub fn delete_edge(&self, id: String) -> bool {
-        if let Some(edge) = self.edges.lock().remove(&id) {
-            // Clean up indices
-            if let Some(edges) = self.node_edges_out.lock().get_mut(&edge.from) {
-                edges.retain(|eid| eid != &id);
-            }
-            if let Some(edges) = self.node_edges_in.lock().get_mut(&edge.to) {
-                edges.retain(|eid| eid != &id);
-            }
-            true
-        } else {
-            false
-        }
+        self.graph.delete_edge(&id).unwrap_or(false)
     }
 
     /// Import Cypher statements
@@ -389,19 +316,15 @@ impl GraphDB {
         let mut cypher = String::new();
 
         // Export nodes
-        for (id, node) in self.nodes.lock().iter() {
-            let labels = if node.labels.is_empty() {
-                String::new()
-            } else {
-                format!(":{}", node.labels.join(":"))
-            };
+        for node in self.graph.get_all_nodes() {
+            let labels: String = node.labels.iter().map(|l| format!(":{}", l.name)).collect();
 
             let props = if node.properties.is_empty() {
                 String::new()
             } else {
                 format!(
                     " {{{}}}",
-                    node.properties
+                    properties_to_json(&node.properties)
                         .iter()
                         .map(|(k, v)| format!("{}: {}", k, v))
                         .collect::<Vec<_>>()
@@ -413,13 +336,13 @@ impl GraphDB {
         }
 
         // Export edges
-        for (id, edge) in self.edges.lock().iter() {
+        for edge in self.graph.get_all_edges() {
             let props = if edge.properties.is_empty() {
                 String::new()
             } else {
                 format!(
                     " {{{}}}",
-                    edge.properties
+                    properties_to_json(&edge.properties)
                         .iter()
                         .map(|(k, v)| format!("{}: {}", k, v))
                         .collect::<Vec<_>>()
@@ -439,8 +362,8 @@ impl GraphDB {
     /// Get database statistics
     #[wasm_bindgen]
     pub fn stats(&self) -> JsValue {
-        let node_count = self.nodes.lock().len();
-        let edge_count = self.edges.lock().len();
+        let node_count = self.graph.node_count();
+        let edge_count = self.graph.edge_count();
         let hyperedge_count = self.hyperedges.lock().len();
         let hypergraph_stats = self.hypergraph.lock().stats();
 
@@ -479,47 +402,37 @@ impl GraphDB {
 // Internal helper methods
 impl GraphDB {
     fn execute_cypher(&self, cypher: &str) -> Result<QueryResult, String> {
-        let cypher = cypher.trim();
-
-        // Very basic Cypher parsing - in production, use a proper parser
-        if cypher.to_uppercase().starts_with("MATCH") {
-            self.execute_match_query(cypher)
-        } else if cypher.to_uppercase().starts_with("CREATE") {
-            self.execute_create_query(cypher)
-        } else {
-            Err(format!("Unsupported Cypher statement: {}", cypher))
+        let result = self
+            .graph
+            .execute(cypher, &Properties::new())
+            .map_err(|e| e.to_string())?;
+
+        // Collect the distinct nodes and relationships returned anywhere in the rows
+        let mut entities = EntityCollector::default();
+        let mut data = Vec::with_capacity(result.len());
+        for row in &result {
+            for value in row.values() {
+                entities.collect(value);
+            }
+            data.push(
+                row.iter()
+                    .map(|(column, value)| (column.to_string(), value.to_json()))
+                    .collect(),
+            );
         }
-    }
-
-    fn execute_match_query(&self, _cypher: &str) -> Result<QueryResult, String> {
-        // Simplified MATCH implementation
-        // In production, parse the pattern and execute accordingly
-
-        Ok(QueryResult {
-            nodes: Vec::new(),
-            edges: Vec::new(),
-            hyperedges: Vec::new(),
-            data: Vec::new(),
-        })
-    }
-
-    fn execute_create_query(&self, _cypher: &str) -> Result<QueryResult, String> {
-        // Simplified CREATE implementation
-        // Parse CREATE statement and create nodes/relationships
 
         Ok(QueryResult {
-            nodes: Vec::new(),
-            edges: Vec::new(),
+            nodes: entities.nodes,
+            edges: entities.edges,
             hyperedges: Vec::new(),
-            data: Vec::new(),
+            data,
         })
     }
 
     fn generate_hyperedge_embedding(&self, node_ids: &[String]) -> Result<Vec<f32>, JsValue> {
-        let nodes = self.nodes.lock();
         let embeddings: Vec<Vec<f32>> = node_ids
             .iter()
-            .filter_map(|id| nodes.get(id).and_then(|n| n.embedding.clone()))
+            .filter_map(|id| self.graph.get_node(id).and_then(|n| node_embedding(&n)))
             .collect();
 
         if embeddings.is_empty() {
@@ -543,6 +456,43 @@ impl GraphDB {
     }
 }
 
+/// Gathers the nodes and relationships contained in query values
+#[derive(Default)]
+struct EntityCollector {
+    seen_nodes: HashSet<String>,
+    seen_edges: HashSet<String>,
+    nodes: Vec<JsNode>,
+    edges: Vec<JsEdge>,
+}
+
+impl EntityCollector {
+    fn collect(&mut self, value: &Value) {
+        match value {
+            Value::Node(node) => self.add_node(node),
+            Value::Relationship(edge) => self.add_edge(edge),
+            Value::Path(path) => {
+                path.nodes.iter().for_each(|n| self.add_node(n));
+                path.relationships.iter().for_each(|e| self.add_edge(e));
+            }
+            Value::List(items) => items.iter().for_each(|v| self.collect(v)),
+            Value::Map(map) => map.values().for_each(|v| self.collect(v)),
+            _ => {}
+        }
+    }
+
+    fn add_node(&mut self, node: &ruvector_graph::Node) {
+        if self.seen_nodes.insert(node.id.clone()) {
+            self.nodes.push(node.into());
+        }
+    }
+
+    fn add_edge(&mut self, edge: &ruvector_graph::Edge) {
+        if self.seen_edges.insert(edge.id.clone()) {
+            self.edges.push(edge.into());
+        }
+    }
+}
+
 /// Get version information
 #[wasm_bindgen]
 pub fn version() -> String {
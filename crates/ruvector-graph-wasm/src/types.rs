@@ -1,6 +1,8 @@
 //! JavaScript-friendly type conversions for graph database
 
 use js_sys::{Array, Object, Reflect};
+use ruvector_graph::cypher::Value;
+use ruvector_graph::{Edge, Node, Properties, PropertyValue};
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::{from_value, to_value};
 use std::collections::HashMap;
@@ -231,44 +233,25 @@ impl From<String> for GraphError {
     }
 }
 
-/// Internal node representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct Node {
-    pub id: NodeId,
-    pub labels: Vec<String>,
-    pub properties: HashMap<String, serde_json::Value>,
-    pub embedding: Option<Vec<f32>>,
-}
-
-impl Node {
-    pub fn to_js(&self) -> JsNode {
+impl From<&Node> for JsNode {
+    fn from(node: &Node) -> Self {
         JsNode {
-            id: self.id.clone(),
-            labels: self.labels.clone(),
-            properties: self.properties.clone(),
-            embedding: self.embedding.clone(),
+            id: node.id.clone(),
+            labels: node.labels.iter().map(|l| l.name.clone()).collect(),
+            properties: properties_to_json(&node.properties),
+            embedding: node_embedding(node),
         }
     }
 }
 
-/// Internal edge representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct Edge {
-    pub id: EdgeId,
-    pub from: NodeId,
-    pub to: NodeId,
-    pub edge_type: String,
-    pub properties: HashMap<String, serde_json::Value>,
-}
-
-impl Edge {
-    pub fn to_js(&self) -> JsEdge {
+impl From<&Edge> for JsEdge {
+    fn from(edge: &Edge) -> Self {
         JsEdge {
-            id: self.id.clone(),
-            from: self.from.clone(),
-            to: self.to.clone(),
-            edge_type: self.edge_type.clone(),
-            properties: self.properties.clone(),
+            id: edge.id.clone(),
+            from: edge.from.clone(),
+            to: edge.to.clone(),
+            edge_type: edge.edge_type.clone(),
+            properties: properties_to_json(&edge.properties),
         }
     }
 }
@@ -297,6 +280,58 @@ impl Hyperedge {
     }
 }
 
+/// Embedding stored in a node's `embedding` property
+pub(crate) fn node_embedding(node: &Node) -> Option<Vec<f32>> {
+    match node.properties.get("embedding")? {
+        PropertyValue::Array(items) | PropertyValue::List(items) => items
+            .iter()
+            .map(|item| match item {
+                PropertyValue::Float(f) => Some(*f as f32),
+                PropertyValue::Integer(i) => Some(*i as f32),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Convert a JSON value into a graph property value
+pub(crate) fn json_to_property(value: serde_json::Value) -> PropertyValue {
+    match value {
+        serde_json::Value::Null => PropertyValue::Null,
+        serde_json::Value::Bool(b) => PropertyValue::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => PropertyValue::Integer(i),
+            None => PropertyValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => PropertyValue::String(s),
+        serde_json::Value::Array(items) => {
+            PropertyValue::List(items.into_iter().map(json_to_property).collect())
+        }
+        serde_json::Value::Object(map) => PropertyValue::Map(
+            map.into_iter()
+                .map(|(k, v)| (k, json_to_property(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert graph properties into JSON values
+pub(crate) fn properties_to_json(properties: &Properties) -> HashMap<String, serde_json::Value> {
+    properties
+        .iter()
+        .map(|(k, v)| (k.clone(), Value::from(v.clone()).to_json()))
+        .collect()
+}
+
+/// Convert JavaScript object to graph properties
+pub(crate) fn js_object_to_properties(obj: JsValue) -> Result<Properties, String> {
+    Ok(js_object_to_hashmap(obj)?
+        .into_iter()
+        .map(|(k, v)| (k, json_to_property(v)))
+        .collect())
+}
+
 /// Convert JavaScript object to HashMap
 pub(crate) fn js_object_to_hashmap(
     obj: JsValue,
@@ -0,0 +1,278 @@
+//! Centrality: PageRank, personalized PageRank, betweenness and closeness
+//!
+//! Every function returns one score per node, highest first.
+
+use super::projection::{Direction, GraphProjection};
+use crate::error::Result;
+use crate::types::NodeId;
+use std::collections::VecDeque;
+
+/// Options for [`pagerank`] and [`personalized_pagerank`]
+#[derive(Debug, Clone, Copy)]
+pub struct PageRankConfig {
+    /// Probability of following a relationship rather than teleporting
+    pub damping_factor: f64,
+    pub max_iterations: usize,
+    /// Stop once the scores change by less than this in total
+    pub tolerance: f64,
+}
+
+impl Default for PageRankConfig {
+    fn default() -> Self {
+        Self {
+            damping_factor: 0.85,
+            max_iterations: 20,
+            tolerance: 1e-7,
+        }
+    }
+}
+
+/// PageRank over outgoing relationships, weighted by relationship weight
+///
+/// Scores sum to one; the rank of nodes without outgoing relationships is
+/// spread over all nodes.
+pub fn pagerank(graph: &GraphProjection, config: &PageRankConfig) -> Vec<(NodeId, f64)> {
+    let n = graph.node_count();
+    let teleport = vec![1.0 / n as f64; n];
+    ranked(graph, power_iteration(graph, &teleport, config))
+}
+
+/// PageRank whose random surfer always restarts at one of `sources`
+///
+/// Scores measure proximity to the source nodes.
+pub fn personalized_pagerank(
+    graph: &GraphProjection,
+    sources: &[NodeId],
+    config: &PageRankConfig,
+) -> Result<Vec<(NodeId, f64)>> {
+    let n = graph.node_count();
+    let mut teleport = vec![0.0; n];
+    if sources.is_empty() {
+        teleport.fill(1.0 / n as f64);
+    }
+    for source in sources {
+        teleport[graph.require(source)?] += 1.0 / sources.len() as f64;
+    }
+    Ok(ranked(graph, power_iteration(graph, &teleport, config)))
+}
+
+fn power_iteration(graph: &GraphProjection, teleport: &[f64], config: &PageRankConfig) -> Vec<f64> {
+    let n = graph.node_count();
+    let out_weight: Vec<f64> = (0..n)
+        .map(|i| {
+            graph
+                .outgoing(i)
+                .iter()
+                .map(|a| graph.weight(a.edge).max(0.0))
+                .sum()
+        })
+        .collect();
+
+    let d = config.damping_factor;
+    let mut rank = teleport.to_vec();
+    for _ in 0..config.max_iterations {
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_weight[i] == 0.0)
+            .map(|i| rank[i])
+            .sum();
+        let mut next: Vec<f64> = teleport
+            .iter()
+            .map(|t| (1.0 - d) * t + d * dangling * t)
+            .collect();
+        for (i, &total) in out_weight.iter().enumerate() {
+            if total == 0.0 {
+                continue;
+            }
+            for a in graph.outgoing(i) {
+                next[a.node] += d * rank[i] * graph.weight(a.edge).max(0.0) / total;
+            }
+        }
+
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < config.tolerance {
+            break;
+        }
+    }
+    rank
+}
+
+/// Betweenness centrality (Brandes), counting shortest paths by hops
+///
+/// With `Direction::Both` every pair of nodes is counted once. Normalized
+/// scores are divided by the number of pairs a node could lie between.
+pub fn betweenness_centrality(
+    graph: &GraphProjection,
+    direction: Direction,
+    normalized: bool,
+) -> Vec<(NodeId, f64)> {
+    let n = graph.node_count();
+    let mut centrality = vec![0.0; n];
+
+    for source in 0..n {
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0f64; n];
+        let mut distance = vec![usize::MAX; n];
+        paths[source] = 1.0;
+        distance[source] = 0;
+
+        let mut queue = VecDeque::from([source]);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for a in graph.adjacent(v, direction) {
+                let w = a.node;
+                if distance[w] == usize::MAX {
+                    distance[w] = distance[v] + 1;
+                    queue.push_back(w);
+                }
+                if distance[w] == distance[v] + 1 {
+                    paths[w] += paths[v];
+                    predecessors[w].push(v);
+                }
+            }
+        }
+
+        let mut dependency = vec![0.0; n];
+        for &w in order.iter().rev() {
+            for &v in &predecessors[w] {
+                dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+            }
+            if w != source {
+                centrality[w] += dependency[w];
+            }
+        }
+    }
+
+    let undirected = direction == Direction::Both;
+    if undirected {
+        centrality.iter_mut().for_each(|c| *c /= 2.0);
+    }
+    if normalized && n > 2 {
+        let mut pairs = ((n - 1) * (n - 2)) as f64;
+        if undirected {
+            pairs /= 2.0;
+        }
+        centrality.iter_mut().for_each(|c| *c /= pairs);
+    }
+    ranked(graph, centrality)
+}
+
+/// Closeness centrality by hops, using the Wasserman-Faust formula
+///
+/// A node reaching `r` of the other `n - 1` nodes at total distance `d` scores
+/// `(r / d) * (r / (n - 1))`, so nodes in small components are not favored.
+pub fn closeness_centrality(graph: &GraphProjection, direction: Direction) -> Vec<(NodeId, f64)> {
+    let n = graph.node_count();
+    let scores = (0..n)
+        .map(|source| {
+            let mut distance = vec![usize::MAX; n];
+            distance[source] = 0;
+            let mut queue = VecDeque::from([source]);
+            let (mut reached, mut total) = (0usize, 0usize);
+            while let Some(v) = queue.pop_front() {
+                for a in graph.adjacent(v, direction) {
+                    if distance[a.node] == usize::MAX {
+                        distance[a.node] = distance[v] + 1;
+                        reached += 1;
+                        total += distance[a.node];
+                        queue.push_back(a.node);
+                    }
+                }
+            }
+            if total == 0 {
+                0.0
+            } else {
+                let r = reached as f64;
+                (r / total as f64) * (r / (n - 1) as f64)
+            }
+        })
+        .collect();
+    ranked(graph, scores)
+}
+
+/// Pair scores with node IDs, highest score first
+fn ranked(graph: &GraphProjection, scores: Vec<f64>) -> Vec<(NodeId, f64)> {
+    let mut ranked = graph.label(scores);
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn projection(nodes: &[&str], edges: &[(&str, &str)]) -> GraphProjection {
+        GraphProjection::from_edges(
+            nodes.iter().map(|n| n.to_string()),
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (f, t))| (format!("e{}", i), f.to_string(), t.to_string(), 1.0)),
+        )
+    }
+
+    fn scores(ranked: Vec<(NodeId, f64)>) -> HashMap<NodeId, f64> {
+        ranked.into_iter().collect()
+    }
+
+    #[test]
+    fn test_pagerank_favors_linked_nodes() {
+        // Everyone links to hub; hub links back to a
+        let graph = projection(
+            &["a", "b", "c", "hub"],
+            &[("a", "hub"), ("b", "hub"), ("c", "hub"), ("hub", "a")],
+        );
+        let config = PageRankConfig {
+            max_iterations: 100,
+            ..Default::default()
+        };
+        let ranked = pagerank(&graph, &config);
+        assert_eq!(ranked[0].0, "hub");
+        assert_eq!(ranked[1].0, "a");
+        let total: f64 = ranked.iter().map(|(_, s)| s).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_personalized_pagerank() {
+        // Two separate chains; restarting at x keeps all rank on x's side
+        let graph = projection(&["a", "b", "x", "y"], &[("a", "b"), ("x", "y")]);
+        let ranked =
+            personalized_pagerank(&graph, &["x".to_string()], &PageRankConfig::default()).unwrap();
+        let personalized = scores(ranked);
+        assert!(personalized["x"] > personalized["a"]);
+        assert!(personalized["y"] > personalized["b"]);
+        assert_eq!(personalized["a"], 0.0);
+
+        assert!(
+            personalized_pagerank(&graph, &["z".to_string()], &PageRankConfig::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_betweenness_of_path() {
+        // a - b - c - d
+        let graph = projection(&["a", "b", "c", "d"], &[("a", "b"), ("b", "c"), ("c", "d")]);
+        let raw = scores(betweenness_centrality(&graph, Direction::Both, false));
+        assert_eq!(raw["a"], 0.0);
+        assert_eq!(raw["b"], 2.0);
+        assert_eq!(raw["c"], 2.0);
+
+        let normalized = scores(betweenness_centrality(&graph, Direction::Both, true));
+        assert!((normalized["b"] - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_closeness_of_star() {
+        let graph = projection(
+            &["center", "a", "b", "c"],
+            &[("center", "a"), ("center", "b"), ("center", "c")],
+        );
+        let ranked = closeness_centrality(&graph, Direction::Both);
+        assert_eq!(ranked[0], ("center".to_string(), 1.0));
+        let leaf = ranked.iter().find(|(id, _)| id == "a").unwrap().1;
+        assert!((leaf - 3.0 / 5.0).abs() < 1e-9);
+    }
+}
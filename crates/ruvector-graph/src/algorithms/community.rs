@@ -0,0 +1,348 @@
+//! Community detection (Louvain, label propagation) and triangle counting
+//!
+//! Communities ignore relationship direction and use relationship weights.
+
+use super::projection::GraphProjection;
+use crate::types::NodeId;
+use std::collections::HashMap;
+
+/// Options for [`louvain`]
+#[derive(Debug, Clone, Copy)]
+pub struct LouvainConfig {
+    /// Maximum number of times communities are merged into super-nodes
+    pub max_levels: usize,
+    /// Maximum passes over the nodes within one level
+    pub max_iterations: usize,
+    /// Higher values favor more, smaller communities
+    pub resolution: f64,
+}
+
+impl Default for LouvainConfig {
+    fn default() -> Self {
+        Self {
+            max_levels: 10,
+            max_iterations: 10,
+            resolution: 1.0,
+        }
+    }
+}
+
+/// Community assignment of every node
+#[derive(Debug, Clone, PartialEq)]
+pub struct Communities {
+    /// Community of each node, numbered from zero in node order
+    pub membership: Vec<(NodeId, usize)>,
+    pub count: usize,
+    pub modularity: f64,
+}
+
+impl Communities {
+    fn new(graph: &GraphProjection, labels: &[usize], adjacency: &[HashMap<usize, f64>]) -> Self {
+        let (labels, count) = renumber(labels);
+        Self {
+            modularity: modularity(adjacency, &labels, 1.0),
+            membership: graph.label(labels),
+            count,
+        }
+    }
+}
+
+/// Louvain modularity optimization
+///
+/// Nodes repeatedly move to the neighboring community that increases
+/// modularity most; the resulting communities are then merged into single
+/// nodes and the process repeats on the smaller graph.
+pub fn louvain(graph: &GraphProjection, config: &LouvainConfig) -> Communities {
+    let original = graph.undirected_weights();
+    let mut membership: Vec<usize> = (0..graph.node_count()).collect();
+    let mut adjacency = original.clone();
+
+    for _ in 0..config.max_levels {
+        let Some(communities) = local_moves(&adjacency, config) else {
+            break;
+        };
+        let (communities, count) = renumber(&communities);
+        for community in membership.iter_mut() {
+            *community = communities[*community];
+        }
+
+        let mut aggregated = vec![HashMap::new(); count];
+        for (i, row) in adjacency.iter().enumerate() {
+            for (&j, &w) in row {
+                *aggregated[communities[i]]
+                    .entry(communities[j])
+                    .or_insert(0.0) += w;
+            }
+        }
+        adjacency = aggregated;
+    }
+
+    Communities::new(graph, &membership, &original)
+}
+
+/// One Louvain level; `None` if no node changed community
+fn local_moves(adjacency: &[HashMap<usize, f64>], config: &LouvainConfig) -> Option<Vec<usize>> {
+    let n = adjacency.len();
+    let degree: Vec<f64> = adjacency.iter().map(|row| row.values().sum()).collect();
+    let m2: f64 = degree.iter().sum();
+    if m2 == 0.0 {
+        return None;
+    }
+
+    let mut community: Vec<usize> = (0..n).collect();
+    let mut total = degree.clone();
+    let mut moved = false;
+
+    for _ in 0..config.max_iterations {
+        let mut changed = false;
+        for i in 0..n {
+            let current = community[i];
+            total[current] -= degree[i];
+
+            let mut links: HashMap<usize, f64> = HashMap::new();
+            for (&j, &w) in &adjacency[i] {
+                if j != i {
+                    *links.entry(community[j]).or_insert(0.0) += w;
+                }
+            }
+            let gain = |c: usize| {
+                links.get(&c).copied().unwrap_or(0.0)
+                    - config.resolution * total[c] * degree[i] / m2
+            };
+
+            let mut best = current;
+            let mut best_gain = gain(current);
+            let mut candidates: Vec<usize> = links.keys().copied().collect();
+            candidates.sort_unstable();
+            for c in candidates {
+                let g = gain(c);
+                if g > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            total[best] += degree[i];
+            if best != current {
+                community[i] = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        moved = true;
+    }
+
+    moved.then_some(community)
+}
+
+/// Label propagation: every node adopts the label most common among its
+/// neighbors (by relationship weight) until labels stop changing
+///
+/// Ties keep the current label if it is among the best, otherwise the
+/// smallest one wins, so results are deterministic.
+pub fn label_propagation(graph: &GraphProjection, max_iterations: usize) -> Communities {
+    let adjacency = graph.undirected_weights();
+    let mut labels: Vec<usize> = (0..graph.node_count()).collect();
+
+    for _ in 0..max_iterations {
+        let mut changed = false;
+        for i in 0..labels.len() {
+            let mut counts: HashMap<usize, f64> = HashMap::new();
+            for (&j, &w) in &adjacency[i] {
+                if j != i {
+                    *counts.entry(labels[j]).or_insert(0.0) += w;
+                }
+            }
+            let Some(max) = counts.values().copied().reduce(f64::max) else {
+                continue;
+            };
+            let best = |label: &usize| counts[label] >= max - 1e-12;
+            if counts.contains_key(&labels[i]) && best(&labels[i]) {
+                continue;
+            }
+            let label = *counts
+                .keys()
+                .filter(|l| best(l))
+                .min()
+                .expect("maximum exists");
+            if label != labels[i] {
+                labels[i] = label;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    Communities::new(graph, &labels, &adjacency)
+}
+
+/// Modularity of a partition of a symmetric weighted adjacency
+fn modularity(adjacency: &[HashMap<usize, f64>], community: &[usize], resolution: f64) -> f64 {
+    let m2: f64 = adjacency.iter().flat_map(|row| row.values()).sum();
+    if m2 == 0.0 {
+        return 0.0;
+    }
+    let mut internal: HashMap<usize, f64> = HashMap::new();
+    let mut total: HashMap<usize, f64> = HashMap::new();
+    for (i, row) in adjacency.iter().enumerate() {
+        for (&j, &w) in row {
+            *total.entry(community[i]).or_insert(0.0) += w;
+            if community[i] == community[j] {
+                *internal.entry(community[i]).or_insert(0.0) += w;
+            }
+        }
+    }
+    total
+        .iter()
+        .map(|(c, &t)| internal.get(c).copied().unwrap_or(0.0) / m2 - resolution * (t / m2).powi(2))
+        .sum()
+}
+
+/// Renumber labels from zero in order of first appearance
+fn renumber(labels: &[usize]) -> (Vec<usize>, usize) {
+    let mut ids = HashMap::new();
+    let renumbered = labels
+        .iter()
+        .map(|label| {
+            let next = ids.len();
+            *ids.entry(*label).or_insert(next)
+        })
+        .collect();
+    (renumbered, ids.len())
+}
+
+/// Triangles in the graph, ignoring relationship direction
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleCount {
+    pub total: usize,
+    /// Triangles each node is part of, in node order
+    pub per_node: Vec<(NodeId, usize)>,
+}
+
+/// Count triangles; parallel relationships and self-loops are ignored
+pub fn triangle_count(graph: &GraphProjection) -> TriangleCount {
+    let n = graph.node_count();
+    let neighbors: Vec<Vec<usize>> = (0..n).map(|i| graph.neighbors(i)).collect();
+    let mut per_node = vec![0; n];
+    let mut total = 0;
+
+    for u in 0..n {
+        for &v in neighbors[u].iter().filter(|&&v| v > u) {
+            for &w in neighbors[v].iter().filter(|&&w| w > v) {
+                if neighbors[u].binary_search(&w).is_ok() {
+                    total += 1;
+                    per_node[u] += 1;
+                    per_node[v] += 1;
+                    per_node[w] += 1;
+                }
+            }
+        }
+    }
+
+    TriangleCount {
+        total,
+        per_node: graph.label(per_node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projection(nodes: &[&str], edges: &[(&str, &str)]) -> GraphProjection {
+        GraphProjection::from_edges(
+            nodes.iter().map(|n| n.to_string()),
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (f, t))| (format!("e{}", i), f.to_string(), t.to_string(), 1.0)),
+        )
+    }
+
+    /// Two triangles joined by a single relationship c - d
+    fn two_cliques() -> GraphProjection {
+        projection(
+            &["a", "b", "c", "d", "e", "f"],
+            &[
+                ("a", "b"),
+                ("b", "c"),
+                ("c", "a"),
+                ("d", "e"),
+                ("e", "f"),
+                ("f", "d"),
+                ("c", "d"),
+            ],
+        )
+    }
+
+    fn community_of(communities: &Communities, node: &str) -> usize {
+        communities
+            .membership
+            .iter()
+            .find(|(id, _)| id == node)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_louvain_two_cliques() {
+        let communities = louvain(&two_cliques(), &LouvainConfig::default());
+        assert_eq!(communities.count, 2);
+        assert_eq!(
+            community_of(&communities, "a"),
+            community_of(&communities, "c")
+        );
+        assert_eq!(
+            community_of(&communities, "d"),
+            community_of(&communities, "f")
+        );
+        assert_ne!(
+            community_of(&communities, "a"),
+            community_of(&communities, "d")
+        );
+        // 2 * (6/14 - (7/14)^2)
+        assert!((communities.modularity - 5.0 / 14.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let graph = projection(
+            &["a", "b", "c", "x", "y", "z"],
+            &[("a", "b"), ("b", "c"), ("c", "a"), ("x", "y"), ("y", "z")],
+        );
+        let communities = label_propagation(&graph, 10);
+        assert_eq!(communities.count, 2);
+        assert_eq!(community_of(&communities, "a"), 0);
+        assert_eq!(community_of(&communities, "c"), 0);
+        assert_eq!(community_of(&communities, "z"), 1);
+    }
+
+    #[test]
+    fn test_triangle_count() {
+        let count = triangle_count(&two_cliques());
+        assert_eq!(count.total, 2);
+        let per_node: HashMap<_, _> = count.per_node.into_iter().collect();
+        assert_eq!(per_node["a"], 1);
+        assert_eq!(per_node["d"], 1);
+
+        // A 4-clique has four triangles, each node in three
+        let k4 = projection(
+            &["a", "b", "c", "d"],
+            &[
+                ("a", "b"),
+                ("a", "c"),
+                ("a", "d"),
+                ("b", "c"),
+                ("b", "d"),
+                ("c", "d"),
+            ],
+        );
+        let count = triangle_count(&k4);
+        assert_eq!(count.total, 4);
+        assert!(count.per_node.iter().all(|(_, t)| *t == 3));
+    }
+}
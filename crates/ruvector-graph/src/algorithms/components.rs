@@ -0,0 +1,151 @@
+//! Weakly and strongly connected components
+//!
+//! Components are returned largest first, each with its node IDs sorted.
+
+use super::projection::{Direction, GraphProjection};
+use crate::types::NodeId;
+
+/// Components of the graph with relationship direction ignored
+pub fn connected_components(graph: &GraphProjection) -> Vec<Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut component = vec![usize::MAX; n];
+    let mut count = 0;
+
+    for start in 0..n {
+        if component[start] != usize::MAX {
+            continue;
+        }
+        component[start] = count;
+        let mut stack = vec![start];
+        while let Some(v) = stack.pop() {
+            for a in graph.adjacent(v, Direction::Both) {
+                if component[a.node] == usize::MAX {
+                    component[a.node] = count;
+                    stack.push(a.node);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    group(graph, &component, count)
+}
+
+/// Strongly connected components (Tarjan's algorithm)
+///
+/// Within a strongly connected component every node can reach every other
+/// node along outgoing relationships.
+pub fn strongly_connected_components(graph: &GraphProjection) -> Vec<Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut index = vec![usize::MAX; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut component = vec![usize::MAX; n];
+    let mut next_index = 0;
+    let mut count = 0;
+
+    for root in 0..n {
+        if index[root] != usize::MAX {
+            continue;
+        }
+        // Explicit call stack of (node, position in its adjacency list)
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (v, ref mut position)) = calls.last_mut() {
+            if let Some(a) = graph.outgoing(v).get(*position) {
+                *position += 1;
+                let w = a.node;
+                if index[w] == usize::MAX {
+                    index[w] = next_index;
+                    low_link[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low_link[v] = low_link[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low_link[parent] = low_link[parent].min(low_link[v]);
+            }
+            if low_link[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = count;
+                    if w == v {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+
+    group(graph, &component, count)
+}
+
+fn group(graph: &GraphProjection, component: &[usize], count: usize) -> Vec<Vec<NodeId>> {
+    let mut groups = vec![Vec::new(); count];
+    for (node, &c) in component.iter().enumerate() {
+        groups[c].push(graph.node_id(node).clone());
+    }
+    // Node IDs are visited in order, so each group is already sorted
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projection(nodes: &[&str], edges: &[(&str, &str)]) -> GraphProjection {
+        GraphProjection::from_edges(
+            nodes.iter().map(|n| n.to_string()),
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (f, t))| (format!("e{}", i), f.to_string(), t.to_string(), 1.0)),
+        )
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let graph = projection(
+            &["a", "b", "c", "d", "e"],
+            &[("a", "b"), ("c", "b"), ("d", "e")],
+        );
+        let components = connected_components(&graph);
+        assert_eq!(components, vec![vec!["a", "b", "c"], vec!["d", "e"]]);
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // Cycle a -> b -> c -> a, then c -> d -> e -> d
+        let graph = projection(
+            &["a", "b", "c", "d", "e", "f"],
+            &[
+                ("a", "b"),
+                ("b", "c"),
+                ("c", "a"),
+                ("c", "d"),
+                ("d", "e"),
+                ("e", "d"),
+            ],
+        );
+        let components = strongly_connected_components(&graph);
+        assert_eq!(
+            components,
+            vec![vec!["a", "b", "c"], vec!["d", "e"], vec!["f"]]
+        );
+    }
+}
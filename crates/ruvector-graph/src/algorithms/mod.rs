@@ -0,0 +1,33 @@
+//! Graph algorithms over projections of a [`GraphDB`](crate::GraphDB)
+//!
+//! Algorithms run on a [`GraphProjection`], a snapshot of the selected nodes
+//! and relationships read through the adjacency index:
+//! - Traversal: breadth-first and depth-first search with depth limits
+//! - Weighted shortest paths: Dijkstra, A* and k-shortest paths (Yen)
+//! - Centrality: PageRank, personalized PageRank, betweenness and closeness
+//! - Components: weakly and strongly connected components
+//! - Communities: Louvain, label propagation and triangle counting
+//! - Similarity: Jaccard and Adamic-Adar over node neighborhoods
+//!
+//! The same algorithms are callable from Cypher as `algo.*` procedures, e.g.
+//! `CALL algo.pagerank() YIELD node, score`.
+
+pub mod centrality;
+pub mod community;
+pub mod components;
+pub mod paths;
+pub mod projection;
+pub mod similarity;
+pub mod traversal;
+
+pub use centrality::{
+    betweenness_centrality, closeness_centrality, pagerank, personalized_pagerank, PageRankConfig,
+};
+pub use community::{
+    label_propagation, louvain, triangle_count, Communities, LouvainConfig, TriangleCount,
+};
+pub use components::{connected_components, strongly_connected_components};
+pub use paths::{astar, dijkstra, k_shortest_paths, WeightedPath};
+pub use projection::{Adjacent, Direction, GraphProjection, ProjectionConfig};
+pub use similarity::{adamic_adar, jaccard, most_similar, SimilarityMetric};
+pub use traversal::{bfs, dfs, TraversalConfig, Visit};
@@ -0,0 +1,347 @@
+//! Weighted shortest paths: Dijkstra, A* and Yen's k-shortest paths
+//!
+//! Relationship weights come from the projection and must be non-negative.
+
+use super::projection::{Direction, GraphProjection};
+use crate::error::{GraphError, Result};
+use crate::types::{EdgeId, NodeId};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+/// A path with the sum of its relationship weights
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedPath {
+    pub nodes: Vec<NodeId>,
+    /// Relationships between consecutive nodes
+    pub relationships: Vec<EdgeId>,
+    pub cost: f64,
+}
+
+/// Cheapest path from `source` to `target`, or `None` if there is none
+pub fn dijkstra(
+    graph: &GraphProjection,
+    source: &str,
+    target: &str,
+    direction: Direction,
+) -> Result<Option<WeightedPath>> {
+    astar(graph, source, target, direction, |_| 0.0)
+}
+
+/// Cheapest path from `source` to `target` guided by a heuristic
+///
+/// `heuristic` estimates the remaining cost from a node to the target; the
+/// result is optimal as long as it never overestimates.
+pub fn astar(
+    graph: &GraphProjection,
+    source: &str,
+    target: &str,
+    direction: Direction,
+    heuristic: impl Fn(&NodeId) -> f64,
+) -> Result<Option<WeightedPath>> {
+    check_weights(graph)?;
+    let source = graph.require(source)?;
+    let target = graph.require(target)?;
+    let heuristic = |node: usize| heuristic(graph.node_id(node));
+
+    let search = Search {
+        graph,
+        direction,
+        banned_nodes: vec![false; graph.node_count()],
+        banned_edges: HashSet::new(),
+    };
+    Ok(search
+        .run(source, target, &heuristic)
+        .map(|route| route.to_path(graph)))
+}
+
+/// Up to `k` cheapest loopless paths from `source` to `target`, cheapest first
+pub fn k_shortest_paths(
+    graph: &GraphProjection,
+    source: &str,
+    target: &str,
+    k: usize,
+    direction: Direction,
+) -> Result<Vec<WeightedPath>> {
+    check_weights(graph)?;
+    let source = graph.require(source)?;
+    let target = graph.require(target)?;
+    if k == 0 {
+        return Ok(Vec::new());
+    }
+    let no_heuristic = |_: usize| 0.0;
+
+    let mut search = Search {
+        graph,
+        direction,
+        banned_nodes: vec![false; graph.node_count()],
+        banned_edges: HashSet::new(),
+    };
+    let Some(first) = search.run(source, target, &no_heuristic) else {
+        return Ok(Vec::new());
+    };
+
+    // Yen's algorithm: every further path deviates from an accepted one at a
+    // spur node, after sharing its root
+    let mut accepted = vec![first];
+    let mut candidates: Vec<Route> = Vec::new();
+    while accepted.len() < k {
+        let previous = accepted.last().expect("at least one path").clone();
+        for i in 0..previous.nodes.len() - 1 {
+            let spur = previous.nodes[i];
+            let root_edges = &previous.edges[..i];
+
+            search.banned_edges = accepted
+                .iter()
+                .filter(|p| p.edges.len() > i && p.edges[..i] == *root_edges)
+                .map(|p| p.edges[i])
+                .collect();
+            search.banned_nodes.fill(false);
+            for &node in &previous.nodes[..i] {
+                search.banned_nodes[node] = true;
+            }
+
+            let Some(spur_route) = search.run(spur, target, &no_heuristic) else {
+                continue;
+            };
+            let mut nodes = previous.nodes[..i].to_vec();
+            nodes.extend(spur_route.nodes);
+            let mut edges = root_edges.to_vec();
+            edges.extend(spur_route.edges);
+            let cost = edges.iter().map(|&e| graph.weight(e)).sum();
+            let route = Route { nodes, edges, cost };
+
+            if !accepted
+                .iter()
+                .chain(&candidates)
+                .any(|r| r.edges == route.edges)
+            {
+                candidates.push(route);
+            }
+        }
+
+        let Some(best) = (0..candidates.len()).min_by(|&a, &b| {
+            let (a, b) = (&candidates[a], &candidates[b]);
+            a.cost
+                .total_cmp(&b.cost)
+                .then(a.edges.len().cmp(&b.edges.len()))
+        }) else {
+            break;
+        };
+        accepted.push(candidates.swap_remove(best));
+    }
+
+    Ok(accepted.into_iter().map(|r| r.to_path(graph)).collect())
+}
+
+fn check_weights(graph: &GraphProjection) -> Result<()> {
+    let invalid = |e: &usize| graph.weight(*e).is_nan() || graph.weight(*e) < 0.0;
+    match (0..graph.relationship_count()).find(invalid) {
+        Some(edge) => Err(GraphError::InvalidInput(format!(
+            "Relationship {} has weight {}; path algorithms need non-negative weights",
+            graph.edge_id(edge),
+            graph.weight(edge)
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Path as projection indexes
+#[derive(Debug, Clone)]
+struct Route {
+    nodes: Vec<usize>,
+    edges: Vec<usize>,
+    cost: f64,
+}
+
+impl Route {
+    fn to_path(&self, graph: &GraphProjection) -> WeightedPath {
+        WeightedPath {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|&n| graph.node_id(n).clone())
+                .collect(),
+            relationships: self
+                .edges
+                .iter()
+                .map(|&e| graph.edge_id(e).clone())
+                .collect(),
+            cost: self.cost,
+        }
+    }
+}
+
+/// Best-first search that can exclude nodes and relationships
+struct Search<'g> {
+    graph: &'g GraphProjection,
+    direction: Direction,
+    banned_nodes: Vec<bool>,
+    banned_edges: HashSet<usize>,
+}
+
+impl Search<'_> {
+    fn run(&self, source: usize, target: usize, heuristic: &dyn Fn(usize) -> f64) -> Option<Route> {
+        let n = self.graph.node_count();
+        let mut cost = vec![f64::INFINITY; n];
+        let mut via: Vec<Option<(usize, usize)>> = vec![None; n];
+        let mut heap = BinaryHeap::new();
+
+        cost[source] = 0.0;
+        heap.push(Entry {
+            priority: heuristic(source),
+            cost: 0.0,
+            node: source,
+        });
+
+        while let Some(Entry { cost: c, node, .. }) = heap.pop() {
+            if node == target {
+                break;
+            }
+            if c > cost[node] {
+                continue;
+            }
+            for adjacent in self.graph.adjacent(node, self.direction) {
+                if self.banned_nodes[adjacent.node] || self.banned_edges.contains(&adjacent.edge) {
+                    continue;
+                }
+                let next = c + self.graph.weight(adjacent.edge);
+                if next < cost[adjacent.node] {
+                    cost[adjacent.node] = next;
+                    via[adjacent.node] = Some((node, adjacent.edge));
+                    heap.push(Entry {
+                        priority: next + heuristic(adjacent.node),
+                        cost: next,
+                        node: adjacent.node,
+                    });
+                }
+            }
+        }
+
+        if cost[target].is_infinite() {
+            return None;
+        }
+        let mut nodes = vec![target];
+        let mut edges = Vec::new();
+        let mut current = target;
+        while let Some((previous, edge)) = via[current] {
+            nodes.push(previous);
+            edges.push(edge);
+            current = previous;
+        }
+        nodes.reverse();
+        edges.reverse();
+        Some(Route {
+            nodes,
+            edges,
+            cost: cost[target],
+        })
+    }
+}
+
+/// Heap entry ordered so that the lowest priority pops first
+struct Entry {
+    priority: f64,
+    cost: f64,
+    node: usize,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a -1-> b -1-> d, a -4-> c -1-> d, b -5-> c, a -10-> d
+    fn diamond() -> GraphProjection {
+        let edges = [
+            ("ab", "a", "b", 1.0),
+            ("bd", "b", "d", 1.0),
+            ("ac", "a", "c", 4.0),
+            ("cd", "c", "d", 1.0),
+            ("bc", "b", "c", 5.0),
+            ("ad", "a", "d", 10.0),
+        ];
+        GraphProjection::from_edges(
+            ["a", "b", "c", "d"].map(String::from),
+            edges
+                .iter()
+                .map(|(id, f, t, w)| (id.to_string(), f.to_string(), t.to_string(), *w)),
+        )
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let graph = diamond();
+        let path = dijkstra(&graph, "a", "d", Direction::Outgoing)
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.nodes, vec!["a", "b", "d"]);
+        assert_eq!(path.relationships, vec!["ab", "bd"]);
+        assert_eq!(path.cost, 2.0);
+
+        assert!(dijkstra(&graph, "d", "a", Direction::Outgoing)
+            .unwrap()
+            .is_none());
+        let back = dijkstra(&graph, "d", "a", Direction::Both)
+            .unwrap()
+            .unwrap();
+        assert_eq!(back.cost, 2.0);
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        let graph = diamond();
+        // Admissible heuristic: hops to d
+        let hops = |id: &NodeId| if id == "d" { 0.0 } else { 1.0 };
+        let path = astar(&graph, "a", "d", Direction::Outgoing, hops)
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.nodes, vec!["a", "b", "d"]);
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let graph = diamond();
+        let paths = k_shortest_paths(&graph, "a", "d", 10, Direction::Outgoing).unwrap();
+        let costs: Vec<f64> = paths.iter().map(|p| p.cost).collect();
+        assert_eq!(costs, vec![2.0, 5.0, 7.0, 10.0]);
+        assert_eq!(paths[1].nodes, vec!["a", "c", "d"]);
+        assert_eq!(paths[2].nodes, vec!["a", "b", "c", "d"]);
+
+        let two = k_shortest_paths(&graph, "a", "d", 2, Direction::Outgoing).unwrap();
+        assert_eq!(two.len(), 2);
+    }
+
+    #[test]
+    fn test_negative_weights_rejected() {
+        let graph = GraphProjection::from_edges(
+            ["a", "b"].map(String::from),
+            [("ab".to_string(), "a".to_string(), "b".to_string(), -1.0)],
+        );
+        assert!(matches!(
+            dijkstra(&graph, "a", "b", Direction::Outgoing),
+            Err(GraphError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+//! Compact graph snapshots that algorithms run on
+//!
+//! A [`GraphProjection`] numbers the selected nodes densely and stores their
+//! relationships, read through the adjacency index, as adjacency lists with
+//! one weight per relationship.
+
+use crate::error::{GraphError, Result};
+use crate::graph::GraphDB;
+use crate::types::{EdgeId, NodeId, PropertyValue};
+use std::collections::HashMap;
+
+/// Which relationships to follow from a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    Outgoing,
+    Incoming,
+    /// Follow relationships in both directions, ignoring their orientation
+    Both,
+}
+
+/// Selects the part of the graph an algorithm sees
+#[derive(Debug, Clone)]
+pub struct ProjectionConfig {
+    /// Only include nodes with one of these labels (all nodes if empty)
+    pub labels: Vec<String>,
+    /// Only include relationships of these types (all types if empty)
+    pub relationship_types: Vec<String>,
+    /// Numeric relationship property used as weight
+    pub weight_property: Option<String>,
+    /// Weight of relationships without a numeric weight property
+    pub default_weight: f64,
+}
+
+impl Default for ProjectionConfig {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            relationship_types: Vec::new(),
+            weight_property: None,
+            default_weight: 1.0,
+        }
+    }
+}
+
+/// Relationship of a projection: the node it leads to and its index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adjacent {
+    pub node: usize,
+    pub edge: usize,
+}
+
+/// Immutable snapshot of (part of) a graph with dense node indexes
+///
+/// Nodes are ordered by ID and adjacency lists by relationship ID, so
+/// algorithms over a projection are deterministic.
+#[derive(Debug, Clone, Default)]
+pub struct GraphProjection {
+    ids: Vec<NodeId>,
+    index: HashMap<NodeId, usize>,
+    edge_ids: Vec<EdgeId>,
+    weights: Vec<f64>,
+    outgoing: Vec<Vec<Adjacent>>,
+    incoming: Vec<Vec<Adjacent>>,
+}
+
+impl GraphProjection {
+    /// Project the nodes and relationships of `db` selected by `config`
+    pub fn new(db: &GraphDB, config: &ProjectionConfig) -> Self {
+        let mut ids: Vec<NodeId> = if config.labels.is_empty() {
+            db.get_all_nodes().into_iter().map(|n| n.id).collect()
+        } else {
+            config
+                .labels
+                .iter()
+                .flat_map(|label| db.get_nodes_by_label(label))
+                .map(|n| n.id)
+                .collect()
+        };
+        ids.sort();
+        ids.dedup();
+
+        let index: HashMap<NodeId, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+
+        let mut projection = Self {
+            outgoing: vec![Vec::new(); ids.len()],
+            incoming: vec![Vec::new(); ids.len()],
+            ids,
+            index,
+            edge_ids: Vec::new(),
+            weights: Vec::new(),
+        };
+
+        for from in 0..projection.ids.len() {
+            let mut edges = db.get_outgoing_edges(&projection.ids[from]);
+            edges.sort_by(|a, b| a.id.cmp(&b.id));
+            for edge in edges {
+                if !config.relationship_types.is_empty()
+                    && !config.relationship_types.contains(&edge.edge_type)
+                {
+                    continue;
+                }
+                let Some(&to) = projection.index.get(&edge.to) else {
+                    continue;
+                };
+                let weight = config
+                    .weight_property
+                    .as_ref()
+                    .and_then(|key| match edge.properties.get(key) {
+                        Some(PropertyValue::Integer(i)) => Some(*i as f64),
+                        Some(PropertyValue::Float(f)) => Some(*f),
+                        _ => None,
+                    })
+                    .unwrap_or(config.default_weight);
+                projection.add_edge(from, to, edge.id, weight);
+            }
+        }
+
+        projection
+    }
+
+    /// Build a projection from explicit node IDs and weighted relationships
+    pub fn from_edges(
+        nodes: impl IntoIterator<Item = NodeId>,
+        edges: impl IntoIterator<Item = (EdgeId, NodeId, NodeId, f64)>,
+    ) -> Self {
+        let mut ids: Vec<NodeId> = nodes.into_iter().collect();
+        ids.sort();
+        ids.dedup();
+        let index = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+
+        let mut projection = Self {
+            outgoing: vec![Vec::new(); ids.len()],
+            incoming: vec![Vec::new(); ids.len()],
+            ids,
+            index,
+            edge_ids: Vec::new(),
+            weights: Vec::new(),
+        };
+        for (id, from, to, weight) in edges {
+            if let (Some(&from), Some(&to)) =
+                (projection.index.get(&from), projection.index.get(&to))
+            {
+                projection.add_edge(from, to, id, weight);
+            }
+        }
+        projection
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, id: EdgeId, weight: f64) {
+        let edge = self.edge_ids.len();
+        self.edge_ids.push(id);
+        self.weights.push(weight);
+        self.outgoing[from].push(Adjacent { node: to, edge });
+        self.incoming[to].push(Adjacent { node: from, edge });
+    }
+
+    /// Number of projected nodes
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Number of projected relationships
+    pub fn relationship_count(&self) -> usize {
+        self.edge_ids.len()
+    }
+
+    /// ID of the node at an index
+    pub fn node_id(&self, node: usize) -> &NodeId {
+        &self.ids[node]
+    }
+
+    /// Index of a node, if it is part of the projection
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    /// Index of a node, or `NodeNotFound` if it is not part of the projection
+    pub fn require(&self, id: &str) -> Result<usize> {
+        self.index_of(id)
+            .ok_or_else(|| GraphError::NodeNotFound(id.to_string()))
+    }
+
+    /// ID of the relationship at an index
+    pub fn edge_id(&self, edge: usize) -> &EdgeId {
+        &self.edge_ids[edge]
+    }
+
+    /// Weight of the relationship at an index
+    pub fn weight(&self, edge: usize) -> f64 {
+        self.weights[edge]
+    }
+
+    /// Relationships leaving a node
+    pub fn outgoing(&self, node: usize) -> &[Adjacent] {
+        &self.outgoing[node]
+    }
+
+    /// Relationships entering a node
+    pub fn incoming(&self, node: usize) -> &[Adjacent] {
+        &self.incoming[node]
+    }
+
+    /// Relationships of a node in the given direction
+    pub fn adjacent(
+        &self,
+        node: usize,
+        direction: Direction,
+    ) -> impl Iterator<Item = Adjacent> + '_ {
+        let (outgoing, incoming): (&[Adjacent], &[Adjacent]) = match direction {
+            Direction::Outgoing => (&self.outgoing[node], &[]),
+            Direction::Incoming => (&[], &self.incoming[node]),
+            Direction::Both => (&self.outgoing[node], &self.incoming[node]),
+        };
+        outgoing.iter().chain(incoming).copied()
+    }
+
+    /// Distinct neighbors of a node ignoring direction, without the node itself
+    pub fn neighbors(&self, node: usize) -> Vec<usize> {
+        let mut neighbors: Vec<usize> = self
+            .adjacent(node, Direction::Both)
+            .map(|a| a.node)
+            .filter(|&n| n != node)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Symmetric weighted adjacency: the summed weight between each pair of nodes
+    ///
+    /// Self-loops count twice, so each node's row sums to its weighted degree.
+    pub fn undirected_weights(&self) -> Vec<HashMap<usize, f64>> {
+        let mut adjacency = vec![HashMap::new(); self.node_count()];
+        for (from, edges) in self.outgoing.iter().enumerate() {
+            for a in edges {
+                let weight = self.weights[a.edge];
+                *adjacency[from].entry(a.node).or_insert(0.0) += weight;
+                *adjacency[a.node].entry(from).or_insert(0.0) += weight;
+            }
+        }
+        adjacency
+    }
+
+    /// Pair node IDs with per-node values
+    pub fn label<T>(&self, values: impl IntoIterator<Item = T>) -> Vec<(NodeId, T)> {
+        self.ids.iter().cloned().zip(values).collect()
+    }
+}
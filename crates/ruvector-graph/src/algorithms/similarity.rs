@@ -0,0 +1,162 @@
+//! Node similarity from shared neighbors: Jaccard and Adamic-Adar
+//!
+//! Neighborhoods ignore relationship direction, parallel relationships and
+//! self-loops.
+
+use super::projection::GraphProjection;
+use crate::error::Result;
+use crate::types::NodeId;
+
+/// Neighborhood similarity measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimilarityMetric {
+    /// Shared neighbors divided by all neighbors of either node
+    #[default]
+    Jaccard,
+    /// Shared neighbors weighted by `1 / ln(degree)`, favoring rare ones
+    AdamicAdar,
+}
+
+/// Jaccard similarity of the neighborhoods of `a` and `b`, between 0 and 1
+pub fn jaccard(graph: &GraphProjection, a: &str, b: &str) -> Result<f64> {
+    let (a, b) = (graph.require(a)?, graph.require(b)?);
+    Ok(score(
+        graph,
+        SimilarityMetric::Jaccard,
+        a,
+        &graph.neighbors(a),
+        b,
+    ))
+}
+
+/// Adamic-Adar index of `a` and `b`
+pub fn adamic_adar(graph: &GraphProjection, a: &str, b: &str) -> Result<f64> {
+    let (a, b) = (graph.require(a)?, graph.require(b)?);
+    Ok(score(
+        graph,
+        SimilarityMetric::AdamicAdar,
+        a,
+        &graph.neighbors(a),
+        b,
+    ))
+}
+
+/// The `top_k` nodes most similar to `node`, most similar first
+///
+/// Nodes with no shared neighbors are left out.
+pub fn most_similar(
+    graph: &GraphProjection,
+    node: &str,
+    metric: SimilarityMetric,
+    top_k: usize,
+) -> Result<Vec<(NodeId, f64)>> {
+    let a = graph.require(node)?;
+    let neighbors = graph.neighbors(a);
+
+    // Only nodes two hops away can share a neighbor
+    let mut candidates: Vec<usize> = neighbors.iter().flat_map(|&n| graph.neighbors(n)).collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let mut scored: Vec<(NodeId, f64)> = candidates
+        .into_iter()
+        .filter(|&b| b != a)
+        .map(|b| {
+            (
+                graph.node_id(b).clone(),
+                score(graph, metric, a, &neighbors, b),
+            )
+        })
+        .filter(|(_, s)| *s > 0.0)
+        .collect();
+    scored.sort_by(|x, y| y.1.total_cmp(&x.1).then_with(|| x.0.cmp(&y.0)));
+    scored.truncate(top_k);
+    Ok(scored)
+}
+
+fn score(
+    graph: &GraphProjection,
+    metric: SimilarityMetric,
+    a: usize,
+    a_neighbors: &[usize],
+    b: usize,
+) -> f64 {
+    let b_neighbors = if a == b {
+        a_neighbors.to_vec()
+    } else {
+        graph.neighbors(b)
+    };
+    let shared = a_neighbors
+        .iter()
+        .filter(|n| b_neighbors.binary_search(n).is_ok());
+
+    match metric {
+        SimilarityMetric::Jaccard => {
+            let shared = shared.count();
+            let union = a_neighbors.len() + b_neighbors.len() - shared;
+            if union == 0 {
+                0.0
+            } else {
+                shared as f64 / union as f64
+            }
+        }
+        SimilarityMetric::AdamicAdar => shared
+            .map(|&z| graph.neighbors(z).len())
+            .filter(|&degree| degree > 1)
+            .map(|degree| 1.0 / (degree as f64).ln())
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a and b share c and d; d is also linked to e
+    fn graph() -> GraphProjection {
+        let edges = [
+            ("a", "c"),
+            ("a", "d"),
+            ("b", "c"),
+            ("b", "d"),
+            ("b", "e"),
+            ("d", "e"),
+        ];
+        GraphProjection::from_edges(
+            ["a", "b", "c", "d", "e"].map(String::from),
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (f, t))| (i.to_string(), f.to_string(), t.to_string(), 1.0)),
+        )
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let graph = graph();
+        // {c, d} vs {c, d, e}
+        assert!((jaccard(&graph, "a", "b").unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        // {c, d} vs {b, d}
+        assert!((jaccard(&graph, "a", "e").unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert!(jaccard(&graph, "a", "missing").is_err());
+    }
+
+    #[test]
+    fn test_adamic_adar() {
+        let graph = graph();
+        // c has degree 2, d has degree 3
+        let expected = 1.0 / 2f64.ln() + 1.0 / 3f64.ln();
+        assert!((adamic_adar(&graph, "a", "b").unwrap() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_most_similar() {
+        let graph = graph();
+        let similar = most_similar(&graph, "a", SimilarityMetric::Jaccard, 1).unwrap();
+        assert_eq!(similar, vec![("b".to_string(), 2.0 / 3.0)]);
+
+        let all = most_similar(&graph, "a", SimilarityMetric::Jaccard, 10).unwrap();
+        assert!(all.iter().all(|(id, _)| id != "a"));
+        assert_eq!(all.len(), 2);
+    }
+}
@@ -0,0 +1,146 @@
+//! Breadth-first and depth-first traversal with depth limits
+
+use super::projection::{Direction, GraphProjection};
+use crate::error::Result;
+use crate::types::NodeId;
+
+/// Options shared by [`bfs`] and [`dfs`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraversalConfig {
+    pub direction: Direction,
+    /// Do not visit nodes further than this many hops from the start
+    pub max_depth: Option<usize>,
+}
+
+/// A node reached by a traversal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visit {
+    pub node: NodeId,
+    /// Hops from the start node along the traversal tree
+    pub depth: usize,
+    /// Node the traversal came from; `None` for the start node
+    pub parent: Option<NodeId>,
+}
+
+/// Visit the nodes reachable from `start` in breadth-first order
+pub fn bfs(graph: &GraphProjection, start: &str, config: &TraversalConfig) -> Result<Vec<Visit>> {
+    let start = graph.require(start)?;
+    let mut seen = vec![false; graph.node_count()];
+    let mut order = vec![(start, 0, None)];
+    seen[start] = true;
+
+    let mut next = 0;
+    while let Some(&(node, depth, _)) = order.get(next) {
+        next += 1;
+        if config.max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        for adjacent in graph.adjacent(node, config.direction) {
+            if !seen[adjacent.node] {
+                seen[adjacent.node] = true;
+                order.push((adjacent.node, depth + 1, Some(node)));
+            }
+        }
+    }
+
+    Ok(visits(graph, order))
+}
+
+/// Visit the nodes reachable from `start` in depth-first preorder
+///
+/// Neighbors are explored in adjacency order, so the first neighbor of a node
+/// is visited (and fully explored) before the second.
+pub fn dfs(graph: &GraphProjection, start: &str, config: &TraversalConfig) -> Result<Vec<Visit>> {
+    let start = graph.require(start)?;
+    let mut seen = vec![false; graph.node_count()];
+    let mut order = Vec::new();
+    let mut stack = vec![(start, 0, None)];
+
+    while let Some((node, depth, parent)) = stack.pop() {
+        if seen[node] {
+            continue;
+        }
+        seen[node] = true;
+        order.push((node, depth, parent));
+        if config.max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        let adjacent: Vec<usize> = graph
+            .adjacent(node, config.direction)
+            .map(|a| a.node)
+            .filter(|&n| !seen[n])
+            .collect();
+        stack.extend(
+            adjacent
+                .into_iter()
+                .rev()
+                .map(|n| (n, depth + 1, Some(node))),
+        );
+    }
+
+    Ok(visits(graph, order))
+}
+
+fn visits(graph: &GraphProjection, order: Vec<(usize, usize, Option<usize>)>) -> Vec<Visit> {
+    order
+        .into_iter()
+        .map(|(node, depth, parent)| Visit {
+            node: graph.node_id(node).clone(),
+            depth,
+            parent: parent.map(|p| graph.node_id(p).clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> GraphProjection {
+        // a -> b -> c -> d, a -> e
+        let edges = [("a", "b"), ("b", "c"), ("c", "d"), ("a", "e")];
+        GraphProjection::from_edges(
+            ["a", "b", "c", "d", "e"].map(String::from),
+            edges
+                .iter()
+                .enumerate()
+                .map(|(i, (f, t))| (i.to_string(), f.to_string(), t.to_string(), 1.0)),
+        )
+    }
+
+    fn names(visits: &[Visit]) -> Vec<&str> {
+        visits.iter().map(|v| v.node.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bfs_depth_limit() {
+        let graph = chain();
+        let all = bfs(&graph, "a", &TraversalConfig::default()).unwrap();
+        assert_eq!(names(&all), vec!["a", "b", "e", "c", "d"]);
+        assert_eq!(all[3].depth, 2);
+        assert_eq!(all[3].parent.as_deref(), Some("b"));
+
+        let config = TraversalConfig {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let near = bfs(&graph, "a", &config).unwrap();
+        assert_eq!(names(&near), vec!["a", "b", "e"]);
+    }
+
+    #[test]
+    fn test_dfs_order_and_direction() {
+        let graph = chain();
+        let all = dfs(&graph, "a", &TraversalConfig::default()).unwrap();
+        assert_eq!(names(&all), vec!["a", "b", "c", "d", "e"]);
+
+        let config = TraversalConfig {
+            direction: Direction::Incoming,
+            max_depth: Some(2),
+        };
+        let back = dfs(&graph, "d", &config).unwrap();
+        assert_eq!(names(&back), vec!["d", "c", "b"]);
+
+        assert!(dfs(&graph, "missing", &config).is_err());
+    }
+}
@@ -6,10 +6,14 @@
 //! - Projections (RETURN, WITH)
 //! - Mutations (CREATE, MERGE, DELETE, SET)
 //! - Aggregations and ordering
+//! - Procedure calls (CALL ... YIELD)
+//! - Schema commands (CREATE/DROP INDEX and CONSTRAINT, SHOW INDEXES)
 //! - Hyperedge support for N-ary relationships
 
+use crate::schema::{ConstraintKind, IndexKind};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// Top-level query representation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -28,6 +32,8 @@ pub enum Statement {
     Remove(RemoveClause),
     Return(ReturnClause),
     With(WithClause),
+    Call(CallClause),
+    Schema(SchemaCommand),
 }
 
 /// MATCH clause for pattern matching
@@ -200,6 +206,107 @@ pub struct WithClause {
     pub limit: Option<Expression>,
 }
 
+/// CALL clause invoking a procedure such as `algo.pagerank`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallClause {
+    /// Dotted procedure name
+    pub procedure: String,
+    pub arguments: Vec<Expression>,
+    /// `None` when the query has no YIELD, which returns every output column
+    pub yield_items: Option<Vec<YieldItem>>,
+    pub where_clause: Option<WhereClause>,
+}
+
+/// Schema command; always the only statement of its query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SchemaCommand {
+    CreateIndex {
+        name: String,
+        kind: IndexKind,
+        label: String,
+        properties: Vec<String>,
+        if_not_exists: bool,
+    },
+    DropIndex {
+        name: String,
+        if_exists: bool,
+    },
+    CreateConstraint {
+        name: String,
+        kind: ConstraintKind,
+        label: String,
+        property: String,
+        if_not_exists: bool,
+    },
+    DropConstraint {
+        name: String,
+        if_exists: bool,
+    },
+    ShowIndexes,
+    ShowConstraints,
+}
+
+impl SchemaCommand {
+    /// Columns of the rows the command returns
+    pub fn columns(&self) -> &'static [&'static str] {
+        match self {
+            SchemaCommand::ShowIndexes => &[
+                "name",
+                "type",
+                "entityType",
+                "labelsOrTypes",
+                "properties",
+                "owningConstraint",
+            ],
+            SchemaCommand::ShowConstraints => &["name", "type", "labelsOrTypes", "properties"],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for SchemaCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaCommand::CreateIndex {
+                name,
+                kind,
+                label,
+                properties,
+                ..
+            } => write!(
+                f,
+                "CREATE {} INDEX {} FOR (:{}) ON ({})",
+                kind,
+                name,
+                label,
+                properties.join(", ")
+            ),
+            SchemaCommand::DropIndex { name, .. } => write!(f, "DROP INDEX {}", name),
+            SchemaCommand::CreateConstraint {
+                name,
+                kind,
+                label,
+                property,
+                ..
+            } => write!(
+                f,
+                "CREATE {} CONSTRAINT {} FOR (:{}) REQUIRE {}",
+                kind, name, label, property
+            ),
+            SchemaCommand::DropConstraint { name, .. } => write!(f, "DROP CONSTRAINT {}", name),
+            SchemaCommand::ShowIndexes => write!(f, "SHOW INDEXES"),
+            SchemaCommand::ShowConstraints => write!(f, "SHOW CONSTRAINTS"),
+        }
+    }
+}
+
+/// Yield item: procedure output column AS alias
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct YieldItem {
+    pub column: String,
+    pub alias: Option<String>,
+}
+
 /// Return item: expression AS alias
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReturnItem {
@@ -345,7 +452,13 @@ impl Query {
         self.statements.iter().all(|stmt| {
             matches!(
                 stmt,
-                Statement::Match(_) | Statement::Return(_) | Statement::With(_)
+                Statement::Match(_)
+                    | Statement::Return(_)
+                    | Statement::With(_)
+                    | Statement::Call(_)
+                    | Statement::Schema(
+                        SchemaCommand::ShowIndexes | SchemaCommand::ShowConstraints
+                    )
             )
         })
     }
@@ -412,6 +525,124 @@ impl Expression {
     }
 }
 
+impl fmt::Display for Expression {
+    /// Renders the expression as Cypher text; used to name unaliased RETURN columns
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn join(f: &mut fmt::Formatter<'_>, items: &[Expression]) -> fmt::Result {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", item)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            Expression::Integer(n) => write!(f, "{}", n),
+            Expression::Float(n) => write!(f, "{}", n),
+            Expression::String(s) => write!(f, "'{}'", s.replace('\'', "\\'")),
+            Expression::Boolean(b) => write!(f, "{}", b),
+            Expression::Null => write!(f, "null"),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Property { object, property } => write!(f, "{}.{}", object, property),
+            Expression::List(items) => {
+                write!(f, "[")?;
+                join(f, items)?;
+                write!(f, "]")
+            }
+            Expression::Map(map) => {
+                let mut keys: Vec<_> = map.keys().collect();
+                keys.sort();
+                write!(f, "{{")?;
+                for (i, key) in keys.into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, map[key])?;
+                }
+                write!(f, "}}")
+            }
+            Expression::BinaryOp { left, op, right } => {
+                let op = match op {
+                    BinaryOperator::Add => "+",
+                    BinaryOperator::Subtract => "-",
+                    BinaryOperator::Multiply => "*",
+                    BinaryOperator::Divide => "/",
+                    BinaryOperator::Modulo => "%",
+                    BinaryOperator::Power => "^",
+                    BinaryOperator::Equal => "=",
+                    BinaryOperator::NotEqual => "<>",
+                    BinaryOperator::LessThan => "<",
+                    BinaryOperator::LessThanOrEqual => "<=",
+                    BinaryOperator::GreaterThan => ">",
+                    BinaryOperator::GreaterThanOrEqual => ">=",
+                    BinaryOperator::And => "AND",
+                    BinaryOperator::Or => "OR",
+                    BinaryOperator::Xor => "XOR",
+                    BinaryOperator::Contains => "CONTAINS",
+                    BinaryOperator::StartsWith => "STARTS WITH",
+                    BinaryOperator::EndsWith => "ENDS WITH",
+                    BinaryOperator::Matches => "=~",
+                    BinaryOperator::In => "IN",
+                    BinaryOperator::Is => "IS",
+                    BinaryOperator::IsNot => "IS NOT",
+                };
+                write!(f, "{} {} {}", left, op, right)
+            }
+            Expression::UnaryOp { op, operand } => match op {
+                UnaryOperator::Not => write!(f, "NOT {}", operand),
+                UnaryOperator::Minus => write!(f, "-{}", operand),
+                UnaryOperator::Plus => write!(f, "+{}", operand),
+                UnaryOperator::IsNull => write!(f, "{} IS NULL", operand),
+                UnaryOperator::IsNotNull => write!(f, "{} IS NOT NULL", operand),
+            },
+            Expression::FunctionCall { name, args } => {
+                write!(f, "{}(", name)?;
+                join(f, args)?;
+                write!(f, ")")
+            }
+            Expression::Aggregation {
+                function,
+                expression,
+                distinct,
+            } => {
+                let name = match function {
+                    AggregationFunction::Count => "count",
+                    AggregationFunction::Sum => "sum",
+                    AggregationFunction::Avg => "avg",
+                    AggregationFunction::Min => "min",
+                    AggregationFunction::Max => "max",
+                    AggregationFunction::Collect => "collect",
+                    AggregationFunction::StdDev => "stDev",
+                    AggregationFunction::StdDevP => "stDevP",
+                    AggregationFunction::Percentile => "percentileCont",
+                };
+                let distinct = if *distinct { "DISTINCT " } else { "" };
+                write!(f, "{}({}{})", name, distinct, expression)
+            }
+            Expression::PatternPredicate(_) => write!(f, "<pattern>"),
+            Expression::Case {
+                expression,
+                alternatives,
+                default,
+            } => {
+                write!(f, "CASE")?;
+                if let Some(expression) = expression {
+                    write!(f, " {}", expression)?;
+                }
+                for (when, then) in alternatives {
+                    write!(f, " WHEN {} THEN {}", when, then)?;
+                }
+                if let Some(default) = default {
+                    write!(f, " ELSE {}", default)?;
+                }
+                write!(f, " END")
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -469,4 +700,24 @@ mod tests {
         });
         assert_eq!(hyperedge.arity(), 3);
     }
+
+    #[test]
+    fn test_expression_display() {
+        let expr = Expression::BinaryOp {
+            left: Box::new(Expression::Property {
+                object: Box::new(Expression::Variable("n".to_string())),
+                property: "age".to_string(),
+            }),
+            op: BinaryOperator::GreaterThan,
+            right: Box::new(Expression::Integer(30)),
+        };
+        assert_eq!(expr.to_string(), "n.age > 30");
+
+        let count = Expression::Aggregation {
+            function: AggregationFunction::Count,
+            expression: Box::new(Expression::Variable("n".to_string())),
+            distinct: true,
+        };
+        assert_eq!(count.to_string(), "count(DISTINCT n)");
+    }
 }
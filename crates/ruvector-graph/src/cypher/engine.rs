@@ -0,0 +1,2211 @@
+//! Query engine: executes a [`QueryPlan`] against a [`GraphDB`]
+//!
+//! Records flow between operators as maps from variable to [`Value`].
+//! Expressions follow Cypher's three-valued logic: comparisons involving
+//! null, or values that cannot be compared, yield null, and filters keep only
+//! the records whose predicate is true.
+
+use super::ast::*;
+use super::plan::*;
+use super::procedures;
+use super::result::{Path, QueryResult, QueryStats, Row, Value};
+use crate::edge::{Edge, EdgeBuilder};
+use crate::error::{GraphError, Result};
+use crate::graph::GraphDB;
+use crate::node::{Node, NodeBuilder};
+use crate::schema::{ConstraintDefinition, IndexDefinition, IndexKey, IndexSeek};
+use crate::types::{Properties, PropertyValue};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Bound;
+use std::sync::Arc;
+
+/// Variable bindings of one intermediate result
+type Record = HashMap<String, Value>;
+
+fn exec_error(message: impl Into<String>) -> GraphError {
+    GraphError::CypherExecutionError(message.into())
+}
+
+/// Executes plans for one graph with one set of parameters
+pub struct QueryEngine<'a> {
+    db: &'a GraphDB,
+    params: &'a Properties,
+    stats: QueryStats,
+}
+
+impl<'a> QueryEngine<'a> {
+    pub fn new(db: &'a GraphDB, params: &'a Properties) -> Self {
+        Self {
+            db,
+            params,
+            stats: QueryStats::default(),
+        }
+    }
+
+    /// Run a plan and collect its result rows
+    pub fn execute(mut self, plan: &QueryPlan) -> Result<QueryResult> {
+        let records = self.run(&plan.root, &Record::new())?;
+
+        let columns = Arc::new(plan.columns.clone());
+        let rows = if columns.is_empty() {
+            Vec::new()
+        } else {
+            records
+                .into_iter()
+                .map(|mut record| {
+                    let values = columns
+                        .iter()
+                        .map(|c| record.remove(c).unwrap_or(Value::Null))
+                        .collect();
+                    Row::new(columns.clone(), values)
+                })
+                .collect()
+        };
+
+        Ok(QueryResult {
+            columns: plan.columns.clone(),
+            rows,
+            stats: self.stats,
+        })
+    }
+
+    fn run(&mut self, op: &PlanOperator, seed: &Record) -> Result<Vec<Record>> {
+        if let PlanOperator::Argument { .. } = op {
+            return Ok(vec![seed.clone()]);
+        }
+        let input = op.input().expect("non-leaf operator has an input");
+        let records = self.run(input, seed)?;
+
+        match op {
+            PlanOperator::Argument { .. } => unreachable!(),
+            PlanOperator::NodeScan { node, access, .. } => {
+                let mut out = Vec::new();
+                for record in records {
+                    // Variables bound by the input are checked rather than scanned
+                    if let Some(value) = record.get(&node.variable) {
+                        if let Value::Node(bound) = value {
+                            if self.node_matches(bound, node, &record)? {
+                                out.push(record);
+                            }
+                        }
+                        continue;
+                    }
+                    for candidate in self.node_candidates(access, &record)? {
+                        if self.node_matches(&candidate, node, &record)? {
+                            let mut next = record.clone();
+                            next.insert(node.variable.clone(), Value::Node(candidate));
+                            out.push(next);
+                        }
+                    }
+                }
+                Ok(out)
+            }
+            PlanOperator::Expand {
+                from,
+                relationship,
+                to,
+                unique,
+                ..
+            } => {
+                let mut out = Vec::new();
+                for record in &records {
+                    self.expand(record, from, relationship, to, unique, &mut out)?;
+                }
+                Ok(out)
+            }
+            PlanOperator::Filter { predicate, .. } => {
+                let mut out = Vec::new();
+                for record in records {
+                    if truth(&self.eval(predicate, &record)?)? == Some(true) {
+                        out.push(record);
+                    }
+                }
+                Ok(out)
+            }
+            PlanOperator::Optional {
+                plan, variables, ..
+            } => {
+                let mut out = Vec::new();
+                for mut record in records {
+                    let matches = self.run(plan, &record)?;
+                    if matches.is_empty() {
+                        for variable in variables {
+                            record.entry(variable.clone()).or_insert(Value::Null);
+                        }
+                        out.push(record);
+                    } else {
+                        out.extend(matches);
+                    }
+                }
+                Ok(out)
+            }
+            PlanOperator::BuildPath {
+                variable,
+                start,
+                relationships,
+                ..
+            } => {
+                let mut out = Vec::with_capacity(records.len());
+                for mut record in records {
+                    let path = self.build_path(&record, start, relationships)?;
+                    record.insert(variable.clone(), path);
+                    out.push(record);
+                }
+                Ok(out)
+            }
+            PlanOperator::Project { items, .. } => {
+                let mut out = Vec::with_capacity(records.len());
+                for mut record in records {
+                    // Evaluate every item before binding, so that a column may
+                    // shadow a variable another item still reads
+                    let values = items
+                        .iter()
+                        .map(|(_, e)| self.eval(e, &record))
+                        .collect::<Result<Vec<_>>>()?;
+                    for ((name, _), value) in items.iter().zip(values) {
+                        record.insert(name.clone(), value);
+                    }
+                    out.push(record);
+                }
+                Ok(out)
+            }
+            PlanOperator::Aggregate {
+                keys, aggregates, ..
+            } => self.aggregate(records, keys, aggregates),
+            PlanOperator::Distinct { columns, .. } => {
+                let mut seen = HashSet::new();
+                let mut out = Vec::new();
+                for record in records {
+                    let record = select(record, columns);
+                    let key: Vec<ValueKey> =
+                        columns.iter().map(|c| ValueKey::from(&record[c])).collect();
+                    if seen.insert(key) {
+                        out.push(record);
+                    }
+                }
+                Ok(out)
+            }
+            PlanOperator::Sort { keys, .. } => {
+                let mut keyed = Vec::with_capacity(records.len());
+                for record in records {
+                    let values = keys
+                        .iter()
+                        .map(|(e, _)| self.eval(e, &record))
+                        .collect::<Result<Vec<_>>>()?;
+                    keyed.push((values, record));
+                }
+                keyed.sort_by(|(a, _), (b, _)| {
+                    for ((x, y), (_, ascending)) in a.iter().zip(b).zip(keys) {
+                        let ordering = order_values(x, y);
+                        if ordering != Ordering::Equal {
+                            return if *ascending {
+                                ordering
+                            } else {
+                                ordering.reverse()
+                            };
+                        }
+                    }
+                    Ordering::Equal
+                });
+                Ok(keyed.into_iter().map(|(_, record)| record).collect())
+            }
+            PlanOperator::Skip { count, .. } => {
+                let count = self.eval_count(count, "SKIP")?;
+                Ok(records.into_iter().skip(count).collect())
+            }
+            PlanOperator::Limit { count, .. } => {
+                let count = self.eval_count(count, "LIMIT")?;
+                Ok(records.into_iter().take(count).collect())
+            }
+            PlanOperator::Select { columns, .. } => Ok(records
+                .into_iter()
+                .map(|record| select(record, columns))
+                .collect()),
+            PlanOperator::Create { chains, .. } => {
+                let mut out = Vec::with_capacity(records.len());
+                for mut record in records {
+                    for chain in chains {
+                        self.create_chain(&mut record, chain)?;
+                    }
+                    out.push(record);
+                }
+                Ok(out)
+            }
+            PlanOperator::Merge {
+                chain,
+                matcher,
+                on_create,
+                on_match,
+                ..
+            } => {
+                let mut out = Vec::new();
+                for record in records {
+                    let matches = self.run(matcher, &record)?;
+                    if matches.is_empty() {
+                        let mut record = record;
+                        self.create_chain(&mut record, chain)?;
+                        self.apply_set(&mut record, on_create)?;
+                        out.push(record);
+                    } else {
+                        for mut record in matches {
+                            self.apply_set(&mut record, on_match)?;
+                            out.push(record);
+                        }
+                    }
+                }
+                if !on_create.is_empty() || !on_match.is_empty() {
+                    self.refresh(&mut out);
+                }
+                Ok(out)
+            }
+            PlanOperator::Set { items, .. } => {
+                let mut out = records;
+                for record in &mut out {
+                    self.apply_set(record, items)?;
+                }
+                self.refresh(&mut out);
+                Ok(out)
+            }
+            PlanOperator::Remove { items, .. } => {
+                let mut out = records;
+                for record in &mut out {
+                    self.apply_remove(record, items)?;
+                }
+                self.refresh(&mut out);
+                Ok(out)
+            }
+            PlanOperator::Delete {
+                expressions,
+                detach,
+                ..
+            } => {
+                self.delete(&records, expressions, *detach)?;
+                Ok(records)
+            }
+            PlanOperator::ProcedureCall {
+                procedure,
+                arguments,
+                yields,
+                ..
+            } => {
+                let signature = procedures::signature(procedure)
+                    .ok_or_else(|| exec_error(format!("Unknown procedure `{}`", procedure)))?;
+                let positions: Vec<usize> = yields
+                    .iter()
+                    .map(|(column, _)| {
+                        signature
+                            .outputs
+                            .iter()
+                            .position(|o| o == column)
+                            .expect("planner checks yielded columns")
+                    })
+                    .collect();
+
+                let mut out = Vec::new();
+                for record in records {
+                    let values = arguments
+                        .iter()
+                        .map(|a| self.eval(a, &record))
+                        .collect::<Result<Vec<_>>>()?;
+                    for row in procedures::call(self.db, procedure, &values)? {
+                        let mut next = record.clone();
+                        for ((_, variable), &i) in yields.iter().zip(&positions) {
+                            next.insert(variable.clone(), row[i].clone());
+                        }
+                        out.push(next);
+                    }
+                }
+                Ok(out)
+            }
+            PlanOperator::Schema { command, .. } => self.schema_command(command),
+        }
+    }
+
+    // Reads
+
+    fn node_candidates(&self, access: &NodeAccess, record: &Record) -> Result<Vec<Node>> {
+        Ok(match access {
+            NodeAccess::PropertyIndex { key, value } => {
+                match self.eval(value, record)?.to_property_value() {
+                    None | Some(PropertyValue::Null) => Vec::new(),
+                    Some(value) => self.db.get_nodes_by_property(key, &value),
+                }
+            }
+            NodeAccess::RangeIndex { index, seek, .. } => {
+                let key = |expression: &Expression| -> Result<Option<IndexKey>> {
+                    Ok(self
+                        .eval(expression, record)?
+                        .to_property_value()
+                        .and_then(|v| IndexKey::from_value(&v)))
+                };
+                let bound =
+                    |bound: &Option<(Expression, bool)>| -> Result<Option<Bound<IndexKey>>> {
+                        Ok(match bound {
+                            None => Some(Bound::Unbounded),
+                            Some((expression, inclusive)) => key(expression)?.map(|k| {
+                                if *inclusive {
+                                    Bound::Included(k)
+                                } else {
+                                    Bound::Excluded(k)
+                                }
+                            }),
+                        })
+                    };
+                // Comparisons with null or unindexable values are never true
+                let seek = match seek {
+                    RangeSeek::Equal(value) => key(value)?.map(IndexSeek::Equal),
+                    RangeSeek::Range { lower, upper } => match (bound(lower)?, bound(upper)?) {
+                        (Some(lower), Some(upper)) => Some(IndexSeek::Range(lower, upper)),
+                        _ => None,
+                    },
+                    RangeSeek::Prefix(prefix) => match self.eval(prefix, record)? {
+                        Value::String(prefix) => Some(IndexSeek::Prefix(prefix)),
+                        _ => None,
+                    },
+                };
+                let ids = seek
+                    .and_then(|seek| self.db.schema().seek(index, &seek))
+                    .unwrap_or_default();
+                ids.iter().filter_map(|id| self.db.get_node(id)).collect()
+            }
+            NodeAccess::FullTextIndex {
+                index,
+                label,
+                property,
+                text,
+            } => match self.eval(text, record)? {
+                Value::String(text) => match self.db.schema().contains(index, property, &text) {
+                    Some(ids) => ids.iter().filter_map(|id| self.db.get_node(id)).collect(),
+                    // Too short for the index
+                    None => self.db.get_nodes_by_label(label),
+                },
+                _ => Vec::new(),
+            },
+            NodeAccess::LabelIndex { label } => self.db.get_nodes_by_label(label),
+            NodeAccess::Bound | NodeAccess::AllNodes => self.db.get_all_nodes(),
+        })
+    }
+
+    // Schema
+
+    fn schema_command(&mut self, command: &SchemaCommand) -> Result<Vec<Record>> {
+        let schema = self.db.schema();
+        match command {
+            SchemaCommand::CreateIndex {
+                name,
+                kind,
+                label,
+                properties,
+                if_not_exists,
+            } => {
+                let exists = schema.index(name).is_some() || self.db.vector_index(name).is_some();
+                let equivalent = schema
+                    .indexes()
+                    .iter()
+                    .any(|d| d.kind == *kind && &d.label == label && &d.properties == properties);
+                if !(*if_not_exists && (exists || equivalent)) {
+                    self.db.create_index(IndexDefinition {
+                        name: name.clone(),
+                        kind: *kind,
+                        label: label.clone(),
+                        properties: properties.clone(),
+                        owning_constraint: None,
+                    })?;
+                    self.stats.indexes_added += 1;
+                }
+                Ok(Vec::new())
+            }
+            SchemaCommand::DropIndex { name, if_exists } => {
+                if self.db.drop_index(name)? || self.db.drop_vector_index(name) {
+                    self.stats.indexes_removed += 1;
+                } else if !if_exists {
+                    return Err(GraphError::IndexError(format!("No index named {}", name)));
+                }
+                Ok(Vec::new())
+            }
+            SchemaCommand::CreateConstraint {
+                name,
+                kind,
+                label,
+                property,
+                if_not_exists,
+            } => {
+                let exists = schema.constraint(name).is_some();
+                let equivalent = schema
+                    .constraints()
+                    .iter()
+                    .any(|c| c.kind == *kind && &c.label == label && &c.property == property);
+                if !(*if_not_exists && (exists || equivalent)) {
+                    self.db.create_constraint(ConstraintDefinition {
+                        name: name.clone(),
+                        kind: *kind,
+                        label: label.clone(),
+                        property: property.clone(),
+                    })?;
+                    self.stats.constraints_added += 1;
+                }
+                Ok(Vec::new())
+            }
+            SchemaCommand::DropConstraint { name, if_exists } => {
+                if self.db.drop_constraint(name)? {
+                    self.stats.constraints_removed += 1;
+                } else if !if_exists {
+                    return Err(GraphError::IndexError(format!(
+                        "No constraint named {}",
+                        name
+                    )));
+                }
+                Ok(Vec::new())
+            }
+            SchemaCommand::ShowIndexes => {
+                let strings = |items: &[String]| {
+                    Value::List(items.iter().map(|s| Value::from(s.as_str())).collect())
+                };
+                let mut rows: Vec<(String, [Value; 5])> = schema
+                    .indexes()
+                    .into_iter()
+                    .map(|d| {
+                        let row = [
+                            Value::from(d.kind.to_string().as_str()),
+                            Value::from("NODE"),
+                            strings(std::slice::from_ref(&d.label)),
+                            strings(&d.properties),
+                            d.owning_constraint
+                                .as_deref()
+                                .map(Value::from)
+                                .unwrap_or(Value::Null),
+                        ];
+                        (d.name, row)
+                    })
+                    .collect();
+                rows.extend(self.db.vector_indexes().into_iter().map(|index| {
+                    let row = [
+                        Value::from("VECTOR"),
+                        Value::from("NODE"),
+                        strings(&[index.label().to_string()]),
+                        strings(&[index.property().to_string()]),
+                        Value::Null,
+                    ];
+                    (index.name().to_string(), row)
+                }));
+                rows.sort_by(|a, b| a.0.cmp(&b.0));
+                Ok(rows
+                    .into_iter()
+                    .map(|(name, row)| schema_record(command, Value::from(name.as_str()), row))
+                    .collect())
+            }
+            SchemaCommand::ShowConstraints => Ok(schema
+                .constraints()
+                .into_iter()
+                .map(|c| {
+                    let row = [
+                        Value::from(c.kind.to_string().as_str()),
+                        Value::List(vec![Value::from(c.label.as_str())]),
+                        Value::List(vec![Value::from(c.property.as_str())]),
+                    ];
+                    schema_record(command, Value::from(c.name.as_str()), row)
+                })
+                .collect()),
+        }
+    }
+
+    fn node_matches(&self, node: &Node, step: &NodeStep, record: &Record) -> Result<bool> {
+        if !step.labels.iter().all(|l| node.has_label(l)) {
+            return Ok(false);
+        }
+        self.properties_match(&node.properties, &step.properties, record)
+    }
+
+    /// Index seeks are approximate, so every constraint is checked again here
+    fn properties_match(
+        &self,
+        properties: &Properties,
+        constraints: &[(String, Expression)],
+        record: &Record,
+    ) -> Result<bool> {
+        for (key, expression) in constraints {
+            let expected = self.eval(expression, record)?;
+            let actual = properties
+                .get(key)
+                .cloned()
+                .map(Value::from)
+                .unwrap_or(Value::Null);
+            if values_equal(&actual, &expected) != Some(true) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Relationships of a node that fit a step, with the node at their other end
+    fn neighbors(&self, node_id: &String, step: &RelStep) -> Vec<(Edge, String)> {
+        let type_matches = |edge: &Edge| {
+            step.rel_type
+                .as_ref()
+                .map_or(true, |t| &edge.edge_type == t)
+        };
+
+        let mut result = Vec::new();
+        if step.direction != Direction::Incoming {
+            for edge in self.db.get_outgoing_edges(node_id) {
+                if type_matches(&edge) {
+                    let to = edge.to.clone();
+                    result.push((edge, to));
+                }
+            }
+        }
+        if step.direction != Direction::Outgoing {
+            for edge in self.db.get_incoming_edges(node_id) {
+                // An undirected step already found self-loops as outgoing
+                let seen = step.direction == Direction::Undirected && edge.from == edge.to;
+                if type_matches(&edge) && !seen {
+                    let from = edge.from.clone();
+                    result.push((edge, from));
+                }
+            }
+        }
+        result
+    }
+
+    fn expand(
+        &self,
+        record: &Record,
+        from: &str,
+        step: &RelStep,
+        to: &NodeStep,
+        unique: &[String],
+        out: &mut Vec<Record>,
+    ) -> Result<()> {
+        let start = match record.get(from) {
+            Some(Value::Node(node)) => node.id.clone(),
+            Some(Value::Null) => return Ok(()),
+            _ => return Err(exec_error(format!("`{}` is not a node", from))),
+        };
+
+        // Relationships already matched by the same MATCH cannot be reused
+        let mut used = HashSet::new();
+        for variable in unique.iter().filter(|v| **v != step.variable) {
+            match record.get(variable) {
+                Some(Value::Relationship(edge)) => {
+                    used.insert(edge.id.clone());
+                }
+                Some(Value::List(items)) => {
+                    used.extend(
+                        items
+                            .iter()
+                            .filter_map(Value::as_relationship)
+                            .map(|e| e.id.clone()),
+                    );
+                }
+                _ => {}
+            }
+        }
+        let bound = record.get(&step.variable);
+
+        let Some((min, max)) = step.range else {
+            for (edge, next) in self.neighbors(&start, step) {
+                if used.contains(&edge.id)
+                    || !self.properties_match(&edge.properties, &step.properties, record)?
+                {
+                    continue;
+                }
+                let value = Value::Relationship(edge);
+                if bound.is_some_and(|b| *b != value) {
+                    continue;
+                }
+                if let Some(mut next_record) = self.bind_target(record, to, &next)? {
+                    next_record.insert(step.variable.clone(), value);
+                    out.push(next_record);
+                }
+            }
+            return Ok(());
+        };
+
+        // Variable-length steps: breadth-first over paths that do not repeat
+        // a relationship, so shorter paths come first
+        let mut queue = VecDeque::from([(start, Vec::<Edge>::new())]);
+        while let Some((node_id, path)) = queue.pop_front() {
+            if path.len() >= min {
+                if let Some(mut next_record) = self.bind_target(record, to, &node_id)? {
+                    let value = Value::List(
+                        path.iter()
+                            .map(|e| Value::Relationship(e.clone()))
+                            .collect(),
+                    );
+                    if bound.map_or(true, |b| *b == value) {
+                        next_record.insert(step.variable.clone(), value);
+                        out.push(next_record);
+                    }
+                }
+            }
+            if max.is_some_and(|max| path.len() >= max) {
+                continue;
+            }
+            for (edge, next) in self.neighbors(&node_id, step) {
+                if used.contains(&edge.id)
+                    || path.iter().any(|e| e.id == edge.id)
+                    || !self.properties_match(&edge.properties, &step.properties, record)?
+                {
+                    continue;
+                }
+                let mut extended = path.clone();
+                extended.push(edge);
+                queue.push_back((next, extended));
+            }
+        }
+        Ok(())
+    }
+
+    /// Bind the node at the end of an expansion, if it fits the step
+    fn bind_target(
+        &self,
+        record: &Record,
+        step: &NodeStep,
+        node_id: &str,
+    ) -> Result<Option<Record>> {
+        let node = match record.get(&step.variable) {
+            Some(Value::Node(bound)) if bound.id == node_id => bound.clone(),
+            Some(_) => return Ok(None),
+            None => match self.db.get_node(node_id) {
+                Some(node) => node,
+                None => return Ok(None),
+            },
+        };
+        if !self.node_matches(&node, step, record)? {
+            return Ok(None);
+        }
+        let mut next = record.clone();
+        next.insert(step.variable.clone(), Value::Node(node));
+        Ok(Some(next))
+    }
+
+    fn build_path(&self, record: &Record, start: &str, relationships: &[String]) -> Result<Value> {
+        let mut current = match record.get(start) {
+            Some(Value::Node(node)) => node.clone(),
+            Some(Value::Null) | None => return Ok(Value::Null),
+            Some(_) => return Err(exec_error(format!("`{}` is not a node", start))),
+        };
+
+        let mut path = Path {
+            nodes: vec![current.clone()],
+            relationships: Vec::new(),
+        };
+        for variable in relationships {
+            let edges: Vec<Edge> = match record.get(variable) {
+                Some(Value::Relationship(edge)) => vec![edge.clone()],
+                Some(Value::List(items)) => items
+                    .iter()
+                    .filter_map(Value::as_relationship)
+                    .cloned()
+                    .collect(),
+                Some(Value::Null) | None => return Ok(Value::Null),
+                Some(_) => return Err(exec_error(format!("`{}` is not a relationship", variable))),
+            };
+            for edge in edges {
+                let next_id = if edge.from == current.id {
+                    &edge.to
+                } else {
+                    &edge.from
+                };
+                current = self
+                    .db
+                    .get_node(next_id)
+                    .ok_or_else(|| exec_error(format!("Node {} not found", next_id)))?;
+                path.nodes.push(current.clone());
+                path.relationships.push(edge);
+            }
+        }
+        Ok(Value::Path(path))
+    }
+
+    // Aggregation
+
+    fn aggregate(
+        &self,
+        records: Vec<Record>,
+        keys: &[(String, Expression)],
+        aggregates: &[AggregateItem],
+    ) -> Result<Vec<Record>> {
+        let new_accumulators = || -> Result<Vec<Accumulator>> {
+            aggregates
+                .iter()
+                .map(|a| Accumulator::new(&a.aggregation))
+                .collect()
+        };
+
+        let mut groups: Vec<(Vec<Value>, Vec<Accumulator>)> = Vec::new();
+        let mut index: HashMap<Vec<ValueKey>, usize> = HashMap::new();
+        for record in &records {
+            let key_values = keys
+                .iter()
+                .map(|(_, e)| self.eval(e, record))
+                .collect::<Result<Vec<_>>>()?;
+            let key: Vec<ValueKey> = key_values.iter().map(ValueKey::from).collect();
+            let group = match index.get(&key) {
+                Some(&group) => group,
+                None => {
+                    groups.push((key_values, new_accumulators()?));
+                    index.insert(key, groups.len() - 1);
+                    groups.len() - 1
+                }
+            };
+
+            for accumulator in &mut groups[group].1 {
+                let value = match accumulator.argument {
+                    // count(*) counts records
+                    Expression::Variable(ref name) if name == "*" => Value::Boolean(true),
+                    ref argument => self.eval(argument, record)?,
+                };
+                accumulator.add(value);
+            }
+        }
+
+        // Aggregating without grouping keys always yields one record
+        if groups.is_empty() && keys.is_empty() {
+            groups.push((Vec::new(), new_accumulators()?));
+        }
+
+        groups
+            .into_iter()
+            .map(|(key_values, accumulators)| {
+                let mut record: Record = keys
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(key_values)
+                    .collect();
+                for (item, accumulator) in aggregates.iter().zip(accumulators) {
+                    record.insert(item.column.clone(), accumulator.finish()?);
+                }
+                Ok(record)
+            })
+            .collect()
+    }
+
+    // Writes
+
+    fn eval_properties(
+        &self,
+        properties: &[(String, Expression)],
+        record: &Record,
+    ) -> Result<Properties> {
+        let mut result = Properties::new();
+        for (key, expression) in properties {
+            let value = self.eval(expression, record)?;
+            match to_property(&value)? {
+                PropertyValue::Null => {}
+                value => {
+                    result.insert(key.clone(), value);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create the nodes and relationships of a chain that are not yet bound
+    fn create_chain(&mut self, record: &mut Record, chain: &PatternChain) -> Result<()> {
+        let mut node_ids = Vec::with_capacity(chain.nodes.len());
+        for step in &chain.nodes {
+            match record.get(&step.variable) {
+                Some(Value::Node(node)) => node_ids.push(node.id.clone()),
+                Some(Value::Null) => {
+                    return Err(exec_error(format!(
+                        "Cannot create a relationship to `{}`, which is null",
+                        step.variable
+                    )))
+                }
+                Some(_) => return Err(exec_error(format!("`{}` is not a node", step.variable))),
+                None => {
+                    let properties = self.eval_properties(&step.properties, record)?;
+                    let node = NodeBuilder::new()
+                        .labels(step.labels.iter().cloned())
+                        .properties(properties)
+                        .build();
+                    self.db.create_node(node.clone())?;
+                    self.stats.nodes_created += 1;
+                    self.stats.labels_added += node.labels.len();
+                    self.stats.properties_set += node.properties.len();
+                    node_ids.push(node.id.clone());
+                    record.insert(step.variable.clone(), Value::Node(node));
+                }
+            }
+        }
+
+        for (i, step) in chain.relationships.iter().enumerate() {
+            let rel_type = step
+                .rel_type
+                .clone()
+                .ok_or_else(|| exec_error("Relationships must have a type to be created"))?;
+            let (from, to) = match step.direction {
+                Direction::Incoming => (node_ids[i + 1].clone(), node_ids[i].clone()),
+                Direction::Outgoing | Direction::Undirected => {
+                    (node_ids[i].clone(), node_ids[i + 1].clone())
+                }
+            };
+            let properties = self.eval_properties(&step.properties, record)?;
+            let edge = EdgeBuilder::new(from, to, rel_type)
+                .properties(properties)
+                .build();
+            self.db.create_edge(edge.clone())?;
+            self.stats.relationships_created += 1;
+            self.stats.properties_set += edge.properties.len();
+            record.insert(step.variable.clone(), Value::Relationship(edge));
+        }
+
+        if let Some(path) = &chain.path {
+            let relationships: Vec<String> = chain
+                .relationships
+                .iter()
+                .map(|r| r.variable.clone())
+                .collect();
+            let value = self.build_path(record, &chain.nodes[0].variable, &relationships)?;
+            record.insert(path.clone(), value);
+        }
+        Ok(())
+    }
+
+    /// Latest stored version of the node or relationship bound to a variable
+    fn entity(&self, record: &Record, variable: &str) -> Result<Option<Entity>> {
+        let deleted = |id: &str| exec_error(format!("Entity {} has been deleted", id));
+        Ok(match record.get(variable) {
+            Some(Value::Node(node)) => Some(Entity::Node(
+                self.db
+                    .get_node(&node.id)
+                    .ok_or_else(|| deleted(&node.id))?,
+            )),
+            Some(Value::Relationship(edge)) => Some(Entity::Relationship(
+                self.db
+                    .get_edge(&edge.id)
+                    .ok_or_else(|| deleted(&edge.id))?,
+            )),
+            // Updating null is a no-op
+            Some(Value::Null) => None,
+            Some(other) => {
+                return Err(exec_error(format!(
+                    "Expected `{}` to be a node or relationship, got {}",
+                    variable,
+                    type_name(other)
+                )))
+            }
+            None => return Err(exec_error(format!("Variable `{}` not defined", variable))),
+        })
+    }
+
+    fn store(&self, record: &mut Record, variable: &str, entity: Entity) -> Result<()> {
+        let value = match entity {
+            Entity::Node(node) => {
+                self.db.update_node(node.clone())?;
+                Value::Node(node)
+            }
+            Entity::Relationship(edge) => {
+                self.db.update_edge(edge.clone())?;
+                Value::Relationship(edge)
+            }
+        };
+        record.insert(variable.to_string(), value);
+        Ok(())
+    }
+
+    fn apply_set(&mut self, record: &mut Record, items: &[SetItem]) -> Result<()> {
+        for item in items {
+            match item {
+                SetItem::Property {
+                    variable,
+                    property,
+                    value,
+                } => {
+                    let value = to_property(&self.eval(value, record)?)?;
+                    let Some(mut entity) = self.entity(record, variable)? else {
+                        continue;
+                    };
+                    let properties = entity.properties_mut();
+                    if value == PropertyValue::Null {
+                        if properties.remove(property).is_some() {
+                            self.stats.properties_set += 1;
+                        }
+                    } else {
+                        properties.insert(property.clone(), value);
+                        self.stats.properties_set += 1;
+                    }
+                    self.store(record, variable, entity)?;
+                }
+                SetItem::Variable { variable, value } => {
+                    let replacement = match self.eval(value, record)? {
+                        Value::Node(node) => node.properties,
+                        Value::Relationship(edge) => edge.properties,
+                        Value::Map(map) => {
+                            let mut properties = Properties::new();
+                            for (key, value) in map {
+                                match to_property(&value)? {
+                                    PropertyValue::Null => {}
+                                    value => {
+                                        properties.insert(key, value);
+                                    }
+                                }
+                            }
+                            properties
+                        }
+                        other => {
+                            return Err(exec_error(format!(
+                                "SET {} = ... expects a map, got {}",
+                                variable,
+                                type_name(&other)
+                            )))
+                        }
+                    };
+                    let Some(mut entity) = self.entity(record, variable)? else {
+                        continue;
+                    };
+                    let properties = entity.properties_mut();
+                    let removed = properties
+                        .keys()
+                        .filter(|k| !replacement.contains_key(*k))
+                        .count();
+                    self.stats.properties_set += removed + replacement.len();
+                    *properties = replacement;
+                    self.store(record, variable, entity)?;
+                }
+                SetItem::Labels { variable, labels } => {
+                    let Some(entity) = self.entity(record, variable)? else {
+                        continue;
+                    };
+                    let Entity::Node(mut node) = entity else {
+                        return Err(exec_error(format!(
+                            "Labels can only be set on nodes; `{}` is a relationship",
+                            variable
+                        )));
+                    };
+                    for label in labels {
+                        if !node.has_label(label) {
+                            node.add_label(label.clone());
+                            self.stats.labels_added += 1;
+                        }
+                    }
+                    self.store(record, variable, Entity::Node(node))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_remove(&mut self, record: &mut Record, items: &[RemoveItem]) -> Result<()> {
+        for item in items {
+            match item {
+                RemoveItem::Property { variable, property } => {
+                    let Some(mut entity) = self.entity(record, variable)? else {
+                        continue;
+                    };
+                    if entity.properties_mut().remove(property).is_some() {
+                        self.stats.properties_set += 1;
+                    }
+                    self.store(record, variable, entity)?;
+                }
+                RemoveItem::Labels { variable, labels } => {
+                    let Some(entity) = self.entity(record, variable)? else {
+                        continue;
+                    };
+                    let Entity::Node(mut node) = entity else {
+                        return Err(exec_error(format!(
+                            "Labels can only be removed from nodes; `{}` is a relationship",
+                            variable
+                        )));
+                    };
+                    for label in labels {
+                        if node.remove_label(label) {
+                            self.stats.labels_removed += 1;
+                        }
+                    }
+                    self.store(record, variable, Entity::Node(node))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete(
+        &mut self,
+        records: &[Record],
+        expressions: &[Expression],
+        detach: bool,
+    ) -> Result<()> {
+        let mut nodes: Vec<String> = Vec::new();
+        let mut edges: Vec<String> = Vec::new();
+        for record in records {
+            for expression in expressions {
+                match self.eval(expression, record)? {
+                    Value::Null => {}
+                    Value::Node(node) => nodes.push(node.id),
+                    Value::Relationship(edge) => edges.push(edge.id),
+                    Value::Path(path) => {
+                        nodes.extend(path.nodes.into_iter().map(|n| n.id));
+                        edges.extend(path.relationships.into_iter().map(|e| e.id));
+                    }
+                    other => {
+                        return Err(exec_error(format!(
+                            "DELETE expects a node, relationship or path, got {}",
+                            type_name(&other)
+                        )))
+                    }
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        nodes.retain(|id| seen.insert(id.clone()));
+        let edge_set: HashSet<String> = edges.iter().cloned().collect();
+
+        // Check every node before deleting anything
+        if !detach {
+            for id in &nodes {
+                let attached = self
+                    .db
+                    .get_outgoing_edges(id)
+                    .into_iter()
+                    .chain(self.db.get_incoming_edges(id))
+                    .any(|e| !edge_set.contains(&e.id));
+                if attached {
+                    return Err(exec_error(format!(
+                        "Cannot delete node {} because it still has relationships; use DETACH DELETE",
+                        id
+                    )));
+                }
+            }
+        }
+
+        for id in &edges {
+            if self.db.delete_edge(id)? {
+                self.stats.relationships_deleted += 1;
+            }
+        }
+        for id in &nodes {
+            let attached: Vec<Edge> = self
+                .db
+                .get_outgoing_edges(id)
+                .into_iter()
+                .chain(self.db.get_incoming_edges(id))
+                .collect();
+            for edge in attached {
+                if self.db.delete_edge(&edge.id)? {
+                    self.stats.relationships_deleted += 1;
+                }
+            }
+            if self.db.delete_node(id)? {
+                self.stats.nodes_deleted += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reload nodes and relationships after an update changed them
+    fn refresh(&self, records: &mut [Record]) {
+        fn refresh_value(db: &GraphDB, value: &mut Value) {
+            match value {
+                Value::Node(node) => {
+                    if let Some(fresh) = db.get_node(&node.id) {
+                        *node = fresh;
+                    }
+                }
+                Value::Relationship(edge) => {
+                    if let Some(fresh) = db.get_edge(&edge.id) {
+                        *edge = fresh;
+                    }
+                }
+                Value::List(items) => items.iter_mut().for_each(|v| refresh_value(db, v)),
+                Value::Map(map) => map.values_mut().for_each(|v| refresh_value(db, v)),
+                Value::Path(path) => {
+                    for node in &mut path.nodes {
+                        if let Some(fresh) = db.get_node(&node.id) {
+                            *node = fresh;
+                        }
+                    }
+                    for edge in &mut path.relationships {
+                        if let Some(fresh) = db.get_edge(&edge.id) {
+                            *edge = fresh;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for record in records {
+            for value in record.values_mut() {
+                refresh_value(self.db, value);
+            }
+        }
+    }
+
+    // Expressions
+
+    fn eval_count(&self, expression: &Expression, clause: &str) -> Result<usize> {
+        match self.eval(expression, &Record::new())? {
+            Value::Integer(n) if n >= 0 => Ok(n as usize),
+            other => Err(exec_error(format!(
+                "{} expects a non-negative integer, got {}",
+                clause,
+                type_name(&other)
+            ))),
+        }
+    }
+
+    fn eval(&self, expression: &Expression, record: &Record) -> Result<Value> {
+        Ok(match expression {
+            Expression::Integer(i) => Value::Integer(*i),
+            Expression::Float(f) => Value::Float(*f),
+            Expression::String(s) => Value::String(s.clone()),
+            Expression::Boolean(b) => Value::Boolean(*b),
+            Expression::Null => Value::Null,
+            Expression::Variable(name) => {
+                if let Some(param) = name.strip_prefix('$') {
+                    return self
+                        .params
+                        .get(param)
+                        .cloned()
+                        .map(Value::from)
+                        .ok_or_else(|| exec_error(format!("Expected parameter ${}", param)));
+                }
+                record
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| exec_error(format!("Variable `{}` not defined", name)))?
+            }
+            Expression::Property { object, property } => match self.eval(object, record)? {
+                Value::Null => Value::Null,
+                Value::Node(node) => node
+                    .properties
+                    .get(property)
+                    .cloned()
+                    .map(Value::from)
+                    .unwrap_or(Value::Null),
+                Value::Relationship(edge) => edge
+                    .properties
+                    .get(property)
+                    .cloned()
+                    .map(Value::from)
+                    .unwrap_or(Value::Null),
+                Value::Map(mut map) => map.remove(property).unwrap_or(Value::Null),
+                other => {
+                    return Err(exec_error(format!(
+                        "Cannot read property `{}` of {}",
+                        property,
+                        type_name(&other)
+                    )))
+                }
+            },
+            Expression::List(items) => Value::List(
+                items
+                    .iter()
+                    .map(|e| self.eval(e, record))
+                    .collect::<Result<_>>()?,
+            ),
+            Expression::Map(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(k, e)| Ok((k.clone(), self.eval(e, record)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Expression::BinaryOp { left, op, right } => {
+                self.eval_binary(left, *op, right, record)?
+            }
+            Expression::UnaryOp { op, operand } => {
+                let value = self.eval(operand, record)?;
+                match op {
+                    UnaryOperator::Not => match truth(&value)? {
+                        Some(b) => Value::Boolean(!b),
+                        None => Value::Null,
+                    },
+                    UnaryOperator::Minus => match value {
+                        Value::Integer(i) => Value::Integer(
+                            i.checked_neg()
+                                .ok_or_else(|| exec_error("Integer overflow"))?,
+                        ),
+                        Value::Float(f) => Value::Float(-f),
+                        Value::Null => Value::Null,
+                        other => {
+                            return Err(exec_error(format!("Cannot negate {}", type_name(&other))))
+                        }
+                    },
+                    UnaryOperator::Plus => match value {
+                        Value::Integer(_) | Value::Float(_) | Value::Null => value,
+                        other => {
+                            return Err(exec_error(format!(
+                                "Unary plus expects a number, got {}",
+                                type_name(&other)
+                            )))
+                        }
+                    },
+                    UnaryOperator::IsNull => Value::Boolean(value.is_null()),
+                    UnaryOperator::IsNotNull => Value::Boolean(!value.is_null()),
+                }
+            }
+            Expression::FunctionCall { name, args } => self.call_function(name, args, record)?,
+            Expression::Aggregation { .. } => {
+                return Err(exec_error(format!(
+                    "Aggregation is not allowed here: {}",
+                    expression
+                )))
+            }
+            Expression::PatternPredicate(_) => {
+                return Err(exec_error("Pattern predicates are not supported"))
+            }
+            Expression::Case {
+                expression,
+                alternatives,
+                default,
+            } => {
+                let subject = match expression {
+                    Some(e) => Some(self.eval(e, record)?),
+                    None => None,
+                };
+                for (when, then) in alternatives {
+                    let condition = self.eval(when, record)?;
+                    let taken = match &subject {
+                        Some(subject) => values_equal(subject, &condition) == Some(true),
+                        None => condition == Value::Boolean(true),
+                    };
+                    if taken {
+                        return self.eval(then, record);
+                    }
+                }
+                match default {
+                    Some(e) => self.eval(e, record)?,
+                    None => Value::Null,
+                }
+            }
+        })
+    }
+
+    fn eval_binary(
+        &self,
+        left: &Expression,
+        op: BinaryOperator,
+        right: &Expression,
+        record: &Record,
+    ) -> Result<Value> {
+        // Logical operators short-circuit where the result is already known
+        match op {
+            BinaryOperator::And => {
+                let l = truth(&self.eval(left, record)?)?;
+                if l == Some(false) {
+                    return Ok(Value::Boolean(false));
+                }
+                let r = truth(&self.eval(right, record)?)?;
+                return Ok(match (l, r) {
+                    (_, Some(false)) => Value::Boolean(false),
+                    (Some(true), Some(true)) => Value::Boolean(true),
+                    _ => Value::Null,
+                });
+            }
+            BinaryOperator::Or => {
+                let l = truth(&self.eval(left, record)?)?;
+                if l == Some(true) {
+                    return Ok(Value::Boolean(true));
+                }
+                let r = truth(&self.eval(right, record)?)?;
+                return Ok(match (l, r) {
+                    (_, Some(true)) => Value::Boolean(true),
+                    (Some(false), Some(false)) => Value::Boolean(false),
+                    _ => Value::Null,
+                });
+            }
+            BinaryOperator::Xor => {
+                let l = truth(&self.eval(left, record)?)?;
+                let r = truth(&self.eval(right, record)?)?;
+                return Ok(match (l, r) {
+                    (Some(a), Some(b)) => Value::Boolean(a ^ b),
+                    _ => Value::Null,
+                });
+            }
+            _ => {}
+        }
+
+        let l = self.eval(left, record)?;
+        let r = self.eval(right, record)?;
+        let boolean = |b: Option<bool>| b.map(Value::Boolean).unwrap_or(Value::Null);
+        let compare = |accept: fn(Ordering) -> bool| boolean(compare_values(&l, &r).map(accept));
+
+        Ok(match op {
+            BinaryOperator::Add
+            | BinaryOperator::Subtract
+            | BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Modulo
+            | BinaryOperator::Power => arithmetic(op, l, r)?,
+            BinaryOperator::Equal => boolean(values_equal(&l, &r)),
+            BinaryOperator::NotEqual => boolean(values_equal(&l, &r).map(|b| !b)),
+            BinaryOperator::LessThan => compare(|o| o == Ordering::Less),
+            BinaryOperator::LessThanOrEqual => compare(|o| o != Ordering::Greater),
+            BinaryOperator::GreaterThan => compare(|o| o == Ordering::Greater),
+            BinaryOperator::GreaterThanOrEqual => compare(|o| o != Ordering::Less),
+            BinaryOperator::Contains | BinaryOperator::StartsWith | BinaryOperator::EndsWith => {
+                match (&l, &r) {
+                    (Value::String(a), Value::String(b)) => Value::Boolean(match op {
+                        BinaryOperator::Contains => a.contains(b.as_str()),
+                        BinaryOperator::StartsWith => a.starts_with(b.as_str()),
+                        _ => a.ends_with(b.as_str()),
+                    }),
+                    _ => Value::Null,
+                }
+            }
+            BinaryOperator::In => match r {
+                Value::Null => Value::Null,
+                Value::List(items) => {
+                    let mut unknown = false;
+                    for item in &items {
+                        match values_equal(&l, item) {
+                            Some(true) => return Ok(Value::Boolean(true)),
+                            Some(false) => {}
+                            None => unknown = true,
+                        }
+                    }
+                    if unknown {
+                        Value::Null
+                    } else {
+                        Value::Boolean(false)
+                    }
+                }
+                other => {
+                    return Err(exec_error(format!(
+                        "IN expects a list, got {}",
+                        type_name(&other)
+                    )))
+                }
+            },
+            BinaryOperator::Is => Value::Boolean(l == r),
+            BinaryOperator::IsNot => Value::Boolean(l != r),
+            BinaryOperator::Matches => {
+                return Err(exec_error("Regular expression matching is not supported"))
+            }
+            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => unreachable!(),
+        })
+    }
+
+    fn call_function(&self, name: &str, args: &[Expression], record: &Record) -> Result<Value> {
+        let function = name.to_lowercase();
+
+        // coalesce only evaluates arguments up to the first non-null one
+        if function == "coalesce" {
+            for arg in args {
+                let value = self.eval(arg, record)?;
+                if !value.is_null() {
+                    return Ok(value);
+                }
+            }
+            return Ok(Value::Null);
+        }
+
+        let values = args
+            .iter()
+            .map(|e| self.eval(e, record))
+            .collect::<Result<Vec<_>>>()?;
+        let arity = |min: usize, max: usize| -> Result<()> {
+            if values.len() < min || values.len() > max {
+                Err(exec_error(format!(
+                    "Wrong number of arguments for {}(): {}",
+                    name,
+                    values.len()
+                )))
+            } else {
+                Ok(())
+            }
+        };
+        let wrong_type = |value: &Value| {
+            exec_error(format!(
+                "Invalid argument for {}(): {}",
+                name,
+                type_name(value)
+            ))
+        };
+
+        match function.as_str() {
+            "exists" => {
+                arity(1, 1)?;
+                return Ok(Value::Boolean(!values[0].is_null()));
+            }
+            "timestamp" => {
+                arity(0, 0)?;
+                let millis = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as i64)
+                    .unwrap_or(0);
+                return Ok(Value::Integer(millis));
+            }
+            _ => {}
+        }
+
+        // Everything else returns null for null arguments
+        if values.iter().any(Value::is_null) {
+            return Ok(Value::Null);
+        }
+
+        let first = values.first();
+        Ok(match function.as_str() {
+            "id" | "elementid" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Node(node) => Value::String(node.id.clone()),
+                    Value::Relationship(edge) => Value::String(edge.id.clone()),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "labels" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Node(node) => Value::List(
+                        node.labels
+                            .iter()
+                            .map(|l| Value::String(l.name.clone()))
+                            .collect(),
+                    ),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "type" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Relationship(edge) => Value::String(edge.edge_type.clone()),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "properties" | "keys" => {
+                arity(1, 1)?;
+                let map: HashMap<String, Value> = match &values[0] {
+                    Value::Node(node) => node
+                        .properties
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Value::from(v.clone())))
+                        .collect(),
+                    Value::Relationship(edge) => edge
+                        .properties
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Value::from(v.clone())))
+                        .collect(),
+                    Value::Map(map) => map.clone(),
+                    other => return Err(wrong_type(other)),
+                };
+                if function == "properties" {
+                    Value::Map(map)
+                } else {
+                    let mut keys: Vec<String> = map.into_keys().collect();
+                    keys.sort();
+                    Value::List(keys.into_iter().map(Value::String).collect())
+                }
+            }
+            "size" | "length" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::List(items) => Value::Integer(items.len() as i64),
+                    Value::String(s) => Value::Integer(s.chars().count() as i64),
+                    Value::Path(path) => Value::Integer(path.len() as i64),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "nodes" | "relationships" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Path(path) if function == "nodes" => {
+                        Value::List(path.nodes.iter().cloned().map(Value::Node).collect())
+                    }
+                    Value::Path(path) => Value::List(
+                        path.relationships
+                            .iter()
+                            .cloned()
+                            .map(Value::Relationship)
+                            .collect(),
+                    ),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "startnode" | "endnode" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Relationship(edge) => {
+                        let id = if function == "startnode" {
+                            &edge.from
+                        } else {
+                            &edge.to
+                        };
+                        self.db.get_node(id).map(Value::Node).unwrap_or(Value::Null)
+                    }
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "head" | "last" | "tail" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::List(items) => match function.as_str() {
+                        "head" => items.first().cloned().unwrap_or(Value::Null),
+                        "last" => items.last().cloned().unwrap_or(Value::Null),
+                        _ => Value::List(items.iter().skip(1).cloned().collect()),
+                    },
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "range" => {
+                arity(2, 3)?;
+                let bound = |v: &Value| v.as_i64().ok_or_else(|| wrong_type(v));
+                let start = bound(&values[0])?;
+                let end = bound(&values[1])?;
+                let step = match values.get(2) {
+                    Some(v) => bound(v)?,
+                    None => 1,
+                };
+                if step == 0 {
+                    return Err(exec_error("range() step cannot be zero"));
+                }
+                let mut items = Vec::new();
+                let mut current = start;
+                while (step > 0 && current <= end) || (step < 0 && current >= end) {
+                    items.push(Value::Integer(current));
+                    current = match current.checked_add(step) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+                Value::List(items)
+            }
+            "tostring" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::String(s) => Value::String(s.clone()),
+                    Value::Integer(i) => Value::String(i.to_string()),
+                    Value::Float(f) => Value::String(f.to_string()),
+                    Value::Boolean(b) => Value::String(b.to_string()),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "tointeger" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Integer(i) => Value::Integer(*i),
+                    Value::Float(f) => Value::Integer(f.trunc() as i64),
+                    Value::Boolean(b) => Value::Integer(*b as i64),
+                    Value::String(s) => s
+                        .trim()
+                        .parse::<i64>()
+                        .ok()
+                        .or_else(|| s.trim().parse::<f64>().ok().map(|f| f.trunc() as i64))
+                        .map(Value::Integer)
+                        .unwrap_or(Value::Null),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "tofloat" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Integer(i) => Value::Float(*i as f64),
+                    Value::Float(f) => Value::Float(*f),
+                    Value::String(s) => s
+                        .trim()
+                        .parse::<f64>()
+                        .map(Value::Float)
+                        .unwrap_or(Value::Null),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "toboolean" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Boolean(b) => Value::Boolean(*b),
+                    Value::String(s) => match s.trim().to_lowercase().as_str() {
+                        "true" => Value::Boolean(true),
+                        "false" => Value::Boolean(false),
+                        _ => Value::Null,
+                    },
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "tolower" | "lower" | "toupper" | "upper" | "trim" | "ltrim" | "rtrim" => {
+                arity(1, 1)?;
+                let s = values[0].as_str().ok_or_else(|| wrong_type(&values[0]))?;
+                Value::String(match function.as_str() {
+                    "tolower" | "lower" => s.to_lowercase(),
+                    "toupper" | "upper" => s.to_uppercase(),
+                    "trim" => s.trim().to_string(),
+                    "ltrim" => s.trim_start().to_string(),
+                    _ => s.trim_end().to_string(),
+                })
+            }
+            "reverse" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::String(s) => Value::String(s.chars().rev().collect()),
+                    Value::List(items) => Value::List(items.iter().rev().cloned().collect()),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "substring" | "left" | "right" => {
+                let s = first
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| exec_error(format!("{}() expects a string", name)))?;
+                let chars: Vec<char> = s.chars().collect();
+                let index = |v: &Value| -> Result<usize> {
+                    match v {
+                        Value::Integer(i) if *i >= 0 => Ok(*i as usize),
+                        other => Err(wrong_type(other)),
+                    }
+                };
+                let (start, length) = match function.as_str() {
+                    "substring" => {
+                        arity(2, 3)?;
+                        let start = index(&values[1])?;
+                        let length = match values.get(2) {
+                            Some(v) => index(v)?,
+                            None => usize::MAX,
+                        };
+                        (start, length)
+                    }
+                    "left" => {
+                        arity(2, 2)?;
+                        (0, index(&values[1])?)
+                    }
+                    _ => {
+                        arity(2, 2)?;
+                        let length = index(&values[1])?.min(chars.len());
+                        (chars.len() - length, length)
+                    }
+                };
+                Value::String(chars.iter().skip(start).take(length).collect())
+            }
+            "replace" => {
+                arity(3, 3)?;
+                match (&values[0], &values[1], &values[2]) {
+                    (Value::String(s), Value::String(from), Value::String(to)) => {
+                        Value::String(s.replace(from.as_str(), to))
+                    }
+                    _ => return Err(exec_error("replace() expects strings")),
+                }
+            }
+            "split" => {
+                arity(2, 2)?;
+                match (&values[0], &values[1]) {
+                    (Value::String(s), Value::String(delimiter)) => Value::List(
+                        s.split(delimiter.as_str())
+                            .map(|part| Value::String(part.to_string()))
+                            .collect(),
+                    ),
+                    _ => return Err(exec_error("split() expects strings")),
+                }
+            }
+            "abs" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Integer(i) => Value::Integer(
+                        i.checked_abs()
+                            .ok_or_else(|| exec_error("Integer overflow"))?,
+                    ),
+                    Value::Float(f) => Value::Float(f.abs()),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "sign" => {
+                arity(1, 1)?;
+                match &values[0] {
+                    Value::Integer(i) => Value::Integer(i.signum()),
+                    Value::Float(f) if *f == 0.0 => Value::Integer(0),
+                    Value::Float(f) => Value::Integer(f.signum() as i64),
+                    other => return Err(wrong_type(other)),
+                }
+            }
+            "ceil" | "floor" | "round" | "sqrt" | "exp" | "log" | "log10" => {
+                arity(1, 1)?;
+                let x = values[0].as_f64().ok_or_else(|| wrong_type(&values[0]))?;
+                Value::Float(match function.as_str() {
+                    "ceil" => x.ceil(),
+                    "floor" => x.floor(),
+                    "round" => x.round(),
+                    "sqrt" => x.sqrt(),
+                    "exp" => x.exp(),
+                    "log" => x.ln(),
+                    _ => x.log10(),
+                })
+            }
+            "vector.similarity" | "vector.similarity.cosine" | "vector.similarity.euclidean" => {
+                arity(2, 2)?;
+                let vector = |value: &Value| match value {
+                    Value::List(items) => items
+                        .iter()
+                        .map(|item| item.as_f64().ok_or_else(|| wrong_type(item)))
+                        .collect::<Result<Vec<f64>>>(),
+                    other => Err(wrong_type(other)),
+                };
+                let (a, b) = (vector(&values[0])?, vector(&values[1])?);
+                if a.len() != b.len() {
+                    return Err(exec_error(format!(
+                        "{}() needs vectors of the same dimension, got {} and {}",
+                        name,
+                        a.len(),
+                        b.len()
+                    )));
+                }
+                if function == "vector.similarity.euclidean" {
+                    let distance = a
+                        .iter()
+                        .zip(&b)
+                        .map(|(x, y)| (x - y).powi(2))
+                        .sum::<f64>()
+                        .sqrt();
+                    Value::Float(1.0 / (1.0 + distance))
+                } else {
+                    let dot: f64 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+                    let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+                    let norms = norm(&a) * norm(&b);
+                    // Undefined for zero vectors
+                    if norms == 0.0 {
+                        Value::Null
+                    } else {
+                        Value::Float(dot / norms)
+                    }
+                }
+            }
+            _ => return Err(exec_error(format!("Unknown function `{}`", name))),
+        })
+    }
+}
+
+/// Node or relationship being updated
+enum Entity {
+    Node(Node),
+    Relationship(Edge),
+}
+
+impl Entity {
+    fn properties_mut(&mut self) -> &mut Properties {
+        match self {
+            Entity::Node(node) => &mut node.properties,
+            Entity::Relationship(edge) => &mut edge.properties,
+        }
+    }
+}
+
+/// Accumulates the values of one aggregation within one group
+struct Accumulator {
+    function: AggregationFunction,
+    argument: Expression,
+    distinct: bool,
+    seen: HashSet<ValueKey>,
+    values: Vec<Value>,
+}
+
+impl Accumulator {
+    fn new(aggregation: &Expression) -> Result<Self> {
+        let Expression::Aggregation {
+            function,
+            expression,
+            distinct,
+        } = aggregation
+        else {
+            return Err(exec_error(format!(
+                "Expected an aggregation, got {}",
+                aggregation
+            )));
+        };
+        Ok(Self {
+            function: *function,
+            argument: expression.as_ref().clone(),
+            distinct: *distinct,
+            seen: HashSet::new(),
+            values: Vec::new(),
+        })
+    }
+
+    /// Aggregations ignore nulls
+    fn add(&mut self, value: Value) {
+        if value.is_null() || (self.distinct && !self.seen.insert(ValueKey::from(&value))) {
+            return;
+        }
+        self.values.push(value);
+    }
+
+    fn finish(self) -> Result<Value> {
+        let numbers = |values: &[Value]| -> Result<Vec<f64>> {
+            values
+                .iter()
+                .map(|v| {
+                    v.as_f64().ok_or_else(|| {
+                        exec_error(format!(
+                            "Expected numbers to aggregate, got {}",
+                            type_name(v)
+                        ))
+                    })
+                })
+                .collect()
+        };
+
+        Ok(match self.function {
+            AggregationFunction::Count => Value::Integer(self.values.len() as i64),
+            AggregationFunction::Collect => Value::List(self.values),
+            AggregationFunction::Sum => {
+                if self.values.iter().all(|v| matches!(v, Value::Integer(_))) {
+                    let mut total: i64 = 0;
+                    for value in &self.values {
+                        total = total
+                            .checked_add(value.as_i64().unwrap_or(0))
+                            .ok_or_else(|| exec_error("Integer overflow in sum()"))?;
+                    }
+                    Value::Integer(total)
+                } else {
+                    Value::Float(numbers(&self.values)?.iter().sum())
+                }
+            }
+            AggregationFunction::Avg => {
+                let numbers = numbers(&self.values)?;
+                if numbers.is_empty() {
+                    Value::Null
+                } else {
+                    Value::Float(numbers.iter().sum::<f64>() / numbers.len() as f64)
+                }
+            }
+            AggregationFunction::Min => self
+                .values
+                .into_iter()
+                .min_by(order_values)
+                .unwrap_or(Value::Null),
+            AggregationFunction::Max => self
+                .values
+                .into_iter()
+                .max_by(order_values)
+                .unwrap_or(Value::Null),
+            AggregationFunction::StdDev | AggregationFunction::StdDevP => {
+                let numbers = numbers(&self.values)?;
+                let n = numbers.len() as f64;
+                let sample = self.function == AggregationFunction::StdDev;
+                if numbers.is_empty() || (sample && numbers.len() < 2) {
+                    Value::Float(0.0)
+                } else {
+                    let mean = numbers.iter().sum::<f64>() / n;
+                    let squares: f64 = numbers.iter().map(|x| (x - mean).powi(2)).sum();
+                    Value::Float((squares / if sample { n - 1.0 } else { n }).sqrt())
+                }
+            }
+            AggregationFunction::Percentile => {
+                return Err(exec_error("Percentile aggregations are not supported"))
+            }
+        })
+    }
+}
+
+/// Hashable form of a value, used for DISTINCT and grouping
+///
+/// Integral floats hash like the equal integer, so `1` and `1.0` group together.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ValueKey {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(u64),
+    String(String),
+    List(Vec<ValueKey>),
+    Map(Vec<(String, ValueKey)>),
+    Node(String),
+    Relationship(String),
+    Path(Vec<String>),
+}
+
+impl From<&Value> for ValueKey {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => ValueKey::Null,
+            Value::Boolean(b) => ValueKey::Boolean(*b),
+            Value::Integer(i) => ValueKey::Integer(*i),
+            Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                ValueKey::Integer(*f as i64)
+            }
+            Value::Float(f) => ValueKey::Float(f.to_bits()),
+            Value::String(s) => ValueKey::String(s.clone()),
+            Value::List(items) => ValueKey::List(items.iter().map(ValueKey::from).collect()),
+            Value::Map(map) => {
+                let mut entries: Vec<_> = map
+                    .iter()
+                    .map(|(k, v)| (k.clone(), ValueKey::from(v)))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                ValueKey::Map(entries)
+            }
+            Value::Node(node) => ValueKey::Node(node.id.clone()),
+            Value::Relationship(edge) => ValueKey::Relationship(edge.id.clone()),
+            Value::Path(path) => ValueKey::Path(
+                path.nodes
+                    .iter()
+                    .map(|n| n.id.clone())
+                    .chain(path.relationships.iter().map(|r| r.id.clone()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+fn select(mut record: Record, columns: &[String]) -> Record {
+    columns
+        .iter()
+        .map(|c| (c.clone(), record.remove(c).unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "NULL",
+        Value::Boolean(_) => "BOOLEAN",
+        Value::Integer(_) => "INTEGER",
+        Value::Float(_) => "FLOAT",
+        Value::String(_) => "STRING",
+        Value::List(_) => "LIST",
+        Value::Map(_) => "MAP",
+        Value::Node(_) => "NODE",
+        Value::Relationship(_) => "RELATIONSHIP",
+        Value::Path(_) => "PATH",
+    }
+}
+
+/// Record of a SHOW command: the name followed by the other columns
+fn schema_record<const N: usize>(command: &SchemaCommand, name: Value, row: [Value; N]) -> Record {
+    command
+        .columns()
+        .iter()
+        .map(|c| c.to_string())
+        .zip(std::iter::once(name).chain(row))
+        .collect()
+}
+
+fn to_property(value: &Value) -> Result<PropertyValue> {
+    value.to_property_value().ok_or_else(|| {
+        exec_error(format!(
+            "{} cannot be stored as a property value",
+            type_name(value)
+        ))
+    })
+}
+
+/// Truth value of a predicate result; null is unknown
+fn truth(value: &Value) -> Result<Option<bool>> {
+    match value {
+        Value::Boolean(b) => Ok(Some(*b)),
+        Value::Null => Ok(None),
+        other => Err(exec_error(format!(
+            "Expected a boolean, got {}",
+            type_name(other)
+        ))),
+    }
+}
+
+/// Cypher equality: `None` when the result is unknown because of nulls
+fn values_equal(a: &Value, b: &Value) -> Option<bool> {
+    fn all_equal<'v>(pairs: impl Iterator<Item = (&'v Value, &'v Value)>) -> Option<bool> {
+        let mut result = Some(true);
+        for (x, y) in pairs {
+            match values_equal(x, y) {
+                Some(false) => return Some(false),
+                None => result = None,
+                Some(true) => {}
+            }
+        }
+        result
+    }
+
+    match (a, b) {
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::Integer(x), Value::Float(y)) | (Value::Float(y), Value::Integer(x)) => {
+            Some(*x as f64 == *y)
+        }
+        (Value::List(x), Value::List(y)) => {
+            if x.len() != y.len() {
+                Some(false)
+            } else {
+                all_equal(x.iter().zip(y))
+            }
+        }
+        (Value::Map(x), Value::Map(y)) => {
+            if x.len() != y.len() || !x.keys().all(|k| y.contains_key(k)) {
+                Some(false)
+            } else {
+                all_equal(x.iter().map(|(k, v)| (v, &y[k])))
+            }
+        }
+        _ => Some(a == b),
+    }
+}
+
+/// Ordering for `<`, `>` and friends; `None` when the values are not comparable
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => Some(x.cmp(y)),
+        (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => {
+            a.as_f64()?.partial_cmp(&b.as_f64()?)
+        }
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        (Value::Boolean(x), Value::Boolean(y)) => Some(x.cmp(y)),
+        (Value::List(x), Value::List(y)) => {
+            for (i, j) in x.iter().zip(y) {
+                match compare_values(i, j)? {
+                    Ordering::Equal => {}
+                    other => return Some(other),
+                }
+            }
+            Some(x.len().cmp(&y.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Total order used by ORDER BY, min() and max(); nulls sort last
+fn order_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Map(_) => 0,
+            Value::Node(_) => 1,
+            Value::Relationship(_) => 2,
+            Value::List(_) => 3,
+            Value::Path(_) => 4,
+            Value::String(_) => 5,
+            Value::Boolean(_) => 6,
+            Value::Integer(_) | Value::Float(_) => 7,
+            Value::Null => 8,
+        }
+    }
+
+    match (a, b) {
+        (Value::Integer(x), Value::Integer(y)) => x.cmp(y),
+        (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => {
+            let (x, y) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            x.total_cmp(&y)
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Boolean(x), Value::Boolean(y)) => x.cmp(y),
+        (Value::List(x), Value::List(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(i, j)| order_values(i, j))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Node(x), Value::Node(y)) => x.id.cmp(&y.id),
+        (Value::Relationship(x), Value::Relationship(y)) => x.id.cmp(&y.id),
+        (Value::Path(x), Value::Path(y)) => x.len().cmp(&y.len()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn arithmetic(op: BinaryOperator, l: Value, r: Value) -> Result<Value> {
+    let overflow = || exec_error("Integer overflow");
+
+    Ok(match (op, l, r) {
+        (_, Value::Null, _) | (_, _, Value::Null) => Value::Null,
+        (BinaryOperator::Add, Value::String(a), Value::String(b)) => Value::String(a + &b),
+        (BinaryOperator::Add, Value::String(a), b @ (Value::Integer(_) | Value::Float(_))) => {
+            Value::String(format!("{}{}", a, scalar_string(&b)))
+        }
+        (BinaryOperator::Add, a @ (Value::Integer(_) | Value::Float(_)), Value::String(b)) => {
+            Value::String(format!("{}{}", scalar_string(&a), b))
+        }
+        (BinaryOperator::Add, Value::List(mut a), Value::List(b)) => {
+            a.extend(b);
+            Value::List(a)
+        }
+        (BinaryOperator::Add, Value::List(mut a), b) => {
+            a.push(b);
+            Value::List(a)
+        }
+        (BinaryOperator::Add, a, Value::List(mut b)) => {
+            b.insert(0, a);
+            Value::List(b)
+        }
+        (op, Value::Integer(a), Value::Integer(b)) => match op {
+            BinaryOperator::Add => Value::Integer(a.checked_add(b).ok_or_else(overflow)?),
+            BinaryOperator::Subtract => Value::Integer(a.checked_sub(b).ok_or_else(overflow)?),
+            BinaryOperator::Multiply => Value::Integer(a.checked_mul(b).ok_or_else(overflow)?),
+            BinaryOperator::Divide | BinaryOperator::Modulo if b == 0 => {
+                return Err(exec_error("Division by zero"))
+            }
+            BinaryOperator::Divide => Value::Integer(a.checked_div(b).ok_or_else(overflow)?),
+            BinaryOperator::Modulo => Value::Integer(a.checked_rem(b).ok_or_else(overflow)?),
+            _ => Value::Float((a as f64).powf(b as f64)),
+        },
+        (
+            op,
+            a @ (Value::Integer(_) | Value::Float(_)),
+            b @ (Value::Integer(_) | Value::Float(_)),
+        ) => {
+            let (x, y) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            Value::Float(match op {
+                BinaryOperator::Add => x + y,
+                BinaryOperator::Subtract => x - y,
+                BinaryOperator::Multiply => x * y,
+                BinaryOperator::Divide => x / y,
+                BinaryOperator::Modulo => x % y,
+                _ => x.powf(y),
+            })
+        }
+        (op, a, b) => {
+            return Err(exec_error(format!(
+                "Cannot apply {:?} to {} and {}",
+                op,
+                type_name(&a),
+                type_name(&b)
+            )))
+        }
+    })
+}
+
+fn scalar_string(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_three_valued_equality() {
+        assert_eq!(
+            values_equal(&Value::Integer(1), &Value::Float(1.0)),
+            Some(true)
+        );
+        assert_eq!(values_equal(&Value::Null, &Value::Integer(1)), None);
+        assert_eq!(
+            values_equal(
+                &Value::List(vec![Value::Integer(1), Value::Null]),
+                &Value::List(vec![Value::Integer(2), Value::Null]),
+            ),
+            Some(false)
+        );
+        assert_eq!(compare_values(&Value::from("a"), &Value::Integer(1)), None);
+    }
+
+    #[test]
+    fn test_order_puts_nulls_last() {
+        let mut values = vec![
+            Value::Null,
+            Value::Integer(3),
+            Value::Float(1.5),
+            Value::from("b"),
+        ];
+        values.sort_by(order_values);
+        assert_eq!(
+            values,
+            vec![
+                Value::from("b"),
+                Value::Float(1.5),
+                Value::Integer(3),
+                Value::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn test_integer_arithmetic_is_checked() {
+        assert!(arithmetic(
+            BinaryOperator::Add,
+            Value::Integer(i64::MAX),
+            Value::Integer(1)
+        )
+        .is_err());
+        assert!(arithmetic(BinaryOperator::Divide, Value::Integer(1), Value::Integer(0)).is_err());
+        assert_eq!(
+            arithmetic(BinaryOperator::Divide, Value::Integer(7), Value::Integer(2)).unwrap(),
+            Value::Integer(3)
+        );
+    }
+}
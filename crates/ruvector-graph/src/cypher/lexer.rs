@@ -68,6 +68,8 @@ pub enum TokenKind {
     False,
     OnCreate,
     OnMatch,
+    Call,
+    Yield,
 
     // Identifiers and literals
     Identifier(String),
@@ -196,6 +198,21 @@ fn parse_token(input: &str) -> IResult<&str, (TokenKind, &str)> {
 fn parse_keyword(input: &str) -> IResult<&str, (TokenKind, &str)> {
     let (input, _) = multispace0(input)?;
 
+    let (rest, keyword) = keyword_token(input)?;
+
+    // A keyword must end at a word boundary, otherwise `order` would lex as
+    // `OR` followed by `der`
+    if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        )));
+    }
+
+    Ok((rest, keyword))
+}
+
+fn keyword_token(input: &str) -> IResult<&str, (TokenKind, &str)> {
     // Split into nested alt() calls since nom's alt() supports max 21 alternatives
     alt((
         alt((
@@ -239,6 +256,8 @@ fn parse_keyword(input: &str) -> IResult<&str, (TokenKind, &str)> {
             map(tag_no_case("TRUE"), |s: &str| (TokenKind::True, s)),
             map(tag_no_case("FALSE"), |s: &str| (TokenKind::False, s)),
             map(tag_no_case("AS"), |s: &str| (TokenKind::As, s)),
+            map(tag_no_case("CALL"), |s: &str| (TokenKind::Call, s)),
+            map(tag_no_case("YIELD"), |s: &str| (TokenKind::Yield, s)),
         )),
     ))(input)
 }
@@ -417,6 +436,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_keywords_need_word_boundary() {
+        let tokens = tokenize("n.order n.created isActive ascending AS").unwrap();
+        assert_eq!(tokens[2].kind, TokenKind::Identifier("order".to_string()));
+        assert_eq!(tokens[5].kind, TokenKind::Identifier("created".to_string()));
+        assert_eq!(
+            tokens[6].kind,
+            TokenKind::Identifier("isActive".to_string())
+        );
+        assert_eq!(
+            tokens[7].kind,
+            TokenKind::Identifier("ascending".to_string())
+        );
+        assert_eq!(tokens[8].kind, TokenKind::As);
+    }
+
     #[test]
     fn test_tokenize_operators() {
         let tokens = tokenize("-> <- = <> >= <=").unwrap();
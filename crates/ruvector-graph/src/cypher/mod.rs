@@ -5,16 +5,29 @@
 //! - Syntax parsing (AST generation)
 //! - Semantic analysis and type checking
 //! - Query optimization
+//! - Logical planning over the label, property and adjacency indexes
+//! - Execution producing typed result rows
+//! - Built-in procedures such as the `algo.*` graph algorithms (CALL ... YIELD)
 //! - Support for hyperedges (N-ary relationships)
 
 pub mod ast;
+pub mod engine;
 pub mod lexer;
 pub mod optimizer;
 pub mod parser;
+pub mod plan;
+pub mod planner;
+pub mod procedures;
+pub mod result;
 pub mod semantic;
 
 pub use ast::{Query, Statement};
+pub use engine::QueryEngine;
 pub use lexer::{Token, TokenKind};
 pub use optimizer::{OptimizationPlan, QueryOptimizer};
 pub use parser::{parse_cypher, ParseError};
+pub use plan::{PlanOperator, QueryPlan};
+pub use planner::QueryPlanner;
+pub use procedures::ProcedureSignature;
+pub use result::{Path, QueryResult, QueryStats, Row, Value};
 pub use semantic::{SemanticAnalyzer, SemanticError};
@@ -173,19 +173,19 @@ impl QueryOptimizer {
         match (left, op, right) {
             // Arithmetic operations
             (Expression::Integer(a), BinaryOperator::Add, Expression::Integer(b)) => {
-                Some(Expression::Integer(a + b))
+                a.checked_add(*b).map(Expression::Integer)
             }
             (Expression::Integer(a), BinaryOperator::Subtract, Expression::Integer(b)) => {
-                Some(Expression::Integer(a - b))
+                a.checked_sub(*b).map(Expression::Integer)
             }
             (Expression::Integer(a), BinaryOperator::Multiply, Expression::Integer(b)) => {
-                Some(Expression::Integer(a * b))
+                a.checked_mul(*b).map(Expression::Integer)
             }
             (Expression::Integer(a), BinaryOperator::Divide, Expression::Integer(b)) if *b != 0 => {
-                Some(Expression::Integer(a / b))
+                a.checked_div(*b).map(Expression::Integer)
             }
             (Expression::Integer(a), BinaryOperator::Modulo, Expression::Integer(b)) if *b != 0 => {
-                Some(Expression::Integer(a % b))
+                a.checked_rem(*b).map(Expression::Integer)
             }
             (Expression::Float(a), BinaryOperator::Add, Expression::Float(b)) => {
                 Some(Expression::Float(a + b))
@@ -270,7 +270,9 @@ impl QueryOptimizer {
     ) -> Option<Expression> {
         match (op, operand) {
             (UnaryOperator::Not, Expression::Boolean(b)) => Some(Expression::Boolean(!b)),
-            (UnaryOperator::Minus, Expression::Integer(n)) => Some(Expression::Integer(-n)),
+            (UnaryOperator::Minus, Expression::Integer(n)) => {
+                n.checked_neg().map(Expression::Integer)
+            }
             (UnaryOperator::Minus, Expression::Float(n)) => Some(Expression::Float(-n)),
             _ => None,
         }
@@ -429,6 +431,10 @@ impl QueryOptimizer {
                 cost
             }
             Statement::With(_) => 15.0,
+            // Graph algorithms usually visit every node
+            Statement::Call(_) => 1000.0,
+            // Index builds scan one label
+            Statement::Schema(_) => 100.0,
         }
     }
 
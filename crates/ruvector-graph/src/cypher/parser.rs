@@ -4,6 +4,7 @@
 
 use super::ast::*;
 use super::lexer::{tokenize, Token, TokenKind};
+use crate::schema::{ConstraintKind, IndexKind};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -106,6 +107,10 @@ impl Parser {
     }
 
     fn parse_statement(&mut self) -> ParseResult<Statement> {
+        if self.is_schema_command() {
+            return Ok(Statement::Schema(self.parse_schema_command()?));
+        }
+
         match &self.peek().kind {
             TokenKind::Match | TokenKind::OptionalMatch => {
                 Ok(Statement::Match(self.parse_match()?))
@@ -119,6 +124,7 @@ impl Parser {
             TokenKind::Remove => Ok(Statement::Remove(self.parse_remove()?)),
             TokenKind::Return => Ok(Statement::Return(self.parse_return()?)),
             TokenKind::With => Ok(Statement::With(self.parse_with()?)),
+            TokenKind::Call => Ok(Statement::Call(self.parse_call()?)),
             _ => {
                 let token = self.peek();
                 Err(ParseError::UnexpectedToken {
@@ -592,6 +598,19 @@ impl Parser {
                         variable: var,
                         value,
                     });
+                } else if self.check(&TokenKind::Colon) {
+                    // SET n:Label1:Label2
+                    let mut labels = vec![];
+                    while self.match_token(&[TokenKind::Colon]) {
+                        if let TokenKind::Identifier(label) = &self.peek().kind {
+                            labels.push(label.clone());
+                            self.advance();
+                        }
+                    }
+                    items.push(SetItem::Labels {
+                        variable: var,
+                        labels,
+                    });
                 }
             }
 
@@ -729,6 +748,94 @@ impl Parser {
         })
     }
 
+    fn parse_call(&mut self) -> ParseResult<CallClause> {
+        self.consume(TokenKind::Call, "CALL")?;
+
+        // Dotted name; later segments may be keywords, as in `db.index.match`
+        let mut procedure = self.parse_identifier("procedure name")?;
+        while self.match_token(&[TokenKind::Dot]) {
+            let token = self.peek();
+            let is_word = !token.lexeme.is_empty()
+                && token
+                    .lexeme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !is_word || matches!(token.kind, TokenKind::Integer(_) | TokenKind::Float(_)) {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "procedure name".to_string(),
+                    found: token.kind.to_string(),
+                    line: token.position.line,
+                    column: token.position.column,
+                });
+            }
+            let segment = token.lexeme.clone();
+            procedure.push('.');
+            procedure.push_str(&segment);
+            self.advance();
+        }
+
+        let mut arguments = vec![];
+        if self.match_token(&[TokenKind::LeftParen]) {
+            if !self.check(&TokenKind::RightParen) {
+                loop {
+                    arguments.push(self.parse_expression()?);
+                    if !self.match_token(&[TokenKind::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume(TokenKind::RightParen, ")")?;
+        }
+
+        let mut yield_items = None;
+        let mut where_clause = None;
+        if self.match_token(&[TokenKind::Yield]) {
+            let mut items = vec![];
+            loop {
+                let column = self.parse_identifier("yield column")?;
+                let alias = if self.match_token(&[TokenKind::As]) {
+                    Some(self.parse_identifier("alias")?)
+                } else {
+                    None
+                };
+                items.push(YieldItem { column, alias });
+                if !self.match_token(&[TokenKind::Comma]) {
+                    break;
+                }
+            }
+            yield_items = Some(items);
+
+            if self.match_token(&[TokenKind::Where]) {
+                where_clause = Some(WhereClause {
+                    condition: self.parse_expression()?,
+                });
+            }
+        }
+
+        Ok(CallClause {
+            procedure,
+            arguments,
+            yield_items,
+            where_clause,
+        })
+    }
+
+    fn parse_identifier(&mut self, expected: &str) -> ParseResult<String> {
+        let token = self.peek();
+        if let TokenKind::Identifier(name) = &token.kind {
+            let name = name.clone();
+            self.advance();
+            Ok(name)
+        } else {
+            Err(ParseError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: token.kind.to_string(),
+                line: token.position.line,
+                column: token.position.column,
+            })
+        }
+    }
+
     fn parse_return_items(&mut self) -> ParseResult<Vec<ReturnItem>> {
         let mut items = vec![];
 
@@ -820,10 +927,10 @@ impl Parser {
     }
 
     fn parse_and(&mut self) -> ParseResult<Expression> {
-        let mut expr = self.parse_comparison()?;
+        let mut expr = self.parse_not()?;
 
         while self.match_token(&[TokenKind::And]) {
-            let right = self.parse_comparison()?;
+            let right = self.parse_not()?;
             expr = Expression::BinaryOp {
                 left: Box::new(expr),
                 op: BinaryOperator::And,
@@ -834,9 +941,36 @@ impl Parser {
         Ok(expr)
     }
 
+    /// NOT binds looser than comparisons: `NOT n.age > 30` negates the comparison
+    fn parse_not(&mut self) -> ParseResult<Expression> {
+        if self.match_token(&[TokenKind::Not]) {
+            let operand = self.parse_not()?;
+            return Ok(Expression::UnaryOp {
+                op: UnaryOperator::Not,
+                operand: Box::new(operand),
+            });
+        }
+
+        self.parse_comparison()
+    }
+
     fn parse_comparison(&mut self) -> ParseResult<Expression> {
         let mut expr = self.parse_additive()?;
 
+        // IS NULL / IS NOT NULL
+        if self.match_token(&[TokenKind::Is]) {
+            let negated = self.match_token(&[TokenKind::Not]);
+            self.consume(TokenKind::Null, "NULL")?;
+            return Ok(Expression::UnaryOp {
+                op: if negated {
+                    UnaryOperator::IsNotNull
+                } else {
+                    UnaryOperator::IsNull
+                },
+                operand: Box::new(expr),
+            });
+        }
+
         if let Some(op) = self.parse_comparison_op() {
             let right = self.parse_additive()?;
             expr = Expression::BinaryOp {
@@ -849,8 +983,254 @@ impl Parser {
         Ok(expr)
     }
 
+    /// Check whether the current token is the given (non-reserved) word
+    fn check_word(&self, word: &str) -> bool {
+        self.check_word_at(0, word)
+    }
+
+    /// Check whether the token `offset` places ahead is the given word
+    fn check_word_at(&self, offset: usize, word: &str) -> bool {
+        matches!(
+            self.tokens.get(self.current + offset).map(|t| &t.kind),
+            Some(TokenKind::Identifier(w)) if w.eq_ignore_ascii_case(word)
+        )
+    }
+
+    fn consume_word(&mut self, word: &str) -> ParseResult<()> {
+        if self.check_word(word) {
+            self.advance();
+            Ok(())
+        } else {
+            let token = self.peek();
+            Err(ParseError::UnexpectedToken {
+                expected: word.to_string(),
+                found: token.kind.to_string(),
+                line: token.position.line,
+                column: token.position.column,
+            })
+        }
+    }
+
+    // Schema commands. INDEX, CONSTRAINT, DROP, SHOW and friends are not
+    // reserved, so they are recognised by position.
+
+    fn is_schema_command(&self) -> bool {
+        if self.check(&TokenKind::Create) {
+            let kind_offset = ["RANGE", "TEXT", "FULLTEXT"]
+                .iter()
+                .any(|w| self.check_word_at(1, w)) as usize;
+            return self.check_word_at(1 + kind_offset, "INDEX")
+                || self.check_word_at(1, "CONSTRAINT");
+        }
+        (self.check_word("DROP") || self.check_word("SHOW"))
+            && ["INDEX", "INDEXES", "CONSTRAINT", "CONSTRAINTS"]
+                .iter()
+                .any(|w| self.check_word_at(1, w))
+    }
+
+    fn parse_schema_command(&mut self) -> ParseResult<SchemaCommand> {
+        if self.match_token(&[TokenKind::Create]) {
+            if self.check_word("CONSTRAINT") {
+                self.advance();
+                return self.parse_create_constraint();
+            }
+            return self.parse_create_index();
+        }
+
+        if self.check_word("SHOW") {
+            self.advance();
+            let indexes = self.check_word("INDEX") || self.check_word("INDEXES");
+            self.advance();
+            return Ok(if indexes {
+                SchemaCommand::ShowIndexes
+            } else {
+                SchemaCommand::ShowConstraints
+            });
+        }
+
+        self.consume_word("DROP")?;
+        let index = self.check_word("INDEX");
+        if index {
+            self.advance();
+        } else {
+            self.consume_word("CONSTRAINT")?;
+        }
+        let name = self.parse_identifier("index or constraint name")?;
+        let if_exists = self.check_word("IF");
+        if if_exists {
+            self.advance();
+            self.consume_word("EXISTS")?;
+        }
+        Ok(if index {
+            SchemaCommand::DropIndex { name, if_exists }
+        } else {
+            SchemaCommand::DropConstraint { name, if_exists }
+        })
+    }
+
+    /// `[RANGE|TEXT|FULLTEXT] INDEX [name] [IF NOT EXISTS] FOR (v:Label)
+    /// ON (v.p) | ON EACH [v.p, ...]`
+    fn parse_create_index(&mut self) -> ParseResult<SchemaCommand> {
+        let kind = if self.check_word("TEXT") || self.check_word("FULLTEXT") {
+            self.advance();
+            IndexKind::FullText
+        } else {
+            if self.check_word("RANGE") {
+                self.advance();
+            }
+            IndexKind::Range
+        };
+        self.consume_word("INDEX")?;
+        let name = self.parse_schema_name()?;
+        let if_not_exists = self.parse_if_not_exists()?;
+        self.consume_word("FOR")?;
+        let (variable, label) = self.parse_schema_node()?;
+        self.consume_word("ON")?;
+
+        let close = if self.check_word("EACH") {
+            self.advance();
+            self.consume(TokenKind::LeftBracket, "[")?;
+            TokenKind::RightBracket
+        } else {
+            self.consume(TokenKind::LeftParen, "(")?;
+            TokenKind::RightParen
+        };
+        let mut properties = vec![self.parse_schema_property(&variable)?];
+        while self.match_token(&[TokenKind::Comma]) {
+            properties.push(self.parse_schema_property(&variable)?);
+        }
+        self.consume(close, "closing bracket")?;
+
+        let name = name.unwrap_or_else(|| {
+            let prefix = match kind {
+                IndexKind::Range => "range",
+                IndexKind::FullText => "fulltext",
+            };
+            format!("{}_{}_{}", prefix, label, properties.join("_"))
+        });
+        Ok(SchemaCommand::CreateIndex {
+            name,
+            kind,
+            label,
+            properties,
+            if_not_exists,
+        })
+    }
+
+    /// `CONSTRAINT [name] [IF NOT EXISTS] FOR|ON (v:Label) REQUIRE|ASSERT v.p
+    /// IS UNIQUE | IS NOT NULL`
+    fn parse_create_constraint(&mut self) -> ParseResult<SchemaCommand> {
+        let name = self.parse_schema_name()?;
+        let if_not_exists = self.parse_if_not_exists()?;
+        if !self.check_word("ON") {
+            self.consume_word("FOR")?;
+        } else {
+            self.advance();
+        }
+        let (variable, label) = self.parse_schema_node()?;
+        if !self.check_word("ASSERT") {
+            self.consume_word("REQUIRE")?;
+        } else {
+            self.advance();
+        }
+        let property = self.parse_schema_property(&variable)?;
+        self.consume(TokenKind::Is, "IS")?;
+        let kind = if self.match_token(&[TokenKind::Not]) {
+            self.consume(TokenKind::Null, "NULL")?;
+            ConstraintKind::NotNull
+        } else {
+            self.consume_word("UNIQUE")?;
+            ConstraintKind::Unique
+        };
+
+        let name = name.unwrap_or_else(|| {
+            let prefix = match kind {
+                ConstraintKind::Unique => "unique",
+                ConstraintKind::NotNull => "not_null",
+            };
+            format!("{}_{}_{}", prefix, label, property)
+        });
+        Ok(SchemaCommand::CreateConstraint {
+            name,
+            kind,
+            label,
+            property,
+            if_not_exists,
+        })
+    }
+
+    /// Optional name before `IF NOT EXISTS` / `FOR` / `ON`
+    fn parse_schema_name(&mut self) -> ParseResult<Option<String>> {
+        if ["IF", "FOR", "ON"].iter().any(|w| self.check_word(w)) {
+            return Ok(None);
+        }
+        self.parse_identifier("index or constraint name").map(Some)
+    }
+
+    fn parse_if_not_exists(&mut self) -> ParseResult<bool> {
+        if !self.check_word("IF") {
+            return Ok(false);
+        }
+        self.advance();
+        self.consume(TokenKind::Not, "NOT")?;
+        self.consume_word("EXISTS")?;
+        Ok(true)
+    }
+
+    /// `(v:Label)`, returning the variable and label
+    fn parse_schema_node(&mut self) -> ParseResult<(String, String)> {
+        self.consume(TokenKind::LeftParen, "(")?;
+        let variable = self.parse_identifier("variable")?;
+        self.consume(TokenKind::Colon, ":")?;
+        let label = self.parse_identifier("label")?;
+        self.consume(TokenKind::RightParen, ")")?;
+        Ok((variable, label))
+    }
+
+    /// `v.property`, where `v` must be the pattern's variable
+    fn parse_schema_property(&mut self, variable: &str) -> ParseResult<String> {
+        let owner = self.parse_identifier("variable")?;
+        if owner != variable {
+            return Err(ParseError::InvalidSyntax(format!(
+                "Expected a property of {}, found {}",
+                variable, owner
+            )));
+        }
+        self.consume(TokenKind::Dot, ".")?;
+        self.parse_identifier("property name")
+    }
+
+    /// Parse the string predicates CONTAINS, STARTS WITH and ENDS WITH
+    fn parse_string_predicate(&mut self) -> Option<BinaryOperator> {
+        if self.check_word("CONTAINS") {
+            self.advance();
+            return Some(BinaryOperator::Contains);
+        }
+
+        let op = if self.check_word("STARTS") {
+            BinaryOperator::StartsWith
+        } else if self.check_word("ENDS") {
+            BinaryOperator::EndsWith
+        } else {
+            return None;
+        };
+
+        if self.tokens.get(self.current + 1).map(|t| &t.kind) != Some(&TokenKind::With) {
+            return None;
+        }
+        self.advance();
+        self.advance();
+        Some(op)
+    }
+
     fn parse_comparison_op(&mut self) -> Option<BinaryOperator> {
-        if self.match_token(&[TokenKind::Equal]) {
+        if let Some(op) = self.parse_string_predicate() {
+            return Some(op);
+        }
+
+        if self.match_token(&[TokenKind::In]) {
+            Some(BinaryOperator::In)
+        } else if self.match_token(&[TokenKind::Equal]) {
             Some(BinaryOperator::Equal)
         } else if self.match_token(&[TokenKind::NotEqual]) {
             Some(BinaryOperator::NotEqual)
@@ -885,10 +1265,22 @@ impl Parser {
     fn parse_additive_op(&mut self) -> Option<BinaryOperator> {
         if self.match_token(&[TokenKind::Plus]) {
             Some(BinaryOperator::Add)
-        } else if self.match_token(&[TokenKind::Minus]) {
+        } else if self.match_token(&[TokenKind::Minus, TokenKind::Dash]) {
             Some(BinaryOperator::Subtract)
         } else {
-            None
+            // The lexer folds `-` into numbers, so `n.age-1` arrives as an
+            // operand followed by the literal -1
+            let token = &mut self.tokens[self.current];
+            let negated = match token.kind {
+                TokenKind::Integer(n) if token.lexeme.starts_with('-') => {
+                    TokenKind::Integer(n.checked_neg()?)
+                }
+                TokenKind::Float(n) if token.lexeme.starts_with('-') => TokenKind::Float(-n),
+                _ => return None,
+            };
+            token.kind = negated;
+            token.lexeme.remove(0);
+            Some(BinaryOperator::Subtract)
         }
     }
 
@@ -922,15 +1314,7 @@ impl Parser {
     }
 
     fn parse_unary(&mut self) -> ParseResult<Expression> {
-        if self.match_token(&[TokenKind::Not]) {
-            let operand = self.parse_unary()?;
-            return Ok(Expression::UnaryOp {
-                op: UnaryOperator::Not,
-                operand: Box::new(operand),
-            });
-        }
-
-        if self.match_token(&[TokenKind::Minus]) {
+        if self.match_token(&[TokenKind::Minus, TokenKind::Dash]) {
             let operand = self.parse_unary()?;
             return Ok(Expression::UnaryOp {
                 op: UnaryOperator::Minus,
@@ -994,9 +1378,20 @@ impl Parser {
                 Ok(Expression::Null)
             }
             TokenKind::Identifier(name) => {
-                let name = name.clone();
+                let mut name = name.clone();
                 self.advance();
 
+                // Namespaced function such as vector.similarity(...); without
+                // the parenthesis the dots are property accesses
+                let segments = self.function_namespace_segments();
+                for _ in 0..segments {
+                    self.advance();
+                    if let TokenKind::Identifier(segment) = &self.advance().kind {
+                        name.push('.');
+                        name.push_str(segment);
+                    }
+                }
+
                 // Check for function call
                 if self.match_token(&[TokenKind::LeftParen]) {
                     self.parse_function_call(name)
@@ -1082,9 +1477,40 @@ impl Parser {
         }
     }
 
+    /// Number of `.name` segments ahead that are followed by `(`
+    fn function_namespace_segments(&self) -> usize {
+        let kind = |offset: usize| self.tokens.get(self.current + offset).map(|t| &t.kind);
+        let mut segments = 0;
+        while kind(segments * 2) == Some(&TokenKind::Dot)
+            && matches!(kind(segments * 2 + 1), Some(TokenKind::Identifier(_)))
+        {
+            segments += 1;
+        }
+        if segments > 0 && kind(segments * 2) == Some(&TokenKind::LeftParen) {
+            segments
+        } else {
+            0
+        }
+    }
+
     fn parse_function_call(&mut self, name: String) -> ParseResult<Expression> {
         let mut args = vec![];
 
+        // count(*) counts rows; it is represented as an aggregation over the
+        // variable `*`, which no pattern can bind
+        if name.eq_ignore_ascii_case("count")
+            && self.check(&TokenKind::Star)
+            && self.tokens.get(self.current + 1).map(|t| &t.kind) == Some(&TokenKind::RightParen)
+        {
+            self.advance();
+            self.advance();
+            return Ok(Expression::Aggregation {
+                function: AggregationFunction::Count,
+                expression: Box::new(Expression::Variable("*".to_string())),
+                distinct: false,
+            });
+        }
+
         if !self.check(&TokenKind::RightParen) {
             // Check for DISTINCT in aggregation
             let distinct = self.match_token(&[TokenKind::Distinct]);
@@ -1292,4 +1718,181 @@ mod tests {
         let result = parse_cypher(query);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_predicate_operators() {
+        let query = parse_cypher(
+            "MATCH (n) WHERE n.name STARTS WITH 'A' AND n.email IS NOT NULL \
+             AND n.age IN [1, 2] AND NOT n.tag CONTAINS 'x' RETURN n",
+        )
+        .unwrap();
+        let Statement::Match(m) = &query.statements[0] else {
+            panic!("expected MATCH");
+        };
+        let condition = &m.where_clause.as_ref().unwrap().condition;
+        let Expression::BinaryOp { right, .. } = condition else {
+            panic!("expected AND");
+        };
+        // NOT applies to the whole CONTAINS comparison
+        assert!(matches!(
+            right.as_ref(),
+            Expression::UnaryOp {
+                op: UnaryOperator::Not,
+                operand,
+            } if matches!(operand.as_ref(), Expression::BinaryOp { op: BinaryOperator::Contains, .. })
+        ));
+    }
+
+    #[test]
+    fn test_subtraction_and_count_star() {
+        let query = parse_cypher("MATCH (n) RETURN n.age - 1, n.age-1, -n.age, count(*)").unwrap();
+        let Statement::Return(r) = &query.statements[1] else {
+            panic!("expected RETURN");
+        };
+        for item in &r.items[..2] {
+            assert!(matches!(
+                &item.expression,
+                Expression::BinaryOp {
+                    op: BinaryOperator::Subtract,
+                    right,
+                    ..
+                } if **right == Expression::Integer(1)
+            ));
+        }
+        assert!(matches!(
+            &r.items[2].expression,
+            Expression::UnaryOp {
+                op: UnaryOperator::Minus,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &r.items[3].expression,
+            Expression::Aggregation {
+                function: AggregationFunction::Count,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_set_labels() {
+        let query = parse_cypher("MATCH (n) SET n:Admin:Active, n.level = 2").unwrap();
+        let Statement::Set(set) = &query.statements[1] else {
+            panic!("expected SET");
+        };
+        assert_eq!(
+            set.items[0],
+            SetItem::Labels {
+                variable: "n".to_string(),
+                labels: vec!["Admin".to_string(), "Active".to_string()],
+            }
+        );
+        assert_eq!(set.items.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_call() {
+        let query = parse_cypher(
+            "CALL algo.pagerank({dampingFactor: 0.9}) YIELD node, score AS rank WHERE rank > 0.1 \
+             RETURN node.name, rank",
+        )
+        .unwrap();
+        let Statement::Call(call) = &query.statements[0] else {
+            panic!("expected CALL");
+        };
+        assert_eq!(call.procedure, "algo.pagerank");
+        assert_eq!(call.arguments.len(), 1);
+        assert_eq!(
+            call.yield_items.as_ref().unwrap()[1],
+            YieldItem {
+                column: "score".to_string(),
+                alias: Some("rank".to_string()),
+            }
+        );
+        assert!(call.where_clause.is_some());
+        assert!(query.is_read_only());
+
+        let query = parse_cypher("CALL algo.connectedComponents").unwrap();
+        let Statement::Call(call) = &query.statements[0] else {
+            panic!("expected CALL");
+        };
+        assert!(call.arguments.is_empty());
+        assert!(call.yield_items.is_none());
+    }
+
+    #[test]
+    fn test_parse_namespaced_function() {
+        let query =
+            parse_cypher("MATCH (a) RETURN vector.similarity(a.embedding, $q), a.x.y").unwrap();
+        let Statement::Return(ret) = &query.statements[1] else {
+            panic!("expected RETURN");
+        };
+        match &ret.items[0].expression {
+            Expression::FunctionCall { name, args } => {
+                assert_eq!(name, "vector.similarity");
+                assert_eq!(args.len(), 2);
+            }
+            other => panic!("expected function call, got {:?}", other),
+        }
+        assert!(matches!(
+            ret.items[1].expression,
+            Expression::Property { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_schema_commands() {
+        let schema = |cypher: &str| match parse_cypher(cypher).unwrap().statements.as_slice() {
+            [Statement::Schema(command)] => command.clone(),
+            other => panic!("expected a schema command, got {:?}", other),
+        };
+
+        assert_eq!(
+            schema("CREATE INDEX person_age IF NOT EXISTS FOR (p:Person) ON (p.age)"),
+            SchemaCommand::CreateIndex {
+                name: "person_age".to_string(),
+                kind: IndexKind::Range,
+                label: "Person".to_string(),
+                properties: vec!["age".to_string()],
+                if_not_exists: true,
+            }
+        );
+        assert!(matches!(
+            schema("CREATE FULLTEXT INDEX FOR (d:Doc) ON EACH [d.title, d.body]"),
+            SchemaCommand::CreateIndex { name, kind: IndexKind::FullText, properties, .. }
+                if name == "fulltext_Doc_title_body" && properties.len() == 2
+        ));
+        assert_eq!(
+            schema("create constraint for (p:Person) require p.email is unique"),
+            SchemaCommand::CreateConstraint {
+                name: "unique_Person_email".to_string(),
+                kind: ConstraintKind::Unique,
+                label: "Person".to_string(),
+                property: "email".to_string(),
+                if_not_exists: false,
+            }
+        );
+        assert!(matches!(
+            schema("CREATE CONSTRAINT named ON (p:Person) ASSERT p.name IS NOT NULL"),
+            SchemaCommand::CreateConstraint {
+                kind: ConstraintKind::NotNull,
+                ..
+            }
+        ));
+        assert_eq!(
+            schema("DROP INDEX person_age IF EXISTS"),
+            SchemaCommand::DropIndex {
+                name: "person_age".to_string(),
+                if_exists: true,
+            }
+        );
+        assert_eq!(schema("SHOW INDEXES"), SchemaCommand::ShowIndexes);
+        assert_eq!(schema("SHOW CONSTRAINTS"), SchemaCommand::ShowConstraints);
+
+        // The property must belong to the pattern variable
+        assert!(parse_cypher("CREATE INDEX FOR (p:Person) ON (q.age)").is_err());
+        // `index` stays usable as a name
+        assert!(parse_cypher("CREATE (index:Item) RETURN index").is_ok());
+    }
 }
@@ -0,0 +1,557 @@
+//! Logical query plans for Cypher execution
+//!
+//! The planner turns a parsed query into a tree of [`PlanOperator`]s:
+//! - Leaf: `Argument` seeds the pipeline with the incoming record
+//! - Reads: node scans, relationship expansion and path construction
+//! - Procedures: `ProcedureCall` runs a procedure such as `algo.pagerank`
+//! - Schema: `Schema` creates, drops or lists indexes and constraints
+//! - Relational: filter, projection, aggregation, sort, skip and limit
+//! - Writes: CREATE, MERGE, SET, REMOVE and DELETE
+//!
+//! Each operator consumes the records of its input and produces new ones.
+
+use super::ast::{Direction, Expression, RemoveItem, SchemaCommand, SetItem};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Node of a flattened pattern chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeStep {
+    /// Variable bound to the node; anonymous nodes get a hidden `#` name
+    pub variable: String,
+    pub labels: Vec<String>,
+    /// Inline property constraints, sorted by key
+    pub properties: Vec<(String, Expression)>,
+}
+
+/// Relationship of a flattened pattern chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelStep {
+    /// Variable bound to the relationship (or list of relationships for
+    /// variable-length steps); anonymous steps get a hidden `#` name
+    pub variable: String,
+    pub rel_type: Option<String>,
+    pub properties: Vec<(String, Expression)>,
+    /// Direction from the preceding node to the following one
+    pub direction: Direction,
+    /// Hop bounds for variable-length steps
+    pub range: Option<(usize, Option<usize>)>,
+}
+
+/// Linear pattern `(n0)-[r0]-(n1)-[r1]-(n2)...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternChain {
+    /// Path variable from `p = (...)`
+    pub path: Option<String>,
+    pub nodes: Vec<NodeStep>,
+    pub relationships: Vec<RelStep>,
+}
+
+impl PatternChain {
+    /// All variables bound by the chain, including hidden ones
+    pub fn variables(&self) -> Vec<String> {
+        let mut vars: Vec<String> = self.nodes.iter().map(|n| n.variable.clone()).collect();
+        vars.extend(self.relationships.iter().map(|r| r.variable.clone()));
+        vars.extend(self.path.clone());
+        vars
+    }
+}
+
+/// How a node scan finds its candidates
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeAccess {
+    /// The variable is already bound by the input record
+    Bound,
+    /// Seek the property index with the value of an expression
+    PropertyIndex { key: String, value: Expression },
+    /// Seek a declared range index
+    RangeIndex {
+        index: String,
+        property: String,
+        seek: RangeSeek,
+    },
+    /// Narrow a label scan to the full-text index candidates for `CONTAINS`
+    FullTextIndex {
+        index: String,
+        label: String,
+        property: String,
+        text: Expression,
+    },
+    /// Scan the label index
+    LabelIndex { label: String },
+    /// Scan every node
+    AllNodes,
+}
+
+/// Lookup on a range index; bounds are `(value, inclusive)`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeSeek {
+    Equal(Expression),
+    Range {
+        lower: Option<(Expression, bool)>,
+        upper: Option<(Expression, bool)>,
+    },
+    Prefix(Expression),
+}
+
+/// Aggregation computed by an `Aggregate` operator
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregateItem {
+    /// Column holding the result
+    pub column: String,
+    /// The `Expression::Aggregation` to compute
+    pub aggregation: Expression,
+}
+
+/// Operator of a logical plan
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanOperator {
+    /// Produces the incoming record, whose variables are listed
+    Argument { variables: Vec<String> },
+    /// Bind a node variable to every matching node
+    NodeScan {
+        input: Box<PlanOperator>,
+        node: NodeStep,
+        access: NodeAccess,
+    },
+    /// Follow relationships from a bound node
+    Expand {
+        input: Box<PlanOperator>,
+        from: String,
+        relationship: RelStep,
+        to: NodeStep,
+        /// Relationship variables of the same MATCH that must not be reused
+        unique: Vec<String>,
+    },
+    /// Keep the records for which the predicate is true
+    Filter {
+        input: Box<PlanOperator>,
+        predicate: Expression,
+    },
+    /// Run `plan` per record; records without a match get nulls instead
+    Optional {
+        input: Box<PlanOperator>,
+        plan: Box<PlanOperator>,
+        /// Variables introduced by `plan`
+        variables: Vec<String>,
+    },
+    /// Bind a path from a start node and the relationships that follow it
+    BuildPath {
+        input: Box<PlanOperator>,
+        variable: String,
+        start: String,
+        relationships: Vec<String>,
+    },
+    /// Add computed columns to each record
+    Project {
+        input: Box<PlanOperator>,
+        items: Vec<(String, Expression)>,
+    },
+    /// Group by the key columns and compute aggregations per group
+    Aggregate {
+        input: Box<PlanOperator>,
+        keys: Vec<(String, Expression)>,
+        aggregates: Vec<AggregateItem>,
+    },
+    /// Keep only the listed columns and drop duplicate records
+    Distinct {
+        input: Box<PlanOperator>,
+        columns: Vec<String>,
+    },
+    /// Order records; `true` means ascending
+    Sort {
+        input: Box<PlanOperator>,
+        keys: Vec<(Expression, bool)>,
+    },
+    Skip {
+        input: Box<PlanOperator>,
+        count: Expression,
+    },
+    Limit {
+        input: Box<PlanOperator>,
+        count: Expression,
+    },
+    /// Keep only the listed columns
+    Select {
+        input: Box<PlanOperator>,
+        columns: Vec<String>,
+    },
+    Create {
+        input: Box<PlanOperator>,
+        chains: Vec<PatternChain>,
+    },
+    /// Match `chain` with `matcher`, creating it when nothing matches
+    Merge {
+        input: Box<PlanOperator>,
+        chain: PatternChain,
+        matcher: Box<PlanOperator>,
+        on_create: Vec<SetItem>,
+        on_match: Vec<SetItem>,
+    },
+    Set {
+        input: Box<PlanOperator>,
+        items: Vec<SetItem>,
+    },
+    Remove {
+        input: Box<PlanOperator>,
+        items: Vec<RemoveItem>,
+    },
+    Delete {
+        input: Box<PlanOperator>,
+        expressions: Vec<Expression>,
+        detach: bool,
+    },
+    /// Call a procedure once per record, adding a record per output row
+    ProcedureCall {
+        input: Box<PlanOperator>,
+        procedure: String,
+        arguments: Vec<Expression>,
+        /// Output columns and the variables they are bound to
+        yields: Vec<(String, String)>,
+    },
+    /// Run a schema command; SHOW commands produce a record per entry
+    Schema {
+        input: Box<PlanOperator>,
+        command: SchemaCommand,
+    },
+}
+
+impl PlanOperator {
+    /// The operator's input, if it has one
+    pub fn input(&self) -> Option<&PlanOperator> {
+        match self {
+            PlanOperator::Argument { .. } => None,
+            PlanOperator::NodeScan { input, .. }
+            | PlanOperator::Expand { input, .. }
+            | PlanOperator::Filter { input, .. }
+            | PlanOperator::Optional { input, .. }
+            | PlanOperator::BuildPath { input, .. }
+            | PlanOperator::Project { input, .. }
+            | PlanOperator::Aggregate { input, .. }
+            | PlanOperator::Distinct { input, .. }
+            | PlanOperator::Sort { input, .. }
+            | PlanOperator::Skip { input, .. }
+            | PlanOperator::Limit { input, .. }
+            | PlanOperator::Select { input, .. }
+            | PlanOperator::Create { input, .. }
+            | PlanOperator::Merge { input, .. }
+            | PlanOperator::Set { input, .. }
+            | PlanOperator::Remove { input, .. }
+            | PlanOperator::Delete { input, .. }
+            | PlanOperator::ProcedureCall { input, .. }
+            | PlanOperator::Schema { input, .. } => Some(input),
+        }
+    }
+
+    /// Mutable access to the operator's input
+    pub fn input_mut(&mut self) -> Option<&mut Box<PlanOperator>> {
+        match self {
+            PlanOperator::Argument { .. } => None,
+            PlanOperator::NodeScan { input, .. }
+            | PlanOperator::Expand { input, .. }
+            | PlanOperator::Filter { input, .. }
+            | PlanOperator::Optional { input, .. }
+            | PlanOperator::BuildPath { input, .. }
+            | PlanOperator::Project { input, .. }
+            | PlanOperator::Aggregate { input, .. }
+            | PlanOperator::Distinct { input, .. }
+            | PlanOperator::Sort { input, .. }
+            | PlanOperator::Skip { input, .. }
+            | PlanOperator::Limit { input, .. }
+            | PlanOperator::Select { input, .. }
+            | PlanOperator::Create { input, .. }
+            | PlanOperator::Merge { input, .. }
+            | PlanOperator::Set { input, .. }
+            | PlanOperator::Remove { input, .. }
+            | PlanOperator::Delete { input, .. }
+            | PlanOperator::ProcedureCall { input, .. }
+            | PlanOperator::Schema { input, .. } => Some(input),
+        }
+    }
+
+    /// Whether neither this operator nor its inputs change the graph or schema
+    pub fn is_read_only(&self) -> bool {
+        let reads = match self {
+            PlanOperator::Create { .. }
+            | PlanOperator::Merge { .. }
+            | PlanOperator::Set { .. }
+            | PlanOperator::Remove { .. }
+            | PlanOperator::Delete { .. }
+            | PlanOperator::Schema { .. } => false,
+            PlanOperator::Optional { plan, .. } => plan.is_read_only(),
+            _ => true,
+        };
+        reads && self.input().map_or(true, PlanOperator::is_read_only)
+    }
+
+    /// Variables bound in the records this operator produces
+    pub fn bound_variables(&self) -> HashSet<String> {
+        match self {
+            PlanOperator::Argument { variables } => variables.iter().cloned().collect(),
+            PlanOperator::NodeScan { input, node, .. } => {
+                let mut vars = input.bound_variables();
+                vars.insert(node.variable.clone());
+                vars
+            }
+            PlanOperator::Expand {
+                input,
+                relationship,
+                to,
+                ..
+            } => {
+                let mut vars = input.bound_variables();
+                vars.insert(relationship.variable.clone());
+                vars.insert(to.variable.clone());
+                vars
+            }
+            PlanOperator::Optional {
+                input, variables, ..
+            } => {
+                let mut vars = input.bound_variables();
+                vars.extend(variables.iter().cloned());
+                vars
+            }
+            PlanOperator::BuildPath {
+                input, variable, ..
+            } => {
+                let mut vars = input.bound_variables();
+                vars.insert(variable.clone());
+                vars
+            }
+            PlanOperator::Project { input, items } => {
+                let mut vars = input.bound_variables();
+                vars.extend(items.iter().map(|(name, _)| name.clone()));
+                vars
+            }
+            PlanOperator::Aggregate {
+                keys, aggregates, ..
+            } => keys
+                .iter()
+                .map(|(name, _)| name.clone())
+                .chain(aggregates.iter().map(|a| a.column.clone()))
+                .collect(),
+            PlanOperator::Distinct { columns, .. } | PlanOperator::Select { columns, .. } => {
+                columns.iter().cloned().collect()
+            }
+            PlanOperator::Create { input, chains } => {
+                let mut vars = input.bound_variables();
+                vars.extend(chains.iter().flat_map(PatternChain::variables));
+                vars
+            }
+            PlanOperator::Merge { input, chain, .. } => {
+                let mut vars = input.bound_variables();
+                vars.extend(chain.variables());
+                vars
+            }
+            PlanOperator::ProcedureCall { input, yields, .. } => {
+                let mut vars = input.bound_variables();
+                vars.extend(yields.iter().map(|(_, variable)| variable.clone()));
+                vars
+            }
+            PlanOperator::Schema { input, command } => {
+                let mut vars = input.bound_variables();
+                vars.extend(command.columns().iter().map(|c| c.to_string()));
+                vars
+            }
+            PlanOperator::Filter { input, .. }
+            | PlanOperator::Sort { input, .. }
+            | PlanOperator::Skip { input, .. }
+            | PlanOperator::Limit { input, .. }
+            | PlanOperator::Set { input, .. }
+            | PlanOperator::Remove { input, .. }
+            | PlanOperator::Delete { input, .. } => input.bound_variables(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        fn list<T: fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+            items
+                .into_iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        match self {
+            PlanOperator::Argument { variables } => format!("Argument({})", list(variables)),
+            PlanOperator::NodeScan { node, access, .. } => {
+                let access = match access {
+                    NodeAccess::Bound => "bound".to_string(),
+                    NodeAccess::PropertyIndex { key, value } => {
+                        format!("property index {} = {}", key, value)
+                    }
+                    NodeAccess::RangeIndex {
+                        index,
+                        property,
+                        seek,
+                    } => {
+                        let seek = match seek {
+                            RangeSeek::Equal(value) => format!("{} = {}", property, value),
+                            RangeSeek::Range { lower, upper } => {
+                                let lower = lower.iter().map(|(value, inclusive)| {
+                                    format!(
+                                        "{} {} {}",
+                                        property,
+                                        if *inclusive { ">=" } else { ">" },
+                                        value
+                                    )
+                                });
+                                let upper = upper.iter().map(|(value, inclusive)| {
+                                    format!(
+                                        "{} {} {}",
+                                        property,
+                                        if *inclusive { "<=" } else { "<" },
+                                        value
+                                    )
+                                });
+                                lower.chain(upper).collect::<Vec<_>>().join(" AND ")
+                            }
+                            RangeSeek::Prefix(prefix) => {
+                                format!("{} STARTS WITH {}", property, prefix)
+                            }
+                        };
+                        format!("range index {} ({})", index, seek)
+                    }
+                    NodeAccess::FullTextIndex {
+                        index,
+                        property,
+                        text,
+                        ..
+                    } => format!("full-text index {} ({} CONTAINS {})", index, property, text),
+                    NodeAccess::LabelIndex { label } => format!("label index :{}", label),
+                    NodeAccess::AllNodes => "all nodes".to_string(),
+                };
+                format!("NodeScan({}) via {}", node.variable, access)
+            }
+            PlanOperator::Expand {
+                from,
+                relationship,
+                to,
+                ..
+            } => {
+                let rel_type = relationship.rel_type.as_deref().unwrap_or("");
+                let range = match relationship.range {
+                    Some((min, Some(max))) => format!("*{}..{}", min, max),
+                    Some((min, None)) => format!("*{}..", min),
+                    None => String::new(),
+                };
+                let (left, right) = match relationship.direction {
+                    Direction::Outgoing => ("-", "->"),
+                    Direction::Incoming => ("<-", "-"),
+                    Direction::Undirected => ("-", "-"),
+                };
+                format!(
+                    "Expand(({}){}[{}:{}{}]{}({}))",
+                    from, left, relationship.variable, rel_type, range, right, to.variable
+                )
+            }
+            PlanOperator::Filter { predicate, .. } => format!("Filter({})", predicate),
+            PlanOperator::Optional { variables, .. } => format!("Optional({})", list(variables)),
+            PlanOperator::BuildPath { variable, .. } => format!("BuildPath({})", variable),
+            PlanOperator::Project { items, .. } => format!(
+                "Project({})",
+                list(
+                    items
+                        .iter()
+                        .map(|(name, expr)| format!("{} AS {}", expr, name))
+                )
+            ),
+            PlanOperator::Aggregate {
+                keys, aggregates, ..
+            } => format!(
+                "Aggregate(keys: [{}], aggregates: [{}])",
+                list(keys.iter().map(|(name, _)| name)),
+                list(
+                    aggregates
+                        .iter()
+                        .map(|a| format!("{} AS {}", a.aggregation, a.column))
+                )
+            ),
+            PlanOperator::Distinct { columns, .. } => format!("Distinct({})", list(columns)),
+            PlanOperator::Sort { keys, .. } => format!(
+                "Sort({})",
+                list(keys.iter().map(|(expr, asc)| {
+                    format!("{} {}", expr, if *asc { "ASC" } else { "DESC" })
+                }))
+            ),
+            PlanOperator::Skip { count, .. } => format!("Skip({})", count),
+            PlanOperator::Limit { count, .. } => format!("Limit({})", count),
+            PlanOperator::Select { columns, .. } => format!("Select({})", list(columns)),
+            PlanOperator::Create { chains, .. } => format!(
+                "Create({})",
+                list(chains.iter().flat_map(PatternChain::variables))
+            ),
+            PlanOperator::Merge { chain, .. } => format!("Merge({})", list(chain.variables())),
+            PlanOperator::Set { items, .. } => format!("Set({} items)", items.len()),
+            PlanOperator::Remove { items, .. } => format!("Remove({} items)", items.len()),
+            PlanOperator::Delete {
+                expressions,
+                detach,
+                ..
+            } => format!(
+                "{}Delete({})",
+                if *detach { "Detach" } else { "" },
+                list(expressions)
+            ),
+            PlanOperator::ProcedureCall {
+                procedure,
+                arguments,
+                yields,
+                ..
+            } => format!(
+                "ProcedureCall({}({}) YIELD {})",
+                procedure,
+                list(arguments),
+                list(
+                    yields
+                        .iter()
+                        .map(|(column, variable)| if column == variable {
+                            column.clone()
+                        } else {
+                            format!("{} AS {}", column, variable)
+                        })
+                )
+            ),
+            PlanOperator::Schema { command, .. } => format!("Schema({})", command),
+        }
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(f, "{}{}", "  ".repeat(depth), self.describe())?;
+        match self {
+            PlanOperator::Optional { input, plan, .. } => {
+                plan.fmt_tree(f, depth + 2)?;
+                input.fmt_tree(f, depth + 1)
+            }
+            PlanOperator::Merge { input, matcher, .. } => {
+                matcher.fmt_tree(f, depth + 2)?;
+                input.fmt_tree(f, depth + 1)
+            }
+            _ => match self.input() {
+                Some(input) => input.fmt_tree(f, depth + 1),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PlanOperator {
+    /// Indented operator tree, root first
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
+/// Executable plan for a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryPlan {
+    pub root: PlanOperator,
+    /// Result columns; empty when the query has no RETURN
+    pub columns: Vec<String>,
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
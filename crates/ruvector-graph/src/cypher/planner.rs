@@ -0,0 +1,1362 @@
+//! Query planner: compiles a Cypher AST into a [`QueryPlan`]
+//!
+//! Planning flattens each pattern into a chain, starts chains from their
+//! cheaper end and turns every clause into operators while tracking which
+//! variables are in scope. [`QueryPlanner::optimize`] then rewrites the plan:
+//! - WHERE conjuncts are pushed down to the operator that binds their variables
+//! - Node scans seek declared range indexes for equality, range and
+//!   `STARTS WITH` predicates and full-text indexes for `CONTAINS`, then the
+//!   property index for other equalities, otherwise the label index of their
+//!   most selective label
+
+use super::ast::*;
+use super::plan::*;
+use super::procedures;
+use crate::error::{GraphError, Result};
+use crate::graph::GraphDB;
+use crate::schema::IndexKind;
+use std::collections::HashSet;
+
+fn invalid(message: impl Into<String>) -> GraphError {
+    GraphError::InvalidQuery(message.into())
+}
+
+/// Compiles parsed queries into executable plans for one graph
+pub struct QueryPlanner<'a> {
+    db: &'a GraphDB,
+    /// Variables visible to the clause being planned
+    scope: HashSet<String>,
+    /// Counter for hidden variable names
+    hidden: usize,
+}
+
+impl<'a> QueryPlanner<'a> {
+    pub fn new(db: &'a GraphDB) -> Self {
+        Self {
+            db,
+            scope: HashSet::new(),
+            hidden: 0,
+        }
+    }
+
+    /// Build a plan for a query
+    pub fn plan(&mut self, query: &Query) -> Result<QueryPlan> {
+        self.scope.clear();
+        let mut op = PlanOperator::Argument { variables: vec![] };
+        let mut columns = Vec::new();
+
+        for (i, statement) in query.statements.iter().enumerate() {
+            op = match statement {
+                Statement::Match(clause) => self.plan_match(op, clause)?,
+                Statement::Create(clause) => self.plan_create(op, clause)?,
+                Statement::Merge(clause) => self.plan_merge(op, clause)?,
+                Statement::Set(clause) => {
+                    self.check_set_items(&clause.items)?;
+                    PlanOperator::Set {
+                        input: Box::new(op),
+                        items: clause.items.clone(),
+                    }
+                }
+                Statement::Remove(clause) => {
+                    for item in &clause.items {
+                        let (RemoveItem::Property { variable, .. }
+                        | RemoveItem::Labels { variable, .. }) = item;
+                        self.check_variable(variable)?;
+                    }
+                    PlanOperator::Remove {
+                        input: Box::new(op),
+                        items: clause.items.clone(),
+                    }
+                }
+                Statement::Delete(clause) => {
+                    for expression in &clause.expressions {
+                        self.check_expression(expression, false)?;
+                    }
+                    PlanOperator::Delete {
+                        input: Box::new(op),
+                        expressions: clause.expressions.clone(),
+                        detach: clause.detach,
+                    }
+                }
+                Statement::With(clause) => {
+                    let projection = Projection {
+                        distinct: clause.distinct,
+                        items: &clause.items,
+                        order_by: clause.order_by.as_ref(),
+                        skip: clause.skip.as_ref(),
+                        limit: clause.limit.as_ref(),
+                    };
+                    let (mut op, _) = self.plan_projection(op, projection, true)?;
+                    if let Some(where_clause) = &clause.where_clause {
+                        self.check_expression(&where_clause.condition, false)?;
+                        op = PlanOperator::Filter {
+                            input: Box::new(op),
+                            predicate: where_clause.condition.clone(),
+                        };
+                    }
+                    op
+                }
+                Statement::Call(clause) => {
+                    let standalone = query.statements.len() == 1;
+                    if i + 1 == query.statements.len() && !standalone {
+                        return Err(invalid("A query cannot end with CALL; add a RETURN clause"));
+                    }
+                    let (op, names) = self.plan_call(op, clause, standalone)?;
+                    if standalone {
+                        columns = names;
+                    }
+                    op
+                }
+                Statement::Schema(command) => {
+                    if query.statements.len() != 1 {
+                        return Err(invalid(
+                            "Schema commands cannot be combined with other clauses",
+                        ));
+                    }
+                    columns = command.columns().iter().map(|c| c.to_string()).collect();
+                    PlanOperator::Schema {
+                        input: Box::new(op),
+                        command: command.clone(),
+                    }
+                }
+                Statement::Return(clause) => {
+                    if i + 1 != query.statements.len() {
+                        return Err(invalid("RETURN can only be used at the end of the query"));
+                    }
+                    let projection = Projection {
+                        distinct: clause.distinct,
+                        items: &clause.items,
+                        order_by: clause.order_by.as_ref(),
+                        skip: clause.skip.as_ref(),
+                        limit: clause.limit.as_ref(),
+                    };
+                    let (op, names) = self.plan_projection(op, projection, false)?;
+                    columns = names;
+                    op
+                }
+            };
+        }
+
+        Ok(QueryPlan { root: op, columns })
+    }
+
+    /// Apply the rewrite rules to a plan
+    pub fn optimize(&self, mut plan: QueryPlan) -> QueryPlan {
+        push_down_filters(&mut plan.root);
+        self.choose_access(&mut plan.root);
+        plan
+    }
+
+    // Clauses
+
+    fn plan_match(&mut self, input: PlanOperator, clause: &MatchClause) -> Result<PlanOperator> {
+        let chains = clause
+            .patterns
+            .iter()
+            .map(|p| self.flatten(p))
+            .collect::<Result<Vec<_>>>()?;
+        let predicate = clause.where_clause.as_ref().map(|w| &w.condition);
+
+        let outer = self.scope.clone();
+        // An OPTIONAL MATCH is planned on its own, starting from each input record
+        let (mut op, input) = if clause.optional {
+            let mut variables: Vec<String> = outer.iter().cloned().collect();
+            variables.sort();
+            (PlanOperator::Argument { variables }, Some(input))
+        } else {
+            (input, None)
+        };
+
+        let mut relationships = Vec::new();
+        for chain in chains {
+            op = self.plan_chain(op, chain, &mut relationships, predicate)?;
+        }
+
+        if let Some(predicate) = predicate {
+            self.check_expression(predicate, false)?;
+            op = PlanOperator::Filter {
+                input: Box::new(op),
+                predicate: predicate.clone(),
+            };
+        }
+
+        let Some(input) = input else {
+            return Ok(op);
+        };
+
+        let mut variables: Vec<String> = self.scope.difference(&outer).cloned().collect();
+        variables.sort();
+        Ok(PlanOperator::Optional {
+            input: Box::new(input),
+            plan: Box::new(op),
+            variables,
+        })
+    }
+
+    fn plan_create(&mut self, input: PlanOperator, clause: &CreateClause) -> Result<PlanOperator> {
+        let mut chains = Vec::new();
+        for pattern in &clause.patterns {
+            let chain = self.flatten(pattern)?;
+            self.check_writable(&chain, "CREATE")?;
+            self.scope.extend(chain.variables());
+            chains.push(chain);
+        }
+
+        Ok(PlanOperator::Create {
+            input: Box::new(input),
+            chains,
+        })
+    }
+
+    fn plan_merge(&mut self, input: PlanOperator, clause: &MergeClause) -> Result<PlanOperator> {
+        let chain = self.flatten(&clause.pattern)?;
+        self.check_writable(&chain, "MERGE")?;
+
+        let mut variables: Vec<String> = self.scope.iter().cloned().collect();
+        variables.sort();
+        let matcher = self.plan_chain(
+            PlanOperator::Argument { variables },
+            chain.clone(),
+            &mut Vec::new(),
+            None,
+        )?;
+
+        let on_create = clause
+            .on_create
+            .as_ref()
+            .map(|s| s.items.clone())
+            .unwrap_or_default();
+        let on_match = clause
+            .on_match
+            .as_ref()
+            .map(|s| s.items.clone())
+            .unwrap_or_default();
+        self.check_set_items(&on_create)?;
+        self.check_set_items(&on_match)?;
+
+        Ok(PlanOperator::Merge {
+            input: Box::new(input),
+            chain,
+            matcher: Box::new(matcher),
+            on_create,
+            on_match,
+        })
+    }
+
+    /// Plan a procedure call; returns the operator and the yielded variables
+    fn plan_call(
+        &mut self,
+        input: PlanOperator,
+        clause: &CallClause,
+        standalone: bool,
+    ) -> Result<(PlanOperator, Vec<String>)> {
+        let signature = procedures::signature(&clause.procedure)
+            .ok_or_else(|| invalid(format!("Unknown procedure `{}`", clause.procedure)))?;
+        for argument in &clause.arguments {
+            self.check_expression(argument, false)?;
+        }
+
+        let yields: Vec<(String, String)> = match &clause.yield_items {
+            Some(items) => items
+                .iter()
+                .map(|item| {
+                    if !signature.outputs.contains(&item.column.as_str()) {
+                        return Err(invalid(format!(
+                            "Procedure `{}` has no output `{}`; it yields {}",
+                            signature.name,
+                            item.column,
+                            signature.outputs.join(", ")
+                        )));
+                    }
+                    let variable = item.alias.clone().unwrap_or_else(|| item.column.clone());
+                    Ok((item.column.clone(), variable))
+                })
+                .collect::<Result<_>>()?,
+            None if standalone => signature
+                .outputs
+                .iter()
+                .map(|o| (o.to_string(), o.to_string()))
+                .collect(),
+            None => {
+                return Err(invalid(format!(
+                    "CALL {} inside a larger query must use YIELD",
+                    signature.name
+                )))
+            }
+        };
+
+        for (_, variable) in &yields {
+            if !self.scope.insert(variable.clone()) {
+                return Err(invalid(format!("Variable `{}` already declared", variable)));
+            }
+        }
+        let names = yields.iter().map(|(_, v)| v.clone()).collect();
+
+        let mut op = PlanOperator::ProcedureCall {
+            input: Box::new(input),
+            procedure: signature.name.to_string(),
+            arguments: clause.arguments.clone(),
+            yields,
+        };
+        if let Some(where_clause) = &clause.where_clause {
+            self.check_expression(&where_clause.condition, false)?;
+            op = PlanOperator::Filter {
+                input: Box::new(op),
+                predicate: where_clause.condition.clone(),
+            };
+        }
+        Ok((op, names))
+    }
+
+    fn plan_projection(
+        &mut self,
+        input: PlanOperator,
+        projection: Projection<'_>,
+        is_with: bool,
+    ) -> Result<(PlanOperator, Vec<String>)> {
+        let mut columns: Vec<String> = Vec::new();
+        for item in projection.items {
+            self.check_expression(&item.expression, true)?;
+            let name = match (&item.alias, &item.expression) {
+                (Some(alias), _) => alias.clone(),
+                (None, Expression::Variable(name)) if !name.starts_with('$') => name.clone(),
+                (None, expression) if !is_with => expression.to_string(),
+                (None, expression) => {
+                    return Err(invalid(format!(
+                        "Expression in WITH must be aliased (use AS): {}",
+                        expression
+                    )))
+                }
+            };
+            if columns.contains(&name) {
+                return Err(invalid(format!(
+                    "Multiple result columns with the same name `{}`",
+                    name
+                )));
+            }
+            columns.push(name);
+        }
+
+        let aggregating = projection
+            .items
+            .iter()
+            .any(|item| item.expression.has_aggregation());
+
+        let mut op;
+        let sort_scope: HashSet<String>;
+        if aggregating {
+            let mut keys = Vec::new();
+            let mut aggregates = Vec::new();
+            let mut results = Vec::new();
+            for (item, name) in projection.items.iter().zip(&columns) {
+                if item.expression.has_aggregation() {
+                    let rewritten = self.extract_aggregations(&item.expression, &mut aggregates)?;
+                    results.push((name.clone(), rewritten));
+                } else {
+                    keys.push((name.clone(), item.expression.clone()));
+                }
+            }
+
+            let key_names: HashSet<&String> = keys.iter().map(|(name, _)| name).collect();
+            for (name, expression) in &results {
+                if let Some(variable) = expression_variables(expression)
+                    .into_iter()
+                    .find(|v| !v.starts_with(['#', '$']) && !key_names.contains(v))
+                {
+                    return Err(invalid(format!(
+                        "Variable `{}` in `{}` must be a grouping key",
+                        variable, name
+                    )));
+                }
+            }
+
+            op = PlanOperator::Project {
+                input: Box::new(PlanOperator::Aggregate {
+                    input: Box::new(input),
+                    keys,
+                    aggregates,
+                }),
+                items: results,
+            };
+            sort_scope = columns.iter().cloned().collect();
+        } else {
+            op = PlanOperator::Project {
+                input: Box::new(input),
+                items: columns
+                    .iter()
+                    .cloned()
+                    .zip(projection.items.iter().map(|i| i.expression.clone()))
+                    .collect(),
+            };
+            sort_scope = if projection.distinct {
+                columns.iter().cloned().collect()
+            } else {
+                self.scope.iter().chain(&columns).cloned().collect()
+            };
+        }
+
+        if projection.distinct {
+            op = PlanOperator::Distinct {
+                input: Box::new(op),
+                columns: columns.clone(),
+            };
+        }
+
+        if let Some(order_by) = projection.order_by {
+            self.scope = sort_scope;
+            let mut keys = Vec::new();
+            for item in &order_by.items {
+                // Sorting by a projected expression sorts by its column
+                let key = projection
+                    .items
+                    .iter()
+                    .zip(&columns)
+                    .find(|(i, _)| i.expression == item.expression)
+                    .map(|(_, name)| Expression::Variable(name.clone()))
+                    .unwrap_or_else(|| item.expression.clone());
+                self.check_expression(&key, false)?;
+                keys.push((key, item.ascending));
+            }
+            op = PlanOperator::Sort {
+                input: Box::new(op),
+                keys,
+            };
+        }
+
+        if let Some(count) = projection.skip {
+            check_count(count, "SKIP")?;
+            op = PlanOperator::Skip {
+                input: Box::new(op),
+                count: count.clone(),
+            };
+        }
+        if let Some(count) = projection.limit {
+            check_count(count, "LIMIT")?;
+            op = PlanOperator::Limit {
+                input: Box::new(op),
+                count: count.clone(),
+            };
+        }
+
+        op = PlanOperator::Select {
+            input: Box::new(op),
+            columns: columns.clone(),
+        };
+        self.scope = columns.iter().cloned().collect();
+        Ok((op, columns))
+    }
+
+    /// Replace aggregations with hidden columns computed by an `Aggregate`
+    fn extract_aggregations(
+        &mut self,
+        expression: &Expression,
+        aggregates: &mut Vec<AggregateItem>,
+    ) -> Result<Expression> {
+        Ok(match expression {
+            Expression::Aggregation {
+                expression: argument,
+                ..
+            } => {
+                if argument.has_aggregation() {
+                    return Err(invalid(format!(
+                        "Aggregations cannot be nested: {}",
+                        expression
+                    )));
+                }
+                let column = match aggregates.iter().find(|a| &a.aggregation == expression) {
+                    Some(existing) => existing.column.clone(),
+                    None => {
+                        let column = self.hidden_name();
+                        aggregates.push(AggregateItem {
+                            column: column.clone(),
+                            aggregation: expression.clone(),
+                        });
+                        column
+                    }
+                };
+                Expression::Variable(column)
+            }
+            Expression::BinaryOp { left, op, right } => Expression::BinaryOp {
+                left: Box::new(self.extract_aggregations(left, aggregates)?),
+                op: *op,
+                right: Box::new(self.extract_aggregations(right, aggregates)?),
+            },
+            Expression::UnaryOp { op, operand } => Expression::UnaryOp {
+                op: *op,
+                operand: Box::new(self.extract_aggregations(operand, aggregates)?),
+            },
+            Expression::Property { object, property } => Expression::Property {
+                object: Box::new(self.extract_aggregations(object, aggregates)?),
+                property: property.clone(),
+            },
+            Expression::FunctionCall { name, args } => Expression::FunctionCall {
+                name: name.clone(),
+                args: args
+                    .iter()
+                    .map(|a| self.extract_aggregations(a, aggregates))
+                    .collect::<Result<_>>()?,
+            },
+            Expression::List(items) => Expression::List(
+                items
+                    .iter()
+                    .map(|i| self.extract_aggregations(i, aggregates))
+                    .collect::<Result<_>>()?,
+            ),
+            Expression::Map(entries) => Expression::Map(
+                entries
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.extract_aggregations(v, aggregates)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    // Patterns
+
+    fn hidden_name(&mut self) -> String {
+        self.hidden += 1;
+        format!("#{}", self.hidden)
+    }
+
+    fn flatten(&mut self, pattern: &Pattern) -> Result<PatternChain> {
+        match pattern {
+            Pattern::Node(node) => Ok(PatternChain {
+                path: None,
+                nodes: vec![self.node_step(node)],
+                relationships: vec![],
+            }),
+            Pattern::Relationship(rel) => {
+                let mut chain = PatternChain {
+                    path: None,
+                    nodes: vec![self.node_step(&rel.from)],
+                    relationships: vec![],
+                };
+                self.flatten_relationship(rel, &mut chain)?;
+                Ok(chain)
+            }
+            Pattern::Path(path) => {
+                let mut chain = self.flatten(&path.pattern)?;
+                chain.path = Some(path.variable.clone());
+                Ok(chain)
+            }
+            Pattern::Hyperedge(_) => Err(invalid(
+                "Hyperedge patterns are not supported by the query engine",
+            )),
+        }
+    }
+
+    fn flatten_relationship(
+        &mut self,
+        rel: &RelationshipPattern,
+        chain: &mut PatternChain,
+    ) -> Result<()> {
+        let variable = match &rel.variable {
+            Some(v) => v.clone(),
+            None => self.hidden_name(),
+        };
+        chain.relationships.push(RelStep {
+            variable,
+            rel_type: rel.rel_type.clone(),
+            properties: sorted_properties(rel.properties.as_ref()),
+            direction: rel.direction,
+            range: rel.range.as_ref().map(|r| (r.min.unwrap_or(1), r.max)),
+        });
+
+        match rel.to.as_ref() {
+            Pattern::Node(node) => {
+                chain.nodes.push(self.node_step(node));
+                Ok(())
+            }
+            Pattern::Relationship(next) => {
+                chain.nodes.push(self.node_step(&next.from));
+                self.flatten_relationship(next, chain)
+            }
+            _ => Err(invalid("Unsupported pattern")),
+        }
+    }
+
+    fn node_step(&mut self, node: &NodePattern) -> NodeStep {
+        NodeStep {
+            variable: match &node.variable {
+                Some(v) => v.clone(),
+                None => self.hidden_name(),
+            },
+            labels: node.labels.clone(),
+            properties: sorted_properties(node.properties.as_ref()),
+        }
+    }
+
+    /// Plan the scans and expansions for a chain
+    fn plan_chain(
+        &mut self,
+        input: PlanOperator,
+        chain: PatternChain,
+        relationships: &mut Vec<String>,
+        predicate: Option<&Expression>,
+    ) -> Result<PlanOperator> {
+        // Paths follow the written order even if the chain is reversed
+        let path = chain.path.clone().map(|variable| {
+            let relationships: Vec<String> = chain
+                .relationships
+                .iter()
+                .map(|r| r.variable.clone())
+                .collect();
+            (variable, chain.nodes[0].variable.clone(), relationships)
+        });
+        let chain = self.orient(chain, predicate);
+
+        let first = &chain.nodes[0];
+        self.check_properties(&first.properties)?;
+        let mut op = PlanOperator::NodeScan {
+            input: Box::new(input),
+            node: first.clone(),
+            access: NodeAccess::AllNodes,
+        };
+        self.scope.insert(first.variable.clone());
+
+        for (i, rel) in chain.relationships.iter().enumerate() {
+            let to = &chain.nodes[i + 1];
+            self.check_properties(&rel.properties)?;
+            self.check_properties(&to.properties)?;
+            op = PlanOperator::Expand {
+                input: Box::new(op),
+                from: chain.nodes[i].variable.clone(),
+                relationship: rel.clone(),
+                to: to.clone(),
+                unique: relationships.clone(),
+            };
+            relationships.push(rel.variable.clone());
+            self.scope.insert(rel.variable.clone());
+            self.scope.insert(to.variable.clone());
+        }
+
+        if let Some((variable, start, relationships)) = path {
+            self.scope.insert(variable.clone());
+            op = PlanOperator::BuildPath {
+                input: Box::new(op),
+                variable,
+                start,
+                relationships,
+            };
+        }
+
+        Ok(op)
+    }
+
+    /// Reverse a chain when its last node is cheaper to find than its first
+    fn orient(&self, mut chain: PatternChain, predicate: Option<&Expression>) -> PatternChain {
+        if chain.relationships.is_empty() || chain.relationships.iter().any(|r| r.range.is_some()) {
+            return chain;
+        }
+
+        // Property constraints may only refer to variables bound before the
+        // chain, otherwise the chain has to be matched in the written order
+        let self_referencing = chain
+            .nodes
+            .iter()
+            .flat_map(|n| &n.properties)
+            .chain(chain.relationships.iter().flat_map(|r| &r.properties))
+            .any(|(_, e)| {
+                expression_variables(e)
+                    .iter()
+                    .any(|v| !self.scope.contains(v))
+            });
+        if self_referencing {
+            return chain;
+        }
+
+        let first = self.start_cost(&chain.nodes[0], predicate);
+        let last = self.start_cost(chain.nodes.last().unwrap(), predicate);
+        if last < first {
+            chain.nodes.reverse();
+            chain.relationships.reverse();
+            for rel in &mut chain.relationships {
+                rel.direction = match rel.direction {
+                    Direction::Outgoing => Direction::Incoming,
+                    Direction::Incoming => Direction::Outgoing,
+                    Direction::Undirected => Direction::Undirected,
+                };
+            }
+        }
+        chain
+    }
+
+    /// Estimated number of candidates when a chain starts at this node
+    fn start_cost(&self, node: &NodeStep, predicate: Option<&Expression>) -> usize {
+        if self.scope.contains(&node.variable) {
+            return 0;
+        }
+        let indexed = !node.properties.is_empty()
+            || predicate
+                .map(|p| {
+                    conjuncts(p)
+                        .iter()
+                        .any(|c| equality_on(c, &node.variable, &self.scope).is_some())
+                })
+                .unwrap_or(false);
+        if indexed {
+            return 1;
+        }
+        node.labels
+            .iter()
+            .map(|l| self.db.count_nodes_by_label(l))
+            .min()
+            .unwrap_or_else(|| self.db.node_count())
+    }
+
+    // Validation
+
+    fn check_writable(&self, chain: &PatternChain, clause: &str) -> Result<()> {
+        for node in &chain.nodes {
+            if self.scope.contains(&node.variable)
+                && (!node.labels.is_empty() || !node.properties.is_empty())
+            {
+                return Err(invalid(format!(
+                    "Variable `{}` already declared; {} cannot add labels or properties to it",
+                    node.variable, clause
+                )));
+            }
+            self.check_properties(&node.properties)?;
+        }
+        for rel in &chain.relationships {
+            if rel.rel_type.is_none() {
+                return Err(invalid(format!(
+                    "Exactly one relationship type must be specified for {}",
+                    clause
+                )));
+            }
+            if rel.range.is_some() {
+                return Err(invalid(format!(
+                    "Variable length relationships cannot be used in {}",
+                    clause
+                )));
+            }
+            if clause == "CREATE" && rel.direction == Direction::Undirected {
+                return Err(invalid(
+                    "Only directed relationships are supported in CREATE",
+                ));
+            }
+            if self.scope.contains(&rel.variable) {
+                return Err(invalid(format!(
+                    "Variable `{}` already declared",
+                    rel.variable
+                )));
+            }
+            self.check_properties(&rel.properties)?;
+        }
+        Ok(())
+    }
+
+    fn check_set_items(&self, items: &[SetItem]) -> Result<()> {
+        for item in items {
+            match item {
+                SetItem::Property {
+                    variable, value, ..
+                }
+                | SetItem::Variable { variable, value } => {
+                    self.check_variable(variable)?;
+                    self.check_expression(value, false)?;
+                }
+                SetItem::Labels { variable, .. } => self.check_variable(variable)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_properties(&self, properties: &[(String, Expression)]) -> Result<()> {
+        for (_, expression) in properties {
+            self.check_expression(expression, false)?;
+        }
+        Ok(())
+    }
+
+    fn check_variable(&self, variable: &str) -> Result<()> {
+        if self.scope.contains(variable) {
+            Ok(())
+        } else {
+            Err(invalid(format!("Variable `{}` not defined", variable)))
+        }
+    }
+
+    fn check_expression(&self, expression: &Expression, allow_aggregation: bool) -> Result<()> {
+        if !allow_aggregation && expression.has_aggregation() {
+            return Err(invalid(format!(
+                "Invalid use of aggregating function in this context: {}",
+                expression
+            )));
+        }
+        for variable in expression_variables(expression) {
+            if !variable.starts_with(['#', '$']) {
+                self.check_variable(&variable)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Optimization
+
+    /// Pick the access path of every node scan
+    fn choose_access(&self, op: &mut PlanOperator) {
+        match op {
+            PlanOperator::Optional { plan, .. } => self.choose_access(plan),
+            PlanOperator::Merge { matcher, .. } => self.choose_access(matcher),
+            _ => {}
+        }
+        if let Some(input) = op.input_mut() {
+            self.choose_access(input);
+        }
+
+        match op {
+            PlanOperator::NodeScan {
+                input,
+                node,
+                access,
+            } => {
+                let bound = input.bound_variables();
+                let usable = |(_, e): &&(String, Expression)| {
+                    expression_variables(e).iter().all(|v| bound.contains(v))
+                };
+                *access = if bound.contains(&node.variable) {
+                    NodeAccess::Bound
+                } else if let Some(access) =
+                    node.properties
+                        .iter()
+                        .filter(usable)
+                        .find_map(|(key, value)| {
+                            let (index, _) = self.index_on(node, IndexKind::Range, key)?;
+                            Some(NodeAccess::RangeIndex {
+                                index,
+                                property: key.clone(),
+                                seek: RangeSeek::Equal(value.clone()),
+                            })
+                        })
+                {
+                    access
+                } else if let Some((key, value)) = node.properties.iter().find(usable) {
+                    NodeAccess::PropertyIndex {
+                        key: key.clone(),
+                        value: value.clone(),
+                    }
+                } else if let Some(label) = node
+                    .labels
+                    .iter()
+                    .min_by_key(|l| self.db.count_nodes_by_label(l))
+                {
+                    NodeAccess::LabelIndex {
+                        label: label.clone(),
+                    }
+                } else {
+                    NodeAccess::AllNodes
+                };
+            }
+            PlanOperator::Filter { .. } => {
+                // Predicates in the filters directly above a scan let it seek
+                // an index; the filters stay to check the candidates
+                let mut predicates = Vec::new();
+                let mut current = &mut *op;
+                while let PlanOperator::Filter { input, predicate } = current {
+                    predicates.push(predicate.clone());
+                    current = input.as_mut();
+                }
+                if let PlanOperator::NodeScan {
+                    input,
+                    node,
+                    access,
+                } = current
+                {
+                    // Seeks chosen by lower filters are revisited with all of them
+                    let revisable = match access {
+                        NodeAccess::Bound => false,
+                        NodeAccess::LabelIndex { .. } | NodeAccess::AllNodes => true,
+                        _ => node.properties.is_empty(),
+                    };
+                    if revisable {
+                        let bound = input.bound_variables();
+                        let conjuncts: Vec<Expression> =
+                            predicates.iter().flat_map(conjuncts).collect();
+                        if let Some(seek) = self.index_seek(node, &conjuncts, &bound) {
+                            *access = seek;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Declared index of `kind` on `property` for one of the node's labels,
+    /// with that label
+    fn index_on(
+        &self,
+        node: &NodeStep,
+        kind: IndexKind,
+        property: &str,
+    ) -> Option<(String, String)> {
+        node.labels.iter().find_map(|label| {
+            let index = self.db.schema().find_index(kind, label, property)?;
+            Some((index, label.clone()))
+        })
+    }
+
+    /// Best index access for the filter conjuncts on a scanned node
+    fn index_seek(
+        &self,
+        node: &NodeStep,
+        conjuncts: &[Expression],
+        bound: &HashSet<String>,
+    ) -> Option<NodeAccess> {
+        if let Some((key, value)) = conjuncts
+            .iter()
+            .find_map(|c| equality_on(c, &node.variable, bound))
+        {
+            return Some(match self.index_on(node, IndexKind::Range, &key) {
+                Some((index, _)) => NodeAccess::RangeIndex {
+                    index,
+                    property: key,
+                    seek: RangeSeek::Equal(value),
+                },
+                None => NodeAccess::PropertyIndex { key, value },
+            });
+        }
+
+        let comparisons: Vec<_> = conjuncts
+            .iter()
+            .filter_map(|c| comparison_on(c, &node.variable, bound))
+            .collect();
+        for (property, op, value) in &comparisons {
+            if *op == BinaryOperator::Contains {
+                continue;
+            }
+            let Some((index, _)) = self.index_on(node, IndexKind::Range, property) else {
+                continue;
+            };
+            let seek = if *op == BinaryOperator::StartsWith {
+                RangeSeek::Prefix(value.clone())
+            } else {
+                // Every bound on the property narrows the same seek
+                let (mut lower, mut upper) = (None, None);
+                for (other, op, value) in &comparisons {
+                    if other != property {
+                        continue;
+                    }
+                    match op {
+                        BinaryOperator::GreaterThan => lower = Some((value.clone(), false)),
+                        BinaryOperator::GreaterThanOrEqual => lower = Some((value.clone(), true)),
+                        BinaryOperator::LessThan => upper = Some((value.clone(), false)),
+                        BinaryOperator::LessThanOrEqual => upper = Some((value.clone(), true)),
+                        _ => {}
+                    }
+                }
+                RangeSeek::Range { lower, upper }
+            };
+            return Some(NodeAccess::RangeIndex {
+                index,
+                property: property.clone(),
+                seek,
+            });
+        }
+
+        comparisons.into_iter().find_map(|(property, op, text)| {
+            if op != BinaryOperator::Contains {
+                return None;
+            }
+            let (index, label) = self.index_on(node, IndexKind::FullText, &property)?;
+            Some(NodeAccess::FullTextIndex {
+                index,
+                label,
+                property,
+                text,
+            })
+        })
+    }
+}
+
+/// Projection clauses shared by WITH and RETURN
+struct Projection<'q> {
+    distinct: bool,
+    items: &'q [ReturnItem],
+    order_by: Option<&'q OrderBy>,
+    skip: Option<&'q Expression>,
+    limit: Option<&'q Expression>,
+}
+
+fn sorted_properties(properties: Option<&PropertyMap>) -> Vec<(String, Expression)> {
+    let mut properties: Vec<_> = properties
+        .map(|p| p.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+    properties.sort_by(|a, b| a.0.cmp(&b.0));
+    properties
+}
+
+/// SKIP and LIMIT only accept literals and parameters
+fn check_count(expression: &Expression, clause: &str) -> Result<()> {
+    if expression_variables(expression)
+        .iter()
+        .all(|v| v.starts_with('$'))
+    {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "{} must be a literal or a parameter, got `{}`",
+            clause, expression
+        )))
+    }
+}
+
+/// Variables referenced by an expression, including `$parameters`
+///
+/// The `*` of `count(*)` is not a variable and is left out.
+pub fn expression_variables(expression: &Expression) -> HashSet<String> {
+    fn collect(expression: &Expression, vars: &mut HashSet<String>) {
+        match expression {
+            Expression::Variable(name) if name != "*" => {
+                vars.insert(name.clone());
+            }
+            Expression::Property { object, .. } => collect(object, vars),
+            Expression::List(items) => items.iter().for_each(|e| collect(e, vars)),
+            Expression::Map(entries) => entries.values().for_each(|e| collect(e, vars)),
+            Expression::BinaryOp { left, right, .. } => {
+                collect(left, vars);
+                collect(right, vars);
+            }
+            Expression::UnaryOp { operand, .. } => collect(operand, vars),
+            Expression::FunctionCall { args, .. } => args.iter().for_each(|e| collect(e, vars)),
+            Expression::Aggregation { expression, .. } => collect(expression, vars),
+            Expression::Case {
+                expression,
+                alternatives,
+                default,
+            } => {
+                if let Some(e) = expression {
+                    collect(e, vars);
+                }
+                for (when, then) in alternatives {
+                    collect(when, vars);
+                    collect(then, vars);
+                }
+                if let Some(e) = default {
+                    collect(e, vars);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut vars = HashSet::new();
+    collect(expression, &mut vars);
+    vars
+}
+
+/// Split a predicate into its AND-ed parts
+fn conjuncts(expression: &Expression) -> Vec<Expression> {
+    match expression {
+        Expression::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut parts = conjuncts(left);
+            parts.extend(conjuncts(right));
+            parts
+        }
+        other => vec![other.clone()],
+    }
+}
+
+/// Match `variable.key = value`, where `value` only uses `bound` variables
+fn equality_on(
+    expression: &Expression,
+    variable: &str,
+    bound: &HashSet<String>,
+) -> Option<(String, Expression)> {
+    let Expression::BinaryOp {
+        left,
+        op: BinaryOperator::Equal,
+        right,
+    } = expression
+    else {
+        return None;
+    };
+
+    [(left, right), (right, left)]
+        .into_iter()
+        .find_map(|(property, value)| match property.as_ref() {
+            Expression::Property { object, property }
+                if matches!(object.as_ref(), Expression::Variable(v) if v == variable) =>
+            {
+                let usable = expression_variables(value)
+                    .iter()
+                    .all(|v| v.starts_with('$') || (v != variable && bound.contains(v)));
+                usable.then(|| (property.clone(), value.as_ref().clone()))
+            }
+            _ => None,
+        })
+}
+
+/// Match a range or string predicate on `variable.key`, turned around so the
+/// property is on the left; `value` only uses `bound` variables
+fn comparison_on(
+    expression: &Expression,
+    variable: &str,
+    bound: &HashSet<String>,
+) -> Option<(String, BinaryOperator, Expression)> {
+    let Expression::BinaryOp { left, op, right } = expression else {
+        return None;
+    };
+    let flipped = match op {
+        BinaryOperator::LessThan => Some(BinaryOperator::GreaterThan),
+        BinaryOperator::LessThanOrEqual => Some(BinaryOperator::GreaterThanOrEqual),
+        BinaryOperator::GreaterThan => Some(BinaryOperator::LessThan),
+        BinaryOperator::GreaterThanOrEqual => Some(BinaryOperator::LessThanOrEqual),
+        BinaryOperator::StartsWith | BinaryOperator::Contains => None,
+        _ => return None,
+    };
+
+    [(left, right, Some(*op)), (right, left, flipped)]
+        .into_iter()
+        .find_map(|(property, value, op)| match property.as_ref() {
+            Expression::Property { object, property }
+                if matches!(object.as_ref(), Expression::Variable(v) if v == variable) =>
+            {
+                let usable = expression_variables(value)
+                    .iter()
+                    .all(|v| v.starts_with('$') || (v != variable && bound.contains(v)));
+                if !usable {
+                    return None;
+                }
+                op.map(|op| (property.clone(), op, value.as_ref().clone()))
+            }
+            _ => None,
+        })
+}
+
+/// Move every filter conjunct down to the operator that binds its variables
+fn push_down_filters(op: &mut PlanOperator) {
+    match op {
+        PlanOperator::Optional { plan, .. } => push_down_filters(plan),
+        PlanOperator::Merge { matcher, .. } => push_down_filters(matcher),
+        _ => {}
+    }
+    if let Some(input) = op.input_mut() {
+        push_down_filters(input);
+    }
+
+    if let PlanOperator::Filter { .. } = op {
+        let placeholder = PlanOperator::Argument { variables: vec![] };
+        let PlanOperator::Filter { input, predicate } = std::mem::replace(op, placeholder) else {
+            unreachable!()
+        };
+        let mut result = *input;
+        for conjunct in conjuncts(&predicate) {
+            let variables = expression_variables(&conjunct);
+            result = push_conjunct(result, conjunct, &variables);
+        }
+        *op = result;
+    }
+}
+
+fn push_conjunct(
+    mut op: PlanOperator,
+    conjunct: Expression,
+    variables: &HashSet<String>,
+) -> PlanOperator {
+    let transparent = match &op {
+        PlanOperator::NodeScan { .. }
+        | PlanOperator::Expand { .. }
+        | PlanOperator::Filter { .. }
+        | PlanOperator::BuildPath { .. }
+        | PlanOperator::Optional { .. } => true,
+        PlanOperator::Project { items, .. } => {
+            items.iter().all(|(name, _)| !variables.contains(name))
+        }
+        PlanOperator::ProcedureCall { yields, .. } => {
+            yields.iter().all(|(_, name)| !variables.contains(name))
+        }
+        _ => false,
+    };
+
+    if transparent {
+        if let Some(input) = op.input_mut() {
+            let bound = input.bound_variables();
+            if variables
+                .iter()
+                .all(|v| v.starts_with('$') || bound.contains(v))
+            {
+                let placeholder = PlanOperator::Argument { variables: vec![] };
+                let inner = std::mem::replace(input.as_mut(), placeholder);
+                **input = push_conjunct(inner, conjunct, variables);
+                return op;
+            }
+        }
+    }
+
+    PlanOperator::Filter {
+        input: Box::new(op),
+        predicate: conjunct,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cypher::parse_cypher;
+    use crate::node::NodeBuilder;
+    use crate::schema::IndexDefinition;
+
+    fn plan(db: &GraphDB, cypher: &str) -> QueryPlan {
+        let query = parse_cypher(cypher).unwrap();
+        let mut planner = QueryPlanner::new(db);
+        let plan = planner.plan(&query).unwrap();
+        planner.optimize(plan)
+    }
+
+    fn find_scan<'p>(op: &'p PlanOperator, variable: &str) -> Option<&'p NodeAccess> {
+        match op {
+            PlanOperator::NodeScan { node, access, .. } if node.variable == variable => {
+                Some(access)
+            }
+            _ => op.input().and_then(|input| find_scan(input, variable)),
+        }
+    }
+
+    #[test]
+    fn test_where_equality_uses_property_index() {
+        let db = GraphDB::new();
+        let plan = plan(&db, "MATCH (n:Person) WHERE n.name = $name RETURN n");
+        assert_eq!(
+            find_scan(&plan.root, "n"),
+            Some(&NodeAccess::PropertyIndex {
+                key: "name".to_string(),
+                value: Expression::Variable("$name".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_declared_indexes_chosen_for_predicates() {
+        let db = GraphDB::new();
+        db.create_index(IndexDefinition::range("by_age", "Person", "age"))
+            .unwrap();
+        db.create_index(IndexDefinition::full_text("bios", "Person", ["bio"]))
+            .unwrap();
+
+        let indexed = plan(
+            &db,
+            "MATCH (n:Person) WHERE n.age > 30 AND n.age <= $max RETURN n",
+        );
+        assert_eq!(
+            find_scan(&indexed.root, "n"),
+            Some(&NodeAccess::RangeIndex {
+                index: "by_age".to_string(),
+                property: "age".to_string(),
+                seek: RangeSeek::Range {
+                    lower: Some((Expression::Integer(30), false)),
+                    upper: Some((Expression::Variable("$max".to_string()), true)),
+                },
+            })
+        );
+
+        let indexed = plan(&db, "MATCH (n:Person) WHERE n.bio CONTAINS 'rust' RETURN n");
+        assert!(matches!(
+            find_scan(&indexed.root, "n"),
+            Some(NodeAccess::FullTextIndex { index, .. }) if index == "bios"
+        ));
+
+        // Other labels fall back to the property index
+        let indexed = plan(&db, "MATCH (n:Robot {age: 3}) RETURN n");
+        assert!(matches!(
+            find_scan(&indexed.root, "n"),
+            Some(NodeAccess::PropertyIndex { .. })
+        ));
+    }
+
+    #[test]
+    fn test_filters_pushed_below_expansion() {
+        let db = GraphDB::new();
+        let plan = plan(
+            &db,
+            "MATCH (a:Person)-[:KNOWS]->(b) WHERE a.age > 30 AND b.age < 40 RETURN a, b",
+        );
+        // a's filter sits on the scan, below the expansion; b's filter above it
+        let PlanOperator::Select { input, .. } = &plan.root else {
+            panic!("expected Select");
+        };
+        let PlanOperator::Project { input, .. } = input.as_ref() else {
+            panic!("expected Project");
+        };
+        let PlanOperator::Filter { input, .. } = input.as_ref() else {
+            panic!("expected Filter on b");
+        };
+        let PlanOperator::Expand { input, .. } = input.as_ref() else {
+            panic!("expected Expand");
+        };
+        assert!(matches!(input.as_ref(), PlanOperator::Filter { .. }));
+    }
+
+    #[test]
+    fn test_chain_starts_at_selective_end() {
+        let db = GraphDB::new();
+        for _ in 0..10 {
+            db.create_node(NodeBuilder::new().label("Person").build())
+                .unwrap();
+        }
+        db.create_node(NodeBuilder::new().label("City").build())
+            .unwrap();
+
+        let plan = plan(&db, "MATCH (p:Person)-[:LIVES_IN]->(c:City) RETURN p");
+        assert_eq!(
+            find_scan(&plan.root, "c"),
+            Some(&NodeAccess::LabelIndex {
+                label: "City".to_string()
+            })
+        );
+        assert!(find_scan(&plan.root, "p").is_none());
+    }
+
+    #[test]
+    fn test_undefined_variable_rejected() {
+        let db = GraphDB::new();
+        let query = parse_cypher("MATCH (n) RETURN m").unwrap();
+        assert!(matches!(
+            QueryPlanner::new(&db).plan(&query),
+            Err(GraphError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn test_procedure_call_validation() {
+        let db = GraphDB::new();
+        let standalone = plan(&db, "CALL algo.pagerank()");
+        assert_eq!(standalone.columns, vec!["node", "score"]);
+
+        let filtered = plan(
+            &db,
+            "CALL algo.pagerank() YIELD node, score AS rank WHERE rank > 0.5 RETURN node",
+        );
+        let tree = filtered.to_string();
+        let filter = tree.find("Filter(").expect("WHERE becomes a filter");
+        let call = tree
+            .find("ProcedureCall(algo.pagerank() YIELD node, score AS rank)")
+            .expect("procedure call in plan");
+        assert!(filter < call, "{}", tree);
+
+        for invalid in [
+            "CALL algo.missing()",
+            "CALL algo.pagerank() YIELD rank RETURN rank",
+            "MATCH (n) CALL algo.pagerank() RETURN n",
+            "MATCH (node) CALL algo.pagerank() YIELD node RETURN node",
+            "MATCH (n) CALL algo.pagerank() YIELD score",
+        ] {
+            let query = parse_cypher(invalid).unwrap();
+            assert!(
+                matches!(
+                    QueryPlanner::new(&db).plan(&query),
+                    Err(GraphError::InvalidQuery(_))
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,588 @@
+//! Built-in procedures invoked with `CALL`
+//!
+//! - `algo.*`: graph algorithms from [`crate::algorithms`], run on a projection
+//!   of the graph chosen by an optional configuration map
+//! - `db.index.vector.queryNodes`: nearest neighbors from a vector index
+//!   created with [`GraphDB::create_vector_index`]
+//! - `db.index.fulltext.queryNodes`: word search on a full-text index created
+//!   with `CREATE FULLTEXT INDEX`
+//!
+//! Procedures receive their evaluated arguments and return rows whose values
+//! follow the procedure's output columns.
+
+use super::result::{Path, Value};
+use crate::algorithms::{self, Direction, GraphProjection, ProjectionConfig, SimilarityMetric};
+use crate::error::{GraphError, Result};
+use crate::graph::GraphDB;
+use crate::types::{NodeId, PropertyValue};
+use std::collections::HashMap;
+
+/// Name and output columns of a procedure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcedureSignature {
+    pub name: &'static str,
+    pub outputs: &'static [&'static str],
+}
+
+const fn procedure(name: &'static str, outputs: &'static [&'static str]) -> ProcedureSignature {
+    ProcedureSignature { name, outputs }
+}
+
+const PROCEDURES: &[ProcedureSignature] = &[
+    procedure("algo.bfs", &["node", "depth"]),
+    procedure("algo.dfs", &["node", "depth"]),
+    procedure("algo.dijkstra", &["path", "cost"]),
+    procedure("algo.astar", &["path", "cost"]),
+    procedure("algo.kShortestPaths", &["index", "path", "cost"]),
+    procedure("algo.pagerank", &["node", "score"]),
+    procedure("algo.personalizedPagerank", &["node", "score"]),
+    procedure("algo.connectedComponents", &["node", "componentId"]),
+    procedure("algo.stronglyConnectedComponents", &["node", "componentId"]),
+    procedure("algo.louvain", &["node", "communityId"]),
+    procedure("algo.labelPropagation", &["node", "communityId"]),
+    procedure("algo.betweenness", &["node", "score"]),
+    procedure("algo.closeness", &["node", "score"]),
+    procedure("algo.triangleCount", &["node", "triangles"]),
+    procedure("algo.jaccard", &["similarity"]),
+    procedure("algo.adamicAdar", &["similarity"]),
+    procedure("algo.similarNodes", &["node", "similarity"]),
+    procedure("db.index.vector.queryNodes", &["node", "score"]),
+    procedure("db.index.fulltext.queryNodes", &["node", "score"]),
+];
+
+/// Configuration keys understood by the `algo.*` procedures
+const CONFIG_KEYS: &[&str] = &[
+    "labels",
+    "relationshipTypes",
+    "weightProperty",
+    "defaultWeight",
+    "direction",
+    "maxDepth",
+    "dampingFactor",
+    "maxIterations",
+    "tolerance",
+    "maxLevels",
+    "resolution",
+    "normalized",
+    "positionProperty",
+    "metric",
+    "topK",
+];
+
+/// All built-in procedures
+pub fn procedures() -> &'static [ProcedureSignature] {
+    PROCEDURES
+}
+
+/// Look up a procedure by name, ignoring case
+pub fn signature(name: &str) -> Option<&'static ProcedureSignature> {
+    PROCEDURES
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Run a procedure and return its rows
+pub fn call(db: &GraphDB, name: &str, arguments: &[Value]) -> Result<Vec<Vec<Value>>> {
+    let signature = signature(name)
+        .ok_or_else(|| GraphError::InvalidQuery(format!("Unknown procedure `{}`", name)))?;
+    let args = Arguments {
+        procedure: signature.name,
+        values: arguments,
+    };
+
+    match signature.name {
+        "algo.bfs" | "algo.dfs" => {
+            args.arity(1, 2)?;
+            let start = args.node(0)?;
+            let config = args.config(1)?;
+            let graph = config.projection(db)?;
+            let traversal = algorithms::TraversalConfig {
+                direction: config.direction()?,
+                max_depth: config.usize("maxDepth")?,
+            };
+            let visits = if signature.name == "algo.bfs" {
+                algorithms::bfs(&graph, &start, &traversal)?
+            } else {
+                algorithms::dfs(&graph, &start, &traversal)?
+            };
+            visits
+                .into_iter()
+                .map(|visit| {
+                    Ok(vec![
+                        node(db, &visit.node)?,
+                        Value::Integer(visit.depth as i64),
+                    ])
+                })
+                .collect()
+        }
+        "algo.dijkstra" | "algo.astar" => {
+            args.arity(2, 3)?;
+            let (source, target) = (args.node(0)?, args.node(1)?);
+            let config = args.config(2)?;
+            let graph = config.projection(db)?;
+            let direction = config.direction()?;
+            let path = match config.string("positionProperty")? {
+                Some(key) if signature.name == "algo.astar" => {
+                    let goal = position(db, &target, &key);
+                    let heuristic = |id: &NodeId| match (&goal, position(db, id, &key)) {
+                        (Some(goal), Some(here)) => distance(goal, &here),
+                        _ => 0.0,
+                    };
+                    algorithms::astar(&graph, &source, &target, direction, heuristic)?
+                }
+                _ => algorithms::dijkstra(&graph, &source, &target, direction)?,
+            };
+            path.into_iter()
+                .map(|p| Ok(vec![path_value(db, &p)?, Value::Float(p.cost)]))
+                .collect()
+        }
+        "algo.kShortestPaths" => {
+            args.arity(3, 4)?;
+            let (source, target) = (args.node(0)?, args.node(1)?);
+            let k = args.count(2)?;
+            let config = args.config(3)?;
+            let graph = config.projection(db)?;
+            let paths =
+                algorithms::k_shortest_paths(&graph, &source, &target, k, config.direction()?)?;
+            paths
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    Ok(vec![
+                        Value::Integer(i as i64),
+                        path_value(db, p)?,
+                        Value::Float(p.cost),
+                    ])
+                })
+                .collect()
+        }
+        "algo.pagerank" | "algo.personalizedPagerank" => {
+            let personalized = signature.name == "algo.personalizedPagerank";
+            let offset = usize::from(personalized);
+            args.arity(offset, offset + 1)?;
+            let config = args.config(offset)?;
+            let graph = config.projection(db)?;
+            let defaults = algorithms::PageRankConfig::default();
+            let pagerank = algorithms::PageRankConfig {
+                damping_factor: config
+                    .f64("dampingFactor")?
+                    .unwrap_or(defaults.damping_factor),
+                max_iterations: config
+                    .usize("maxIterations")?
+                    .unwrap_or(defaults.max_iterations),
+                tolerance: config.f64("tolerance")?.unwrap_or(defaults.tolerance),
+            };
+            if !(0.0..=1.0).contains(&pagerank.damping_factor) {
+                return Err(args.error("dampingFactor must be between 0 and 1"));
+            }
+            let scores = if personalized {
+                algorithms::personalized_pagerank(&graph, &args.nodes(0)?, &pagerank)?
+            } else {
+                algorithms::pagerank(&graph, &pagerank)
+            };
+            scored_rows(db, scores)
+        }
+        "algo.connectedComponents" | "algo.stronglyConnectedComponents" => {
+            args.arity(0, 1)?;
+            let graph = args.config(0)?.projection(db)?;
+            let components = if signature.name == "algo.connectedComponents" {
+                algorithms::connected_components(&graph)
+            } else {
+                algorithms::strongly_connected_components(&graph)
+            };
+            let mut membership: Vec<(NodeId, usize)> = components
+                .into_iter()
+                .enumerate()
+                .flat_map(|(c, members)| members.into_iter().map(move |id| (id, c)))
+                .collect();
+            membership.sort();
+            membership_rows(db, membership)
+        }
+        "algo.louvain" => {
+            args.arity(0, 1)?;
+            let config = args.config(0)?;
+            let graph = config.projection(db)?;
+            let defaults = algorithms::LouvainConfig::default();
+            let louvain = algorithms::LouvainConfig {
+                max_levels: config.usize("maxLevels")?.unwrap_or(defaults.max_levels),
+                max_iterations: config
+                    .usize("maxIterations")?
+                    .unwrap_or(defaults.max_iterations),
+                resolution: config.f64("resolution")?.unwrap_or(defaults.resolution),
+            };
+            membership_rows(db, algorithms::louvain(&graph, &louvain).membership)
+        }
+        "algo.labelPropagation" => {
+            args.arity(0, 1)?;
+            let config = args.config(0)?;
+            let graph = config.projection(db)?;
+            let max_iterations = config.usize("maxIterations")?.unwrap_or(10);
+            membership_rows(
+                db,
+                algorithms::label_propagation(&graph, max_iterations).membership,
+            )
+        }
+        "algo.betweenness" | "algo.closeness" => {
+            args.arity(0, 1)?;
+            let config = args.config(0)?;
+            let graph = config.projection(db)?;
+            let direction = config.direction()?;
+            let scores = if signature.name == "algo.betweenness" {
+                let normalized = config.bool("normalized")?.unwrap_or(false);
+                algorithms::betweenness_centrality(&graph, direction, normalized)
+            } else {
+                algorithms::closeness_centrality(&graph, direction)
+            };
+            scored_rows(db, scores)
+        }
+        "algo.triangleCount" => {
+            args.arity(0, 1)?;
+            let graph = args.config(0)?.projection(db)?;
+            algorithms::triangle_count(&graph)
+                .per_node
+                .into_iter()
+                .map(|(id, count)| Ok(vec![node(db, &id)?, Value::Integer(count as i64)]))
+                .collect()
+        }
+        "algo.jaccard" | "algo.adamicAdar" => {
+            args.arity(2, 3)?;
+            let (a, b) = (args.node(0)?, args.node(1)?);
+            let graph = args.config(2)?.projection(db)?;
+            let similarity = if signature.name == "algo.jaccard" {
+                algorithms::jaccard(&graph, &a, &b)?
+            } else {
+                algorithms::adamic_adar(&graph, &a, &b)?
+            };
+            Ok(vec![vec![Value::Float(similarity)]])
+        }
+        "algo.similarNodes" => {
+            args.arity(1, 2)?;
+            let start = args.node(0)?;
+            let config = args.config(1)?;
+            let graph = config.projection(db)?;
+            let metric = match config.string("metric")?.as_deref() {
+                None => SimilarityMetric::default(),
+                Some(m) if m.eq_ignore_ascii_case("jaccard") => SimilarityMetric::Jaccard,
+                Some(m) if m.eq_ignore_ascii_case("adamicAdar") => SimilarityMetric::AdamicAdar,
+                Some(m) => {
+                    return Err(args.error(format!(
+                        "metric must be 'jaccard' or 'adamicAdar', got '{}'",
+                        m
+                    )))
+                }
+            };
+            let top_k = config.usize("topK")?.unwrap_or(10);
+            scored_rows(db, algorithms::most_similar(&graph, &start, metric, top_k)?)
+        }
+        "db.index.vector.queryNodes" => {
+            args.arity(3, 3)?;
+            let name = args.string(0)?;
+            let k = args.count(1)?;
+            let query = args.vector(2)?;
+            let index = db
+                .vector_index(&name)
+                .ok_or_else(|| GraphError::IndexError(format!("No vector index named {}", name)))?;
+            index
+                .query(&query, k)?
+                .into_iter()
+                // Skip nodes deleted since the search started
+                .filter_map(|(id, score)| db.get_node(&id).map(|n| (n, score)))
+                .map(|(n, score)| Ok(vec![Value::Node(n), Value::Float(score as f64)]))
+                .collect()
+        }
+        "db.index.fulltext.queryNodes" => {
+            args.arity(2, 2)?;
+            let name = args.string(0)?;
+            let query = args.string(1)?;
+            Ok(db
+                .schema()
+                .search(&name, &query)?
+                .into_iter()
+                .filter_map(|(id, score)| db.get_node(&id).map(|n| (n, score)))
+                .map(|(n, score)| vec![Value::Node(n), Value::Float(score)])
+                .collect())
+        }
+        _ => unreachable!("every signature has an implementation"),
+    }
+}
+
+/// Positional procedure arguments
+struct Arguments<'a> {
+    procedure: &'static str,
+    values: &'a [Value],
+}
+
+impl Arguments<'_> {
+    fn error(&self, message: impl std::fmt::Display) -> GraphError {
+        GraphError::CypherExecutionError(format!("{}: {}", self.procedure, message))
+    }
+
+    fn arity(&self, min: usize, max: usize) -> Result<()> {
+        let n = self.values.len();
+        if n < min || n > max {
+            let expected = if min == max {
+                min.to_string()
+            } else {
+                format!("{} to {}", min, max)
+            };
+            return Err(self.error(format!("expected {} arguments, got {}", expected, n)));
+        }
+        Ok(())
+    }
+
+    /// A node, given as a node value or its ID
+    fn node(&self, i: usize) -> Result<NodeId> {
+        node_id(&self.values[i])
+            .ok_or_else(|| self.error(format!("argument {} must be a node or node ID", i + 1)))
+    }
+
+    /// One node or a list of nodes
+    fn nodes(&self, i: usize) -> Result<Vec<NodeId>> {
+        match &self.values[i] {
+            Value::List(items) => items
+                .iter()
+                .map(|item| {
+                    node_id(item).ok_or_else(|| {
+                        self.error(format!("argument {} must be a list of nodes", i + 1))
+                    })
+                })
+                .collect(),
+            _ => Ok(vec![self.node(i)?]),
+        }
+    }
+
+    fn string(&self, i: usize) -> Result<String> {
+        match &self.values[i] {
+            Value::String(s) => Ok(s.clone()),
+            _ => Err(self.error(format!("argument {} must be a string", i + 1))),
+        }
+    }
+
+    /// A list of numbers
+    fn vector(&self, i: usize) -> Result<Vec<f32>> {
+        let error = || self.error(format!("argument {} must be a list of numbers", i + 1));
+        match &self.values[i] {
+            Value::List(items) => items
+                .iter()
+                .map(|item| match item {
+                    Value::Float(f) => Ok(*f as f32),
+                    Value::Integer(n) => Ok(*n as f32),
+                    _ => Err(error()),
+                })
+                .collect(),
+            _ => Err(error()),
+        }
+    }
+
+    fn count(&self, i: usize) -> Result<usize> {
+        match self.values[i] {
+            Value::Integer(n) if n >= 0 => Ok(n as usize),
+            _ => Err(self.error(format!("argument {} must be a non-negative integer", i + 1))),
+        }
+    }
+
+    /// Optional configuration map; a missing or null argument means defaults
+    fn config(&self, i: usize) -> Result<Config> {
+        let options = match self.values.get(i) {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Map(map)) => map.clone(),
+            Some(_) => {
+                return Err(self.error(format!("argument {} must be a configuration map", i + 1)))
+            }
+        };
+        if let Some(key) = options.keys().find(|k| !CONFIG_KEYS.contains(&k.as_str())) {
+            return Err(self.error(format!("unknown configuration key `{}`", key)));
+        }
+        Ok(Config {
+            procedure: self.procedure,
+            options,
+        })
+    }
+}
+
+/// Configuration map of an `algo.*` procedure
+struct Config {
+    procedure: &'static str,
+    options: HashMap<String, Value>,
+}
+
+impl Config {
+    fn error(&self, key: &str, expected: &str) -> GraphError {
+        GraphError::CypherExecutionError(format!(
+            "{}: configuration key `{}` must be {}",
+            self.procedure, key, expected
+        ))
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.options.get(key).filter(|v| !v.is_null())
+    }
+
+    fn string(&self, key: &str) -> Result<Option<String>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(self.error(key, "a string")),
+        }
+    }
+
+    /// A string or a list of strings
+    fn strings(&self, key: &str) -> Result<Vec<String>> {
+        match self.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::String(s)) => Ok(vec![s.clone()]),
+            Some(Value::List(items)) => items
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| self.error(key, "a list of strings"))
+                })
+                .collect(),
+            Some(_) => Err(self.error(key, "a string or a list of strings")),
+        }
+    }
+
+    fn f64(&self, key: &str) -> Result<Option<f64>> {
+        self.get(key)
+            .map(|v| v.as_f64().ok_or_else(|| self.error(key, "a number")))
+            .transpose()
+    }
+
+    fn usize(&self, key: &str) -> Result<Option<usize>> {
+        self.get(key)
+            .map(|v| match v {
+                Value::Integer(n) if *n >= 0 => Ok(*n as usize),
+                _ => Err(self.error(key, "a non-negative integer")),
+            })
+            .transpose()
+    }
+
+    fn bool(&self, key: &str) -> Result<Option<bool>> {
+        self.get(key)
+            .map(|v| v.as_bool().ok_or_else(|| self.error(key, "a boolean")))
+            .transpose()
+    }
+
+    fn direction(&self) -> Result<Direction> {
+        match self.string("direction")? {
+            None => Ok(Direction::default()),
+            Some(d) if d.eq_ignore_ascii_case("OUTGOING") => Ok(Direction::Outgoing),
+            Some(d) if d.eq_ignore_ascii_case("INCOMING") => Ok(Direction::Incoming),
+            Some(d) if d.eq_ignore_ascii_case("BOTH") => Ok(Direction::Both),
+            Some(_) => Err(self.error("direction", "'OUTGOING', 'INCOMING' or 'BOTH'")),
+        }
+    }
+
+    fn projection(&self, db: &GraphDB) -> Result<GraphProjection> {
+        let defaults = ProjectionConfig::default();
+        let config = ProjectionConfig {
+            labels: self.strings("labels")?,
+            relationship_types: self.strings("relationshipTypes")?,
+            weight_property: self.string("weightProperty")?,
+            default_weight: self
+                .f64("defaultWeight")?
+                .unwrap_or(defaults.default_weight),
+        };
+        Ok(GraphProjection::new(db, &config))
+    }
+}
+
+fn node_id(value: &Value) -> Option<NodeId> {
+    match value {
+        Value::Node(node) => Some(node.id.clone()),
+        Value::String(id) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+fn node(db: &GraphDB, id: &NodeId) -> Result<Value> {
+    db.get_node(id)
+        .map(Value::Node)
+        .ok_or_else(|| GraphError::NodeNotFound(id.clone()))
+}
+
+fn path_value(db: &GraphDB, path: &algorithms::WeightedPath) -> Result<Value> {
+    let nodes = path
+        .nodes
+        .iter()
+        .map(|id| {
+            db.get_node(id)
+                .ok_or_else(|| GraphError::NodeNotFound(id.clone()))
+        })
+        .collect::<Result<_>>()?;
+    let relationships = path
+        .relationships
+        .iter()
+        .map(|id| {
+            db.get_edge(id)
+                .ok_or_else(|| GraphError::EdgeNotFound(id.clone()))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Value::Path(Path {
+        nodes,
+        relationships,
+    }))
+}
+
+fn scored_rows(db: &GraphDB, scores: Vec<(NodeId, f64)>) -> Result<Vec<Vec<Value>>> {
+    scores
+        .into_iter()
+        .map(|(id, score)| Ok(vec![node(db, &id)?, Value::Float(score)]))
+        .collect()
+}
+
+fn membership_rows(db: &GraphDB, membership: Vec<(NodeId, usize)>) -> Result<Vec<Vec<Value>>> {
+    membership
+        .into_iter()
+        .map(|(id, c)| Ok(vec![node(db, &id)?, Value::Integer(c as i64)]))
+        .collect()
+}
+
+/// Numeric list property used as coordinates by the A* heuristic
+fn position(db: &GraphDB, id: &NodeId, key: &str) -> Option<Vec<f64>> {
+    match db.get_node(id)?.properties.get(key)? {
+        PropertyValue::Array(items) | PropertyValue::List(items) => items
+            .iter()
+            .map(|item| match item {
+                PropertyValue::Integer(i) => Some(*i as f64),
+                PropertyValue::Float(f) => Some(*f),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_lookup() {
+        let pagerank = signature("ALGO.PAGERANK").unwrap();
+        assert_eq!(pagerank.name, "algo.pagerank");
+        assert_eq!(pagerank.outputs, &["node", "score"]);
+        assert!(signature("algo.missing").is_none());
+    }
+
+    #[test]
+    fn test_argument_errors() {
+        let db = GraphDB::new();
+        assert!(matches!(
+            call(&db, "algo.bfs", &[]),
+            Err(GraphError::CypherExecutionError(_))
+        ));
+        let config = Value::Map(HashMap::from([("colour".to_string(), Value::Null)]));
+        assert!(call(&db, "algo.pagerank", &[config]).is_err());
+        assert!(call(&db, "algo.pagerank", &[]).unwrap().is_empty());
+    }
+}
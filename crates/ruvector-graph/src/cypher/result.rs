@@ -0,0 +1,465 @@
+//! Typed results of Cypher query execution
+//!
+//! A [`QueryResult`] holds the returned columns, one [`Row`] per result record
+//! and the [`QueryStats`] of any writes the query performed. Cells are
+//! [`Value`]s, which extend property values with nodes, relationships and
+//! paths.
+
+use crate::edge::Edge;
+use crate::node::Node;
+use crate::types::PropertyValue;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::Index;
+use std::sync::Arc;
+
+/// Value produced by evaluating a Cypher expression
+#[derive(Debug, Clone, Serialize)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(HashMap<String, Value>),
+    Node(Node),
+    Relationship(Edge),
+    Path(Path),
+}
+
+/// Path matched by a pattern such as `p = (a)-[*1..3]->(b)`
+#[derive(Debug, Clone, Serialize)]
+pub struct Path {
+    /// Nodes along the path, starting with the first node of the pattern
+    pub nodes: Vec<Node>,
+    /// Relationships between consecutive nodes
+    pub relationships: Vec<Edge>,
+}
+
+impl Path {
+    /// Number of relationships in the path
+    pub fn len(&self) -> usize {
+        self.relationships.len()
+    }
+
+    /// Whether the path consists of a single node
+    pub fn is_empty(&self) -> bool {
+        self.relationships.is_empty()
+    }
+}
+
+impl Value {
+    /// Whether the value is null
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Numeric value as a float; integers are converted
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Integer(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_node(&self) -> Option<&Node> {
+        match self {
+            Value::Node(node) => Some(node),
+            _ => None,
+        }
+    }
+
+    pub fn as_relationship(&self) -> Option<&Edge> {
+        match self {
+            Value::Relationship(edge) => Some(edge),
+            _ => None,
+        }
+    }
+
+    pub fn as_path(&self) -> Option<&Path> {
+        match self {
+            Value::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Convert to a property value that can be stored on a node or edge
+    ///
+    /// Returns `None` for nodes, relationships and paths, and for lists or
+    /// maps that contain them.
+    pub fn to_property_value(&self) -> Option<PropertyValue> {
+        Some(match self {
+            Value::Null => PropertyValue::Null,
+            Value::Boolean(b) => PropertyValue::Boolean(*b),
+            Value::Integer(i) => PropertyValue::Integer(*i),
+            Value::Float(f) => PropertyValue::Float(*f),
+            Value::String(s) => PropertyValue::String(s.clone()),
+            Value::List(items) => PropertyValue::Array(
+                items
+                    .iter()
+                    .map(Value::to_property_value)
+                    .collect::<Option<_>>()?,
+            ),
+            Value::Map(map) => PropertyValue::Map(
+                map.iter()
+                    .map(|(k, v)| Some((k.clone(), v.to_property_value()?)))
+                    .collect::<Option<_>>()?,
+            ),
+            Value::Node(_) | Value::Relationship(_) | Value::Path(_) => return None,
+        })
+    }
+
+    /// Convert to plain JSON
+    ///
+    /// Nodes become `{id, labels, properties}`, relationships
+    /// `{id, type, start, end, properties}` and paths `{nodes, relationships}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{json, Value as Json};
+
+        match self {
+            Value::Null => Json::Null,
+            Value::Boolean(b) => Json::Bool(*b),
+            Value::Integer(i) => json!(i),
+            Value::Float(f) => serde_json::Number::from_f64(*f)
+                .map(Json::Number)
+                .unwrap_or(Json::Null),
+            Value::String(s) => Json::String(s.clone()),
+            Value::List(items) => Json::Array(items.iter().map(Value::to_json).collect()),
+            Value::Map(map) => {
+                Json::Object(map.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+            }
+            Value::Node(node) => json!({
+                "id": node.id,
+                "labels": node.labels.iter().map(|l| l.name.clone()).collect::<Vec<_>>(),
+                "properties": properties_to_json(&node.properties),
+            }),
+            Value::Relationship(edge) => json!({
+                "id": edge.id,
+                "type": edge.edge_type,
+                "start": edge.from,
+                "end": edge.to,
+                "properties": properties_to_json(&edge.properties),
+            }),
+            Value::Path(path) => json!({
+                "nodes": path.nodes.iter().map(|n| Value::Node(n.clone()).to_json()).collect::<Vec<_>>(),
+                "relationships": path
+                    .relationships
+                    .iter()
+                    .map(|r| Value::Relationship(r.clone()).to_json())
+                    .collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+fn properties_to_json(properties: &HashMap<String, PropertyValue>) -> serde_json::Value {
+    serde_json::Value::Object(
+        properties
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::from(v.clone()).to_json()))
+            .collect(),
+    )
+}
+
+impl PartialEq for Value {
+    /// Structural equality; nodes and relationships compare by id
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Node(a), Value::Node(b)) => a.id == b.id,
+            (Value::Relationship(a), Value::Relationship(b)) => a.id == b.id,
+            (Value::Path(a), Value::Path(b)) => {
+                a.nodes
+                    .iter()
+                    .map(|n| &n.id)
+                    .eq(b.nodes.iter().map(|n| &n.id))
+                    && a.relationships
+                        .iter()
+                        .map(|r| &r.id)
+                        .eq(b.relationships.iter().map(|r| &r.id))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<PropertyValue> for Value {
+    fn from(value: PropertyValue) -> Self {
+        match value {
+            PropertyValue::Null => Value::Null,
+            PropertyValue::Boolean(b) => Value::Boolean(b),
+            PropertyValue::Integer(i) => Value::Integer(i),
+            PropertyValue::Float(f) => Value::Float(f),
+            PropertyValue::String(s) => Value::String(s),
+            PropertyValue::Array(items) | PropertyValue::List(items) => {
+                Value::List(items.into_iter().map(Value::from).collect())
+            }
+            PropertyValue::Map(map) => {
+                Value::Map(map.into_iter().map(|(k, v)| (k, Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Value::Integer(i)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self {
+        Value::Float(f)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<Node> for Value {
+    fn from(node: Node) -> Self {
+        Value::Node(node)
+    }
+}
+
+impl From<Edge> for Value {
+    fn from(edge: Edge) -> Self {
+        Value::Relationship(edge)
+    }
+}
+
+/// One record of a query result
+#[derive(Debug, Clone)]
+pub struct Row {
+    columns: Arc<Vec<String>>,
+    values: Vec<Value>,
+}
+
+impl Row {
+    pub fn new(columns: Arc<Vec<String>>, values: Vec<Value>) -> Self {
+        debug_assert_eq!(columns.len(), values.len());
+        Self { columns, values }
+    }
+
+    /// Value of the named column
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.columns
+            .iter()
+            .position(|c| c == column)
+            .map(|i| &self.values[i])
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn into_values(self) -> Vec<Value> {
+        self.values
+    }
+
+    /// Iterate over `(column, value)` pairs in column order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.columns
+            .iter()
+            .map(String::as_str)
+            .zip(self.values.iter())
+    }
+}
+
+impl Index<&str> for Row {
+    type Output = Value;
+
+    fn index(&self, column: &str) -> &Value {
+        self.get(column)
+            .unwrap_or_else(|| panic!("no column named '{}'", column))
+    }
+}
+
+impl Index<usize> for Row {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        &self.values[index]
+    }
+}
+
+impl Serialize for Row {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.values.len()))?;
+        for (column, value) in self.iter() {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Counters for the writes performed by a query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct QueryStats {
+    pub nodes_created: usize,
+    pub nodes_deleted: usize,
+    pub relationships_created: usize,
+    pub relationships_deleted: usize,
+    pub properties_set: usize,
+    pub labels_added: usize,
+    pub labels_removed: usize,
+    pub indexes_added: usize,
+    pub indexes_removed: usize,
+    pub constraints_added: usize,
+    pub constraints_removed: usize,
+}
+
+impl QueryStats {
+    /// Whether the query changed the graph
+    pub fn contains_updates(&self) -> bool {
+        *self != QueryStats::default()
+    }
+}
+
+/// Result of executing a Cypher query
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct QueryResult {
+    /// Column names in RETURN order
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+    pub stats: QueryStats,
+}
+
+impl QueryResult {
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn has_column(&self, column: &str) -> bool {
+        self.columns.iter().any(|c| c == column)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Row> {
+        self.rows.iter()
+    }
+
+    /// Values of one column across all rows
+    pub fn column(&self, column: &str) -> Vec<&Value> {
+        self.rows.iter().filter_map(|row| row.get(column)).collect()
+    }
+}
+
+impl Index<usize> for QueryResult {
+    type Output = Row;
+
+    fn index(&self, index: usize) -> &Row {
+        &self.rows[index]
+    }
+}
+
+impl IntoIterator for QueryResult {
+    type Item = Row;
+    type IntoIter = std::vec::IntoIter<Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a QueryResult {
+    type Item = &'a Row;
+    type IntoIter = std::slice::Iter<'a, Row>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodeBuilder;
+
+    #[test]
+    fn test_row_access() {
+        let columns = Arc::new(vec!["name".to_string(), "age".to_string()]);
+        let row = Row::new(columns, vec![Value::from("Alice"), Value::Integer(30)]);
+
+        assert_eq!(row["name"], Value::from("Alice"));
+        assert_eq!(row[1], Value::Integer(30));
+        assert!(row.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        let value = Value::from(PropertyValue::Array(vec![PropertyValue::Integer(1)]));
+        assert_eq!(value, Value::List(vec![Value::Integer(1)]));
+        assert_eq!(
+            value.to_property_value(),
+            Some(PropertyValue::Array(vec![PropertyValue::Integer(1)]))
+        );
+
+        let node = NodeBuilder::new().id("n1").label("Person").build();
+        assert!(Value::Node(node.clone()).to_property_value().is_none());
+        assert_eq!(
+            Value::Node(node).to_json(),
+            serde_json::json!({"id": "n1", "labels": ["Person"], "properties": {}})
+        );
+    }
+}
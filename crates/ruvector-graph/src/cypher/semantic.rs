@@ -177,9 +177,34 @@ impl SemanticAnalyzer {
             Statement::Remove(clause) => self.analyze_remove(clause),
             Statement::Return(clause) => self.analyze_return(clause),
             Statement::With(clause) => self.analyze_with(clause),
+            Statement::Call(clause) => self.analyze_call(clause),
+            Statement::Schema(_) => Ok(()),
         }
     }
 
+    fn analyze_call(&mut self, clause: &CallClause) -> SemanticResult<()> {
+        for argument in &clause.arguments {
+            self.analyze_expression(argument)?;
+        }
+
+        for item in clause.yield_items.iter().flatten() {
+            let name = item.alias.clone().unwrap_or_else(|| item.column.clone());
+            self.define_variable(name, ValueType::Any)?;
+        }
+
+        if let Some(where_clause) = &clause.where_clause {
+            let expr_type = self.analyze_expression(&where_clause.condition)?;
+            if !expr_type.is_compatible_with(&ValueType::Boolean) {
+                return Err(SemanticError::TypeMismatch {
+                    expected: "Boolean".to_string(),
+                    found: format!("{:?}", expr_type),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     fn analyze_remove(&mut self, clause: &RemoveClause) -> SemanticResult<()> {
         for item in &clause.items {
             match item {
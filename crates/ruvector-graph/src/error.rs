@@ -19,6 +19,9 @@ pub enum GraphError {
     #[error("Transaction error: {0}")]
     TransactionError(String),
 
+    #[error("Transaction conflict: {0}")]
+    TransactionConflict(String),
+
     #[error("Constraint violation: {0}")]
     ConstraintViolation(String),
 
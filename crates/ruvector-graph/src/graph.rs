@@ -1,14 +1,23 @@
 //! Graph database implementation with concurrent access and indexing
 
+use crate::cypher::{
+    parse_cypher, QueryEngine, QueryOptimizer, QueryPlan, QueryPlanner, QueryResult,
+};
 use crate::edge::Edge;
-use crate::error::Result;
+use crate::error::{GraphError, Result};
+use crate::hybrid::{EmbeddingConfig, NodeVectorIndex};
 use crate::hyperedge::{Hyperedge, HyperedgeId};
 use crate::index::{AdjacencyIndex, EdgeTypeIndex, HyperedgeNodeIndex, LabelIndex, PropertyIndex};
 use crate::node::Node;
+use crate::schema::{ConstraintDefinition, IndexDefinition, Schema};
 #[cfg(feature = "storage")]
 use crate::storage::GraphStorage;
-use crate::types::{EdgeId, NodeId, PropertyValue};
+use crate::transaction::{
+    CommittedState, IsolationLevel, Transaction, TransactionManager, TxnId, WriteSet,
+};
+use crate::types::{EdgeId, NodeId, Properties, PropertyValue};
 use dashmap::DashMap;
+use parking_lot::RwLock;
 #[cfg(feature = "storage")]
 use std::path::Path;
 use std::sync::Arc;
@@ -31,6 +40,15 @@ pub struct GraphDB {
     adjacency_index: AdjacencyIndex,
     /// Hyperedge node index
     hyperedge_node_index: HyperedgeNodeIndex,
+    /// Named vector indexes over node embeddings
+    vector_indexes: DashMap<String, Arc<NodeVectorIndex>>,
+    /// Declared range and full-text indexes and constraints
+    schema: Schema,
+    /// MVCC versions kept for active transactions
+    transactions: TransactionManager,
+    /// Held for writing while a commit updates memory and the indexes, and
+    /// for reading by every read, so readers never see part of a commit
+    publish: RwLock<()>,
     /// Optional persistent storage
     #[cfg(feature = "storage")]
     storage: Option<GraphStorage>,
@@ -48,6 +66,10 @@ impl GraphDB {
             edge_type_index: EdgeTypeIndex::new(),
             adjacency_index: AdjacencyIndex::new(),
             hyperedge_node_index: HyperedgeNodeIndex::new(),
+            vector_indexes: DashMap::new(),
+            schema: Schema::new(),
+            transactions: TransactionManager::history_only(),
+            publish: RwLock::new(()),
             #[cfg(feature = "storage")]
             storage: None,
         }
@@ -97,6 +119,15 @@ impl GraphDB {
                     self.hyperedge_node_index.add_hyperedge(&hyperedge);
                 }
             }
+
+            // Rebuild declared indexes; constraints bring their own
+            let nodes = self.get_all_nodes();
+            for constraint in storage.all_constraints()? {
+                self.schema.create_constraint(constraint, &nodes)?;
+            }
+            for index in storage.all_indexes()? {
+                self.schema.create_index(index, &nodes)?;
+            }
         }
         Ok(())
     }
@@ -106,49 +137,51 @@ impl GraphDB {
     /// Create a node
     pub fn create_node(&self, node: Node) -> Result<NodeId> {
         let id = node.id.clone();
-
-        // Update indexes
-        self.label_index.add_node(&node);
-        self.property_index.add_node(&node);
-
-        // Insert into memory
-        self.nodes.insert(id.clone(), node.clone());
-
-        // Persist to storage if available
-        #[cfg(feature = "storage")]
-        if let Some(storage) = &self.storage {
-            storage.insert_node(&node)?;
-        }
-
+        let mut writes = WriteSet::new();
+        writes.put_node(node);
+        self.auto_commit(&writes)?;
         Ok(id)
     }
 
     /// Get a node by ID
     pub fn get_node(&self, id: impl AsRef<str>) -> Option<Node> {
+        let _published = self.publish.read_recursive();
         self.nodes.get(id.as_ref()).map(|entry| entry.clone())
     }
 
     /// Delete a node
     pub fn delete_node(&self, id: impl AsRef<str>) -> Result<bool> {
-        if let Some((_, node)) = self.nodes.remove(id.as_ref()) {
-            // Update indexes
-            self.label_index.remove_node(&node);
-            self.property_index.remove_node(&node);
-
-            // Delete from storage if available
-            #[cfg(feature = "storage")]
-            if let Some(storage) = &self.storage {
-                storage.delete_node(id.as_ref())?;
-            }
+        if !self.nodes.contains_key(id.as_ref()) {
+            return Ok(false);
+        }
+        let mut writes = WriteSet::new();
+        writes.delete_node(id.as_ref().to_string());
+        self.auto_commit(&writes)?;
+        Ok(true)
+    }
 
-            Ok(true)
-        } else {
-            Ok(false)
+    /// Replace a stored node with an updated version, keeping the indexes in sync
+    pub fn update_node(&self, node: Node) -> Result<()> {
+        if !self.nodes.contains_key(&node.id) {
+            return Err(GraphError::NodeNotFound(node.id));
         }
+        let mut writes = WriteSet::new();
+        writes.put_node(node);
+        self.auto_commit(&writes)
+    }
+
+    /// Get all nodes
+    pub fn get_all_nodes(&self) -> Vec<Node> {
+        let _published = self.publish.read_recursive();
+        self.nodes
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect()
     }
 
     /// Get nodes by label
     pub fn get_nodes_by_label(&self, label: &str) -> Vec<Node> {
+        let _published = self.publish.read_recursive();
         self.label_index
             .get_nodes_by_label(label)
             .into_iter()
@@ -158,6 +191,7 @@ impl GraphDB {
 
     /// Get nodes by property
     pub fn get_nodes_by_property(&self, key: &str, value: &PropertyValue) -> Vec<Node> {
+        let _published = self.publish.read_recursive();
         self.property_index
             .get_nodes_by_property(key, value)
             .into_iter()
@@ -170,56 +204,51 @@ impl GraphDB {
     /// Create an edge
     pub fn create_edge(&self, edge: Edge) -> Result<EdgeId> {
         let id = edge.id.clone();
-
-        // Verify nodes exist
-        if !self.nodes.contains_key(&edge.from) || !self.nodes.contains_key(&edge.to) {
-            return Err(crate::error::GraphError::NodeNotFound(
-                "Source or target node not found".to_string(),
-            ));
-        }
-
-        // Update indexes
-        self.edge_type_index.add_edge(&edge);
-        self.adjacency_index.add_edge(&edge);
-
-        // Insert into memory
-        self.edges.insert(id.clone(), edge.clone());
-
-        // Persist to storage if available
-        #[cfg(feature = "storage")]
-        if let Some(storage) = &self.storage {
-            storage.insert_edge(&edge)?;
-        }
-
+        let mut writes = WriteSet::new();
+        writes.put_edge(edge);
+        self.auto_commit(&writes)?;
         Ok(id)
     }
 
     /// Get an edge by ID
     pub fn get_edge(&self, id: impl AsRef<str>) -> Option<Edge> {
+        let _published = self.publish.read_recursive();
         self.edges.get(id.as_ref()).map(|entry| entry.clone())
     }
 
     /// Delete an edge
     pub fn delete_edge(&self, id: impl AsRef<str>) -> Result<bool> {
-        if let Some((_, edge)) = self.edges.remove(id.as_ref()) {
-            // Update indexes
-            self.edge_type_index.remove_edge(&edge);
-            self.adjacency_index.remove_edge(&edge);
-
-            // Delete from storage if available
-            #[cfg(feature = "storage")]
-            if let Some(storage) = &self.storage {
-                storage.delete_edge(id.as_ref())?;
-            }
+        if !self.edges.contains_key(id.as_ref()) {
+            return Ok(false);
+        }
+        let mut writes = WriteSet::new();
+        writes.delete_edge(id.as_ref().to_string());
+        self.auto_commit(&writes)?;
+        Ok(true)
+    }
 
-            Ok(true)
-        } else {
-            Ok(false)
+    /// Replace a stored edge with an updated version, keeping the indexes in sync
+    pub fn update_edge(&self, edge: Edge) -> Result<()> {
+        if !self.edges.contains_key(&edge.id) {
+            return Err(GraphError::EdgeNotFound(edge.id));
         }
+        let mut writes = WriteSet::new();
+        writes.put_edge(edge);
+        self.auto_commit(&writes)
+    }
+
+    /// Get all edges
+    pub fn get_all_edges(&self) -> Vec<Edge> {
+        let _published = self.publish.read_recursive();
+        self.edges
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect()
     }
 
     /// Get edges by type
     pub fn get_edges_by_type(&self, edge_type: &str) -> Vec<Edge> {
+        let _published = self.publish.read_recursive();
         self.edge_type_index
             .get_edges_by_type(edge_type)
             .into_iter()
@@ -229,6 +258,7 @@ impl GraphDB {
 
     /// Get outgoing edges from a node
     pub fn get_outgoing_edges(&self, node_id: &NodeId) -> Vec<Edge> {
+        let _published = self.publish.read_recursive();
         self.adjacency_index
             .get_outgoing_edges(node_id)
             .into_iter()
@@ -238,6 +268,7 @@ impl GraphDB {
 
     /// Get incoming edges to a node
     pub fn get_incoming_edges(&self, node_id: &NodeId) -> Vec<Edge> {
+        let _published = self.publish.read_recursive();
         self.adjacency_index
             .get_incoming_edges(node_id)
             .into_iter()
@@ -250,60 +281,358 @@ impl GraphDB {
     /// Create a hyperedge
     pub fn create_hyperedge(&self, hyperedge: Hyperedge) -> Result<HyperedgeId> {
         let id = hyperedge.id.clone();
+        let mut writes = WriteSet::new();
+        writes.put_hyperedge(hyperedge);
+        self.auto_commit(&writes)?;
+        Ok(id)
+    }
+
+    /// Get a hyperedge by ID
+    pub fn get_hyperedge(&self, id: &HyperedgeId) -> Option<Hyperedge> {
+        let _published = self.publish.read_recursive();
+        self.hyperedges.get(id).map(|entry| entry.clone())
+    }
 
-        // Verify all nodes exist
-        for node_id in &hyperedge.nodes {
-            if !self.nodes.contains_key(node_id) {
-                return Err(crate::error::GraphError::NodeNotFound(format!(
+    /// Get hyperedges containing a node
+    pub fn get_hyperedges_by_node(&self, node_id: &NodeId) -> Vec<Hyperedge> {
+        let _published = self.publish.read_recursive();
+        self.hyperedge_node_index
+            .get_hyperedges_by_node(node_id)
+            .into_iter()
+            .filter_map(|id| self.get_hyperedge(&id))
+            .collect()
+    }
+
+    // Transactions
+
+    /// Begin a transaction
+    ///
+    /// Reads go through the MVCC versions kept for the transaction's snapshot
+    /// and writes are buffered until [`GraphTransaction::commit`], which
+    /// applies them to memory and storage atomically.
+    pub fn begin(&self, isolation_level: IsolationLevel) -> GraphTransaction<'_> {
+        GraphTransaction {
+            db: self,
+            txn: self.transactions.begin(isolation_level),
+        }
+    }
+
+    /// Remove the record versions no active transaction can read
+    ///
+    /// Returns the number of versions removed. Collection also runs
+    /// automatically as transactions finish.
+    pub fn collect_garbage(&self) -> usize {
+        self.transactions.collect_garbage()
+    }
+
+    /// Commit a single change outside of an explicit transaction
+    fn auto_commit(&self, writes: &WriteSet) -> Result<()> {
+        self.transactions
+            .commit_writes(None, writes, Some(self), |w| self.apply_writes(w))
+    }
+
+    /// Apply a committed write set to storage, memory and the indexes
+    fn apply_writes(&self, writes: &WriteSet) -> Result<()> {
+        if writes.is_empty() {
+            return Ok(());
+        }
+
+        // Validate endpoints against the state after the commit
+        let node_exists = |id: &NodeId| {
+            writes.nodes.contains_key(id)
+                || (!writes.deleted_nodes.contains(id) && self.nodes.contains_key(id))
+        };
+        for edge in writes.edges.values() {
+            if !node_exists(&edge.from) || !node_exists(&edge.to) {
+                return Err(GraphError::NodeNotFound(
+                    "Source or target node not found".to_string(),
+                ));
+            }
+        }
+        for hyperedge in writes.hyperedges.values() {
+            if let Some(node_id) = hyperedge.nodes.iter().find(|id| !node_exists(id)) {
+                return Err(GraphError::NodeNotFound(format!(
                     "Node {} not found",
                     node_id
                 )));
             }
         }
 
-        // Update index
-        self.hyperedge_node_index.add_hyperedge(&hyperedge);
-
-        // Insert into memory
-        self.hyperedges.insert(id.clone(), hyperedge.clone());
+        self.schema.check(writes)?;
+        for index in self.vector_indexes.iter() {
+            for node in writes.nodes.values() {
+                index.check(node)?;
+            }
+        }
 
-        // Persist to storage if available
+        // Persist in a single storage transaction before touching memory
         #[cfg(feature = "storage")]
         if let Some(storage) = &self.storage {
-            storage.insert_hyperedge(&hyperedge)?;
+            storage.apply_write_set(writes)?;
         }
 
-        Ok(id)
+        let _published = self.publish.write();
+        for id in &writes.deleted_hyperedges {
+            if let Some((_, hyperedge)) = self.hyperedges.remove(id) {
+                self.hyperedge_node_index.remove_hyperedge(&hyperedge);
+            }
+        }
+        for id in &writes.deleted_edges {
+            if let Some((_, edge)) = self.edges.remove(id) {
+                self.edge_type_index.remove_edge(&edge);
+                self.adjacency_index.remove_edge(&edge);
+            }
+        }
+        for id in &writes.deleted_nodes {
+            if let Some((_, node)) = self.nodes.remove(id) {
+                self.label_index.remove_node(&node);
+                self.property_index.remove_node(&node);
+                self.schema.update(Some(&node), None);
+                self.update_vector_indexes(Some(&node), None);
+            }
+        }
+
+        for node in writes.nodes.values() {
+            let old = self.nodes.insert(node.id.clone(), node.clone());
+            if let Some(old) = &old {
+                self.label_index.remove_node(old);
+                self.property_index.remove_node(old);
+            }
+            self.label_index.add_node(node);
+            self.property_index.add_node(node);
+            self.schema.update(old.as_ref(), Some(node));
+            self.update_vector_indexes(old.as_ref(), Some(node));
+        }
+        for edge in writes.edges.values() {
+            if let Some(old) = self.edges.insert(edge.id.clone(), edge.clone()) {
+                self.edge_type_index.remove_edge(&old);
+                self.adjacency_index.remove_edge(&old);
+            }
+            self.edge_type_index.add_edge(edge);
+            self.adjacency_index.add_edge(edge);
+        }
+        for hyperedge in writes.hyperedges.values() {
+            if let Some(old) = self
+                .hyperedges
+                .insert(hyperedge.id.clone(), hyperedge.clone())
+            {
+                self.hyperedge_node_index.remove_hyperedge(&old);
+            }
+            self.hyperedge_node_index.add_hyperedge(hyperedge);
+        }
+
+        Ok(())
     }
 
-    /// Get a hyperedge by ID
-    pub fn get_hyperedge(&self, id: &HyperedgeId) -> Option<Hyperedge> {
-        self.hyperedges.get(id).map(|entry| entry.clone())
+    /// Keep the vector indexes in sync with a node write
+    fn update_vector_indexes(&self, old: Option<&Node>, new: Option<&Node>) {
+        for index in self.vector_indexes.iter() {
+            index.update(old, new);
+        }
     }
 
-    /// Get hyperedges containing a node
-    pub fn get_hyperedges_by_node(&self, node_id: &NodeId) -> Vec<Hyperedge> {
-        self.hyperedge_node_index
-            .get_hyperedges_by_node(node_id)
-            .into_iter()
-            .filter_map(|id| self.get_hyperedge(&id))
-            .collect()
+    // Vector indexes
+
+    /// Create a vector index over the embeddings of nodes with `label`
+    ///
+    /// Existing nodes are indexed immediately and later node writes keep the
+    /// index up to date. Cypher queries use it through
+    /// `CALL db.index.vector.queryNodes(name, k, vector)`. Fails with
+    /// `InvalidEmbedding` if a node with the label has an embedding
+    /// [`NodeVectorIndex::check`] rejects, as later writes of such nodes do.
+    pub fn create_vector_index(
+        &self,
+        name: impl Into<String>,
+        label: impl Into<String>,
+        config: EmbeddingConfig,
+    ) -> Result<Arc<NodeVectorIndex>> {
+        let name = name.into();
+        if self.schema.index(&name).is_some() || self.schema.constraint(&name).is_some() {
+            return Err(GraphError::IndexError(format!(
+                "An index or constraint named {} already exists",
+                name
+            )));
+        }
+        let index = Arc::new(NodeVectorIndex::new(name.clone(), label, config)?);
+        // Commits are held off so no node is missed or written unchecked
+        self.transactions.exclusive(|| {
+            if self.vector_indexes.contains_key(&name) {
+                return Err(GraphError::IndexError(format!(
+                    "Vector index {} already exists",
+                    name
+                )));
+            }
+            let nodes = self.get_nodes_by_label(index.label());
+            for node in &nodes {
+                index.check(node)?;
+            }
+            self.vector_indexes.insert(name, index.clone());
+            for node in &nodes {
+                index.update(None, Some(node));
+            }
+            Ok(())
+        })?;
+        Ok(index)
+    }
+
+    /// Drop a vector index; returns whether it existed
+    pub fn drop_vector_index(&self, name: &str) -> bool {
+        self.vector_indexes.remove(name).is_some()
+    }
+
+    /// Get a vector index by name
+    pub fn vector_index(&self, name: &str) -> Option<Arc<NodeVectorIndex>> {
+        self.vector_indexes.get(name).map(|entry| entry.clone())
+    }
+
+    /// All vector indexes, sorted by name
+    pub fn vector_indexes(&self) -> Vec<Arc<NodeVectorIndex>> {
+        let mut indexes: Vec<_> = self
+            .vector_indexes
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        indexes.sort_by(|a, b| a.name().cmp(b.name()));
+        indexes
+    }
+
+    // Schema
+
+    /// Declared range and full-text indexes and constraints
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Create a range or full-text index over the nodes with a label
+    ///
+    /// The planner uses range indexes for equality, range and `STARTS WITH`
+    /// predicates and full-text indexes for `CONTAINS`.
+    pub fn create_index(&self, definition: IndexDefinition) -> Result<()> {
+        if self.vector_indexes.contains_key(&definition.name) {
+            return Err(GraphError::IndexError(format!(
+                "An index named {} already exists",
+                definition.name
+            )));
+        }
+        // Schema changes wait for in-flight commits and hold off new ones
+        self.transactions.exclusive(|| {
+            let nodes = self.get_nodes_by_label(&definition.label);
+            self.schema.create_index(definition.clone(), &nodes)?;
+            #[cfg(feature = "storage")]
+            if let Some(storage) = &self.storage {
+                if let Err(e) = storage.save_index(&definition) {
+                    self.schema.drop_index(&definition.name)?;
+                    return Err(e.into());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Drop a range or full-text index; returns whether it existed
+    pub fn drop_index(&self, name: &str) -> Result<bool> {
+        self.transactions.exclusive(|| {
+            if !self.schema.drop_index(name)? {
+                return Ok(false);
+            }
+            #[cfg(feature = "storage")]
+            if let Some(storage) = &self.storage {
+                storage.delete_index(name)?;
+            }
+            Ok(true)
+        })
+    }
+
+    /// Create a constraint, failing if existing nodes violate it
+    ///
+    /// Later commits that would violate it fail with
+    /// [`GraphError::ConstraintViolation`].
+    pub fn create_constraint(&self, definition: ConstraintDefinition) -> Result<()> {
+        if self.vector_indexes.contains_key(&definition.name) {
+            return Err(GraphError::IndexError(format!(
+                "An index named {} already exists",
+                definition.name
+            )));
+        }
+        self.transactions.exclusive(|| {
+            let nodes = self.get_nodes_by_label(&definition.label);
+            self.schema.create_constraint(definition.clone(), &nodes)?;
+            #[cfg(feature = "storage")]
+            if let Some(storage) = &self.storage {
+                if let Err(e) = storage.save_constraint(&definition) {
+                    self.schema.drop_constraint(&definition.name);
+                    return Err(e.into());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Drop a constraint and its backing index; returns whether it existed
+    pub fn drop_constraint(&self, name: &str) -> Result<bool> {
+        self.transactions.exclusive(|| {
+            if !self.schema.drop_constraint(name) {
+                return Ok(false);
+            }
+            #[cfg(feature = "storage")]
+            if let Some(storage) = &self.storage {
+                storage.delete_constraint(name)?;
+            }
+            Ok(true)
+        })
+    }
+
+    // Queries
+
+    /// Execute a Cypher query with the given parameters
+    ///
+    /// The query is parsed, rewritten by the AST optimizer, compiled into a
+    /// logical plan that uses the label, property and adjacency indexes, and
+    /// run against this graph. A read-only query sees a single committed
+    /// state; in a query that writes, each read sees only whole commits.
+    pub fn execute(&self, cypher: &str, params: &Properties) -> Result<QueryResult> {
+        let plan = self.explain(cypher)?;
+        // Commits wait for the query, which must not commit itself
+        let _published = plan
+            .root
+            .is_read_only()
+            .then(|| self.publish.read_recursive());
+        QueryEngine::new(self, params).execute(&plan)
+    }
+
+    /// Compile a Cypher query into the optimized plan `execute` would run
+    pub fn explain(&self, cypher: &str) -> Result<QueryPlan> {
+        let query =
+            parse_cypher(cypher).map_err(|e| GraphError::CypherParseError(e.to_string()))?;
+        let query = QueryOptimizer::new().optimize(query).optimized_query;
+        let mut planner = QueryPlanner::new(self);
+        let plan = planner.plan(&query)?;
+        Ok(planner.optimize(plan))
     }
 
     // Statistics
 
     /// Get the number of nodes
     pub fn node_count(&self) -> usize {
+        let _published = self.publish.read_recursive();
         self.nodes.len()
     }
 
+    /// Get the number of nodes with a label
+    pub fn count_nodes_by_label(&self, label: &str) -> usize {
+        let _published = self.publish.read_recursive();
+        self.label_index.count_by_label(label)
+    }
+
     /// Get the number of edges
     pub fn edge_count(&self) -> usize {
+        let _published = self.publish.read_recursive();
         self.edges.len()
     }
 
     /// Get the number of hyperedges
     pub fn hyperedge_count(&self) -> usize {
+        let _published = self.publish.read_recursive();
         self.hyperedges.len()
     }
 }
@@ -314,6 +643,154 @@ impl Default for GraphDB {
     }
 }
 
+impl CommittedState for GraphDB {
+    fn committed_node(&self, id: &NodeId) -> Option<Node> {
+        self.get_node(id)
+    }
+
+    fn committed_edge(&self, id: &EdgeId) -> Option<Edge> {
+        self.get_edge(id)
+    }
+
+    fn committed_hyperedge(&self, id: &HyperedgeId) -> Option<Hyperedge> {
+        self.get_hyperedge(id)
+    }
+}
+
+/// A transaction on a [`GraphDB`]
+///
+/// Reads see the transaction's own writes on top of the snapshot selected by
+/// its isolation level. Dropping the handle without committing rolls it back.
+pub struct GraphTransaction<'a> {
+    db: &'a GraphDB,
+    txn: Transaction,
+}
+
+impl GraphTransaction<'_> {
+    /// Get the transaction ID
+    pub fn id(&self) -> TxnId {
+        self.txn.id()
+    }
+
+    /// Get the isolation level
+    pub fn isolation_level(&self) -> IsolationLevel {
+        self.txn.isolation_level
+    }
+
+    /// Get a node by ID
+    pub fn get_node(&self, id: impl AsRef<str>) -> Option<Node> {
+        self.txn
+            .read_node_from(&id.as_ref().to_string(), Some(self.db))
+    }
+
+    /// Get an edge by ID
+    pub fn get_edge(&self, id: impl AsRef<str>) -> Option<Edge> {
+        self.txn
+            .read_edge_from(&id.as_ref().to_string(), Some(self.db))
+    }
+
+    /// Get a hyperedge by ID
+    pub fn get_hyperedge(&self, id: &HyperedgeId) -> Option<Hyperedge> {
+        self.txn.read_hyperedge_from(id, Some(self.db))
+    }
+
+    /// Create a node
+    pub fn create_node(&self, node: Node) -> NodeId {
+        let id = node.id.clone();
+        self.txn.write_node(node);
+        id
+    }
+
+    /// Replace a node with an updated version
+    pub fn update_node(&self, node: Node) -> Result<()> {
+        if self.get_node(&node.id).is_none() {
+            return Err(GraphError::NodeNotFound(node.id));
+        }
+        self.txn.write_node(node);
+        Ok(())
+    }
+
+    /// Delete a node
+    pub fn delete_node(&self, id: impl AsRef<str>) -> bool {
+        if self.get_node(id.as_ref()).is_none() {
+            return false;
+        }
+        self.txn.delete_node(id.as_ref().to_string());
+        true
+    }
+
+    /// Create an edge between nodes visible to this transaction
+    pub fn create_edge(&self, edge: Edge) -> Result<EdgeId> {
+        if self.get_node(&edge.from).is_none() || self.get_node(&edge.to).is_none() {
+            return Err(GraphError::NodeNotFound(
+                "Source or target node not found".to_string(),
+            ));
+        }
+        let id = edge.id.clone();
+        self.txn.write_edge(edge);
+        Ok(id)
+    }
+
+    /// Replace an edge with an updated version
+    pub fn update_edge(&self, edge: Edge) -> Result<()> {
+        if self.get_edge(&edge.id).is_none() {
+            return Err(GraphError::EdgeNotFound(edge.id));
+        }
+        self.txn.write_edge(edge);
+        Ok(())
+    }
+
+    /// Delete an edge
+    pub fn delete_edge(&self, id: impl AsRef<str>) -> bool {
+        if self.get_edge(id.as_ref()).is_none() {
+            return false;
+        }
+        self.txn.delete_edge(id.as_ref().to_string());
+        true
+    }
+
+    /// Create a hyperedge between nodes visible to this transaction
+    pub fn create_hyperedge(&self, hyperedge: Hyperedge) -> Result<HyperedgeId> {
+        if let Some(node_id) = hyperedge
+            .nodes
+            .iter()
+            .find(|id| self.get_node(id.as_str()).is_none())
+        {
+            return Err(GraphError::NodeNotFound(format!(
+                "Node {} not found",
+                node_id
+            )));
+        }
+        let id = hyperedge.id.clone();
+        self.txn.write_hyperedge(hyperedge);
+        Ok(id)
+    }
+
+    /// Delete a hyperedge
+    pub fn delete_hyperedge(&self, id: &HyperedgeId) -> bool {
+        if self.get_hyperedge(id).is_none() {
+            return false;
+        }
+        self.txn.delete_hyperedge(id.clone());
+        true
+    }
+
+    /// Commit the transaction
+    ///
+    /// All writes reach storage in a single write transaction. Serializable
+    /// transactions fail with [`GraphError::TransactionConflict`] if another
+    /// transaction committed a change to a record they read or wrote.
+    pub fn commit(self) -> Result<()> {
+        let GraphTransaction { db, txn } = self;
+        txn.commit_to(Some(db), |writes| db.apply_writes(writes))
+    }
+
+    /// Rollback the transaction, discarding its writes
+    pub fn rollback(self) -> Result<()> {
+        self.txn.rollback()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
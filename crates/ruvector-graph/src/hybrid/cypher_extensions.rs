@@ -1,11 +1,18 @@
 //! Cypher query extensions for vector similarity
 //!
 //! Extends Cypher syntax to support vector operations like SIMILAR TO.
+//! Queries run on the Cypher engine, which also offers vector search
+//! directly through `CALL db.index.vector.queryNodes(...)` and
+//! `vector.similarity(a, b)`.
 
+use super::vector_index::{EmbeddingConfig, NodeVectorIndex};
 use crate::error::{GraphError, Result};
-use crate::types::NodeId;
+use crate::graph::GraphDB;
+use crate::types::{NodeId, Properties, PropertyValue};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Extended Cypher parser with vector support
 pub struct VectorCypherParser {
@@ -41,7 +48,7 @@ impl VectorCypherParser {
         // This is a simplified parser for demonstration
         // Real implementation would use proper parser combinators or generated parser
 
-        if query.contains("SIMILAR TO") {
+        if query.to_ascii_uppercase().contains("SIMILAR TO") {
             self.parse_similarity_query(query)
         } else if query.contains("SEMANTIC PATH") {
             self.parse_semantic_path_query(query)
@@ -49,7 +56,7 @@ impl VectorCypherParser {
             Ok(VectorCypherQuery {
                 match_clause: query.to_string(),
                 similarity_predicate: None,
-                return_clause: "RETURN *".to_string(),
+                return_clause: String::new(),
                 limit: None,
                 order_by: None,
             })
@@ -59,26 +66,67 @@ impl VectorCypherParser {
     /// Parse similarity query
     fn parse_similarity_query(&self, query: &str) -> Result<VectorCypherQuery> {
         // Example: MATCH (n:Document) WHERE n.embedding SIMILAR TO $query_vector LIMIT 10 RETURN n
-
-        // Extract components (simplified parsing)
-        let match_clause = query
-            .split("WHERE")
-            .next()
-            .ok_or_else(|| GraphError::QueryError("Invalid MATCH clause".to_string()))?
+        let invalid = |message: &str| GraphError::QueryError(message.to_string());
+        // Keywords are matched case-insensitively; ASCII upper-casing keeps
+        // byte offsets valid for the original query
+        let upper = query.to_ascii_uppercase();
+
+        let where_start = upper
+            .find("WHERE")
+            .ok_or_else(|| invalid("SIMILAR TO must appear in a WHERE clause"))?;
+        let similar_start = upper
+            .find("SIMILAR TO")
+            .filter(|&i| i > where_start)
+            .ok_or_else(|| invalid("SIMILAR TO must appear in a WHERE clause"))?;
+        let match_clause = query[..where_start].trim().to_string();
+
+        let (variable, property) = query[where_start + "WHERE".len()..similar_start]
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| invalid("SIMILAR TO needs a property such as n.embedding"))?;
+        let (variable, property) = (variable.trim().to_string(), property.trim().to_string());
+
+        let rest = query[similar_start + "SIMILAR TO".len()..].trim_start();
+        let parameter_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let parameter = rest[..parameter_end]
+            .strip_prefix('$')
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid("SIMILAR TO needs a parameter such as $query_vector"))?
             .to_string();
 
-        let similarity_predicate = Some(SimilarityPredicate {
-            property: "embedding".to_string(),
-            query_vector: Vec::new(), // Would be populated from parameters
-            top_k: 10,
-            min_score: 0.0,
-        });
+        // Optional LIMIT before RETURN sets the number of neighbors
+        let rest = rest[parameter_end..].trim();
+        let return_start = rest
+            .to_ascii_uppercase()
+            .find("RETURN")
+            .unwrap_or(rest.len());
+        let limit = match rest[..return_start].split_whitespace().collect::<Vec<_>>()[..] {
+            [] => None,
+            [keyword, n] if keyword.eq_ignore_ascii_case("LIMIT") => Some(
+                n.parse::<usize>()
+                    .map_err(|_| invalid("LIMIT must be a non-negative integer"))?,
+            ),
+            _ => return Err(invalid("Expected LIMIT or RETURN after SIMILAR TO")),
+        };
+        let return_clause = rest[return_start..].trim().to_string();
+        if return_clause.is_empty() {
+            return Err(invalid("Similarity queries need a RETURN clause"));
+        }
 
+        let label = label_of(&match_clause, &variable);
         Ok(VectorCypherQuery {
             match_clause,
-            similarity_predicate,
-            return_clause: "RETURN n".to_string(),
-            limit: Some(10),
+            similarity_predicate: Some(SimilarityPredicate {
+                variable,
+                label,
+                property,
+                parameter: Some(parameter),
+                query_vector: Vec::new(),
+                top_k: limit.unwrap_or(10),
+                min_score: 0.0,
+            }),
+            return_clause,
+            limit,
             order_by: Some("semanticScore DESC".to_string()),
         })
     }
@@ -112,9 +160,15 @@ pub struct VectorCypherQuery {
 /// Similarity predicate in WHERE clause
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimilarityPredicate {
+    /// Variable whose embedding is compared
+    pub variable: String,
+    /// Label of the variable in the MATCH pattern, if any
+    pub label: Option<String>,
     /// Property containing embedding
     pub property: String,
-    /// Query vector for comparison
+    /// Query parameter holding the query vector
+    pub parameter: Option<String>,
+    /// Query vector for comparison, used when no parameter is given
     pub query_vector: Vec<f32>,
     /// Number of results
     pub top_k: usize,
@@ -122,65 +176,179 @@ pub struct SimilarityPredicate {
     pub min_score: f32,
 }
 
+/// First label given to `variable` in a MATCH pattern, e.g. `Document` in
+/// `(n:Document)`
+fn label_of(pattern: &str, variable: &str) -> Option<String> {
+    let mut rest = pattern;
+    while let Some(open) = rest.find('(') {
+        rest = &rest[open + 1..];
+        let Some(after) = rest.trim_start().strip_prefix(variable) else {
+            continue;
+        };
+        let Some(label) = after.trim_start().strip_prefix(':') else {
+            continue;
+        };
+        let label: String = label
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if !label.is_empty() {
+            return Some(label);
+        }
+    }
+    None
+}
+
 /// Executor for vector-aware Cypher queries
-pub struct VectorCypherExecutor {
-    // In real implementation, this would have access to:
-    // - Graph storage
-    // - Vector index
-    // - Query planner
+///
+/// Similarity queries are rewritten into a `db.index.vector.queryNodes` call
+/// on the vector index covering the predicate's label and property, followed
+/// by the query's MATCH and RETURN, and run by the regular Cypher engine.
+/// The similarity of each match is available as `semanticScore`.
+pub struct VectorCypherExecutor<'a> {
+    db: &'a GraphDB,
+    /// Property read by [`semantic_score`](Self::semantic_score)
+    embedding_property: String,
 }
 
-impl VectorCypherExecutor {
+impl<'a> VectorCypherExecutor<'a> {
     /// Create a new executor
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(db: &'a GraphDB) -> Self {
+        Self {
+            db,
+            embedding_property: EmbeddingConfig::default().embedding_property,
+        }
+    }
+
+    /// Read path embeddings from another property
+    pub fn with_embedding_property(mut self, property: impl Into<String>) -> Self {
+        self.embedding_property = property.into();
+        self
     }
 
     /// Execute a vector-aware Cypher query
-    pub fn execute(&self, _query: &VectorCypherQuery) -> Result<QueryResult> {
-        // This is a placeholder for actual execution
-        // Real implementation would:
-        // 1. Plan query execution (optimize with vector indices)
-        // 2. Execute vector similarity search
-        // 3. Apply graph pattern matching
-        // 4. Combine results
-        // 5. Apply ordering and limits
+    pub fn execute(&self, query: &VectorCypherQuery, params: &Properties) -> Result<QueryResult> {
+        let started = Instant::now();
+        let mut params = params.clone();
+        let mut index_hits = 0;
+        let mut vectors_compared = 0;
+
+        let cypher = match &query.similarity_predicate {
+            None => [query.match_clause.as_str(), query.return_clause.as_str()].join(" "),
+            Some(predicate) => {
+                let index = self.index_for(predicate)?;
+                let parameter = match &predicate.parameter {
+                    Some(name) => name.clone(),
+                    None => {
+                        let vector = predicate
+                            .query_vector
+                            .iter()
+                            .map(|&x| PropertyValue::Float(x as f64))
+                            .collect();
+                        params.insert("queryVector".to_string(), PropertyValue::List(vector));
+                        "queryVector".to_string()
+                    }
+                };
+                index_hits = predicate.top_k.min(index.len());
+                vectors_compared = index.len();
+                format!(
+                    "CALL db.index.vector.queryNodes('{}', {}, ${}) \
+                     YIELD node AS {}, score AS semanticScore WHERE semanticScore >= {:?} {} {}",
+                    index.name().replace('\'', "\\'"),
+                    predicate.top_k,
+                    parameter,
+                    predicate.variable,
+                    predicate.min_score as f64,
+                    query.match_clause,
+                    query.return_clause
+                )
+            }
+        };
+
+        let result = self.db.execute(&cypher, &params)?;
+        let rows: Vec<HashMap<String, serde_json::Value>> = result
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(column, value)| (column.to_string(), value.to_json()))
+                    .collect()
+            })
+            .collect();
 
         Ok(QueryResult {
-            rows: Vec::new(),
-            execution_time_ms: 0,
             stats: ExecutionStats {
-                nodes_scanned: 0,
-                vectors_compared: 0,
-                index_hits: 0,
+                nodes_scanned: rows.len(),
+                vectors_compared,
+                index_hits,
             },
+            rows,
+            execution_time_ms: started.elapsed().as_millis() as u64,
         })
     }
 
-    /// Execute similarity search
+    /// Execute similarity search, returning the closest nodes first
     pub fn execute_similarity_search(
         &self,
-        _predicate: &SimilarityPredicate,
+        predicate: &SimilarityPredicate,
     ) -> Result<Vec<NodeId>> {
-        // Placeholder for vector similarity search
-        Ok(Vec::new())
+        let index = self.index_for(predicate)?;
+        Ok(index
+            .query(&predicate.query_vector, predicate.top_k)?
+            .into_iter()
+            .filter(|(_, score)| *score >= predicate.min_score)
+            .map(|(id, _)| id)
+            .collect())
     }
 
     /// Compute semantic score for a path
-    pub fn semantic_score(&self, _path: &[NodeId]) -> f32 {
-        // Placeholder for path scoring
-        // Real implementation would:
-        // 1. Retrieve embeddings for all nodes in path
-        // 2. Compute pairwise similarities
-        // 3. Aggregate scores (e.g., average, min, product)
-
-        0.85 // Dummy score
+    ///
+    /// The average cosine similarity of consecutive node embeddings; nodes
+    /// without an embedding are skipped, and incomparable embeddings score 0.
+    pub fn semantic_score(&self, path: &[NodeId]) -> f32 {
+        let embeddings: Vec<Vec<f32>> = path
+            .iter()
+            .filter_map(|id| self.db.get_node(id))
+            .filter_map(|node| embedding(node.properties.get(&self.embedding_property)?))
+            .collect();
+        functions::semantic_score(&embeddings).unwrap_or(0.0)
+    }
+
+    /// Vector index over the predicate's property, preferring its label
+    fn index_for(&self, predicate: &SimilarityPredicate) -> Result<Arc<NodeVectorIndex>> {
+        self.db
+            .vector_indexes()
+            .into_iter()
+            .filter(|index| index.property() == predicate.property)
+            .find(|index| {
+                predicate
+                    .label
+                    .as_deref()
+                    .map_or(true, |label| index.label() == label)
+            })
+            .ok_or_else(|| {
+                GraphError::IndexError(format!(
+                    "No vector index on :{}({})",
+                    predicate.label.as_deref().unwrap_or("*"),
+                    predicate.property
+                ))
+            })
     }
 }
 
-impl Default for VectorCypherExecutor {
-    fn default() -> Self {
-        Self::new()
+/// Numeric list property as an embedding
+fn embedding(value: &PropertyValue) -> Option<Vec<f32>> {
+    match value {
+        PropertyValue::Array(items) | PropertyValue::List(items) => items
+            .iter()
+            .map(|item| match item {
+                PropertyValue::Float(f) => Some(*f as f32),
+                PropertyValue::Integer(i) => Some(*i as f32),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
     }
 }
 
@@ -291,6 +459,18 @@ mod tests {
         assert!(parsed.similarity_predicate.is_some());
         assert_eq!(parsed.limit, Some(10));
 
+        let predicate = parsed.similarity_predicate.unwrap();
+        assert_eq!(predicate.variable, "n");
+        assert_eq!(predicate.label.as_deref(), Some("Document"));
+        assert_eq!(predicate.property, "embedding");
+        assert_eq!(predicate.parameter.as_deref(), Some("query_vector"));
+        assert_eq!(parsed.match_clause, "MATCH (n:Document)");
+        assert_eq!(parsed.return_clause, "RETURN n");
+
+        assert!(parser
+            .parse("MATCH (n) WHERE n.embedding SIMILAR TO [1, 2] RETURN n")
+            .is_err());
+
         Ok(())
     }
 
@@ -317,8 +497,26 @@ mod tests {
 
     #[test]
     fn test_executor_creation() {
-        let executor = VectorCypherExecutor::new();
-        let score = executor.semantic_score(&vec!["n1".to_string()]);
+        use crate::node::NodeBuilder;
+
+        let db = GraphDB::new();
+        for (id, embedding) in [("n1", [1.0, 0.0]), ("n2", [1.0, 0.0]), ("n3", [0.0, 1.0])] {
+            let embedding = embedding.iter().map(|&x| PropertyValue::Float(x)).collect();
+            db.create_node(
+                NodeBuilder::new()
+                    .id(id)
+                    .property("embedding", PropertyValue::List(embedding))
+                    .build(),
+            )
+            .unwrap();
+        }
+
+        let executor = VectorCypherExecutor::new(&db);
+        let score = executor.semantic_score(&["n1".to_string()]);
         assert!(score > 0.0);
+        let path = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        assert!((executor.semantic_score(&path(&["n1", "n2"])) - 1.0).abs() < 1e-6);
+        assert!(executor.semantic_score(&path(&["n1", "n3"])).abs() < 1e-6);
+        assert_eq!(executor.semantic_score(&path(&["missing"])), 0.0);
     }
 }
@@ -16,7 +16,7 @@ pub use graph_neural::{
 };
 pub use rag_integration::{Context, Evidence, RagConfig, RagEngine, ReasoningPath};
 pub use semantic_search::{ClusterResult, SemanticPath, SemanticSearch, SemanticSearchConfig};
-pub use vector_index::{EmbeddingConfig, HybridIndex, VectorIndexType};
+pub use vector_index::{EmbeddingConfig, HybridIndex, NodeVectorIndex, VectorIndexType};
 
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
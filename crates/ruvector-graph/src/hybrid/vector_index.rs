@@ -3,6 +3,7 @@
 //! Integrates RuVector's index (HNSW or Flat) with graph nodes, edges, and hyperedges.
 
 use crate::error::{GraphError, Result};
+use crate::node::Node;
 use crate::types::{EdgeId, NodeId, Properties, PropertyValue};
 use dashmap::DashMap;
 use parking_lot::RwLock;
@@ -210,6 +211,22 @@ impl HybridIndex {
         Ok(())
     }
 
+    /// Remove a node embedding; returns whether the node was indexed
+    pub fn remove_node_embedding(&self, node_id: &NodeId) -> Result<bool> {
+        let Some((_, vector_id)) = self.node_id_map.remove(node_id) else {
+            return Ok(false);
+        };
+
+        let mut index_guard = self.node_index.write();
+        let index = index_guard
+            .as_mut()
+            .ok_or_else(|| GraphError::IndexError("Node index not initialized".to_string()))?;
+
+        index
+            .remove(&vector_id)
+            .map_err(|e| GraphError::IndexError(format!("Failed to remove node embedding: {}", e)))
+    }
+
     /// Search for similar nodes
     pub fn search_similar_nodes(&self, query: &[f32], k: usize) -> Result<Vec<(NodeId, f32)>> {
         let index_guard = self.node_index.read();
@@ -279,7 +296,7 @@ impl HybridIndex {
         };
 
         match prop_value {
-            PropertyValue::Array(arr) => {
+            PropertyValue::Array(arr) | PropertyValue::List(arr) => {
                 let embedding: Result<Vec<f32>> = arr
                     .iter()
                     .map(|v| match v {
@@ -298,6 +315,23 @@ impl HybridIndex {
         }
     }
 
+    /// Index configuration
+    pub fn config(&self) -> &EmbeddingConfig {
+        &self.config
+    }
+
+    /// Convert a search distance into a similarity where higher is closer
+    ///
+    /// Cosine gives the cosine similarity, dot product the dot product, and
+    /// Euclidean and Manhattan distances map into (0, 1] as `1 / (1 + d)`.
+    pub fn similarity(&self, distance: f32) -> f32 {
+        match self.config.metric {
+            DistanceMetric::Cosine => 1.0 - distance,
+            DistanceMetric::DotProduct => -distance,
+            DistanceMetric::Euclidean | DistanceMetric::Manhattan => 1.0 / (1.0 + distance),
+        }
+    }
+
     /// Get index statistics
     pub fn stats(&self) -> HybridIndexStats {
         let node_count = self.node_id_map.len();
@@ -322,6 +356,160 @@ pub struct HybridIndexStats {
     pub total_embeddings: usize,
 }
 
+/// Named vector index over the embeddings of all nodes with one label
+///
+/// [`GraphDB`](crate::GraphDB) keeps registered indexes in sync with node
+/// writes. Nodes without the embedding property are not indexed; writes that
+/// give a node with the label an embedding that is not a numeric vector of
+/// the configured dimension are rejected before they are stored.
+pub struct NodeVectorIndex {
+    name: String,
+    label: String,
+    index: HybridIndex,
+}
+
+impl NodeVectorIndex {
+    /// Create an empty index for nodes with `label`
+    pub fn new(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        config: EmbeddingConfig,
+    ) -> Result<Self> {
+        let index = HybridIndex::new(config)?;
+        index.initialize_index(VectorIndexType::Node)?;
+        Ok(Self {
+            name: name.into(),
+            label: label.into(),
+            index,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Property holding the embeddings
+    pub fn property(&self) -> &str {
+        &self.index.config.embedding_property
+    }
+
+    pub fn config(&self) -> &EmbeddingConfig {
+        self.index.config()
+    }
+
+    /// Number of indexed nodes
+    pub fn len(&self) -> usize {
+        self.index.node_id_map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Embedding of `node` if it belongs in this index
+    ///
+    /// Embeddings [`check`](Self::check) would reject are left out.
+    pub fn embedding_of(&self, node: &Node) -> Option<Vec<f32>> {
+        if !node.has_label(&self.label) {
+            return None;
+        }
+        self.index
+            .extract_embedding(&node.properties)
+            .ok()
+            .flatten()
+            .filter(|embedding| embedding.len() == self.index.config.dimensions)
+    }
+
+    /// Check that `node` can be written while this index exists
+    ///
+    /// A node with the label must have no embedding, or a numeric vector
+    /// of the configured dimension.
+    pub fn check(&self, node: &Node) -> Result<()> {
+        if !node.has_label(&self.label) {
+            return Ok(());
+        }
+        let invalid = |reason: String| {
+            GraphError::InvalidEmbedding(format!(
+                "Node {} cannot be indexed by {}: {}",
+                node.id, self.name, reason
+            ))
+        };
+        match self.index.extract_embedding(&node.properties) {
+            Ok(Some(embedding)) if embedding.len() != self.index.config.dimensions => {
+                Err(invalid(format!(
+                    "expected {} dimensions, got {}",
+                    self.index.config.dimensions,
+                    embedding.len()
+                )))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(invalid(e.to_string())),
+        }
+    }
+
+    /// Update the index after `old` was replaced by `new`
+    ///
+    /// Either side may be `None` for node creation and deletion. Runs after
+    /// the write is stored, so it cannot fail: nodes were validated with
+    /// [`check`](Self::check) beforehand, and the underlying index failing
+    /// anyway only leaves the node out of search results.
+    pub fn update(&self, old: Option<&Node>, new: Option<&Node>) {
+        let Some(id) = new.or(old).map(|node| &node.id) else {
+            return;
+        };
+        let result = match new.and_then(|node| self.embedding_of(node)) {
+            Some(embedding) => {
+                let unchanged = old
+                    .and_then(|node| self.embedding_of(node))
+                    .is_some_and(|previous| previous == embedding)
+                    && self.index.node_id_map.contains_key(id);
+                if unchanged {
+                    Ok(())
+                } else {
+                    self.index
+                        .remove_node_embedding(id)
+                        .and_then(|_| self.index.add_node_embedding(id.clone(), embedding))
+                }
+            }
+            None => self.index.remove_node_embedding(id).map(|_| ()),
+        };
+        if let Err(e) = result {
+            tracing::error!(
+                "Vector index {} failed to update node {}: {}",
+                self.name,
+                id,
+                e
+            );
+        }
+    }
+
+    /// The `k` nodes most similar to `query`, most similar first
+    pub fn query(&self, query: &[f32], k: usize) -> Result<Vec<(NodeId, f32)>> {
+        if query.len() != self.index.config.dimensions {
+            return Err(GraphError::InvalidEmbedding(format!(
+                "Expected {} dimensions, got {}",
+                self.index.config.dimensions,
+                query.len()
+            )));
+        }
+        if k == 0 || self.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut results: Vec<(NodeId, f32)> = self
+            .index
+            .search_similar_nodes(query, k)?
+            .into_iter()
+            .map(|(id, distance)| (id, self.index.similarity(distance)))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(results)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -379,4 +567,73 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_node_vector_index_tracks_updates() -> Result<()> {
+        use crate::node::NodeBuilder;
+
+        let config = EmbeddingConfig {
+            dimensions: 2,
+            ..Default::default()
+        };
+        let index = NodeVectorIndex::new("docs", "Doc", config)?;
+        let doc = |id: &str, x: f64, y: f64| {
+            NodeBuilder::new()
+                .id(id)
+                .label("Doc")
+                .property(
+                    "embedding",
+                    PropertyValue::List(vec![PropertyValue::Float(x), PropertyValue::Float(y)]),
+                )
+                .build()
+        };
+
+        let a = doc("a", 1.0, 0.0);
+        let b = doc("b", 0.0, 1.0);
+        index.update(None, Some(&a));
+        index.update(None, Some(&b));
+        assert_eq!(index.len(), 2);
+
+        let results = index.query(&[1.0, 0.1], 2)?;
+        assert_eq!(results[0].0, "a");
+        assert!(results[0].1 > results[1].1);
+
+        // Moving b next to the query makes it the best match
+        let moved = doc("b", 1.0, 0.1);
+        index.update(Some(&b), Some(&moved));
+        assert_eq!(index.query(&[1.0, 0.1], 1)?[0].0, "b");
+
+        // Nodes without the label or an embedding are dropped
+        let unlabeled = NodeBuilder::new().id("a").label("Other").build();
+        index.update(Some(&a), Some(&unlabeled));
+        index.update(Some(&moved), None);
+        assert!(index.is_empty());
+        assert!(index.query(&[1.0], 1).is_err());
+
+        // Embeddings that could not be indexed are rejected up front
+        assert!(index.check(&a).is_ok());
+        assert!(index.check(&unlabeled).is_ok());
+        assert!(index
+            .check(&NodeBuilder::new().label("Doc").build())
+            .is_ok());
+        let wrong_dimension = NodeBuilder::new()
+            .label("Doc")
+            .property(
+                "embedding",
+                PropertyValue::List(vec![PropertyValue::Float(1.0)]),
+            )
+            .build();
+        let not_numeric = NodeBuilder::new()
+            .label("Doc")
+            .property("embedding", "text")
+            .build();
+        for node in [&wrong_dimension, &not_numeric] {
+            assert!(matches!(
+                index.check(node),
+                Err(GraphError::InvalidEmbedding(_))
+            ));
+        }
+
+        Ok(())
+    }
 }
@@ -3,6 +3,7 @@
 //! A high-performance graph database layer built on RuVector with Neo4j compatibility.
 //! Supports property graphs, hypergraphs, Cypher queries, ACID transactions, and distributed queries.
 
+pub mod algorithms;
 pub mod cypher;
 pub mod edge;
 pub mod error;
@@ -12,6 +13,7 @@ pub mod hyperedge;
 pub mod index;
 pub mod node;
 pub mod property;
+pub mod schema;
 pub mod storage;
 pub mod transaction;
 pub mod types;
@@ -27,14 +29,16 @@ pub mod hybrid;
 pub mod distributed;
 
 // Core type re-exports
+pub use cypher::{QueryResult, Row, Value};
 pub use edge::{Edge, EdgeBuilder};
 pub use error::{GraphError, Result};
-pub use graph::GraphDB;
+pub use graph::{GraphDB, GraphTransaction};
 pub use hyperedge::{Hyperedge, HyperedgeBuilder, HyperedgeId};
 pub use node::{Node, NodeBuilder};
+pub use schema::{ConstraintDefinition, ConstraintKind, IndexDefinition, IndexKind};
 #[cfg(feature = "storage")]
 pub use storage::GraphStorage;
-pub use transaction::{IsolationLevel, Transaction, TransactionManager};
+pub use transaction::{IsolationLevel, Transaction, TransactionManager, WriteSet};
 pub use types::{EdgeId, Label, NodeId, Properties, PropertyValue, RelationType};
 
 // Re-export hybrid query types when available
@@ -0,0 +1,916 @@
+//! Declared secondary indexes and constraints on node properties
+//!
+//! - Range indexes: a B-tree over one property of the nodes with a label,
+//!   used for equality, range and prefix (`STARTS WITH`) predicates
+//! - Full-text indexes: word and trigram postings over string properties,
+//!   used for `CONTAINS` predicates and `db.index.fulltext.queryNodes`
+//! - Constraints: `IS UNIQUE`, backed by a range index of the same name, and
+//!   `IS NOT NULL`, both checked whenever writes commit
+//!
+//! Definitions are persisted in [`GraphStorage`](crate::storage::GraphStorage)
+//! when the database has storage; the index contents are rebuilt on load.
+
+use crate::error::{GraphError, Result};
+use crate::node::Node;
+use crate::transaction::WriteSet;
+use crate::types::{NodeId, PropertyValue};
+use bincode::{Decode, Encode};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::ops::Bound;
+
+/// Kind of a secondary index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode)]
+pub enum IndexKind {
+    /// Ordered index over a single property
+    Range,
+    /// Text index over one or more string properties
+    FullText,
+}
+
+impl fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexKind::Range => write!(f, "RANGE"),
+            IndexKind::FullText => write!(f, "FULLTEXT"),
+        }
+    }
+}
+
+/// Index over properties of the nodes with a label
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct IndexDefinition {
+    pub name: String,
+    pub kind: IndexKind,
+    pub label: String,
+    pub properties: Vec<String>,
+    /// Constraint the index belongs to; such indexes are dropped with it
+    pub owning_constraint: Option<String>,
+}
+
+impl IndexDefinition {
+    pub fn range(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        property: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind: IndexKind::Range,
+            label: label.into(),
+            properties: vec![property.into()],
+            owning_constraint: None,
+        }
+    }
+
+    pub fn full_text(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        properties: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind: IndexKind::FullText,
+            label: label.into(),
+            properties: properties.into_iter().map(Into::into).collect(),
+            owning_constraint: None,
+        }
+    }
+}
+
+/// Kind of a property constraint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Encode, Decode)]
+pub enum ConstraintKind {
+    /// No two nodes with the label share a value
+    Unique,
+    /// Every node with the label has a value
+    NotNull,
+}
+
+impl fmt::Display for ConstraintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstraintKind::Unique => write!(f, "UNIQUENESS"),
+            ConstraintKind::NotNull => write!(f, "NODE_PROPERTY_EXISTENCE"),
+        }
+    }
+}
+
+/// Constraint on one property of the nodes with a label
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ConstraintDefinition {
+    pub name: String,
+    pub kind: ConstraintKind,
+    pub label: String,
+    pub property: String,
+}
+
+impl ConstraintDefinition {
+    pub fn unique(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        property: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind: ConstraintKind::Unique,
+            label: label.into(),
+            property: property.into(),
+        }
+    }
+
+    pub fn not_null(
+        name: impl Into<String>,
+        label: impl Into<String>,
+        property: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            kind: ConstraintKind::NotNull,
+            label: label.into(),
+            property: property.into(),
+        }
+    }
+
+    /// Backing index of a uniqueness constraint
+    fn index(&self) -> Option<IndexDefinition> {
+        (self.kind == ConstraintKind::Unique).then(|| IndexDefinition {
+            owning_constraint: Some(self.name.clone()),
+            ..IndexDefinition::range(&self.name, &self.label, &self.property)
+        })
+    }
+}
+
+/// Totally ordered key of an indexed property value
+///
+/// Values of different types never compare equal; integers and floats are
+/// both numbers, so `1` and `1.0` share a key as they do in Cypher.
+#[derive(Debug, Clone)]
+pub enum IndexKey {
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    List(Vec<IndexKey>),
+}
+
+impl IndexKey {
+    /// Key of a value; null, maps and lists containing them are not indexed
+    pub fn from_value(value: &PropertyValue) -> Option<Self> {
+        Some(match value {
+            PropertyValue::Boolean(b) => IndexKey::Boolean(*b),
+            // -0.0 and 0.0 are equal
+            PropertyValue::Integer(i) => IndexKey::Number(*i as f64 + 0.0),
+            PropertyValue::Float(f) => IndexKey::Number(*f + 0.0),
+            PropertyValue::String(s) => IndexKey::String(s.clone()),
+            PropertyValue::Array(items) | PropertyValue::List(items) => {
+                IndexKey::List(items.iter().map(Self::from_value).collect::<Option<_>>()?)
+            }
+            PropertyValue::Null | PropertyValue::Map(_) => return None,
+        })
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            IndexKey::Boolean(_) => 0,
+            IndexKey::Number(_) => 1,
+            IndexKey::String(_) => 2,
+            IndexKey::List(_) => 3,
+        }
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexKey::Boolean(a), IndexKey::Boolean(b)) => a.cmp(b),
+            (IndexKey::Number(a), IndexKey::Number(b)) => a.total_cmp(b),
+            (IndexKey::String(a), IndexKey::String(b)) => a.cmp(b),
+            (IndexKey::List(a), IndexKey::List(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+/// B-tree from the values of one property to the nodes holding them
+#[derive(Debug, Clone, Default)]
+pub struct RangeIndex {
+    entries: BTreeMap<IndexKey, BTreeSet<NodeId>>,
+}
+
+impl RangeIndex {
+    fn insert(&mut self, key: IndexKey, id: &NodeId) {
+        self.entries.entry(key).or_default().insert(id.clone());
+    }
+
+    fn remove(&mut self, key: &IndexKey, id: &NodeId) {
+        if let Some(ids) = self.entries.get_mut(key) {
+            ids.remove(id);
+            if ids.is_empty() {
+                self.entries.remove(key);
+            }
+        }
+    }
+
+    /// Nodes whose value equals `key`
+    pub fn get(&self, key: &IndexKey) -> Vec<NodeId> {
+        self.entries
+            .get(key)
+            .map(|ids| ids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Nodes whose value lies between the bounds, in key order
+    ///
+    /// Only values of the bounds' type are returned, since comparisons
+    /// between types are never true.
+    pub fn range(&self, lower: Bound<&IndexKey>, upper: Bound<&IndexKey>) -> Vec<NodeId> {
+        let rank = match (lower, upper) {
+            (Bound::Included(k) | Bound::Excluded(k), _)
+            | (Bound::Unbounded, Bound::Included(k) | Bound::Excluded(k)) => k.rank(),
+            (Bound::Unbounded, Bound::Unbounded) => return Vec::new(),
+        };
+        if let (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) =
+            (lower, upper)
+        {
+            // BTreeMap::range panics on inverted or empty exclusive ranges
+            if l > u
+                || (l == u && !matches!((lower, upper), (Bound::Included(_), Bound::Included(_))))
+            {
+                return Vec::new();
+            }
+        }
+        self.entries
+            .range::<IndexKey, _>((lower, upper))
+            .filter(|(key, _)| key.rank() == rank)
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect()
+    }
+
+    /// Nodes whose string value starts with `prefix`
+    pub fn prefix(&self, prefix: &str) -> Vec<NodeId> {
+        let start = IndexKey::String(prefix.to_string());
+        self.entries
+            .range(start..)
+            .take_while(|(key, _)| matches!(key, IndexKey::String(s) if s.starts_with(prefix)))
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.values().map(BTreeSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Word and trigram postings over string properties
+#[derive(Debug, Clone, Default)]
+pub struct FullTextIndex {
+    /// Lower-cased word -> nodes containing it in any indexed property
+    terms: HashMap<String, HashSet<NodeId>>,
+    /// (property, trigram) -> nodes whose property contains the trigram
+    trigrams: HashMap<(String, String), HashSet<NodeId>>,
+    documents: usize,
+}
+
+/// Lower-cased words of a text
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Distinct three-character windows of a text
+fn trigrams(text: &str) -> HashSet<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+impl FullTextIndex {
+    fn update(&mut self, node: &Node, properties: &[String], insert: bool) {
+        let mut indexed = false;
+        for property in properties {
+            let Some(PropertyValue::String(text)) = node.properties.get(property) else {
+                continue;
+            };
+            indexed = true;
+            for term in tokenize(text) {
+                Self::post(&mut self.terms, term, &node.id, insert);
+            }
+            for trigram in trigrams(text) {
+                Self::post(
+                    &mut self.trigrams,
+                    (property.clone(), trigram),
+                    &node.id,
+                    insert,
+                );
+            }
+        }
+        if indexed {
+            if insert {
+                self.documents += 1;
+            } else {
+                self.documents -= 1;
+            }
+        }
+    }
+
+    fn post<K: std::hash::Hash + Eq>(
+        postings: &mut HashMap<K, HashSet<NodeId>>,
+        key: K,
+        id: &NodeId,
+        insert: bool,
+    ) {
+        if insert {
+            postings.entry(key).or_default().insert(id.clone());
+        } else if let Some(ids) = postings.get_mut(&key) {
+            ids.remove(id);
+            if ids.is_empty() {
+                postings.remove(&key);
+            }
+        }
+    }
+
+    /// Candidate nodes whose `property` may contain `needle`
+    ///
+    /// Every node that does contain it is returned, possibly with others.
+    /// `None` if the needle is too short to use the trigram postings.
+    pub fn contains(&self, property: &str, needle: &str) -> Option<Vec<NodeId>> {
+        let grams = trigrams(needle);
+        if grams.is_empty() {
+            return None;
+        }
+        let mut postings: Vec<&HashSet<NodeId>> = Vec::with_capacity(grams.len());
+        for gram in grams {
+            match self.trigrams.get(&(property.to_string(), gram)) {
+                Some(ids) => postings.push(ids),
+                None => return Some(Vec::new()),
+            }
+        }
+        postings.sort_by_key(|ids| ids.len());
+        let (smallest, rest) = postings.split_first().expect("at least one trigram");
+        let mut ids: Vec<NodeId> = smallest
+            .iter()
+            .filter(|id| rest.iter().all(|ids| ids.contains(*id)))
+            .cloned()
+            .collect();
+        ids.sort();
+        Some(ids)
+    }
+
+    /// Nodes matching any word of `query`, best first
+    ///
+    /// The score is the fraction of the query's distinct words a node
+    /// contains.
+    pub fn search(&self, query: &str) -> Vec<(NodeId, f64)> {
+        let words: BTreeSet<String> = tokenize(query).collect();
+        let mut hits: HashMap<&NodeId, usize> = HashMap::new();
+        for word in &words {
+            for id in self.terms.get(word).into_iter().flatten() {
+                *hits.entry(id).or_insert(0) += 1;
+            }
+        }
+        let mut results: Vec<(NodeId, f64)> = hits
+            .into_iter()
+            .map(|(id, count)| (id.clone(), count as f64 / words.len() as f64))
+            .collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        results
+    }
+
+    /// Number of indexed nodes
+    pub fn len(&self) -> usize {
+        self.documents
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+enum IndexData {
+    Range(RangeIndex),
+    FullText(FullTextIndex),
+}
+
+#[derive(Debug, Clone)]
+struct Index {
+    definition: IndexDefinition,
+    data: IndexData,
+}
+
+impl Index {
+    fn new(definition: IndexDefinition) -> Self {
+        let data = match definition.kind {
+            IndexKind::Range => IndexData::Range(RangeIndex::default()),
+            IndexKind::FullText => IndexData::FullText(FullTextIndex::default()),
+        };
+        Self { definition, data }
+    }
+
+    fn update(&mut self, node: &Node, insert: bool) {
+        if !node.has_label(&self.definition.label) {
+            return;
+        }
+        match &mut self.data {
+            IndexData::Range(index) => {
+                let key = node
+                    .properties
+                    .get(&self.definition.properties[0])
+                    .and_then(IndexKey::from_value);
+                match key {
+                    Some(key) if insert => index.insert(key, &node.id),
+                    Some(key) => index.remove(&key, &node.id),
+                    None => {}
+                }
+            }
+            IndexData::FullText(index) => index.update(node, &self.definition.properties, insert),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SchemaState {
+    indexes: BTreeMap<String, Index>,
+    constraints: BTreeMap<String, ConstraintDefinition>,
+}
+
+/// Indexes and constraints of a graph, kept in sync with its nodes
+///
+/// [`GraphDB`](crate::GraphDB) validates commits with [`check`](Self::check)
+/// and reports every node change through [`update`](Self::update).
+#[derive(Debug, Default)]
+pub struct Schema {
+    state: RwLock<SchemaState>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the indexes after `old` was replaced by `new`
+    pub fn update(&self, old: Option<&Node>, new: Option<&Node>) {
+        let mut state = self.state.write();
+        for index in state.indexes.values_mut() {
+            if let Some(old) = old {
+                index.update(old, false);
+            }
+            if let Some(new) = new {
+                index.update(new, true);
+            }
+        }
+    }
+
+    /// All index definitions, sorted by name
+    pub fn indexes(&self) -> Vec<IndexDefinition> {
+        self.state
+            .read()
+            .indexes
+            .values()
+            .map(|index| index.definition.clone())
+            .collect()
+    }
+
+    /// All constraint definitions, sorted by name
+    pub fn constraints(&self) -> Vec<ConstraintDefinition> {
+        self.state.read().constraints.values().cloned().collect()
+    }
+
+    pub fn index(&self, name: &str) -> Option<IndexDefinition> {
+        self.state
+            .read()
+            .indexes
+            .get(name)
+            .map(|index| index.definition.clone())
+    }
+
+    pub fn constraint(&self, name: &str) -> Option<ConstraintDefinition> {
+        self.state.read().constraints.get(name).cloned()
+    }
+
+    /// Name of an index of `kind` covering `label` and `property`
+    pub fn find_index(&self, kind: IndexKind, label: &str, property: &str) -> Option<String> {
+        self.state
+            .read()
+            .indexes
+            .values()
+            .map(|index| &index.definition)
+            .find(|d| {
+                d.kind == kind && d.label == label && d.properties.iter().any(|p| p == property)
+            })
+            .map(|d| d.name.clone())
+    }
+
+    /// Create an index and fill it from `nodes`
+    pub fn create_index(&self, definition: IndexDefinition, nodes: &[Node]) -> Result<()> {
+        let mut state = self.state.write();
+        Self::check_index(&state, &definition)?;
+        let mut index = Index::new(definition);
+        for node in nodes {
+            index.update(node, true);
+        }
+        state.indexes.insert(index.definition.name.clone(), index);
+        Ok(())
+    }
+
+    /// Drop an index; returns whether it existed
+    ///
+    /// Indexes backing a constraint are dropped with the constraint instead.
+    pub fn drop_index(&self, name: &str) -> Result<bool> {
+        let mut state = self.state.write();
+        match state.indexes.get(name) {
+            None => Ok(false),
+            Some(index) if index.definition.owning_constraint.is_some() => {
+                Err(GraphError::IndexError(format!(
+                    "Index {} belongs to constraint {}; drop the constraint instead",
+                    name,
+                    index
+                        .definition
+                        .owning_constraint
+                        .as_deref()
+                        .unwrap_or_default()
+                )))
+            }
+            Some(_) => Ok(state.indexes.remove(name).is_some()),
+        }
+    }
+
+    /// Create a constraint after checking that `nodes` satisfy it
+    pub fn create_constraint(
+        &self,
+        definition: ConstraintDefinition,
+        nodes: &[Node],
+    ) -> Result<()> {
+        let mut state = self.state.write();
+        if state.constraints.contains_key(&definition.name)
+            || state.indexes.contains_key(&definition.name)
+        {
+            return Err(GraphError::IndexError(format!(
+                "An index or constraint named {} already exists",
+                definition.name
+            )));
+        }
+        if let Some(existing) = state.constraints.values().find(|c| {
+            c.kind == definition.kind
+                && c.label == definition.label
+                && c.property == definition.property
+        }) {
+            return Err(GraphError::IndexError(format!(
+                "Constraint {} already enforces the same rule",
+                existing.name
+            )));
+        }
+
+        let mut index = definition.index().map(Index::new);
+        let mut seen: BTreeMap<IndexKey, &NodeId> = BTreeMap::new();
+        for node in nodes.iter().filter(|n| n.has_label(&definition.label)) {
+            let value = node.properties.get(&definition.property);
+            match definition.kind {
+                ConstraintKind::NotNull => {
+                    if value.map_or(true, |v| matches!(v, PropertyValue::Null)) {
+                        return Err(violation(&definition, node));
+                    }
+                }
+                ConstraintKind::Unique => {
+                    if let Some(key) = value.and_then(IndexKey::from_value) {
+                        if let Some(other) = seen.insert(key, &node.id) {
+                            return Err(GraphError::ConstraintViolation(format!(
+                                "Nodes {} and {} share {}, so constraint {} cannot be created",
+                                other, node.id, definition.property, definition.name
+                            )));
+                        }
+                    }
+                }
+            }
+            if let Some(index) = &mut index {
+                index.update(node, true);
+            }
+        }
+
+        if let Some(index) = index {
+            state.indexes.insert(index.definition.name.clone(), index);
+        }
+        state
+            .constraints
+            .insert(definition.name.clone(), definition);
+        Ok(())
+    }
+
+    /// Drop a constraint and its backing index; returns whether it existed
+    pub fn drop_constraint(&self, name: &str) -> bool {
+        let mut state = self.state.write();
+        let removed = state.constraints.remove(name).is_some();
+        if removed {
+            state
+                .indexes
+                .retain(|_, index| index.definition.owning_constraint.as_deref() != Some(name));
+        }
+        removed
+    }
+
+    /// Check that the nodes after committing `writes` satisfy every constraint
+    pub fn check(&self, writes: &WriteSet) -> Result<()> {
+        let state = self.state.read();
+        for constraint in state.constraints.values() {
+            let mut seen: BTreeMap<IndexKey, &NodeId> = BTreeMap::new();
+            for node in writes
+                .nodes
+                .values()
+                .filter(|n| n.has_label(&constraint.label))
+            {
+                let value = node.properties.get(&constraint.property);
+                match constraint.kind {
+                    ConstraintKind::NotNull => {
+                        if value.map_or(true, |v| matches!(v, PropertyValue::Null)) {
+                            return Err(violation(constraint, node));
+                        }
+                    }
+                    ConstraintKind::Unique => {
+                        let Some(key) = value.and_then(IndexKey::from_value) else {
+                            continue;
+                        };
+                        let Some(IndexData::Range(index)) =
+                            state.indexes.get(&constraint.name).map(|i| &i.data)
+                        else {
+                            continue;
+                        };
+                        // Nodes replaced or deleted by this commit no longer count
+                        let clash = index.get(&key).into_iter().find(|id| {
+                            id != &node.id
+                                && !writes.nodes.contains_key(id)
+                                && !writes.deleted_nodes.contains(id)
+                        });
+                        if clash.is_some() || seen.insert(key, &node.id).is_some() {
+                            return Err(violation(constraint, node));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Equality, range or prefix seek on a range index
+    pub fn seek(&self, name: &str, seek: &IndexSeek) -> Option<Vec<NodeId>> {
+        let state = self.state.read();
+        let IndexData::Range(index) = &state.indexes.get(name)?.data else {
+            return None;
+        };
+        Some(match seek {
+            IndexSeek::Equal(key) => index.get(key),
+            IndexSeek::Range(lower, upper) => index.range(lower.as_ref(), upper.as_ref()),
+            IndexSeek::Prefix(prefix) => index.prefix(prefix),
+        })
+    }
+
+    /// Candidates whose `property` may contain `needle`, from a full-text index
+    pub fn contains(&self, name: &str, property: &str, needle: &str) -> Option<Vec<NodeId>> {
+        let state = self.state.read();
+        match &state.indexes.get(name)?.data {
+            IndexData::FullText(index) => index.contains(property, needle),
+            IndexData::Range(_) => None,
+        }
+    }
+
+    /// Word search on a full-text index, best matches first
+    pub fn search(&self, name: &str, query: &str) -> Result<Vec<(NodeId, f64)>> {
+        let state = self.state.read();
+        match state.indexes.get(name).map(|index| &index.data) {
+            Some(IndexData::FullText(index)) => Ok(index.search(query)),
+            Some(IndexData::Range(_)) => Err(GraphError::IndexError(format!(
+                "Index {} is not a full-text index",
+                name
+            ))),
+            None => Err(GraphError::IndexError(format!("No index named {}", name))),
+        }
+    }
+
+    /// Number of entries in an index
+    pub fn index_size(&self, name: &str) -> Option<usize> {
+        Some(match &self.state.read().indexes.get(name)?.data {
+            IndexData::Range(index) => index.len(),
+            IndexData::FullText(index) => index.len(),
+        })
+    }
+
+    fn check_index(state: &SchemaState, definition: &IndexDefinition) -> Result<()> {
+        if state.indexes.contains_key(&definition.name)
+            || state.constraints.contains_key(&definition.name)
+        {
+            return Err(GraphError::IndexError(format!(
+                "An index or constraint named {} already exists",
+                definition.name
+            )));
+        }
+        match (definition.kind, definition.properties.len()) {
+            (_, 0) => {
+                return Err(GraphError::IndexError(
+                    "An index needs at least one property".to_string(),
+                ))
+            }
+            (IndexKind::Range, n) if n > 1 => {
+                return Err(GraphError::IndexError(
+                    "Range indexes cover a single property".to_string(),
+                ))
+            }
+            _ => {}
+        }
+        if let Some(existing) = state.indexes.values().find(|index| {
+            let d = &index.definition;
+            d.kind == definition.kind
+                && d.label == definition.label
+                && d.properties == definition.properties
+        }) {
+            return Err(GraphError::IndexError(format!(
+                "Index {} already covers the same properties",
+                existing.definition.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Lookup on a range index
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexSeek {
+    Equal(IndexKey),
+    Range(Bound<IndexKey>, Bound<IndexKey>),
+    Prefix(String),
+}
+
+fn violation(constraint: &ConstraintDefinition, node: &Node) -> GraphError {
+    let rule = match constraint.kind {
+        ConstraintKind::Unique => "must be unique",
+        ConstraintKind::NotNull => "must not be null",
+    };
+    GraphError::ConstraintViolation(format!(
+        "Node {} violates constraint {}: :{}({}) {}",
+        node.id, constraint.name, constraint.label, constraint.property, rule
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::NodeBuilder;
+
+    fn person(id: &str, email: Option<&str>) -> Node {
+        let mut builder = NodeBuilder::new().id(id).label("Person");
+        if let Some(email) = email {
+            builder = builder.property("email", email);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_index_key_order() {
+        let key = |v: PropertyValue| IndexKey::from_value(&v).unwrap();
+        assert_eq!(
+            key(PropertyValue::Integer(1)),
+            key(PropertyValue::Float(1.0))
+        );
+        assert_eq!(
+            key(PropertyValue::Float(-0.0)),
+            key(PropertyValue::Float(0.0))
+        );
+        assert!(key(PropertyValue::Integer(2)) < key(PropertyValue::Float(2.5)));
+        assert!(key(PropertyValue::Integer(100)) < key(PropertyValue::String("1".into())));
+        assert!(IndexKey::from_value(&PropertyValue::Null).is_none());
+    }
+
+    #[test]
+    fn test_range_index_seeks() {
+        let schema = Schema::new();
+        let nodes: Vec<Node> = (0..10)
+            .map(|i| {
+                NodeBuilder::new()
+                    .id(format!("n{}", i))
+                    .label("Item")
+                    .property("rank", i as i64)
+                    .property("code", format!("c{}", i % 3))
+                    .build()
+            })
+            .collect();
+        schema
+            .create_index(IndexDefinition::range("rank", "Item", "rank"), &nodes)
+            .unwrap();
+        schema
+            .create_index(IndexDefinition::range("code", "Item", "code"), &nodes)
+            .unwrap();
+
+        let number = |n: f64| IndexKey::Number(n);
+        let seek = |name: &str, seek: IndexSeek| schema.seek(name, &seek).unwrap();
+        assert_eq!(seek("rank", IndexSeek::Equal(number(3.0))), vec!["n3"]);
+        assert_eq!(
+            seek(
+                "rank",
+                IndexSeek::Range(Bound::Excluded(number(6.0)), Bound::Unbounded)
+            ),
+            vec!["n7", "n8", "n9"]
+        );
+        assert_eq!(
+            seek(
+                "rank",
+                IndexSeek::Range(Bound::Included(number(5.0)), Bound::Excluded(number(5.0)))
+            ),
+            Vec::<NodeId>::new()
+        );
+        assert_eq!(seek("code", IndexSeek::Prefix("c1".into())).len(), 3);
+
+        schema.update(Some(&nodes[3]), None);
+        assert!(seek("rank", IndexSeek::Equal(number(3.0))).is_empty());
+        assert_eq!(schema.index_size("rank"), Some(9));
+    }
+
+    #[test]
+    fn test_full_text_index() {
+        let schema = Schema::new();
+        let doc = |id: &str, title: &str| {
+            NodeBuilder::new()
+                .id(id)
+                .label("Doc")
+                .property("title", title)
+                .build()
+        };
+        let docs = vec![
+            doc("a", "Graph databases in Rust"),
+            doc("b", "Vector search with graphs"),
+            doc("c", "Cooking pasta"),
+        ];
+        schema
+            .create_index(
+                IndexDefinition::full_text("titles", "Doc", ["title"]),
+                &docs,
+            )
+            .unwrap();
+
+        let results = schema.search("titles", "rust graph").unwrap();
+        assert_eq!(results[0], ("a".to_string(), 1.0));
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            schema.contains("titles", "title", "raph"),
+            Some(vec!["a".into(), "b".into()])
+        );
+        assert_eq!(schema.contains("titles", "title", "xyz"), Some(vec![]));
+        assert_eq!(schema.contains("titles", "title", "ra"), None);
+    }
+
+    #[test]
+    fn test_constraints() {
+        let schema = Schema::new();
+        let existing = vec![person("a", Some("a@x")), person("b", None)];
+        schema
+            .create_constraint(
+                ConstraintDefinition::unique("email", "Person", "email"),
+                &existing,
+            )
+            .unwrap();
+        assert_eq!(
+            schema.index("email").unwrap().owning_constraint.as_deref(),
+            Some("email")
+        );
+        assert!(matches!(
+            schema.create_constraint(
+                ConstraintDefinition::not_null("has_email", "Person", "email"),
+                &existing
+            ),
+            Err(GraphError::ConstraintViolation(_))
+        ));
+
+        let mut writes = WriteSet::new();
+        writes.put_node(person("c", Some("a@x")));
+        assert!(matches!(
+            schema.check(&writes),
+            Err(GraphError::ConstraintViolation(_))
+        ));
+
+        // Deleting the holder in the same commit frees the value
+        writes.delete_node("a".to_string());
+        assert!(schema.check(&writes).is_ok());
+
+        assert!(schema.drop_index("email").is_err());
+        assert!(schema.drop_constraint("email"));
+        assert!(schema.index("email").is_none());
+    }
+}
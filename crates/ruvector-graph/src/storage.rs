@@ -9,6 +9,10 @@ use crate::hyperedge::{Hyperedge, HyperedgeId};
 #[cfg(feature = "storage")]
 use crate::node::Node;
 #[cfg(feature = "storage")]
+use crate::schema::{ConstraintDefinition, IndexDefinition};
+#[cfg(feature = "storage")]
+use crate::transaction::WriteSet;
+#[cfg(feature = "storage")]
 use crate::types::{EdgeId, NodeId};
 #[cfg(feature = "storage")]
 use anyhow::Result;
@@ -36,6 +40,8 @@ const EDGES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("edges");
 const HYPEREDGES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("hyperedges");
 #[cfg(feature = "storage")]
 const METADATA_TABLE: TableDefinition<&str, &str> = TableDefinition::new("metadata");
+#[cfg(feature = "storage")]
+const SCHEMA_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("schema");
 
 #[cfg(feature = "storage")]
 // Global database connection pool to allow multiple GraphStorage instances
@@ -109,6 +115,7 @@ impl GraphStorage {
                     let _ = write_txn.open_table(EDGES_TABLE)?;
                     let _ = write_txn.open_table(HYPEREDGES_TABLE)?;
                     let _ = write_txn.open_table(METADATA_TABLE)?;
+                    let _ = write_txn.open_table(SCHEMA_TABLE)?;
                 }
                 write_txn.commit()?;
 
@@ -354,6 +361,43 @@ impl GraphStorage {
         Ok(ids)
     }
 
+    // Transaction operations
+
+    /// Apply a transaction's write set atomically in a single write transaction
+    pub fn apply_write_set(&self, writes: &WriteSet) -> Result<()> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut nodes = write_txn.open_table(NODES_TABLE)?;
+            for id in &writes.deleted_nodes {
+                nodes.remove(id.as_str())?;
+            }
+            for node in writes.nodes.values() {
+                let node_data = bincode::encode_to_vec(node, config::standard())?;
+                nodes.insert(node.id.as_str(), node_data.as_slice())?;
+            }
+
+            let mut edges = write_txn.open_table(EDGES_TABLE)?;
+            for id in &writes.deleted_edges {
+                edges.remove(id.as_str())?;
+            }
+            for edge in writes.edges.values() {
+                let edge_data = bincode::encode_to_vec(edge, config::standard())?;
+                edges.insert(edge.id.as_str(), edge_data.as_slice())?;
+            }
+
+            let mut hyperedges = write_txn.open_table(HYPEREDGES_TABLE)?;
+            for id in &writes.deleted_hyperedges {
+                hyperedges.remove(id.as_str())?;
+            }
+            for hyperedge in writes.hyperedges.values() {
+                let hyperedge_data = bincode::encode_to_vec(hyperedge, config::standard())?;
+                hyperedges.insert(hyperedge.id.as_str(), hyperedge_data.as_slice())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
     // Metadata operations
 
     /// Set metadata
@@ -376,6 +420,77 @@ impl GraphStorage {
         Ok(value)
     }
 
+    // Schema operations
+
+    /// Persist an index definition
+    pub fn save_index(&self, index: &IndexDefinition) -> Result<()> {
+        self.put_schema(&format!("index/{}", index.name), index)
+    }
+
+    /// Remove an index definition
+    pub fn delete_index(&self, name: &str) -> Result<bool> {
+        self.remove_schema(&format!("index/{}", name))
+    }
+
+    /// All persisted index definitions
+    pub fn all_indexes(&self) -> Result<Vec<IndexDefinition>> {
+        self.schema_entries("index/")
+    }
+
+    /// Persist a constraint definition
+    pub fn save_constraint(&self, constraint: &ConstraintDefinition) -> Result<()> {
+        self.put_schema(&format!("constraint/{}", constraint.name), constraint)
+    }
+
+    /// Remove a constraint definition
+    pub fn delete_constraint(&self, name: &str) -> Result<bool> {
+        self.remove_schema(&format!("constraint/{}", name))
+    }
+
+    /// All persisted constraint definitions
+    pub fn all_constraints(&self) -> Result<Vec<ConstraintDefinition>> {
+        self.schema_entries("constraint/")
+    }
+
+    fn put_schema<T: bincode::Encode>(&self, key: &str, value: &T) -> Result<()> {
+        let data = bincode::encode_to_vec(value, config::standard())?;
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(SCHEMA_TABLE)?;
+            table.insert(key, data.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    fn remove_schema(&self, key: &str) -> Result<bool> {
+        let write_txn = self.db.begin_write()?;
+        let deleted;
+        {
+            let mut table = write_txn.open_table(SCHEMA_TABLE)?;
+            deleted = table.remove(key)?.is_some();
+        }
+        write_txn.commit()?;
+        Ok(deleted)
+    }
+
+    fn schema_entries<T: bincode::Decode<()>>(&self, prefix: &str) -> Result<Vec<T>> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(SCHEMA_TABLE)?;
+
+        let mut entries = Vec::new();
+        for item in table.range(prefix..)? {
+            let (key, value) = item?;
+            if !key.value().starts_with(prefix) {
+                break;
+            }
+            let (entry, _): (T, usize) =
+                bincode::decode_from_slice(value.value(), config::standard())?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
     // Statistics
 
     /// Get the number of nodes
@@ -485,4 +600,47 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_apply_write_set() -> Result<()> {
+        let dir = tempdir()?;
+        let storage = GraphStorage::new(dir.path().join("test.db"))?;
+
+        let old = NodeBuilder::new().id("old").build();
+        storage.insert_node(&old)?;
+
+        let mut writes = WriteSet::new();
+        writes.delete_node("old".to_string());
+        writes.put_node(NodeBuilder::new().id("a").build());
+        writes.put_node(NodeBuilder::new().id("b").build());
+        writes.put_edge(EdgeBuilder::new("a".to_string(), "b".to_string(), "KNOWS").build());
+        storage.apply_write_set(&writes)?;
+
+        assert!(storage.get_node("old")?.is_none());
+        assert_eq!(storage.node_count()?, 2);
+        assert_eq!(storage.edge_count()?, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_storage() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("test.db");
+        let storage = GraphStorage::new(&path)?;
+
+        storage.save_index(&IndexDefinition::range("by_age", "Person", "age"))?;
+        storage.save_index(&IndexDefinition::full_text("bios", "Person", ["bio"]))?;
+        storage.save_constraint(&ConstraintDefinition::unique("email", "Person", "email"))?;
+        assert!(storage.delete_index("by_age")?);
+        assert!(!storage.delete_index("by_age")?);
+
+        let reopened = GraphStorage::new(&path)?;
+        let indexes = reopened.all_indexes()?;
+        assert_eq!(indexes.len(), 1);
+        assert_eq!(indexes[0].name, "bios");
+        assert_eq!(reopened.all_constraints()?[0].property, "email");
+
+        Ok(())
+    }
 }
@@ -1,122 +1,203 @@
 //! Transaction support for ACID guarantees with MVCC
 //!
-//! Provides multi-version concurrency control for high-throughput concurrent access
+//! Provides multi-version concurrency control for high-throughput concurrent access:
+//! - Commits are stamped by a logical clock and become visible all at once
+//! - RepeatableRead and Serializable read the snapshot taken at `begin`, the
+//!   weaker levels read the latest committed data
+//! - Serializable commits fail when a record they read or wrote was changed by
+//!   a concurrent commit (first committer wins), which rules out write skew
+//! - Versions that no active transaction can see are garbage-collected
 
 use crate::edge::Edge;
-use crate::error::Result;
+use crate::error::{GraphError, Result};
 use crate::hyperedge::{Hyperedge, HyperedgeId};
 use crate::node::Node;
 use crate::types::{EdgeId, NodeId};
 use dashmap::DashMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use uuid::Uuid;
 
 /// Transaction isolation level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum IsolationLevel {
-    /// Dirty reads allowed
+    /// Dirty reads allowed (reads behave as ReadCommitted)
     ReadUncommitted,
     /// Only committed data visible
     ReadCommitted,
     /// Repeatable reads (default)
     RepeatableRead,
-    /// Full isolation
+    /// Snapshot reads, and commits fail if anything read or written changed
     Serializable,
 }
 
+impl IsolationLevel {
+    /// Whether reads use the snapshot taken when the transaction began
+    pub fn uses_snapshot(self) -> bool {
+        matches!(
+            self,
+            IsolationLevel::RepeatableRead | IsolationLevel::Serializable
+        )
+    }
+}
+
 /// Transaction ID type
 pub type TxnId = u64;
 
-/// Timestamp for MVCC
+/// Logical commit timestamp for MVCC
 pub type Timestamp = u64;
 
-/// Get current timestamp
-fn now() -> Timestamp {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_micros() as u64
-}
+/// Number of commits between garbage collections while transactions overlap
+const GC_INTERVAL: u64 = 64;
 
 /// Versioned value for MVCC
 #[derive(Debug, Clone)]
 struct Version<T> {
-    /// Creation timestamp
+    /// Commit timestamp of the version
     created_at: Timestamp,
-    /// Deletion timestamp (None if not deleted)
+    /// Commit timestamp of the deletion (None if not deleted)
     deleted_at: Option<Timestamp>,
-    /// Transaction ID that created this version
-    created_by: TxnId,
-    /// Transaction ID that deleted this version
-    deleted_by: Option<TxnId>,
     /// The actual value
     value: T,
 }
 
-/// Transaction state
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum TxnState {
-    Active,
-    Committed,
-    Aborted,
+/// Committed versions of a record, oldest first
+type VersionChain<T> = Vec<Version<T>>;
+
+/// The version of a chain visible at `ts`
+fn visible<T: Clone>(chain: &[Version<T>], ts: Timestamp) -> Option<T> {
+    let version = chain.iter().rev().find(|v| v.created_at <= ts)?;
+    match version.deleted_at {
+        Some(deleted_at) if deleted_at <= ts => None,
+        _ => Some(version.value.clone()),
+    }
+}
+
+/// Whether a chain was changed by a commit after `ts`
+fn modified_since<T>(chain: &[Version<T>], ts: Timestamp) -> bool {
+    chain
+        .last()
+        .is_some_and(|v| v.created_at > ts || v.deleted_at.is_some_and(|d| d > ts))
+}
+
+/// Drop the versions no snapshot at or after `horizon` can see
+///
+/// Returns the number of versions removed and whether the chain can be
+/// dropped entirely.
+fn prune<T>(chain: &mut VersionChain<T>, horizon: Timestamp, keep_latest: bool) -> (usize, bool) {
+    let before = chain.len();
+
+    if let Some(oldest_needed) = chain.iter().rposition(|v| v.created_at <= horizon) {
+        chain.drain(..oldest_needed);
+        if chain[0].deleted_at.is_some_and(|d| d <= horizon) {
+            chain.remove(0);
+        }
+    }
+
+    // Without `keep_latest` the store holds the latest version, so a chain
+    // whose only version is visible to everyone carries no extra history
+    let redundant = !keep_latest
+        && chain.len() == 1
+        && chain[0].created_at <= horizon
+        && chain[0].deleted_at.is_none();
+    let removed = before - chain.len();
+    if redundant || chain.is_empty() {
+        (before, true)
+    } else {
+        (removed, false)
+    }
+}
+
+/// Latest committed records of a store whose history a [`TransactionManager`] keeps
+pub trait CommittedState {
+    fn committed_node(&self, id: &NodeId) -> Option<Node>;
+    fn committed_edge(&self, id: &EdgeId) -> Option<Edge>;
+    fn committed_hyperedge(&self, id: &HyperedgeId) -> Option<Hyperedge>;
+}
+
+/// Records a Serializable transaction read from the committed state
+#[derive(Default)]
+struct ReadSet {
+    nodes: HashSet<NodeId>,
+    edges: HashSet<EdgeId>,
+    hyperedges: HashSet<HyperedgeId>,
 }
 
 /// Transaction metadata
 struct TxnMetadata {
-    id: TxnId,
-    state: TxnState,
     isolation_level: IsolationLevel,
     start_time: Timestamp,
-    commit_time: Option<Timestamp>,
+    /// Validated at commit; only tracked for Serializable transactions
+    reads: ReadSet,
 }
 
 /// Transaction manager for MVCC
+#[derive(Clone)]
 pub struct TransactionManager {
     /// Next transaction ID
-    next_txn_id: AtomicU64,
+    next_txn_id: Arc<AtomicU64>,
+    /// Timestamp of the latest commit visible to readers
+    clock: Arc<AtomicU64>,
+    /// Serializes commits and snapshot acquisition
+    commit_lock: Arc<Mutex<()>>,
     /// Active transactions
     active_txns: Arc<DashMap<TxnId, TxnMetadata>>,
-    /// Committed transactions (for cleanup)
-    committed_txns: Arc<DashMap<TxnId, Timestamp>>,
+    /// Commits since the last garbage collection
+    commits_since_gc: Arc<AtomicU64>,
     /// Node versions (key -> list of versions)
-    node_versions: Arc<DashMap<NodeId, Vec<Version<Node>>>>,
+    node_versions: Arc<DashMap<NodeId, VersionChain<Node>>>,
     /// Edge versions
-    edge_versions: Arc<DashMap<EdgeId, Vec<Version<Edge>>>>,
+    edge_versions: Arc<DashMap<EdgeId, VersionChain<Edge>>>,
     /// Hyperedge versions
-    hyperedge_versions: Arc<DashMap<HyperedgeId, Vec<Version<Hyperedge>>>>,
+    hyperedge_versions: Arc<DashMap<HyperedgeId, VersionChain<Hyperedge>>>,
+    /// Whether the latest committed records live in an external store
+    history_only: bool,
 }
 
 impl TransactionManager {
-    /// Create a new transaction manager
+    /// Create a new transaction manager that stores every committed record
     pub fn new() -> Self {
         Self {
-            next_txn_id: AtomicU64::new(1),
+            next_txn_id: Arc::new(AtomicU64::new(1)),
+            clock: Arc::new(AtomicU64::new(0)),
+            commit_lock: Arc::new(Mutex::new(())),
             active_txns: Arc::new(DashMap::new()),
-            committed_txns: Arc::new(DashMap::new()),
+            commits_since_gc: Arc::new(AtomicU64::new(0)),
             node_versions: Arc::new(DashMap::new()),
             edge_versions: Arc::new(DashMap::new()),
             hyperedge_versions: Arc::new(DashMap::new()),
+            history_only: false,
+        }
+    }
+
+    /// Create a manager for a store that holds the latest committed records
+    ///
+    /// Only the versions that active snapshots still need are retained; commits
+    /// must pass the store as their [`CommittedState`].
+    pub fn history_only() -> Self {
+        Self {
+            history_only: true,
+            ..Self::new()
         }
     }
 
     /// Begin a new transaction
     pub fn begin(&self, isolation_level: IsolationLevel) -> Transaction {
+        // Taking the snapshot under the commit lock keeps in-flight commits out of it
+        let _guard = self.commit_lock.lock();
         let txn_id = self.next_txn_id.fetch_add(1, Ordering::SeqCst);
-        let start_time = now();
-
-        let metadata = TxnMetadata {
-            id: txn_id,
-            state: TxnState::Active,
-            isolation_level,
-            start_time,
-            commit_time: None,
-        };
+        let start_time = self.clock.load(Ordering::SeqCst);
 
-        self.active_txns.insert(txn_id, metadata);
+        self.active_txns.insert(
+            txn_id,
+            TxnMetadata {
+                isolation_level,
+                start_time,
+                reads: ReadSet::default(),
+            },
+        );
 
         Transaction {
             id: txn_id,
@@ -124,134 +205,347 @@ impl TransactionManager {
             isolation_level,
             start_time,
             writes: Arc::new(RwLock::new(WriteSet::new())),
+            finished: false,
         }
     }
 
-    /// Commit a transaction
-    fn commit(&self, txn_id: TxnId, writes: &WriteSet) -> Result<()> {
-        let commit_time = now();
-
-        // Apply all writes
-        for (node_id, node) in &writes.nodes {
-            self.node_versions
-                .entry(node_id.clone())
-                .or_insert_with(Vec::new)
-                .push(Version {
-                    created_at: commit_time,
-                    deleted_at: None,
-                    created_by: txn_id,
-                    deleted_by: None,
-                    value: node.clone(),
-                });
-        }
-
-        for (edge_id, edge) in &writes.edges {
-            self.edge_versions
-                .entry(edge_id.clone())
-                .or_insert_with(Vec::new)
-                .push(Version {
-                    created_at: commit_time,
-                    deleted_at: None,
-                    created_by: txn_id,
-                    deleted_by: None,
-                    value: edge.clone(),
-                });
-        }
-
-        for (hyperedge_id, hyperedge) in &writes.hyperedges {
-            self.hyperedge_versions
-                .entry(hyperedge_id.clone())
-                .or_insert_with(Vec::new)
-                .push(Version {
-                    created_at: commit_time,
-                    deleted_at: None,
-                    created_by: txn_id,
-                    deleted_by: None,
-                    value: hyperedge.clone(),
-                });
-        }
-
-        // Mark deletes
-        for node_id in &writes.deleted_nodes {
-            if let Some(mut versions) = self.node_versions.get_mut(node_id) {
-                if let Some(last) = versions.last_mut() {
-                    last.deleted_at = Some(commit_time);
-                    last.deleted_by = Some(txn_id);
-                }
+    /// Timestamp of the latest visible commit
+    pub fn current_timestamp(&self) -> Timestamp {
+        self.clock.load(Ordering::SeqCst)
+    }
+
+    /// Number of transactions that are still active
+    pub fn active_count(&self) -> usize {
+        self.active_txns.len()
+    }
+
+    /// Number of record versions currently retained
+    pub fn version_count(&self) -> usize {
+        self.node_versions.iter().map(|c| c.len()).sum::<usize>()
+            + self.edge_versions.iter().map(|c| c.len()).sum::<usize>()
+            + self
+                .hyperedge_versions
+                .iter()
+                .map(|c| c.len())
+                .sum::<usize>()
+    }
+
+    /// Run `f` with commits held off
+    ///
+    /// Used for schema changes, which must see every committed write and
+    /// must not interleave with constraint checks.
+    pub fn exclusive<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _guard = self.commit_lock.lock();
+        f()
+    }
+
+    /// Commit a write set
+    ///
+    /// `txn_id` is the committing transaction, or `None` for a single
+    /// auto-committed change. Under the commit lock this checks Serializable
+    /// transactions for conflicts with the records they read or wrote, persists the changes with
+    /// `apply`, records the versions active snapshots need and finally makes
+    /// the commit visible. The transaction is finished whatever the outcome.
+    pub fn commit_writes(
+        &self,
+        txn_id: Option<TxnId>,
+        writes: &WriteSet,
+        store: Option<&dyn CommittedState>,
+        apply: impl FnOnce(&WriteSet) -> Result<()>,
+    ) -> Result<()> {
+        let result = self.commit_locked(txn_id, writes, store, apply);
+        if let Some(txn_id) = txn_id {
+            self.finish(txn_id);
+        }
+        result
+    }
+
+    fn commit_locked(
+        &self,
+        txn_id: Option<TxnId>,
+        writes: &WriteSet,
+        store: Option<&dyn CommittedState>,
+        apply: impl FnOnce(&WriteSet) -> Result<()>,
+    ) -> Result<()> {
+        let _guard = self.commit_lock.lock();
+
+        if let Some(txn_id) = txn_id {
+            let txn = self.active_txns.get(&txn_id).ok_or_else(|| {
+                GraphError::TransactionError(format!("Transaction {} is not active", txn_id))
+            })?;
+            // A read-only transaction serializes at its snapshot
+            if txn.isolation_level == IsolationLevel::Serializable && !writes.is_empty() {
+                self.check_conflicts(writes, &txn.reads, txn.start_time)?;
             }
         }
 
-        for edge_id in &writes.deleted_edges {
-            if let Some(mut versions) = self.edge_versions.get_mut(edge_id) {
-                if let Some(last) = versions.last_mut() {
-                    last.deleted_at = Some(commit_time);
-                    last.deleted_by = Some(txn_id);
-                }
+        // Other transactions may read the records being replaced
+        let record = store.is_none() || self.active_txns.iter().any(|t| Some(*t.key()) != txn_id);
+        if record {
+            if let Some(store) = store {
+                self.seed(writes, store);
             }
+        } else {
+            self.forget(writes);
         }
 
-        // Update transaction state
-        if let Some(mut metadata) = self.active_txns.get_mut(&txn_id) {
-            metadata.state = TxnState::Committed;
-            metadata.commit_time = Some(commit_time);
+        apply(writes)?;
+
+        let commit_time = self.clock.load(Ordering::SeqCst) + 1;
+        if record {
+            self.install(writes, commit_time);
         }
+        self.clock.store(commit_time, Ordering::SeqCst);
+        self.commits_since_gc.fetch_add(1, Ordering::Relaxed);
 
-        self.active_txns.remove(&txn_id);
-        self.committed_txns.insert(txn_id, commit_time);
+        Ok(())
+    }
 
+    /// Fail if another transaction committed a change to a read or written
+    /// record after `start_time`
+    fn check_conflicts(
+        &self,
+        writes: &WriteSet,
+        reads: &ReadSet,
+        start_time: Timestamp,
+    ) -> Result<()> {
+        fn conflicts<K: Eq + Hash, T>(
+            versions: &DashMap<K, VersionChain<T>>,
+            mut keys: impl Iterator<Item = K>,
+            start_time: Timestamp,
+        ) -> bool {
+            keys.any(|key| {
+                versions
+                    .get(&key)
+                    .is_some_and(|chain| modified_since(&chain, start_time))
+            })
+        }
+
+        let nodes = writes
+            .nodes
+            .keys()
+            .chain(&writes.deleted_nodes)
+            .chain(&reads.nodes)
+            .cloned();
+        let edges = writes
+            .edges
+            .keys()
+            .chain(&writes.deleted_edges)
+            .chain(&reads.edges)
+            .cloned();
+        let hyperedges = writes
+            .hyperedges
+            .keys()
+            .chain(&writes.deleted_hyperedges)
+            .chain(&reads.hyperedges)
+            .cloned();
+
+        if conflicts(&self.node_versions, nodes, start_time)
+            || conflicts(&self.edge_versions, edges, start_time)
+            || conflicts(&self.hyperedge_versions, hyperedges, start_time)
+        {
+            return Err(GraphError::TransactionConflict(
+                "a record read or written by this transaction was modified by a concurrent commit"
+                    .to_string(),
+            ));
+        }
         Ok(())
     }
 
-    /// Abort a transaction
-    fn abort(&self, txn_id: TxnId) -> Result<()> {
-        if let Some(mut metadata) = self.active_txns.get_mut(&txn_id) {
-            metadata.state = TxnState::Aborted;
+    /// Make sure every written record has a chain holding its current version
+    fn seed(&self, writes: &WriteSet, store: &dyn CommittedState) {
+        fn seed_chain<K: Eq + Hash + Clone, T>(
+            versions: &DashMap<K, VersionChain<T>>,
+            key: &K,
+            current: impl FnOnce() -> Option<T>,
+        ) {
+            versions.entry(key.clone()).or_insert_with(|| {
+                // Without a chain the record is unchanged since every active
+                // snapshot was taken, so its current version is visible to all
+                current()
+                    .map(|value| {
+                        vec![Version {
+                            created_at: 0,
+                            deleted_at: None,
+                            value,
+                        }]
+                    })
+                    .unwrap_or_default()
+            });
+        }
+
+        for id in writes.nodes.keys().chain(&writes.deleted_nodes) {
+            seed_chain(&self.node_versions, id, || store.committed_node(id));
+        }
+        for id in writes.edges.keys().chain(&writes.deleted_edges) {
+            seed_chain(&self.edge_versions, id, || store.committed_edge(id));
+        }
+        for id in writes.hyperedges.keys().chain(&writes.deleted_hyperedges) {
+            seed_chain(&self.hyperedge_versions, id, || {
+                store.committed_hyperedge(id)
+            });
+        }
+    }
+
+    /// Drop the history of written records that nobody else can read
+    fn forget(&self, writes: &WriteSet) {
+        for id in writes.nodes.keys().chain(&writes.deleted_nodes) {
+            self.node_versions.remove(id);
+        }
+        for id in writes.edges.keys().chain(&writes.deleted_edges) {
+            self.edge_versions.remove(id);
+        }
+        for id in writes.hyperedges.keys().chain(&writes.deleted_hyperedges) {
+            self.hyperedge_versions.remove(id);
+        }
+    }
+
+    /// Append the versions created by a commit
+    fn install(&self, writes: &WriteSet, commit_time: Timestamp) {
+        fn put<K: Eq + Hash + Clone, T: Clone>(
+            versions: &DashMap<K, VersionChain<T>>,
+            key: &K,
+            value: &T,
+            commit_time: Timestamp,
+        ) {
+            versions.entry(key.clone()).or_default().push(Version {
+                created_at: commit_time,
+                deleted_at: None,
+                value: value.clone(),
+            });
         }
+
+        fn delete<K: Eq + Hash, T>(
+            versions: &DashMap<K, VersionChain<T>>,
+            key: &K,
+            commit_time: Timestamp,
+        ) {
+            if let Some(mut chain) = versions.get_mut(key) {
+                if let Some(last) = chain.last_mut().filter(|v| v.deleted_at.is_none()) {
+                    last.deleted_at = Some(commit_time);
+                }
+            }
+        }
+
+        for id in &writes.deleted_nodes {
+            delete(&self.node_versions, id, commit_time);
+        }
+        for id in &writes.deleted_edges {
+            delete(&self.edge_versions, id, commit_time);
+        }
+        for id in &writes.deleted_hyperedges {
+            delete(&self.hyperedge_versions, id, commit_time);
+        }
+        for (id, node) in &writes.nodes {
+            put(&self.node_versions, id, node, commit_time);
+        }
+        for (id, edge) in &writes.edges {
+            put(&self.edge_versions, id, edge, commit_time);
+        }
+        for (id, hyperedge) in &writes.hyperedges {
+            put(&self.hyperedge_versions, id, hyperedge, commit_time);
+        }
+    }
+
+    /// Abort a transaction
+    fn abort(&self, txn_id: TxnId) {
+        self.finish(txn_id);
+    }
+
+    /// Forget a finished transaction and collect garbage when it is due
+    fn finish(&self, txn_id: TxnId) {
         self.active_txns.remove(&txn_id);
-        Ok(())
+        if self.active_txns.is_empty()
+            || self.commits_since_gc.load(Ordering::Relaxed) >= GC_INTERVAL
+        {
+            self.collect_garbage();
+        }
     }
 
-    /// Read a node with MVCC
-    fn read_node(&self, node_id: &NodeId, txn_id: TxnId, start_time: Timestamp) -> Option<Node> {
-        self.node_versions.get(node_id).and_then(|versions| {
-            versions
-                .iter()
-                .rev()
-                .find(|v| {
-                    v.created_at <= start_time
-                        && v.deleted_at.map_or(true, |d| d > start_time)
-                        && v.created_by != txn_id
-                })
-                .map(|v| v.value.clone())
-        })
+    /// Remove the versions no active transaction can read
+    ///
+    /// Returns the number of versions removed.
+    pub fn collect_garbage(&self) -> usize {
+        // Commits are excluded so that the horizon and the chains stay consistent
+        let _guard = self.commit_lock.lock();
+        self.commits_since_gc.store(0, Ordering::Relaxed);
+
+        let horizon = self
+            .active_txns
+            .iter()
+            .map(|t| t.start_time)
+            .min()
+            .unwrap_or_else(|| self.clock.load(Ordering::SeqCst));
+        let keep_latest = !self.history_only;
+
+        fn collect<K: Eq + Hash, T>(
+            versions: &DashMap<K, VersionChain<T>>,
+            horizon: Timestamp,
+            keep_latest: bool,
+        ) -> usize {
+            let mut removed = 0;
+            versions.retain(|_, chain| {
+                let (count, drop_chain) = prune(chain, horizon, keep_latest);
+                removed += count;
+                !drop_chain
+            });
+            removed
+        }
+
+        collect(&self.node_versions, horizon, keep_latest)
+            + collect(&self.edge_versions, horizon, keep_latest)
+            + collect(&self.hyperedge_versions, horizon, keep_latest)
     }
 
-    /// Read an edge with MVCC
-    fn read_edge(&self, edge_id: &EdgeId, txn_id: TxnId, start_time: Timestamp) -> Option<Edge> {
-        self.edge_versions.get(edge_id).and_then(|versions| {
-            versions
-                .iter()
-                .rev()
-                .find(|v| {
-                    v.created_at <= start_time
-                        && v.deleted_at.map_or(true, |d| d > start_time)
-                        && v.created_by != txn_id
-                })
-                .map(|v| v.value.clone())
-        })
+    /// Read a node as of `ts`, falling back to `current` for records without history
+    fn read_node(
+        &self,
+        node_id: &NodeId,
+        ts: Timestamp,
+        current: impl Fn() -> Option<Node>,
+    ) -> Option<Node> {
+        read_versioned(&self.node_versions, node_id, ts, current)
+    }
+
+    /// Read an edge as of `ts`, falling back to `current` for records without history
+    fn read_edge(
+        &self,
+        edge_id: &EdgeId,
+        ts: Timestamp,
+        current: impl Fn() -> Option<Edge>,
+    ) -> Option<Edge> {
+        read_versioned(&self.edge_versions, edge_id, ts, current)
+    }
+
+    /// Read a hyperedge as of `ts`, falling back to `current` for records without history
+    fn read_hyperedge(
+        &self,
+        hyperedge_id: &HyperedgeId,
+        ts: Timestamp,
+        current: impl Fn() -> Option<Hyperedge>,
+    ) -> Option<Hyperedge> {
+        read_versioned(&self.hyperedge_versions, hyperedge_id, ts, current)
     }
 }
 
-impl Clone for TransactionManager {
-    fn clone(&self) -> Self {
-        Self {
-            next_txn_id: AtomicU64::new(self.next_txn_id.load(Ordering::SeqCst)),
-            active_txns: Arc::clone(&self.active_txns),
-            committed_txns: Arc::clone(&self.committed_txns),
-            node_versions: Arc::clone(&self.node_versions),
-            edge_versions: Arc::clone(&self.edge_versions),
-            hyperedge_versions: Arc::clone(&self.hyperedge_versions),
-        }
+/// Read the version of a record visible at `ts`
+fn read_versioned<K: Eq + Hash, T: Clone>(
+    versions: &DashMap<K, VersionChain<T>>,
+    key: &K,
+    ts: Timestamp,
+    current: impl Fn() -> Option<T>,
+) -> Option<T> {
+    if let Some(chain) = versions.get(key) {
+        return visible(&chain, ts);
+    }
+
+    // Commits seed a record's chain before changing the store, so when the
+    // chain is still missing after reading the store, the value read predates
+    // every commit the snapshot must not see
+    let value = current();
+    match versions.get(key) {
+        Some(chain) => visible(&chain, ts),
+        None => value,
     }
 }
 
@@ -261,24 +555,92 @@ impl Default for TransactionManager {
     }
 }
 
-/// Write set for a transaction
+/// Changes buffered by a transaction
+///
+/// A record is either written or deleted, never both.
 #[derive(Debug, Clone, Default)]
-struct WriteSet {
-    nodes: HashMap<NodeId, Node>,
-    edges: HashMap<EdgeId, Edge>,
-    hyperedges: HashMap<HyperedgeId, Hyperedge>,
-    deleted_nodes: HashSet<NodeId>,
-    deleted_edges: HashSet<EdgeId>,
-    deleted_hyperedges: HashSet<HyperedgeId>,
+pub struct WriteSet {
+    pub nodes: HashMap<NodeId, Node>,
+    pub edges: HashMap<EdgeId, Edge>,
+    pub hyperedges: HashMap<HyperedgeId, Hyperedge>,
+    pub deleted_nodes: HashSet<NodeId>,
+    pub deleted_edges: HashSet<EdgeId>,
+    pub deleted_hyperedges: HashSet<HyperedgeId>,
 }
 
 impl WriteSet {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self::default()
     }
+
+    /// Whether the write set contains no changes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+            && self.edges.is_empty()
+            && self.hyperedges.is_empty()
+            && self.deleted_nodes.is_empty()
+            && self.deleted_edges.is_empty()
+            && self.deleted_hyperedges.is_empty()
+    }
+
+    pub fn put_node(&mut self, node: Node) {
+        self.deleted_nodes.remove(&node.id);
+        self.nodes.insert(node.id.clone(), node);
+    }
+
+    pub fn put_edge(&mut self, edge: Edge) {
+        self.deleted_edges.remove(&edge.id);
+        self.edges.insert(edge.id.clone(), edge);
+    }
+
+    pub fn put_hyperedge(&mut self, hyperedge: Hyperedge) {
+        self.deleted_hyperedges.remove(&hyperedge.id);
+        self.hyperedges.insert(hyperedge.id.clone(), hyperedge);
+    }
+
+    pub fn delete_node(&mut self, node_id: NodeId) {
+        self.nodes.remove(&node_id);
+        self.deleted_nodes.insert(node_id);
+    }
+
+    pub fn delete_edge(&mut self, edge_id: EdgeId) {
+        self.edges.remove(&edge_id);
+        self.deleted_edges.insert(edge_id);
+    }
+
+    pub fn delete_hyperedge(&mut self, hyperedge_id: HyperedgeId) {
+        self.hyperedges.remove(&hyperedge_id);
+        self.deleted_hyperedges.insert(hyperedge_id);
+    }
+
+    /// The buffered state of a node: `Some(None)` if deleted, `None` if untouched
+    pub fn node(&self, node_id: &NodeId) -> Option<Option<Node>> {
+        if self.deleted_nodes.contains(node_id) {
+            return Some(None);
+        }
+        self.nodes.get(node_id).map(|n| Some(n.clone()))
+    }
+
+    /// The buffered state of an edge: `Some(None)` if deleted, `None` if untouched
+    pub fn edge(&self, edge_id: &EdgeId) -> Option<Option<Edge>> {
+        if self.deleted_edges.contains(edge_id) {
+            return Some(None);
+        }
+        self.edges.get(edge_id).map(|e| Some(e.clone()))
+    }
+
+    /// The buffered state of a hyperedge: `Some(None)` if deleted, `None` if untouched
+    pub fn hyperedge(&self, hyperedge_id: &HyperedgeId) -> Option<Option<Hyperedge>> {
+        if self.deleted_hyperedges.contains(hyperedge_id) {
+            return Some(None);
+        }
+        self.hyperedges.get(hyperedge_id).map(|h| Some(h.clone()))
+    }
 }
 
 /// Transaction handle
+///
+/// Dropping an unfinished transaction rolls it back.
 pub struct Transaction {
     id: TxnId,
     manager: Arc<TransactionManager>,
@@ -286,6 +648,7 @@ pub struct Transaction {
     pub isolation_level: IsolationLevel,
     start_time: Timestamp,
     writes: Arc<RwLock<WriteSet>>,
+    finished: bool,
 }
 
 impl Transaction {
@@ -303,79 +666,164 @@ impl Transaction {
         self.id
     }
 
+    /// Timestamp of the snapshot taken when the transaction began
+    pub fn start_time(&self) -> Timestamp {
+        self.start_time
+    }
+
+    /// Timestamp reads are served at
+    fn read_time(&self) -> Timestamp {
+        if self.isolation_level.uses_snapshot() {
+            self.start_time
+        } else {
+            self.manager.current_timestamp()
+        }
+    }
+
+    /// Remember a committed record read, for validation at commit
+    fn track_read(&self, record: impl FnOnce(&mut ReadSet)) {
+        if self.isolation_level == IsolationLevel::Serializable {
+            if let Some(mut txn) = self.manager.active_txns.get_mut(&self.id) {
+                record(&mut txn.reads);
+            }
+        }
+    }
+
     /// Write a node (buffered until commit)
     pub fn write_node(&self, node: Node) {
-        let mut writes = self.writes.write();
-        writes.nodes.insert(node.id.clone(), node);
+        self.writes.write().put_node(node);
     }
 
     /// Write an edge (buffered until commit)
     pub fn write_edge(&self, edge: Edge) {
-        let mut writes = self.writes.write();
-        writes.edges.insert(edge.id.clone(), edge);
+        self.writes.write().put_edge(edge);
     }
 
     /// Write a hyperedge (buffered until commit)
     pub fn write_hyperedge(&self, hyperedge: Hyperedge) {
-        let mut writes = self.writes.write();
-        writes.hyperedges.insert(hyperedge.id.clone(), hyperedge);
+        self.writes.write().put_hyperedge(hyperedge);
     }
 
     /// Delete a node (buffered until commit)
     pub fn delete_node(&self, node_id: NodeId) {
-        let mut writes = self.writes.write();
-        writes.deleted_nodes.insert(node_id);
+        self.writes.write().delete_node(node_id);
     }
 
     /// Delete an edge (buffered until commit)
     pub fn delete_edge(&self, edge_id: EdgeId) {
-        let mut writes = self.writes.write();
-        writes.deleted_edges.insert(edge_id);
+        self.writes.write().delete_edge(edge_id);
+    }
+
+    /// Delete a hyperedge (buffered until commit)
+    pub fn delete_hyperedge(&self, hyperedge_id: HyperedgeId) {
+        self.writes.write().delete_hyperedge(hyperedge_id);
     }
 
     /// Read a node (with MVCC visibility)
     pub fn read_node(&self, node_id: &NodeId) -> Option<Node> {
-        // Check write set first
-        {
-            let writes = self.writes.read();
-            if writes.deleted_nodes.contains(node_id) {
-                return None;
-            }
-            if let Some(node) = writes.nodes.get(node_id) {
-                return Some(node.clone());
-            }
-        }
-
-        // Read from MVCC store
-        self.manager.read_node(node_id, self.id, self.start_time)
+        self.read_node_from(node_id, None)
     }
 
     /// Read an edge (with MVCC visibility)
     pub fn read_edge(&self, edge_id: &EdgeId) -> Option<Edge> {
-        // Check write set first
-        {
-            let writes = self.writes.read();
-            if writes.deleted_edges.contains(edge_id) {
-                return None;
-            }
-            if let Some(edge) = writes.edges.get(edge_id) {
-                return Some(edge.clone());
-            }
+        self.read_edge_from(edge_id, None)
+    }
+
+    /// Read a hyperedge (with MVCC visibility)
+    pub fn read_hyperedge(&self, hyperedge_id: &HyperedgeId) -> Option<Hyperedge> {
+        self.read_hyperedge_from(hyperedge_id, None)
+    }
+
+    /// Read a node, consulting `store` for records the manager keeps no history of
+    pub fn read_node_from(
+        &self,
+        node_id: &NodeId,
+        store: Option<&dyn CommittedState>,
+    ) -> Option<Node> {
+        if let Some(pending) = self.writes.read().node(node_id) {
+            return pending;
+        }
+        self.track_read(|reads| {
+            reads.nodes.insert(node_id.clone());
+        });
+        self.manager.read_node(node_id, self.read_time(), || {
+            store.and_then(|s| s.committed_node(node_id))
+        })
+    }
+
+    /// Read an edge, consulting `store` for records the manager keeps no history of
+    pub fn read_edge_from(
+        &self,
+        edge_id: &EdgeId,
+        store: Option<&dyn CommittedState>,
+    ) -> Option<Edge> {
+        if let Some(pending) = self.writes.read().edge(edge_id) {
+            return pending;
         }
+        self.track_read(|reads| {
+            reads.edges.insert(edge_id.clone());
+        });
+        self.manager.read_edge(edge_id, self.read_time(), || {
+            store.and_then(|s| s.committed_edge(edge_id))
+        })
+    }
+
+    /// Read a hyperedge, consulting `store` for records the manager keeps no history of
+    pub fn read_hyperedge_from(
+        &self,
+        hyperedge_id: &HyperedgeId,
+        store: Option<&dyn CommittedState>,
+    ) -> Option<Hyperedge> {
+        if let Some(pending) = self.writes.read().hyperedge(hyperedge_id) {
+            return pending;
+        }
+        self.track_read(|reads| {
+            reads.hyperedges.insert(hyperedge_id.clone());
+        });
+        self.manager
+            .read_hyperedge(hyperedge_id, self.read_time(), || {
+                store.and_then(|s| s.committed_hyperedge(hyperedge_id))
+            })
+    }
 
-        // Read from MVCC store
-        self.manager.read_edge(edge_id, self.id, self.start_time)
+    /// Snapshot of the buffered changes
+    pub fn write_set(&self) -> WriteSet {
+        self.writes.read().clone()
     }
 
     /// Commit the transaction
     pub fn commit(self) -> Result<()> {
-        let writes = self.writes.read();
-        self.manager.commit(self.id, &writes)
+        self.commit_to(None, |_| Ok(()))
+    }
+
+    /// Commit the transaction, persisting its changes to `store` with `apply`
+    ///
+    /// `apply` runs once the commit is known not to conflict; if it fails the
+    /// transaction is rolled back.
+    pub fn commit_to(
+        mut self,
+        store: Option<&dyn CommittedState>,
+        apply: impl FnOnce(&WriteSet) -> Result<()>,
+    ) -> Result<()> {
+        self.finished = true;
+        let writes = self.writes.read().clone();
+        self.manager
+            .commit_writes(Some(self.id), &writes, store, apply)
     }
 
     /// Rollback the transaction
-    pub fn rollback(self) -> Result<()> {
-        self.manager.abort(self.id)
+    pub fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        self.manager.abort(self.id);
+        Ok(())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.manager.abort(self.id);
+        }
     }
 }
 
@@ -436,4 +884,53 @@ mod tests {
         let txn3 = manager.begin(IsolationLevel::ReadCommitted);
         assert!(txn3.read_node(&node_id).is_some());
     }
+
+    #[test]
+    fn test_serializable_write_conflict() {
+        let manager = TransactionManager::new();
+        let node = NodeBuilder::new().id("n").property("v", 0i64).build();
+        let setup = manager.begin(IsolationLevel::Serializable);
+        setup.write_node(node.clone());
+        setup.commit().unwrap();
+
+        let txn1 = manager.begin(IsolationLevel::Serializable);
+        let txn2 = manager.begin(IsolationLevel::Serializable);
+        txn1.write_node(node.clone());
+        txn2.write_node(node);
+
+        txn1.commit().unwrap();
+        let err = txn2.commit().unwrap_err();
+        assert!(matches!(err, GraphError::TransactionConflict(_)));
+        assert_eq!(manager.active_count(), 0);
+    }
+
+    #[test]
+    fn test_garbage_collection_keeps_visible_versions() {
+        let manager = TransactionManager::new();
+        let id = "n".to_string();
+        let write = |value: i64| {
+            let txn = manager.begin(IsolationLevel::ReadCommitted);
+            txn.write_node(NodeBuilder::new().id("n").property("v", value).build());
+            txn.commit().unwrap();
+        };
+
+        write(1);
+        let reader = manager.begin(IsolationLevel::RepeatableRead);
+        write(2);
+        write(3);
+        assert_eq!(manager.version_count(), 3);
+
+        // The reader's snapshot still needs the first version
+        manager.collect_garbage();
+        assert_eq!(manager.version_count(), 3);
+        let seen = reader.read_node(&id).unwrap();
+        assert_eq!(seen.get_property("v"), Some(&1i64.into()));
+
+        // Once it finishes only the latest version remains
+        reader.commit().unwrap();
+        assert_eq!(manager.version_count(), 1);
+        let latest = manager.begin(IsolationLevel::ReadCommitted);
+        let node = latest.read_node(&id).unwrap();
+        assert_eq!(node.get_property("v"), Some(&3i64.into()));
+    }
 }
@@ -0,0 +1,263 @@
+//! Graph algorithm tests
+//!
+//! Runs the algorithms both directly on a projection and as `algo.*` Cypher
+//! procedures.
+
+mod common;
+
+use common::{names, run};
+use ruvector_graph::algorithms::{self, Direction, GraphProjection, ProjectionConfig};
+use ruvector_graph::{
+    EdgeBuilder, GraphDB, GraphError, NodeBuilder, Properties, PropertyValue, Value,
+};
+
+/// Road network with distances:
+/// a -2-> b -2-> d, a -5-> c -1-> d, c -3-> e, plus an isolated node z
+fn road_network() -> GraphDB {
+    let db = GraphDB::new();
+    let places = [
+        ("a", 0.0, 0.0),
+        ("b", 1.0, 1.0),
+        ("c", 1.0, -1.0),
+        ("d", 2.0, 0.0),
+        ("e", 3.0, -1.0),
+        ("z", 9.0, 9.0),
+    ];
+    for (id, x, y) in places {
+        db.create_node(
+            NodeBuilder::new()
+                .id(id)
+                .label("Place")
+                .property("name", id)
+                .property(
+                    "position",
+                    PropertyValue::List(vec![PropertyValue::Float(x), PropertyValue::Float(y)]),
+                )
+                .build(),
+        )
+        .unwrap();
+    }
+    let roads = [
+        ("ab", "a", "b", 2),
+        ("bd", "b", "d", 2),
+        ("ac", "a", "c", 5),
+        ("cd", "c", "d", 1),
+        ("ce", "c", "e", 3),
+    ];
+    for (id, from, to, km) in roads {
+        db.create_edge(
+            EdgeBuilder::new(from.to_string(), to.to_string(), "ROAD")
+                .id(id)
+                .property("km", km as i64)
+                .build(),
+        )
+        .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_projection_filters_and_weights() {
+    let db = road_network();
+    let config = ProjectionConfig {
+        weight_property: Some("km".to_string()),
+        ..Default::default()
+    };
+    let graph = GraphProjection::new(&db, &config);
+    assert_eq!(graph.node_count(), 6);
+    assert_eq!(graph.relationship_count(), 5);
+
+    let path = algorithms::dijkstra(&graph, "a", "d", Direction::Outgoing)
+        .unwrap()
+        .unwrap();
+    assert_eq!(path.nodes, vec!["a", "b", "d"]);
+    assert_eq!(path.cost, 4.0);
+
+    let components = algorithms::connected_components(&graph);
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[1], vec!["z"]);
+}
+
+#[test]
+fn test_call_dijkstra_returns_path() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "MATCH (a:Place {name: 'a'}), (e:Place {name: 'e'}) \
+         CALL algo.dijkstra(a, e, {weightProperty: 'km'}) YIELD path, cost \
+         RETURN nodes(path) AS route, cost",
+    );
+    assert_eq!(result.len(), 1);
+    assert_eq!(result.rows[0]["cost"], Value::Float(8.0));
+    let route: Vec<&str> = result.rows[0]["route"]
+        .as_list()
+        .unwrap()
+        .iter()
+        .map(|n| n.as_node().unwrap().id.as_str())
+        .collect();
+    assert_eq!(route, vec!["a", "c", "e"]);
+}
+
+#[test]
+fn test_call_astar_and_k_shortest_paths() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "CALL algo.astar('a', 'd', {weightProperty: 'km', positionProperty: 'position'}) \
+         YIELD path, cost RETURN length(path) AS hops, cost",
+    );
+    assert_eq!(result.rows[0]["hops"], Value::Integer(2));
+    assert_eq!(result.rows[0]["cost"], Value::Float(4.0));
+
+    let result = run(
+        &db,
+        "CALL algo.kShortestPaths('a', 'd', 3, {weightProperty: 'km'}) \
+         YIELD index, cost RETURN index, cost",
+    );
+    assert_eq!(
+        result.column("cost"),
+        vec![&Value::Float(4.0), &Value::Float(6.0)]
+    );
+}
+
+#[test]
+fn test_call_bfs_with_depth_limit() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "CALL algo.bfs('a', {maxDepth: 1}) YIELD node, depth RETURN node.name AS name, depth",
+    );
+    assert_eq!(names(&result, "name"), vec!["a", "b", "c"]);
+
+    let result = run(
+        &db,
+        "CALL algo.dfs('d', {direction: 'INCOMING'}) YIELD node RETURN node.name AS name",
+    );
+    assert_eq!(names(&result, "name"), vec!["d", "b", "a", "c"]);
+}
+
+#[test]
+fn test_call_pagerank_orders_by_score() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "CALL algo.pagerank({maxIterations: 50}) YIELD node, score \
+         RETURN node.name AS name, score ORDER BY score DESC LIMIT 2",
+    );
+    assert_eq!(names(&result, "name"), vec!["d", "e"]);
+
+    let result = run(
+        &db,
+        "MATCH (z:Place {name: 'z'}) CALL algo.personalizedPagerank([z]) YIELD node, score \
+         WHERE score > 0 RETURN node.name AS name",
+    );
+    assert_eq!(names(&result, "name"), vec!["z"]);
+}
+
+#[test]
+fn test_call_components_and_communities() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "CALL algo.connectedComponents() YIELD node, componentId \
+         RETURN componentId, count(node) AS size ORDER BY size DESC",
+    );
+    assert_eq!(
+        result.column("size"),
+        vec![&Value::Integer(5), &Value::Integer(1)]
+    );
+
+    let result = run(
+        &db,
+        "CALL algo.stronglyConnectedComponents() YIELD componentId \
+         RETURN count(DISTINCT componentId) AS components",
+    );
+    assert_eq!(result.rows[0]["components"], Value::Integer(6));
+
+    for procedure in ["algo.louvain", "algo.labelPropagation"] {
+        let result = run(
+            &db,
+            &format!(
+                "CALL {}() YIELD node, communityId \
+                 WITH communityId, collect(node.name) AS members \
+                 RETURN members ORDER BY size(members) DESC",
+                procedure
+            ),
+        );
+        // z is alone; everyone else is linked
+        let last = result.rows.last().unwrap();
+        assert_eq!(
+            last["members"],
+            Value::List(vec![Value::from("z")]),
+            "{}",
+            procedure
+        );
+    }
+}
+
+#[test]
+fn test_call_centrality_and_triangles() {
+    let db = road_network();
+    let result = run(
+        &db,
+        "CALL algo.betweenness({direction: 'BOTH'}) YIELD node, score \
+         RETURN node.name AS name ORDER BY score DESC LIMIT 1",
+    );
+    assert_eq!(names(&result, "name"), vec!["c"]);
+
+    let result = run(
+        &db,
+        "CALL algo.closeness({direction: 'BOTH'}) YIELD node, score \
+         WHERE node.name = 'z' RETURN score",
+    );
+    assert_eq!(result.rows[0]["score"], Value::Float(0.0));
+
+    let result = run(
+        &db,
+        "CALL algo.triangleCount() YIELD node, triangles \
+         WHERE triangles > 0 RETURN node.name AS name ORDER BY name",
+    );
+    assert!(result.is_empty());
+}
+
+#[test]
+fn test_call_similarity() {
+    let db = road_network();
+    // b and c share a and d
+    let result = run(
+        &db,
+        "CALL algo.jaccard('b', 'c') YIELD similarity RETURN similarity",
+    );
+    assert_eq!(result.rows[0]["similarity"], Value::Float(2.0 / 3.0));
+
+    let result = run(&db, "CALL algo.adamicAdar('b', 'c')");
+    assert_eq!(result.columns, vec!["similarity"]);
+
+    let result = run(
+        &db,
+        "CALL algo.similarNodes('b', {topK: 1}) YIELD node, similarity RETURN node.name AS name",
+    );
+    assert_eq!(names(&result, "name"), vec!["c"]);
+}
+
+#[test]
+fn test_call_errors() {
+    let db = road_network();
+    let params = Properties::new();
+    assert!(matches!(
+        db.execute("CALL algo.unknown()", &params),
+        Err(GraphError::InvalidQuery(_))
+    ));
+    assert!(matches!(
+        db.execute("CALL algo.pagerank() YIELD rank RETURN rank", &params),
+        Err(GraphError::InvalidQuery(_))
+    ));
+    assert!(matches!(
+        db.execute("CALL algo.pagerank({colour: 'red'})", &params),
+        Err(GraphError::CypherExecutionError(_))
+    ));
+    assert!(matches!(
+        db.execute("CALL algo.bfs('missing')", &params),
+        Err(GraphError::NodeNotFound(_))
+    ));
+}
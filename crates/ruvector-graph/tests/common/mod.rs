@@ -0,0 +1,22 @@
+//! Helpers shared by the integration tests
+
+use ruvector_graph::{GraphDB, Properties, QueryResult, Value};
+
+/// Execute `cypher` without parameters, panicking with the query on error
+pub fn run(db: &GraphDB, cypher: &str) -> QueryResult {
+    db.execute(cypher, &Properties::new())
+        .unwrap_or_else(|e| panic!("{}: {}", cypher, e))
+}
+
+/// The strings in a result column, with nulls as `"null"`
+#[allow(dead_code)] // not every test crate reads string columns
+pub fn names(result: &QueryResult, column: &str) -> Vec<String> {
+    result
+        .column(column)
+        .into_iter()
+        .map(|v| match v {
+            Value::Null => "null".to_string(),
+            v => v.as_str().expect("string column").to_string(),
+        })
+        .collect()
+}
@@ -3,7 +3,10 @@
 //! Tests to verify that RuVector graph database is compatible with Neo4j
 //! in terms of query syntax and result format.
 
-use ruvector_graph::{Edge, GraphDB, Label, Node, Properties, PropertyValue};
+mod common;
+
+use common::run;
+use ruvector_graph::{Edge, GraphDB, Label, Node, Properties, PropertyValue, Value};
 
 fn setup_movie_graph() -> GraphDB {
     let db = GraphDB::new();
@@ -135,102 +138,100 @@ fn setup_movie_graph() -> GraphDB {
 fn test_neo4j_match_all_nodes() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (n) RETURN n
-    // TODO: Implement query execution
-    // let results = db.execute("MATCH (n) RETURN n").unwrap();
-    // assert_eq!(results.len(), 4); // 3 people + 1 movie
-
-    // For now, verify graph setup
-    assert!(db.get_node("keanu").is_some());
-    assert!(db.get_node("matrix").is_some());
+    let results = run(&db, "MATCH (n) RETURN n");
+    assert_eq!(results.len(), 4); // 3 people + 1 movie
 }
 
 #[test]
 fn test_neo4j_match_with_label() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (p:Person) RETURN p
-    // TODO: Implement
-    // let results = db.execute("MATCH (p:Person) RETURN p").unwrap();
-    // assert_eq!(results.len(), 3);
-
-    // Verify label filtering would work
-    let keanu = db.get_node("keanu").unwrap();
-    assert_eq!(keanu.labels[0].name, "Person");
+    let results = run(&db, "MATCH (p:Person) RETURN p");
+    assert_eq!(results.len(), 3);
+    assert!(results
+        .iter()
+        .all(|row| row["p"].as_node().unwrap().has_label("Person")));
 }
 
 #[test]
 fn test_neo4j_match_with_properties() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (m:Movie {title: 'The Matrix'}) RETURN m
-    // TODO: Implement
-    // let results = db.execute("MATCH (m:Movie {title: 'The Matrix'}) RETURN m").unwrap();
-    // assert_eq!(results.len(), 1);
-
-    let matrix = db.get_node("matrix").unwrap();
-    assert_eq!(
-        matrix.properties.get("title"),
-        Some(&PropertyValue::String("The Matrix".to_string()))
-    );
+    let results = run(&db, "MATCH (m:Movie {title: 'The Matrix'}) RETURN m");
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["m"].as_node().unwrap().id, "matrix");
 }
 
 #[test]
 fn test_neo4j_match_relationship() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (a:Person)-[r:ACTED_IN]->(m:Movie) RETURN a, r, m
-    // TODO: Implement
-    // let results = db.execute("MATCH (a:Person)-[r:ACTED_IN]->(m:Movie) RETURN a, r, m").unwrap();
-    // assert_eq!(results.len(), 3);
-
-    let edge = db.get_edge("e1").unwrap();
-    assert_eq!(edge.edge_type, "ACTED_IN");
+    let results = run(
+        &db,
+        "MATCH (a:Person)-[r:ACTED_IN]->(m:Movie) RETURN a, r, m",
+    );
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.columns, vec!["a", "r", "m"]);
+    for row in &results {
+        let r = row["r"].as_relationship().unwrap();
+        assert_eq!(r.edge_type, "ACTED_IN");
+        assert_eq!(r.from, row["a"].as_node().unwrap().id);
+        assert_eq!(r.to, "matrix");
+    }
 }
 
 #[test]
 fn test_neo4j_where_clause() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (p:Person) WHERE p.born > 1965 RETURN p
-    // TODO: Implement
-    // let results = db.execute("MATCH (p:Person) WHERE p.born > 1965 RETURN p").unwrap();
-    // assert_eq!(results.len(), 1); // Only Carrie-Anne Moss
-
-    let carrie = db.get_node("carrie").unwrap();
-    if let Some(PropertyValue::Integer(born)) = carrie.properties.get("born") {
-        assert!(*born > 1965);
-    }
+    let results = run(&db, "MATCH (p:Person) WHERE p.born > 1965 RETURN p.name");
+    assert_eq!(results.len(), 1); // Only Carrie-Anne Moss
+    assert_eq!(results[0]["p.name"], Value::from("Carrie-Anne Moss"));
 }
 
 #[test]
 fn test_neo4j_count_aggregation() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (p:Person) RETURN COUNT(p)
-    // TODO: Implement
-    // let results = db.execute("MATCH (p:Person) RETURN COUNT(p)").unwrap();
-    // assert_eq!(results[0]["count"], 3);
-
-    // Manually verify
-    assert!(db.get_node("keanu").is_some());
-    assert!(db.get_node("carrie").is_some());
-    assert!(db.get_node("laurence").is_some());
+    let results = run(&db, "MATCH (p:Person) RETURN COUNT(p)");
+    assert_eq!(results.columns, vec!["count(p)"]);
+    assert_eq!(results[0]["count(p)"], Value::Integer(3));
 }
 
 #[test]
 fn test_neo4j_collect_aggregation() {
     let db = setup_movie_graph();
 
-    // Neo4j query: MATCH (p:Person)-[:ACTED_IN]->(m:Movie)
-    //              RETURN m.title, COLLECT(p.name) AS actors
-    // TODO: Implement
-    // let results = db.execute("...").unwrap();
+    let results = run(
+        &db,
+        "MATCH (p:Person)-[:ACTED_IN]->(m:Movie)
+         RETURN m.title, COLLECT(p.name) AS actors",
+    );
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["m.title"], Value::from("The Matrix"));
+
+    let mut actors: Vec<&str> = results[0]["actors"]
+        .as_list()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    actors.sort();
+    assert_eq!(
+        actors,
+        vec!["Carrie-Anne Moss", "Keanu Reeves", "Laurence Fishburne"]
+    );
+}
+
+#[test]
+fn test_neo4j_list_property_access() {
+    let db = setup_movie_graph();
 
-    // Verify relationships exist
-    assert!(db.get_edge("e1").is_some());
-    assert!(db.get_edge("e2").is_some());
-    assert!(db.get_edge("e3").is_some());
+    let results = run(
+        &db,
+        "MATCH (p:Person {name: 'Keanu Reeves'})-[r:ACTED_IN]->() RETURN r.roles AS roles",
+    );
+    assert_eq!(results[0]["roles"], Value::List(vec![Value::from("Neo")]));
 }
 
 // ============================================================================
@@ -2,7 +2,9 @@
 //!
 //! Tests for multi-threaded access, lock-free operations, and concurrent modifications.
 
-use ruvector_graph::{Edge, GraphDB, Label, Node, Properties, PropertyValue};
+use ruvector_graph::{
+    Edge, GraphDB, IsolationLevel, Label, Node, NodeBuilder, Properties, PropertyValue,
+};
 use std::sync::Arc;
 use std::thread;
 
@@ -394,3 +396,50 @@ fn test_concurrent_batch_operations() {
         handle.join().unwrap();
     }
 }
+
+#[test]
+fn test_readers_see_whole_commits() {
+    let db = Arc::new(GraphDB::new());
+    let pair = |id: &str, value: i64| {
+        NodeBuilder::new()
+            .id(id)
+            .label("Pair")
+            .property("value", value)
+            .build()
+    };
+    db.create_node(pair("a", 0)).unwrap();
+    db.create_node(pair("b", 0)).unwrap();
+
+    // Every commit moves both nodes of the pair to the next value
+    let writer = {
+        let db = Arc::clone(&db);
+        thread::spawn(move || {
+            for value in 1..=300 {
+                let tx = db.begin(IsolationLevel::ReadCommitted);
+                tx.update_node(pair("a", value)).unwrap();
+                tx.update_node(pair("b", value)).unwrap();
+                tx.commit().unwrap();
+            }
+        })
+    };
+
+    let values = |nodes: Vec<Node>| -> Vec<PropertyValue> {
+        nodes
+            .iter()
+            .map(|n| n.get_property("value").cloned().unwrap())
+            .collect()
+    };
+    while !writer.is_finished() {
+        let by_label = values(db.get_nodes_by_label("Pair"));
+        assert_eq!(by_label.len(), 2);
+        assert_eq!(by_label[0], by_label[1]);
+
+        let result = db
+            .execute("MATCH (n:Pair) RETURN n.value AS v", &Properties::new())
+            .unwrap();
+        let queried: Vec<_> = result.column("v").into_iter().cloned().collect();
+        assert_eq!(queried.len(), 2);
+        assert_eq!(queried[0], queried[1]);
+    }
+    writer.join().unwrap();
+}
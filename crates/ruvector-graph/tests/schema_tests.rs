@@ -0,0 +1,360 @@
+//! Schema DDL tests
+//!
+//! Covers CREATE/DROP INDEX and CONSTRAINT, SHOW INDEXES, constraint checks
+//! on commit, the planner's use of range and full-text indexes, and schema
+//! persistence.
+
+mod common;
+
+use common::{names, run};
+use ruvector_graph::{
+    GraphDB, GraphError, IndexKind, IsolationLevel, NodeBuilder, Properties, Value,
+};
+
+/// People p0..p9 aged 20..29 with names `person<i>`, plus bios
+fn people() -> GraphDB {
+    let db = GraphDB::new();
+    for i in 0..10 {
+        db.create_node(
+            NodeBuilder::new()
+                .id(format!("p{}", i))
+                .label("Person")
+                .property("name", format!("person{}", i))
+                .property("age", 20 + i as i64)
+                .property(
+                    "bio",
+                    if i % 2 == 0 {
+                        "Enjoys graph databases"
+                    } else {
+                        "Writes Rust compilers"
+                    },
+                )
+                .build(),
+        )
+        .unwrap();
+    }
+    db
+}
+
+#[test]
+fn test_create_show_and_drop_indexes() {
+    let db = people();
+    let result = run(&db, "CREATE INDEX person_age FOR (p:Person) ON (p.age)");
+    assert_eq!(result.stats.indexes_added, 1);
+    run(
+        &db,
+        "CREATE FULLTEXT INDEX bios FOR (p:Person) ON EACH [p.bio]",
+    );
+    run(
+        &db,
+        "CREATE CONSTRAINT FOR (p:Person) REQUIRE p.name IS UNIQUE",
+    );
+
+    let indexes = run(&db, "SHOW INDEXES");
+    assert_eq!(
+        names(&indexes, "name"),
+        vec!["bios", "person_age", "unique_Person_name"]
+    );
+    assert_eq!(names(&indexes, "type"), vec!["FULLTEXT", "RANGE", "RANGE"]);
+    assert_eq!(
+        indexes.rows[2]["owningConstraint"],
+        Value::from("unique_Person_name")
+    );
+    assert_eq!(db.schema().index_size("person_age"), Some(10));
+
+    let constraints = run(&db, "SHOW CONSTRAINTS");
+    assert_eq!(names(&constraints, "type"), vec!["UNIQUENESS"]);
+
+    // Same name or same definition
+    assert!(matches!(
+        db.execute(
+            "CREATE INDEX person_age FOR (p:Person) ON (p.name)",
+            &Properties::new()
+        ),
+        Err(GraphError::IndexError(_))
+    ));
+    let result = run(
+        &db,
+        "CREATE INDEX other IF NOT EXISTS FOR (p:Person) ON (p.age)",
+    );
+    assert_eq!(result.stats.indexes_added, 0);
+
+    // A constraint's index goes with the constraint
+    assert!(matches!(
+        db.execute("DROP INDEX unique_Person_name", &Properties::new()),
+        Err(GraphError::IndexError(_))
+    ));
+    assert_eq!(
+        run(&db, "DROP CONSTRAINT unique_Person_name")
+            .stats
+            .constraints_removed,
+        1
+    );
+    run(&db, "DROP INDEX person_age");
+    run(&db, "DROP INDEX person_age IF EXISTS");
+    assert!(matches!(
+        db.execute("DROP INDEX person_age", &Properties::new()),
+        Err(GraphError::IndexError(_))
+    ));
+    assert_eq!(names(&run(&db, "SHOW INDEXES"), "name"), vec!["bios"]);
+}
+
+#[test]
+fn test_unique_constraint() {
+    let db = people();
+    run(
+        &db,
+        "CREATE CONSTRAINT person_name FOR (p:Person) REQUIRE p.name IS UNIQUE",
+    );
+
+    assert!(matches!(
+        db.execute("CREATE (:Person {name: 'person3'})", &Properties::new()),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+
+    // Duplicates within one commit are caught too, and nothing is written
+    let tx = db.begin(IsolationLevel::Serializable);
+    for _ in 0..2 {
+        tx.create_node(
+            NodeBuilder::new()
+                .label("Person")
+                .property("name", "new")
+                .build(),
+        );
+    }
+    assert!(matches!(
+        tx.commit(),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+    assert_eq!(db.count_nodes_by_label("Person"), 10);
+
+    // Other labels and missing values are not constrained
+    run(&db, "CREATE (:Robot {name: 'person3'}), (:Person)");
+
+    // Renaming frees the old value within the same transaction
+    let tx = db.begin(IsolationLevel::Serializable);
+    let mut p3 = tx.get_node("p3").unwrap();
+    p3.set_property("name", "renamed".into());
+    tx.update_node(p3).unwrap();
+    tx.create_node(
+        NodeBuilder::new()
+            .label("Person")
+            .property("name", "person3")
+            .build(),
+    );
+    tx.commit().unwrap();
+
+    // Existing duplicates block the constraint
+    assert!(matches!(
+        db.execute(
+            "CREATE CONSTRAINT FOR (p:Person) REQUIRE p.bio IS UNIQUE",
+            &Properties::new()
+        ),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+}
+
+#[test]
+fn test_not_null_constraint() {
+    let db = people();
+    let result = run(
+        &db,
+        "CREATE CONSTRAINT FOR (p:Person) REQUIRE p.age IS NOT NULL",
+    );
+    assert_eq!(result.stats.constraints_added, 1);
+
+    assert!(matches!(
+        db.execute("CREATE (:Person {name: 'ageless'})", &Properties::new()),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+    assert!(matches!(
+        db.execute(
+            "MATCH (p:Person {name: 'person1'}) REMOVE p.age",
+            &Properties::new()
+        ),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+    assert!(matches!(
+        db.execute(
+            "MATCH (p:Person {name: 'person1'}) SET p.age = null",
+            &Properties::new()
+        ),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+    assert!(db.get_node("p1").unwrap().get_property("age").is_some());
+}
+
+#[test]
+fn test_planner_uses_range_index() {
+    let db = people();
+    run(&db, "CREATE INDEX person_age FOR (p:Person) ON (p.age)");
+    run(&db, "CREATE INDEX person_name FOR (p:Person) ON (p.name)");
+
+    let plan = db
+        .explain("MATCH (p:Person) WHERE p.age >= 25 AND p.age < 28 RETURN p.name")
+        .unwrap()
+        .to_string();
+    assert!(
+        plan.contains("range index person_age (age >= 25 AND age < 28)"),
+        "{}",
+        plan
+    );
+    let result = run(
+        &db,
+        "MATCH (p:Person) WHERE 25 <= p.age AND p.age < 28 RETURN p.name AS name",
+    );
+    assert_eq!(
+        names(&result, "name"),
+        vec!["person5", "person6", "person7"]
+    );
+
+    let plan = db
+        .explain("MATCH (p:Person) WHERE p.name STARTS WITH 'person1' RETURN p")
+        .unwrap()
+        .to_string();
+    assert!(plan.contains("range index person_name"), "{}", plan);
+    let result = run(
+        &db,
+        "MATCH (p:Person) WHERE p.name STARTS WITH 'person1' RETURN p.age AS age",
+    );
+    assert_eq!(result.rows[0]["age"], Value::Integer(21));
+
+    let plan = db
+        .explain("MATCH (p:Person {age: 23}) RETURN p")
+        .unwrap()
+        .to_string();
+    assert!(
+        plan.contains("range index person_age (age = 23)"),
+        "{}",
+        plan
+    );
+    let result = run(&db, "MATCH (p:Person {age: 23.0}) RETURN p.name AS name");
+    assert_eq!(names(&result, "name"), vec!["person3"]);
+
+    // Comparisons across types are never true
+    assert!(run(&db, "MATCH (p:Person) WHERE p.age > 'a' RETURN p").is_empty());
+}
+
+#[test]
+fn test_merge_uses_unique_index() {
+    let db = people();
+    run(
+        &db,
+        "CREATE CONSTRAINT person_name FOR (p:Person) REQUIRE p.name IS UNIQUE",
+    );
+    let plan = db
+        .explain("MERGE (p:Person {name: 'person4'}) RETURN p")
+        .unwrap()
+        .to_string();
+    assert!(plan.contains("range index person_name"), "{}", plan);
+
+    let result = run(
+        &db,
+        "MERGE (p:Person {name: 'person4'}) ON MATCH SET p.seen = true RETURN p.age AS age",
+    );
+    assert_eq!(result.rows[0]["age"], Value::Integer(24));
+    assert_eq!(result.stats.nodes_created, 0);
+
+    let result = run(&db, "MERGE (p:Person {name: 'person10'}) RETURN p");
+    assert_eq!(result.stats.nodes_created, 1);
+    assert_eq!(db.schema().index_size("person_name"), Some(11));
+}
+
+#[test]
+fn test_full_text_index() {
+    let db = people();
+    run(&db, "CREATE TEXT INDEX bios FOR (p:Person) ON (p.bio)");
+
+    let plan = db
+        .explain("MATCH (p:Person) WHERE p.bio CONTAINS 'Rust' RETURN p")
+        .unwrap()
+        .to_string();
+    assert!(
+        plan.contains("full-text index bios (bio CONTAINS 'Rust')"),
+        "{}",
+        plan
+    );
+    assert_eq!(
+        run(&db, "MATCH (p:Person) WHERE p.bio CONTAINS 'Rust' RETURN p").len(),
+        5
+    );
+    // Case-sensitive like CONTAINS itself, and short needles still work
+    assert!(run(&db, "MATCH (p:Person) WHERE p.bio CONTAINS 'rust' RETURN p").is_empty());
+    assert_eq!(
+        run(&db, "MATCH (p:Person) WHERE p.bio CONTAINS 'gr' RETURN p").len(),
+        5
+    );
+
+    let result = run(
+        &db,
+        "CALL db.index.fulltext.queryNodes('bios', 'graph compilers') YIELD node, score \
+         RETURN node.name AS name, score ORDER BY score DESC, name LIMIT 2",
+    );
+    assert_eq!(names(&result, "name"), vec!["person0", "person1"]);
+    assert_eq!(result.rows[0]["score"], Value::Float(0.5));
+
+    assert!(matches!(
+        db.execute(
+            "CALL db.index.fulltext.queryNodes('missing', 'x') YIELD node RETURN node",
+            &Properties::new()
+        ),
+        Err(GraphError::IndexError(_))
+    ));
+}
+
+#[test]
+fn test_schema_api() {
+    let db = people();
+    db.create_index(ruvector_graph::IndexDefinition::range(
+        "by_age", "Person", "age",
+    ))
+    .unwrap();
+    assert_eq!(
+        db.schema().find_index(IndexKind::Range, "Person", "age"),
+        Some("by_age".to_string())
+    );
+    assert!(db.drop_index("by_age").unwrap());
+    assert!(!db.drop_index("by_age").unwrap());
+
+    // Schema commands stand alone
+    assert!(matches!(
+        db.execute(
+            "MATCH (n) CREATE INDEX FOR (p:Person) ON (p.age)",
+            &Properties::new()
+        ),
+        Err(GraphError::CypherParseError(_) | GraphError::InvalidQuery(_))
+    ));
+}
+
+#[cfg(feature = "storage")]
+#[test]
+fn test_schema_persists() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graph.db");
+
+    {
+        let db = GraphDB::with_storage(&path).unwrap();
+        run(&db, "CREATE (:Person {name: 'a', age: 1})");
+        run(&db, "CREATE INDEX person_age FOR (p:Person) ON (p.age)");
+        run(
+            &db,
+            "CREATE FULLTEXT INDEX dropped FOR (p:Person) ON (p.name)",
+        );
+        run(&db, "DROP INDEX dropped");
+        run(
+            &db,
+            "CREATE CONSTRAINT person_name FOR (p:Person) REQUIRE p.name IS UNIQUE",
+        );
+    }
+
+    let db = GraphDB::with_storage(&path).unwrap();
+    assert_eq!(
+        names(&run(&db, "SHOW INDEXES"), "name"),
+        vec!["person_age", "person_name"]
+    );
+    assert_eq!(db.schema().index_size("person_age"), Some(1));
+    assert!(matches!(
+        db.execute("CREATE (:Person {name: 'a'})", &Properties::new()),
+        Err(GraphError::ConstraintViolation(_))
+    ));
+}
@@ -5,7 +5,9 @@
 use ruvector_graph::edge::EdgeBuilder;
 use ruvector_graph::node::NodeBuilder;
 use ruvector_graph::transaction::{IsolationLevel, Transaction, TransactionManager};
-use ruvector_graph::{GraphDB, Label, Node, Properties, PropertyValue};
+use ruvector_graph::{
+    GraphDB, GraphError, GraphTransaction, Label, Node, Properties, PropertyValue,
+};
 use std::sync::Arc;
 use std::thread;
 
@@ -13,69 +15,87 @@ use std::thread;
 // Atomicity Tests
 // ============================================================================
 
+fn counter_value(node: &Node) -> i64 {
+    match node.get_property("value") {
+        Some(PropertyValue::Integer(val)) => *val,
+        _ => 0,
+    }
+}
+
 #[test]
 fn test_transaction_commit() {
-    let _db = GraphDB::new();
+    let db = GraphDB::new();
 
-    let tx = Transaction::begin(IsolationLevel::ReadCommitted).unwrap();
+    let tx = db.begin(IsolationLevel::ReadCommitted);
+    tx.create_node(NodeBuilder::new().id("a").label("Person").build());
+    tx.create_node(NodeBuilder::new().id("b").label("Person").build());
+    tx.create_edge(EdgeBuilder::new("a".to_string(), "b".to_string(), "KNOWS").build())
+        .unwrap();
 
-    // TODO: Implement transaction operations
-    // tx.create_node(...)?;
-    // tx.create_edge(...)?;
+    // Nothing is visible outside the transaction before commit
+    assert!(db.get_node("a").is_none());
+    assert!(tx.get_node("a").is_some());
 
     let result = tx.commit();
     assert!(result.is_ok());
+
+    assert_eq!(db.node_count(), 2);
+    assert_eq!(db.edge_count(), 1);
+    assert_eq!(db.get_outgoing_edges(&"a".to_string()).len(), 1);
 }
 
 #[test]
 fn test_transaction_rollback() {
-    let _db = GraphDB::new();
-
-    let tx = Transaction::begin(IsolationLevel::ReadCommitted).unwrap();
+    let db = GraphDB::new();
 
-    // TODO: Implement transaction operations
-    // tx.create_node(...)?;
+    let tx = db.begin(IsolationLevel::ReadCommitted);
+    tx.create_node(NodeBuilder::new().id("a").build());
 
     let result = tx.rollback();
     assert!(result.is_ok());
 
-    // TODO: Verify that changes were not applied
+    assert!(db.get_node("a").is_none());
+    assert_eq!(db.node_count(), 0);
+
+    // Dropping an uncommitted transaction also discards its writes
+    {
+        let tx = db.begin(IsolationLevel::ReadCommitted);
+        tx.create_node(NodeBuilder::new().id("b").build());
+    }
+    assert!(db.get_node("b").is_none());
 }
 
 #[test]
 fn test_transaction_atomic_batch_insert() {
     let db = GraphDB::new();
 
-    // TODO: Implement transactional batch insert
     // Either all nodes are created or none
-    /*
-    let tx = db.begin_transaction(IsolationLevel::Serializable)?;
-
+    let tx = db.begin(IsolationLevel::Serializable);
     for i in 0..100 {
-        tx.create_node(Node::new(
-            format!("node_{}", i),
-            vec![],
-            Properties::new(),
-        ))?;
+        tx.create_node(Node::new(format!("node_{}", i), vec![], Properties::new()));
 
         if i == 50 {
             // Simulate error
-            tx.rollback()?;
+            tx.rollback().unwrap();
             break;
         }
     }
 
     // Verify no nodes were created
     assert!(db.get_node("node_0").is_none());
-    */
 
-    // For now, just create without transaction
-    for i in 0..10 {
-        db.create_node(Node::new(format!("node_{}", i), vec![], Properties::new()))
-            .unwrap();
+    let tx = db.begin(IsolationLevel::Serializable);
+    for i in 0..100 {
+        tx.create_node(Node::new(format!("node_{}", i), vec![], Properties::new()));
     }
-
-    assert!(db.get_node("node_0").is_some());
+    // An edge to a missing node fails validation and aborts the whole commit
+    tx.create_node(NodeBuilder::new().id("doomed").build());
+    tx.create_edge(EdgeBuilder::new("node_0".to_string(), "doomed".to_string(), "TO").build())
+        .unwrap();
+    tx.delete_node("doomed");
+    assert!(tx.commit().is_err());
+    assert_eq!(db.node_count(), 0);
+    assert_eq!(db.edge_count(), 0);
 }
 
 #[test]
@@ -163,15 +183,24 @@ fn test_concurrent_transactions_read_committed() {
     ))
     .unwrap();
 
-    // TODO: Implement transactional updates
     // Spawn multiple threads that increment the counter
     let handles: Vec<_> = (0..10)
         .map(|_| {
             let db_clone = Arc::clone(&db);
-            thread::spawn(move || {
-                // TODO: Begin transaction, read counter, increment, commit
-                // For now, just read
-                let _node = db_clone.get_node("counter");
+            thread::spawn(move || loop {
+                let tx = db_clone.begin(IsolationLevel::Serializable);
+                let mut node = tx.get_node("counter").unwrap();
+                let value = match node.get_property("counter") {
+                    Some(PropertyValue::Integer(val)) => *val,
+                    _ => 0,
+                };
+                node.set_property("counter", PropertyValue::Integer(value + 1));
+                tx.update_node(node).unwrap();
+                match tx.commit() {
+                    Ok(()) => break,
+                    Err(GraphError::TransactionConflict(_)) => continue,
+                    Err(e) => panic!("unexpected error: {}", e),
+                }
             })
         })
         .collect();
@@ -180,7 +209,11 @@ fn test_concurrent_transactions_read_committed() {
         handle.join().unwrap();
     }
 
-    // TODO: Verify final counter value
+    let node = db.get_node("counter").unwrap();
+    assert_eq!(
+        node.get_property("counter"),
+        Some(&PropertyValue::Integer(10))
+    );
 }
 
 #[test]
@@ -505,8 +538,32 @@ fn test_mvcc_snapshot_isolation() {
 
 #[test]
 fn test_mvcc_concurrent_reads_and_writes() {
-    // TODO: Implement
-    // Verify that readers don't block writers and vice versa
+    let db = GraphDB::new();
+    db.create_node(NodeBuilder::new().id("n").property("value", 1i64).build())
+        .unwrap();
+
+    let snapshot = db.begin(IsolationLevel::RepeatableRead);
+    let latest = db.begin(IsolationLevel::ReadCommitted);
+    assert_eq!(counter_value(&snapshot.get_node("n").unwrap()), 1);
+
+    // A writer commits while both readers are open
+    db.update_node(NodeBuilder::new().id("n").property("value", 2i64).build())
+        .unwrap();
+    db.delete_node("n").unwrap();
+    db.create_node(NodeBuilder::new().id("m").build()).unwrap();
+
+    // The snapshot reader keeps its view, the read-committed one follows commits
+    assert_eq!(counter_value(&snapshot.get_node("n").unwrap()), 1);
+    assert!(snapshot.get_node("m").is_none());
+    assert!(latest.get_node("n").is_none());
+    assert!(latest.get_node("m").is_some());
+
+    snapshot.commit().unwrap();
+    latest.commit().unwrap();
+
+    // With no readers left the old versions are collected
+    assert_eq!(db.collect_garbage(), 0);
+    assert!(db.get_node("n").is_none());
 }
 
 // ============================================================================
@@ -515,9 +572,49 @@ fn test_mvcc_concurrent_reads_and_writes() {
 
 #[test]
 fn test_write_skew_detection() {
-    // TODO: Implement
-    // Classic write skew scenario: two transactions read overlapping data
-    // and make decisions based on what they read, leading to inconsistency
+    // Two doctors are on call; each goes off call if the other still is
+    let on_call = |id: &str, value: bool| {
+        NodeBuilder::new()
+            .id(id)
+            .label("Doctor")
+            .property("on_call", value)
+            .build()
+    };
+    let go_off_call = |tx: &GraphTransaction, me: &str, other: &str| {
+        let other = tx.get_node(other).unwrap();
+        if other.get_property("on_call") == Some(&PropertyValue::Boolean(true)) {
+            tx.update_node(on_call(me, false)).unwrap();
+        }
+    };
+
+    for (isolation, skew_allowed) in [
+        (IsolationLevel::RepeatableRead, true),
+        (IsolationLevel::Serializable, false),
+    ] {
+        let db = GraphDB::new();
+        db.create_node(on_call("alice", true)).unwrap();
+        db.create_node(on_call("bob", true)).unwrap();
+
+        let tx1 = db.begin(isolation);
+        let tx2 = db.begin(isolation);
+        go_off_call(&tx1, "alice", "bob");
+        go_off_call(&tx2, "bob", "alice");
+
+        tx1.commit().unwrap();
+        let result = tx2.commit();
+        let still_on_call = db
+            .get_nodes_by_property("on_call", &PropertyValue::Boolean(true))
+            .len();
+
+        // Snapshot isolation lets both commit, leaving nobody on call
+        if skew_allowed {
+            result.unwrap();
+            assert_eq!(still_on_call, 0);
+        } else {
+            assert!(matches!(result, Err(GraphError::TransactionConflict(_))));
+            assert_eq!(still_on_call, 1);
+        }
+    }
 }
 
 // ============================================================================
@@ -646,8 +743,40 @@ fn test_unique_constraint_enforcement() {
 
 #[test]
 fn test_index_consistency() {
-    // TODO: Implement
-    // Verify that indexes remain consistent after transaction commit/rollback
+    let db = GraphDB::new();
+    db.create_node(NodeBuilder::new().id("a").label("Person").build())
+        .unwrap();
+
+    // Rolled back changes never reach the indexes
+    let tx = db.begin(IsolationLevel::ReadCommitted);
+    tx.create_node(NodeBuilder::new().id("b").label("Person").build());
+    tx.rollback().unwrap();
+    assert_eq!(db.get_nodes_by_label("Person").len(), 1);
+
+    // Committed relabels and deletes replace the old index entries
+    let tx = db.begin(IsolationLevel::ReadCommitted);
+    let mut a = tx.get_node("a").unwrap();
+    a.labels.clear();
+    a.add_label("Employee");
+    tx.update_node(a).unwrap();
+    tx.create_node(NodeBuilder::new().id("c").label("Person").build());
+    tx.create_edge(EdgeBuilder::new("a".to_string(), "c".to_string(), "MANAGES").build())
+        .unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(db.get_nodes_by_label("Employee").len(), 1);
+    assert_eq!(db.get_nodes_by_label("Person").len(), 1);
+    assert_eq!(db.get_edges_by_type("MANAGES").len(), 1);
+
+    let tx = db.begin(IsolationLevel::ReadCommitted);
+    let edge_id = db.get_edges_by_type("MANAGES")[0].id.clone();
+    assert!(tx.delete_edge(&edge_id));
+    assert!(tx.delete_node("c"));
+    tx.commit().unwrap();
+
+    assert!(db.get_nodes_by_label("Person").is_empty());
+    assert!(db.get_edges_by_type("MANAGES").is_empty());
+    assert!(db.get_outgoing_edges(&"a".to_string()).is_empty());
 }
 
 // ============================================================================
@@ -696,8 +825,30 @@ fn test_write_ahead_log() {
 
 #[test]
 fn test_crash_recovery() {
-    // TODO: Implement
-    // Simulate crash and verify that committed transactions are preserved
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("graph.db");
+
+    {
+        let db = GraphDB::with_storage(&path).unwrap();
+
+        let tx = db.begin(IsolationLevel::Serializable);
+        tx.create_node(NodeBuilder::new().id("a").build());
+        tx.create_node(NodeBuilder::new().id("b").build());
+        tx.create_edge(EdgeBuilder::new("a".to_string(), "b".to_string(), "KNOWS").build())
+            .unwrap();
+        tx.commit().unwrap();
+
+        // The process stops before this transaction commits
+        let tx = db.begin(IsolationLevel::Serializable);
+        tx.create_node(NodeBuilder::new().id("uncommitted").build());
+        std::mem::forget(tx);
+    }
+
+    let db = GraphDB::with_storage(&path).unwrap();
+    assert!(db.get_node("a").is_some());
+    assert!(db.get_node("b").is_some());
+    assert!(db.get_node("uncommitted").is_none());
+    assert_eq!(db.edge_count(), 1);
 }
 
 #[test]
@@ -728,69 +879,43 @@ fn test_lost_update_prevention() {
     tx_init.write_node(node);
     tx_init.commit().unwrap();
 
-    // Two transactions both try to increment the counter
-    let manager_clone1 = Arc::clone(&manager);
-    let handle1 = thread::spawn(move || {
-        let tx1 = manager_clone1.begin(IsolationLevel::Serializable);
-
-        // Read current value
-        let node = tx1.read_node(&"counter".to_string()).unwrap();
-        let current_value = if let Some(PropertyValue::Integer(val)) = node.get_property("value") {
-            *val
-        } else {
-            0
-        };
-
-        thread::sleep(std::time::Duration::from_millis(50));
-
-        // Increment and write back
-        let mut updated_node = node.clone();
-        updated_node.set_property("value", PropertyValue::Integer(current_value + 1));
-        tx1.write_node(updated_node);
-
-        tx1.commit().unwrap();
-    });
-
-    let manager_clone2 = Arc::clone(&manager);
-    let handle2 = thread::spawn(move || {
-        thread::sleep(std::time::Duration::from_millis(10));
-
-        let tx2 = manager_clone2.begin(IsolationLevel::Serializable);
-
-        // Read current value
-        let node = tx2.read_node(&"counter".to_string()).unwrap();
-        let current_value = if let Some(PropertyValue::Integer(val)) = node.get_property("value") {
-            *val
-        } else {
-            0
-        };
-
-        thread::sleep(std::time::Duration::from_millis(50));
-
-        // Increment and write back
-        let mut updated_node = node.clone();
-        updated_node.set_property("value", PropertyValue::Integer(current_value + 1));
-        tx2.write_node(updated_node);
-
-        tx2.commit().unwrap();
-    });
+    // Two transactions both try to increment the counter; the second
+    // committer hits a write-write conflict and retries
+    let increment = |manager: Arc<TransactionManager>, delay: u64| {
+        thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(delay));
+            loop {
+                let tx = manager.begin(IsolationLevel::Serializable);
+
+                // Read current value
+                let node = tx.read_node(&"counter".to_string()).unwrap();
+                let current_value = counter_value(&node);
+
+                thread::sleep(std::time::Duration::from_millis(50));
+
+                // Increment and write back
+                let mut updated_node = node.clone();
+                updated_node.set_property("value", PropertyValue::Integer(current_value + 1));
+                tx.write_node(updated_node);
+
+                match tx.commit() {
+                    Ok(()) => break,
+                    Err(GraphError::TransactionConflict(_)) => continue,
+                    Err(e) => panic!("unexpected error: {}", e),
+                }
+            }
+        })
+    };
 
+    let handle1 = increment(Arc::clone(&manager), 0);
+    let handle2 = increment(Arc::clone(&manager), 10);
     handle1.join().unwrap();
     handle2.join().unwrap();
 
-    // Verify final value - with proper serializable isolation,
-    // both increments should be preserved (value should be 2)
+    // With serializable isolation both increments are preserved
     let tx_verify = manager.begin(IsolationLevel::ReadCommitted);
     let final_node = tx_verify.read_node(&"counter".to_string()).unwrap();
-    let final_value = if let Some(PropertyValue::Integer(val)) = final_node.get_property("value") {
-        *val
-    } else {
-        0
-    };
-
-    // With MVCC and proper isolation, both writes succeed independently
-    // The last committed transaction's value wins (value = 1 from one of them)
-    assert!(final_value >= 1);
+    assert_eq!(counter_value(&final_node), 2);
     tx_verify.commit().unwrap();
 }
 
@@ -0,0 +1,239 @@
+//! Vector search from Cypher
+//!
+//! Combines `db.index.vector.queryNodes` and `vector.similarity` with graph
+//! pattern matching, as used for graph RAG.
+
+mod common;
+
+use common::{names, run};
+use ruvector_graph::hybrid::cypher_extensions::ParserOptions;
+use ruvector_graph::hybrid::{VectorCypherExecutor, VectorCypherParser};
+use ruvector_graph::{
+    EdgeBuilder, EmbeddingConfig, GraphDB, GraphError, NodeBuilder, Properties, PropertyValue,
+    Value,
+};
+
+fn vector(values: &[f64]) -> PropertyValue {
+    PropertyValue::List(values.iter().map(|&x| PropertyValue::Float(x)).collect())
+}
+
+fn params(query: &[f64]) -> Properties {
+    let mut params = Properties::new();
+    params.insert("q".to_string(), vector(query));
+    params
+}
+
+/// Papers p1..p4 with 3-dimensional embeddings; p1 -> p3, p2 -> p4 and
+/// p3 -> p4 are citations
+fn papers() -> GraphDB {
+    let db = GraphDB::new();
+    let papers = [
+        ("p1", [1.0, 0.0, 0.0]),
+        ("p2", [0.9, 0.1, 0.0]),
+        ("p3", [0.0, 1.0, 0.0]),
+        ("p4", [0.0, 0.0, 1.0]),
+    ];
+    for (id, embedding) in papers {
+        db.create_node(
+            NodeBuilder::new()
+                .id(id)
+                .label("Paper")
+                .property("title", id)
+                .property("embedding", vector(&embedding))
+                .build(),
+        )
+        .unwrap();
+    }
+    for (from, to) in [("p1", "p3"), ("p2", "p4"), ("p3", "p4")] {
+        db.create_edge(EdgeBuilder::new(from.to_string(), to.to_string(), "CITES").build())
+            .unwrap();
+    }
+    db.create_vector_index(
+        "papers",
+        "Paper",
+        EmbeddingConfig {
+            dimensions: 3,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    db
+}
+
+#[test]
+fn test_query_nodes_then_expand() {
+    let db = papers();
+    assert_eq!(db.vector_index("papers").unwrap().len(), 4);
+
+    let result = db
+        .execute(
+            "CALL db.index.vector.queryNodes('papers', 2, $q) YIELD node, score \
+             MATCH (node)-[:CITES]->(m) \
+             RETURN node.title AS paper, m.title AS cited, score",
+            &params(&[1.0, 0.0, 0.0]),
+        )
+        .unwrap();
+    assert_eq!(names(&result, "paper"), vec!["p1", "p2"]);
+    assert_eq!(names(&result, "cited"), vec!["p3", "p4"]);
+    let score = result.rows[0]["score"].as_f64().unwrap();
+    assert!((score - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_vector_index_follows_writes() {
+    let db = papers();
+    let query = "CALL db.index.vector.queryNodes('papers', 1, $q) YIELD node \
+                 RETURN node.title AS title";
+    let near_p5 = params(&[0.0, 0.1, 1.0]);
+
+    run(
+        &db,
+        "CREATE (:Paper {title: 'p5', embedding: [0.0, 0.1, 1.0]})",
+    );
+    assert_eq!(
+        names(&db.execute(query, &near_p5).unwrap(), "title"),
+        vec!["p5"]
+    );
+
+    // Moving the embedding away drops p5 behind p4
+    run(
+        &db,
+        "MATCH (p:Paper {title: 'p5'}) SET p.embedding = [1.0, -1.0, 0.0]",
+    );
+    assert_eq!(
+        names(&db.execute(query, &near_p5).unwrap(), "title"),
+        vec!["p4"]
+    );
+
+    run(&db, "MATCH (p:Paper {title: 'p4'}) DETACH DELETE p");
+    let index = db.vector_index("papers").unwrap();
+    assert_eq!(index.len(), 4);
+    assert!(index
+        .query(&[0.0, 0.0, 1.0], 5)
+        .unwrap()
+        .iter()
+        .all(|(id, _)| id != "p4"));
+}
+
+#[test]
+fn test_vector_similarity_function() {
+    let db = papers();
+    let result = db
+        .execute(
+            "MATCH (p:Paper) \
+             RETURN p.title AS title, vector.similarity(p.embedding, $q) AS similarity \
+             ORDER BY similarity DESC LIMIT 2",
+            &params(&[0.0, 1.0, 0.0]),
+        )
+        .unwrap();
+    assert_eq!(names(&result, "title"), vec!["p3", "p2"]);
+    assert_eq!(result.rows[0]["similarity"], Value::Float(1.0));
+
+    let result = run(
+        &db,
+        "RETURN vector.similarity.euclidean([0, 0], [3, 4]) AS s, \
+         vector.similarity([0, 0], [1, 1]) AS undefined",
+    );
+    assert_eq!(result.rows[0]["s"], Value::Float(1.0 / 6.0));
+    assert_eq!(result.rows[0]["undefined"], Value::Null);
+
+    assert!(matches!(
+        db.execute("RETURN vector.similarity([1, 2], [1])", &Properties::new()),
+        Err(GraphError::CypherExecutionError(_))
+    ));
+}
+
+#[test]
+fn test_similar_to_executor() {
+    let db = papers();
+    let parser = VectorCypherParser::new(ParserOptions::default());
+    let query = parser
+        .parse(
+            "MATCH (p:Paper)-[:CITES]->(c) WHERE p.embedding SIMILAR TO $q LIMIT 2 \
+             RETURN p.title AS paper, c.title AS cited, semanticScore",
+        )
+        .unwrap();
+
+    let executor = VectorCypherExecutor::new(&db);
+    let result = executor.execute(&query, &params(&[1.0, 0.0, 0.0])).unwrap();
+    let papers: Vec<&str> = result
+        .rows
+        .iter()
+        .map(|row| row["paper"].as_str().unwrap())
+        .collect();
+    assert_eq!(papers, vec!["p1", "p2"]);
+    assert_eq!(result.stats.index_hits, 2);
+
+    let predicate = query.similarity_predicate.as_ref().unwrap();
+    let mut predicate = predicate.clone();
+    predicate.query_vector = vec![0.0, 0.0, 1.0];
+    predicate.top_k = 1;
+    assert_eq!(
+        executor.execute_similarity_search(&predicate).unwrap(),
+        vec!["p4"]
+    );
+
+    // Only the Paper index covers `embedding`
+    predicate.label = Some("Author".to_string());
+    assert!(matches!(
+        executor.execute_similarity_search(&predicate),
+        Err(GraphError::IndexError(_))
+    ));
+}
+
+#[test]
+fn test_vector_index_errors() {
+    let db = papers();
+    assert!(matches!(
+        db.create_vector_index("papers", "Paper", EmbeddingConfig::default()),
+        Err(GraphError::IndexError(_))
+    ));
+    assert!(matches!(
+        db.execute(
+            "CALL db.index.vector.queryNodes('missing', 1, $q) YIELD node RETURN node",
+            &params(&[1.0, 0.0, 0.0]),
+        ),
+        Err(GraphError::IndexError(_))
+    ));
+    assert!(matches!(
+        db.execute(
+            "CALL db.index.vector.queryNodes('papers', 1, $q) YIELD node RETURN node",
+            &params(&[1.0, 0.0]),
+        ),
+        Err(GraphError::InvalidEmbedding(_))
+    ));
+
+    // Embeddings the index cannot hold are rejected, not silently skipped
+    for create in [
+        "CREATE (:Paper {title: 'p5', embedding: [1.0, 0.0]})",
+        "CREATE (:Paper {title: 'p5', embedding: 'text'})",
+    ] {
+        assert!(matches!(
+            db.execute(create, &Properties::new()),
+            Err(GraphError::InvalidEmbedding(_))
+        ));
+    }
+    assert!(matches!(
+        db.execute(
+            "MATCH (p:Paper {title: 'p1'}) SET p.embedding = [1.0]",
+            &Properties::new()
+        ),
+        Err(GraphError::InvalidEmbedding(_))
+    ));
+    assert_eq!(db.get_nodes_by_label("Paper").len(), 4);
+    assert_eq!(
+        db.get_node("p1").unwrap().properties["embedding"],
+        vector(&[1.0, 0.0, 0.0])
+    );
+    assert_eq!(db.vector_index("papers").unwrap().len(), 4);
+
+    assert!(db.drop_vector_index("papers"));
+    assert!(db.vector_index("papers").is_none());
+
+    // Nor can an index be created over nodes it could not hold
+    assert!(matches!(
+        db.create_vector_index("papers", "Paper", EmbeddingConfig::default()),
+        Err(GraphError::InvalidEmbedding(_))
+    ));
+    assert!(db.vector_index("papers").is_none());
+}
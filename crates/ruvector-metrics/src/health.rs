@@ -30,13 +30,17 @@ pub struct CollectionHealth {
     pub status: HealthStatus,
     pub vectors_count: usize,
     pub last_updated: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct CollectionStats {
     pub name: String,
     pub vectors_count: usize,
     pub last_updated: Option<chrono::DateTime<chrono::Utc>>,
+    /// Why the collection could not be read, which makes it unhealthy
+    pub error: Option<String>,
 }
 
 pub struct HealthChecker {
@@ -76,7 +80,9 @@ impl HealthChecker {
 
         let mut details = HashMap::new();
         for collection in collections {
-            let status = if collection.vectors_count > 0 {
+            let status = if collection.error.is_some() {
+                HealthStatus::Unhealthy
+            } else if collection.vectors_count > 0 {
                 HealthStatus::Healthy
             } else {
                 HealthStatus::Degraded
@@ -88,6 +94,7 @@ impl HealthChecker {
                     status,
                     vectors_count: collection.vectors_count,
                     last_updated: collection.last_updated.map(|dt| dt.to_rfc3339()),
+                    error: collection.error.clone(),
                 },
             );
         }
@@ -150,11 +157,13 @@ mod tests {
                 name: "test1".to_string(),
                 vectors_count: 100,
                 last_updated: Some(chrono::Utc::now()),
+                error: None,
             },
             CollectionStats {
                 name: "test2".to_string(),
                 vectors_count: 200,
                 last_updated: None,
+                error: None,
             },
         ];
 
@@ -173,6 +182,7 @@ mod tests {
             name: "empty".to_string(),
             vectors_count: 0,
             last_updated: None,
+            error: None,
         }];
 
         let readiness = checker.readiness(&collections);
@@ -192,11 +202,13 @@ mod tests {
                 name: "healthy".to_string(),
                 vectors_count: 100,
                 last_updated: Some(chrono::Utc::now()),
+                error: None,
             },
             CollectionStats {
                 name: "degraded".to_string(),
                 vectors_count: 0,
                 last_updated: None,
+                error: None,
             },
         ];
 
@@ -211,4 +223,28 @@ mod tests {
             HealthStatus::Degraded
         );
     }
+
+    #[test]
+    fn test_readiness_with_failed_collection() {
+        let checker = HealthChecker::new();
+        let collections = vec![
+            CollectionStats {
+                name: "healthy".to_string(),
+                vectors_count: 100,
+                ..Default::default()
+            },
+            CollectionStats {
+                name: "broken".to_string(),
+                error: Some("storage unavailable".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let readiness = checker.readiness(&collections);
+
+        assert_eq!(readiness.status, HealthStatus::Degraded);
+        let broken = readiness.details.get("broken").unwrap();
+        assert_eq!(broken.status, HealthStatus::Unhealthy);
+        assert_eq!(broken.error.as_deref(), Some("storage unavailable"));
+    }
 }
@@ -53,6 +53,19 @@ lazy_static! {
         &["collection", "status"]
     ).unwrap();
 
+    // Point reads and payload updates, by operation
+    pub static ref POINT_OPERATIONS_TOTAL: CounterVec = register_counter_vec!(
+        Opts::new("ruvector_point_operations_total", "Total point reads and payload updates"),
+        &["collection", "operation", "status"]
+    ).unwrap();
+
+    pub static ref POINT_OPERATION_LATENCY_SECONDS: HistogramVec = register_histogram_vec!(
+        "ruvector_point_operation_latency_seconds",
+        "Point read and payload update latency in seconds",
+        &["collection", "operation"],
+        vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0]
+    ).unwrap();
+
     // Collection metrics
     pub static ref VECTORS_TOTAL: GaugeVec = register_gauge_vec!(
         Opts::new("ruvector_vectors_total", "Total vectors stored"),
@@ -63,6 +76,40 @@ lazy_static! {
         Opts::new("ruvector_collections_total", "Total number of collections")
     ).unwrap();
 
+    // Index metrics
+    pub static ref INDEX_MEMORY_BYTES: GaugeVec = register_gauge_vec!(
+        Opts::new("ruvector_index_memory_bytes", "Estimated index memory in bytes"),
+        &["collection"]
+    ).unwrap();
+
+    pub static ref TOMBSTONE_RATIO: GaugeVec = register_gauge_vec!(
+        Opts::new("ruvector_index_tombstone_ratio", "Share of index nodes that are deleted"),
+        &["collection"]
+    ).unwrap();
+
+    pub static ref HNSW_EF_SEARCH: GaugeVec = register_gauge_vec!(
+        Opts::new("ruvector_hnsw_ef_search", "Default HNSW ef_search"),
+        &["collection"]
+    ).unwrap();
+
+    pub static ref HNSW_RECALL_ESTIMATE: GaugeVec = register_gauge_vec!(
+        Opts::new("ruvector_hnsw_recall_estimate", "Sampled HNSW recall against exact search"),
+        &["collection"]
+    ).unwrap();
+
+    // HTTP metrics
+    pub static ref HTTP_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
+        Opts::new("ruvector_http_requests_total", "Total HTTP requests"),
+        &["collection", "method", "route", "status"]
+    ).unwrap();
+
+    pub static ref HTTP_REQUEST_LATENCY_SECONDS: HistogramVec = register_histogram_vec!(
+        "ruvector_http_request_latency_seconds",
+        "HTTP request latency in seconds",
+        &["collection", "method", "route"],
+        vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]
+    ).unwrap();
+
     // System metrics
     pub static ref MEMORY_USAGE_BYTES: Gauge = register_gauge!(
         Opts::new("ruvector_memory_usage_bytes", "Memory usage in bytes")
@@ -102,4 +149,18 @@ mod tests {
             .with_label_values(&["test"])
             .observe(0.001);
     }
+
+    #[test]
+    fn test_index_gauges_exported() {
+        INDEX_MEMORY_BYTES
+            .with_label_values(&["test_export"])
+            .set(1024.0);
+        HNSW_RECALL_ESTIMATE
+            .with_label_values(&["test_export"])
+            .set(0.95);
+
+        let metrics = gather_metrics();
+        assert!(metrics.contains("ruvector_index_memory_bytes{collection=\"test_export\"} 1024"));
+        assert!(metrics.contains("ruvector_hnsw_recall_estimate{collection=\"test_export\"} 0.95"));
+    }
 }
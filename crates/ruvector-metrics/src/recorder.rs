@@ -1,7 +1,9 @@
 use crate::{
-    COLLECTIONS_TOTAL, DELETE_REQUESTS_TOTAL, INSERT_LATENCY_SECONDS, INSERT_REQUESTS_TOTAL,
-    MEMORY_USAGE_BYTES, SEARCH_LATENCY_SECONDS, SEARCH_REQUESTS_TOTAL, VECTORS_INSERTED_TOTAL,
-    VECTORS_TOTAL,
+    COLLECTIONS_TOTAL, DELETE_REQUESTS_TOTAL, HNSW_EF_SEARCH, HNSW_RECALL_ESTIMATE,
+    HTTP_REQUESTS_TOTAL, HTTP_REQUEST_LATENCY_SECONDS, INDEX_MEMORY_BYTES, INSERT_LATENCY_SECONDS,
+    INSERT_REQUESTS_TOTAL, MEMORY_USAGE_BYTES, POINT_OPERATIONS_TOTAL,
+    POINT_OPERATION_LATENCY_SECONDS, SEARCH_LATENCY_SECONDS, SEARCH_REQUESTS_TOTAL,
+    TOMBSTONE_RATIO, UPTIME_SECONDS, VECTORS_INSERTED_TOTAL, VECTORS_TOTAL,
 };
 
 /// Helper struct for recording metrics
@@ -66,6 +68,27 @@ impl MetricsRecorder {
             .inc();
     }
 
+    /// Record a point read or payload update
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    /// * `operation` - The operation, e.g. `scroll` or `set_payload`
+    /// * `latency_secs` - The latency in seconds
+    /// * `success` - Whether the operation succeeded
+    pub fn record_operation(collection: &str, operation: &str, latency_secs: f64, success: bool) {
+        let status = if success { "success" } else { "error" };
+
+        POINT_OPERATIONS_TOTAL
+            .with_label_values(&[collection, operation, status])
+            .inc();
+
+        if success {
+            POINT_OPERATION_LATENCY_SECONDS
+                .with_label_values(&[collection, operation])
+                .observe(latency_secs);
+        }
+    }
+
     /// Update the total vector count for a collection
     ///
     /// # Arguments
@@ -93,6 +116,105 @@ impl MetricsRecorder {
         MEMORY_USAGE_BYTES.set(bytes as f64);
     }
 
+    /// Update the uptime counter
+    ///
+    /// # Arguments
+    /// * `seconds` - Seconds since the process started
+    pub fn set_uptime(seconds: f64) {
+        let elapsed = seconds - UPTIME_SECONDS.get();
+        if elapsed > 0.0 {
+            UPTIME_SECONDS.inc_by(elapsed);
+        }
+    }
+
+    /// Record an HTTP request
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name, or empty for other routes
+    /// * `method` - The HTTP method
+    /// * `route` - The matched route pattern, e.g. `/collections/:name`
+    /// * `status` - The response status code
+    /// * `latency_secs` - The latency in seconds
+    pub fn record_http_request(
+        collection: &str,
+        method: &str,
+        route: &str,
+        status: u16,
+        latency_secs: f64,
+    ) {
+        HTTP_REQUESTS_TOTAL
+            .with_label_values(&[collection, method, route, &status.to_string()])
+            .inc();
+
+        HTTP_REQUEST_LATENCY_SECONDS
+            .with_label_values(&[collection, method, route])
+            .observe(latency_secs);
+    }
+
+    /// Update the index memory estimate for a collection
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    /// * `bytes` - The estimated index memory in bytes
+    pub fn set_index_memory(collection: &str, bytes: usize) {
+        INDEX_MEMORY_BYTES
+            .with_label_values(&[collection])
+            .set(bytes as f64);
+    }
+
+    /// Update the share of deleted index nodes for a collection
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    /// * `ratio` - Deleted nodes over all nodes, between 0 and 1
+    pub fn set_tombstone_ratio(collection: &str, ratio: f32) {
+        TOMBSTONE_RATIO
+            .with_label_values(&[collection])
+            .set(ratio as f64);
+    }
+
+    /// Update the default HNSW ef_search of a collection
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    /// * `ef_search` - The ef_search used when a query does not set one
+    pub fn set_hnsw_ef_search(collection: &str, ef_search: usize) {
+        HNSW_EF_SEARCH
+            .with_label_values(&[collection])
+            .set(ef_search as f64);
+    }
+
+    /// Update the sampled HNSW recall estimate of a collection
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    /// * `recall` - The estimated recall, between 0 and 1
+    pub fn set_recall_estimate(collection: &str, recall: f32) {
+        HNSW_RECALL_ESTIMATE
+            .with_label_values(&[collection])
+            .set(recall as f64);
+    }
+
+    /// Drop the gauges of a deleted collection
+    ///
+    /// Counters and histograms are kept, as Prometheus expects them to only
+    /// grow.
+    ///
+    /// # Arguments
+    /// * `collection` - The collection name
+    pub fn remove_collection(collection: &str) {
+        for gauge in [
+            &*VECTORS_TOTAL,
+            &*INDEX_MEMORY_BYTES,
+            &*TOMBSTONE_RATIO,
+            &*HNSW_EF_SEARCH,
+            &*HNSW_RECALL_ESTIMATE,
+        ] {
+            // Missing labels just mean the gauge was never set
+            let _ = gauge.remove_label_values(&[collection]);
+        }
+    }
+
     /// Record a batch of operations
     ///
     /// # Arguments
@@ -149,6 +271,15 @@ mod tests {
         // Metrics are recorded, no panic
     }
 
+    #[test]
+    fn test_record_operation() {
+        MetricsRecorder::record_operation("test", "scroll", 0.001, true);
+        MetricsRecorder::record_operation("test", "set_payload", 0.001, false);
+        let metrics = crate::gather_metrics();
+        assert!(metrics.contains("operation=\"scroll\""));
+        assert!(metrics.contains("operation=\"set_payload\""));
+    }
+
     #[test]
     fn test_set_vectors_count() {
         MetricsRecorder::set_vectors_count("test", 1000);
@@ -167,6 +298,39 @@ mod tests {
         // Metrics are recorded, no panic
     }
 
+    #[test]
+    fn test_record_http_request() {
+        MetricsRecorder::record_http_request("test", "POST", "/collections/:name", 200, 0.003);
+        // Metrics are recorded, no panic
+    }
+
+    #[test]
+    fn test_set_index_gauges() {
+        MetricsRecorder::set_index_memory("test", 4096);
+        MetricsRecorder::set_tombstone_ratio("test", 0.1);
+        MetricsRecorder::set_hnsw_ef_search("test", 64);
+        MetricsRecorder::set_recall_estimate("test", 0.98);
+        // Metrics are recorded, no panic
+    }
+
+    #[test]
+    fn test_remove_collection() {
+        MetricsRecorder::set_vectors_count("removed", 10);
+        MetricsRecorder::set_index_memory("removed", 4096);
+        MetricsRecorder::remove_collection("removed");
+
+        assert!(!crate::gather_metrics().contains("collection=\"removed\""));
+        // Removing again is a no-op
+        MetricsRecorder::remove_collection("removed");
+    }
+
+    #[test]
+    fn test_set_uptime() {
+        MetricsRecorder::set_uptime(10.0);
+        MetricsRecorder::set_uptime(5.0);
+        assert!(UPTIME_SECONDS.get() >= 10.0);
+    }
+
     #[test]
     fn test_record_batch() {
         MetricsRecorder::record_batch("test", 100, 50, 10);
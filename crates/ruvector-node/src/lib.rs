@@ -51,12 +51,8 @@ impl From<JsDistanceMetric> for DistanceMetric {
 #[napi(object)]
 #[derive(Debug, Clone)]
 pub struct JsQuantizationConfig {
-    /// Quantization type: "none", "scalar", "product", "binary"
+    /// Quantization type: "none", "scalar", "binary"
     pub r#type: String,
-    /// Number of subspaces (for product quantization)
-    pub subspaces: Option<u32>,
-    /// Codebook size (for product quantization)
-    pub k: Option<u32>,
 }
 
 impl From<JsQuantizationConfig> for QuantizationConfig {
@@ -64,10 +60,6 @@ impl From<JsQuantizationConfig> for QuantizationConfig {
         match config.r#type.as_str() {
             "none" => QuantizationConfig::None,
             "scalar" => QuantizationConfig::Scalar,
-            "product" => QuantizationConfig::Product {
-                subspaces: config.subspaces.unwrap_or(16) as usize,
-                k: config.k.unwrap_or(256) as usize,
-            },
             "binary" => QuantizationConfig::Binary,
             _ => QuantizationConfig::Scalar,
         }
@@ -95,6 +87,7 @@ impl From<JsHnswConfig> for HnswConfig {
             ef_construction: config.ef_construction.unwrap_or(200) as usize,
             ef_search: config.ef_search.unwrap_or(100) as usize,
             max_elements: config.max_elements.unwrap_or(10_000_000) as usize,
+            ..Default::default()
         }
     }
 }
@@ -128,6 +121,7 @@ impl From<JsDbOptions> for DbOptions {
                 .unwrap_or_else(|| "./ruvector.db".to_string()),
             hnsw_config: options.hnsw_config.map(Into::into),
             quantization: options.quantization.map(Into::into),
+            named_vectors: None,
         }
     }
 }
@@ -153,6 +147,7 @@ impl JsVectorEntry {
         Ok(VectorEntry {
             id: self.id.clone(),
             vector: self.vector.to_vec(),
+            named_vectors: None,
             metadata,
         })
     }
@@ -173,13 +168,16 @@ pub struct JsSearchQuery {
 
 impl JsSearchQuery {
     fn to_core(&self) -> Result<SearchQuery> {
-        // Parse JSON string to HashMap<String, serde_json::Value>
+        // Parse JSON string to a filter expression or exact-match map
         let filter = self.filter.as_ref().and_then(|s| {
-            serde_json::from_str::<std::collections::HashMap<String, serde_json::Value>>(s).ok()
+            serde_json::from_str(s)
+                .ok()
+                .and_then(|value| FilterExpression::from_json(value).ok())
         });
 
         Ok(SearchQuery {
             vector: self.vector.to_vec(),
+            using: None,
             k: self.k as usize,
             filter,
             ef_search: self.ef_search.map(|v| v as usize),
@@ -515,6 +513,7 @@ impl From<JsCollectionConfig> for ruvector_collections::CollectionConfig {
             hnsw_config: config.hnsw_config.map(Into::into),
             quantization: config.quantization.map(Into::into),
             on_disk_payload: true,
+            named_vectors: None,
         }
     }
 }
@@ -6,16 +6,27 @@
 
 pub mod election;
 pub mod log;
+pub mod membership;
 pub mod node;
 pub mod rpc;
 pub mod state;
+pub mod state_machine;
+pub mod storage;
+pub mod transport;
 
-pub use node::{RaftNode, RaftNodeConfig};
+pub use log::EntryKind;
+pub use membership::{Membership, MembershipChange};
+pub use node::{CommandResult, RaftNode, RaftNodeConfig, MAX_COMMAND_LEN};
 pub use rpc::{
     AppendEntriesRequest, AppendEntriesResponse, InstallSnapshotRequest, InstallSnapshotResponse,
-    RequestVoteRequest, RequestVoteResponse,
+    RaftMessage, RequestVoteRequest, RequestVoteResponse, TimeoutNowRequest,
 };
 pub use state::{LeaderState, PersistentState, RaftState, VolatileState};
+pub use state_machine::StateMachine;
+pub use storage::{MemoryStorage, RaftStorage, RedbStorage};
+pub use transport::{
+    InProcessNetwork, RaftTransport, TcpRaftListener, TcpTransport, MAX_FRAME_LEN,
+};
 
 use thiserror::Error;
 
@@ -46,6 +57,9 @@ pub enum RaftError {
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
     #[error("Election timeout")]
     ElectionTimeout,
 
@@ -55,6 +69,15 @@ pub enum RaftError {
     #[error("Snapshot installation failed: {0}")]
     SnapshotFailed(String),
 
+    #[error("Leadership lost before the command at index {0} could be confirmed")]
+    LeadershipLost(u64),
+
+    #[error("Command of {size} bytes exceeds the limit of {limit} bytes")]
+    CommandTooLarge { size: usize, limit: usize },
+
+    #[error("Command rejected by the state machine: {0}")]
+    CommandRejected(String),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
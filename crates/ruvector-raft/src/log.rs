@@ -6,7 +6,7 @@
 //! - Snapshots and compaction
 //! - Persistence
 
-use crate::{LogIndex, RaftError, RaftResult, Term};
+use crate::{membership::Membership, LogIndex, RaftError, RaftResult, Term};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
@@ -21,6 +21,23 @@ pub struct LogEntry {
 
     /// State machine command
     pub command: Vec<u8>,
+
+    /// What the entry carries
+    pub kind: EntryKind,
+}
+
+/// Kind of a log entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Command applied to the state machine
+    #[default]
+    Command,
+
+    /// Empty entry a new leader appends to commit entries of earlier terms
+    Noop,
+
+    /// Cluster membership, in effect as soon as the entry is in the log
+    Membership(Membership),
 }
 
 impl LogEntry {
@@ -30,6 +47,17 @@ impl LogEntry {
             term,
             index,
             command,
+            kind: EntryKind::Command,
+        }
+    }
+
+    /// Create an entry of the given kind without a command
+    pub fn with_kind(term: Term, index: LogIndex, kind: EntryKind) -> Self {
+        Self {
+            term,
+            index,
+            command: Vec::new(),
+            kind,
         }
     }
 }
@@ -47,7 +75,7 @@ pub struct Snapshot {
     pub data: Vec<u8>,
 
     /// Configuration at the time of snapshot
-    pub configuration: Vec<String>,
+    pub configuration: Membership,
 }
 
 /// The Raft replicated log
@@ -203,7 +231,7 @@ impl RaftLog {
         &mut self,
         up_to_index: LogIndex,
         data: Vec<u8>,
-        configuration: Vec<String>,
+        configuration: Membership,
     ) -> RaftResult<Snapshot> {
         if up_to_index <= self.base_index {
             return Err(RaftError::InvalidLogIndex(up_to_index));
@@ -226,6 +254,22 @@ impl RaftLog {
         Ok(snapshot)
     }
 
+    /// Membership in effect at `index`: the latest membership entry up to
+    /// it, or the snapshot's configuration
+    ///
+    /// Returns `None` if the log and snapshot hold no membership.
+    pub fn membership_at(&self, index: LogIndex) -> Option<&Membership> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.index <= index)
+            .find_map(|entry| match &entry.kind {
+                EntryKind::Membership(membership) => Some(membership),
+                _ => None,
+            })
+            .or_else(|| self.snapshot.as_ref().map(|s| &s.configuration))
+    }
+
     /// Get the current snapshot
     pub fn snapshot(&self) -> Option<&Snapshot> {
         self.snapshot.as_ref()
@@ -326,7 +370,7 @@ mod tests {
         log.append(2, b"cmd3".to_vec());
 
         let snapshot = log
-            .create_snapshot(2, b"state".to_vec(), vec!["node1".to_string()])
+            .create_snapshot(2, b"state".to_vec(), Membership::new(vec!["node1".to_string()]))
             .unwrap();
 
         assert_eq!(snapshot.last_included_index, 2);
@@ -347,4 +391,29 @@ mod tests {
         assert_eq!(entries[0].index, 2);
         assert_eq!(entries[1].index, 3);
     }
+
+    #[test]
+    fn test_membership_at() {
+        let three = Membership::new(vec!["a".into(), "b".into(), "c".into()]);
+        let two = Membership::new(vec!["a".into(), "b".into()]);
+
+        let mut log = RaftLog::new();
+        log.append(1, b"cmd1".to_vec());
+        assert!(log.membership_at(1).is_none());
+
+        log.append_entries(vec![
+            LogEntry::with_kind(1, 2, EntryKind::Membership(three.clone())),
+            LogEntry::new(1, 3, b"cmd3".to_vec()),
+            LogEntry::with_kind(2, 4, EntryKind::Membership(two.clone())),
+        ])
+        .unwrap();
+        assert_eq!(log.membership_at(3), Some(&three));
+        assert_eq!(log.membership_at(4), Some(&two));
+
+        // Compaction keeps the configuration in the snapshot
+        log.create_snapshot(3, Vec::new(), three.clone()).unwrap();
+        assert_eq!(log.membership_at(3), Some(&three));
+        log.truncate_from(4).unwrap();
+        assert_eq!(log.membership_at(4), Some(&three));
+    }
 }
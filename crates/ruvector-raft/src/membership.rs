@@ -0,0 +1,162 @@
+//! Cluster membership
+//!
+//! Membership changes are replicated as log entries and change one server
+//! at a time, so any two consecutive configurations share a majority. A
+//! node uses the latest configuration in its log, committed or not. New
+//! servers join as learners, which receive the log but do not vote, and
+//! are promoted to voters once they have caught up.
+
+use crate::{NodeId, RaftError, RaftResult};
+use serde::{Deserialize, Serialize};
+
+/// Voting members and learners of a cluster
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Membership {
+    voters: Vec<NodeId>,
+    learners: Vec<NodeId>,
+}
+
+impl Membership {
+    /// Create a membership of voters only
+    pub fn new(voters: Vec<NodeId>) -> Self {
+        Self {
+            voters,
+            learners: Vec::new(),
+        }
+    }
+
+    /// Members that vote and count towards the commit quorum
+    pub fn voters(&self) -> &[NodeId] {
+        &self.voters
+    }
+
+    /// Members that only replicate the log
+    pub fn learners(&self) -> &[NodeId] {
+        &self.learners
+    }
+
+    /// Check if a node is a voting member
+    pub fn is_voter(&self, node_id: &str) -> bool {
+        self.voters.iter().any(|voter| voter == node_id)
+    }
+
+    /// Check if a node is a learner
+    pub fn is_learner(&self, node_id: &str) -> bool {
+        self.learners.iter().any(|learner| learner == node_id)
+    }
+
+    /// All members, voters first
+    pub fn members(&self) -> impl Iterator<Item = &NodeId> {
+        self.voters.iter().chain(self.learners.iter())
+    }
+
+    /// Number of voters needed for a majority
+    pub fn quorum(&self) -> usize {
+        self.voters.len() / 2 + 1
+    }
+
+    /// The membership after `change`
+    pub fn apply(&self, change: &MembershipChange) -> RaftResult<Self> {
+        let mut next = self.clone();
+        match change {
+            MembershipChange::AddLearner(node_id) => {
+                if self.is_voter(node_id) || self.is_learner(node_id) {
+                    return Err(RaftError::ConfigError(format!(
+                        "{} is already a member",
+                        node_id
+                    )));
+                }
+                next.learners.push(node_id.clone());
+            }
+            MembershipChange::PromoteLearner(node_id) => {
+                if !self.is_learner(node_id) {
+                    return Err(RaftError::ConfigError(format!(
+                        "{} is not a learner",
+                        node_id
+                    )));
+                }
+                next.learners.retain(|learner| learner != node_id);
+                next.voters.push(node_id.clone());
+            }
+            MembershipChange::Remove(node_id) => {
+                if self.is_voter(node_id) && self.voters.len() == 1 {
+                    return Err(RaftError::ConfigError(format!(
+                        "Cannot remove {}, the last voter",
+                        node_id
+                    )));
+                }
+                if !self.is_voter(node_id) && !self.is_learner(node_id) {
+                    return Err(RaftError::ConfigError(format!(
+                        "{} is not a member",
+                        node_id
+                    )));
+                }
+                next.voters.retain(|voter| voter != node_id);
+                next.learners.retain(|learner| learner != node_id);
+            }
+        }
+        Ok(next)
+    }
+}
+
+/// A change of one server's membership
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MembershipChange {
+    /// Start replicating to a new server without giving it a vote
+    AddLearner(NodeId),
+    /// Give a learner a vote
+    PromoteLearner(NodeId),
+    /// Remove a voter or learner
+    Remove(NodeId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[&str]) -> Vec<NodeId> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_membership_changes() {
+        let membership = Membership::new(ids(&["node1", "node2", "node3"]));
+        assert_eq!(membership.quorum(), 2);
+
+        let membership = membership
+            .apply(&MembershipChange::AddLearner("node4".to_string()))
+            .unwrap();
+        assert!(membership.is_learner("node4"));
+        assert!(!membership.is_voter("node4"));
+        assert_eq!(membership.quorum(), 2);
+        assert_eq!(membership.members().count(), 4);
+
+        let membership = membership
+            .apply(&MembershipChange::PromoteLearner("node4".to_string()))
+            .unwrap();
+        assert!(membership.is_voter("node4"));
+        assert!(membership.learners().is_empty());
+        assert_eq!(membership.quorum(), 3);
+
+        let membership = membership
+            .apply(&MembershipChange::Remove("node1".to_string()))
+            .unwrap();
+        assert_eq!(membership.voters(), ids(&["node2", "node3", "node4"]));
+    }
+
+    #[test]
+    fn test_invalid_membership_changes() {
+        let membership = Membership::new(ids(&["node1"]));
+        for change in [
+            MembershipChange::AddLearner("node1".to_string()),
+            MembershipChange::PromoteLearner("node2".to_string()),
+            MembershipChange::Remove("node2".to_string()),
+            MembershipChange::Remove("node1".to_string()),
+        ] {
+            assert!(matches!(
+                membership.apply(&change),
+                Err(RaftError::ConfigError(_))
+            ));
+        }
+    }
+}
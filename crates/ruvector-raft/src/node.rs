@@ -9,18 +9,25 @@
 
 use crate::{
     election::{ElectionState, VoteValidator},
+    log::{EntryKind, LogEntry, Snapshot},
+    membership::{Membership, MembershipChange},
     rpc::{
         AppendEntriesRequest, AppendEntriesResponse, InstallSnapshotRequest,
         InstallSnapshotResponse, RaftMessage, RequestVoteRequest, RequestVoteResponse,
+        TimeoutNowRequest,
     },
     state::{LeaderState, PersistentState, RaftState, VolatileState},
+    state_machine::{NoopStateMachine, StateMachine},
+    storage::{MemoryStorage, RaftStorage},
+    transport::{NullTransport, RaftTransport, MAX_FRAME_LEN},
     LogIndex, NodeId, RaftError, RaftResult, Term,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
-use tokio::time::{interval, sleep};
+use tokio::time::{interval, Instant};
 use tracing::{debug, error, info, warn};
 
 /// Configuration for a Raft node
@@ -29,7 +36,8 @@ pub struct RaftNodeConfig {
     /// This node's ID
     pub node_id: NodeId,
 
-    /// IDs of all cluster members (including self)
+    /// IDs of the initial voting members (including self), used until the
+    /// log holds a membership entry
     pub cluster_members: Vec<NodeId>,
 
     /// Minimum election timeout (milliseconds)
@@ -44,8 +52,18 @@ pub struct RaftNodeConfig {
     /// Maximum entries per AppendEntries RPC
     pub max_entries_per_message: usize,
 
+    /// Approximate size budget of the entries in one AppendEntries RPC
+    /// (bytes), kept below [`MAX_FRAME_LEN`] so the message can be sent
+    ///
+    /// A message always carries at least one entry, which
+    /// [`MAX_COMMAND_LEN`] keeps within the frame limit.
+    pub max_bytes_per_message: usize,
+
     /// Snapshot chunk size (bytes)
     pub snapshot_chunk_size: usize,
+
+    /// Applied entries kept in the log before it is compacted into a snapshot
+    pub snapshot_threshold: u64,
 }
 
 impl RaftNodeConfig {
@@ -58,11 +76,20 @@ impl RaftNodeConfig {
             election_timeout_max: 300,
             heartbeat_interval: 50,
             max_entries_per_message: 100,
-            snapshot_chunk_size: 64 * 1024, // 64KB
+            max_bytes_per_message: 16 * 1024 * 1024, // 16MB
+            snapshot_chunk_size: 64 * 1024,          // 64KB
+            snapshot_threshold: 10_000,
         }
     }
 }
 
+/// Largest command [`RaftNode::submit_command`] accepts (bytes), leaving
+/// room in a frame for the rest of an AppendEntries message
+pub const MAX_COMMAND_LEN: usize = MAX_FRAME_LEN - 64 * 1024;
+
+/// Bytes counted for an entry on top of its command when sizing a batch
+const ENTRY_OVERHEAD: usize = 64;
+
 /// Command to apply to the state machine
 #[derive(Debug, Clone)]
 pub struct Command {
@@ -76,6 +103,36 @@ pub struct CommandResult {
     pub term: Term,
 }
 
+/// Client waiting for its command to be applied
+#[derive(Debug)]
+struct PendingCommand {
+    /// Term the command was appended in
+    term: Term,
+    response_tx: mpsc::Sender<RaftResult<CommandResult>>,
+}
+
+/// Linearizable read waiting for leadership to be confirmed and the
+/// state machine to catch up
+#[derive(Debug)]
+struct PendingRead {
+    /// Commit index when the read was requested
+    index: LogIndex,
+    /// First AppendEntries sequence number sent after the read was requested
+    seq: u64,
+    response_tx: mpsc::Sender<RaftResult<LogIndex>>,
+}
+
+/// Leadership transfer in progress
+#[derive(Debug)]
+struct LeadershipTransfer {
+    target: NodeId,
+    /// Give up if the target has not taken over by then
+    deadline: Instant,
+    /// Whether TimeoutNow was sent to the target
+    timeout_sent: bool,
+    response_tx: mpsc::Sender<RaftResult<()>>,
+}
+
 /// Internal messages for the Raft node
 #[derive(Debug)]
 enum InternalMessage {
@@ -86,6 +143,20 @@ enum InternalMessage {
         command: Command,
         response_tx: mpsc::Sender<RaftResult<CommandResult>>,
     },
+    /// Membership change to replicate
+    ChangeMembership {
+        change: MembershipChange,
+        response_tx: mpsc::Sender<RaftResult<CommandResult>>,
+    },
+    /// Hand leadership over to another voter
+    TransferLeadership {
+        target: NodeId,
+        response_tx: mpsc::Sender<RaftResult<()>>,
+    },
+    /// Linearizable read
+    ReadIndex {
+        response_tx: mpsc::Sender<RaftResult<LogIndex>>,
+    },
     /// Election timeout fired
     ElectionTimeout,
     /// Heartbeat timeout fired
@@ -100,6 +171,35 @@ pub struct RaftNode {
     /// Persistent state
     persistent: Arc<RwLock<PersistentState>>,
 
+    /// Stable storage backing the persistent state
+    storage: Arc<dyn RaftStorage>,
+
+    /// Application state that committed entries are applied to
+    state_machine: Arc<dyn StateMachine>,
+
+    /// Held while the state machine is changed or a snapshot of it is
+    /// taken, so the snapshot matches `last_applied` exactly
+    apply_lock: Arc<tokio::sync::Mutex<()>>,
+
+    /// Snapshot being received from the leader, chunk by chunk
+    incoming_snapshot: Arc<RwLock<Option<Snapshot>>>,
+
+    /// Outgoing messages to other nodes
+    transport: Arc<dyn RaftTransport>,
+
+    /// Commands appended by this node as leader, by log index, waiting to
+    /// be applied
+    pending: Arc<Mutex<BTreeMap<LogIndex, PendingCommand>>>,
+
+    /// Membership of the latest configuration in the log
+    membership: Arc<RwLock<Membership>>,
+
+    /// Reads waiting to be served as leader
+    pending_reads: Arc<Mutex<Vec<PendingRead>>>,
+
+    /// Leadership transfer started as leader
+    transfer: Arc<Mutex<Option<LeadershipTransfer>>>,
+
     /// Volatile state
     volatile: Arc<RwLock<VolatileState>>,
 
@@ -115,20 +215,79 @@ pub struct RaftNode {
     /// Current leader ID (if known)
     current_leader: Arc<RwLock<Option<NodeId>>>,
 
+    /// When an append from the leader last found this node's state machine
+    /// caught up with the leader's commit index
+    caught_up: Arc<RwLock<Option<Instant>>>,
+
     /// Channel for internal messages
     internal_tx: mpsc::UnboundedSender<InternalMessage>,
-    internal_rx: Arc<RwLock<mpsc::UnboundedReceiver<InternalMessage>>>,
+    internal_rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<InternalMessage>>>,
 }
 
 impl RaftNode {
-    /// Create a new Raft node
+    /// Create a new Raft node whose state is kept in memory only
     pub fn new(config: RaftNodeConfig) -> Self {
+        Self::from_state(
+            config,
+            Arc::new(MemoryStorage::new()),
+            Arc::new(NoopStateMachine),
+            PersistentState::new(),
+        )
+    }
+
+    /// Create a Raft node on durable storage, recovering the term, vote and
+    /// log stored by a previous run and restoring `state_machine` from the
+    /// latest snapshot
+    pub fn with_storage(
+        config: RaftNodeConfig,
+        storage: Arc<dyn RaftStorage>,
+        state_machine: Arc<dyn StateMachine>,
+    ) -> RaftResult<Self> {
+        let persistent = storage.load()?;
+        if let Some(snapshot) = persistent.log.snapshot() {
+            state_machine.restore(&mut snapshot.data.as_slice())?;
+        }
+        info!(
+            "Recovered Raft state for {}: term {}, snapshot at {}, last log index {}",
+            config.node_id,
+            persistent.current_term,
+            persistent.log.base_index(),
+            persistent.log.last_index()
+        );
+        Ok(Self::from_state(config, storage, state_machine, persistent))
+    }
+
+    fn from_state(
+        config: RaftNodeConfig,
+        storage: Arc<dyn RaftStorage>,
+        state_machine: Arc<dyn StateMachine>,
+        persistent: PersistentState,
+    ) -> Self {
         let (internal_tx, internal_rx) = mpsc::unbounded_channel();
-        let cluster_size = config.cluster_members.len();
+        let membership = persistent
+            .log
+            .membership_at(persistent.log.last_index())
+            .cloned()
+            .unwrap_or_else(|| Membership::new(config.cluster_members.clone()));
+        let cluster_size = membership.voters().len();
+
+        // Everything in the snapshot is committed and already applied
+        let mut volatile = VolatileState::new();
+        volatile.update_commit_index(persistent.log.base_index());
+        volatile.apply_entries(persistent.log.base_index());
 
         Self {
-            persistent: Arc::new(RwLock::new(PersistentState::new())),
-            volatile: Arc::new(RwLock::new(VolatileState::new())),
+            persistent: Arc::new(RwLock::new(persistent)),
+            storage,
+            state_machine,
+            apply_lock: Arc::new(tokio::sync::Mutex::new(())),
+            incoming_snapshot: Arc::new(RwLock::new(None)),
+            transport: Arc::new(NullTransport),
+            pending: Arc::new(Mutex::new(BTreeMap::new())),
+            membership: Arc::new(RwLock::new(membership)),
+            pending_reads: Arc::new(Mutex::new(Vec::new())),
+            transfer: Arc::new(Mutex::new(None)),
+            volatile: Arc::new(RwLock::new(volatile)),
             state: Arc::new(RwLock::new(RaftState::Follower)),
             leader_state: Arc::new(RwLock::new(None)),
             election_state: Arc::new(RwLock::new(ElectionState::new(
@@ -137,9 +296,32 @@ impl RaftNode {
                 config.election_timeout_max,
             ))),
             current_leader: Arc::new(RwLock::new(None)),
+            caught_up: Arc::new(RwLock::new(None)),
             config,
             internal_tx,
-            internal_rx: Arc::new(RwLock::new(internal_rx)),
+            internal_rx: Arc::new(tokio::sync::Mutex::new(internal_rx)),
+        }
+    }
+
+    /// Send messages to other nodes through `transport`
+    ///
+    /// Without a transport the node cannot reach its peers.
+    pub fn with_transport(mut self, transport: Arc<dyn RaftTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Hand a message received from node `from` to this node
+    ///
+    /// Called by transports; the message is processed by the loop run by
+    /// [`start`](Self::start).
+    pub fn receive(&self, from: NodeId, message: RaftMessage) {
+        if self
+            .internal_tx
+            .send(InternalMessage::Rpc { from, message })
+            .is_err()
+        {
+            debug!("Node {} stopped, dropping message", self.config.node_id);
         }
     }
 
@@ -159,11 +341,9 @@ impl RaftNode {
 
     /// Main message processing loop
     async fn run(self: Arc<Self>) {
+        let mut rx = self.internal_rx.lock().await;
         loop {
-            let message = {
-                let mut rx = self.internal_rx.write();
-                rx.recv().await
-            };
+            let message = rx.recv().await;
 
             match message {
                 Some(InternalMessage::Rpc { from, message }) => {
@@ -175,6 +355,21 @@ impl RaftNode {
                 }) => {
                     self.handle_client_command(command, response_tx).await;
                 }
+                Some(InternalMessage::ChangeMembership {
+                    change,
+                    response_tx,
+                }) => {
+                    self.handle_membership_change(change, response_tx).await;
+                }
+                Some(InternalMessage::TransferLeadership {
+                    target,
+                    response_tx,
+                }) => {
+                    self.handle_transfer_leadership(target, response_tx).await;
+                }
+                Some(InternalMessage::ReadIndex { response_tx }) => {
+                    self.handle_read_index(response_tx).await;
+                }
                 Some(InternalMessage::ElectionTimeout) => {
                     self.handle_election_timeout().await;
                 }
@@ -196,34 +391,43 @@ impl RaftNode {
         let current_term = self.persistent.read().current_term;
 
         if message_term > current_term {
-            self.step_down(message_term).await;
+            if let Err(e) = self.step_down(message_term).await {
+                // Without the new term on disk the message cannot be answered
+                error!("Failed to persist term {}: {}", message_term, e);
+                return;
+            }
         }
 
         match message {
             RaftMessage::AppendEntriesRequest(req) => {
-                let response = self.handle_append_entries(req).await;
-                // TODO: Send response back to sender
-                debug!("AppendEntries response to {}: {:?}", from, response);
+                let seq = req.seq;
+                let mut response = self.handle_append_entries(req).await;
+                response.seq = seq;
+                self.transport
+                    .send(&from, RaftMessage::AppendEntriesResponse(response));
             }
             RaftMessage::AppendEntriesResponse(resp) => {
                 self.handle_append_entries_response(from, resp).await;
             }
             RaftMessage::RequestVoteRequest(req) => {
                 let response = self.handle_request_vote(req).await;
-                // TODO: Send response back to sender
-                debug!("RequestVote response to {}: {:?}", from, response);
+                self.transport
+                    .send(&from, RaftMessage::RequestVoteResponse(response));
             }
             RaftMessage::RequestVoteResponse(resp) => {
                 self.handle_request_vote_response(from, resp).await;
             }
             RaftMessage::InstallSnapshotRequest(req) => {
                 let response = self.handle_install_snapshot(req).await;
-                // TODO: Send response back to sender
-                debug!("InstallSnapshot response to {}: {:?}", from, response);
+                self.transport
+                    .send(&from, RaftMessage::InstallSnapshotResponse(response));
             }
             RaftMessage::InstallSnapshotResponse(resp) => {
                 self.handle_install_snapshot_response(from, resp).await;
             }
+            RaftMessage::TimeoutNow(req) => {
+                self.handle_timeout_now(req).await;
+            }
         }
     }
 
@@ -241,13 +445,21 @@ impl RaftNode {
         self.election_state.write().reset_timer();
         *self.current_leader.write() = Some(req.leader_id.clone());
 
+        // Entries up to the snapshot are committed, so they always match
+        let base_index = persistent.log.base_index();
+
         // Reply false if log doesn't contain an entry at prevLogIndex with prevLogTerm
-        if !persistent
-            .log
-            .matches(req.prev_log_index, req.prev_log_term)
+        if req.prev_log_index > base_index
+            && !persistent
+                .log
+                .matches(req.prev_log_index, req.prev_log_term)
         {
-            let conflict_index = req.prev_log_index;
-            let conflict_term = persistent.log.term_at(conflict_index);
+            let conflict_term = persistent.log.term_at(req.prev_log_index);
+            // A log that ends early lets the leader skip straight past its end
+            let conflict_index = match conflict_term {
+                Some(_) => req.prev_log_index,
+                None => persistent.log.last_index() + 1,
+            };
             return AppendEntriesResponse::failure(
                 persistent.current_term,
                 Some(conflict_index),
@@ -255,38 +467,63 @@ impl RaftNode {
             );
         }
 
-        // Append new entries
-        if !req.entries.is_empty() {
-            // Delete conflicting entries and append new ones
-            let mut index = req.prev_log_index + 1;
-            for entry in &req.entries {
-                if let Some(existing_term) = persistent.log.term_at(index) {
-                    if existing_term != entry.term {
-                        // Conflict found, truncate from here
-                        let _ = persistent.log.truncate_from(index);
+        // Skip entries already in the log, deleting the first conflicting
+        // entry and all that follow it
+        let mut new_entries = req.entries.as_slice();
+        while let Some(entry) = new_entries.first() {
+            if entry.index <= base_index {
+                new_entries = &new_entries[1..];
+                continue;
+            }
+            match persistent.log.term_at(entry.index) {
+                Some(term) if term == entry.term => new_entries = &new_entries[1..],
+                Some(_) => {
+                    let truncated = self
+                        .storage
+                        .truncate_from(entry.index)
+                        .and_then(|()| persistent.log.truncate_from(entry.index));
+                    if let Err(e) = truncated {
+                        error!("Failed to truncate log at {}: {}", entry.index, e);
+                        return AppendEntriesResponse::failure(persistent.current_term, None, None);
                     }
+                    break;
                 }
-                index += 1;
+                None => break,
             }
+        }
 
-            // Append entries
-            if let Err(e) = persistent.log.append_entries(req.entries.clone()) {
+        // Append new entries, durably before acknowledging them
+        if !new_entries.is_empty() {
+            let appended = self
+                .storage
+                .append(new_entries)
+                .and_then(|()| persistent.log.append_entries(new_entries.to_vec()));
+            if let Err(e) = appended {
                 error!("Failed to append entries: {}", e);
                 return AppendEntriesResponse::failure(persistent.current_term, None, None);
             }
         }
 
+        self.refresh_membership(&persistent);
+
+        // Only the entries sent are known to match the leader's log
+        let last_new_entry = req.prev_log_index + req.entries.len() as LogIndex;
+
         // Update commit index
         if req.leader_commit > volatile.commit_index {
-            let last_new_entry = if req.entries.is_empty() {
-                req.prev_log_index
-            } else {
-                req.entries.last().unwrap().index
-            };
             volatile.update_commit_index(std::cmp::min(req.leader_commit, last_new_entry));
         }
 
-        AppendEntriesResponse::success(persistent.current_term, persistent.log.last_index())
+        let response = AppendEntriesResponse::success(persistent.current_term, last_new_entry);
+        drop(volatile);
+        drop(persistent);
+
+        self.apply_committed();
+        // Heartbeats alone say nothing about how far behind this node is
+        if self.volatile.read().last_applied >= req.leader_commit {
+            *self.caught_up.write() = Some(Instant::now());
+        }
+        response
     }
 
     /// Handle AppendEntries response
@@ -295,33 +532,76 @@ impl RaftNode {
             return;
         }
 
-        let persistent = self.persistent.write();
-        let mut leader_state_guard = self.leader_state.write();
+        let catch_up = {
+            let persistent = self.persistent.read();
+            if resp.term != persistent.current_term {
+                return;
+            }
+            let mut leader_state_guard = self.leader_state.write();
+            let Some(leader_state) = leader_state_guard.as_mut() else {
+                return;
+            };
+
+            // Any answer in our term shows the follower still follows us
+            leader_state.ack_seq(&from, resp.seq);
 
-        if let Some(leader_state) = leader_state_guard.as_mut() {
             if resp.success {
                 // Update next_index and match_index
                 if let Some(match_index) = resp.match_index {
                     leader_state.update_replication(&from, match_index);
-
-                    // Update commit index
-                    let new_commit = leader_state.calculate_commit_index();
-                    let mut volatile = self.volatile.write();
-                    if new_commit > volatile.commit_index {
-                        // Verify the entry is from current term
-                        if let Some(term) = persistent.log.term_at(new_commit) {
-                            if term == persistent.current_term {
-                                volatile.update_commit_index(new_commit);
-                                info!("Updated commit index to {}", new_commit);
-                            }
-                        }
-                    }
                 }
+            } else if let (Some(index), None) = (resp.conflict_index, resp.conflict_term) {
+                // The follower's log ends before the entries we sent
+                leader_state.rewind_next_index(&from, index);
+                debug!("Follower {} log ends at {}", from, index.saturating_sub(1));
             } else {
                 // Decrement next_index and retry
                 leader_state.decrement_next_index(&from);
                 debug!("Replication failed for {}, decrementing next_index", from);
             }
+
+            // Keep going without waiting for the next heartbeat while the
+            // follower is missing entries or its log is being repaired
+            let lagging = leader_state
+                .get_next_index(&from)
+                .is_some_and(|next| next <= persistent.log.last_index());
+            lagging || resp.conflict_index.is_some()
+        };
+
+        self.advance_commit_index();
+        self.apply_committed();
+        self.resolve_reads();
+        if self.advance_transfer(&from) {
+            return;
+        }
+        if catch_up {
+            if let Some(request) = self.replication_request(&from) {
+                self.transport.send(&from, request);
+            }
+        }
+    }
+
+    /// Commit the entries stored by a majority of voters
+    fn advance_commit_index(&self) {
+        let persistent = self.persistent.read();
+        let membership = self.membership.read();
+        let leader_state = self.leader_state.read();
+        let Some(leader_state) = leader_state.as_ref() else {
+            return;
+        };
+
+        let new_commit = leader_state.quorum_match_index(
+            membership.voters(),
+            &self.config.node_id,
+            persistent.log.last_index(),
+        );
+        let mut volatile = self.volatile.write();
+        // Only entries from the current term are committed by counting
+        if new_commit > volatile.commit_index
+            && persistent.log.term_at(new_commit) == Some(persistent.current_term)
+        {
+            volatile.update_commit_index(new_commit);
+            info!("Updated commit index to {}", new_commit);
         }
     }
 
@@ -350,6 +630,13 @@ impl RaftNode {
         );
 
         if should_grant {
+            if let Err(e) = self
+                .storage
+                .save_hard_state(persistent.current_term, Some(&req.candidate_id))
+            {
+                error!("Failed to persist vote for {}: {}", req.candidate_id, e);
+                return RequestVoteResponse::denied(persistent.current_term);
+            }
             persistent.vote_for(req.candidate_id.clone());
             self.election_state.write().reset_timer();
             info!("Granted vote to {} for term {}", req.candidate_id, req.term);
@@ -371,7 +658,7 @@ impl RaftNode {
             return;
         }
 
-        if resp.vote_granted {
+        if resp.vote_granted && self.membership.read().is_voter(&from) {
             let won_election = self.election_state.write().record_vote(from.clone());
             if won_election {
                 info!("Won election for term {}", current_term);
@@ -381,28 +668,162 @@ impl RaftNode {
     }
 
     /// Handle InstallSnapshot RPC
+    ///
+    /// Chunks are assembled in order; a response carrying `next_offset`
+    /// asks the leader for the chunk at that offset, and one without it
+    /// means the snapshot is installed.
     async fn handle_install_snapshot(
         &self,
         req: InstallSnapshotRequest,
     ) -> InstallSnapshotResponse {
-        let persistent = self.persistent.write();
+        let mut persistent = self.persistent.write();
+        let term = persistent.current_term;
 
-        if req.term < persistent.current_term {
-            return InstallSnapshotResponse::failure(persistent.current_term);
+        if req.term < term {
+            return InstallSnapshotResponse::failure(term);
+        }
+
+        self.election_state.write().reset_timer();
+        *self.current_leader.write() = Some(req.leader_id.clone());
+
+        // Nothing to do if our own snapshot already covers it
+        if req.last_included_index <= persistent.log.base_index() {
+            *self.incoming_snapshot.write() = None;
+            return InstallSnapshotResponse::success(term, None);
+        }
+
+        let mut incoming = self.incoming_snapshot.write();
+        if req.offset == 0 {
+            *incoming = Some(Snapshot {
+                last_included_index: req.last_included_index,
+                last_included_term: req.last_included_term,
+                data: Vec::new(),
+                configuration: req.configuration.clone(),
+            });
+        }
+
+        // Ask for the chunk we need if this one does not continue ours
+        let expected = match incoming.as_ref() {
+            Some(snapshot)
+                if snapshot.last_included_index == req.last_included_index
+                    && snapshot.last_included_term == req.last_included_term =>
+            {
+                snapshot.data.len() as u64
+            }
+            _ => 0,
+        };
+        if req.offset != expected {
+            if expected == 0 {
+                *incoming = None;
+            }
+            return InstallSnapshotResponse::success(term, Some(expected));
+        }
+
+        let Some(snapshot) = incoming.as_mut() else {
+            return InstallSnapshotResponse::success(term, Some(0));
+        };
+        snapshot.data.extend_from_slice(&req.data);
+        if !req.done {
+            return InstallSnapshotResponse::success(term, Some(snapshot.data.len() as u64));
+        }
+        // The leader starts the transfer over once the local snapshot is done
+        let Ok(_applying) = self.apply_lock.try_lock() else {
+            debug!("Taking a local snapshot, not installing the leader's yet");
+            *incoming = None;
+            return InstallSnapshotResponse::failure(term);
+        };
+        let Some(snapshot) = incoming.take() else {
+            return InstallSnapshotResponse::success(term, Some(0));
+        };
+        drop(incoming);
+
+        // Keep the entries after the snapshot only if the log agrees with it
+        let index = snapshot.last_included_index;
+        let keep_suffix = persistent.log.matches(index, snapshot.last_included_term);
+        let installed = self
+            .storage
+            .save_snapshot(&snapshot)
+            .and_then(|()| match keep_suffix {
+                true => Ok(()),
+                false => self.storage.truncate_from(index + 1),
+            })
+            .and_then(|()| self.state_machine.restore(&mut snapshot.data.as_slice()));
+        if let Err(e) = installed {
+            error!("Failed to install snapshot at {}: {}", index, e);
+            return InstallSnapshotResponse::failure(term);
+        }
+
+        let compacted =
+            persistent
+                .log
+                .install_snapshot(snapshot)
+                .and_then(|()| match keep_suffix {
+                    true => Ok(()),
+                    false => persistent.log.truncate_from(index + 1),
+                });
+        if let Err(e) = compacted {
+            error!("Failed to compact log at {}: {}", index, e);
+            return InstallSnapshotResponse::failure(term);
+        }
+        self.refresh_membership(&persistent);
+
+        let mut volatile = self.volatile.write();
+        volatile.update_commit_index(index);
+        volatile.apply_entries(index);
+        info!("Installed snapshot from {} up to {}", req.leader_id, index);
+
+        // Whether the commands this node proposed made it into the
+        // snapshot cannot be told anymore
+        let mut pending = self.pending.lock();
+        let still_pending = pending.split_off(&(index + 1));
+        for (index, command) in std::mem::replace(&mut *pending, still_pending) {
+            let _ = command
+                .response_tx
+                .try_send(Err(RaftError::LeadershipLost(index)));
         }
 
-        // TODO: Implement snapshot installation
-        // For now, just acknowledge
-        InstallSnapshotResponse::success(persistent.current_term, None)
+        InstallSnapshotResponse::success(term, None)
     }
 
     /// Handle InstallSnapshot response
-    async fn handle_install_snapshot_response(
-        &self,
-        _from: NodeId,
-        _resp: InstallSnapshotResponse,
-    ) {
-        // TODO: Implement snapshot response handling
+    async fn handle_install_snapshot_response(&self, from: NodeId, resp: InstallSnapshotResponse) {
+        if !self.state.read().is_leader() || resp.term != self.current_term() {
+            return;
+        }
+
+        {
+            let mut leader_state_guard = self.leader_state.write();
+            let Some(leader_state) = leader_state_guard.as_mut() else {
+                return;
+            };
+            let Some(&(index, _)) = leader_state.snapshot_transfers.get(&from) else {
+                return;
+            };
+
+            match (resp.success, resp.next_offset) {
+                (true, Some(offset)) => {
+                    leader_state
+                        .snapshot_transfers
+                        .insert(from.clone(), (index, offset));
+                }
+                (true, None) => {
+                    leader_state.snapshot_transfers.remove(&from);
+                    leader_state.update_replication(&from, index);
+                    info!("Follower {} installed snapshot up to {}", from, index);
+                }
+                (false, _) => {
+                    // Start the transfer over with the next request
+                    leader_state.snapshot_transfers.remove(&from);
+                }
+            }
+        }
+
+        // Send the next chunk, or the entries after the snapshot
+        if resp.success {
+            if let Some(request) = self.replication_request(&from) {
+                self.transport.send(&from, request);
+            }
+        }
     }
 
     /// Handle client command
@@ -411,45 +832,318 @@ impl RaftNode {
         command: Command,
         response_tx: mpsc::Sender<RaftResult<CommandResult>>,
     ) {
-        // Only leader can handle client commands
-        if !self.state.read().is_leader() {
-            let _ = response_tx.send(Err(RaftError::NotLeader)).await;
+        if let Err(e) = self.check_accepting_proposals() {
+            let _ = response_tx.send(Err(e)).await;
             return;
         }
 
-        let mut persistent = self.persistent.write();
-        let term = persistent.current_term;
-        let index = persistent.log.append(term, command.data);
+        self.propose(EntryKind::Command, command.data, response_tx)
+            .await;
+    }
+
+    /// Handle membership change
+    async fn handle_membership_change(
+        &self,
+        change: MembershipChange,
+        response_tx: mpsc::Sender<RaftResult<CommandResult>>,
+    ) {
+        let membership = match self
+            .check_accepting_proposals()
+            .and_then(|()| self.check_membership_change(&change))
+        {
+            Ok(membership) => membership,
+            Err(e) => {
+                let _ = response_tx.send(Err(e)).await;
+                return;
+            }
+        };
+
+        info!("Proposing membership change {:?}", change);
+        self.propose(EntryKind::Membership(membership), Vec::new(), response_tx)
+            .await;
+    }
+
+    /// Only a leader that is not handing over leadership accepts new entries
+    fn check_accepting_proposals(&self) -> RaftResult<()> {
+        if !self.state.read().is_leader() || self.transfer.lock().is_some() {
+            return Err(RaftError::NotLeader);
+        }
+        Ok(())
+    }
+
+    /// Validate a membership change, returning the membership after it
+    ///
+    /// Servers change one at a time, and only once the previous change and
+    /// an entry of the leader's own term are committed.
+    fn check_membership_change(&self, change: &MembershipChange) -> RaftResult<Membership> {
+        let persistent = self.persistent.read();
+        let membership = self.membership.read();
+        let leader_state = self.leader_state.read();
+        let Some(leader_state) = leader_state.as_ref() else {
+            return Err(RaftError::NotLeader);
+        };
+        let commit_index = self.volatile.read().commit_index;
+
+        if commit_index < leader_state.term_start_index {
+            return Err(RaftError::ConfigError(
+                "Leader has not committed an entry in its term yet".to_string(),
+            ));
+        }
+        let uncommitted_change = persistent
+            .log
+            .entries_from(commit_index + 1)
+            .iter()
+            .any(|entry| matches!(entry.kind, EntryKind::Membership(_)));
+        if uncommitted_change {
+            return Err(RaftError::ConfigError(
+                "A membership change is already in progress".to_string(),
+            ));
+        }
+        if let MembershipChange::PromoteLearner(learner) = change {
+            let matched = leader_state.get_match_index(learner).unwrap_or(0);
+            if membership.is_learner(learner) && matched < commit_index {
+                return Err(RaftError::ConfigError(format!(
+                    "{} has not caught up with the log",
+                    learner
+                )));
+            }
+        }
+
+        membership.apply(change)
+    }
 
-        let result = CommandResult { index, term };
-        let _ = response_tx.send(Ok(result)).await;
+    /// Append an entry as leader and answer `response_tx` once it is applied
+    async fn propose(
+        &self,
+        kind: EntryKind,
+        command: Vec<u8>,
+        response_tx: mpsc::Sender<RaftResult<CommandResult>>,
+    ) {
+        let result = {
+            let mut persistent = self.persistent.write();
+            let term = persistent.current_term;
+            let mut entry = LogEntry::with_kind(term, persistent.log.last_index() + 1, kind);
+            entry.command = command;
+            let index = entry.index;
+
+            let appended = self
+                .storage
+                .append(std::slice::from_ref(&entry))
+                .and_then(|()| persistent.log.append_entries(vec![entry]))
+                .map(|()| (index, term));
+            self.refresh_membership(&persistent);
+            appended
+        };
+        let (index, term) = match result {
+            Ok(appended) => appended,
+            Err(e) => {
+                error!("Failed to append client command: {}", e);
+                let _ = response_tx.send(Err(e)).await;
+                return;
+            }
+        };
+
+        // The client is answered once the entry is applied
+        self.pending
+            .lock()
+            .insert(index, PendingCommand { term, response_tx });
+
+        // The only voter commits on its own
+        self.advance_commit_index();
+        self.apply_committed();
 
         // Trigger immediate replication
-        drop(persistent);
         let _ = self.internal_tx.send(InternalMessage::HeartbeatTimeout);
     }
 
+    /// Handle leadership transfer request
+    ///
+    /// The target is brought up to date, then told to start an election
+    /// right away. New entries are refused until the transfer ends.
+    async fn handle_transfer_leadership(
+        &self,
+        target: NodeId,
+        response_tx: mpsc::Sender<RaftResult<()>>,
+    ) {
+        let checked = if !self.state.read().is_leader() {
+            Err(RaftError::NotLeader)
+        } else if target == self.config.node_id {
+            let _ = response_tx.send(Ok(())).await;
+            return;
+        } else if !self.membership.read().is_voter(&target) {
+            Err(RaftError::ConfigError(format!("{} is not a voter", target)))
+        } else if self.transfer.lock().is_some() {
+            Err(RaftError::ConfigError(
+                "A leadership transfer is already in progress".to_string(),
+            ))
+        } else {
+            Ok(())
+        };
+        if let Err(e) = checked {
+            let _ = response_tx.send(Err(e)).await;
+            return;
+        }
+
+        info!("Transferring leadership to {}", target);
+        *self.transfer.lock() = Some(LeadershipTransfer {
+            target: target.clone(),
+            deadline: Instant::now() + Duration::from_millis(self.config.election_timeout_max),
+            timeout_sent: false,
+            response_tx,
+        });
+        if !self.advance_transfer(&target) {
+            if let Some(request) = self.replication_request(&target) {
+                self.transport.send(&target, request);
+            }
+        }
+    }
+
+    /// Send TimeoutNow to the transfer target once it holds the whole log,
+    /// returning whether it was sent
+    fn advance_transfer(&self, follower: &NodeId) -> bool {
+        let mut transfer = self.transfer.lock();
+        let Some(transfer) = transfer.as_mut() else {
+            return false;
+        };
+        if &transfer.target != follower || transfer.timeout_sent {
+            return false;
+        }
+
+        let persistent = self.persistent.read();
+        let matched = self
+            .leader_state
+            .read()
+            .as_ref()
+            .and_then(|leader_state| leader_state.get_match_index(follower));
+        if matched != Some(persistent.log.last_index()) {
+            return false;
+        }
+
+        transfer.timeout_sent = true;
+        let request = TimeoutNowRequest::new(persistent.current_term, self.config.node_id.clone());
+        self.transport
+            .send(follower, RaftMessage::TimeoutNow(request));
+        true
+    }
+
+    /// Handle TimeoutNow RPC
+    async fn handle_timeout_now(&self, req: TimeoutNowRequest) {
+        if req.term != self.current_term()
+            || self.state.read().is_leader()
+            || !self.membership.read().is_voter(&self.config.node_id)
+        {
+            return;
+        }
+
+        info!("{} hands leadership over, starting election", req.leader_id);
+        self.start_election().await;
+        if self.election_state.read().votes.has_quorum() {
+            self.become_leader().await;
+        }
+    }
+
+    /// Handle linearizable read request
+    ///
+    /// The read index is the commit index, or this leader's first entry if
+    /// that is not committed yet. The read is served once a majority has
+    /// answered a heartbeat sent after the request, so no newer leader can
+    /// have committed anything, and the state machine has applied it.
+    async fn handle_read_index(&self, response_tx: mpsc::Sender<RaftResult<LogIndex>>) {
+        if let Err(e) = self.check_accepting_proposals() {
+            let _ = response_tx.send(Err(e)).await;
+            return;
+        }
+
+        let stepped_down = {
+            let leader_state = self.leader_state.read();
+            match leader_state.as_ref() {
+                Some(leader_state) => {
+                    let commit_index = self.volatile.read().commit_index;
+                    self.pending_reads.lock().push(PendingRead {
+                        index: commit_index.max(leader_state.term_start_index),
+                        seq: leader_state.last_seq + 1,
+                        response_tx,
+                    });
+                    None
+                }
+                None => Some(response_tx),
+            }
+        };
+        // Lost leadership since the check above
+        if let Some(response_tx) = stepped_down {
+            let _ = response_tx.send(Err(RaftError::NotLeader)).await;
+            return;
+        }
+
+        self.send_heartbeats().await;
+        self.resolve_reads();
+    }
+
+    /// Serve the reads whose leadership check and read index are satisfied
+    fn resolve_reads(&self) {
+        let mut pending_reads = self.pending_reads.lock();
+        if pending_reads.is_empty() {
+            return;
+        }
+
+        let membership = self.membership.read();
+        let leader_state = self.leader_state.read();
+        let Some(leader_state) = leader_state.as_ref() else {
+            return;
+        };
+        let confirmed_seq =
+            leader_state.quorum_acked_seq(membership.voters(), &self.config.node_id);
+        let last_applied = self.volatile.read().last_applied;
+
+        pending_reads.retain(|read| {
+            if read.seq > confirmed_seq || read.index > last_applied {
+                return true;
+            }
+            let _ = read.response_tx.try_send(Ok(read.index));
+            false
+        });
+    }
+
     /// Handle election timeout
     async fn handle_election_timeout(&self) {
         if self.state.read().is_leader() {
             return;
         }
 
+        // Learners and removed servers never stand for election
+        if !self.membership.read().is_voter(&self.config.node_id) {
+            self.election_state.write().reset_timer();
+            return;
+        }
+
         if !self.election_state.read().should_start_election() {
             return;
         }
 
         info!("Election timeout, starting election");
         self.start_election().await;
+
+        // A single-node cluster wins with its own vote
+        if self.election_state.read().votes.has_quorum() {
+            self.become_leader().await;
+        }
     }
 
     /// Start a new election
     async fn start_election(&self) {
+        let mut persistent = self.persistent.write();
+        if let Err(e) = self
+            .storage
+            .save_hard_state(persistent.current_term + 1, Some(&self.config.node_id))
+        {
+            error!("Failed to persist new term, not starting election: {}", e);
+            return;
+        }
+
         // Transition to candidate
         *self.state.write() = RaftState::Candidate;
 
         // Increment term and vote for self
-        let mut persistent = self.persistent.write();
         persistent.increment_term();
         persistent.vote_for(self.config.node_id.clone());
         let term = persistent.current_term;
@@ -467,17 +1161,18 @@ impl RaftNode {
             term, self.config.node_id
         );
 
-        // Send RequestVote RPCs to all other nodes
-        for member in &self.config.cluster_members {
+        // Send RequestVote RPCs to all other voters
+        let voters = self.membership.read().voters().to_vec();
+        for member in &voters {
             if member != &self.config.node_id {
-                let _request = RequestVoteRequest::new(
+                let request = RequestVoteRequest::new(
                     term,
                     self.config.node_id.clone(),
                     last_log_index,
                     last_log_term,
                 );
-                // TODO: Send request to member
-                debug!("Would send RequestVote to {}", member);
+                self.transport
+                    .send(member, RaftMessage::RequestVoteRequest(request));
             }
         }
     }
@@ -494,29 +1189,59 @@ impl RaftNode {
 
         let last_log_index = self.persistent.read().log.last_index();
         let other_members: Vec<_> = self
-            .config
-            .cluster_members
-            .iter()
+            .membership
+            .read()
+            .members()
             .filter(|m| *m != &self.config.node_id)
             .cloned()
             .collect();
 
         *self.leader_state.write() = Some(LeaderState::new(&other_members, last_log_index));
 
+        // Entries of earlier terms are committed through one of this term
+        let appended = {
+            let mut persistent = self.persistent.write();
+            let entry =
+                LogEntry::with_kind(persistent.current_term, last_log_index + 1, EntryKind::Noop);
+            self.storage
+                .append(std::slice::from_ref(&entry))
+                .and_then(|()| persistent.log.append_entries(vec![entry]))
+        };
+        if let Err(e) = appended {
+            error!("Failed to append entry for new term: {}", e);
+        }
+        self.advance_commit_index();
+        self.apply_committed();
+
         // Send initial heartbeats
         let _ = self.internal_tx.send(InternalMessage::HeartbeatTimeout);
     }
 
     /// Step down to follower (when discovering higher term)
-    async fn step_down(&self, term: Term) {
+    async fn step_down(&self, term: Term) -> RaftResult<()> {
         info!("Stepping down to follower for term {}", term);
 
-        *self.state.write() = RaftState::Follower;
-        *self.leader_state.write() = None;
+        self.resign();
         *self.current_leader.write() = None;
 
         let mut persistent = self.persistent.write();
+        if term > persistent.current_term {
+            self.storage.save_hard_state(term, None)?;
+        }
         persistent.update_term(term);
+        Ok(())
+    }
+
+    /// Stop acting as leader, answering the requests that needed it
+    fn resign(&self) {
+        *self.state.write() = RaftState::Follower;
+        *self.leader_state.write() = None;
+        for read in self.pending_reads.lock().drain(..) {
+            let _ = read.response_tx.try_send(Err(RaftError::NotLeader));
+        }
+        if let Some(transfer) = self.transfer.lock().take() {
+            let _ = transfer.response_tx.try_send(Ok(()));
+        }
     }
 
     /// Handle heartbeat timeout (for leaders)
@@ -525,26 +1250,251 @@ impl RaftNode {
             return;
         }
 
+        let expired = {
+            let mut transfer = self.transfer.lock();
+            match transfer.as_ref() {
+                Some(t) if Instant::now() >= t.deadline => transfer.take(),
+                _ => None,
+            }
+        };
+        if let Some(transfer) = expired {
+            warn!("Leadership transfer to {} timed out", transfer.target);
+            let _ = transfer
+                .response_tx
+                .try_send(Err(RaftError::ElectionTimeout));
+        }
+
         self.send_heartbeats().await;
     }
 
     /// Send heartbeats to all followers
+    ///
+    /// Each heartbeat carries the entries the follower is missing, or the
+    /// next snapshot chunk if it needs entries the log no longer holds.
     async fn send_heartbeats(&self) {
+        let members: Vec<NodeId> = self.membership.read().members().cloned().collect();
+        for member in &members {
+            if member != &self.config.node_id {
+                if let Some(request) = self.replication_request(member) {
+                    self.transport.send(member, request);
+                }
+            }
+        }
+    }
+
+    /// Build the next replication message for a follower
+    fn replication_request(&self, follower: &NodeId) -> Option<RaftMessage> {
         let persistent = self.persistent.read();
+        let mut leader_state_guard = self.leader_state.write();
+        let leader_state = leader_state_guard.as_mut()?;
+        let next_index = leader_state.get_next_index(follower)?;
         let term = persistent.current_term;
-        let commit_index = self.volatile.read().commit_index;
 
-        for member in &self.config.cluster_members {
-            if member != &self.config.node_id {
-                let request = AppendEntriesRequest::heartbeat(
+        if next_index <= persistent.log.base_index() {
+            let snapshot = persistent.log.snapshot()?;
+            let offset = match leader_state.snapshot_transfers.get(follower) {
+                Some(&(index, offset)) if index == snapshot.last_included_index => offset,
+                _ => 0,
+            };
+            leader_state
+                .snapshot_transfers
+                .insert(follower.clone(), (snapshot.last_included_index, offset));
+
+            return Some(RaftMessage::InstallSnapshotRequest(
+                InstallSnapshotRequest::new(
                     term,
                     self.config.node_id.clone(),
-                    commit_index,
-                );
-                // TODO: Send heartbeat to member
-                debug!("Would send heartbeat to {}", member);
+                    snapshot,
+                    offset,
+                    self.config.snapshot_chunk_size,
+                ),
+            ));
+        }
+
+        let prev_log_index = next_index - 1;
+        let prev_log_term = persistent.log.term_at(prev_log_index)?;
+        let mut budget = self.config.max_bytes_per_message;
+        let entries = persistent
+            .log
+            .entries_from(next_index)
+            .into_iter()
+            .take(self.config.max_entries_per_message)
+            .enumerate()
+            .take_while(|(i, entry)| {
+                let size = entry.command.len() + ENTRY_OVERHEAD;
+                let fits = *i == 0 || size <= budget;
+                budget = budget.saturating_sub(size);
+                fits
+            })
+            .map(|(_, entry)| entry)
+            .collect();
+
+        let mut request = AppendEntriesRequest::new(
+            term,
+            self.config.node_id.clone(),
+            prev_log_index,
+            prev_log_term,
+            entries,
+            self.volatile.read().commit_index,
+        );
+        request.seq = leader_state.next_seq();
+        Some(RaftMessage::AppendEntriesRequest(request))
+    }
+
+    /// Switch to the latest membership in the log if it changed
+    fn refresh_membership(&self, persistent: &PersistentState) {
+        let last_index = persistent.log.last_index();
+        let latest = persistent
+            .log
+            .membership_at(last_index)
+            .cloned()
+            .unwrap_or_else(|| Membership::new(self.config.cluster_members.clone()));
+
+        let mut membership = self.membership.write();
+        if *membership == latest {
+            return;
+        }
+        info!(
+            "Membership changed: voters {:?}, learners {:?}",
+            latest.voters(),
+            latest.learners()
+        );
+        self.election_state
+            .write()
+            .update_cluster_size(latest.voters().len());
+        if let Some(leader_state) = self.leader_state.write().as_mut() {
+            leader_state.set_followers(
+                latest.members().filter(|m| *m != &self.config.node_id),
+                last_index,
+            );
+        }
+        *membership = latest;
+    }
+
+    /// Apply committed entries to the state machine, then compact the log
+    /// if enough of it has been applied
+    ///
+    /// Nothing is applied while a snapshot is being taken; a later call
+    /// picks the entries up.
+    fn apply_committed(&self) {
+        let Ok(applying) = self.apply_lock.try_lock() else {
+            return;
+        };
+        loop {
+            let entry = {
+                let persistent = self.persistent.read();
+                let volatile = self.volatile.read();
+                if volatile.last_applied >= volatile.commit_index {
+                    break;
+                }
+                match persistent.log.get(volatile.last_applied + 1) {
+                    Some(entry) => entry.clone(),
+                    None => break,
+                }
+            };
+            let (index, term) = (entry.index, entry.term);
+
+            let applied = match &entry.kind {
+                EntryKind::Command => self.state_machine.apply(index, &entry.command),
+                EntryKind::Noop => Ok(()),
+                EntryKind::Membership(membership) => {
+                    // A leader removed from the voters leaves once the
+                    // change is committed
+                    if self.state.read().is_leader() && !membership.is_voter(&self.config.node_id) {
+                        info!("No longer a voter, stepping down");
+                        self.resign();
+                    }
+                    Ok(())
+                }
+            };
+            match &applied {
+                Err(RaftError::CommandRejected(reason)) => {
+                    warn!("Entry {} rejected by the state machine: {}", index, reason);
+                }
+                // Skipping the entry would leave this replica diverged from
+                // the others; it is retried on the next call
+                Err(e) => {
+                    error!("Failed to apply entry {}, retrying later: {}", index, e);
+                    break;
+                }
+                Ok(()) => {}
+            }
+            self.volatile.write().apply_entries(index);
+
+            // Answer the client, unless another leader replaced its entry
+            if let Some(pending) = self.pending.lock().remove(&index) {
+                let result = match applied {
+                    Ok(()) if pending.term == term => Ok(CommandResult { index, term }),
+                    Ok(()) => Err(RaftError::LeadershipLost(index)),
+                    Err(e) => Err(e),
+                };
+                let _ = pending.response_tx.try_send(result);
             }
         }
+        drop(applying);
+
+        self.maybe_compact();
+        self.resolve_reads();
+    }
+
+    /// Replace the applied prefix of the log with a snapshot once it grows
+    /// past `snapshot_threshold` entries
+    ///
+    /// The state machine is serialized on the blocking pool without holding
+    /// the Raft state, which only has to be locked again to install the
+    /// result. Entries are not applied in the meantime, so the snapshot is
+    /// the state at the `last_applied` it was started at.
+    fn maybe_compact(&self) {
+        let Ok(applying) = self.apply_lock.clone().try_lock_owned() else {
+            return;
+        };
+        let last_applied = self.volatile.read().last_applied;
+        let (last_included_term, configuration) = {
+            let persistent = self.persistent.read();
+            if last_applied.saturating_sub(persistent.log.base_index())
+                < self.config.snapshot_threshold
+            {
+                return;
+            }
+            let Some(term) = persistent.log.term_at(last_applied) else {
+                return;
+            };
+            let configuration = persistent
+                .log
+                .membership_at(last_applied)
+                .cloned()
+                .unwrap_or_else(|| Membership::new(self.config.cluster_members.clone()));
+            (term, configuration)
+        };
+
+        let state_machine = self.state_machine.clone();
+        let persistent = self.persistent.clone();
+        let storage = self.storage.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut data = Vec::new();
+            let data = state_machine.snapshot(&mut data).map(|()| data);
+            drop(applying);
+
+            let compacted = data.and_then(|data| {
+                let mut persistent = persistent.write();
+                // A snapshot from the leader may have been installed meanwhile
+                if persistent.log.base_index() >= last_applied {
+                    return Ok(());
+                }
+                let snapshot = Snapshot {
+                    last_included_index: last_applied,
+                    last_included_term,
+                    data,
+                    configuration,
+                };
+                storage.save_snapshot(&snapshot)?;
+                persistent.log.install_snapshot(snapshot)
+            });
+            match compacted {
+                Ok(()) => info!("Compacted log up to {}", last_applied),
+                Err(e) => error!("Failed to compact log up to {}: {}", last_applied, e),
+            }
+        });
     }
 
     /// Spawn election timer task
@@ -577,15 +1527,83 @@ impl RaftNode {
     }
 
     /// Submit a command to the Raft cluster
+    ///
+    /// Resolves once the command is committed and applied on this node.
+    /// Commands over [`MAX_COMMAND_LEN`] bytes could not be replicated and
+    /// are rejected.
     pub async fn submit_command(&self, data: Vec<u8>) -> RaftResult<CommandResult> {
-        let (tx, mut rx) = mpsc::channel(1);
-        let command = Command { data };
+        if data.len() > MAX_COMMAND_LEN {
+            return Err(RaftError::CommandTooLarge {
+                size: data.len(),
+                limit: MAX_COMMAND_LEN,
+            });
+        }
+        self.call(|response_tx| InternalMessage::ClientCommand {
+            command: Command { data },
+            response_tx,
+        })
+        .await
+    }
+
+    /// Add a server that receives the log without voting
+    pub async fn add_learner(&self, node_id: NodeId) -> RaftResult<CommandResult> {
+        self.change_membership(MembershipChange::AddLearner(node_id))
+            .await
+    }
+
+    /// Give a learner that has caught up with the log a vote
+    pub async fn promote_learner(&self, node_id: NodeId) -> RaftResult<CommandResult> {
+        self.change_membership(MembershipChange::PromoteLearner(node_id))
+            .await
+    }
+
+    /// Remove a voter or learner from the cluster
+    pub async fn remove_member(&self, node_id: NodeId) -> RaftResult<CommandResult> {
+        self.change_membership(MembershipChange::Remove(node_id))
+            .await
+    }
+
+    /// Replicate a membership change, resolving once it is applied
+    pub async fn change_membership(&self, change: MembershipChange) -> RaftResult<CommandResult> {
+        self.call(|response_tx| InternalMessage::ChangeMembership {
+            change,
+            response_tx,
+        })
+        .await
+    }
+
+    /// Hand leadership over to the voter `target`
+    ///
+    /// Resolves once this node has stepped down, or fails with
+    /// [`RaftError::ElectionTimeout`] if the target did not take over within
+    /// an election timeout.
+    pub async fn transfer_leadership(&self, target: NodeId) -> RaftResult<()> {
+        self.call(|response_tx| InternalMessage::TransferLeadership {
+            target,
+            response_tx,
+        })
+        .await
+    }
+
+    /// Confirm leadership for a linearizable read
+    ///
+    /// Resolves to a log index once this node has checked it is still the
+    /// leader and applied the log up to that index, so reading the state
+    /// machine afterwards observes every write committed before the call.
+    /// Nothing is written to the log.
+    pub async fn read_index(&self) -> RaftResult<LogIndex> {
+        self.call(|response_tx| InternalMessage::ReadIndex { response_tx })
+            .await
+    }
 
+    /// Send a request to the message loop and wait for its answer
+    async fn call<T>(
+        &self,
+        message: impl FnOnce(mpsc::Sender<RaftResult<T>>) -> InternalMessage,
+    ) -> RaftResult<T> {
+        let (tx, mut rx) = mpsc::channel(1);
         self.internal_tx
-            .send(InternalMessage::ClientCommand {
-                command,
-                response_tx: tx,
-            })
+            .send(message(tx))
             .map_err(|_| RaftError::Internal("Node stopped".to_string()))?;
 
         rx.recv()
@@ -603,15 +1621,60 @@ impl RaftNode {
         self.persistent.read().current_term
     }
 
+    /// Get the index of the last entry applied to the state machine
+    pub fn last_applied(&self) -> LogIndex {
+        self.volatile.read().last_applied
+    }
+
+    /// Time since this node's state machine was last known to have applied
+    /// everything the leader had committed, zero on the leader itself and
+    /// `None` if that never happened
+    ///
+    /// Only successful appends count, so a follower that rejects appends or
+    /// is still receiving a snapshot keeps aging. Bounds how far behind the
+    /// state machine of a follower can be.
+    pub fn caught_up_age(&self) -> Option<Duration> {
+        if self.state.read().is_leader() {
+            return Some(Duration::ZERO);
+        }
+        self.caught_up.read().map(|caught_up| caught_up.elapsed())
+    }
+
     /// Get current leader
     pub fn current_leader(&self) -> Option<NodeId> {
         self.current_leader.read().clone()
     }
+
+    /// Get this node's ID
+    pub fn node_id(&self) -> &NodeId {
+        &self.config.node_id
+    }
+
+    /// Get the membership of the latest configuration in the log
+    pub fn membership(&self) -> Membership {
+        self.membership.read().clone()
+    }
+
+    /// Whether `node_id` belongs to the latest configuration or to the
+    /// initial members in the config
+    pub fn is_known_peer(&self, node_id: &str) -> bool {
+        self.membership
+            .read()
+            .members()
+            .any(|member| member == node_id)
+            || self
+                .config
+                .cluster_members
+                .iter()
+                .any(|member| member == node_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state_machine::Recorder;
+    use crate::storage::RedbStorage;
 
     #[test]
     fn test_node_creation() {
@@ -628,4 +1691,608 @@ mod tests {
         assert_eq!(node.current_state(), RaftState::Follower);
         assert_eq!(node.current_term(), 0);
     }
+
+    fn members() -> Vec<NodeId> {
+        vec![
+            "node1".to_string(),
+            "node2".to_string(),
+            "node3".to_string(),
+        ]
+    }
+
+    /// Start a node on the storage file at `path`, as after a crash
+    fn restart(node_id: &str, path: &std::path::Path) -> RaftNode {
+        let storage = Arc::new(RedbStorage::open(path).unwrap());
+        RaftNode::with_storage(
+            RaftNodeConfig::new(node_id.to_string(), members()),
+            storage,
+            Arc::new(NoopStateMachine),
+        )
+        .unwrap()
+    }
+
+    fn recording_node(
+        node_id: &str,
+        storage: Arc<dyn RaftStorage>,
+        snapshot_threshold: u64,
+    ) -> (RaftNode, Arc<Recorder>) {
+        let mut config = RaftNodeConfig::new(node_id.to_string(), members());
+        config.snapshot_threshold = snapshot_threshold;
+        config.snapshot_chunk_size = 4;
+        let recorder = Arc::new(Recorder::default());
+        let node = RaftNode::with_storage(config, storage, recorder.clone()).unwrap();
+        (node, recorder)
+    }
+
+    /// Deliver AppendEntries the way `handle_rpc_message` does
+    async fn append(node: &RaftNode, req: AppendEntriesRequest) -> AppendEntriesResponse {
+        if req.term > node.current_term() {
+            node.step_down(req.term).await.unwrap();
+        }
+        node.handle_append_entries(req).await
+    }
+
+    /// Deliver RequestVote the way `handle_rpc_message` does
+    async fn request_vote(node: &RaftNode, req: RequestVoteRequest) -> RequestVoteResponse {
+        if req.term > node.current_term() {
+            node.step_down(req.term).await.unwrap();
+        }
+        node.handle_request_vote(req).await
+    }
+
+    fn entry(term: Term, index: LogIndex) -> LogEntry {
+        LogEntry::new(term, index, format!("cmd{}", index).into_bytes())
+    }
+
+    /// Wait for the background compaction of the log up to `index`
+    async fn compacted(node: &RaftNode, index: LogIndex) {
+        for _ in 0..500 {
+            if node.persistent.read().log.base_index() == index {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("log not compacted up to {}", index);
+    }
+
+    fn log_terms(node: &RaftNode) -> Vec<Term> {
+        let persistent = node.persistent.read();
+        (1..=persistent.log.last_index())
+            .map(|index| persistent.log.term_at(index).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_vote_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node2.raft");
+
+        let node = restart("node2", &path);
+        let vote = request_vote(&node, RequestVoteRequest::new(1, "node1".to_string(), 0, 0)).await;
+        assert!(vote.vote_granted);
+        drop(node);
+
+        // The restarted node must not vote twice in the same term
+        let node = restart("node2", &path);
+        assert_eq!(node.current_term(), 1);
+        let vote = request_vote(&node, RequestVoteRequest::new(1, "node3".to_string(), 0, 0)).await;
+        assert!(!vote.vote_granted);
+        let vote = request_vote(&node, RequestVoteRequest::new(1, "node1".to_string(), 0, 0)).await;
+        assert!(vote.vote_granted);
+    }
+
+    #[tokio::test]
+    async fn test_candidate_term_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node1.raft");
+
+        let node = restart("node1", &path);
+        node.start_election().await;
+        assert_eq!(node.current_term(), 1);
+        drop(node);
+
+        let node = restart("node1", &path);
+        assert_eq!(node.current_term(), 1);
+        assert_eq!(
+            node.persistent.read().voted_for.as_deref(),
+            Some("node1"),
+            "vote for self must be recovered"
+        );
+        let vote = request_vote(&node, RequestVoteRequest::new(1, "node2".to_string(), 0, 0)).await;
+        assert!(!vote.vote_granted);
+    }
+
+    #[tokio::test]
+    async fn test_follower_restarts_mid_replication() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node2.raft");
+        let leader = "node1".to_string();
+
+        // The first batch is acknowledged, then the follower crashes
+        let node = restart("node2", &path);
+        let resp = append(
+            &node,
+            AppendEntriesRequest::new(1, leader.clone(), 0, 0, vec![entry(1, 1), entry(1, 2)], 0),
+        )
+        .await;
+        assert_eq!(resp.match_index, Some(2));
+        drop(node);
+
+        // The leader retries from the start after the restart, then continues
+        let node = restart("node2", &path);
+        assert_eq!(log_terms(&node), vec![1, 1]);
+        let resp = append(
+            &node,
+            AppendEntriesRequest::new(
+                1,
+                leader.clone(),
+                0,
+                0,
+                vec![entry(1, 1), entry(1, 2), entry(1, 3)],
+                2,
+            ),
+        )
+        .await;
+        assert!(resp.success);
+        assert_eq!(resp.match_index, Some(3));
+        let resp = append(
+            &node,
+            AppendEntriesRequest::new(1, leader, 3, 1, vec![entry(1, 4)], 3),
+        )
+        .await;
+        assert_eq!(resp.match_index, Some(4));
+        drop(node);
+
+        // A new leader overwrites the uncommitted tail
+        let node = restart("node2", &path);
+        assert_eq!(log_terms(&node), vec![1, 1, 1, 1]);
+        let resp = append(
+            &node,
+            AppendEntriesRequest::new(2, "node3".to_string(), 2, 1, vec![entry(2, 3)], 2),
+        )
+        .await;
+        assert_eq!(resp.match_index, Some(3));
+        drop(node);
+
+        let node = restart("node2", &path);
+        assert_eq!(node.current_term(), 2);
+        assert_eq!(log_terms(&node), vec![1, 1, 2]);
+        assert_eq!(node.persistent.read().log.get(3).unwrap().command, b"cmd3");
+    }
+
+    #[tokio::test]
+    async fn test_leader_log_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node1.raft");
+
+        let node = restart("node1", &path);
+        node.start_election().await;
+        node.handle_request_vote_response("node2".to_string(), RequestVoteResponse::granted(1))
+            .await;
+        assert!(node.current_state().is_leader());
+
+        let mut rx = propose(&node, b"set x").await;
+        assert!(rx.try_recv().is_err(), "answered before commit");
+
+        // The leader's no-op entry for its term comes first
+        node.handle_append_entries_response(
+            "node2".to_string(),
+            AppendEntriesResponse::success(1, 2),
+        )
+        .await;
+        let result = rx.recv().await.unwrap().unwrap();
+        assert_eq!((result.index, result.term), (2, 1));
+        drop(node);
+
+        // The acknowledged entry is still there and restarts as a follower
+        let node = restart("node1", &path);
+        assert_eq!(node.current_state(), RaftState::Follower);
+        assert_eq!(node.current_term(), 1);
+        assert_eq!(node.persistent.read().log.get(2).unwrap().command, b"set x");
+    }
+
+    /// Submit a command to a leader, returning where its result will arrive
+    async fn propose(node: &RaftNode, data: &[u8]) -> mpsc::Receiver<RaftResult<CommandResult>> {
+        let (tx, rx) = mpsc::channel(1);
+        node.handle_client_command(
+            Command {
+                data: data.to_vec(),
+            },
+            tx,
+        )
+        .await;
+        rx
+    }
+
+    /// Exchange replication messages between a leader and a follower until
+    /// the follower has the leader's whole log and commit index
+    async fn catch_up(leader: &RaftNode, follower: &RaftNode, follower_id: &str) -> usize {
+        let id = follower_id.to_string();
+        let mut snapshot_chunks = 0;
+        for _ in 0..100 {
+            match leader.replication_request(&id).unwrap() {
+                RaftMessage::AppendEntriesRequest(req) => {
+                    let resp = append(follower, req).await;
+                    leader
+                        .handle_append_entries_response(id.clone(), resp)
+                        .await;
+                }
+                RaftMessage::InstallSnapshotRequest(req) => {
+                    if req.term > follower.current_term() {
+                        follower.step_down(req.term).await.unwrap();
+                    }
+                    snapshot_chunks += 1;
+                    let resp = follower.handle_install_snapshot(req).await;
+                    leader
+                        .handle_install_snapshot_response(id.clone(), resp)
+                        .await;
+                }
+                other => panic!("unexpected replication message {:?}", other),
+            }
+
+            let leader_commit = leader.volatile.read().commit_index;
+            if follower.persistent.read().log.last_index()
+                == leader.persistent.read().log.last_index()
+                && follower.volatile.read().last_applied == leader_commit
+            {
+                return snapshot_chunks;
+            }
+        }
+        panic!("{} did not catch up", follower_id);
+    }
+
+    #[tokio::test]
+    async fn test_applied_log_is_compacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("node2.raft");
+        let leader = "node1".to_string();
+
+        let storage = Arc::new(RedbStorage::open(&path).unwrap());
+        let (node, recorder) = recording_node("node2", storage, 3);
+        let entries = (1..=4).map(|index| entry(1, index)).collect();
+        append(
+            &node,
+            AppendEntriesRequest::new(1, leader.clone(), 0, 0, entries, 2),
+        )
+        .await;
+
+        // Two applied entries stay below the threshold
+        assert_eq!(recorder.commands().len(), 2);
+        assert_eq!(node.persistent.read().log.base_index(), 0);
+
+        append(&node, AppendEntriesRequest::new(1, leader, 4, 1, vec![], 4)).await;
+        assert_eq!(recorder.commands().len(), 4);
+        compacted(&node, 4).await;
+        {
+            let persistent = node.persistent.read();
+            assert_eq!(persistent.log.base_index(), 4);
+            assert_eq!(persistent.log.len(), 0);
+        }
+        drop(node);
+
+        // The state machine comes back from the snapshot, not the log
+        let storage = Arc::new(RedbStorage::open(&path).unwrap());
+        let (node, recorder) = recording_node("node2", storage, 3);
+        assert_eq!(
+            recorder.commands(),
+            (1..=4)
+                .map(|i| format!("cmd{}", i).into_bytes())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(node.volatile.read().last_applied, 4);
+        assert_eq!(node.persistent.read().log.base_index(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_apply_is_retried() {
+        let leader = "node1".to_string();
+        let (node, recorder) = recording_node("node2", Arc::new(MemoryStorage::new()), 100);
+
+        // A transient failure leaves the entry, and those after it, unapplied
+        recorder.fail_next(RaftError::StorageError("disk full".to_string()));
+        let entries = (1..=2).map(|index| entry(1, index)).collect();
+        append(
+            &node,
+            AppendEntriesRequest::new(1, leader.clone(), 0, 0, entries, 2),
+        )
+        .await;
+        assert!(recorder.commands().is_empty());
+        assert_eq!(node.volatile.read().last_applied, 0);
+
+        append(
+            &node,
+            AppendEntriesRequest::new(1, leader.clone(), 2, 1, vec![], 2),
+        )
+        .await;
+        assert_eq!(recorder.commands().len(), 2);
+        assert_eq!(node.volatile.read().last_applied, 2);
+
+        // A rejected command counts as applied
+        recorder.fail_next(RaftError::CommandRejected("invalid".to_string()));
+        append(
+            &node,
+            AppendEntriesRequest::new(1, leader, 2, 1, vec![entry(1, 3)], 3),
+        )
+        .await;
+        assert_eq!(recorder.commands().len(), 2);
+        assert_eq!(node.volatile.read().last_applied, 3);
+    }
+
+    #[tokio::test]
+    async fn test_entries_wait_for_snapshot() {
+        let leader = "node1".to_string();
+        let (node, recorder) = recording_node("node2", Arc::new(MemoryStorage::new()), 100);
+
+        // As while a snapshot is being taken
+        let snapshotting = node.apply_lock.clone().try_lock_owned().unwrap();
+        let entries = (1..=2).map(|index| entry(1, index)).collect();
+        append(
+            &node,
+            AppendEntriesRequest::new(1, leader.clone(), 0, 0, entries, 2),
+        )
+        .await;
+        assert!(recorder.commands().is_empty());
+        assert_eq!(node.volatile.read().last_applied, 0);
+
+        drop(snapshotting);
+        append(&node, AppendEntriesRequest::new(1, leader, 2, 1, vec![], 2)).await;
+        assert_eq!(recorder.commands().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_follower_installs_snapshot() {
+        let (leader, leader_sm) = recording_node("node1", Arc::new(MemoryStorage::new()), 3);
+        leader.start_election().await;
+        leader
+            .handle_request_vote_response("node3".to_string(), RequestVoteResponse::granted(1))
+            .await;
+        assert!(leader.current_state().is_leader());
+
+        // node3 acknowledges five commands; node2 has seen none of them
+        let mut proposals = Vec::new();
+        for i in 1..=5 {
+            proposals.push(propose(&leader, format!("set {}", i).as_bytes()).await);
+        }
+        leader
+            .handle_append_entries_response(
+                "node3".to_string(),
+                AppendEntriesResponse::success(1, 6),
+            )
+            .await;
+        for (i, rx) in proposals.iter_mut().enumerate() {
+            assert_eq!(rx.recv().await.unwrap().unwrap().index, i as LogIndex + 2);
+        }
+        assert_eq!(leader_sm.commands().len(), 5);
+        compacted(&leader, 6).await;
+        let mut last = propose(&leader, b"set 6").await;
+
+        // node2 must receive the compacted prefix as a snapshot
+        let (follower, follower_sm) = recording_node("node2", Arc::new(MemoryStorage::new()), 3);
+        let chunks = catch_up(&leader, &follower, "node2").await;
+        assert!(chunks > 1, "snapshot should span several chunks");
+
+        assert_eq!(follower.current_term(), 1);
+        assert_eq!(follower.persistent.read().log.base_index(), 6);
+        assert_eq!(leader.volatile.read().commit_index, 7);
+        assert_eq!(follower_sm.commands(), leader_sm.commands());
+        assert_eq!(follower_sm.commands().last().unwrap(), b"set 6");
+        assert_eq!(last.recv().await.unwrap().unwrap().index, 7);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_keeps_matching_suffix() {
+        let (follower, recorder) = recording_node("node2", Arc::new(MemoryStorage::new()), 100);
+        let entries = (1..=4).map(|index| entry(1, index)).collect();
+        append(
+            &follower,
+            AppendEntriesRequest::new(1, "node1".to_string(), 0, 0, entries, 0),
+        )
+        .await;
+
+        let snapshot = Snapshot {
+            last_included_index: 2,
+            last_included_term: 1,
+            data: b"cmd1\ncmd2".to_vec(),
+            configuration: Membership::new(members()),
+        };
+        let resp = follower
+            .handle_install_snapshot(InstallSnapshotRequest::new(
+                1,
+                "node1".to_string(),
+                &snapshot,
+                0,
+                1024,
+            ))
+            .await;
+        assert!(resp.success);
+        assert_eq!(resp.next_offset, None);
+
+        // Entries after the snapshot agree with it, so they are kept
+        assert_eq!(log_terms_after_snapshot(&follower), vec![1, 1]);
+        assert_eq!(recorder.commands().len(), 2);
+        assert_eq!(follower.volatile.read().commit_index, 2);
+    }
+
+    /// Elect node1 leader of a three-node cluster with node2's vote
+    async fn elected_leader() -> RaftNode {
+        let node = RaftNode::new(RaftNodeConfig::new("node1".to_string(), members()));
+        node.start_election().await;
+        node.handle_request_vote_response("node2".to_string(), RequestVoteResponse::granted(1))
+            .await;
+        assert!(node.current_state().is_leader());
+        node
+    }
+
+    async fn change(
+        node: &RaftNode,
+        change: MembershipChange,
+    ) -> mpsc::Receiver<RaftResult<CommandResult>> {
+        let (tx, rx) = mpsc::channel(1);
+        node.handle_membership_change(change, tx).await;
+        rx
+    }
+
+    #[tokio::test]
+    async fn test_membership_changes_one_at_a_time() {
+        let leader = elected_leader().await;
+        let add = |id: &str| MembershipChange::AddLearner(id.to_string());
+
+        // Nothing changes before the leader has committed its no-op entry
+        let mut rx = change(&leader, add("node4")).await;
+        assert!(matches!(
+            rx.recv().await,
+            Some(Err(RaftError::ConfigError(_)))
+        ));
+        leader
+            .handle_append_entries_response(
+                "node2".to_string(),
+                AppendEntriesResponse::success(1, 1),
+            )
+            .await;
+
+        // The new membership is used as soon as it is appended
+        let mut first = change(&leader, add("node4")).await;
+        assert!(leader.membership().is_learner("node4"));
+        assert_eq!(
+            leader
+                .leader_state
+                .read()
+                .as_ref()
+                .unwrap()
+                .get_next_index(&"node4".to_string()),
+            Some(3)
+        );
+        let mut second = change(&leader, add("node5")).await;
+        assert!(matches!(
+            second.recv().await,
+            Some(Err(RaftError::ConfigError(_)))
+        ));
+
+        leader
+            .handle_append_entries_response(
+                "node3".to_string(),
+                AppendEntriesResponse::success(1, 2),
+            )
+            .await;
+        assert_eq!(first.recv().await.unwrap().unwrap().index, 2);
+
+        // A learner that has not caught up keeps its place
+        let mut promote = change(
+            &leader,
+            MembershipChange::PromoteLearner("node4".to_string()),
+        )
+        .await;
+        assert!(matches!(
+            promote.recv().await,
+            Some(Err(RaftError::ConfigError(_)))
+        ));
+        assert_eq!(leader.membership().voters(), members());
+    }
+
+    /// Heartbeats that leave a follower's state machine short of the
+    /// leader's commit index do not make it fresh for bounded reads
+    #[tokio::test]
+    async fn test_caught_up_only_once_leader_commit_is_applied() {
+        let (follower, _) = recording_node("node2", Arc::new(MemoryStorage::new()), 100);
+        let leader = "node1".to_string();
+        assert_eq!(follower.caught_up_age(), None);
+
+        // Probes for a log the follower does not have are rejected
+        let probe = AppendEntriesRequest::new(1, leader.clone(), 5, 1, Vec::new(), 5);
+        assert!(!append(&follower, probe).await.success);
+        assert_eq!(follower.caught_up_age(), None);
+
+        // A matching heartbeat still leaves the committed entries unapplied
+        let heartbeat = AppendEntriesRequest::new(1, leader.clone(), 0, 0, Vec::new(), 5);
+        assert!(append(&follower, heartbeat).await.success);
+        assert_eq!(follower.caught_up_age(), None);
+
+        let entries = (1..=5).map(|index| entry(1, index)).collect();
+        let request = AppendEntriesRequest::new(1, leader, 0, 0, entries, 5);
+        assert!(append(&follower, request).await.success);
+        assert_eq!(follower.last_applied(), 5);
+        assert!(follower.caught_up_age().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_append_entries_batches_fit_byte_budget() {
+        let mut config = RaftNodeConfig::new("node1".to_string(), members());
+        config.max_bytes_per_message = 2 * (1000 + ENTRY_OVERHEAD);
+        let leader = RaftNode::new(config);
+        leader.start_election().await;
+        leader
+            .handle_request_vote_response("node2".to_string(), RequestVoteResponse::granted(1))
+            .await;
+        for _ in 0..5 {
+            propose(&leader, &[0; 1000]).await;
+        }
+        // An entry over the budget still goes out, on its own
+        propose(&leader, &[0; 5000]).await;
+
+        let (follower, _) = recording_node("node2", Arc::new(MemoryStorage::new()), 100);
+        let id = "node2".to_string();
+        let mut batches = Vec::new();
+        while follower.persistent.read().log.last_index()
+            < leader.persistent.read().log.last_index()
+        {
+            let Some(RaftMessage::AppendEntriesRequest(req)) = leader.replication_request(&id)
+            else {
+                panic!("expected AppendEntries");
+            };
+            if !req.entries.is_empty() {
+                batches.push(req.entries.len());
+            }
+            let resp = append(&follower, req).await;
+            leader
+                .handle_append_entries_response(id.clone(), resp)
+                .await;
+        }
+        // The leader's no-op, five small commands, then the large one
+        assert_eq!(batches, vec![2, 2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_command_rejected() {
+        let node = RaftNode::new(RaftNodeConfig::new("node1".to_string(), members()));
+        let result = node.submit_command(vec![0; MAX_COMMAND_LEN + 1]).await;
+        assert!(matches!(
+            result,
+            Err(RaftError::CommandTooLarge {
+                limit: MAX_COMMAND_LEN,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_index_waits_for_quorum() {
+        let leader = elected_leader().await;
+        let (tx, mut rx) = mpsc::channel(1);
+        leader.handle_read_index(tx).await;
+        assert!(
+            rx.try_recv().is_err(),
+            "served before leadership was confirmed"
+        );
+
+        // node2 answers the first heartbeat sent for the read, which also
+        // commits the leader's no-op entry
+        let mut resp = AppendEntriesResponse::success(1, 1);
+        resp.seq = 1;
+        leader
+            .handle_append_entries_response("node2".to_string(), resp)
+            .await;
+        assert_eq!(rx.recv().await.unwrap().unwrap(), 1);
+
+        let follower = RaftNode::new(RaftNodeConfig::new("node2".to_string(), members()));
+        let (tx, mut rx) = mpsc::channel(1);
+        follower.handle_read_index(tx).await;
+        assert!(matches!(rx.recv().await, Some(Err(RaftError::NotLeader))));
+    }
+
+    fn log_terms_after_snapshot(node: &RaftNode) -> Vec<Term> {
+        let persistent = node.persistent.read();
+        (persistent.log.base_index() + 1..=persistent.log.last_index())
+            .map(|index| persistent.log.term_at(index).unwrap())
+            .collect()
+    }
 }
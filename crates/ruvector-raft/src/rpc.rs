@@ -4,8 +4,9 @@
 //! - AppendEntries (log replication and heartbeat)
 //! - RequestVote (leader election)
 //! - InstallSnapshot (snapshot transfer)
+//! - TimeoutNow (leadership transfer)
 
-use crate::{log::LogEntry, log::Snapshot, LogIndex, NodeId, Term};
+use crate::{log::LogEntry, log::Snapshot, membership::Membership, LogIndex, NodeId, Term};
 use serde::{Deserialize, Serialize};
 
 /// AppendEntries RPC request
@@ -30,6 +31,10 @@ pub struct AppendEntriesRequest {
 
     /// Leader's commitIndex
     pub leader_commit: LogIndex,
+
+    /// Leader's sequence number for this request, echoed in the response
+    /// so the leader knows which requests a follower has acknowledged
+    pub seq: u64,
 }
 
 impl AppendEntriesRequest {
@@ -49,6 +54,7 @@ impl AppendEntriesRequest {
             prev_log_term,
             entries,
             leader_commit,
+            seq: 0,
         }
     }
 
@@ -61,6 +67,7 @@ impl AppendEntriesRequest {
             prev_log_term: 0,
             entries: Vec::new(),
             leader_commit,
+            seq: 0,
         }
     }
 
@@ -99,6 +106,9 @@ pub struct AppendEntriesResponse {
     /// Conflict information for faster log backtracking
     pub conflict_index: Option<LogIndex>,
     pub conflict_term: Option<Term>,
+
+    /// Sequence number of the request this answers
+    pub seq: u64,
 }
 
 impl AppendEntriesResponse {
@@ -110,6 +120,7 @@ impl AppendEntriesResponse {
             match_index: Some(match_index),
             conflict_index: None,
             conflict_term: None,
+            seq: 0,
         }
     }
 
@@ -125,6 +136,7 @@ impl AppendEntriesResponse {
             match_index: None,
             conflict_index,
             conflict_term,
+            seq: 0,
         }
     }
 
@@ -259,6 +271,9 @@ pub struct InstallSnapshotRequest {
 
     /// True if this is the last chunk
     pub done: bool,
+
+    /// Cluster membership at lastIncludedIndex
+    pub configuration: Membership,
 }
 
 impl InstallSnapshotRequest {
@@ -266,13 +281,14 @@ impl InstallSnapshotRequest {
     pub fn new(
         term: Term,
         leader_id: NodeId,
-        snapshot: Snapshot,
+        snapshot: &Snapshot,
         offset: u64,
         chunk_size: usize,
     ) -> Self {
         let data_len = snapshot.data.len();
-        let chunk_end = std::cmp::min(offset as usize + chunk_size, data_len);
-        let chunk = snapshot.data[offset as usize..chunk_end].to_vec();
+        let chunk_start = std::cmp::min(offset as usize, data_len);
+        let chunk_end = std::cmp::min(chunk_start + chunk_size, data_len);
+        let chunk = snapshot.data[chunk_start..chunk_end].to_vec();
         let done = chunk_end >= data_len;
 
         Self {
@@ -283,6 +299,7 @@ impl InstallSnapshotRequest {
             offset,
             data: chunk,
             done,
+            configuration: snapshot.configuration.clone(),
         }
     }
 
@@ -348,6 +365,26 @@ impl InstallSnapshotResponse {
     }
 }
 
+/// TimeoutNow RPC request
+///
+/// Sent by a leader handing leadership over to a caught-up follower, which
+/// starts an election immediately instead of waiting for its timeout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutNowRequest {
+    /// Leader's term
+    pub term: Term,
+
+    /// Leader's ID
+    pub leader_id: NodeId,
+}
+
+impl TimeoutNowRequest {
+    /// Create a new TimeoutNow request
+    pub fn new(term: Term, leader_id: NodeId) -> Self {
+        Self { term, leader_id }
+    }
+}
+
 /// RPC message envelope
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RaftMessage {
@@ -357,6 +394,7 @@ pub enum RaftMessage {
     RequestVoteResponse(RequestVoteResponse),
     InstallSnapshotRequest(InstallSnapshotRequest),
     InstallSnapshotResponse(InstallSnapshotResponse),
+    TimeoutNow(TimeoutNowRequest),
 }
 
 impl RaftMessage {
@@ -369,6 +407,7 @@ impl RaftMessage {
             RaftMessage::RequestVoteResponse(resp) => resp.term,
             RaftMessage::InstallSnapshotRequest(req) => req.term,
             RaftMessage::InstallSnapshotResponse(resp) => resp.term,
+            RaftMessage::TimeoutNow(req) => req.term,
         }
     }
 
@@ -439,4 +478,24 @@ mod tests {
         let denied = RequestVoteResponse::denied(1);
         assert!(!denied.vote_granted);
     }
+
+    #[test]
+    fn test_install_snapshot_chunks() {
+        let snapshot = Snapshot {
+            last_included_index: 10,
+            last_included_term: 2,
+            data: (0..10).collect(),
+            configuration: Membership::new(vec!["leader".to_string()]),
+        };
+
+        let first = InstallSnapshotRequest::new(3, "leader".to_string(), &snapshot, 0, 4);
+        assert_eq!(first.data, vec![0, 1, 2, 3]);
+        assert!(!first.done);
+
+        let last = InstallSnapshotRequest::new(3, "leader".to_string(), &snapshot, 8, 4);
+        assert_eq!(last.data, vec![8, 9]);
+        assert_eq!(last.last_included_index, 10);
+        assert_eq!(last.configuration, snapshot.configuration);
+        assert!(last.done);
+    }
 }
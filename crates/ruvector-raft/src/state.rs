@@ -174,6 +174,19 @@ pub struct LeaderState {
     /// For each server, index of highest log entry known to be replicated
     /// (initialized to 0, increases monotonically)
     pub match_index: HashMap<NodeId, LogIndex>,
+
+    /// For each server being sent a snapshot, the last index the snapshot
+    /// covers and the byte offset of the next chunk
+    pub snapshot_transfers: HashMap<NodeId, (LogIndex, u64)>,
+
+    /// Index of the first entry appended in this leader's term
+    pub term_start_index: LogIndex,
+
+    /// Sequence number of the last AppendEntries request sent
+    pub last_seq: u64,
+
+    /// For each server, the highest request sequence number it answered
+    pub acked_seq: HashMap<NodeId, u64>,
 }
 
 impl LeaderState {
@@ -192,9 +205,68 @@ impl LeaderState {
         Self {
             next_index,
             match_index,
+            snapshot_transfers: HashMap::new(),
+            term_start_index: last_log_index + 1,
+            last_seq: 0,
+            acked_seq: HashMap::new(),
+        }
+    }
+
+    /// Track exactly `members`, starting new ones at `last_log_index + 1`
+    pub fn set_followers<'a>(
+        &mut self,
+        members: impl IntoIterator<Item = &'a NodeId>,
+        last_log_index: LogIndex,
+    ) {
+        let members: Vec<&NodeId> = members.into_iter().collect();
+        self.next_index.retain(|node, _| members.contains(&node));
+        self.match_index.retain(|node, _| members.contains(&node));
+        self.snapshot_transfers
+            .retain(|node, _| members.contains(&node));
+        self.acked_seq.retain(|node, _| members.contains(&node));
+        for member in members {
+            self.next_index
+                .entry(member.clone())
+                .or_insert(last_log_index + 1);
+            self.match_index.entry(member.clone()).or_insert(0);
         }
     }
 
+    /// Highest index stored by a majority of `voters`, counting the
+    /// leader `self_id` as having stored `self_last_index`
+    pub fn quorum_match_index(
+        &self,
+        voters: &[NodeId],
+        self_id: &NodeId,
+        self_last_index: LogIndex,
+    ) -> LogIndex {
+        quorum_value(voters, |voter| match voter == self_id {
+            true => self_last_index,
+            false => self.match_index.get(voter).copied().unwrap_or(0),
+        })
+    }
+
+    /// Allocate the sequence number of the next AppendEntries request
+    pub fn next_seq(&mut self) -> u64 {
+        self.last_seq += 1;
+        self.last_seq
+    }
+
+    /// Record that a server answered the request numbered `seq`
+    pub fn ack_seq(&mut self, node_id: &NodeId, seq: u64) {
+        let acked = self.acked_seq.entry(node_id.clone()).or_insert(0);
+        *acked = (*acked).max(seq);
+    }
+
+    /// Highest sequence number answered by a majority of `voters`, the
+    /// leader `self_id` included
+    pub fn quorum_acked_seq(&self, voters: &[NodeId], self_id: &NodeId) -> u64 {
+        quorum_value(voters, |voter| match voter == self_id {
+            true => u64::MAX,
+            false => self.acked_seq.get(voter).copied().unwrap_or(0),
+        })
+    }
+
     /// Update next_index for a follower (decrement on failure)
     pub fn decrement_next_index(&mut self, node_id: &NodeId) {
         if let Some(index) = self.next_index.get_mut(node_id) {
@@ -204,6 +276,13 @@ impl LeaderState {
         }
     }
 
+    /// Move next_index back to `index`, e.g. just past a follower's last entry
+    pub fn rewind_next_index(&mut self, node_id: &NodeId, index: LogIndex) {
+        if let Some(next) = self.next_index.get_mut(node_id) {
+            *next = index.clamp(1, *next);
+        }
+    }
+
     /// Update both next_index and match_index for successful replication
     pub fn update_replication(&mut self, node_id: &NodeId, match_index: LogIndex) {
         self.match_index.insert(node_id.clone(), match_index);
@@ -235,6 +314,13 @@ impl LeaderState {
     }
 }
 
+/// Highest value reached by a majority of `voters`
+fn quorum_value(voters: &[NodeId], value: impl Fn(&NodeId) -> u64) -> u64 {
+    let mut values: Vec<u64> = voters.iter().map(value).collect();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+    values.get(voters.len() / 2).copied().unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,4 +400,33 @@ mod tests {
         let commit = leader_state.calculate_commit_index();
         assert_eq!(commit, 5); // Median of [3, 5, 8]
     }
+
+    #[test]
+    fn test_quorum_over_voters() {
+        let voters = vec![
+            "node1".to_string(),
+            "node2".to_string(),
+            "node3".to_string(),
+        ];
+        let learner = "node4".to_string();
+        let mut leader_state = LeaderState::new(&voters[1..], 10);
+        leader_state.set_followers(voters[1..].iter().chain([&learner]), 10);
+
+        // Learners never count towards the quorum
+        leader_state.update_replication(&learner, 10);
+        assert_eq!(leader_state.quorum_match_index(&voters, &voters[0], 10), 0);
+        leader_state.update_replication(&voters[2], 7);
+        assert_eq!(leader_state.quorum_match_index(&voters, &voters[0], 10), 7);
+
+        let seq = leader_state.next_seq();
+        leader_state.ack_seq(&learner, seq);
+        assert_eq!(leader_state.quorum_acked_seq(&voters, &voters[0]), 0);
+        leader_state.ack_seq(&voters[1], seq);
+        assert_eq!(leader_state.quorum_acked_seq(&voters, &voters[0]), seq);
+
+        // Removed followers are forgotten
+        leader_state.set_followers(&voters[1..2], 10);
+        assert_eq!(leader_state.get_match_index(&voters[2]), None);
+        assert_eq!(leader_state.get_next_index(&learner), None);
+    }
 }
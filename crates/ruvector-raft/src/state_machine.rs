@@ -0,0 +1,99 @@
+//! Application state machine driven by the Raft log
+//!
+//! Committed log entries are applied in index order. To keep the log from
+//! growing without bound, the node periodically asks the state machine for
+//! a snapshot of everything applied so far and discards the covered prefix
+//! of the log. Followers that fall behind that prefix receive the snapshot
+//! instead and restore their state machine from it.
+
+use crate::{LogIndex, RaftResult};
+use std::io::{Read, Write};
+
+/// Replicated application state
+pub trait StateMachine: Send + Sync {
+    /// Apply the committed command at `index`
+    ///
+    /// Called in index order, after every entry up to the latest snapshot
+    /// has been restored. [`RaftError::CommandRejected`] is for commands
+    /// that fail the same way on every node: it is returned to the client
+    /// that submitted the command, and the entry still counts as applied.
+    /// Any other error leaves the entry unapplied, and the node retries it
+    /// before applying anything after it, so a failed attempt must be safe
+    /// to repeat.
+    ///
+    /// [`RaftError::CommandRejected`]: crate::RaftError::CommandRejected
+    fn apply(&self, index: LogIndex, command: &[u8]) -> RaftResult<()>;
+
+    /// Serialize the state produced by all entries applied so far into `out`
+    ///
+    /// Large states should be written piece by piece as they are read,
+    /// rather than encoded whole first.
+    fn snapshot(&self, out: &mut dyn Write) -> RaftResult<()>;
+
+    /// Replace the whole state with one produced by [`snapshot`](Self::snapshot)
+    ///
+    /// The snapshot is stored before it is restored, and a node restarted
+    /// after a crash restores it again, so restoring must be safe to repeat.
+    fn restore(&self, data: &mut dyn Read) -> RaftResult<()>;
+}
+
+/// State machine for nodes that only replicate the log itself
+#[derive(Debug, Default)]
+pub(crate) struct NoopStateMachine;
+
+impl StateMachine for NoopStateMachine {
+    fn apply(&self, _index: LogIndex, _command: &[u8]) -> RaftResult<()> {
+        Ok(())
+    }
+
+    fn snapshot(&self, _out: &mut dyn Write) -> RaftResult<()> {
+        Ok(())
+    }
+
+    fn restore(&self, _data: &mut dyn Read) -> RaftResult<()> {
+        Ok(())
+    }
+}
+
+/// State machine recording every applied command
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    commands: parking_lot::Mutex<Vec<Vec<u8>>>,
+    failure: parking_lot::Mutex<Option<crate::RaftError>>,
+}
+
+#[cfg(test)]
+impl Recorder {
+    pub(crate) fn commands(&self) -> Vec<Vec<u8>> {
+        self.commands.lock().clone()
+    }
+
+    /// Fail the next command with `error` instead of recording it
+    pub(crate) fn fail_next(&self, error: crate::RaftError) {
+        *self.failure.lock() = Some(error);
+    }
+}
+
+#[cfg(test)]
+impl StateMachine for Recorder {
+    fn apply(&self, _index: LogIndex, command: &[u8]) -> RaftResult<()> {
+        if let Some(error) = self.failure.lock().take() {
+            return Err(error);
+        }
+        self.commands.lock().push(command.to_vec());
+        Ok(())
+    }
+
+    fn snapshot(&self, out: &mut dyn Write) -> RaftResult<()> {
+        out.write_all(&self.commands.lock().join(&b'\n'))?;
+        Ok(())
+    }
+
+    fn restore(&self, data: &mut dyn Read) -> RaftResult<()> {
+        let mut bytes = Vec::new();
+        data.read_to_end(&mut bytes)?;
+        *self.commands.lock() = bytes.split(|&b| b == b'\n').map(<[u8]>::to_vec).collect();
+        Ok(())
+    }
+}
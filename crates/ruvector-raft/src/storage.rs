@@ -0,0 +1,341 @@
+//! Durable storage for Raft persistent state
+//!
+//! Raft requires the current term, the vote, the log and the latest
+//! snapshot to be on stable storage before a node responds to an RPC. A [`RaftStorage`] receives
+//! every change to that state and must make it durable before returning:
+//! - [`MemoryStorage`] keeps the state in memory (tests, ephemeral nodes)
+//! - [`RedbStorage`] keeps it in a redb file, fsynced on every commit
+
+use crate::{
+    log::{LogEntry, RaftLog, Snapshot},
+    state::PersistentState,
+    LogIndex, NodeId, RaftError, RaftResult, Term,
+};
+use parking_lot::Mutex;
+use redb::{Database, TableDefinition};
+use std::path::Path;
+
+/// Stable storage for a node's term, vote and log
+///
+/// Every method must only return once the change is durable, so that a
+/// node restarted after a crash never forgets a vote or an acknowledged
+/// entry.
+pub trait RaftStorage: Send + Sync {
+    /// Load the stored state, or the initial state if nothing was stored
+    fn load(&self) -> RaftResult<PersistentState>;
+
+    /// Store the current term and the vote cast in it
+    fn save_hard_state(&self, term: Term, voted_for: Option<&NodeId>) -> RaftResult<()>;
+
+    /// Store entries appended to the end of the log
+    fn append(&self, entries: &[LogEntry]) -> RaftResult<()>;
+
+    /// Delete the entries at `index` and after it
+    fn truncate_from(&self, index: LogIndex) -> RaftResult<()>;
+
+    /// Store a snapshot, replacing the previous one, and delete the log
+    /// entries it covers
+    fn save_snapshot(&self, snapshot: &Snapshot) -> RaftResult<()>;
+}
+
+/// Storage that keeps the state in memory
+///
+/// The state survives a node being dropped and recreated on the same
+/// storage, but not the process exiting.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    state: Mutex<PersistentState>,
+}
+
+impl MemoryStorage {
+    /// Create empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RaftStorage for MemoryStorage {
+    fn load(&self) -> RaftResult<PersistentState> {
+        Ok(self.state.lock().clone())
+    }
+
+    fn save_hard_state(&self, term: Term, voted_for: Option<&NodeId>) -> RaftResult<()> {
+        let mut state = self.state.lock();
+        state.current_term = term;
+        state.voted_for = voted_for.cloned();
+        Ok(())
+    }
+
+    fn append(&self, entries: &[LogEntry]) -> RaftResult<()> {
+        self.state.lock().log.append_entries(entries.to_vec())
+    }
+
+    fn truncate_from(&self, index: LogIndex) -> RaftResult<()> {
+        self.state.lock().log.truncate_from(index)
+    }
+
+    fn save_snapshot(&self, snapshot: &Snapshot) -> RaftResult<()> {
+        self.state.lock().log.install_snapshot(snapshot.clone())
+    }
+}
+
+const HARD_STATE_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("hard_state");
+const LOG_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("log");
+const SNAPSHOT_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("snapshot");
+
+/// Key of the current term in HARD_STATE_TABLE
+const TERM_KEY: &str = "current_term";
+
+/// Key of the vote in HARD_STATE_TABLE
+const VOTE_KEY: &str = "voted_for";
+
+/// Key of the latest snapshot in SNAPSHOT_TABLE
+const SNAPSHOT_KEY: &str = "latest";
+
+/// Storage backed by a redb database file
+///
+/// Each call is one write transaction committed with immediate durability,
+/// so it is fsynced before the call returns.
+pub struct RedbStorage {
+    db: Database,
+}
+
+impl RedbStorage {
+    /// Create or open the storage file at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> RaftResult<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let db = Database::create(path)?;
+        let txn = db.begin_write()?;
+        {
+            txn.open_table(HARD_STATE_TABLE)?;
+            txn.open_table(LOG_TABLE)?;
+            txn.open_table(SNAPSHOT_TABLE)?;
+        }
+        txn.commit()?;
+
+        Ok(Self { db })
+    }
+}
+
+impl RaftStorage for RedbStorage {
+    fn load(&self) -> RaftResult<PersistentState> {
+        let txn = self.db.begin_read()?;
+        let hard_state = txn.open_table(HARD_STATE_TABLE)?;
+
+        let current_term = match hard_state.get(TERM_KEY)? {
+            Some(value) => decode::<Term>(value.value())?,
+            None => 0,
+        };
+        let voted_for = match hard_state.get(VOTE_KEY)? {
+            Some(value) => decode::<Option<NodeId>>(value.value())?,
+            None => None,
+        };
+
+        let mut log = RaftLog::new();
+        let snapshots = txn.open_table(SNAPSHOT_TABLE)?;
+        if let Some(value) = snapshots.get(SNAPSHOT_KEY)? {
+            log.install_snapshot(decode::<Snapshot>(value.value())?)?;
+        }
+
+        let entries = txn.open_table(LOG_TABLE)?;
+        for item in entries.range(log.base_index() + 1..)? {
+            let (_, value) = item?;
+            log.append_entries(vec![decode::<LogEntry>(value.value())?])?;
+        }
+
+        Ok(PersistentState {
+            current_term,
+            voted_for,
+            log,
+        })
+    }
+
+    fn save_hard_state(&self, term: Term, voted_for: Option<&NodeId>) -> RaftResult<()> {
+        let term = encode(&term)?;
+        let vote = encode(&voted_for)?;
+
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(HARD_STATE_TABLE)?;
+            table.insert(TERM_KEY, term.as_slice())?;
+            table.insert(VOTE_KEY, vote.as_slice())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn append(&self, entries: &[LogEntry]) -> RaftResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(LOG_TABLE)?;
+            for entry in entries {
+                table.insert(entry.index, encode(entry)?.as_slice())?;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn truncate_from(&self, index: LogIndex) -> RaftResult<()> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(LOG_TABLE)?;
+            table.retain_in(index.., |_, _| false)?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn save_snapshot(&self, snapshot: &Snapshot) -> RaftResult<()> {
+        let bytes = encode(snapshot)?;
+
+        // Store the snapshot and compact the log in one transaction
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(SNAPSHOT_TABLE)?;
+            table.insert(SNAPSHOT_KEY, bytes.as_slice())?;
+            let mut log = txn.open_table(LOG_TABLE)?;
+            log.retain_in(..=snapshot.last_included_index, |_, _| false)?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> RaftResult<Vec<u8>> {
+    Ok(bincode::encode_to_vec(
+        bincode::serde::Compat(value),
+        bincode::config::standard(),
+    )?)
+}
+
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> RaftResult<T> {
+    let (compat, _): (bincode::serde::Compat<T>, _) =
+        bincode::decode_from_slice(bytes, bincode::config::standard())?;
+    Ok(compat.0)
+}
+
+impl From<redb::DatabaseError> for RaftError {
+    fn from(err: redb::DatabaseError) -> Self {
+        RaftError::StorageError(err.to_string())
+    }
+}
+
+impl From<redb::StorageError> for RaftError {
+    fn from(err: redb::StorageError) -> Self {
+        RaftError::StorageError(err.to_string())
+    }
+}
+
+impl From<redb::TableError> for RaftError {
+    fn from(err: redb::TableError) -> Self {
+        RaftError::StorageError(err.to_string())
+    }
+}
+
+impl From<redb::TransactionError> for RaftError {
+    fn from(err: redb::TransactionError) -> Self {
+        RaftError::StorageError(err.to_string())
+    }
+}
+
+impl From<redb::CommitError> for RaftError {
+    fn from(err: redb::CommitError) -> Self {
+        RaftError::StorageError(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::membership::Membership;
+    use tempfile::tempdir;
+
+    fn entries(terms: &[Term]) -> Vec<LogEntry> {
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, &term)| LogEntry::new(term, i as LogIndex + 1, vec![i as u8]))
+            .collect()
+    }
+
+    fn exercise(storage: &dyn RaftStorage) {
+        storage
+            .save_hard_state(3, Some(&"node2".to_string()))
+            .unwrap();
+        storage.append(&entries(&[1, 1, 2])).unwrap();
+        storage.truncate_from(3).unwrap();
+        storage
+            .append(&[LogEntry::new(3, 3, b"new".to_vec())])
+            .unwrap();
+    }
+
+    fn compact(storage: &dyn RaftStorage) {
+        storage
+            .save_snapshot(&Snapshot {
+                last_included_index: 2,
+                last_included_term: 1,
+                data: b"state".to_vec(),
+                configuration: Membership::new(vec!["node1".to_string()]),
+            })
+            .unwrap();
+    }
+
+    fn check_compacted(state: &PersistentState) {
+        assert_eq!(state.log.base_index(), 2);
+        assert_eq!(state.log.base_term(), 1);
+        assert_eq!(state.log.snapshot().unwrap().data, b"state");
+        assert_eq!(state.log.len(), 1);
+        assert_eq!(state.log.last_index(), 3);
+    }
+
+    fn check(state: &PersistentState) {
+        assert_eq!(state.current_term, 3);
+        assert_eq!(state.voted_for.as_deref(), Some("node2"));
+        assert_eq!(state.log.last_index(), 3);
+        assert_eq!(state.log.term_at(2), Some(1));
+        assert_eq!(state.log.get(3).unwrap().command, b"new");
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        exercise(&storage);
+        check(&storage.load().unwrap());
+
+        compact(&storage);
+        check_compacted(&storage.load().unwrap());
+    }
+
+    #[test]
+    fn test_redb_storage_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("raft.db");
+
+        let storage = RedbStorage::open(&path).unwrap();
+        let fresh = storage.load().unwrap();
+        assert_eq!(fresh.current_term, 0);
+        assert!(fresh.voted_for.is_none());
+        assert!(fresh.log.is_empty());
+
+        exercise(&storage);
+        drop(storage);
+
+        let storage = RedbStorage::open(&path).unwrap();
+        check(&storage.load().unwrap());
+
+        compact(&storage);
+        drop(storage);
+        check_compacted(&RedbStorage::open(&path).unwrap().load().unwrap());
+    }
+}
@@ -0,0 +1,598 @@
+//! Network transport between Raft nodes
+//!
+//! A [`RaftTransport`] carries messages out of a node; messages arriving
+//! for a node are handed to [`RaftNode::receive`]. Delivery is best effort:
+//! Raft retries through heartbeats and elections, so a transport may drop
+//! messages it cannot deliver.
+//! - [`InProcessNetwork`] connects nodes in one process, with partitions
+//!   that tests can switch on and off
+//! - [`TcpTransport`] sends messages as length-prefixed bincode frames
+//!
+//! Neither transport authenticates peers or encrypts traffic. A TCP listener
+//! only turns away node IDs outside the cluster's membership, which any
+//! client can claim; run it on a trusted network.
+
+use crate::{node::RaftNode, rpc::RaftMessage, NodeId, RaftError, RaftResult};
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+/// Outgoing side of the network for one node
+pub trait RaftTransport: Send + Sync {
+    /// Queue `message` for delivery to node `to`
+    ///
+    /// Must not block: it is called while the node handles other messages.
+    fn send(&self, to: &NodeId, message: RaftMessage);
+}
+
+/// Transport for nodes that are not connected to any peer
+#[derive(Debug, Default)]
+pub(crate) struct NullTransport;
+
+impl RaftTransport for NullTransport {
+    fn send(&self, to: &NodeId, _message: RaftMessage) {
+        debug!("No transport configured, dropping message to {}", to);
+    }
+}
+
+/// Nodes running in the same process
+///
+/// Messages are delivered in the order they are sent. Isolating a node
+/// drops every message it sends or should receive until it is healed.
+#[derive(Default)]
+pub struct InProcessNetwork {
+    nodes: RwLock<HashMap<NodeId, Weak<RaftNode>>>,
+    isolated: RwLock<HashSet<NodeId>>,
+}
+
+impl InProcessNetwork {
+    /// Create an empty network
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Transport for the node `node_id` to send through this network
+    pub fn transport(self: &Arc<Self>, node_id: impl Into<NodeId>) -> Arc<InProcessTransport> {
+        Arc::new(InProcessTransport {
+            node_id: node_id.into(),
+            network: self.clone(),
+        })
+    }
+
+    /// Deliver messages addressed to `node` from now on
+    pub fn register(&self, node: &Arc<RaftNode>) {
+        self.nodes
+            .write()
+            .insert(node.node_id().clone(), Arc::downgrade(node));
+    }
+
+    /// Cut `node_id` off from the rest of the network
+    pub fn isolate(&self, node_id: &str) {
+        self.isolated.write().insert(node_id.to_string());
+    }
+
+    /// Reconnect a node cut off by [`isolate`](Self::isolate)
+    pub fn heal(&self, node_id: &str) {
+        self.isolated.write().remove(node_id);
+    }
+
+    fn deliver(&self, from: &NodeId, to: &NodeId, message: RaftMessage) {
+        {
+            let isolated = self.isolated.read();
+            if isolated.contains(from) || isolated.contains(to) {
+                return;
+            }
+        }
+
+        let node = self.nodes.read().get(to).and_then(Weak::upgrade);
+        match node {
+            Some(node) => node.receive(from.clone(), message),
+            None => debug!("Node {} is not on the network", to),
+        }
+    }
+}
+
+/// A node's connection to an [`InProcessNetwork`]
+pub struct InProcessTransport {
+    node_id: NodeId,
+    network: Arc<InProcessNetwork>,
+}
+
+impl RaftTransport for InProcessTransport {
+    fn send(&self, to: &NodeId, message: RaftMessage) {
+        self.network.deliver(&self.node_id, to, message);
+    }
+}
+
+/// Largest frame sent to or accepted from a peer (bytes)
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Delay before reconnecting to a peer that could not be reached
+const RECONNECT_DELAY: Duration = Duration::from_millis(100);
+
+/// Transport over TCP
+///
+/// Each peer gets one outgoing connection, opened lazily and reopened after
+/// errors. The connection starts with a frame holding the sender's node ID;
+/// every following frame holds one [`RaftMessage::to_bytes`] encoding. A
+/// frame is a big-endian `u32` length followed by that many bytes.
+/// Messages queued while a peer is unreachable are dropped.
+///
+/// The handshake is not authenticated: connections announcing a node ID
+/// that is not a member of the cluster are closed, but nothing proves the
+/// peer is the node it claims to be.
+pub struct TcpTransport {
+    peers: HashMap<NodeId, mpsc::UnboundedSender<Vec<u8>>>,
+}
+
+impl TcpTransport {
+    /// Create a transport sending from `node_id` to the peers at the given
+    /// addresses
+    ///
+    /// Must be called within a Tokio runtime, which runs one sender task
+    /// per peer.
+    pub fn new(node_id: NodeId, peers: HashMap<NodeId, SocketAddr>) -> Self {
+        let peers = peers
+            .into_iter()
+            .filter(|(peer, _)| *peer != node_id)
+            .map(|(peer, addr)| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                tokio::spawn(run_sender(node_id.clone(), peer.clone(), addr, rx));
+                (peer, tx)
+            })
+            .collect();
+
+        Self { peers }
+    }
+
+    /// Bind the listener that peers connect to
+    pub async fn bind(addr: SocketAddr) -> RaftResult<TcpRaftListener> {
+        let listener = TcpListener::bind(addr).await?;
+        Ok(TcpRaftListener { listener })
+    }
+}
+
+impl RaftTransport for TcpTransport {
+    fn send(&self, to: &NodeId, message: RaftMessage) {
+        let Some(peer) = self.peers.get(to) else {
+            warn!("Unknown peer {}, dropping message", to);
+            return;
+        };
+        match message.to_bytes() {
+            Ok(bytes) => {
+                let _ = peer.send(bytes);
+            }
+            Err(e) => warn!("Failed to encode message to {}: {}", to, e),
+        }
+    }
+}
+
+/// Listener accepting connections from [`TcpTransport`] peers
+pub struct TcpRaftListener {
+    listener: TcpListener,
+}
+
+impl TcpRaftListener {
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> RaftResult<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accept connections in the background, handing every message received
+    /// to `node`
+    pub fn serve(self, node: Arc<RaftNode>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, addr)) => {
+                        debug!("Accepted Raft connection from {}", addr);
+                        tokio::spawn(run_receiver(stream, node.clone()));
+                    }
+                    Err(e) => warn!("Failed to accept Raft connection: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Keep a connection to `peer` open and write queued messages to it
+async fn run_sender(
+    node_id: NodeId,
+    peer: NodeId,
+    addr: SocketAddr,
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+) {
+    loop {
+        let mut stream = match TcpStream::connect(addr).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                debug!("Cannot reach {} at {}: {}", peer, addr, e);
+                // Drop what was queued for the peer instead of replaying
+                // stale messages once it is back
+                while let Ok(_stale) = rx.try_recv() {}
+                if rx.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        info!("Connected to {} at {}", peer, addr);
+
+        if let Err(e) = write_frame(&mut stream, node_id.as_bytes()).await {
+            debug!("Lost connection to {}: {}", peer, e);
+            continue;
+        }
+        loop {
+            let Some(bytes) = rx.recv().await else {
+                return;
+            };
+            if let Err(e) = write_frame(&mut stream, &bytes).await {
+                debug!("Lost connection to {}: {}", peer, e);
+                break;
+            }
+        }
+    }
+}
+
+/// Read messages from one peer connection and hand them to `node`
+async fn run_receiver(mut stream: TcpStream, node: Arc<RaftNode>) {
+    let from = match read_frame(&mut stream).await {
+        Ok(frame) => match String::from_utf8(frame) {
+            Ok(from) => from,
+            Err(_) => {
+                warn!("Invalid node ID in Raft handshake");
+                return;
+            }
+        },
+        Err(e) => {
+            debug!("Raft handshake failed: {}", e);
+            return;
+        }
+    };
+    if !node.is_known_peer(&from) {
+        warn!("Rejecting Raft connection from unknown node {}", from);
+        return;
+    }
+
+    loop {
+        let frame = match read_frame(&mut stream).await {
+            Ok(frame) => frame,
+            Err(e) => {
+                debug!("Connection from {} closed: {}", from, e);
+                return;
+            }
+        };
+        match RaftMessage::from_bytes(&frame) {
+            Ok(message) => node.receive(from.clone(), message),
+            Err(e) => {
+                warn!("Invalid message from {}: {}", from, e);
+                return;
+            }
+        }
+    }
+}
+
+async fn write_frame(stream: &mut TcpStream, bytes: &[u8]) -> RaftResult<()> {
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|&len| len as usize <= MAX_FRAME_LEN)
+        .ok_or_else(|| RaftError::Internal(format!("Frame of {} bytes", bytes.len())))?;
+    stream.write_all(&len.to_be_bytes()).await?;
+    stream.write_all(bytes).await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut TcpStream) -> RaftResult<Vec<u8>> {
+    let len = stream.read_u32().await? as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(RaftError::Internal(format!(
+            "Frame of {} bytes exceeds the limit",
+            len
+        )));
+    }
+    let mut frame = vec![0; len];
+    stream.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::RaftNodeConfig;
+    use crate::state_machine::Recorder;
+    use crate::storage::MemoryStorage;
+    use std::future::Future;
+
+    fn members() -> Vec<NodeId> {
+        vec![
+            "node1".to_string(),
+            "node2".to_string(),
+            "node3".to_string(),
+        ]
+    }
+
+    fn node(node_id: &NodeId, transport: Arc<dyn RaftTransport>) -> (Arc<RaftNode>, Arc<Recorder>) {
+        let recorder = Arc::new(Recorder::default());
+        let node = RaftNode::with_storage(
+            RaftNodeConfig::new(node_id.clone(), members()),
+            Arc::new(MemoryStorage::new()),
+            recorder.clone(),
+        )
+        .unwrap()
+        .with_transport(transport);
+        (Arc::new(node), recorder)
+    }
+
+    /// Start a node connected to `network`
+    fn spawn_in_process(
+        network: &Arc<InProcessNetwork>,
+        node_id: &NodeId,
+    ) -> (Arc<RaftNode>, Arc<Recorder>) {
+        let (node, recorder) = node(node_id, network.transport(node_id.clone()));
+        network.register(&node);
+        tokio::spawn(node.clone().start());
+        (node, recorder)
+    }
+
+    /// Poll `condition` until it holds, failing after ten seconds
+    async fn eventually<T>(mut condition: impl FnMut() -> Option<T>) -> T {
+        for _ in 0..1000 {
+            if let Some(value) = condition() {
+                return value;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not reached in time");
+    }
+
+    fn leader_among<'a>(
+        nodes: impl IntoIterator<Item = &'a Arc<RaftNode>>,
+    ) -> Option<Arc<RaftNode>> {
+        nodes
+            .into_iter()
+            .find(|node| node.current_state().is_leader())
+            .cloned()
+    }
+
+    async fn with_timeout<F: Future>(future: F) -> F::Output {
+        tokio::time::timeout(Duration::from_secs(10), future)
+            .await
+            .expect("timed out")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_in_process_cluster_replicates_commands() {
+        let network = InProcessNetwork::new();
+        let nodes: Vec<_> = members()
+            .iter()
+            .map(|id| spawn_in_process(&network, id))
+            .collect();
+        let all = || nodes.iter().map(|(node, _)| node);
+
+        let leader = eventually(|| leader_among(all())).await;
+        for (i, command) in ["a", "b", "c"].iter().enumerate() {
+            let result = with_timeout(leader.submit_command(command.as_bytes().to_vec()))
+                .await
+                .unwrap();
+            // After the leader's no-op entry
+            assert_eq!(result.index, i as u64 + 2);
+
+            // Resolved only once the leader's state machine has the command
+            let (_, recorder) = nodes
+                .iter()
+                .find(|(n, _)| n.node_id() == leader.node_id())
+                .unwrap();
+            assert_eq!(recorder.commands().last().unwrap(), command.as_bytes());
+        }
+
+        let follower = all()
+            .find(|node| !node.current_state().is_leader())
+            .unwrap();
+        assert!(matches!(
+            follower.submit_command(b"x".to_vec()).await,
+            Err(RaftError::NotLeader)
+        ));
+        eventually(|| {
+            nodes
+                .iter()
+                .all(|(_, r)| r.commands().len() == 3)
+                .then_some(())
+        })
+        .await;
+
+        // The majority side of a partition elects a new leader and commits
+        network.isolate(leader.node_id());
+        let new_leader =
+            eventually(|| leader_among(all().filter(|node| node.node_id() != leader.node_id())))
+                .await;
+        with_timeout(new_leader.submit_command(b"d".to_vec()))
+            .await
+            .unwrap();
+
+        // Once healed, the old leader steps down and catches up
+        network.heal(leader.node_id());
+        let (_, old_recorder) = nodes
+            .iter()
+            .find(|(n, _)| n.node_id() == leader.node_id())
+            .unwrap();
+        eventually(|| (old_recorder.commands().len() == 4).then_some(())).await;
+        assert!(!leader.current_state().is_leader());
+        assert_eq!(old_recorder.commands().last().unwrap(), b"d");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_in_process_membership_changes() {
+        let network = InProcessNetwork::new();
+        let mut nodes: Vec<_> = members()
+            .iter()
+            .map(|id| spawn_in_process(&network, id))
+            .collect();
+        let leader = eventually(|| leader_among(nodes.iter().map(|(node, _)| node))).await;
+        with_timeout(leader.submit_command(b"a".to_vec()))
+            .await
+            .unwrap();
+
+        // A new server receives the log as a learner, then gets a vote
+        let node4 = "node4".to_string();
+        let (learner, learner_sm) = spawn_in_process(&network, &node4);
+        with_timeout(leader.add_learner(node4.clone()))
+            .await
+            .unwrap();
+        eventually(|| {
+            (learner.membership().is_learner(&node4)
+                && learner_sm.commands() == vec![b"a".to_vec()])
+            .then_some(())
+        })
+        .await;
+        // Let the leader hear that the learner is up to date
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        with_timeout(leader.promote_learner(node4.clone()))
+            .await
+            .unwrap();
+        eventually(|| (learner.membership().voters().len() == 4).then_some(())).await;
+        nodes.push((learner, learner_sm));
+
+        // A learner that has received nothing cannot be promoted
+        let node5 = "node5".to_string();
+        with_timeout(leader.add_learner(node5.clone()))
+            .await
+            .unwrap();
+        assert!(matches!(
+            with_timeout(leader.promote_learner(node5.clone())).await,
+            Err(RaftError::ConfigError(_))
+        ));
+        with_timeout(leader.remove_member(node5)).await.unwrap();
+
+        // The removed leader steps down and the others carry on without it
+        with_timeout(leader.remove_member(leader.node_id().clone()))
+            .await
+            .unwrap();
+        assert!(!leader.current_state().is_leader());
+        let remaining = || {
+            nodes
+                .iter()
+                .filter(|(node, _)| node.node_id() != leader.node_id())
+        };
+        let new_leader = eventually(|| leader_among(remaining().map(|(node, _)| node))).await;
+        with_timeout(new_leader.submit_command(b"b".to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(new_leader.membership().voters().len(), 3);
+        assert!(!new_leader.membership().is_voter(leader.node_id()));
+        eventually(|| {
+            remaining()
+                .all(|(_, recorder)| recorder.commands().len() == 2)
+                .then_some(())
+        })
+        .await;
+        assert!(!leader.current_state().is_leader());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_in_process_leadership_transfer_and_reads() {
+        let network = InProcessNetwork::new();
+        let nodes: Vec<_> = members()
+            .iter()
+            .map(|id| spawn_in_process(&network, id))
+            .collect();
+        let all = || nodes.iter().map(|(node, _)| node);
+        let leader = eventually(|| leader_among(all())).await;
+        let written = with_timeout(leader.submit_command(b"a".to_vec()))
+            .await
+            .unwrap();
+
+        // Reads are served by the leader once it has applied earlier writes
+        let read = with_timeout(leader.read_index()).await.unwrap();
+        assert!(read >= written.index);
+        let follower = all()
+            .find(|node| !node.current_state().is_leader())
+            .unwrap()
+            .clone();
+        assert!(matches!(
+            follower.read_index().await,
+            Err(RaftError::NotLeader)
+        ));
+
+        // Only voters can take over
+        assert!(matches!(
+            with_timeout(leader.transfer_leadership("node9".to_string())).await,
+            Err(RaftError::ConfigError(_))
+        ));
+        with_timeout(leader.transfer_leadership(follower.node_id().clone()))
+            .await
+            .unwrap();
+        eventually(|| follower.current_state().is_leader().then_some(())).await;
+        assert!(!leader.current_state().is_leader());
+        with_timeout(follower.submit_command(b"b".to_vec()))
+            .await
+            .unwrap();
+
+        // An isolated leader cannot confirm it is still leading
+        network.isolate(follower.node_id());
+        let stale = tokio::time::timeout(Duration::from_secs(1), follower.read_index()).await;
+        assert!(stale.is_err(), "isolated leader served a read");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_tcp_cluster_replicates_commands() {
+        let localhost: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let mut listeners = Vec::new();
+        let mut addrs = HashMap::new();
+        for id in members() {
+            let listener = TcpTransport::bind(localhost).await.unwrap();
+            addrs.insert(id.clone(), listener.local_addr().unwrap());
+            listeners.push((id, listener));
+        }
+
+        let mut nodes = Vec::new();
+        for (id, listener) in listeners {
+            let transport = Arc::new(TcpTransport::new(id.clone(), addrs.clone()));
+            let (node, recorder) = node(&id, transport);
+            listener.serve(node.clone());
+            tokio::spawn(node.clone().start());
+            nodes.push((node, recorder));
+        }
+
+        let leader = eventually(|| leader_among(nodes.iter().map(|(node, _)| node))).await;
+        for command in ["a", "b"] {
+            with_timeout(leader.submit_command(command.as_bytes().to_vec()))
+                .await
+                .unwrap();
+        }
+        eventually(|| {
+            nodes
+                .iter()
+                .all(|(_, r)| r.commands() == vec![b"a".to_vec(), b"b".to_vec()])
+                .then_some(())
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_tcp_listener_rejects_unknown_nodes() {
+        let listener = TcpTransport::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (node, _) = node(&"node1".to_string(), Arc::new(NullTransport));
+        listener.serve(node);
+
+        let mut intruder = TcpStream::connect(addr).await.unwrap();
+        write_frame(&mut intruder, b"node9").await.unwrap();
+        let closed = with_timeout(intruder.read_u8()).await;
+        assert!(closed.is_err(), "connection from an unknown node kept open");
+
+        let mut peer = TcpStream::connect(addr).await.unwrap();
+        write_frame(&mut peer, b"node2").await.unwrap();
+        let open = tokio::time::timeout(Duration::from_millis(200), peer.read_u8()).await;
+        assert!(open.is_err(), "connection from a member closed");
+    }
+}
@@ -0,0 +1,15 @@
+//! Generates the gRPC service from `proto/ruvector.proto` when the `grpc`
+//! feature is enabled
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/ruvector.proto");
+        // Use a bundled protoc unless one is set explicitly
+        if std::env::var_os("PROTOC").is_none() {
+            std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+        }
+        tonic_build::compile_protos("proto/ruvector.proto")?;
+    }
+    Ok(())
+}
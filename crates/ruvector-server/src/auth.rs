@@ -0,0 +1,422 @@
+//! API-key authentication and per-collection authorization
+//!
+//! Keys are sent as `Authorization: Bearer <key>` or in an `api-key` header.
+//! Each key is read-only or read-write and may be limited to some collections
+//! or aliases. A key limited to an alias may use whichever collection the
+//! alias currently points at, under either name. Creating or deleting
+//! collections and managing aliases takes an unlimited read-write key.
+//! `/health` stays open for liveness probes.
+
+use crate::{error::Error, state::AppState, Result};
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// What a key may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// Read points, search, scroll, count and describe collections
+    ReadOnly,
+    /// Also write points and payloads and manage collections
+    ReadWrite,
+}
+
+fn default_access() -> Access {
+    Access::ReadWrite
+}
+
+/// A static API key from the server configuration
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// The secret clients send
+    pub key: String,
+    /// What the key may do (defaults to read-write)
+    #[serde(default = "default_access")]
+    pub access: Access,
+    /// Collections or aliases the key is limited to; all if `None`
+    #[serde(default)]
+    pub collections: Option<Vec<String>>,
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("key", &"<redacted>")
+            .field("access", &self.access)
+            .field("collections", &self.collections)
+            .finish()
+    }
+}
+
+/// Permissions of the key that authenticated a request
+///
+/// Added to the request extensions, so handlers listing collections or
+/// aliases can leave out those the key may not see.
+#[derive(Debug, Clone)]
+pub struct Grant {
+    /// What the key may do
+    pub access: Access,
+    /// Collections or aliases the key is limited to; all if `None`
+    pub collections: Option<Vec<String>>,
+}
+
+impl Grant {
+    /// Whether the key may use the collection or alias `name`
+    pub fn allows(&self, state: &AppState, name: &str) -> bool {
+        let Some(scope) = &self.collections else {
+            return true;
+        };
+        let resolve = |name: &str| {
+            state
+                .manager
+                .resolve_alias(name)
+                .unwrap_or_else(|| name.to_string())
+        };
+        let target = resolve(name);
+        scope
+            .iter()
+            .any(|allowed| allowed == name || resolve(allowed) == target)
+    }
+}
+
+/// State of the authorization middleware
+#[derive(Clone)]
+pub struct AuthState {
+    keys: Arc<Vec<ApiKey>>,
+    app: AppState,
+}
+
+impl AuthState {
+    /// Check requests against `keys`
+    pub fn new(keys: Vec<ApiKey>, app: AppState) -> Self {
+        Self {
+            keys: Arc::new(keys),
+            app,
+        }
+    }
+
+    /// The configured key equal to `presented`
+    ///
+    /// Every key is compared in full, so the time taken does not reveal how
+    /// much of a key was guessed right.
+    fn find(&self, presented: &str) -> Option<&ApiKey> {
+        let mut found = None;
+        for key in self.keys.iter() {
+            if constant_time_eq(&key.key, presented) {
+                found = Some(key);
+            }
+        }
+        found
+    }
+
+    /// Check the key presented with a request against what it needs
+    pub(crate) fn grant(&self, presented: Option<&str>, target: &Target) -> Result<Grant> {
+        let key = presented.ok_or_else(|| Error::Unauthorized("Missing API key".to_string()))?;
+        let key = self
+            .find(key)
+            .ok_or_else(|| Error::Unauthorized("Invalid API key".to_string()))?;
+        let grant = Grant {
+            access: key.access,
+            collections: key.collections.clone(),
+        };
+
+        let permitted = match target {
+            Target::Public => true,
+            Target::Any(access) => grant.access >= *access,
+            Target::Collection(name, access) => {
+                grant.access >= *access && grant.allows(&self.app, name)
+            }
+            Target::Unlimited => grant.access == Access::ReadWrite && grant.collections.is_none(),
+        };
+        if !permitted {
+            return Err(Error::Forbidden(target.requirement()));
+        }
+        Ok(grant)
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+/// What a request needs from its key
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// Nothing; no key is needed
+    Public,
+    /// Any valid key with the access
+    Any(Access),
+    /// A key with the access that may use the collection or alias
+    Collection(String, Access),
+    /// A read-write key limited to no collections
+    Unlimited,
+}
+
+impl Target {
+    /// What a key needs for the request, for error messages
+    fn requirement(&self) -> String {
+        let access = |access: &Access| match access {
+            Access::ReadOnly => "read",
+            Access::ReadWrite => "write",
+        };
+        match self {
+            Target::Public => "No API key is required".to_string(),
+            Target::Any(a) => format!("API key lacks {} access", access(a)),
+            Target::Collection(name, a) => {
+                format!("API key lacks {} access to '{}'", access(a), name)
+            }
+            Target::Unlimited => {
+                "Creating or deleting collections and managing aliases requires an unrestricted read-write API key"
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Classify a request by method and path
+fn target(method: &Method, path: &str) -> Target {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let read = method == Method::GET || method == Method::HEAD;
+    let access = |read: bool| {
+        if read {
+            Access::ReadOnly
+        } else {
+            Access::ReadWrite
+        }
+    };
+
+    match segments.as_slice() {
+        ["health"] => Target::Public,
+        ["collections"] | ["aliases"] if read => Target::Any(Access::ReadOnly),
+        ["collections"] | ["aliases"] | ["aliases", _] => Target::Unlimited,
+        // Dropping a collection takes everything scoped to it with it
+        ["collections", _] if method == Method::DELETE => Target::Unlimited,
+        ["collections", name, rest @ ..] => {
+            // Lookups and searches are POSTs with a query body
+            let query = method == Method::POST
+                && matches!(
+                    rest,
+                    ["points"]
+                        | ["points", "search"]
+                        | ["points", "search", "batch"]
+                        | ["points", "scroll"]
+                        | ["points", "count"]
+                );
+            Target::Collection(name.to_string(), access(read || query))
+        }
+        _ => Target::Any(access(read)),
+    }
+}
+
+/// The key presented with a request, if any
+pub(crate) fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(value) = headers.get("api-key") {
+        return value.to_str().ok();
+    }
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Reject requests without a key that permits them
+///
+/// Authenticated requests carry the key's [`Grant`] in their extensions.
+pub async fn authorize(
+    State(auth): State<AuthState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response> {
+    let target = target(request.method(), request.uri().path());
+    if target == Target::Public {
+        return Ok(next.run(request).await);
+    }
+
+    let grant = auth.grant(presented_key(request.headers()), &target)?;
+    request.extensions_mut().insert(grant);
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{call_with_key, router};
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_targets() {
+        let collection = |name: &str, access| Target::Collection(name.to_string(), access);
+        let cases = [
+            (Method::GET, "/health", Target::Public),
+            (Method::GET, "/ready", Target::Any(Access::ReadOnly)),
+            (Method::GET, "/collections", Target::Any(Access::ReadOnly)),
+            (Method::POST, "/collections", Target::Unlimited),
+            (Method::PUT, "/aliases/live", Target::Unlimited),
+            (
+                Method::GET,
+                "/collections/docs",
+                collection("docs", Access::ReadOnly),
+            ),
+            (Method::DELETE, "/collections/docs", Target::Unlimited),
+            (
+                Method::PUT,
+                "/collections/docs/points",
+                collection("docs", Access::ReadWrite),
+            ),
+            (
+                Method::POST,
+                "/collections/docs/points",
+                collection("docs", Access::ReadOnly),
+            ),
+            (
+                Method::POST,
+                "/collections/docs/points/search/batch",
+                collection("docs", Access::ReadOnly),
+            ),
+            (
+                Method::POST,
+                "/collections/docs/points/delete",
+                collection("docs", Access::ReadWrite),
+            ),
+            (
+                Method::POST,
+                "/collections/docs/points/payload",
+                collection("docs", Access::ReadWrite),
+            ),
+        ];
+        for (method, path, expected) in cases {
+            assert_eq!(target(&method, path), expected, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn test_key_lookup() {
+        let headers = |name: &str, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                value.parse().unwrap(),
+            );
+            headers
+        };
+        assert_eq!(presented_key(&headers("api-key", "k1")), Some("k1"));
+        assert_eq!(
+            presented_key(&headers("authorization", "Bearer k1")),
+            Some("k1")
+        );
+        assert_eq!(presented_key(&headers("authorization", "Basic k1")), None);
+
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secret2"));
+    }
+
+    /// Router over a collection `docs` and an unrelated `other`, with an
+    /// admin key, a read-only key and a read-write key scoped to `docs`
+    async fn secured(dir: &std::path::Path) -> axum::Router {
+        let key = |key: &str, access, collections: Option<&[&str]>| ApiKey {
+            key: key.to_string(),
+            access,
+            collections: collections.map(|c| c.iter().map(|c| c.to_string()).collect()),
+        };
+        let app = router(
+            dir,
+            vec![
+                key("admin", Access::ReadWrite, None),
+                key("reader", Access::ReadOnly, None),
+                key("docs-writer", Access::ReadWrite, Some(&["docs"])),
+            ],
+        );
+        for name in ["docs", "other"] {
+            let (status, _) = call_with_key(
+                &app,
+                "POST",
+                "/collections",
+                Some(json!({ "name": name, "dimension": 2 })),
+                Some("admin"),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+        app
+    }
+
+    #[tokio::test]
+    async fn test_router_enforces_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = secured(dir.path()).await;
+        let upsert = || Some(json!({ "points": [{ "id": "a", "vector": [1.0, 0.0] }] }));
+
+        // Missing and unknown keys
+        let (status, error) = call_with_key(&app, "GET", "/collections/docs", None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error["code"], "unauthorized");
+        let (status, _) =
+            call_with_key(&app, "GET", "/collections/docs", None, Some("wrong")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = call_with_key(&app, "GET", "/health", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // A read-only key reads but does not write
+        let (status, _) =
+            call_with_key(&app, "GET", "/collections/docs", None, Some("reader")).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, error) = call_with_key(
+            &app,
+            "PUT",
+            "/collections/docs/points",
+            upsert(),
+            Some("reader"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error["code"], "forbidden");
+
+        // A scoped key writes to its collection only
+        let (status, _) = call_with_key(
+            &app,
+            "PUT",
+            "/collections/docs/points",
+            upsert(),
+            Some("docs-writer"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call_with_key(
+            &app,
+            "PUT",
+            "/collections/other/points",
+            upsert(),
+            Some("docs-writer"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (_, list) = call_with_key(&app, "GET", "/collections", None, Some("docs-writer")).await;
+        assert_eq!(list["collections"], json!(["docs"]));
+        let (_, ready) = call_with_key(&app, "GET", "/ready", None, Some("docs-writer")).await;
+        assert!(ready["details"].get("docs").is_some());
+        assert!(ready["details"].get("other").is_none());
+
+        // Deleting a collection takes an unlimited key, even for its own
+        for key in ["docs-writer", "reader"] {
+            let (status, _) =
+                call_with_key(&app, "DELETE", "/collections/docs", None, Some(key)).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", key);
+        }
+        let (status, _) =
+            call_with_key(&app, "DELETE", "/collections/docs", None, Some("admin")).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+}
@@ -1,10 +1,12 @@
 //! Error types for the ruvector server
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use ruvector_collections::CollectionError;
+use ruvector_core::RuvectorError;
 use serde_json::json;
 
 /// Result type for server operations
@@ -25,14 +27,39 @@ pub enum Error {
     #[error("Point not found: {0}")]
     PointNotFound(String),
 
+    /// Missing or unknown API key
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// API key that does not permit the request
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     /// Invalid request
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    /// Filter that does not follow the filter expression schema
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// One query of a batch search failed
+    #[error("Query {index} failed: {source}")]
+    BatchQuery {
+        /// Position of the query in the batch
+        index: usize,
+        /// Why it failed
+        source: Box<Error>,
+    },
+
     /// Core library error
     #[error("Core error: {0}")]
     Core(#[from] ruvector_core::RuvectorError),
 
+    /// Collection management error
+    #[error("{0}")]
+    Collection(#[from] CollectionError),
+
     /// Server error
     #[error("Server error: {0}")]
     Server(String),
@@ -50,27 +77,98 @@ pub enum Error {
     Internal(String),
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            Error::CollectionNotFound(_) | Error::PointNotFound(_) => {
-                (StatusCode::NOT_FOUND, self.to_string())
-            }
-            Error::CollectionExists(_) => (StatusCode::CONFLICT, self.to_string()),
-            Error::InvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            Error::Core(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+impl Error {
+    /// HTTP status and machine-readable error code
+    pub(crate) fn kind(&self) -> (StatusCode, &'static str) {
+        match self {
+            Error::CollectionNotFound(_) => (StatusCode::NOT_FOUND, "collection_not_found"),
+            Error::PointNotFound(_) => (StatusCode::NOT_FOUND, "point_not_found"),
+            Error::CollectionExists(_) => (StatusCode::CONFLICT, "collection_exists"),
+            Error::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized"),
+            Error::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden"),
+            Error::InvalidRequest(_) => (StatusCode::BAD_REQUEST, "invalid_request"),
+            Error::InvalidFilter(_) => (StatusCode::BAD_REQUEST, "invalid_filter"),
+            Error::BatchQuery { source, .. } => source.kind(),
+            Error::Core(e) => core_error_kind(e),
+            Error::Collection(e) => match e {
+                CollectionError::CollectionNotFound { .. } => {
+                    (StatusCode::NOT_FOUND, "collection_not_found")
+                }
+                CollectionError::CollectionAlreadyExists { .. } => {
+                    (StatusCode::CONFLICT, "collection_exists")
+                }
+                CollectionError::AliasNotFound { .. } => (StatusCode::NOT_FOUND, "alias_not_found"),
+                CollectionError::AliasAlreadyExists { .. } => {
+                    (StatusCode::CONFLICT, "alias_exists")
+                }
+                CollectionError::CollectionHasAliases { .. } => {
+                    (StatusCode::CONFLICT, "collection_has_aliases")
+                }
+                CollectionError::InvalidConfiguration { .. }
+                | CollectionError::InvalidAlias { .. }
+                | CollectionError::InvalidName { .. } => {
+                    (StatusCode::BAD_REQUEST, "invalid_request")
+                }
+                CollectionError::DatabaseError(e) => core_error_kind(e),
+                CollectionError::IoError(_) | CollectionError::SerializationError(_) => {
+                    (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+                }
+            },
             Error::Server(_) | Error::Internal(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+            }
+            Error::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "config_error"),
+            Error::Serialization(_) => (StatusCode::BAD_REQUEST, "serialization_error"),
+        }
+    }
+}
+
+/// HTTP status and error code of a core library error
+fn core_error_kind(e: &RuvectorError) -> (StatusCode, &'static str) {
+    match e {
+        RuvectorError::DimensionMismatch { .. }
+        | RuvectorError::InvalidParameter(_)
+        | RuvectorError::InvalidInput(_)
+        | RuvectorError::InvalidDimension(_) => (StatusCode::BAD_REQUEST, "invalid_request"),
+        RuvectorError::VectorNotFound(_) => (StatusCode::NOT_FOUND, "point_not_found"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+    }
+}
+
+impl Error {
+    /// Message for clients, and structured details if the error has any
+    pub(crate) fn message(&self) -> (String, Option<serde_json::Value>) {
+        match self {
+            Error::Core(e) => (e.to_string(), None),
+            Error::Serialization(e) => (e.to_string(), None),
+            Error::BatchQuery { index, source } => {
+                (source.to_string(), Some(json!({ "query": index })))
             }
-            Error::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            Error::Serialization(e) => (StatusCode::BAD_REQUEST, e.to_string()),
-        };
+            _ => (self.to_string(), None),
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let (status, code) = self.kind();
+        let (error_message, details) = self.message();
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": error_message,
+            "code": code,
             "status": status.as_u16(),
-        }));
+        });
+        if let Some(details) = details {
+            body["details"] = details;
+        }
 
-        (status, body).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
     }
 }
@@ -0,0 +1,874 @@
+//! gRPC API
+//!
+//! Serves the `Collections` and `Points` services of `proto/ruvector.proto`
+//! on a second port, over the same [`AppState`] as the REST routes. API keys
+//! and TLS apply as they do for REST; keys are sent in the `authorization`
+//! (`Bearer <key>`) or `api-key` metadata. Errors carry the REST error code
+//! in the `ruvector-error-code` metadata.
+
+use crate::auth::{presented_key, Access, AuthState, Grant, Target};
+use crate::routes::collections::{self, CreateCollectionRequest};
+use crate::routes::points::{self, parse_filter, SelectorRequest};
+use crate::{error::Error, metrics::Operation, state::AppState, Result, TlsConfig};
+use prost_types::{value::Kind, ListValue, Struct};
+use ruvector_core::types::HnswConfig;
+use ruvector_core::{
+    DistanceMetric, SearchParams, SearchResult, VectorEntry, VectorSelector, VectorSpaceConfig,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::net::SocketAddr;
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::transport::{server::Router, Server};
+use tonic::{Code, Request, Response, Status, Streaming};
+
+/// Protobuf messages, services and clients generated from
+/// `proto/ruvector.proto`
+pub mod proto {
+    tonic::include_proto!("ruvector.v1");
+}
+
+use proto::collections_server::{Collections, CollectionsServer};
+use proto::points_server::{Points, PointsServer};
+
+/// Serve the gRPC services on `addr`
+///
+/// Requests are checked against `auth` if given. Uses TLS if `tls` is given,
+/// which requires the `tls` feature.
+pub async fn serve(
+    addr: SocketAddr,
+    state: AppState,
+    auth: Option<AuthState>,
+    tls: Option<&TlsConfig>,
+) -> Result<()> {
+    let mut server = Server::builder();
+    if let Some(tls) = tls {
+        server = with_tls(server, tls).await?;
+    }
+
+    tracing::info!("Starting ruvector-server gRPC on {}", addr);
+
+    services(server, state, auth)
+        .serve(addr)
+        .await
+        .map_err(|e| Error::Server(format!("gRPC server error: {}", e)))
+}
+
+/// Add both services to `server`
+fn services(mut server: Server, state: AppState, auth: Option<AuthState>) -> Router {
+    let context = Context { state, auth };
+    server
+        .add_service(CollectionsServer::new(CollectionsService(context.clone())))
+        .add_service(PointsServer::new(PointsService(context)))
+}
+
+#[cfg(feature = "tls")]
+async fn with_tls(server: Server, tls: &TlsConfig) -> Result<Server> {
+    use tonic::transport::{Identity, ServerTlsConfig};
+
+    let read = |path: &std::path::Path| {
+        std::fs::read(path)
+            .map_err(|e| Error::Config(format!("Failed to load TLS certificate: {}", e)))
+    };
+    let identity = Identity::from_pem(read(&tls.cert_path)?, read(&tls.key_path)?);
+
+    // Another provider may already be installed, which is fine
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    server
+        .tls_config(ServerTlsConfig::new().identity(identity))
+        .map_err(|e| Error::Config(format!("Invalid TLS configuration: {}", e)))
+}
+
+#[cfg(not(feature = "tls"))]
+async fn with_tls(_server: Server, _tls: &TlsConfig) -> Result<Server> {
+    Err(Error::Config(
+        "TLS requires ruvector-server to be built with the `tls` feature".to_string(),
+    ))
+}
+
+impl From<Error> for Status {
+    fn from(e: Error) -> Self {
+        let (status, code) = e.kind();
+        let (message, details) = e.message();
+        let grpc_code = match status.as_u16() {
+            400 => Code::InvalidArgument,
+            401 => Code::Unauthenticated,
+            403 => Code::PermissionDenied,
+            404 => Code::NotFound,
+            409 if code == "collection_has_aliases" => Code::FailedPrecondition,
+            409 => Code::AlreadyExists,
+            _ => Code::Internal,
+        };
+
+        let mut status = Status::new(grpc_code, message);
+        let metadata = status.metadata_mut();
+        metadata.insert("ruvector-error-code", MetadataValue::from_static(code));
+        if let Some(index) = details.and_then(|d| d["query"].as_u64()) {
+            metadata.insert("ruvector-query-index", MetadataValue::from(index));
+        }
+        status
+    }
+}
+
+/// State shared by the services
+#[derive(Clone)]
+struct Context {
+    state: AppState,
+    auth: Option<AuthState>,
+}
+
+impl Context {
+    /// Check the request's key, if keys are configured
+    fn authorize(&self, metadata: &MetadataMap, target: Target) -> Result<Option<Grant>> {
+        let Some(auth) = &self.auth else {
+            return Ok(None);
+        };
+        let headers = metadata.clone().into_headers();
+        auth.grant(presented_key(&headers), &target).map(Some)
+    }
+
+    /// Check the request's key for `access` to `collection`
+    fn authorize_collection(
+        &self,
+        metadata: &MetadataMap,
+        collection: &str,
+        access: Access,
+    ) -> Result<()> {
+        self.authorize(metadata, Target::Collection(collection.to_string(), access))?;
+        Ok(())
+    }
+}
+
+/// The `Collections` service
+struct CollectionsService(Context);
+
+#[tonic::async_trait]
+impl Collections for CollectionsService {
+    async fn create(
+        &self,
+        request: Request<proto::CreateCollectionRequest>,
+    ) -> std::result::Result<Response<proto::CollectionInfo>, Status> {
+        self.0.authorize(request.metadata(), Target::Unlimited)?;
+        let req = request.into_inner();
+
+        let vectors = (!req.vectors.is_empty()).then(|| {
+            req.vectors
+                .iter()
+                .map(|(name, space)| {
+                    let config = VectorSpaceConfig {
+                        dimensions: space.dimension as usize,
+                        distance_metric: metric(space.metric()),
+                        hnsw_config: Some(HnswConfig::default()),
+                        quantization: None,
+                        multivector: space.multivector,
+                    };
+                    (name.clone(), config)
+                })
+                .collect()
+        });
+        let create = CreateCollectionRequest {
+            metric: Some(metric(req.metric())),
+            name: req.name,
+            dimension: req.dimension as usize,
+            vectors,
+            hnsw_config: req
+                .hnsw_config
+                .map(|c| decode(c, "hnsw_config"))
+                .transpose()?,
+            quantization: req
+                .quantization
+                .map(|c| decode(c, "quantization"))
+                .transpose()?,
+            on_disk_payload: req.on_disk_payload,
+        };
+
+        Ok(Response::new(collection_info(
+            create.create(&self.0.state)?,
+        )?))
+    }
+
+    async fn get(
+        &self,
+        request: Request<proto::GetCollectionRequest>,
+    ) -> std::result::Result<Response<proto::CollectionInfo>, Status> {
+        let name = &request.get_ref().name;
+        self.0
+            .authorize_collection(request.metadata(), name, Access::ReadOnly)?;
+        let info = collections::collection_info(&self.0.state, name)?;
+
+        Ok(Response::new(collection_info(info)?))
+    }
+
+    async fn list(
+        &self,
+        request: Request<proto::ListCollectionsRequest>,
+    ) -> std::result::Result<Response<proto::ListCollectionsResponse>, Status> {
+        let grant = self
+            .0
+            .authorize(request.metadata(), Target::Any(Access::ReadOnly))?;
+        let state = &self.0.state;
+        let mut collections = state.collection_names();
+        if let Some(grant) = grant {
+            collections.retain(|name| grant.allows(state, name));
+        }
+
+        Ok(Response::new(proto::ListCollectionsResponse {
+            collections,
+        }))
+    }
+
+    async fn delete(
+        &self,
+        request: Request<proto::DeleteCollectionRequest>,
+    ) -> std::result::Result<Response<proto::DeleteCollectionResponse>, Status> {
+        self.0.authorize(request.metadata(), Target::Unlimited)?;
+        let name = &request.get_ref().name;
+        self.0
+            .state
+            .manager
+            .delete_collection(name)
+            .map_err(Error::from)?;
+
+        Ok(Response::new(proto::DeleteCollectionResponse {}))
+    }
+}
+
+/// The `Points` service
+struct PointsService(Context);
+
+#[tonic::async_trait]
+impl Points for PointsService {
+    async fn upsert(
+        &self,
+        request: Request<proto::UpsertPointsRequest>,
+    ) -> std::result::Result<Response<proto::UpsertPointsResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadWrite)?;
+        let entries: Vec<VectorEntry> = req.points.into_iter().map(entry).collect();
+        let ids = self.0.state.with_db_recorded(
+            &req.collection,
+            Operation::Insert(entries.len()),
+            |db| Ok(db.insert_batch(entries)?),
+        )?;
+
+        Ok(Response::new(proto::UpsertPointsResponse { ids }))
+    }
+
+    async fn upsert_stream(
+        &self,
+        request: Request<Streaming<proto::UpsertPointsRequest>>,
+    ) -> std::result::Result<Response<proto::UpsertStreamResponse>, Status> {
+        let (metadata, _, mut stream) = request.into_parts();
+        self.0
+            .authorize(&metadata, Target::Any(Access::ReadWrite))?;
+
+        // The first message names the collection for the whole stream
+        let mut collection: Option<String> = None;
+        let mut upserted = 0;
+        while let Some(req) = stream.message().await? {
+            let name = match &collection {
+                Some(name) if req.collection.is_empty() || req.collection == *name => name,
+                Some(name) => {
+                    return Err(Error::InvalidRequest(format!(
+                        "Stream upserts into '{}', not '{}'",
+                        name, req.collection
+                    ))
+                    .into())
+                }
+                None => {
+                    self.0
+                        .authorize_collection(&metadata, &req.collection, Access::ReadWrite)?;
+                    collection.insert(req.collection)
+                }
+            };
+
+            let entries: Vec<VectorEntry> = req.points.into_iter().map(entry).collect();
+            upserted += entries.len() as u64;
+            self.0
+                .state
+                .with_db_recorded(name, Operation::Insert(entries.len()), |db| {
+                    Ok(db.insert_batch(entries)?)
+                })?;
+        }
+
+        Ok(Response::new(proto::UpsertStreamResponse { upserted }))
+    }
+
+    async fn delete(
+        &self,
+        request: Request<proto::DeletePointsRequest>,
+    ) -> std::result::Result<Response<proto::UpdateResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadWrite)?;
+
+        use proto::delete_points_request::Selector;
+        let selector = match req.selector {
+            Some(Selector::Points(ids)) => SelectorRequest {
+                points: Some(ids.ids),
+                filter: None,
+            },
+            Some(Selector::Filter(filter)) => SelectorRequest {
+                points: None,
+                filter: Some(Value::Object(from_struct(filter))),
+            },
+            None => SelectorRequest {
+                points: None,
+                filter: None,
+            },
+        }
+        .into_selector()?;
+        let updated = self
+            .0
+            .state
+            .with_db_recorded(&req.collection, Operation::Delete, |db| {
+                Ok(db.delete_points(&selector)?)
+            })?;
+
+        Ok(Response::new(proto::UpdateResponse {
+            updated: updated as u64,
+        }))
+    }
+
+    async fn get(
+        &self,
+        request: Request<proto::GetPointsRequest>,
+    ) -> std::result::Result<Response<proto::GetPointsResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadOnly)?;
+        let points = self
+            .0
+            .state
+            .with_db_recorded(&req.collection, Operation::Get, |db| {
+                let mut points = Vec::with_capacity(req.ids.len());
+                for id in &req.ids {
+                    points.extend(db.get(id)?.map(point));
+                }
+                Ok(points)
+            })?;
+
+        Ok(Response::new(proto::GetPointsResponse { points }))
+    }
+
+    async fn search(
+        &self,
+        request: Request<proto::SearchRequest>,
+    ) -> std::result::Result<Response<proto::SearchResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadOnly)?;
+        let collection = req.collection.clone();
+        let response = self
+            .0
+            .state
+            .with_db_recorded(&collection, Operation::Search, |db| search(db, req))?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn search_batch(
+        &self,
+        request: Request<proto::SearchBatchRequest>,
+    ) -> std::result::Result<Response<proto::SearchBatchResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadOnly)?;
+        let results = self
+            .0
+            .state
+            .with_db_recorded(&req.collection, Operation::Search, |db| {
+                req.searches
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, req)| {
+                        search(db, req).map_err(|e| Error::BatchQuery {
+                            index,
+                            source: Box::new(e),
+                        })
+                    })
+                    .collect::<Result<_>>()
+            })?;
+
+        Ok(Response::new(proto::SearchBatchResponse { results }))
+    }
+
+    async fn scroll(
+        &self,
+        request: Request<proto::ScrollRequest>,
+    ) -> std::result::Result<Response<proto::ScrollResponse>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        self.0
+            .authorize_collection(&metadata, &req.collection, Access::ReadOnly)?;
+        let filter = req
+            .filter
+            .map(|f| parse_filter(Value::Object(from_struct(f))))
+            .transpose()?;
+        let limit =
+            points::scroll_limit(req.limit.map_or_else(points::default_limit, |l| l as usize))?;
+        let page = self
+            .0
+            .state
+            .with_db_recorded(&req.collection, Operation::Scroll, |db| {
+                Ok(db.scroll(filter.as_ref(), req.offset.as_deref(), limit)?)
+            })?;
+
+        Ok(Response::new(proto::ScrollResponse {
+            points: page.points.into_iter().map(point).collect(),
+            next_page_offset: page.next_offset,
+        }))
+    }
+}
+
+/// Run one search through the REST search path
+fn search(
+    db: &ruvector_core::VectorDB,
+    req: proto::SearchRequest,
+) -> Result<proto::SearchResponse> {
+    use proto::search_request::Using;
+
+    let defaults = SearchParams::default();
+    let params = req.params.unwrap_or_default();
+    let with_vector = req.with_vector;
+    let query = points::SearchRequest {
+        vector: req.vector,
+        using: req.using.map(|using| match using {
+            Using::Named(name) => VectorSelector::Named(name),
+            Using::Fusion(fusion) => VectorSelector::Fusion(
+                fusion
+                    .vectors
+                    .into_iter()
+                    .map(|(name, vector)| (name, vector.values))
+                    .collect(),
+            ),
+            Using::MaxSim(name) => VectorSelector::MaxSim(name),
+        }),
+        k: req.limit.map_or_else(points::default_limit, |l| l as usize),
+        score_threshold: req.score_threshold,
+        filter: req.filter.map(|f| Value::Object(from_struct(f))),
+        params: SearchParams {
+            ef_search: params.ef_search.map(|ef| ef as usize),
+            exact: params.exact,
+            rescore: params.rescore.unwrap_or(defaults.rescore),
+            oversampling: params.oversampling.unwrap_or(defaults.oversampling),
+            score_threshold: None,
+        },
+    };
+
+    let results = points::search(db, query)?
+        .into_iter()
+        .map(|result| scored_point(result, with_vector))
+        .collect();
+    Ok(proto::SearchResponse { results })
+}
+
+fn metric(distance: proto::Distance) -> DistanceMetric {
+    match distance {
+        proto::Distance::Cosine => DistanceMetric::Cosine,
+        proto::Distance::Euclidean => DistanceMetric::Euclidean,
+        proto::Distance::DotProduct => DistanceMetric::DotProduct,
+        proto::Distance::Manhattan => DistanceMetric::Manhattan,
+    }
+}
+
+fn distance(metric: DistanceMetric) -> proto::Distance {
+    match metric {
+        DistanceMetric::Cosine => proto::Distance::Cosine,
+        DistanceMetric::Euclidean => proto::Distance::Euclidean,
+        DistanceMetric::DotProduct => proto::Distance::DotProduct,
+        DistanceMetric::Manhattan => proto::Distance::Manhattan,
+    }
+}
+
+fn collection_info(info: collections::CollectionInfo) -> Result<proto::CollectionInfo> {
+    let config = match serde_json::to_value(&info.config)? {
+        Value::Object(config) => Some(to_struct(config)),
+        _ => None,
+    };
+
+    Ok(proto::CollectionInfo {
+        name: info.name,
+        dimension: info.config.dimensions as u32,
+        metric: distance(info.config.distance_metric).into(),
+        vectors_count: info.stats.vectors_count as u64,
+        disk_size_bytes: info.stats.disk_size_bytes,
+        ram_size_bytes: info.stats.ram_size_bytes,
+        aliases: info.aliases,
+        created_at: info.created_at,
+        updated_at: info.updated_at,
+        config,
+    })
+}
+
+fn entry(point: proto::Point) -> VectorEntry {
+    VectorEntry {
+        id: (!point.id.is_empty()).then_some(point.id),
+        vector: point.vector.map(|v| v.values).unwrap_or_default(),
+        named_vectors: (!point.named_vectors.is_empty()).then(|| {
+            point
+                .named_vectors
+                .into_iter()
+                .map(|(name, vector)| (name, vector.values))
+                .collect()
+        }),
+        metadata: point
+            .payload
+            .map(|payload| from_struct(payload).into_iter().collect()),
+    }
+}
+
+fn point(entry: VectorEntry) -> proto::Point {
+    proto::Point {
+        id: entry.id.unwrap_or_default(),
+        vector: Some(proto::Vector {
+            values: entry.vector,
+        }),
+        named_vectors: entry
+            .named_vectors
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, values)| (name, proto::Vector { values }))
+            .collect(),
+        payload: entry
+            .metadata
+            .map(|payload| to_struct(payload.into_iter().collect())),
+    }
+}
+
+fn scored_point(result: SearchResult, with_vector: bool) -> proto::ScoredPoint {
+    proto::ScoredPoint {
+        id: result.id,
+        score: result.score,
+        vector: result
+            .vector
+            .filter(|_| with_vector)
+            .map(|values| proto::Vector { values }),
+        payload: result
+            .metadata
+            .map(|payload| to_struct(payload.into_iter().collect())),
+    }
+}
+
+/// Decode a REST-style JSON setting sent as a `Struct`
+fn decode<T: DeserializeOwned>(value: Struct, field: &str) -> Result<T> {
+    serde_json::from_value(Value::Object(from_struct(value)))
+        .map_err(|e| Error::InvalidRequest(format!("Invalid {}: {}", field, e)))
+}
+
+fn to_struct(map: Map<String, Value>) -> Struct {
+    Struct {
+        fields: map
+            .into_iter()
+            .map(|(key, value)| (key, to_value(value)))
+            .collect(),
+    }
+}
+
+fn to_value(value: Value) -> prost_types::Value {
+    let kind = match value {
+        Value::Null => Kind::NullValue(0),
+        Value::Bool(b) => Kind::BoolValue(b),
+        Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or_default()),
+        Value::String(s) => Kind::StringValue(s),
+        Value::Array(values) => Kind::ListValue(ListValue {
+            values: values.into_iter().map(to_value).collect(),
+        }),
+        Value::Object(map) => Kind::StructValue(to_struct(map)),
+    };
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn from_struct(value: Struct) -> Map<String, Value> {
+    value
+        .fields
+        .into_iter()
+        .map(|(key, value)| (key, from_value(value)))
+        .collect()
+}
+
+fn from_value(value: prost_types::Value) -> Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => Value::Null,
+        Some(Kind::BoolValue(b)) => Value::Bool(b),
+        Some(Kind::NumberValue(n)) => from_number(n),
+        Some(Kind::StringValue(s)) => Value::String(s),
+        Some(Kind::ListValue(list)) => {
+            Value::Array(list.values.into_iter().map(from_value).collect())
+        }
+        Some(Kind::StructValue(map)) => Value::Object(from_struct(map)),
+    }
+}
+
+/// Protobuf numbers are all doubles; whole numbers become JSON integers so
+/// they equal integer payload values written through REST
+fn from_number(n: f64) -> Value {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+    if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::ApiKey;
+    use proto::collections_client::CollectionsClient;
+    use proto::points_client::PointsClient;
+    use ruvector_collections::CollectionManager;
+    use serde_json::json;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Channel;
+
+    /// Serve both services over the collections in `dir` on a local port
+    async fn connect(
+        dir: &std::path::Path,
+        keys: Option<Vec<ApiKey>>,
+    ) -> (CollectionsClient<Channel>, PointsClient<Channel>) {
+        let state = AppState::new(CollectionManager::new(dir.to_path_buf()).unwrap());
+        let auth = keys.map(|keys| AuthState::new(keys, state.clone()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(
+            services(Server::builder(), state, auth)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let channel = Channel::from_shared(url).unwrap().connect().await.unwrap();
+        (
+            CollectionsClient::new(channel.clone()),
+            PointsClient::new(channel),
+        )
+    }
+
+    fn create_request(name: &str) -> proto::CreateCollectionRequest {
+        proto::CreateCollectionRequest {
+            name: name.to_string(),
+            dimension: 2,
+            metric: proto::Distance::Euclidean as i32,
+            ..Default::default()
+        }
+    }
+
+    fn point(id: &str, values: Vec<f32>) -> proto::Point {
+        proto::Point {
+            id: id.to_string(),
+            vector: Some(proto::Vector { values }),
+            ..Default::default()
+        }
+    }
+
+    fn with_key<T>(message: T, key: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {}", key).parse().unwrap());
+        request
+    }
+
+    #[tokio::test]
+    async fn test_create_upsert_search_scroll() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut collections, mut points) = connect(dir.path(), None).await;
+
+        let info = collections
+            .create(create_request("docs"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((info.name.as_str(), info.dimension), ("docs", 2));
+        let status = collections
+            .create(create_request("docs"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::AlreadyExists);
+
+        let upserted = points
+            .upsert(proto::UpsertPointsRequest {
+                collection: "docs".to_string(),
+                points: (0..5)
+                    .map(|i| point(&format!("p{}", i), vec![i as f32, 0.0]))
+                    .collect(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(upserted.ids.len(), 5);
+
+        let found = points
+            .search(proto::SearchRequest {
+                collection: "docs".to_string(),
+                vector: vec![3.1, 0.0],
+                limit: Some(3),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let ids: Vec<_> = found.results.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["p3", "p4", "p2"]);
+
+        let mut offset = None;
+        let mut seen = Vec::new();
+        loop {
+            let page = points
+                .scroll(proto::ScrollRequest {
+                    collection: "docs".to_string(),
+                    offset,
+                    limit: Some(2),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            seen.extend(page.points.into_iter().map(|p| p.id));
+            offset = page.next_page_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+        assert_eq!(seen, ["p0", "p1", "p2", "p3", "p4"]);
+
+        let status = points
+            .scroll(proto::ScrollRequest {
+                collection: "missing".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_upsert_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut collections, mut points) = connect(dir.path(), None).await;
+        collections.create(create_request("docs")).await.unwrap();
+
+        // Only the first message needs to name the collection
+        let requests = vec![
+            proto::UpsertPointsRequest {
+                collection: "docs".to_string(),
+                points: vec![point("a", vec![0.0, 1.0]), point("b", vec![1.0, 0.0])],
+            },
+            proto::UpsertPointsRequest {
+                collection: String::new(),
+                points: vec![point("c", vec![1.0, 1.0])],
+            },
+        ];
+        let response = points
+            .upsert_stream(tokio_stream::iter(requests))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.upserted, 3);
+
+        let info = collections
+            .get(proto::GetCollectionRequest {
+                name: "docs".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(info.vectors_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_authorization_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = vec![
+            ApiKey {
+                key: "admin".to_string(),
+                access: Access::ReadWrite,
+                collections: None,
+            },
+            ApiKey {
+                key: "reader".to_string(),
+                access: Access::ReadOnly,
+                collections: None,
+            },
+        ];
+        let (mut collections, mut points) = connect(dir.path(), Some(keys)).await;
+
+        let status = collections
+            .create(create_request("docs"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        let status = collections
+            .create(with_key(create_request("docs"), "wrong"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        collections
+            .create(with_key(create_request("docs"), "admin"))
+            .await
+            .unwrap();
+
+        let upsert = proto::UpsertPointsRequest {
+            collection: "docs".to_string(),
+            points: vec![point("a", vec![0.0, 1.0])],
+        };
+        let status = points
+            .upsert(with_key(upsert.clone(), "reader"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+        points.upsert(with_key(upsert, "admin")).await.unwrap();
+
+        let scroll = proto::ScrollRequest {
+            collection: "docs".to_string(),
+            ..Default::default()
+        };
+        let page = points
+            .scroll(with_key(scroll, "reader"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.points.len(), 1);
+
+        let status = collections
+            .delete(with_key(
+                proto::DeleteCollectionRequest {
+                    name: "docs".to_string(),
+                },
+                "reader",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+    }
+
+    #[test]
+    fn test_struct_round_trip() {
+        let value = json!({
+            "year": 2020,
+            "rating": 4.5,
+            "tags": ["a", "b"],
+            "nested": {"ok": true, "none": null},
+        });
+        let Value::Object(map) = value.clone() else {
+            unreachable!()
+        };
+        assert_eq!(Value::Object(from_struct(to_struct(map))), value);
+    }
+
+    #[test]
+    fn test_error_status() {
+        let status = Status::from(Error::CollectionNotFound("docs".to_string()));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(
+            status.metadata().get("ruvector-error-code").unwrap(),
+            "collection_not_found"
+        );
+
+        let status = Status::from(Error::BatchQuery {
+            index: 2,
+            source: Box::new(Error::InvalidFilter("bad".to_string())),
+        });
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.metadata().get("ruvector-query-index").unwrap(), "2");
+    }
+}
@@ -1,14 +1,26 @@
 //! ruvector-server: REST API server for rUvector vector database
 //!
-//! This crate provides a REST API server built on axum for interacting with rUvector.
+//! This crate provides a REST API server built on axum for interacting with rUvector,
+//! and with the `grpc` feature a tonic gRPC API on a second port.
+//! Collections and aliases are managed by a `CollectionManager` and persisted
+//! under the configured data directory, so they are reloaded on restart.
 
+pub mod auth;
 pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod metrics;
 pub mod routes;
 pub mod state;
+#[cfg(test)]
+mod test_support;
 
-use axum::{routing::get, Router};
+use auth::{ApiKey, AuthState};
+use axum::{middleware, routing::get, Router};
+use ruvector_collections::CollectionManager;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
@@ -29,6 +41,32 @@ pub struct Config {
     pub enable_cors: bool,
     /// Enable compression
     pub enable_compression: bool,
+    /// Directory holding the collections and aliases
+    #[serde(default = "default_data_dir")]
+    pub data_dir: PathBuf,
+    /// API keys accepted by the server; requests are not authenticated if
+    /// there are none
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    /// Serve HTTPS with these certificate files (requires the `tls` feature)
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Also serve the gRPC API on this port (requires the `grpc` feature)
+    #[serde(default)]
+    pub grpc_port: Option<u16>,
+}
+
+/// TLS certificate files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain
+    pub cert_path: PathBuf,
+    /// PEM file with the private key
+    pub key_path: PathBuf,
+}
+
+fn default_data_dir() -> PathBuf {
+    PathBuf::from("./data")
 }
 
 impl Default for Config {
@@ -38,6 +76,10 @@ impl Default for Config {
             port: 6333,
             enable_cors: true,
             enable_compression: true,
+            data_dir: default_data_dir(),
+            api_keys: Vec::new(),
+            tls: None,
+            grpc_port: None,
         }
     }
 }
@@ -45,7 +87,6 @@ impl Default for Config {
 /// Main server structure
 pub struct RuvectorServer {
     config: Config,
-    state: AppState,
 }
 
 impl RuvectorServer {
@@ -53,28 +94,51 @@ impl RuvectorServer {
     pub fn new() -> Self {
         Self {
             config: Config::default(),
-            state: AppState::new(),
         }
     }
 
     /// Create a new server instance with custom configuration
     pub fn with_config(config: Config) -> Self {
-        Self {
-            config,
-            state: AppState::new(),
-        }
+        Self { config }
+    }
+
+    /// Open the collections stored in the data directory
+    fn open_state(&self) -> Result<AppState> {
+        let manager = CollectionManager::new(self.config.data_dir.clone()).map_err(|e| {
+            Error::Config(format!(
+                "Failed to open data directory {}: {}",
+                self.config.data_dir.display(),
+                e
+            ))
+        })?;
+        tracing::info!(
+            "Loaded {} collections from {}",
+            manager.list_collections().len(),
+            self.config.data_dir.display()
+        );
+        Ok(AppState::new(manager))
     }
 
     /// Build the router with all routes
-    fn build_router(&self) -> Router {
+    fn build_router(&self, state: AppState) -> Router {
         let mut router = Router::new()
             .route("/health", get(routes::health::health_check))
             .route("/ready", get(routes::health::readiness))
+            .route("/metrics", get(metrics::metrics))
             .nest("/collections", routes::collections::routes())
+            .nest("/aliases", routes::aliases::routes())
             .merge(routes::points::routes())
-            .with_state(self.state.clone());
+            .with_state(state.clone());
 
         // Add middleware layers
+        if !self.config.api_keys.is_empty() {
+            let auth = AuthState::new(self.config.api_keys.clone(), state.clone());
+            router = router.layer(middleware::from_fn_with_state(auth, auth::authorize));
+        }
+
+        // Outside authorization, so rejected requests are counted too
+        router = router.layer(middleware::from_fn_with_state(state, metrics::track));
+
         router = router.layer(TraceLayer::new_for_http());
 
         if self.config.enable_compression {
@@ -94,28 +158,91 @@ impl RuvectorServer {
 
     /// Start the server
     ///
+    /// Serves REST on the configured port and, if `grpc_port` is set, gRPC
+    /// alongside it; returns when either stops.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the server fails to bind or start
+    /// Returns an error if the data directory or TLS certificate cannot be
+    /// opened, or the server fails to bind or start
     pub async fn start(self) -> Result<()> {
         let addr: SocketAddr = format!("{}:{}", self.config.host, self.config.port)
             .parse()
             .map_err(|e| Error::Config(format!("Invalid address: {}", e)))?;
 
-        let router = self.build_router();
+        let state = self.open_state()?;
+        let router = self.build_router(state.clone());
 
-        tracing::info!("Starting ruvector-server on {}", addr);
+        if self.config.api_keys.is_empty() && !addr.ip().is_loopback() {
+            tracing::warn!("No API keys configured; {} accepts any request", addr);
+        }
 
-        let listener = tokio::net::TcpListener::bind(addr)
-            .await
-            .map_err(|e| Error::Server(format!("Failed to bind to {}: {}", addr, e)))?;
+        let rest = serve_rest(addr, router, self.config.tls.as_ref());
+        match self.config.grpc_port {
+            Some(port) => {
+                let grpc = self.serve_grpc(SocketAddr::new(addr.ip(), port), state);
+                tokio::try_join!(rest, grpc)?;
+                Ok(())
+            }
+            None => rest.await,
+        }
+    }
 
-        axum::serve(listener, router)
-            .await
-            .map_err(|e| Error::Server(format!("Server error: {}", e)))?;
+    #[cfg(feature = "grpc")]
+    async fn serve_grpc(&self, addr: SocketAddr, state: AppState) -> Result<()> {
+        let auth = (!self.config.api_keys.is_empty())
+            .then(|| AuthState::new(self.config.api_keys.clone(), state.clone()));
+        grpc::serve(addr, state, auth, self.config.tls.as_ref()).await
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    async fn serve_grpc(&self, _addr: SocketAddr, _state: AppState) -> Result<()> {
+        Err(Error::Config(
+            "gRPC requires ruvector-server to be built with the `grpc` feature".to_string(),
+        ))
+    }
+}
 
-        Ok(())
+/// Serve `router` over HTTP, or HTTPS if `tls` is given
+async fn serve_rest(addr: SocketAddr, router: Router, tls: Option<&TlsConfig>) -> Result<()> {
+    if let Some(tls) = tls {
+        return serve_tls(addr, router, tls).await;
     }
+
+    tracing::info!("Starting ruvector-server on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| Error::Server(format!("Failed to bind to {}: {}", addr, e)))?;
+
+    axum::serve(listener, router)
+        .await
+        .map_err(|e| Error::Server(format!("Server error: {}", e)))
+}
+
+/// Serve `router` over HTTPS
+#[cfg(feature = "tls")]
+async fn serve_tls(addr: SocketAddr, router: Router, tls: &TlsConfig) -> Result<()> {
+    // Another provider may already be installed, which is fine
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let rustls_config =
+        axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+            .await
+            .map_err(|e| Error::Config(format!("Failed to load TLS certificate: {}", e)))?;
+
+    tracing::info!("Starting ruvector-server on https://{}", addr);
+
+    axum_server::bind_rustls(addr, rustls_config)
+        .serve(router.into_make_service())
+        .await
+        .map_err(|e| Error::Server(format!("Server error: {}", e)))
+}
+
+#[cfg(not(feature = "tls"))]
+async fn serve_tls(_addr: SocketAddr, _router: Router, _tls: &TlsConfig) -> Result<()> {
+    Err(Error::Config(
+        "TLS requires ruvector-server to be built with the `tls` feature".to_string(),
+    ))
 }
 
 impl Default for RuvectorServer {
@@ -0,0 +1,297 @@
+//! Prometheus metrics: request instrumentation and the `/metrics` endpoint
+
+use crate::{auth::Grant, state::AppState};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use ruvector_core::VectorDB;
+use ruvector_metrics::{gather_metrics, MetricsRecorder};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Minimum time between two recall estimates of a collection
+const RECALL_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Stored vectors sampled per recall estimate
+const RECALL_SAMPLES: usize = 16;
+
+/// Neighbours compared per sampled vector
+const RECALL_K: usize = 10;
+
+/// Metrics bookkeeping shared by the handlers
+#[derive(Default)]
+pub struct MetricsState {
+    /// Collections with exported gauges, so deleted ones can be dropped
+    labelled: Mutex<HashSet<String>>,
+    /// When the recall estimate of each collection was last started
+    recall_refreshed: DashMap<String, Instant>,
+}
+
+/// Point operations recorded in the search, insert and delete metrics, or
+/// in the point operation metrics under their label
+#[derive(Debug, Clone, Copy)]
+pub enum Operation {
+    /// One search request, batched or not
+    Search,
+    /// An upsert of this many points
+    Insert(usize),
+    /// A delete by id or filter
+    Delete,
+    /// A point lookup by id
+    Get,
+    /// A page of points
+    Scroll,
+    /// A count of points
+    Count,
+    /// A merge into the payload of points
+    SetPayload,
+    /// A replacement of the payload of points
+    OverwritePayload,
+    /// A removal of payload keys from points
+    DeletePayload,
+}
+
+impl Operation {
+    /// Record an operation on `collection` that took `latency_secs`
+    pub(crate) fn record(self, collection: &str, latency_secs: f64, success: bool) {
+        let operation = match self {
+            Operation::Search => {
+                return MetricsRecorder::record_search(collection, latency_secs, success)
+            }
+            Operation::Insert(count) => {
+                return MetricsRecorder::record_insert(collection, latency_secs, count, success)
+            }
+            Operation::Delete => return MetricsRecorder::record_delete(collection, success),
+            Operation::Get => "get",
+            Operation::Scroll => "scroll",
+            Operation::Count => "count",
+            Operation::SetPayload => "set_payload",
+            Operation::OverwritePayload => "overwrite_payload",
+            Operation::DeletePayload => "delete_payload",
+        };
+        MetricsRecorder::record_operation(collection, operation, latency_secs, success);
+    }
+}
+
+/// Record the route, status and latency of every request
+///
+/// Requests are labelled with the collection in their path, with aliases
+/// resolved so that switching an alias moves its traffic to the new
+/// collection's series.
+pub async fn track(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = req.method().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let collection = collection_in_path(req.uri().path())
+        .map(|name| state.metrics_label(name))
+        .unwrap_or_default();
+
+    let response = next.run(req).await;
+
+    MetricsRecorder::record_http_request(
+        &collection,
+        method.as_str(),
+        route.as_deref().unwrap_or("unmatched"),
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// The collection or alias a request path refers to
+fn collection_in_path(path: &str) -> Option<&str> {
+    let mut segments = path.trim_matches('/').split('/');
+    match (segments.next(), segments.next()) {
+        (Some("collections"), Some(name)) if !name.is_empty() => Some(name),
+        _ => None,
+    }
+}
+
+/// Metrics in Prometheus text format
+///
+/// Keys scoped to some collections only see the series of those.
+///
+/// GET /metrics
+pub async fn metrics(
+    State(state): State<AppState>,
+    grant: Option<Extension<Grant>>,
+) -> impl IntoResponse {
+    refresh(&state);
+
+    let mut text = gather_metrics();
+    if let Some(Extension(grant)) = grant {
+        text = retain_collections(&text, |name| grant.allows(&state, name));
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text)
+}
+
+/// Drop the samples labelled with a collection that `visible` rejects
+///
+/// Samples without a collection, or with an empty one, are kept.
+fn retain_collections(text: &str, visible: impl Fn(&str) -> bool) -> String {
+    let mut retained = String::with_capacity(text.len());
+    for line in text.lines() {
+        let keep = match collection_label(line) {
+            Some(name) => name.is_empty() || visible(name),
+            None => true,
+        };
+        if keep {
+            retained.push_str(line);
+            retained.push('\n');
+        }
+    }
+    retained
+}
+
+/// The `collection` label of a sample line
+fn collection_label(line: &str) -> Option<&str> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let labels = &line[line.find('{')?..];
+    let start = ["{collection=\"", ",collection=\""]
+        .iter()
+        .find_map(|label| labels.find(label).map(|at| at + label.len()))?;
+    let value = &labels[start..];
+    Some(&value[..value.find('"')?])
+}
+
+/// Update the collection and index gauges before a scrape
+fn refresh(state: &AppState) {
+    let names = state.collection_names();
+    MetricsRecorder::set_collections_count(names.len());
+    MetricsRecorder::set_uptime(state.health.health().uptime_seconds as f64);
+
+    let mut memory = 0;
+    for name in &names {
+        // Deleted since it was listed
+        let Ok(collection) = state.get_collection(name) else {
+            continue;
+        };
+        memory += refresh_collection(name, &collection.read().db);
+        state.metrics.refresh_recall(name, state);
+    }
+    MetricsRecorder::set_memory_usage(memory);
+
+    let current: HashSet<String> = names.into_iter().collect();
+    let mut labelled = state.metrics.labelled.lock();
+    for gone in labelled.difference(&current) {
+        MetricsRecorder::remove_collection(gone);
+        state.metrics.recall_refreshed.remove(gone);
+    }
+    *labelled = current;
+}
+
+/// Update the gauges of one collection, returning its index memory
+fn refresh_collection(name: &str, db: &VectorDB) -> usize {
+    if let Ok(count) = db.len() {
+        MetricsRecorder::set_vectors_count(name, count);
+    }
+
+    let Ok(stats) = db.index_stats(None) else {
+        return 0;
+    };
+    MetricsRecorder::set_tombstone_ratio(name, stats.tombstone_ratio);
+    if let Some(ef_search) = stats.ef_search {
+        MetricsRecorder::set_hnsw_ef_search(name, ef_search);
+    }
+
+    // Named vector spaces have their own indexes
+    let mut memory = stats.memory_bytes;
+    for space in db
+        .options()
+        .named_vectors
+        .iter()
+        .flat_map(|spaces| spaces.keys())
+    {
+        if let Ok(stats) = db.index_stats(Some(space)) {
+            memory += stats.memory_bytes;
+        }
+    }
+    MetricsRecorder::set_index_memory(name, memory);
+    memory
+}
+
+impl MetricsState {
+    /// Start a background recall estimate of a collection, unless one was
+    /// started within the refresh interval
+    ///
+    /// Estimates run exact searches, so scrapes report the last finished
+    /// estimate instead of waiting for a new one.
+    fn refresh_recall(&self, name: &str, state: &AppState) {
+        let now = Instant::now();
+        if let Some(last) = self.recall_refreshed.get(name) {
+            if now.duration_since(*last) < RECALL_REFRESH_INTERVAL {
+                return;
+            }
+        }
+        self.recall_refreshed.insert(name.to_string(), now);
+
+        let state = state.clone();
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || {
+            let Ok(collection) = state.get_collection(&name) else {
+                return;
+            };
+            let estimate = collection
+                .read()
+                .db
+                .estimate_recall(None, RECALL_SAMPLES, RECALL_K);
+            match estimate {
+                Ok(Some(recall)) => MetricsRecorder::set_recall_estimate(&name, recall),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Recall estimate of '{}' failed: {}", name, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_in_path() {
+        assert_eq!(collection_in_path("/collections/docs"), Some("docs"));
+        assert_eq!(
+            collection_in_path("/collections/docs/points/search"),
+            Some("docs")
+        );
+        assert_eq!(collection_in_path("/collections"), None);
+        assert_eq!(collection_in_path("/collections/"), None);
+        assert_eq!(collection_in_path("/aliases/docs"), None);
+        assert_eq!(collection_in_path("/metrics"), None);
+    }
+
+    #[test]
+    fn test_retain_collections() {
+        let text = "\
+# HELP ruvector_vectors_total Total vectors stored
+# TYPE ruvector_vectors_total gauge
+ruvector_vectors_total{collection=\"docs\"} 3
+ruvector_vectors_total{collection=\"other\"} 5
+ruvector_http_requests_total{method=\"GET\",collection=\"other\",status=\"200\"} 1
+ruvector_http_requests_total{method=\"GET\",collection=\"\",status=\"200\"} 2
+ruvector_collections_total 2
+";
+        assert_eq!(
+            retain_collections(text, |name| name == "docs"),
+            "\
+# HELP ruvector_vectors_total Total vectors stored
+# TYPE ruvector_vectors_total gauge
+ruvector_vectors_total{collection=\"docs\"} 3
+ruvector_http_requests_total{method=\"GET\",collection=\"\",status=\"200\"} 2
+ruvector_collections_total 2
+"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Collection alias endpoints
+
+use crate::{auth::Grant, state::AppState, Result};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, put},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+/// Alias creation request
+#[derive(Debug, Deserialize)]
+pub struct CreateAliasRequest {
+    /// Alias name
+    pub alias: String,
+    /// Target collection name
+    pub collection: String,
+}
+
+/// Alias switch request
+#[derive(Debug, Deserialize)]
+pub struct SwitchAliasRequest {
+    /// New target collection name
+    pub collection: String,
+}
+
+/// An alias and the collection it points at
+#[derive(Debug, Serialize)]
+pub struct AliasInfo {
+    /// Alias name
+    pub alias: String,
+    /// Target collection name
+    pub collection: String,
+}
+
+/// List of aliases response
+#[derive(Debug, Serialize)]
+pub struct AliasesList {
+    /// Aliases, sorted by name
+    pub aliases: Vec<AliasInfo>,
+}
+
+/// Create alias routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_aliases).post(create_alias))
+        .route("/:alias", put(switch_alias).delete(delete_alias))
+}
+
+/// List all aliases
+///
+/// GET /aliases
+async fn list_aliases(
+    State(state): State<AppState>,
+    grant: Option<Extension<Grant>>,
+) -> Result<impl IntoResponse> {
+    let mut aliases: Vec<AliasInfo> = state
+        .manager
+        .list_aliases()
+        .into_iter()
+        .map(|(alias, collection)| AliasInfo { alias, collection })
+        .collect();
+    if let Some(Extension(grant)) = grant {
+        aliases.retain(|info| grant.allows(&state, &info.alias));
+    }
+    aliases.sort_by(|a, b| a.alias.cmp(&b.alias));
+
+    Ok(Json(AliasesList { aliases }))
+}
+
+/// Create an alias for a collection
+///
+/// POST /aliases
+async fn create_alias(
+    State(state): State<AppState>,
+    Json(req): Json<CreateAliasRequest>,
+) -> Result<impl IntoResponse> {
+    state.manager.create_alias(&req.alias, &req.collection)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AliasInfo {
+            alias: req.alias,
+            collection: req.collection,
+        }),
+    ))
+}
+
+/// Point an existing alias at another collection
+///
+/// PUT /aliases/:alias
+async fn switch_alias(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+    Json(req): Json<SwitchAliasRequest>,
+) -> Result<impl IntoResponse> {
+    state.manager.switch_alias(&alias, &req.collection)?;
+
+    Ok(Json(AliasInfo {
+        alias,
+        collection: req.collection,
+    }))
+}
+
+/// Delete an alias
+///
+/// DELETE /aliases/:alias
+async fn delete_alias(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+) -> Result<impl IntoResponse> {
+    state.manager.delete_alias(&alias)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{call, router};
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_aliases_reload_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = router(dir.path(), Vec::new());
+        for name in ["docs_v1", "docs_v2"] {
+            let (status, _) = call(
+                &app,
+                "POST",
+                "/collections",
+                Some(json!({ "name": name, "dimension": 2 })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::CREATED);
+        }
+
+        let (status, alias) = call(
+            &app,
+            "POST",
+            "/aliases",
+            Some(json!({ "alias": "docs", "collection": "docs_v1" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(alias, json!({ "alias": "docs", "collection": "docs_v1" }));
+
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/aliases",
+            Some(json!({ "alias": "docs", "collection": "docs_v2" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error["code"], "alias_exists");
+
+        // An aliased collection is described under its own name
+        let (_, info) = call(&app, "GET", "/collections/docs", None).await;
+        assert_eq!(info["name"], "docs_v1");
+        assert_eq!(info["aliases"], json!(["docs"]));
+
+        let (status, _) = call(
+            &app,
+            "PUT",
+            "/aliases/docs",
+            Some(json!({ "collection": "docs_v2" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        drop(app);
+
+        let app = router(dir.path(), Vec::new());
+        let (status, list) = call(&app, "GET", "/aliases", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            list["aliases"],
+            json!([{ "alias": "docs", "collection": "docs_v2" }])
+        );
+        let (_, info) = call(&app, "GET", "/collections/docs", None).await;
+        assert_eq!(info["name"], "docs_v2");
+
+        let (status, _) = call(&app, "DELETE", "/aliases/docs", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, error) = call(&app, "DELETE", "/aliases/docs", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "alias_not_found");
+        drop(app);
+
+        let app = router(dir.path(), Vec::new());
+        let (_, list) = call(&app, "GET", "/aliases", None).await;
+        assert_eq!(list["aliases"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_alias_needs_existing_collection() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = router(dir.path(), Vec::new());
+
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/aliases",
+            Some(json!({ "alias": "docs", "collection": "missing" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "collection_not_found");
+    }
+}
@@ -1,16 +1,18 @@
 //! Collection management endpoints
 
-use crate::{error::Error, state::AppState, Result};
+use crate::{auth::Grant, state::AppState, Result};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
     routing::{get, post},
-    Json, Router,
+    Extension, Json, Router,
 };
-use ruvector_core::{types::DbOptions, DistanceMetric, VectorDB};
+use ruvector_collections::{CollectionConfig, CollectionStats};
+use ruvector_core::types::{HnswConfig, QuantizationConfig};
+use ruvector_core::{DistanceMetric, VectorSpaceConfig};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
 
 /// Collection creation request
 #[derive(Debug, Deserialize)]
@@ -21,6 +23,14 @@ pub struct CreateCollectionRequest {
     pub dimension: usize,
     /// Distance metric (optional, defaults to Cosine)
     pub metric: Option<DistanceMetric>,
+    /// Additional named vector spaces, keyed by name (optional)
+    pub vectors: Option<HashMap<String, VectorSpaceConfig>>,
+    /// HNSW index configuration (optional, defaults to `HnswConfig::default()`)
+    pub hnsw_config: Option<HnswConfig>,
+    /// Quantization configuration (optional, defaults to scalar quantization)
+    pub quantization: Option<QuantizationConfig>,
+    /// Whether to store payload data on disk (optional, defaults to true)
+    pub on_disk_payload: Option<bool>,
 }
 
 /// Collection info response
@@ -28,10 +38,17 @@ pub struct CreateCollectionRequest {
 pub struct CollectionInfo {
     /// Collection name
     pub name: String,
-    /// Vector dimension
-    pub dimension: usize,
-    /// Distance metric
-    pub metric: DistanceMetric,
+    /// Collection configuration
+    pub config: CollectionConfig,
+    /// Collection statistics
+    #[serde(flatten)]
+    pub stats: CollectionStats,
+    /// Aliases pointing at the collection
+    pub aliases: Vec<String>,
+    /// When the collection was created (Unix timestamp in seconds)
+    pub created_at: i64,
+    /// When the collection was last updated (Unix timestamp in seconds)
+    pub updated_at: i64,
 }
 
 /// List of collections response
@@ -55,24 +72,7 @@ async fn create_collection(
     State(state): State<AppState>,
     Json(req): Json<CreateCollectionRequest>,
 ) -> Result<impl IntoResponse> {
-    if state.contains_collection(&req.name) {
-        return Err(Error::CollectionExists(req.name));
-    }
-
-    let mut options = DbOptions::default();
-    options.dimensions = req.dimension;
-    options.distance_metric = req.metric.unwrap_or(DistanceMetric::Cosine);
-    // Use in-memory storage for server (storage path will be ignored for memory storage)
-    options.storage_path = format!("memory://{}", req.name);
-
-    let db = VectorDB::new(options.clone()).map_err(Error::Core)?;
-    state.insert_collection(req.name.clone(), Arc::new(db));
-
-    let info = CollectionInfo {
-        name: req.name,
-        dimension: req.dimension,
-        metric: options.distance_metric,
-    };
+    let info = req.create(&state)?;
 
     Ok((StatusCode::CREATED, Json(info)))
 }
@@ -80,8 +80,14 @@ async fn create_collection(
 /// List all collections
 ///
 /// GET /collections
-async fn list_collections(State(state): State<AppState>) -> Result<impl IntoResponse> {
-    let collections = state.collection_names();
+async fn list_collections(
+    State(state): State<AppState>,
+    grant: Option<Extension<Grant>>,
+) -> Result<impl IntoResponse> {
+    let mut collections = state.collection_names();
+    if let Some(Extension(grant)) = grant {
+        collections.retain(|name| grant.allows(&state, name));
+    }
     Ok(Json(CollectionsList { collections }))
 }
 
@@ -92,18 +98,7 @@ async fn get_collection(
     State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let _db = state
-        .get_collection(&name)
-        .ok_or_else(|| Error::CollectionNotFound(name.clone()))?;
-
-    // Note: VectorDB doesn't expose config directly, so we return basic info
-    let info = CollectionInfo {
-        name,
-        dimension: 0, // Would need to be stored separately or queried from DB
-        metric: DistanceMetric::Cosine, // Default assumption
-    };
-
-    Ok(Json(info))
+    Ok(Json(collection_info(&state, &name)?))
 }
 
 /// Delete a collection
@@ -113,9 +108,154 @@ async fn delete_collection(
     State(state): State<AppState>,
     Path(name): Path<String>,
 ) -> Result<impl IntoResponse> {
-    state
-        .remove_collection(&name)
-        .ok_or_else(|| Error::CollectionNotFound(name))?;
+    state.manager.delete_collection(&name)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+impl CreateCollectionRequest {
+    /// Create the collection, filling unset options with the defaults of
+    /// [`CollectionConfig::with_dimensions`]
+    pub(crate) fn create(self, state: &AppState) -> Result<CollectionInfo> {
+        let defaults = CollectionConfig::with_dimensions(self.dimension);
+        let config = CollectionConfig {
+            dimensions: self.dimension,
+            distance_metric: self.metric.unwrap_or(defaults.distance_metric),
+            hnsw_config: self.hnsw_config.or(defaults.hnsw_config),
+            quantization: self.quantization.or(defaults.quantization),
+            on_disk_payload: self.on_disk_payload.unwrap_or(defaults.on_disk_payload),
+            named_vectors: self.vectors,
+        };
+
+        state.manager.create_collection(&self.name, config)?;
+        collection_info(state, &self.name)
+    }
+}
+
+/// Describe a collection, by name or alias
+pub(crate) fn collection_info(state: &AppState, name: &str) -> Result<CollectionInfo> {
+    let collection = state.get_collection(name)?;
+    let stats = state.manager.collection_stats(name)?;
+    let collection = collection.read();
+    let mut aliases: Vec<String> = state
+        .manager
+        .list_aliases()
+        .into_iter()
+        .filter(|(_, target)| *target == collection.name)
+        .map(|(alias, _)| alias)
+        .collect();
+    aliases.sort();
+
+    Ok(CollectionInfo {
+        name: collection.name.clone(),
+        config: collection.config.clone(),
+        stats,
+        aliases,
+        created_at: collection.created_at,
+        updated_at: collection.updated_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{call, router};
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_collections_reload_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = router(dir.path(), Vec::new());
+
+        let (status, info) = call(
+            &app,
+            "POST",
+            "/collections",
+            Some(json!({
+                "name": "docs",
+                "dimension": 3,
+                "metric": "Euclidean",
+                "hnsw_config": {
+                    "m": 8,
+                    "ef_construction": 64,
+                    "ef_search": 32,
+                    "max_elements": 1000
+                },
+                "quantization": "None"
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(info["config"]["distance_metric"], "Euclidean");
+        assert_eq!(info["config"]["hnsw_config"]["m"], 8);
+        assert_eq!(info["config"]["hnsw_config"]["ef_search"], 32);
+        assert_eq!(info["config"]["quantization"], "None");
+        assert_eq!(info["vectors_count"], 0);
+
+        let (status, _) = call(
+            &app,
+            "POST",
+            "/collections",
+            Some(json!({ "name": "docs", "dimension": 3 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, _) = call(
+            &app,
+            "PUT",
+            "/collections/docs/points",
+            Some(json!({ "points": [
+                { "id": "a", "vector": [1.0, 0.0, 0.0] },
+                { "id": "b", "vector": [0.0, 1.0, 0.0] }
+            ] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        drop(app);
+
+        // The collection, its configuration and its points are reloaded
+        let app = router(dir.path(), Vec::new());
+        let (status, list) = call(&app, "GET", "/collections", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(list["collections"], json!(["docs"]));
+
+        let (status, info) = call(&app, "GET", "/collections/docs", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(info["name"], "docs");
+        assert_eq!(info["config"]["hnsw_config"]["m"], 8);
+        assert_eq!(info["config"]["quantization"], "None");
+        assert_eq!(info["vectors_count"], 2);
+        assert!(info["segments_count"].is_u64());
+        assert!(info["disk_size_bytes"].as_u64().unwrap() > 0);
+        assert!(info["ram_size_bytes"].is_u64());
+
+        let (status, _) = call(&app, "DELETE", "/collections/docs", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        drop(app);
+
+        let app = router(dir.path(), Vec::new());
+        let (status, list) = call(&app, "GET", "/collections", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(list["collections"], json!([]));
+        let (status, error) = call(&app, "GET", "/collections/docs", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "collection_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_collection_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = router(dir.path(), Vec::new());
+
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/collections",
+            Some(json!({ "name": "docs", "dimension": 0 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "invalid_request");
+    }
+}
@@ -1,46 +1,64 @@
 //! Health check endpoints
 
-use crate::{state::AppState, Result};
-use axum::{extract::State, response::IntoResponse, Json};
-use serde::Serialize;
-
-/// Health status response
-#[derive(Debug, Serialize)]
-pub struct HealthStatus {
-    /// Server status
-    pub status: String,
-}
-
-/// Readiness status response
-#[derive(Debug, Serialize)]
-pub struct ReadinessStatus {
-    /// Server status
-    pub status: String,
-    /// Number of collections
-    pub collections: usize,
-    /// Total number of points across all collections
-    pub total_points: usize,
-}
+use crate::{auth::Grant, state::AppState, Result};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+use ruvector_metrics::health::CollectionStats;
+use ruvector_metrics::HealthStatus;
 
 /// Simple health check endpoint
 ///
 /// GET /health
-pub async fn health_check() -> Result<impl IntoResponse> {
-    Ok(Json(HealthStatus {
-        status: "healthy".to_string(),
-    }))
+pub async fn health_check(State(state): State<AppState>) -> Result<impl IntoResponse> {
+    Ok(Json(state.health.health()))
 }
 
-/// Readiness check endpoint with stats
+/// Readiness check endpoint with per-collection health
+///
+/// Answers `503 Service Unavailable` if a collection cannot be read. Empty
+/// collections only degrade the reported status, so a new server stays
+/// ready to be loaded. Keys scoped to some collections only see those.
 ///
 /// GET /ready
-pub async fn readiness(State(state): State<AppState>) -> Result<impl IntoResponse> {
-    let collections_count = state.collection_count();
+pub async fn readiness(
+    State(state): State<AppState>,
+    grant: Option<Extension<Grant>>,
+) -> Result<impl IntoResponse> {
+    let mut names = state.collection_names();
+    if let Some(Extension(grant)) = grant {
+        names.retain(|name| grant.allows(&state, name));
+    }
+    let collections: Vec<CollectionStats> = names
+        .into_iter()
+        .filter_map(|name| collection_stats(&state, name))
+        .collect();
+    let readiness = state.health.readiness(&collections);
+
+    let status = if readiness
+        .details
+        .values()
+        .any(|c| c.status == HealthStatus::Unhealthy)
+    {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+
+    Ok((status, Json(readiness)))
+}
+
+/// Health input of a collection, or `None` if it was deleted meanwhile
+fn collection_stats(state: &AppState, name: String) -> Option<CollectionStats> {
+    let collection = state.get_collection(&name).ok()?;
+    let collection = collection.read();
+    let (vectors_count, error) = match collection.db.len() {
+        Ok(count) => (count, None),
+        Err(e) => (0, Some(e.to_string())),
+    };
 
-    // Note: VectorDB doesn't expose count directly, so we report collections only
-    Ok(Json(ReadinessStatus {
-        status: "ready".to_string(),
-        collections: collections_count,
-        total_points: 0, // Would require tracking or querying each DB
-    }))
+    Some(CollectionStats {
+        name,
+        vectors_count,
+        last_updated: chrono::DateTime::from_timestamp(collection.updated_at, 0),
+        error,
+    })
 }
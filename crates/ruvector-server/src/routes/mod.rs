@@ -1,5 +1,6 @@
 //! API routes
 
+pub mod aliases;
 pub mod collections;
 pub mod health;
 pub mod points;
@@ -1,6 +1,6 @@
 //! Point operations endpoints
 
-use crate::{error::Error, state::AppState, Result};
+use crate::{error::Error, metrics::Operation, state::AppState, Result};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -8,8 +8,12 @@ use axum::{
     routing::{get, post, put},
     Json, Router,
 };
-use ruvector_core::{SearchQuery, SearchResult, VectorEntry};
+use ruvector_core::{
+    FilterExpression, PointSelector, SearchParams, SearchQuery, SearchResult, VectorDB,
+    VectorEntry, VectorSelector,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 /// Point upsert request
@@ -22,21 +26,140 @@ pub struct UpsertPointsRequest {
 /// Search request
 #[derive(Debug, Deserialize)]
 pub struct SearchRequest {
-    /// Query vector
+    /// Query vector; may be omitted when fusing named vectors
+    #[serde(default)]
     pub vector: Vec<f32>,
+    /// Named vector space to search, or several spaces to fuse
+    pub using: Option<VectorSelector>,
     /// Number of results to return
     #[serde(default = "default_limit")]
     pub k: usize,
-    /// Optional score threshold
+    /// Optional maximum distance score; overrides `params.score_threshold`
     pub score_threshold: Option<f32>,
-    /// Optional metadata filters
-    pub filter: Option<HashMap<String, serde_json::Value>>,
+    /// Optional metadata filter: a filter expression or an exact-match map
+    pub filter: Option<serde_json::Value>,
+    /// Search tuning (ef_search, exact, rescore, oversampling)
+    #[serde(default)]
+    pub params: SearchParams,
 }
 
-fn default_limit() -> usize {
+pub(crate) fn default_limit() -> usize {
     10
 }
 
+/// Largest page a scroll returns
+pub const MAX_SCROLL_LIMIT: usize = 1_000;
+
+/// Check a requested scroll page size, capping it at [`MAX_SCROLL_LIMIT`]
+pub(crate) fn scroll_limit(limit: usize) -> Result<usize> {
+    if limit == 0 {
+        return Err(Error::InvalidRequest(
+            "`limit` must be at least 1".to_string(),
+        ));
+    }
+    Ok(limit.min(MAX_SCROLL_LIMIT))
+}
+
+/// Batch search request
+#[derive(Debug, Deserialize)]
+pub struct BatchSearchRequest {
+    /// Searches to run, answered in order
+    pub searches: Vec<SearchRequest>,
+}
+
+/// Points to act on: either `points` (ids) or `filter`
+#[derive(Debug, Deserialize)]
+pub struct SelectorRequest {
+    /// Ids of the points
+    pub points: Option<Vec<String>>,
+    /// Filter expression selecting the points
+    pub filter: Option<Value>,
+}
+
+/// Payload update request
+#[derive(Debug, Deserialize)]
+pub struct SetPayloadRequest {
+    /// Payload keys and values to write
+    pub payload: HashMap<String, Value>,
+    /// Points to update
+    #[serde(flatten)]
+    pub selector: SelectorRequest,
+}
+
+/// Payload key deletion request
+#[derive(Debug, Deserialize)]
+pub struct DeletePayloadRequest {
+    /// Payload keys to remove
+    pub keys: Vec<String>,
+    /// Points to update
+    #[serde(flatten)]
+    pub selector: SelectorRequest,
+}
+
+/// Batch retrieval request
+#[derive(Debug, Deserialize)]
+pub struct GetPointsRequest {
+    /// Ids of the points; missing ones are left out of the response
+    pub ids: Vec<String>,
+}
+
+/// Scroll request
+#[derive(Debug, Deserialize)]
+pub struct ScrollRequest {
+    /// Optional filter expression
+    pub filter: Option<Value>,
+    /// Id to start from, as returned in `next_page_offset`
+    pub offset: Option<String>,
+    /// Maximum number of points to return, at least 1 (larger values are
+    /// capped at [`MAX_SCROLL_LIMIT`])
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+/// Count request
+#[derive(Debug, Default, Deserialize)]
+pub struct CountRequest {
+    /// Optional filter expression
+    pub filter: Option<Value>,
+}
+
+/// Batch search response
+#[derive(Debug, Serialize)]
+pub struct BatchSearchResponse {
+    /// Results of each search, in request order
+    pub results: Vec<Vec<SearchResult>>,
+}
+
+/// Points response
+#[derive(Debug, Serialize)]
+pub struct PointsResponse {
+    /// Points found
+    pub points: Vec<VectorEntry>,
+}
+
+/// Scroll response
+#[derive(Debug, Serialize)]
+pub struct ScrollResponse {
+    /// Points of this page, in id order
+    pub points: Vec<VectorEntry>,
+    /// Offset of the next page, or null after the last page
+    pub next_page_offset: Option<String>,
+}
+
+/// Count response
+#[derive(Debug, Serialize)]
+pub struct CountResponse {
+    /// Number of matching points
+    pub count: usize,
+}
+
+/// Response of deletes and payload updates
+#[derive(Debug, Serialize)]
+pub struct UpdateResponse {
+    /// Number of points deleted or updated
+    pub updated: usize,
+}
+
 /// Search response
 #[derive(Debug, Serialize)]
 pub struct SearchResponse {
@@ -54,9 +177,51 @@ pub struct UpsertResponse {
 /// Create point routes
 pub fn routes() -> Router<AppState> {
     Router::new()
-        .route("/collections/:name/points", put(upsert_points))
+        .route(
+            "/collections/:name/points",
+            put(upsert_points).post(get_points),
+        )
         .route("/collections/:name/points/search", post(search_points))
-        .route("/collections/:name/points/:id", get(get_point))
+        .route(
+            "/collections/:name/points/search/batch",
+            post(search_points_batch),
+        )
+        .route("/collections/:name/points/delete", post(delete_points))
+        .route(
+            "/collections/:name/points/payload",
+            post(set_payload).put(overwrite_payload),
+        )
+        .route(
+            "/collections/:name/points/payload/delete",
+            post(delete_payload),
+        )
+        .route("/collections/:name/points/scroll", post(scroll_points))
+        .route("/collections/:name/points/count", post(count_points))
+        .route(
+            "/collections/:name/points/:id",
+            get(get_point).delete(delete_point),
+        )
+}
+
+/// Parse a filter expression
+///
+/// Unlike search filters, which also accept a plain exact-match object,
+/// these must follow the filter expression schema, so a malformed
+/// expression is reported instead of being read as field values.
+pub(crate) fn parse_filter(filter: Value) -> Result<FilterExpression> {
+    serde_json::from_value(filter).map_err(|e| Error::InvalidFilter(e.to_string()))
+}
+
+impl SelectorRequest {
+    pub(crate) fn into_selector(self) -> Result<PointSelector> {
+        match (self.points, self.filter) {
+            (Some(ids), None) => Ok(PointSelector::Ids(ids)),
+            (None, Some(filter)) => Ok(PointSelector::Filter(parse_filter(filter)?)),
+            _ => Err(Error::InvalidRequest(
+                "Exactly one of `points` and `filter` is required".to_string(),
+            )),
+        }
+    }
 }
 
 /// Upsert points into a collection
@@ -67,11 +232,10 @@ async fn upsert_points(
     Path(name): Path<String>,
     Json(req): Json<UpsertPointsRequest>,
 ) -> Result<impl IntoResponse> {
-    let db = state
-        .get_collection(&name)
-        .ok_or_else(|| Error::CollectionNotFound(name.clone()))?;
-
-    let ids = db.insert_batch(req.points).map_err(Error::Core)?;
+    let count = req.points.len();
+    let ids = state.with_db_recorded(&name, Operation::Insert(count), |db| {
+        Ok(db.insert_batch(req.points)?)
+    })?;
 
     Ok((StatusCode::OK, Json(UpsertResponse { ids })))
 }
@@ -84,25 +248,56 @@ async fn search_points(
     Path(name): Path<String>,
     Json(req): Json<SearchRequest>,
 ) -> Result<impl IntoResponse> {
-    let db = state
-        .get_collection(&name)
-        .ok_or_else(|| Error::CollectionNotFound(name))?;
+    let results = state.with_db_recorded(&name, Operation::Search, |db| search(db, req))?;
+
+    Ok(Json(SearchResponse { results }))
+}
+
+/// Run several searches in one request
+///
+/// POST /collections/:name/points/search/batch
+async fn search_points_batch(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<BatchSearchRequest>,
+) -> Result<impl IntoResponse> {
+    let results = state.with_db_recorded(&name, Operation::Search, |db| {
+        req.searches
+            .into_iter()
+            .enumerate()
+            .map(|(index, req)| {
+                search(db, req).map_err(|e| Error::BatchQuery {
+                    index,
+                    source: Box::new(e),
+                })
+            })
+            .collect::<Result<_>>()
+    })?;
+
+    Ok(Json(BatchSearchResponse { results }))
+}
+
+/// Run one search request against a collection
+pub(crate) fn search(db: &VectorDB, req: SearchRequest) -> Result<Vec<SearchResult>> {
+    let filter = req
+        .filter
+        .map(FilterExpression::from_json)
+        .transpose()
+        .map_err(|e| Error::InvalidFilter(e.to_string()))?;
 
     let query = SearchQuery {
         vector: req.vector,
+        using: req.using,
         k: req.k,
-        filter: req.filter,
+        filter,
         ef_search: None,
     };
+    let params = SearchParams {
+        score_threshold: req.score_threshold.or(req.params.score_threshold),
+        ..req.params
+    };
 
-    let mut results = db.search(query).map_err(Error::Core)?;
-
-    // Apply score threshold if provided
-    if let Some(threshold) = req.score_threshold {
-        results.retain(|r| r.score >= threshold);
-    }
-
-    Ok(Json(SearchResponse { results }))
+    Ok(db.search_with_params(query, &params)?)
 }
 
 /// Get a point by ID
@@ -112,11 +307,422 @@ async fn get_point(
     State(state): State<AppState>,
     Path((name, id)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    let db = state
-        .get_collection(&name)
-        .ok_or_else(|| Error::CollectionNotFound(name))?;
-
-    let entry = db.get(&id).map_err(Error::Core)?;
+    let entry = state.with_db_recorded(&name, Operation::Get, |db| Ok(db.get(&id)?))?;
 
     Ok(Json(entry))
 }
+
+/// Get several points by ID
+///
+/// POST /collections/:name/points
+async fn get_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<GetPointsRequest>,
+) -> Result<impl IntoResponse> {
+    let points = state.with_db_recorded(&name, Operation::Get, |db| {
+        let mut points = Vec::with_capacity(req.ids.len());
+        for id in &req.ids {
+            points.extend(db.get(id)?);
+        }
+        Ok(points)
+    })?;
+
+    Ok(Json(PointsResponse { points }))
+}
+
+/// Delete a point by ID
+///
+/// DELETE /collections/:name/points/:id
+async fn delete_point(
+    State(state): State<AppState>,
+    Path((name, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    if !state.with_db_recorded(&name, Operation::Delete, |db| Ok(db.delete(&id)?))? {
+        return Err(Error::PointNotFound(id));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Delete points by ID or filter
+///
+/// POST /collections/:name/points/delete
+async fn delete_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<SelectorRequest>,
+) -> Result<impl IntoResponse> {
+    let selector = req.into_selector()?;
+    let updated = state.with_db_recorded(&name, Operation::Delete, |db| {
+        Ok(db.delete_points(&selector)?)
+    })?;
+
+    Ok(Json(UpdateResponse { updated }))
+}
+
+/// Set payload keys, keeping the other keys
+///
+/// POST /collections/:name/points/payload
+async fn set_payload(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<SetPayloadRequest>,
+) -> Result<impl IntoResponse> {
+    let selector = req.selector.into_selector()?;
+    let updated = state.with_db_recorded(&name, Operation::SetPayload, |db| {
+        Ok(db.set_payload(&selector, &req.payload)?)
+    })?;
+
+    Ok(Json(UpdateResponse { updated }))
+}
+
+/// Replace the whole payload
+///
+/// PUT /collections/:name/points/payload
+async fn overwrite_payload(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<SetPayloadRequest>,
+) -> Result<impl IntoResponse> {
+    let selector = req.selector.into_selector()?;
+    let updated = state.with_db_recorded(&name, Operation::OverwritePayload, |db| {
+        Ok(db.overwrite_payload(&selector, &req.payload)?)
+    })?;
+
+    Ok(Json(UpdateResponse { updated }))
+}
+
+/// Remove payload keys
+///
+/// POST /collections/:name/points/payload/delete
+async fn delete_payload(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<DeletePayloadRequest>,
+) -> Result<impl IntoResponse> {
+    let selector = req.selector.into_selector()?;
+    let updated = state.with_db_recorded(&name, Operation::DeletePayload, |db| {
+        Ok(db.delete_payload(&selector, &req.keys)?)
+    })?;
+
+    Ok(Json(UpdateResponse { updated }))
+}
+
+/// Page through points in ID order
+///
+/// POST /collections/:name/points/scroll
+async fn scroll_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<ScrollRequest>,
+) -> Result<impl IntoResponse> {
+    let filter = req.filter.map(parse_filter).transpose()?;
+    let limit = scroll_limit(req.limit)?;
+    let page = state.with_db_recorded(&name, Operation::Scroll, |db| {
+        Ok(db.scroll(filter.as_ref(), req.offset.as_deref(), limit)?)
+    })?;
+
+    Ok(Json(ScrollResponse {
+        points: page.points,
+        next_page_offset: page.next_offset,
+    }))
+}
+
+/// Count points, optionally matching a filter
+///
+/// POST /collections/:name/points/count
+async fn count_points(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<CountRequest>,
+) -> Result<impl IntoResponse> {
+    let filter = req.filter.map(parse_filter).transpose()?;
+    let count =
+        state.with_db_recorded(&name, Operation::Count, |db| Ok(db.count(filter.as_ref())?))?;
+
+    Ok(Json(CountResponse { count }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_SCROLL_LIMIT;
+    use crate::test_support::{call, router};
+    use axum::{http::StatusCode, Router};
+    use serde_json::{json, Value};
+
+    /// Router with a collection `docs` of points `p0`..`p9`, tagged `even`
+    /// or `odd`
+    async fn docs(dir: &std::path::Path) -> Router {
+        let app = router(dir, Vec::new());
+        let (status, _) = call(
+            &app,
+            "POST",
+            "/collections",
+            Some(json!({ "name": "docs", "dimension": 2, "metric": "Euclidean" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let points: Vec<_> = (0..10)
+            .map(|i| {
+                json!({
+                    "id": format!("p{}", i),
+                    "vector": [i as f32, 0.0],
+                    "metadata": { "tag": if i % 2 == 0 { "even" } else { "odd" }, "n": i }
+                })
+            })
+            .collect();
+        let (status, _) = call(
+            &app,
+            "PUT",
+            "/collections/docs/points",
+            Some(json!({ "points": points })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        app
+    }
+
+    fn ids(points: &Value) -> Vec<String> {
+        points
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn tag(value: &str) -> Value {
+        json!({ "type": "eq", "field": "tag", "value": value })
+    }
+
+    #[tokio::test]
+    async fn test_scroll_and_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = docs(dir.path()).await;
+
+        let (status, page) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/scroll",
+            Some(json!({ "filter": tag("odd"), "limit": 2 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ids(&page["points"]), ["p1", "p3"]);
+        assert_eq!(page["next_page_offset"], "p5");
+
+        let (_, page) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/scroll",
+            Some(json!({ "filter": tag("odd"), "offset": "p5", "limit": 5 })),
+        )
+        .await;
+        assert_eq!(ids(&page["points"]), ["p5", "p7", "p9"]);
+        assert_eq!(page["next_page_offset"], Value::Null);
+
+        // Oversized pages are capped rather than refused
+        let (status, page) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/scroll",
+            Some(json!({ "limit": MAX_SCROLL_LIMIT + 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["points"].as_array().unwrap().len(), 10);
+
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/scroll",
+            Some(json!({ "limit": 0 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "invalid_request");
+
+        let (_, count) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/count",
+            Some(json!({})),
+        )
+        .await;
+        assert_eq!(count["count"], 10);
+        let (_, count) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/count",
+            Some(json!({ "filter": tag("even") })),
+        )
+        .await;
+        assert_eq!(count["count"], 5);
+
+        // Malformed expressions are reported, not read as field values
+        for path in ["scroll", "count"] {
+            let (status, error) = call(
+                &app,
+                "POST",
+                &format!("/collections/docs/points/{}", path),
+                Some(json!({ "filter": { "type": "eq", "field": "tag" } })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", path);
+            assert_eq!(error["code"], "invalid_filter", "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = docs(dir.path()).await;
+
+        let (status, deleted) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/delete",
+            Some(json!({ "filter": tag("even") })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(deleted["updated"], 5);
+
+        let (_, deleted) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/delete",
+            Some(json!({ "points": ["p1", "p2", "missing"] })),
+        )
+        .await;
+        assert_eq!(deleted["updated"], 1);
+
+        let (status, _) = call(&app, "DELETE", "/collections/docs/points/p3", None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, error) = call(&app, "DELETE", "/collections/docs/points/p3", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["code"], "point_not_found");
+
+        let (_, page) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/scroll",
+            Some(json!({})),
+        )
+        .await;
+        assert_eq!(ids(&page["points"]), ["p5", "p7", "p9"]);
+
+        // Exactly one of `points` and `filter` selects what to delete
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/delete",
+            Some(json!({ "points": ["p5"], "filter": tag("odd") })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "invalid_request");
+    }
+
+    #[tokio::test]
+    async fn test_payload_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = docs(dir.path()).await;
+        let point = |id: &'static str| {
+            let app = app.clone();
+            async move {
+                let (_, point) = call(
+                    &app,
+                    "GET",
+                    &format!("/collections/docs/points/{}", id),
+                    None,
+                )
+                .await;
+                point["metadata"].clone()
+            }
+        };
+
+        let (status, updated) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/payload",
+            Some(json!({ "payload": { "seen": true }, "filter": tag("odd") })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["updated"], 5);
+        assert_eq!(
+            point("p1").await,
+            json!({ "tag": "odd", "n": 1, "seen": true })
+        );
+
+        let (_, updated) = call(
+            &app,
+            "PUT",
+            "/collections/docs/points/payload",
+            Some(json!({ "payload": { "tag": "new" }, "points": ["p2"] })),
+        )
+        .await;
+        assert_eq!(updated["updated"], 1);
+        assert_eq!(point("p2").await, json!({ "tag": "new" }));
+
+        let (_, updated) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/payload/delete",
+            Some(json!({ "keys": ["n"], "points": ["p1", "p3"] })),
+        )
+        .await;
+        assert_eq!(updated["updated"], 2);
+        assert_eq!(point("p1").await, json!({ "tag": "odd", "seen": true }));
+
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/payload",
+            Some(json!({ "payload": {}, "filter": { "type": "nope" } })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "invalid_filter");
+    }
+
+    #[tokio::test]
+    async fn test_batch_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = docs(dir.path()).await;
+
+        let (status, response) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/search/batch",
+            Some(json!({ "searches": [
+                { "vector": [0.0, 0.0], "k": 2 },
+                { "vector": [0.0, 0.0], "k": 2, "filter": tag("odd") },
+                { "vector": [9.0, 0.0], "k": 1, "filter": { "tag": "even" } }
+            ] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let results = response["results"].as_array().unwrap();
+        assert_eq!(ids(&results[0]), ["p0", "p1"]);
+        assert_eq!(ids(&results[1]), ["p1", "p3"]);
+        assert_eq!(ids(&results[2]), ["p8"]);
+
+        // The failing query is named in the error details
+        let (status, error) = call(
+            &app,
+            "POST",
+            "/collections/docs/points/search/batch",
+            Some(json!({ "searches": [
+                { "vector": [0.0, 0.0], "k": 2 },
+                { "vector": [0.0, 0.0], "k": 2, "filter": { "type": "eq", "field": "tag" } }
+            ] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "invalid_filter");
+        assert_eq!(error["details"], json!({ "query": 1 }));
+    }
+}
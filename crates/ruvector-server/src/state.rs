@@ -1,60 +1,127 @@
 //! Shared application state
 
-use dashmap::DashMap;
+use crate::{
+    error::Error,
+    metrics::{MetricsState, Operation},
+    Result,
+};
+use parking_lot::RwLock;
+use ruvector_collections::{Collection, CollectionManager};
 use ruvector_core::VectorDB;
+use ruvector_metrics::HealthChecker;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
-    /// Map of collection name to VectorDB
-    pub collections: Arc<DashMap<String, Arc<VectorDB>>>,
+    /// Collections and aliases, persisted under the data directory
+    pub manager: Arc<CollectionManager>,
+    /// Uptime and collection health for `/health` and `/ready`
+    pub health: Arc<HealthChecker>,
+    /// Bookkeeping for the `/metrics` gauges
+    pub(crate) metrics: Arc<MetricsState>,
 }
 
 impl AppState {
-    /// Create a new application state
-    pub fn new() -> Self {
+    /// Create an application state around a collection manager
+    pub fn new(manager: CollectionManager) -> Self {
         Self {
-            collections: Arc::new(DashMap::new()),
+            manager: Arc::new(manager),
+            health: Arc::new(HealthChecker::with_version(
+                env!("CARGO_PKG_VERSION").to_string(),
+            )),
+            metrics: Arc::default(),
         }
     }
 
-    /// Get a collection by name
-    pub fn get_collection(&self, name: &str) -> Option<Arc<VectorDB>> {
-        self.collections.get(name).map(|c| c.clone())
+    /// Get a collection by name or alias
+    pub fn get_collection(&self, name: &str) -> Result<Arc<RwLock<Collection>>> {
+        self.manager
+            .get_collection(name)
+            .ok_or_else(|| Error::CollectionNotFound(name.to_string()))
     }
 
-    /// Insert a collection
-    pub fn insert_collection(&self, name: String, db: Arc<VectorDB>) {
-        self.collections.insert(name, db);
+    /// Run `f` on the database of a collection, by name or alias
+    pub fn with_db<T>(&self, name: &str, f: impl FnOnce(&VectorDB) -> Result<T>) -> Result<T> {
+        let collection = self.get_collection(name)?;
+        let collection = collection.read();
+        f(&collection.db)
     }
 
-    /// Remove a collection
-    pub fn remove_collection(&self, name: &str) -> Option<Arc<VectorDB>> {
-        self.collections.remove(name).map(|(_, c)| c)
+    /// Like [`AppState::with_db`], also recording `op` in the search, insert
+    /// or delete metrics of the collection
+    pub fn with_db_recorded<T>(
+        &self,
+        name: &str,
+        op: Operation,
+        f: impl FnOnce(&VectorDB) -> Result<T>,
+    ) -> Result<T> {
+        let start = Instant::now();
+        let result = self.with_db(name, f);
+        op.record(
+            &self.metrics_label(name),
+            start.elapsed().as_secs_f64(),
+            result.is_ok(),
+        );
+        result
     }
 
-    /// Check if a collection exists
-    pub fn contains_collection(&self, name: &str) -> bool {
-        self.collections.contains_key(name)
+    /// Collection label of metrics about `name`
+    ///
+    /// Aliases resolve to their collection. Names of missing collections
+    /// become `unknown`, so bad requests cannot add label values.
+    pub fn metrics_label(&self, name: &str) -> String {
+        let name = self
+            .manager
+            .resolve_alias(name)
+            .unwrap_or_else(|| name.to_string());
+        if self.manager.collection_exists(&name) {
+            name
+        } else {
+            "unknown".to_string()
+        }
     }
 
     /// Get all collection names
     pub fn collection_names(&self) -> Vec<String> {
-        self.collections
-            .iter()
-            .map(|entry| entry.key().clone())
-            .collect()
+        let mut names = self.manager.list_collections();
+        names.sort();
+        names
     }
 
     /// Get the number of collections
     pub fn collection_count(&self) -> usize {
-        self.collections.len()
+        self.manager.list_collections().len()
     }
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        Self::new()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruvector_collections::CollectionConfig;
+
+    #[test]
+    fn test_collections_by_name_or_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::new(CollectionManager::new(dir.path().to_path_buf()).unwrap());
+        state
+            .manager
+            .create_collection("docs_v1", CollectionConfig::with_dimensions(2))
+            .unwrap();
+        state.manager.create_alias("docs", "docs_v1").unwrap();
+
+        assert_eq!(state.get_collection("docs").unwrap().read().name, "docs_v1");
+        assert!(matches!(
+            state.get_collection("missing"),
+            Err(Error::CollectionNotFound(_))
+        ));
+        assert_eq!(state.with_db("docs", |db| Ok(db.len()?)).unwrap(), 0);
+
+        // Missing collections share one metrics label
+        assert_eq!(state.metrics_label("docs"), "docs_v1");
+        assert_eq!(state.metrics_label("missing"), "unknown");
+        assert_eq!(state.collection_names(), vec!["docs_v1".to_string()]);
+        assert_eq!(state.collection_count(), 1);
     }
 }
@@ -0,0 +1,67 @@
+//! Helpers for tests that drive the router in process
+
+use crate::{auth::ApiKey, Config, RuvectorServer};
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use std::path::Path;
+use tower::ServiceExt;
+
+/// Router over the collections stored in `data_dir`, as the server would
+/// build it on start
+pub(crate) fn router(data_dir: &Path, api_keys: Vec<ApiKey>) -> Router {
+    let server = RuvectorServer::with_config(Config {
+        data_dir: data_dir.to_path_buf(),
+        api_keys,
+        ..Config::default()
+    });
+    let state = server.open_state().unwrap();
+    server.build_router(state)
+}
+
+/// Send one request, returning the status and the JSON body (`Null` if
+/// there is none)
+pub(crate) async fn call(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    call_with_key(router, method, uri, body, None).await
+}
+
+/// Like [`call`], presenting `key` as a bearer token if given
+pub(crate) async fn call_with_key(
+    router: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+    key: Option<&str>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(key) = key {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", key));
+    }
+    let request = match body {
+        Some(body) => request
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap()
+    };
+    (status, body)
+}
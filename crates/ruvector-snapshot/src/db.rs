@@ -0,0 +1,476 @@
+//! Snapshots of a live [`VectorDB`]
+//!
+//! A snapshot starts from a [`ConsistentCut`] of the database, so it holds
+//! exactly the operations up to one sequence number even while writes
+//! continue. Full snapshots carry every vector plus the links of the HNSW
+//! graphs, so a restore does not rebuild the index. The graphs are read
+//! after the cut, while writes continue, so they are followed by the ids
+//! written in the meantime, which a restore indexes again. Incremental
+//! snapshots carry the vectors upserted and the ids deleted since a base
+//! snapshot, found from the write-ahead log when it still reaches back to
+//! the base and by comparing against the base snapshot otherwise.
+
+use crate::error::{Result, SnapshotError};
+use crate::format::{Frame, FrameReader, FrameWriter, SnapshotHeader, VECTORS_PER_FRAME};
+use crate::manager::SnapshotManager;
+use crate::snapshot::{Snapshot, SnapshotMetadata, SnapshotType, VectorRecord};
+use async_trait::async_trait;
+use ruvector_core::cut::{ConsistentCut, CutWriter};
+use ruvector_core::{VectorDB, VectorId, WalOperation};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+
+/// Snapshot and restore a [`VectorDB`] through a [`SnapshotManager`]
+#[async_trait]
+pub trait VectorDbSnapshots: Sized {
+    /// Write a full snapshot of the database
+    async fn create_snapshot(
+        &self,
+        manager: &SnapshotManager,
+        collection_name: &str,
+    ) -> Result<Snapshot>;
+
+    /// Write a snapshot of the changes since the snapshot `base_id`
+    ///
+    /// The base must be a snapshot of this database, or of the database it
+    /// was restored from.
+    async fn create_incremental_snapshot(
+        &self,
+        manager: &SnapshotManager,
+        base_id: &str,
+    ) -> Result<Snapshot>;
+
+    /// Restore the snapshot `id`, and the snapshots it builds on, into a new
+    /// database at `storage_path`
+    ///
+    /// Every snapshot involved is verified before anything is written.
+    async fn restore_snapshot(
+        manager: &SnapshotManager,
+        id: &str,
+        storage_path: &str,
+    ) -> Result<Self>;
+}
+
+#[async_trait]
+impl VectorDbSnapshots for VectorDB {
+    async fn create_snapshot(
+        &self,
+        manager: &SnapshotManager,
+        collection_name: &str,
+    ) -> Result<Snapshot> {
+        let cut = self.consistent_cut()?;
+        let metadata = SnapshotMetadata::new(
+            collection_name.to_string(),
+            SnapshotType::Full,
+            cut.sequence,
+        );
+        let mut writer = start(manager, &cut, metadata).await?;
+
+        let ids = cut.ids()?;
+        write_vectors(&mut writer, &cut, &ids).await?;
+        for space in cut.spaces() {
+            let mut start = 0;
+            while let Some(part) = cut.graph_part(space.as_deref(), start)? {
+                start = part.end();
+                let space = space.clone();
+                writer.write(&Frame::Graph { space, part }).await?;
+            }
+        }
+        // Every write up to now may have reached the graphs
+        match self.operations_since(cut.sequence)? {
+            Some(records) => {
+                let changed: BTreeSet<VectorId> = records
+                    .into_iter()
+                    .flat_map(|record| record.operation.into_ids())
+                    .collect();
+                let changed: Vec<VectorId> = changed.into_iter().collect();
+                for ids in changed.chunks(VECTORS_PER_FRAME) {
+                    writer
+                        .write(&Frame::GraphChanged(Some(ids.to_vec())))
+                        .await?;
+                }
+            }
+            None => writer.write(&Frame::GraphChanged(None)).await?,
+        }
+
+        let snapshot = writer.finish().await?;
+        manager.storage().commit(&snapshot).await?;
+        Ok(snapshot)
+    }
+
+    async fn create_incremental_snapshot(
+        &self,
+        manager: &SnapshotManager,
+        base_id: &str,
+    ) -> Result<Snapshot> {
+        let base = manager.get_snapshot_info(base_id).await?;
+        let cut = self.consistent_cut()?;
+        if cut.sequence < base.sequence {
+            return Err(SnapshotError::storage(format!(
+                "Snapshot {} is ahead of the database",
+                base_id
+            )));
+        }
+
+        let (upserted, deleted) = match self.operations_since(base.sequence)? {
+            Some(records) => {
+                let mut changed = BTreeSet::new();
+                for record in records.into_iter().filter(|r| r.sequence <= cut.sequence) {
+                    changed.extend(record.operation.into_ids());
+                }
+                let mut upserted = Vec::new();
+                let mut deleted = Vec::new();
+                for id in changed {
+                    match cut.get(&id)? {
+                        Some(_) => upserted.push(id),
+                        None => deleted.push(id),
+                    }
+                }
+                (upserted, deleted)
+            }
+            None => diff_against_base(manager, base_id, &cut).await?,
+        };
+
+        let metadata = SnapshotMetadata::new(
+            base.collection_name.clone(),
+            SnapshotType::Incremental {
+                base_id: base_id.to_string(),
+            },
+            cut.sequence,
+        );
+        let mut writer = start(manager, &cut, metadata).await?;
+        write_vectors(&mut writer, &cut, &upserted).await?;
+        for ids in deleted.chunks(VECTORS_PER_FRAME) {
+            writer.write(&Frame::Deleted(ids.to_vec())).await?;
+        }
+
+        let snapshot = writer.finish().await?;
+        manager.storage().commit(&snapshot).await?;
+        Ok(snapshot)
+    }
+
+    async fn restore_snapshot(
+        manager: &SnapshotManager,
+        id: &str,
+        storage_path: &str,
+    ) -> Result<Self> {
+        let chain = manager.snapshot_chain(id).await?;
+        for snapshot in &chain {
+            manager.verify_snapshot(&snapshot.id).await?;
+        }
+
+        let mut writer: Option<CutWriter> = None;
+        let mut tip = None;
+        for snapshot in &chain {
+            let (mut reader, header) =
+                FrameReader::open(manager.storage().reader(&snapshot.id).await?).await?;
+            let writer = match &mut writer {
+                Some(writer) => writer,
+                None => {
+                    let mut options = header.config.clone();
+                    options.storage_path = storage_path.to_string();
+                    writer.insert(CutWriter::new(options)?)
+                }
+            };
+            let incremental = matches!(
+                header.metadata.snapshot_type,
+                SnapshotType::Incremental { .. }
+            );
+
+            loop {
+                match reader.next().await? {
+                    Frame::Graph { space, part } => {
+                        writer.add_graph_part(space.as_deref(), part)?
+                    }
+                    Frame::GraphChanged(Some(ids)) => writer.mark_changed(ids),
+                    Frame::GraphChanged(None) => writer.discard_graphs()?,
+                    Frame::Vectors(records) => {
+                        let entries = records.into_iter().map(VectorRecord::into_entry).collect();
+                        if incremental {
+                            writer.apply(&WalOperation::Upsert(entries))?;
+                        } else {
+                            writer.insert(&entries)?;
+                        }
+                    }
+                    Frame::Deleted(ids) => writer.apply(&WalOperation::Delete(ids))?,
+                    Frame::End => break,
+                    Frame::Header(_) => {
+                        return Err(SnapshotError::corrupted("Repeated snapshot header"))
+                    }
+                }
+            }
+            tip = Some(header);
+        }
+
+        let (Some(writer), Some(tip)) = (writer, tip) else {
+            return Err(SnapshotError::SnapshotNotFound(id.to_string()));
+        };
+        Ok(writer.finish(tip.metadata.sequence, &tip.payload_indexes)?)
+    }
+}
+
+/// Start a snapshot of `cut` in the manager's storage
+async fn start(
+    manager: &SnapshotManager,
+    cut: &ConsistentCut,
+    metadata: SnapshotMetadata,
+) -> Result<FrameWriter> {
+    let output = manager.storage().writer(&metadata.id).await?;
+    let header = SnapshotHeader {
+        metadata,
+        config: cut.options.clone(),
+        payload_indexes: cut.payload_indexes.clone(),
+    };
+    FrameWriter::start(output, header).await
+}
+
+/// Write the entries `ids` of `cut` in frames
+async fn write_vectors(
+    writer: &mut FrameWriter,
+    cut: &ConsistentCut,
+    ids: &[VectorId],
+) -> Result<()> {
+    for ids in ids.chunks(VECTORS_PER_FRAME) {
+        let mut records = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(entry) = cut.get(id)? {
+                records.push(VectorRecord::from(entry));
+            }
+        }
+        writer.write(&Frame::Vectors(records)).await?;
+    }
+    Ok(())
+}
+
+/// Find the ids upserted and deleted since the snapshot `base_id` by
+/// comparing `cut` against the snapshot chain ending in it
+async fn diff_against_base(
+    manager: &SnapshotManager,
+    base_id: &str,
+    cut: &ConsistentCut,
+) -> Result<(Vec<VectorId>, Vec<VectorId>)> {
+    let mut digests = HashMap::new();
+    for snapshot in manager.snapshot_chain(base_id).await? {
+        let (mut reader, _) =
+            FrameReader::open(manager.storage().reader(&snapshot.id).await?).await?;
+        loop {
+            match reader.next().await? {
+                Frame::Vectors(records) => {
+                    for record in records {
+                        digests.insert(record.id.clone(), digest(&record));
+                    }
+                }
+                Frame::Deleted(ids) => {
+                    for id in ids {
+                        digests.remove(&id);
+                    }
+                }
+                Frame::End => break,
+                _ => {}
+            }
+        }
+        reader.verify(&snapshot.checksum).await?;
+    }
+
+    let mut upserted = Vec::new();
+    for id in cut.ids()? {
+        let Some(entry) = cut.get(&id)? else {
+            continue;
+        };
+        if digests.remove(&id) != Some(digest(&VectorRecord::from(entry))) {
+            upserted.push(id);
+        }
+    }
+    let mut deleted: Vec<VectorId> = digests.into_keys().collect();
+    deleted.sort();
+    Ok((upserted, deleted))
+}
+
+/// Hash of the contents of a record, independent of map ordering
+fn digest(record: &VectorRecord) -> [u8; 32] {
+    fn update_vector(hasher: &mut Sha256, vector: &[f32]) {
+        hasher.update((vector.len() as u64).to_le_bytes());
+        for value in vector {
+            hasher.update(value.to_le_bytes());
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    update_vector(&mut hasher, &record.vector);
+
+    let mut named: Vec<_> = record.named_vectors.iter().flatten().collect();
+    named.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, vector) in named {
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        update_vector(&mut hasher, vector);
+    }
+
+    let payload = record.payload().map(|payload| payload.to_string());
+    hasher.update(payload.unwrap_or_default().as_bytes());
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::LocalStorage;
+    use ruvector_core::types::{DbOptions, HnswConfig};
+    use ruvector_core::{SearchQuery, VectorEntry};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("ruvector-db-snapshot-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn db_path(dir: &std::path::Path, name: &str) -> String {
+        dir.join(name).to_string_lossy().to_string()
+    }
+
+    fn open_db(path: &str) -> VectorDB {
+        VectorDB::new(DbOptions {
+            dimensions: 3,
+            storage_path: path.to_string(),
+            hnsw_config: Some(HnswConfig::default()),
+            ..DbOptions::default()
+        })
+        .unwrap()
+    }
+
+    fn entry(id: &str, vector: Vec<f32>) -> VectorEntry {
+        VectorEntry {
+            id: Some(id.to_string()),
+            vector,
+            named_vectors: None,
+            metadata: None,
+        }
+    }
+
+    fn nearest(db: &VectorDB, vector: Vec<f32>) -> String {
+        let results = db
+            .search(SearchQuery {
+                vector,
+                using: None,
+                k: 1,
+                filter: None,
+                ef_search: None,
+            })
+            .unwrap();
+        results[0].id.clone()
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_while_writing() {
+        let dir = temp_dir();
+        let manager = SnapshotManager::new(Box::new(LocalStorage::new(dir.join("snapshots"))));
+        let db = Arc::new(open_db(&db_path(&dir, "source.db")));
+        db.insert(entry("first", vec![1.0, 0.0, 0.0])).unwrap();
+
+        let writer = {
+            let db = Arc::clone(&db);
+            std::thread::spawn(move || {
+                for i in 0..200 {
+                    let vector = vec![i as f32, 1.0, 0.0];
+                    db.insert(entry(&format!("v{}", i), vector)).unwrap();
+                }
+            })
+        };
+        let snapshot = db.create_snapshot(&manager, "docs").await.unwrap();
+        writer.join().unwrap();
+
+        // Every insert added one vector, so the cut holds exactly as many
+        // vectors as operations
+        assert_eq!(snapshot.vectors_count as u64, snapshot.sequence);
+        manager.verify_snapshot(&snapshot.id).await.unwrap();
+
+        let restored =
+            VectorDB::restore_snapshot(&manager, &snapshot.id, &db_path(&dir, "restored.db"))
+                .await
+                .unwrap();
+        assert_eq!(restored.len().unwrap(), snapshot.vectors_count);
+        assert_eq!(restored.sequence(), snapshot.sequence);
+        assert_eq!(nearest(&restored, vec![1.0, 0.0, 0.0]), "first");
+
+        // The target must be a new database
+        let again =
+            VectorDB::restore_snapshot(&manager, &snapshot.id, &db_path(&dir, "restored.db")).await;
+        assert!(again.is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_snapshots() {
+        let dir = temp_dir();
+        let manager = SnapshotManager::new(Box::new(LocalStorage::new(dir.join("snapshots"))));
+        let db = open_db(&db_path(&dir, "source.db"));
+        for i in 0..20 {
+            db.insert(entry(&format!("v{}", i), vec![i as f32, 1.0, 0.0]))
+                .unwrap();
+        }
+        let base = db.create_snapshot(&manager, "docs").await.unwrap();
+
+        // Changes found from the write-ahead log
+        db.insert(entry("v0", vec![0.0, 0.0, 9.0])).unwrap();
+        db.delete("v1").unwrap();
+        db.insert(entry("new", vec![0.0, -9.0, 0.0])).unwrap();
+        let first = db
+            .create_incremental_snapshot(&manager, &base.id)
+            .await
+            .unwrap();
+        assert_eq!(first.vectors_count, 2);
+        assert_eq!(
+            first.snapshot_type,
+            SnapshotType::Incremental {
+                base_id: base.id.clone()
+            }
+        );
+
+        // Changes found by comparing against the chain, once a checkpoint
+        // dropped them from the log
+        db.delete("v2").unwrap();
+        db.insert(entry("v3", vec![9.0, 9.0, 9.0])).unwrap();
+        db.checkpoint().unwrap();
+        assert!(db.operations_since(first.sequence).unwrap().is_none());
+        let second = db
+            .create_incremental_snapshot(&manager, &first.id)
+            .await
+            .unwrap();
+        assert_eq!(second.vectors_count, 1);
+        let data = manager.restore_snapshot(&second.id).await.unwrap();
+        assert_eq!(data.deleted, ["v2"]);
+
+        let restored =
+            VectorDB::restore_snapshot(&manager, &second.id, &db_path(&dir, "restored.db"))
+                .await
+                .unwrap();
+        let mut expected = db.keys().unwrap();
+        let mut ids = restored.keys().unwrap();
+        expected.sort();
+        ids.sort();
+        assert_eq!(ids, expected);
+        for id in &ids {
+            assert_eq!(
+                restored.get(id).unwrap().unwrap().vector,
+                db.get(id).unwrap().unwrap().vector
+            );
+        }
+        assert_eq!(nearest(&restored, vec![0.0, 0.0, 9.0]), "v0");
+        assert_eq!(nearest(&restored, vec![9.0, 9.0, 9.0]), "v3");
+        assert_eq!(nearest(&restored, vec![0.0, -9.0, 0.0]), "new");
+
+        // A restored database continues the chain
+        restored.delete("v4").unwrap();
+        let third = restored
+            .create_incremental_snapshot(&manager, &second.id)
+            .await
+            .unwrap();
+        assert_eq!(manager.snapshot_chain(&third.id).await.unwrap().len(), 4);
+
+        // Bases cannot be deleted before the snapshots built on them
+        assert!(manager.delete_snapshot(&base.id).await.is_err());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -32,6 +32,9 @@ pub enum SnapshotError {
 
     #[error("Collection error: {0}")]
     CollectionError(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] ruvector_core::RuvectorError),
 }
 
 impl SnapshotError {
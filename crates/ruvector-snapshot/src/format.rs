@@ -0,0 +1,346 @@
+//! Streamed snapshot encoding
+//!
+//! A snapshot is a gzip stream of frames, each a little-endian u64 length
+//! followed by a bincode-encoded [`Frame`]. The header frame comes first and
+//! an end marker last, so a truncated stream is detected. The SHA-256
+//! checksum covers the uncompressed frames. Frames are compressed and handed
+//! to the storage backend as they are produced, and decoded as compressed
+//! bytes arrive, so neither side holds a whole snapshot in memory.
+
+use crate::error::{Result, SnapshotError};
+use crate::snapshot::{Snapshot, SnapshotData, SnapshotMetadata, VectorRecord};
+use crate::storage::{SnapshotSink, SnapshotSource};
+use flate2::write::{GzDecoder, GzEncoder};
+use flate2::Compression;
+use ruvector_core::types::{DbOptions, GraphPart};
+use ruvector_core::IndexType;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Vector records per [`Frame::Vectors`]
+pub(crate) const VECTORS_PER_FRAME: usize = 1024;
+
+/// Compressed bytes buffered before they are written to the backend
+const WRITE_BUFFER: usize = 1 << 20;
+
+/// Compressed bytes read from the backend at a time
+const READ_BUFFER: usize = 64 << 10;
+
+/// Leading frame of every snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SnapshotHeader {
+    pub metadata: SnapshotMetadata,
+    pub config: DbOptions,
+    pub payload_indexes: Vec<(String, IndexType)>,
+}
+
+/// Unit of a snapshot stream
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum Frame {
+    Header(Box<SnapshotHeader>),
+    /// Vectors of a full snapshot, or vectors upserted since the base
+    Vectors(Vec<VectorRecord>),
+    /// Ids deleted since the base
+    Deleted(Vec<String>),
+    /// Slots of the HNSW graph of a vector space (`None` for the default
+    /// one), without their vectors, which the vector frames before it carry
+    Graph {
+        space: Option<String>,
+        part: GraphPart,
+    },
+    /// Ids whose graph nodes may not match their vectors because they
+    /// changed while the graphs were read, in the frames after the graph
+    /// ones; `None` if they are not known and the graphs must be rebuilt
+    GraphChanged(Option<Vec<String>>),
+    End,
+}
+
+/// Compresses frames into a backend stream
+pub(crate) struct FrameWriter {
+    output: SnapshotSink,
+    encoder: GzEncoder<Vec<u8>>,
+    hasher: Sha256,
+    metadata: SnapshotMetadata,
+    vectors_count: usize,
+    size_bytes: u64,
+}
+
+impl FrameWriter {
+    /// Start a snapshot stream with its header
+    pub async fn start(output: SnapshotSink, header: SnapshotHeader) -> Result<Self> {
+        let mut writer = Self {
+            output,
+            encoder: GzEncoder::new(Vec::new(), Compression::default()),
+            hasher: Sha256::new(),
+            metadata: header.metadata.clone(),
+            vectors_count: 0,
+            size_bytes: 0,
+        };
+        writer.write(&Frame::Header(Box::new(header))).await?;
+        Ok(writer)
+    }
+
+    /// Append a frame
+    pub async fn write(&mut self, frame: &Frame) -> Result<()> {
+        if let Frame::Vectors(records) = frame {
+            self.vectors_count += records.len();
+        }
+        let payload = bincode::serde::encode_to_vec(frame, bincode::config::standard())
+            .map_err(|e| SnapshotError::SerializationError(e.to_string()))?;
+        let len = (payload.len() as u64).to_le_bytes();
+        for bytes in [&len[..], &payload] {
+            self.hasher.update(bytes);
+            self.encoder
+                .write_all(bytes)
+                .map_err(|e| SnapshotError::compression(format!("Compression failed: {}", e)))?;
+        }
+
+        if self.encoder.get_ref().len() >= WRITE_BUFFER {
+            self.drain().await?;
+        }
+        Ok(())
+    }
+
+    /// Write out the compressed bytes produced so far
+    async fn drain(&mut self) -> Result<()> {
+        let compressed = std::mem::take(self.encoder.get_mut());
+        self.output.write_all(&compressed).await?;
+        self.size_bytes += compressed.len() as u64;
+        Ok(())
+    }
+
+    /// Append the end marker, flush the stream and describe the snapshot
+    pub async fn finish(mut self) -> Result<Snapshot> {
+        self.write(&Frame::End).await?;
+        self.encoder
+            .try_finish()
+            .map_err(|e| SnapshotError::compression(format!("Finish compression failed: {}", e)))?;
+        self.drain().await?;
+        self.output.shutdown().await?;
+
+        let created_at = chrono::DateTime::parse_from_rfc3339(&self.metadata.created_at)
+            .map_err(|e| SnapshotError::storage(format!("Invalid timestamp: {}", e)))?
+            .with_timezone(&chrono::Utc);
+        Ok(Snapshot {
+            id: self.metadata.id,
+            collection_name: self.metadata.collection_name,
+            created_at,
+            vectors_count: self.vectors_count,
+            checksum: format!("{:x}", self.hasher.finalize()),
+            size_bytes: self.size_bytes,
+            snapshot_type: self.metadata.snapshot_type,
+            sequence: self.metadata.sequence,
+        })
+    }
+}
+
+/// Decompresses frames from a backend stream
+pub(crate) struct FrameReader {
+    input: SnapshotSource,
+    /// Decompressed bytes land in the inner buffer, from `pos` on unread
+    decoder: GzDecoder<Vec<u8>>,
+    pos: usize,
+    hasher: Sha256,
+    eof: bool,
+}
+
+impl FrameReader {
+    /// Open a snapshot stream and read its header
+    pub async fn open(input: SnapshotSource) -> Result<(Self, SnapshotHeader)> {
+        let mut reader = Self {
+            input,
+            decoder: GzDecoder::new(Vec::new()),
+            pos: 0,
+            hasher: Sha256::new(),
+            eof: false,
+        };
+        match reader.next().await? {
+            Frame::Header(header) => Ok((reader, *header)),
+            _ => Err(SnapshotError::corrupted(
+                "Snapshot does not start with a header",
+            )),
+        }
+    }
+
+    /// Read the next frame
+    pub async fn next(&mut self) -> Result<Frame> {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(frame);
+            }
+            if self.eof {
+                return Err(SnapshotError::corrupted("Snapshot is truncated"));
+            }
+            self.fill().await?;
+        }
+    }
+
+    /// Decode a frame if one is completely buffered
+    fn take_frame(&mut self) -> Result<Option<Frame>> {
+        let buffered = &self.decoder.get_ref()[self.pos..];
+        let Some(len) = buffered.get(..8) else {
+            return Ok(None);
+        };
+        let len = u64::from_le_bytes(len.try_into().unwrap()) as usize;
+        let Some(payload) = 8usize.checked_add(len).and_then(|end| buffered.get(8..end)) else {
+            return Ok(None);
+        };
+        let (frame, _) = bincode::serde::decode_from_slice(payload, bincode::config::standard())
+            .map_err(|e| SnapshotError::corrupted(format!("Undecodable frame: {}", e)))?;
+        self.pos += 8 + len;
+        Ok(Some(frame))
+    }
+
+    /// Decompress the next chunk of input
+    async fn fill(&mut self) -> Result<()> {
+        self.decoder.get_mut().drain(..self.pos);
+        self.pos = 0;
+        let before = self.decoder.get_ref().len();
+
+        let mut chunk = vec![0u8; READ_BUFFER];
+        let read = self.input.read(&mut chunk).await?;
+        let decompressed = if read == 0 {
+            self.eof = true;
+            self.decoder.try_finish()
+        } else {
+            self.decoder.write_all(&chunk[..read])
+        };
+        decompressed
+            .map_err(|e| SnapshotError::compression(format!("Decompression failed: {}", e)))?;
+
+        self.hasher.update(&self.decoder.get_ref()[before..]);
+        Ok(())
+    }
+
+    /// Read to the end of the stream and compare its checksum with `expected`
+    ///
+    /// Call once the end marker has been read.
+    pub async fn verify(mut self, expected: &str) -> Result<()> {
+        while !self.eof {
+            self.fill().await?;
+        }
+        if self.pos != self.decoder.get_ref().len() {
+            return Err(SnapshotError::corrupted(
+                "Data after the end of the snapshot",
+            ));
+        }
+        let actual = format!("{:x}", self.hasher.finalize());
+        if actual != expected {
+            return Err(SnapshotError::InvalidChecksum {
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Stream snapshot data into `output`
+pub(crate) async fn write_data(output: SnapshotSink, data: &SnapshotData) -> Result<Snapshot> {
+    let header = SnapshotHeader {
+        metadata: data.metadata.clone(),
+        config: data.config.clone(),
+        payload_indexes: data.payload_indexes.clone(),
+    };
+    let mut writer = FrameWriter::start(output, header).await?;
+    for records in data.vectors.chunks(VECTORS_PER_FRAME) {
+        writer.write(&Frame::Vectors(records.to_vec())).await?;
+    }
+    if !data.deleted.is_empty() {
+        writer.write(&Frame::Deleted(data.deleted.clone())).await?;
+    }
+    writer.finish().await
+}
+
+/// Read snapshot data from `input`, checking it against `checksum`
+pub(crate) async fn read_data(input: SnapshotSource, checksum: &str) -> Result<SnapshotData> {
+    let (mut reader, header) = FrameReader::open(input).await?;
+    let mut data = SnapshotData {
+        metadata: header.metadata,
+        config: header.config,
+        payload_indexes: header.payload_indexes,
+        vectors: Vec::new(),
+        deleted: Vec::new(),
+    };
+    loop {
+        match reader.next().await? {
+            Frame::Vectors(records) => data.vectors.extend(records),
+            Frame::Deleted(ids) => data.deleted.extend(ids),
+            // Graphs are only of use to a database restore
+            Frame::Graph { .. } | Frame::GraphChanged(_) => {}
+            Frame::End => break,
+            Frame::Header(_) => return Err(SnapshotError::corrupted("Repeated snapshot header")),
+        }
+    }
+    reader.verify(checksum).await?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::SnapshotType;
+
+    fn header() -> SnapshotHeader {
+        SnapshotHeader {
+            metadata: SnapshotMetadata::new("test".to_string(), SnapshotType::Full, 7),
+            config: DbOptions::default(),
+            payload_indexes: Vec::new(),
+        }
+    }
+
+    async fn encode(frames: Vec<Frame>) -> (Vec<u8>, Snapshot) {
+        let (sink, mut source) = tokio::io::duplex(1 << 20);
+        let mut writer = FrameWriter::start(Box::pin(sink), header()).await.unwrap();
+        for frame in &frames {
+            writer.write(frame).await.unwrap();
+        }
+        let snapshot = writer.finish().await.unwrap();
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes).await.unwrap();
+        (bytes, snapshot)
+    }
+
+    #[tokio::test]
+    async fn test_frame_roundtrip() {
+        let records = (0..2500)
+            .map(|i| VectorRecord::new(format!("v{}", i), vec![i as f32; 4], None))
+            .collect::<Vec<_>>();
+        let (bytes, snapshot) = encode(vec![
+            Frame::Vectors(records),
+            Frame::Deleted(vec!["gone".to_string()]),
+        ])
+        .await;
+        assert_eq!(snapshot.vectors_count, 2500);
+        assert_eq!(snapshot.size_bytes, bytes.len() as u64);
+        assert_eq!(snapshot.sequence, 7);
+        assert_eq!(snapshot.checksum.len(), 64); // SHA-256 produces 64 hex characters
+
+        let data = read_data(Box::pin(std::io::Cursor::new(bytes)), &snapshot.checksum)
+            .await
+            .unwrap();
+        assert_eq!(data.vectors_count(), 2500);
+        assert_eq!(data.vectors[2499].id, "v2499");
+        assert_eq!(data.deleted, ["gone"]);
+    }
+
+    #[tokio::test]
+    async fn test_corruption_is_detected() {
+        let records = vec![VectorRecord::new("v1".to_string(), vec![1.0; 4], None)];
+        let (bytes, snapshot) = encode(vec![Frame::Vectors(records)]).await;
+
+        let wrong = "0".repeat(64);
+        let result = read_data(Box::pin(std::io::Cursor::new(bytes.clone())), &wrong).await;
+        assert!(matches!(result, Err(SnapshotError::InvalidChecksum { .. })));
+
+        let truncated = bytes[..bytes.len() / 2].to_vec();
+        let result = read_data(
+            Box::pin(std::io::Cursor::new(truncated)),
+            &snapshot.checksum,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
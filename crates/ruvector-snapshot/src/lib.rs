@@ -2,16 +2,21 @@
 //!
 //! This crate provides backup and restore capabilities for vector collections,
 //! including compression, checksums, and multiple storage backends.
+//! [`VectorDbSnapshots`] takes full and incremental snapshots of a live
+//! [`ruvector_core::VectorDB`] and restores them.
 
+mod db;
 mod error;
+mod format;
 mod manager;
 mod snapshot;
 mod storage;
 
+pub use db::VectorDbSnapshots;
 pub use error::{Result, SnapshotError};
 pub use manager::SnapshotManager;
-pub use snapshot::{Snapshot, SnapshotData, SnapshotMetadata, VectorRecord};
-pub use storage::{LocalStorage, SnapshotStorage};
+pub use snapshot::{Snapshot, SnapshotData, SnapshotMetadata, SnapshotType, VectorRecord};
+pub use storage::{LocalStorage, SnapshotSink, SnapshotSource, SnapshotStorage};
 
 #[cfg(test)]
 mod tests {
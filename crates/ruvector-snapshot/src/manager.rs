@@ -1,5 +1,6 @@
 use crate::error::{Result, SnapshotError};
-use crate::snapshot::{Snapshot, SnapshotData};
+use crate::format::{Frame, FrameReader};
+use crate::snapshot::{Snapshot, SnapshotData, SnapshotType};
 use crate::storage::SnapshotStorage;
 
 /// Manages snapshot operations for collections
@@ -28,10 +29,11 @@ impl SnapshotManager {
             ));
         }
 
-        // Verify all vectors have the same dimension
-        let expected_dim = snapshot_data.config.dimension;
+        // Verify all vectors have the same dimension; points may carry
+        // only named vectors
+        let expected_dim = snapshot_data.config.dimensions;
         for (idx, vector) in snapshot_data.vectors.iter().enumerate() {
-            if vector.vector.len() != expected_dim {
+            if !vector.vector.is_empty() && vector.vector.len() != expected_dim {
                 return Err(SnapshotError::storage(format!(
                     "Vector {} has dimension {} but expected {}",
                     idx,
@@ -47,6 +49,8 @@ impl SnapshotManager {
 
     /// Restore a snapshot by ID
     ///
+    /// For an incremental snapshot this is only the changes since its base.
+    ///
     /// # Arguments
     /// * `id` - The unique snapshot identifier
     ///
@@ -60,6 +64,39 @@ impl SnapshotManager {
         self.storage.load(id).await
     }
 
+    /// Check that a snapshot is complete and matches its checksum
+    ///
+    /// # Arguments
+    /// * `id` - The unique snapshot identifier
+    pub async fn verify_snapshot(&self, id: &str) -> Result<()> {
+        let snapshot = self.storage.info(id).await?;
+        let (mut reader, _) = FrameReader::open(self.storage.reader(id).await?).await?;
+        while !matches!(reader.next().await?, Frame::End) {}
+        reader.verify(&snapshot.checksum).await
+    }
+
+    /// Get a snapshot and the snapshots it builds on, oldest first
+    ///
+    /// The first snapshot of the chain is always a full one.
+    ///
+    /// # Arguments
+    /// * `id` - The unique snapshot identifier
+    pub async fn snapshot_chain(&self, id: &str) -> Result<Vec<Snapshot>> {
+        let mut chain = vec![self.get_snapshot_info(id).await?];
+        while let SnapshotType::Incremental { base_id } = &chain[chain.len() - 1].snapshot_type {
+            if chain.iter().any(|s| &s.id == base_id) {
+                return Err(SnapshotError::corrupted(format!(
+                    "Snapshot {} is its own base",
+                    base_id
+                )));
+            }
+            let base = self.get_snapshot_info(base_id).await?;
+            chain.push(base);
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
     /// List all available snapshots
     ///
     /// # Returns
@@ -88,6 +125,9 @@ impl SnapshotManager {
 
     /// Delete a snapshot by ID
     ///
+    /// Snapshots that incremental snapshots build on cannot be deleted
+    /// before them.
+    ///
     /// # Arguments
     /// * `id` - The unique snapshot identifier
     pub async fn delete_snapshot(&self, id: &str) -> Result<()> {
@@ -95,6 +135,16 @@ impl SnapshotManager {
             return Err(SnapshotError::storage("Snapshot ID cannot be empty"));
         }
 
+        let snapshots = self.storage.list().await?;
+        if let Some(dependent) = snapshots.iter().find(
+            |s| matches!(&s.snapshot_type, SnapshotType::Incremental { base_id } if base_id == id),
+        ) {
+            return Err(SnapshotError::storage(format!(
+                "Snapshot {} is the base of snapshot {}",
+                id, dependent.id
+            )));
+        }
+
         self.storage.delete(id).await
     }
 
@@ -106,15 +156,13 @@ impl SnapshotManager {
     /// # Returns
     /// * `Snapshot` - Metadata about the snapshot
     pub async fn get_snapshot_info(&self, id: &str) -> Result<Snapshot> {
-        let snapshots = self.storage.list().await?;
-        snapshots
-            .into_iter()
-            .find(|s| s.id == id)
-            .ok_or_else(|| SnapshotError::SnapshotNotFound(id.to_string()))
+        self.storage.info(id).await
     }
 
     /// Delete old snapshots, keeping only the N most recent
     ///
+    /// Old snapshots that a kept incremental snapshot builds on are kept too.
+    ///
     /// # Arguments
     /// * `collection_name` - Name of the collection
     /// * `keep_count` - Number of recent snapshots to keep
@@ -135,8 +183,9 @@ impl SnapshotManager {
         let to_delete = &snapshots[keep_count..];
         let mut deleted = 0;
 
+        // Newest first, so dependents go before their bases
         for snapshot in to_delete {
-            if self.storage.delete(&snapshot.id).await.is_ok() {
+            if self.delete_snapshot(&snapshot.id).await.is_ok() {
                 deleted += 1;
             }
         }
@@ -155,20 +204,28 @@ impl SnapshotManager {
         let snapshots = self.list_snapshots_for_collection(collection_name).await?;
         Ok(snapshots.iter().map(|s| s.size_bytes).sum())
     }
+
+    /// Get the storage backend
+    pub(crate) fn storage(&self) -> &dyn SnapshotStorage {
+        self.storage.as_ref()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::snapshot::{CollectionConfig, DistanceMetric, VectorRecord};
+    use crate::snapshot::VectorRecord;
     use crate::storage::LocalStorage;
+    use ruvector_core::types::DbOptions;
+    use ruvector_core::DistanceMetric;
     use std::path::PathBuf;
 
     fn create_test_snapshot_data(name: &str, vector_count: usize) -> SnapshotData {
-        let config = CollectionConfig {
-            dimension: 3,
-            metric: DistanceMetric::Cosine,
+        let config = DbOptions {
+            dimensions: 3,
+            distance_metric: DistanceMetric::Cosine,
             hnsw_config: None,
+            ..DbOptions::default()
         };
 
         let vectors = (0..vector_count)
@@ -279,10 +336,11 @@ mod tests {
         let manager = SnapshotManager::new(storage);
 
         // Test empty collection
-        let config = CollectionConfig {
-            dimension: 3,
-            metric: DistanceMetric::Cosine,
+        let config = DbOptions {
+            dimensions: 3,
+            distance_metric: DistanceMetric::Cosine,
             hnsw_config: None,
+            ..DbOptions::default()
         };
         let empty_data = SnapshotData::new("empty".to_string(), config, vec![]);
         let result = manager.create_snapshot(empty_data).await;
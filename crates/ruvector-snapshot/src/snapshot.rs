@@ -1,7 +1,9 @@
-use bincode::{Decode, Encode};
 use chrono::{DateTime, Utc};
+use ruvector_core::types::DbOptions;
+use ruvector_core::{IndexType, VectorEntry};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 /// Snapshot metadata and information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,37 +25,58 @@ pub struct Snapshot {
 
     /// Size of the snapshot in bytes (compressed)
     pub size_bytes: u64,
+
+    /// Whether the snapshot is full or builds on a base snapshot
+    #[serde(default)]
+    pub snapshot_type: SnapshotType,
+
+    /// Sequence number of the last database operation included
+    #[serde(default)]
+    pub sequence: u64,
+}
+
+/// Kind of snapshot
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotType {
+    /// Every vector of the collection, plus its HNSW graphs
+    #[default]
+    Full,
+    /// Only the vectors upserted or deleted since the snapshot `base_id`
+    Incremental {
+        /// Snapshot this one builds on
+        base_id: String,
+    },
 }
 
 /// Complete snapshot data including metadata and vectors
-#[derive(Debug, Serialize, Deserialize, Encode, Decode)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SnapshotData {
     /// Snapshot metadata
     pub metadata: SnapshotMetadata,
 
     /// Collection configuration
-    pub config: CollectionConfig,
+    pub config: DbOptions,
+
+    /// Payload index definitions
+    pub payload_indexes: Vec<(String, IndexType)>,
 
-    /// All vectors in the collection
+    /// All vectors in the collection, or for an incremental snapshot the
+    /// vectors upserted since its base
     pub vectors: Vec<VectorRecord>,
+
+    /// Ids deleted since the base of an incremental snapshot
+    pub deleted: Vec<String>,
 }
 
 impl SnapshotData {
     /// Create a new snapshot data instance
-    pub fn new(
-        collection_name: String,
-        config: CollectionConfig,
-        vectors: Vec<VectorRecord>,
-    ) -> Self {
+    pub fn new(collection_name: String, config: DbOptions, vectors: Vec<VectorRecord>) -> Self {
         Self {
-            metadata: SnapshotMetadata {
-                id: uuid::Uuid::new_v4().to_string(),
-                collection_name,
-                created_at: Utc::now().to_rfc3339(),
-                version: env!("CARGO_PKG_VERSION").to_string(),
-            },
+            metadata: SnapshotMetadata::new(collection_name, SnapshotType::Full, 0),
             config,
+            payload_indexes: Vec::new(),
             vectors,
+            deleted: Vec::new(),
         }
     }
 
@@ -74,7 +97,7 @@ impl SnapshotData {
 }
 
 /// Snapshot metadata
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SnapshotMetadata {
     /// Unique snapshot identifier
     pub id: String,
@@ -87,39 +110,30 @@ pub struct SnapshotMetadata {
 
     /// Version of the snapshot format
     pub version: String,
-}
-
-/// Collection configuration stored in snapshot
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
-pub struct CollectionConfig {
-    /// Vector dimension
-    pub dimension: usize,
 
-    /// Distance metric
-    pub metric: DistanceMetric,
-
-    /// HNSW configuration
-    pub hnsw_config: Option<HnswConfig>,
-}
+    /// Whether the snapshot is full or builds on a base snapshot
+    pub snapshot_type: SnapshotType,
 
-/// Distance metric for vector similarity
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
-pub enum DistanceMetric {
-    Cosine,
-    Euclidean,
-    DotProduct,
+    /// Sequence number of the last database operation included
+    pub sequence: u64,
 }
 
-/// HNSW index configuration
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
-pub struct HnswConfig {
-    pub m: usize,
-    pub ef_construction: usize,
-    pub ef_search: usize,
+impl SnapshotMetadata {
+    /// Metadata for a new snapshot taken now
+    pub fn new(collection_name: String, snapshot_type: SnapshotType, sequence: u64) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            collection_name,
+            created_at: Utc::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            snapshot_type,
+            sequence,
+        }
+    }
 }
 
 /// Individual vector record in a snapshot
-#[derive(Debug, Clone, Serialize, Deserialize, Encode, Decode)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorRecord {
     /// Unique vector identifier
     pub id: String,
@@ -127,9 +141,10 @@ pub struct VectorRecord {
     /// Vector data
     pub vector: Vec<f32>,
 
+    /// Vectors in named vector spaces
+    pub named_vectors: Option<HashMap<String, Vec<f32>>>,
+
     /// Optional metadata payload (stored as JSON string for bincode compatibility)
-    #[serde(skip)]
-    #[bincode(with_serde)]
     payload_json: Option<String>,
 }
 
@@ -140,6 +155,7 @@ impl VectorRecord {
         Self {
             id,
             vector,
+            named_vectors: None,
             payload_json,
         }
     }
@@ -160,11 +176,39 @@ impl VectorRecord {
     pub fn dimension(&self) -> usize {
         self.vector.len()
     }
+
+    /// Convert back into a database entry
+    ///
+    /// Payloads that are not JSON objects cannot be entry metadata and are
+    /// dropped.
+    pub fn into_entry(self) -> VectorEntry {
+        let metadata = self
+            .payload_json
+            .and_then(|json| serde_json::from_str(&json).ok());
+        VectorEntry {
+            id: Some(self.id),
+            vector: self.vector,
+            named_vectors: self.named_vectors,
+            metadata,
+        }
+    }
+}
+
+impl From<VectorEntry> for VectorRecord {
+    fn from(entry: VectorEntry) -> Self {
+        let payload = entry
+            .metadata
+            .map(|metadata| Value::Object(metadata.into_iter().collect()));
+        let mut record = Self::new(entry.id.unwrap_or_default(), entry.vector, payload);
+        record.named_vectors = entry.named_vectors;
+        record
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ruvector_core::DistanceMetric;
 
     #[test]
     fn test_vector_record_creation() {
@@ -175,10 +219,11 @@ mod tests {
 
     #[test]
     fn test_snapshot_data_creation() {
-        let config = CollectionConfig {
-            dimension: 3,
-            metric: DistanceMetric::Cosine,
+        let config = DbOptions {
+            dimensions: 3,
+            distance_metric: DistanceMetric::Cosine,
             hnsw_config: None,
+            ..DbOptions::default()
         };
 
         let vectors = vec![
@@ -1,29 +1,57 @@
 use async_trait::async_trait;
-use flate2::read::GzDecoder;
-use flate2::write::GzEncoder;
-use flate2::Compression;
-use sha2::{Digest, Sha256};
-use std::io::{Read, Write};
 use std::path::PathBuf;
+use std::pin::Pin;
 use tokio::fs;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::error::{Result, SnapshotError};
+use crate::format;
 use crate::snapshot::{Snapshot, SnapshotData};
 
+/// Stream receiving the compressed contents of a new snapshot
+pub type SnapshotSink = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Stream yielding the compressed contents of a stored snapshot
+pub type SnapshotSource = Pin<Box<dyn AsyncRead + Send>>;
+
 /// Trait for snapshot storage backends
+///
+/// Backends store opaque compressed streams plus a metadata record per
+/// snapshot; encoding, compression and checksums are handled by the caller.
+/// A snapshot only becomes visible once its metadata is committed.
 #[async_trait]
 pub trait SnapshotStorage: Send + Sync {
-    /// Save a snapshot to storage
-    async fn save(&self, snapshot: &SnapshotData) -> Result<Snapshot>;
+    /// Open a stream for the contents of the snapshot `id`
+    async fn writer(&self, id: &str) -> Result<SnapshotSink>;
 
-    /// Load a snapshot from storage
-    async fn load(&self, id: &str) -> Result<SnapshotData>;
+    /// Record a snapshot whose contents were completely written
+    async fn commit(&self, snapshot: &Snapshot) -> Result<()>;
+
+    /// Open the contents of a committed snapshot
+    async fn reader(&self, id: &str) -> Result<SnapshotSource>;
+
+    /// Get the metadata of a committed snapshot
+    async fn info(&self, id: &str) -> Result<Snapshot>;
 
     /// List all available snapshots
     async fn list(&self) -> Result<Vec<Snapshot>>;
 
     /// Delete a snapshot from storage
     async fn delete(&self, id: &str) -> Result<()>;
+
+    /// Save a snapshot to storage
+    async fn save(&self, snapshot_data: &SnapshotData) -> Result<Snapshot> {
+        let writer = self.writer(snapshot_data.id()).await?;
+        let snapshot = format::write_data(writer, snapshot_data).await?;
+        self.commit(&snapshot).await?;
+        Ok(snapshot)
+    }
+
+    /// Load a snapshot from storage, verifying its checksum
+    async fn load(&self, id: &str) -> Result<SnapshotData> {
+        let snapshot = self.info(id).await?;
+        format::read_data(self.reader(id).await?, &snapshot.checksum).await
+    }
 }
 
 /// Local filesystem storage backend
@@ -47,34 +75,6 @@ impl LocalStorage {
         self.base_path.join(format!("{}.metadata.json", id))
     }
 
-    /// Compress data using gzip
-    fn compress(data: &[u8]) -> Result<Vec<u8>> {
-        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-        encoder
-            .write_all(data)
-            .map_err(|e| SnapshotError::compression(format!("Compression failed: {}", e)))?;
-        encoder
-            .finish()
-            .map_err(|e| SnapshotError::compression(format!("Finish compression failed: {}", e)))
-    }
-
-    /// Decompress gzip data
-    fn decompress(data: &[u8]) -> Result<Vec<u8>> {
-        let mut decoder = GzDecoder::new(data);
-        let mut decompressed = Vec::new();
-        decoder
-            .read_to_end(&mut decompressed)
-            .map_err(|e| SnapshotError::compression(format!("Decompression failed: {}", e)))?;
-        Ok(decompressed)
-    }
-
-    /// Calculate SHA-256 checksum
-    fn calculate_checksum(data: &[u8]) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        format!("{:x}", hasher.finalize())
-    }
-
     /// Ensure the base directory exists
     async fn ensure_dir(&self) -> Result<()> {
         if !self.base_path.exists() {
@@ -86,84 +86,33 @@ impl LocalStorage {
 
 #[async_trait]
 impl SnapshotStorage for LocalStorage {
-    async fn save(&self, snapshot_data: &SnapshotData) -> Result<Snapshot> {
+    async fn writer(&self, id: &str) -> Result<SnapshotSink> {
         self.ensure_dir().await?;
+        let file = fs::File::create(self.snapshot_path(id)).await?;
+        Ok(Box::pin(file))
+    }
 
-        let id = snapshot_data.id().to_string();
-        let snapshot_path = self.snapshot_path(&id);
-        let metadata_path = self.metadata_path(&id);
-
-        // Serialize snapshot data
-        let config = bincode::config::standard();
-        let serialized = bincode::encode_to_vec(snapshot_data, config)
-            .map_err(|e| SnapshotError::SerializationError(e.to_string()))?;
-
-        // Calculate checksum before compression
-        let checksum = Self::calculate_checksum(&serialized);
-
-        // Compress data
-        let compressed = Self::compress(&serialized)?;
-        let size_bytes = compressed.len() as u64;
-
-        // Write compressed data
-        fs::write(&snapshot_path, &compressed).await?;
-
-        // Create snapshot metadata
-        let created_at = chrono::DateTime::parse_from_rfc3339(&snapshot_data.metadata.created_at)
-            .map_err(|e| SnapshotError::storage(format!("Invalid timestamp: {}", e)))?
-            .with_timezone(&chrono::Utc);
-
-        let snapshot = Snapshot {
-            id: id.clone(),
-            collection_name: snapshot_data.collection_name().to_string(),
-            created_at,
-            vectors_count: snapshot_data.vectors_count(),
-            checksum,
-            size_bytes,
-        };
-
-        // Write metadata
-        let metadata_json = serde_json::to_string_pretty(&snapshot)?;
-        fs::write(&metadata_path, metadata_json).await?;
-
-        Ok(snapshot)
+    async fn commit(&self, snapshot: &Snapshot) -> Result<()> {
+        let metadata_json = serde_json::to_string_pretty(snapshot)?;
+        fs::write(self.metadata_path(&snapshot.id), metadata_json).await?;
+        Ok(())
     }
 
-    async fn load(&self, id: &str) -> Result<SnapshotData> {
+    async fn reader(&self, id: &str) -> Result<SnapshotSource> {
         let snapshot_path = self.snapshot_path(id);
-        let metadata_path = self.metadata_path(id);
-
-        // Check if files exist
         if !snapshot_path.exists() {
             return Err(SnapshotError::SnapshotNotFound(id.to_string()));
         }
+        Ok(Box::pin(fs::File::open(snapshot_path).await?))
+    }
 
-        // Load and verify metadata
-        let metadata_json = fs::read_to_string(&metadata_path).await?;
-        let snapshot: Snapshot = serde_json::from_str(&metadata_json)?;
-
-        // Load compressed data
-        let compressed = fs::read(&snapshot_path).await?;
-
-        // Decompress
-        let decompressed = Self::decompress(&compressed)?;
-
-        // Verify checksum
-        let actual_checksum = Self::calculate_checksum(&decompressed);
-        if actual_checksum != snapshot.checksum {
-            return Err(SnapshotError::InvalidChecksum {
-                expected: snapshot.checksum,
-                actual: actual_checksum,
-            });
+    async fn info(&self, id: &str) -> Result<Snapshot> {
+        let metadata_path = self.metadata_path(id);
+        if !metadata_path.exists() {
+            return Err(SnapshotError::SnapshotNotFound(id.to_string()));
         }
-
-        // Deserialize
-        let config = bincode::config::standard();
-        let (snapshot_data, _): (SnapshotData, usize) =
-            bincode::decode_from_slice(&decompressed, config)
-                .map_err(|e| SnapshotError::SerializationError(e.to_string()))?;
-
-        Ok(snapshot_data)
+        let metadata_json = fs::read_to_string(&metadata_path).await?;
+        Ok(serde_json::from_str(&metadata_json)?)
     }
 
     async fn list(&self) -> Result<Vec<Snapshot>> {
@@ -217,32 +166,20 @@ impl SnapshotStorage for LocalStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::snapshot::{CollectionConfig, DistanceMetric, VectorRecord};
-
-    #[test]
-    fn test_compression_roundtrip() {
-        let data = b"Hello, World! This is test data for compression.";
-        let compressed = LocalStorage::compress(data).unwrap();
-        let decompressed = LocalStorage::decompress(&compressed).unwrap();
-        assert_eq!(data.to_vec(), decompressed);
-    }
-
-    #[test]
-    fn test_checksum_calculation() {
-        let data = b"test data";
-        let checksum = LocalStorage::calculate_checksum(data);
-        assert_eq!(checksum.len(), 64); // SHA-256 produces 64 hex characters
-    }
+    use crate::snapshot::VectorRecord;
+    use ruvector_core::types::DbOptions;
+    use ruvector_core::DistanceMetric;
 
     #[tokio::test]
     async fn test_local_storage_roundtrip() {
         let temp_dir = std::env::temp_dir().join("ruvector-snapshot-test");
         let storage = LocalStorage::new(temp_dir.clone());
 
-        let config = CollectionConfig {
-            dimension: 3,
-            metric: DistanceMetric::Cosine,
+        let config = DbOptions {
+            dimensions: 3,
+            distance_metric: DistanceMetric::Cosine,
             hnsw_config: None,
+            ..DbOptions::default()
         };
 
         let vectors = vec![
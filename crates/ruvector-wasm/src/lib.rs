@@ -16,7 +16,10 @@ use ruvector_collections::{
 };
 use ruvector_core::{
     error::RuvectorError,
-    types::{DbOptions, DistanceMetric, HnswConfig, SearchQuery, SearchResult, VectorEntry},
+    types::{
+        DbOptions, DistanceMetric, FilterExpression, HnswConfig, SearchQuery, SearchResult,
+        VectorEntry,
+    },
     vector_db::VectorDB as CoreVectorDB,
 };
 #[cfg(feature = "collections")]
@@ -110,6 +113,7 @@ impl JsVectorEntry {
             inner: VectorEntry {
                 id,
                 vector: vector_data,
+                named_vectors: None,
                 metadata,
             },
         })
@@ -206,6 +210,7 @@ impl VectorDB {
             storage_path: ":memory:".to_string(), // Use in-memory for WASM
             hnsw_config,
             quantization: None, // Disable quantization for WASM (for now)
+            named_vectors: None,
         };
 
         let db = CoreVectorDB::new(options).map_err(|e| JsValue::from(WasmError::from(e)))?;
@@ -303,13 +308,19 @@ impl VectorDB {
         }
 
         let metadata_filter = if let Some(f) = filter {
-            Some(from_value(f).map_err(|e| JsValue::from_str(&format!("Invalid filter: {}", e)))?)
+            let value: serde_json::Value =
+                from_value(f).map_err(|e| JsValue::from_str(&format!("Invalid filter: {}", e)))?;
+            Some(
+                FilterExpression::from_json(value)
+                    .map_err(|e| JsValue::from_str(&format!("Invalid filter: {}", e)))?,
+            )
         } else {
             None
         };
 
         let search_query = SearchQuery {
             vector: query_vector,
+            using: None,
             k,
             filter: metadata_filter,
             ef_search: None,
@@ -517,6 +528,7 @@ impl CollectionManager {
             hnsw_config: Some(HnswConfig::default()),
             quantization: None,
             on_disk_payload: false, // Disable for WASM
+            named_vectors: None,
         };
 
         let manager = self.inner.lock();
@@ -585,6 +597,7 @@ impl CollectionManager {
             storage_path: ":memory:".to_string(),
             hnsw_config: collection.config.hnsw_config.clone(),
             quantization: collection.config.quantization.clone(),
+            named_vectors: None,
         };
 
         let db = CoreVectorDB::new(db_options)
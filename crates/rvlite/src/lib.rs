@@ -42,7 +42,7 @@ use wasm_bindgen_futures::future_to_promise;
 
 // Import ruvector-core
 use ruvector_core::types::DbOptions;
-use ruvector_core::{DistanceMetric, SearchQuery, VectorDB, VectorEntry};
+use ruvector_core::{DistanceMetric, FilterExpression, SearchQuery, VectorDB, VectorEntry};
 
 // Query language modules
 pub mod cypher;
@@ -171,6 +171,7 @@ impl RvLiteConfig {
             storage_path: "memory://".to_string(),
             hnsw_config: None,
             quantization: None,
+            named_vectors: None,
         }
     }
 }
@@ -338,6 +339,7 @@ impl RvLite {
         let entry = VectorEntry {
             id: None,
             vector,
+            named_vectors: None,
             metadata: metadata_map,
         };
 
@@ -368,6 +370,7 @@ impl RvLite {
         let entry = VectorEntry {
             id: Some(id),
             vector,
+            named_vectors: None,
             metadata: metadata_map,
         };
 
@@ -380,6 +383,7 @@ impl RvLite {
     pub fn search(&self, query_vector: Vec<f32>, k: usize) -> Result<JsValue, JsValue> {
         let query = SearchQuery {
             vector: query_vector,
+            using: None,
             k,
             filter: None,
             ef_search: None,
@@ -403,18 +407,19 @@ impl RvLite {
         k: usize,
         filter: JsValue,
     ) -> Result<JsValue, JsValue> {
-        let filter_map = serde_wasm_bindgen::from_value::<HashMap<String, serde_json::Value>>(
-            filter,
-        )
-        .map_err(|e| RvLiteError {
-            message: format!("Invalid filter: {}", e),
-            kind: ErrorKind::WasmError,
-        })?;
+        let filter = serde_wasm_bindgen::from_value::<serde_json::Value>(filter)
+            .ok()
+            .and_then(|value| FilterExpression::from_json(value).ok())
+            .ok_or_else(|| RvLiteError {
+                message: "Invalid filter".to_string(),
+                kind: ErrorKind::WasmError,
+            })?;
 
         let query = SearchQuery {
             vector: query_vector,
+            using: None,
             k,
-            filter: Some(filter_map),
+            filter: Some(filter),
             ef_search: None,
         };
 
@@ -660,6 +665,7 @@ impl RvLite {
             let vector_entry = VectorEntry {
                 id: Some(entry.id.clone()),
                 vector: entry.vector.clone(),
+                named_vectors: None,
                 metadata: entry.metadata.clone(),
             };
             self.db
@@ -135,6 +135,7 @@ impl SqlEngine {
             storage_path: "memory://".to_string(),
             hnsw_config: None,
             quantization: None,
+            named_vectors: None,
         };
 
         let db = VectorDB::new(db_options).map_err(|e| RvLiteError {
@@ -229,6 +230,7 @@ impl SqlEngine {
         let entry = VectorEntry {
             id,
             vector,
+            named_vectors: None,
             metadata: Some(metadata),
         };
 
@@ -281,13 +283,14 @@ impl SqlEngine {
 
                 // Build filter from WHERE clause
                 let filter = if let Some(where_expr) = where_clause {
-                    Some(self.build_filter(where_expr)?)
+                    Some(self.build_filter(where_expr)?.into())
                 } else {
                     None
                 };
 
                 let query = SearchQuery {
                     vector,
+                    using: None,
                     k,
                     filter,
                     ef_search: None,
@@ -342,13 +345,14 @@ impl SqlEngine {
 
         // Build filter from WHERE clause
         let filter = if let Some(where_expr) = where_clause {
-            Some(self.build_filter(where_expr)?)
+            Some(self.build_filter(where_expr)?.into())
         } else {
             None
         };
 
         let query = SearchQuery {
             vector: query_vector,
+            using: None,
             k,
             filter,
             ef_search: None,
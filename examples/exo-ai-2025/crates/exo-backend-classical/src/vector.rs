@@ -27,6 +27,7 @@ impl VectorIndexWrapper {
             storage_path: temp_path.to_string_lossy().to_string(),
             hnsw_config: Some(HnswConfig::default()),
             quantization: None,
+            named_vectors: None,
         };
 
         let db = VectorDB::new(options)?;
@@ -42,6 +43,7 @@ impl VectorIndexWrapper {
         let entry = VectorEntry {
             id: Some(pattern.id.to_string()),
             vector: pattern.embedding.clone(),
+            named_vectors: None,
             metadata: Some(metadata),
         };
 
@@ -64,6 +66,7 @@ impl VectorIndexWrapper {
         // Build search query
         let search_query = SearchQuery {
             vector: query.to_vec(),
+            using: None,
             k,
             filter: None, // TODO: Convert Filter to ruvector filter
             ef_search: None,
@@ -23,6 +23,7 @@ impl SubstrateInstance {
             storage_path: config.storage_path.clone(),
             hnsw_config: None,
             quantization: None,
+            named_vectors: None,
         };
 
         let db = VectorDB::new(db_options)
@@ -39,6 +40,7 @@ impl SubstrateInstance {
         let entry = VectorEntry {
             id: None,
             vector: pattern.embedding.clone(),
+            named_vectors: None,
             metadata: Some(serde_json::to_value(&pattern.metadata)?),
         };
 
@@ -54,6 +56,7 @@ impl SubstrateInstance {
     pub async fn search(&self, query: Query) -> Result<Vec<SearchResult>> {
         let search_query = ruvector_core::SearchQuery {
             vector: query.embedding.clone(),
+            using: None,
             k: query.k,
             filter: None,
             ef_search: None,
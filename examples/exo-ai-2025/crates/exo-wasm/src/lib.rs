@@ -269,6 +269,7 @@ impl ExoSubstrate {
             storage_path: ":memory:".to_string(), // WASM uses in-memory storage
             hnsw_config,
             quantization: None,
+            named_vectors: None,
         };
 
         let db = ruvector_core::vector_db::VectorDB::new(db_options)
@@ -303,6 +304,7 @@ impl ExoSubstrate {
         let entry = ruvector_core::types::VectorEntry {
             id: pattern.inner.id.clone(),
             vector: pattern.inner.embedding.clone(),
+            named_vectors: None,
             metadata: pattern.inner.metadata.clone(),
         };
 
@@ -339,6 +341,7 @@ impl ExoSubstrate {
         let promise = future_to_promise(async move {
             let search_query = ruvector_core::types::SearchQuery {
                 vector: query_vec,
+                using: None,
                 k: k as usize,
                 filter: None,
                 ef_search: None,
@@ -25,6 +25,7 @@ fn main() -> Result<()> {
     let entry = VectorEntry {
         id: Some("doc_001".to_string()),
         vector: vec![0.1; 128],
+        named_vectors: None,
         metadata: None,
     };
 
@@ -37,6 +38,7 @@ fn main() -> Result<()> {
         .map(|i| VectorEntry {
             id: Some(format!("doc_{:03}", i + 2)),
             vector: vec![0.1 + (i as f32) * 0.001; 128],
+            named_vectors: None,
             metadata: None,
         })
         .collect();
@@ -48,6 +50,7 @@ fn main() -> Result<()> {
     println!("4. Searching for similar vectors...");
     let query = SearchQuery {
         vector: vec![0.15; 128],
+        using: None,
         k: 5,
         filter: None,
         include_vectors: false,
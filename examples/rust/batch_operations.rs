@@ -28,6 +28,7 @@ fn main() -> Result<()> {
             VectorEntry {
                 id: Some(format!("vec_{:05}", i)),
                 vector,
+                named_vectors: None,
                 metadata: None,
             }
         })
@@ -55,6 +56,7 @@ fn main() -> Result<()> {
     for _ in 0..num_queries {
         let query = SearchQuery {
             vector: query_vector.clone(),
+            using: None,
             k: 10,
             filter: None,
             include_vectors: false,
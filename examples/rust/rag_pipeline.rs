@@ -60,6 +60,7 @@ fn main() -> Result<()> {
             VectorEntry {
                 id: Some(format!("doc_{}", i)),
                 vector: embedding,
+                named_vectors: None,
                 metadata: Some(metadata),
             }
         })
@@ -75,6 +76,7 @@ fn main() -> Result<()> {
 
     let query = SearchQuery {
         vector: query_embedding,
+        using: None,
         k: 3,  // Retrieve top 3 most relevant documents
         filter: None,
         include_vectors: false,
@@ -100,6 +100,10 @@ export interface JsQueryResult {
   nodes: Array<JsNodeResult>
   /** Edges returned by the query */
   edges: Array<JsEdgeResult>
+  /** Column names of the returned rows */
+  columns: Array<string>
+  /** Returned rows, with each value encoded as JSON text */
+  rows: Array<Record<string, string>>
   /** Optional statistics */
   stats?: JsGraphStats
 }